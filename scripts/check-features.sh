@@ -0,0 +1,17 @@
+#!/usr/bin/env sh
+# Compila y prueba el crate con cada combinación relevante de features:
+# sólo el núcleo, cada subsistema opcional por separado y todos juntos.
+set -e
+
+FEATURES="tui svg wasm node tracing"
+
+echo "==> núcleo (--no-default-features)"
+cargo test --no-default-features
+
+for feature in $FEATURES; do
+    echo "==> --features $feature"
+    cargo check --all-targets --no-default-features --features "$feature"
+done
+
+echo "==> --all-features"
+cargo test --all-features
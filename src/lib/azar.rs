@@ -0,0 +1,36 @@
+// Generador congruencial lineal: la misma semilla da siempre la misma
+// secuencia. Lo usan `Random` en el mundo, el planificador aleatorio y los
+// generadores de programas de prueba, para que cualquier ejecución o caso
+// generado se pueda repetir a partir de su semilla.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Azar {
+    estado: u64,
+}
+
+impl Azar {
+    pub fn new(semilla: u64) -> Self {
+        Self { estado: semilla }
+    }
+
+    // Los 31 bits altos del siguiente estado, que son los de mejor calidad
+    pub fn siguiente(&mut self) -> u64 {
+        self.estado = self.estado
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.estado >> 33
+    }
+
+    // Un número entre 0 y `cota - 1`
+    pub fn hasta(&mut self, cota: usize) -> usize {
+        (self.siguiente() % cota as u64) as usize
+    }
+
+    // Un número entre `minimo` y `maximo`, ambos incluidos y en cualquier orden
+    pub fn entre(&mut self, minimo: i64, maximo: i64) -> i64 {
+        let (minimo, maximo) = if minimo <= maximo { (minimo, maximo) } else { (maximo, minimo) };
+        let rango = (maximo - minimo + 1) as u64;
+        minimo + (self.siguiente() % rango) as i64
+    }
+}
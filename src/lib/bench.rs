@@ -0,0 +1,60 @@
+use crate::compilerError::CompilerError;
+use crate::lexer::scanner::Lexer;
+use crate::parser::processor::Parser;
+use crate::semanticizer::analizer::SemanticAnalyzer;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Duración de cada fase del pipeline sobre un mismo código fuente
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tiempos {
+    pub lineas: usize,
+    pub tokens: usize,
+    pub lexer: Duration,
+    pub parser: Duration,
+    pub semantico: Duration,
+}
+
+impl Tiempos {
+    pub fn total(&self) -> Duration {
+        self.lexer + self.parser + self.semantico
+    }
+}
+
+impl fmt::Display for Tiempos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} líneas, {} tokens", self.lineas, self.tokens)?;
+        writeln!(f, "  lexer:     {:>10.3?}", self.lexer)?;
+        writeln!(f, "  parser:    {:>10.3?}", self.parser)?;
+        writeln!(f, "  semántico: {:>10.3?}", self.semantico)?;
+        write!(f, "  total:     {:>10.3?}", self.total())
+    }
+}
+
+// Mide por separado tokenizar, parsear y analizar el código. Cada fase parte
+// del resultado de la anterior, así que el primer error corta la medición.
+pub fn medir(source: &str) -> Result<Tiempos, Vec<CompilerError>> {
+    let mut tiempos = Tiempos {
+        lineas: source.lines().count(),
+        ..Tiempos::default()
+    };
+
+    let inicio = Instant::now();
+    let (tokens, errores) = Lexer::new(source).tokenize_all();
+    tiempos.lexer = inicio.elapsed();
+    tiempos.tokens = tokens.len();
+    if !errores.is_empty() {
+        return Err(errores);
+    }
+
+    let inicio = Instant::now();
+    let programa = Parser::new(&tokens).parse().map_err(|error| vec![error])?;
+    tiempos.parser = inicio.elapsed();
+
+    let inicio = Instant::now();
+    let resultado = SemanticAnalyzer::new().analizar(&programa);
+    tiempos.semantico = inicio.elapsed();
+    resultado?;
+
+    Ok(tiempos)
+}
@@ -1,26 +1,175 @@
 use std::fmt;
+use crate::mensajes::{Codigo, Idioma, Mensaje};
+use crate::source::{FileId, Span};
+
+// Información relacionada con un error que señala otro lugar del código,
+// por ejemplo la primera declaración de un nombre repetido
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nota {
+    pub mensaje: Mensaje,
+    pub span: Option<Span>,
+    // Archivo de `span`; `None` si es el mismo del error
+    pub archivo: Option<FileId>,
+}
+
+// Corrección que un editor puede aplicar sin preguntar: reemplazar el tramo
+// por `reemplazo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sugerencia {
+    pub mensaje: Mensaje,
+    pub span: Span,
+    pub reemplazo: String,
+}
+
+// Notas, ayuda y sugerencias de un error. La mayoría de los errores no
+// tiene, así que se guardan aparte y el error sigue siendo chico.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Detalles {
+    pub notas: Vec<Nota>,
+    // Qué hacer para corregir el error, cuando no hay un cambio concreto
+    pub ayuda: Option<Mensaje>,
+    pub sugerencias: Vec<Sugerencia>,
+}
 
 // Error del compilador
 #[derive(Debug,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CompilerError {
+    // El mensaje en español; `mensaje` permite mostrarlo en otro idioma
     pub message: String,
+    pub mensaje: Mensaje,
     pub line: usize,
     pub column: usize,
+    // Dónde termina lo señalado (exclusivo, como en `Span`); 0 si el error
+    // sólo conoce su comienzo
+    pub end_line: usize,
+    pub end_column: usize,
+    // Archivo donde ocurrió el error, si se conoce; el nombre se obtiene del
+    // `SourceMap` de la compilación
+    pub archivo: Option<FileId>,
+    pub detalles: Option<Box<Detalles>>,
 }
 
 impl CompilerError {
-    pub fn new(message: impl Into<String>, line: usize, column: usize) -> Self {
+    pub fn new(mensaje: impl Into<Mensaje>, line: usize, column: usize) -> Self {
+        let mensaje = mensaje.into();
         Self {
-            message: message.into(),
+            message: mensaje.to_string(),
+            mensaje,
             line,
             column,
+            end_line: 0,
+            end_column: 0,
+            archivo: None,
+            detalles: None,
         }
     }
+
+    // Error que señala todo el tramo, por ejemplo un identificador completo
+    pub fn en(mensaje: impl Into<Mensaje>, span: Span) -> Self {
+        Self {
+            end_line: span.fin_linea,
+            end_column: span.fin_columna,
+            ..Self::new(mensaje, span.linea, span.columna)
+        }
+    }
+
+    // El mismo error corrido `lineas` líneas, para los errores de un código
+    // que se parseó aparte (un archivo incluido o una sección). Las
+    // posiciones desconocidas (0) se mantienen.
+    pub fn desplazar(mut self, lineas: isize) -> Self {
+        if self.line > 0 {
+            self.line = self.line.saturating_add_signed(lineas);
+        }
+        if self.end_line > 0 {
+            self.end_line = self.end_line.saturating_add_signed(lineas);
+        }
+        // Las notas de otro archivo no se corren
+        if let Some(detalles) = &mut self.detalles {
+            for nota in detalles.notas.iter_mut().filter(|nota| nota.archivo.is_none()) {
+                if let Some(span) = &mut nota.span {
+                    *span = span.desplazar(lineas);
+                }
+            }
+            for sugerencia in &mut detalles.sugerencias {
+                sugerencia.span = sugerencia.span.desplazar(lineas);
+            }
+        }
+        self
+    }
+
+    // Tramo señalado; sin fin conocido, el carácter de la posición del error
+    pub fn span(&self) -> Span {
+        if self.end_line == 0 {
+            Span::new(self.line, self.column, self.line, self.column + 1)
+        } else {
+            Span::new(self.line, self.column, self.end_line, self.end_column)
+        }
+    }
+
+    // Código del catálogo de mensajes; `None` si el texto no viene de él
+    pub fn codigo(&self) -> Option<Codigo> {
+        self.mensaje.codigo()
+    }
+
+    pub fn mensaje_en(&self, idioma: Idioma) -> String {
+        self.mensaje.traducir(idioma)
+    }
+
+    pub fn en_archivo(mut self, archivo: FileId) -> Self {
+        self.archivo = Some(archivo);
+        self
+    }
+
+    pub fn con_nota(mut self, mensaje: impl Into<Mensaje>, span: Option<Span>) -> Self {
+        self.detalles_mut().notas.push(Nota { mensaje: mensaje.into(), span, archivo: None });
+        self
+    }
+
+    // Nota que señala un lugar de otro archivo
+    pub fn con_nota_en(mut self, mensaje: impl Into<Mensaje>, span: Span, archivo: FileId) -> Self {
+        self.detalles_mut().notas.push(Nota { mensaje: mensaje.into(), span: Some(span), archivo: Some(archivo) });
+        self
+    }
+
+    pub fn con_ayuda(mut self, ayuda: impl Into<Mensaje>) -> Self {
+        self.detalles_mut().ayuda = Some(ayuda.into());
+        self
+    }
+
+    pub fn con_sugerencia(mut self, mensaje: impl Into<Mensaje>, span: Span, reemplazo: impl Into<String>) -> Self {
+        self.detalles_mut().sugerencias.push(Sugerencia { mensaje: mensaje.into(), span, reemplazo: reemplazo.into() });
+        self
+    }
+
+    pub fn detalles_mut(&mut self) -> &mut Detalles {
+        self.detalles.get_or_insert_default()
+    }
+
+    pub fn notas(&self) -> &[Nota] {
+        self.detalles.as_ref().map_or(&[], |detalles| &detalles.notas)
+    }
+
+    pub fn ayuda(&self) -> Option<&Mensaje> {
+        self.detalles.as_ref().and_then(|detalles| detalles.ayuda.as_ref())
+    }
+
+    pub fn sugerencias(&self) -> &[Sugerencia] {
+        self.detalles.as_ref().map_or(&[], |detalles| &detalles.sugerencias)
+    }
 }
 
 impl std::fmt::Display for CompilerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} (línea {}, columna {})", self.message, self.line, self.column)
+        // Como en `SourceMap::mostrar`, las posiciones desconocidas (0) no se muestran
+        match (self.line, self.column) {
+            (0, _) => write!(f, "{}", self.message),
+            (linea, 0) => write!(f, "{} (línea {})", self.message, linea),
+            (linea, columna) => write!(f, "{} (línea {}, columna {})", self.message, linea, columna),
+        }
     }
 }
 
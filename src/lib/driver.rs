@@ -0,0 +1,341 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::compilerError::CompilerError;
+use crate::error::{Error, Result};
+use crate::lexer::scanner::{Lexer, LexerConfig};
+use crate::lexer::token::{Alias, Keywords, Token};
+use crate::mensajes::{Codigo, Idioma, Mensaje};
+use crate::parser::processor::{Inclusion, Parser, Program};
+use crate::semanticizer::analizer::SemanticAnalyzer;
+use crate::semanticizer::consultas::Consultas;
+use crate::semanticizer::lints::{ConfiguracionLints, RegistroLints};
+use crate::source::{FileId, SourceMap};
+use crate::traza;
+
+// Resultado de compilar un programa: lo que cada fase llegó a producir
+// junto con los diagnósticos encontrados
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Compilacion<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub tokens: Vec<Token<'src>>,
+    pub programa: Option<Program>,
+    pub errores: Vec<CompilerError>,
+    pub advertencias: Vec<Mensaje>,
+    // Archivos incorporados con `incluir`, en el orden en que se cargaron
+    pub incluidos: Vec<PathBuf>,
+    // Archivos de la compilación, para mostrar los diagnósticos con su ubicación
+    pub fuentes: SourceMap,
+}
+
+impl Compilacion<'_> {
+    pub fn exitosa(&self) -> bool {
+        self.programa.is_some() && self.errores.is_empty()
+    }
+
+    // Consultas sobre el programa; `None` si no llegó a parsearse
+    pub fn consultas(&self) -> Option<Consultas<'_>> {
+        self.programa.as_ref().map(Consultas::new)
+    }
+
+    // El programa, o los errores según la fase en que se detuvo la
+    // compilación. Los de las inclusiones y de la configuración de las
+    // advertencias, que se detectan antes del análisis, cuentan como del parser.
+    pub fn resultado(self) -> Result<Program> {
+        let lexicos = self.errores.iter()
+            .any(|error| error.codigo().is_some_and(|codigo| codigo.codigo().starts_with('L')));
+        match self.programa {
+            Some(programa) if self.errores.is_empty() => Ok(programa),
+            Some(_) => Err(Error::Semantic(self.errores)),
+            None if lexicos => Err(Error::Lex(self.errores)),
+            None => Err(Error::Parse(self.errores)),
+        }
+    }
+}
+
+// Opciones que cambian cómo se compila un programa
+#[derive(Debug, Clone, Default)]
+pub struct Opciones {
+    // Acepta las secciones del programa en cualquier orden
+    pub secciones_flexibles: bool,
+    // Reconoce las instrucciones de `INSTRUCCIONES_EXTENDIDAS`, las
+    // sentencias de `SENTENCIAS_EXTENDIDAS` y las palabras de
+    // `PALABRAS_EXTENDIDAS`
+    pub extensiones: bool,
+    // Idioma en que se muestran los diagnósticos; no cambia la compilación
+    pub idioma: Idioma,
+    // Cómo se muestran los diagnósticos; tampoco cambia la compilación
+    pub formato_mensajes: FormatoMensajes,
+    // Niveles y umbrales de las reglas de advertencia, de `--lint-config`
+    pub lints: ConfiguracionLints,
+    // Cómo se mide la indentación y qué comentarios se aceptan
+    pub lexer: LexerConfig,
+    // Otros nombres de las instrucciones elementales, de `[alias]` y
+    // `[obsoletos]` en la configuración del proyecto
+    pub alias: Vec<(String, Alias)>,
+}
+
+impl Opciones {
+    // Palabras reservadas con que se lee el código
+    pub fn palabras_clave(&self) -> Keywords {
+        let keywords = if self.extensiones {
+            Keywords::new().con_extensiones()
+        } else {
+            Keywords::new()
+        };
+        self.alias.iter().fold(keywords, |keywords, (nombre, alias)| keywords.con_alias(nombre, *alias))
+    }
+
+    // Lexer configurado con estas opciones
+    pub fn lexer<'src>(&self, source: &'src str) -> Lexer<'src> {
+        Lexer::with_keywords(source, self.palabras_clave()).con_config(self.lexer)
+    }
+
+    // Las reglas de advertencia con los ajustes de `lints`
+    pub fn registro_lints(&self) -> Result<RegistroLints, CompilerError> {
+        let mut registro = RegistroLints::predeterminado();
+        registro.configurar(&self.lints)?;
+        Ok(registro)
+    }
+}
+
+// Formato de los diagnósticos de la línea de comandos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormatoMensajes {
+    // Texto para leer en la terminal, con la línea subrayada
+    #[default]
+    Humano,
+    // Un evento JSON por línea, para los editores; ver `eventos`
+    Json,
+}
+
+// Fases de la compilación, en el orden en que se ejecutan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fase {
+    Lexico,
+    // Incluye la carga de los archivos de `incluir` y la configuración de
+    // las advertencias
+    Sintactico,
+    Semantico,
+}
+
+// Nombre del archivo principal cuando el código no viene de un archivo
+pub const NOMBRE_ENTRADA: &str = "<entrada>";
+
+// Ejecuta lexer, parser y análisis semántico sobre el código fuente. Los
+// archivos de `incluir` se buscan desde el directorio actual.
+pub fn compilar(source: &str) -> Compilacion<'_> {
+    compilar_archivo(source, Path::new(NOMBRE_ENTRADA))
+}
+
+// Como `compilar`, para el código leído de `ruta`: los diagnósticos llevan
+// su nombre y los archivos de `incluir` se buscan desde su directorio
+pub fn compilar_archivo<'src>(source: &'src str, ruta: &Path) -> Compilacion<'src> {
+    compilar_archivo_con(source, ruta, Opciones::default())
+}
+
+// Lee y compila el archivo de `ruta`, para usar la biblioteca con `?`
+pub fn compilar_ruta(ruta: &Path, opciones: Opciones) -> Result<Program> {
+    let source = fs::read_to_string(ruta).map_err(|error| Error::Io { ruta: ruta.to_path_buf(), error })?;
+    compilar_archivo_con(&source, ruta, opciones).resultado()
+}
+
+pub fn compilar_archivo_con<'src>(source: &'src str, ruta: &Path, opciones: Opciones) -> Compilacion<'src> {
+    compilar_observando(source, ruta, opciones, &mut |_, _| {})
+}
+
+// Como `compilar_archivo_con`, pero le avisa a `observar` cada vez que
+// termina una fase, con lo que la compilación lleva hasta ese momento. La
+// primera fase con errores es la última que se avisa.
+pub fn compilar_observando<'src>(
+    source: &'src str,
+    ruta: &Path,
+    opciones: Opciones,
+    observar: &mut dyn FnMut(Fase, &Compilacion<'src>),
+) -> Compilacion<'src> {
+    let _compilacion = traza::span!(INFO, "compilar", archivo = %ruta.display());
+    let mut compilacion = Compilacion::default();
+    let archivo = compilacion.fuentes.agregar(ruta.display().to_string(), source);
+
+    // El lexer informa todos los errores léxicos, no sólo el primero
+    let mut lexer = opciones.lexer(source).en_archivo(archivo);
+    let (tokens, errores) = {
+        let _fase = traza::span!(INFO, "lex");
+        let (tokens, errores) = lexer.tokenize_all();
+        traza::evento!(DEBUG, tokens = tokens.len(), errores = errores.len(), "análisis léxico terminado");
+        (tokens, errores)
+    };
+    compilacion.tokens = tokens;
+    compilacion.advertencias = lexer.advertencias().iter()
+        .map(|advertencia| compilacion.fuentes.ubicado(advertencia))
+        .collect();
+    if !errores.is_empty() {
+        compilacion.errores = errores;
+    }
+    observar(Fase::Lexico, &compilacion);
+    if !compilacion.errores.is_empty() {
+        return compilacion;
+    }
+
+    let (programa, incluidos, errores) = {
+        let _fase = traza::span!(INFO, "parse");
+        let mut parser = Parser::new(&compilacion.tokens).con_secciones_flexibles(opciones.secciones_flexibles);
+        let mut programa = match parser.parse() {
+            Ok(programa) => programa,
+            Err(error) => {
+                traza::evento!(DEBUG, error = %error, "error de sintaxis");
+                compilacion.errores.push(error.en_archivo(archivo));
+                observar(Fase::Sintactico, &compilacion);
+                return compilacion;
+            }
+        };
+        compilacion.advertencias.extend(parser.advertencias().iter()
+            .map(|advertencia| compilacion.fuentes.ubicado(&advertencia.clone().en_archivo(archivo))));
+
+        let directorio = ruta.parent().unwrap_or(Path::new(""));
+        let (incluidos, errores) = incorporar_inclusiones(&mut programa, directorio, &mut compilacion.fuentes, &opciones);
+        traza::evento!(
+            DEBUG,
+            procesos = programa.procesos.len(),
+            robots = programa.robots_definidos.len(),
+            incluidos = incluidos.len(),
+            "programa parseado"
+        );
+        traza::evento!(TRACE, ast = %crate::parser::printer::a_codigo(&programa), "AST");
+        (programa, incluidos, errores)
+    };
+    compilacion.incluidos = incluidos;
+    compilacion.errores = errores;
+    let lints = match opciones.registro_lints() {
+        _ if !compilacion.errores.is_empty() => None,
+        Ok(lints) => Some(lints),
+        Err(error) => {
+            compilacion.errores.push(error);
+            None
+        }
+    };
+    observar(Fase::Sintactico, &compilacion);
+    let Some(lints) = lints else {
+        return compilacion;
+    };
+    {
+        let _fase = traza::span!(INFO, "analizar");
+        let mut analyzer = SemanticAnalyzer::new().con_lints(lints);
+        if let Err(errores) = analyzer.analizar(&programa) {
+            compilacion.errores.extend(errores);
+        }
+        compilacion.advertencias.extend(analyzer.obtener_advertencias().iter().cloned());
+        traza::evento!(
+            DEBUG,
+            errores = compilacion.errores.len(),
+            advertencias = compilacion.advertencias.len(),
+            "análisis semántico terminado"
+        );
+    }
+    compilacion.programa = Some(programa);
+    observar(Fase::Semantico, &compilacion);
+
+    compilacion
+}
+
+// Los archivos incluidos se parsean como el resto de un programa: pueden
+// tener sus propias directivas `incluir` y una sección `procesos`
+const ENCABEZADO_BIBLIOTECA: &str = "programa biblioteca\n";
+
+// Carga los archivos pedidos con `incluir` (y los que ellos incluyen) y
+// agrega sus procesos al programa. Cada archivo se incorpora una sola vez.
+// Cada archivo cargado se agrega a `fuentes`. Devuelve los archivos cargados
+// y los errores encontrados, que indican el archivo donde ocurrieron.
+pub fn incorporar_inclusiones(
+    programa: &mut Program,
+    directorio: &Path,
+    fuentes: &mut SourceMap,
+    opciones: &Opciones,
+) -> (Vec<PathBuf>, Vec<CompilerError>) {
+    let mut incluidos = Vec::new();
+    let mut errores = Vec::new();
+    let inclusiones = std::mem::take(&mut programa.inclusiones);
+    let mut contexto = Contexto {
+        fuentes,
+        opciones,
+        pila: Vec::new(),
+        incluidos: &mut incluidos,
+        errores: &mut errores,
+    };
+    incorporar(programa, &inclusiones, directorio, FileId::PRINCIPAL, &mut contexto);
+    (incluidos, errores)
+}
+
+// Estado compartido por toda la carga de inclusiones
+struct Contexto<'a> {
+    fuentes: &'a mut SourceMap,
+    opciones: &'a Opciones,
+    // Archivos que se están incorporando, para detectar inclusiones circulares
+    pila: Vec<PathBuf>,
+    incluidos: &'a mut Vec<PathBuf>,
+    errores: &'a mut Vec<CompilerError>,
+}
+
+fn incorporar(
+    programa: &mut Program,
+    inclusiones: &[Inclusion],
+    directorio: &Path,
+    origen: FileId,
+    contexto: &mut Contexto,
+) {
+    for inclusion in inclusiones {
+        let ruta = directorio.join(&inclusion.ruta);
+        let canonica = fs::canonicalize(&ruta).unwrap_or_else(|_| ruta.clone());
+        let error = |mensaje: Mensaje| CompilerError::new(mensaje, inclusion.linea, 0).en_archivo(origen);
+
+        if contexto.pila.contains(&canonica) {
+            contexto.errores.push(error(Mensaje::new(Codigo::InclusionCircular).con(&inclusion.ruta)));
+            continue;
+        }
+        if contexto.incluidos.contains(&canonica) {
+            continue;
+        }
+        let texto = match fs::read_to_string(&ruta) {
+            Ok(texto) => texto,
+            Err(e) => {
+                contexto.errores.push(error(Mensaje::new(Codigo::InclusionIlegible).con(&inclusion.ruta).con(e.to_string())));
+                continue;
+            }
+        };
+        contexto.incluidos.push(canonica.clone());
+
+        let source = format!("{}{}", ENCABEZADO_BIBLIOTECA, texto);
+        let archivo = contexto.fuentes.agregar(ruta.display().to_string(), texto);
+        let en_biblioteca = |error: CompilerError| error.desplazar(-1).en_archivo(archivo);
+        let mut biblioteca = match Parser::from_lexer(contexto.opciones.lexer(&source).en_archivo(archivo)).sin_bloque_principal().parse_todo() {
+            Ok(biblioteca) => biblioteca,
+            Err(errores) => {
+                contexto.errores.extend(errores.into_iter().map(en_biblioteca));
+                continue;
+            }
+        };
+        if !biblioteca.constantes.is_empty() || !biblioteca.areas.is_empty() || !biblioteca.robots_definidos.is_empty() {
+            contexto.errores.push(en_biblioteca(CompilerError::new(
+                Codigo::InclusionConDeclaraciones, 0, 0,
+            )));
+            continue;
+        }
+
+        // Primero los archivos que incluye la biblioteca, con sus rutas
+        // relativas a ella
+        let mut anidadas = std::mem::take(&mut biblioteca.inclusiones);
+        anidadas.iter_mut().for_each(|inclusion| inclusion.linea -= 1);
+        let directorio_biblioteca = ruta.parent().unwrap_or(directorio);
+        contexto.pila.push(canonica);
+        incorporar(programa, &anidadas, directorio_biblioteca, archivo, contexto);
+        contexto.pila.pop();
+
+        let simbolos = programa.simbolos.fusionar(&biblioteca.simbolos);
+        let reubicacion = programa.arena.anexar(&biblioteca.arena, &simbolos, -1);
+        for mut proceso in biblioteca.procesos {
+            proceso.instrucciones.iter_mut().for_each(|id| *id = reubicacion.instruccion(*id));
+            proceso.desplazar_lineas(-1);
+            programa.procesos.push(proceso);
+        }
+    }
+}
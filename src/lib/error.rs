@@ -0,0 +1,66 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use crate::compilerError::CompilerError;
+use crate::interpreter::runtime::RuntimeError;
+
+// Error de la biblioteca según la fase en que ocurrió, para quien la usa con
+// `?`. Las fases de compilación conservan todos los diagnósticos, con la
+// ubicación y el código de cada uno.
+#[derive(Debug)]
+pub enum Error {
+    // No se pudo leer o escribir `ruta`
+    Io { ruta: PathBuf, error: io::Error },
+    Lex(Vec<CompilerError>),
+    // Errores del parser y de los archivos incluidos
+    Parse(Vec<CompilerError>),
+    Semantic(Vec<CompilerError>),
+    Runtime(RuntimeError),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    // Diagnósticos de la compilación; vacío para los errores de archivo y de
+    // ejecución
+    pub fn errores(&self) -> &[CompilerError] {
+        match self {
+            Error::Lex(errores) | Error::Parse(errores) | Error::Semantic(errores) => errores,
+            Error::Io { .. } | Error::Runtime(_) => &[],
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { ruta, error } => write!(f, "No se pudo leer '{}': {}", ruta.display(), error),
+            Error::Lex(errores) | Error::Parse(errores) | Error::Semantic(errores) => {
+                for (i, error) in errores.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+            Error::Runtime(error) => write!(f, "Error de ejecución: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            Error::Runtime(error) => Some(error),
+            _ => self.errores().first().map(|error| error as &(dyn std::error::Error + 'static)),
+        }
+    }
+}
+
+impl From<RuntimeError> for Error {
+    fn from(error: RuntimeError) -> Self {
+        Error::Runtime(error)
+    }
+}
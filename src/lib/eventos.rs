@@ -0,0 +1,146 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use crate::compilerError::CompilerError;
+use crate::driver::{compilar_observando, Compilacion, Fase, Opciones};
+use crate::interpreter::reporte::cadena_json;
+use crate::mensajes::Idioma;
+use crate::parser::processor::Parser;
+use crate::source::{FileId, SourceMap, Span};
+
+// Salida de `--message-format json`: un objeto JSON por línea a medida que
+// avanza la compilación, como `cargo --message-format json`, para que un
+// editor muestre los problemas sin esperar al final ni leer stderr:
+//
+//   {"reason": "compiler-message", "phase": "lexer", "severity": "warning", "code": "L005", ...}
+//   {"reason": "phase-finished", "phase": "lexer", "errors": 0, "warnings": 1}
+//   {"reason": "build-finished", "success": true}
+//
+// Cada fase informa sus mensajes y después su fin; `build-finished` es
+// siempre el último evento. Las advertencias de los archivos incluidos no
+// se informan.
+pub fn compilar_con_eventos<'src>(
+    source: &'src str,
+    ruta: &Path,
+    opciones: Opciones,
+    salida: &mut impl Write,
+) -> io::Result<Compilacion<'src>> {
+    let mut emisor = Emisor { source, opciones: opciones.clone(), salida, informados: 0, resultado: Ok(()) };
+    let compilacion = compilar_observando(source, ruta, opciones, &mut |fase, compilacion| emisor.fase(fase, compilacion));
+    emisor.resultado?;
+    writeln!(emisor.salida, "{{\"reason\": \"build-finished\", \"success\": {}}}", compilacion.exitosa())?;
+    Ok(compilacion)
+}
+
+struct Emisor<'a, W> {
+    source: &'a str,
+    opciones: Opciones,
+    salida: &'a mut W,
+    // Errores de la compilación ya informados
+    informados: usize,
+    // El primer error de escritura; después de uno no se escribe más
+    resultado: io::Result<()>,
+}
+
+impl<W: Write> Emisor<'_, W> {
+    fn fase(&mut self, fase: Fase, compilacion: &Compilacion) {
+        if self.resultado.is_err() {
+            return;
+        }
+        let errores = &compilacion.errores[self.informados..];
+        self.informados = compilacion.errores.len();
+        let advertencias = self.advertencias(fase, compilacion);
+
+        let idioma = self.opciones.idioma;
+        let mut eventos = String::new();
+        for (gravedad, diagnostico) in errores.iter().map(|error| ("error", error))
+            .chain(advertencias.iter().map(|advertencia| ("warning", advertencia)))
+        {
+            eventos.push_str(&mensaje(fase, gravedad, diagnostico, &compilacion.fuentes, idioma));
+            eventos.push('\n');
+        }
+        let _ = writeln!(
+            eventos,
+            "{{\"reason\": \"phase-finished\", \"phase\": \"{}\", \"errors\": {}, \"warnings\": {}}}",
+            nombre_fase(fase), errores.len(), advertencias.len()
+        );
+        self.resultado = self.salida.write_all(eventos.as_bytes()).and_then(|_| self.salida.flush());
+    }
+
+    // Las advertencias de la fase con su ubicación. La compilación las guarda
+    // ya como texto, así que se vuelven a buscar, como hace `listado`: las del
+    // parser, parseando otra vez los tokens de la compilación.
+    fn advertencias(&self, fase: Fase, compilacion: &Compilacion) -> Vec<CompilerError> {
+        match fase {
+            Fase::Lexico => {
+                let mut lexer = self.opciones.lexer(self.source).en_archivo(FileId::PRINCIPAL);
+                lexer.tokenize_all();
+                lexer.advertencias().to_vec()
+            }
+            Fase::Sintactico => {
+                let mut parser = Parser::new(&compilacion.tokens).con_secciones_flexibles(self.opciones.secciones_flexibles);
+                let _ = parser.parse();
+                parser.advertencias().iter().map(|advertencia| advertencia.clone().en_archivo(FileId::PRINCIPAL)).collect()
+            }
+            Fase::Semantico => match (&compilacion.programa, self.opciones.registro_lints()) {
+                (Some(programa), Ok(lints)) => lints.advertencias(programa).into_iter()
+                    .map(|hallazgo| CompilerError::new(hallazgo.mensaje, hallazgo.linea, 0).en_archivo(hallazgo.archivo))
+                    .collect(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+fn nombre_fase(fase: Fase) -> &'static str {
+    match fase {
+        Fase::Lexico => "lexer",
+        Fase::Sintactico => "parser",
+        Fase::Semantico => "semantic",
+    }
+}
+
+// Un diagnóstico con su código, si viene del catálogo, y su ubicación, si
+// se conoce. Las notas, la ayuda y las sugerencias van aparte del mensaje.
+fn mensaje(fase: Fase, gravedad: &str, error: &CompilerError, fuentes: &SourceMap, idioma: Idioma) -> String {
+    let archivo = error.archivo.unwrap_or(FileId::PRINCIPAL);
+    let ubicacion = |span: Option<Span>, archivo: FileId| match span {
+        Some(span) if span.linea > 0 => span_json(span, fuentes.nombre(archivo)),
+        _ => "null".to_string(),
+    };
+
+    let notas: Vec<String> = error.notas().iter()
+        .map(|nota| format!(
+            "{{\"message\": {}, \"span\": {}}}",
+            cadena_json(&nota.mensaje.traducir(idioma)), ubicacion(nota.span, nota.archivo.unwrap_or(archivo))
+        ))
+        .collect();
+    let sugerencias: Vec<String> = error.sugerencias().iter()
+        .map(|sugerencia| format!(
+            "{{\"message\": {}, \"span\": {}, \"replacement\": {}}}",
+            cadena_json(&sugerencia.mensaje.traducir(idioma)), ubicacion(Some(sugerencia.span), archivo), cadena_json(&sugerencia.reemplazo)
+        ))
+        .collect();
+    format!(
+        "{{\"reason\": \"compiler-message\", \"phase\": \"{}\", \"severity\": \"{}\", \"code\": {}, \"message\": {}, \"span\": {}, \"notes\": [{}], \"help\": {}, \"suggestions\": [{}]}}",
+        nombre_fase(fase),
+        gravedad,
+        error.codigo().map_or("null".to_string(), |codigo| cadena_json(codigo.codigo())),
+        cadena_json(&error.mensaje_en(idioma)),
+        ubicacion(Some(error.span()), archivo),
+        notas.join(", "),
+        error.ayuda().map_or("null".to_string(), |ayuda| cadena_json(&ayuda.traducir(idioma))),
+        sugerencias.join(", "),
+    )
+}
+
+// Columnas contadas en caracteres desde 1 y fin exclusivo, como `Span`. Si
+// sólo se conoce la línea, las columnas son `null`, como en la salida para
+// personas, que no las muestra.
+fn span_json(span: Span, archivo: Option<&str>) -> String {
+    let columna = |columna: usize| if span.columna == 0 { "null".to_string() } else { columna.to_string() };
+    format!(
+        "{{\"file\": {}, \"line\": {}, \"column\": {}, \"end_line\": {}, \"end_column\": {}}}",
+        archivo.map_or("null".to_string(), cadena_json), span.linea, columna(span.columna), span.fin_linea, columna(span.fin_columna)
+    )
+}
@@ -0,0 +1,41 @@
+use crate::compilerError::CompilerError;
+use crate::lexer::scanner::Lexer;
+use crate::parser::processor::{parse_tokens_lossy, Instruccion, Parser};
+
+// Punto de entrada para cargo-fuzz o AFL: pasa bytes cualesquiera por el
+// lexer y el parser, leyendo los tokens de una vez y a medida que se
+// necesitan. Un pánico, o una pila agotada, es un error del compilador;
+// también lo es que el programa parcial no coincida con sus errores. Con
+// cargo-fuzz alcanza con
+//
+//     fuzz_target!(|datos: &[u8]| rinfo::fuzz::parsear(datos));
+pub fn parsear(datos: &[u8]) {
+    let source = String::from_utf8_lossy(datos);
+
+    let (tokens, _errores) = Lexer::new(&source).tokenize_all();
+    let (programa, errores) = parse_tokens_lossy(&tokens);
+
+    // Sin programa siempre hay un error que lo explique; con programa, cada
+    // error es una instrucción que quedó en su lugar como `Instruccion::Error`
+    match &programa {
+        None => assert!(!errores.is_empty(), "Ni programa ni errores"),
+        Some(programa) => {
+            let fallidas = programa.arena.instrucciones()
+                .filter(|instruccion| matches!(instruccion, Instruccion::Error { .. }))
+                .count();
+            assert_eq!(fallidas, errores.len(), "Instrucciones en error y errores no coinciden: {:?}", errores);
+        }
+    }
+
+    // `parse_todo` rechaza el programa exactamente con los mismos errores
+    match Parser::new(&tokens).parse_todo() {
+        Ok(_) => assert!(errores.is_empty(), "parse_todo aceptó un programa con errores: {:?}", errores),
+        Err(todos) => assert_eq!(posiciones(&todos), posiciones(&errores)),
+    }
+
+    let _ = Parser::from_lexer(Lexer::new(&source)).parse_todo();
+}
+
+fn posiciones(errores: &[CompilerError]) -> Vec<(usize, usize, &str)> {
+    errores.iter().map(|error| (error.line, error.column, error.message.as_str())).collect()
+}
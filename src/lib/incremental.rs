@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use crate::compilerError::CompilerError;
+use crate::driver::{compilar_archivo_con, incorporar_inclusiones, Compilacion, Opciones, NOMBRE_ENTRADA};
+use crate::parser::processor::{Parser, Program};
+use crate::semanticizer::analizer::SemanticAnalyzer;
+use crate::source::FileId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TipoSeccion {
+    Proceso,
+    Robot,
+}
+
+impl TipoSeccion {
+    // Encabezado con el que una sección suelta se parsea como un programa
+    fn encabezado(self) -> &'static str {
+        match self {
+            TipoSeccion::Proceso => "programa seccion\nprocesos\n",
+            TipoSeccion::Robot => "programa seccion\nrobots\n",
+        }
+    }
+}
+
+// Líneas que el encabezado agrega antes del texto de la sección
+const LINEAS_ENCABEZADO: usize = 2;
+
+// Un `proceso` o `robot` completo, desde su encabezado hasta su `fin`
+struct Fragmento<'src> {
+    tipo: TipoSeccion,
+    // Línea del código fuente (desde 1) donde comienza
+    linea: usize,
+    texto: &'src str,
+}
+
+// Resultado de parsear una sección sola. Sus líneas son relativas al
+// encabezado agregado; se corrigen al unirla al programa.
+type SeccionCompilada = Result<Program, Vec<CompilerError>>;
+
+// Compila un programa que se edita de a poco (modo watch, LSP). Cada proceso
+// y robot se guarda ya parseado bajo un hash de su texto: al recompilar sólo
+// se parsean y analizan las secciones que cambiaron. El resto del programa
+// (encabezado, áreas, variables y bloque principal) se parsea siempre.
+// No se conservan los tokens del archivo completo, por lo que la compilación
+// resultante no los incluye.
+#[derive(Default)]
+pub struct CompiladorIncremental {
+    secciones: HashMap<u64, SeccionCompilada>,
+    // Errores semánticos de cada sección, según su texto y las declaraciones
+    // del programa, con las líneas contadas desde su encabezado
+    errores_semanticos: HashMap<u64, Vec<CompilerError>>,
+    reutilizadas: usize,
+    opciones: Opciones,
+}
+
+impl CompiladorIncremental {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn con_opciones(opciones: Opciones) -> Self {
+        Self { opciones, ..Self::default() }
+    }
+
+    // Secciones que la última compilación tomó de la cache
+    pub fn secciones_reutilizadas(&self) -> usize {
+        self.reutilizadas
+    }
+
+    pub fn compilar(&mut self, source: &str) -> Compilacion<'static> {
+        self.compilar_archivo(source, Path::new(NOMBRE_ENTRADA))
+    }
+
+    // Como `compilar`, para el código leído de `ruta`. Los archivos incluidos
+    // se vuelven a leer en cada compilación.
+    pub fn compilar_archivo(&mut self, source: &str, ruta: &Path) -> Compilacion<'static> {
+        self.reutilizadas = 0;
+        let Some((esqueleto, fragmentos)) = dividir(source) else {
+            // Sin secciones bien delimitadas no hay nada que reutilizar
+            let mut compilacion = compilar_archivo_con(source, ruta, self.opciones.clone());
+            return Compilacion {
+                tokens: Vec::new(),
+                programa: compilacion.programa.take(),
+                errores: std::mem::take(&mut compilacion.errores),
+                advertencias: std::mem::take(&mut compilacion.advertencias),
+                incluidos: std::mem::take(&mut compilacion.incluidos),
+                fuentes: std::mem::take(&mut compilacion.fuentes),
+            };
+        };
+
+        // Las secciones guardadas en la cache son todas del archivo principal
+        let mut compilacion = Compilacion::default();
+        compilacion.fuentes.agregar(ruta.display().to_string(), source);
+        let mut parser = Parser::from_lexer(self.opciones.lexer(&esqueleto))
+            .con_secciones_flexibles(self.opciones.secciones_flexibles);
+        let mut programa = match parser.parse_todo() {
+            Ok(programa) => programa,
+            Err(errores) => {
+                compilacion.errores.extend(errores.into_iter().map(|error| error.en_archivo(FileId::PRINCIPAL)));
+                return compilacion;
+            }
+        };
+
+        let claves: Vec<u64> = fragmentos.iter().map(|f| hash(&(f.tipo, f.texto))).collect();
+        let mut vigentes = HashMap::new();
+        for clave in &claves {
+            if let Some(seccion) = self.secciones.remove(clave) {
+                self.reutilizadas += 1;
+                vigentes.insert(*clave, seccion);
+            }
+        }
+
+        // Los robots del esqueleto sólo declaran los tipos: se reemplazan por los parseados
+        programa.robots_definidos.clear();
+        let mut claves_procesos = Vec::new();
+        let mut claves_robots = Vec::new();
+        for (fragmento, clave) in fragmentos.iter().zip(&claves) {
+            let seccion = vigentes.entry(*clave).or_insert_with(|| parsear_seccion(fragmento, &self.opciones));
+            let desplazamiento = fragmento.linea as isize - (LINEAS_ENCABEZADO + 1) as isize;
+
+            let parcial = match seccion {
+                Ok(parcial) => parcial,
+                Err(errores) => {
+                    for error in errores {
+                        compilacion.errores.push(error.clone().desplazar(desplazamiento).en_archivo(FileId::PRINCIPAL));
+                    }
+                    continue;
+                }
+            };
+
+            let simbolos = programa.simbolos.fusionar(&parcial.simbolos);
+            let reubicacion = programa.arena.anexar(&parcial.arena, &simbolos, desplazamiento);
+            match fragmento.tipo {
+                TipoSeccion::Proceso => {
+                    for proceso in &parcial.procesos {
+                        let mut proceso = proceso.clone();
+                        proceso.instrucciones.iter_mut().for_each(|id| *id = reubicacion.instruccion(*id));
+                        proceso.desplazar_lineas(desplazamiento);
+                        programa.procesos.push(proceso);
+                        claves_procesos.push(Some(*clave));
+                    }
+                }
+                TipoSeccion::Robot => {
+                    for robot in &parcial.robots_definidos {
+                        let mut robot = robot.clone();
+                        robot.instrucciones.iter_mut().for_each(|id| *id = reubicacion.instruccion(*id));
+                        robot.desplazar_lineas(desplazamiento);
+                        programa.robots_definidos.push(robot);
+                        claves_robots.push(Some(*clave));
+                    }
+                }
+            }
+        }
+        self.secciones = vigentes;
+
+        if !compilacion.errores.is_empty() {
+            return compilacion;
+        }
+
+        let directorio = ruta.parent().unwrap_or(Path::new(""));
+        let (incluidos, errores) = incorporar_inclusiones(&mut programa, directorio, &mut compilacion.fuentes, &self.opciones);
+        compilacion.incluidos = incluidos;
+        if !errores.is_empty() {
+            compilacion.errores = errores;
+            return compilacion;
+        }
+
+        // El análisis de cada sección depende también de los procesos (sus
+        // parámetros y lo que devuelven), robots, áreas, constantes y variables de cada tipo de
+        // robot declarados. Los procesos incluidos no tienen clave: se verifican
+        // siempre.
+        let declarados = hash(&(
+            programa.procesos.iter()
+                .map(|p| (p.nombre.as_str(), p.parametros.iter().map(|param| (&param.tipo, &param.tipo_dato)).collect::<Vec<_>>(), &p.retorno))
+                .collect::<Vec<_>>(),
+            programa.robots_definidos.iter()
+                .map(|r| (r.nombre.as_str(), r.variables.iter().map(|v| v.nombre.as_str()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>(),
+            programa.robots_instanciados.iter().map(|r| r.nombre.as_str()).collect::<Vec<_>>(),
+            programa.areas.iter().map(|a| a.nombre.as_str()).collect::<Vec<_>>(),
+            // El valor importa: con él se verifican los índices de los arreglos
+            programa.constantes.iter().map(|c| (c.nombre.as_str(), c.valor)).collect::<Vec<_>>(),
+        ));
+        claves_procesos.resize(programa.procesos.len(), None);
+        let claves_semanticas: Vec<Option<u64>> = claves_procesos.into_iter()
+            .chain(claves_robots)
+            .map(|clave| clave.map(|clave| hash(&(clave, declarados))))
+            .collect();
+        let mut errores_semanticos = HashMap::new();
+        for clave in claves_semanticas.iter().flatten() {
+            if let Some(errores) = self.errores_semanticos.remove(clave) {
+                errores_semanticos.insert(*clave, errores);
+            }
+        }
+
+        let lints = match self.opciones.registro_lints() {
+            Ok(lints) => lints,
+            Err(error) => {
+                compilacion.errores.push(error);
+                return compilacion;
+            }
+        };
+        let mut analyzer = SemanticAnalyzer::new().con_lints(lints);
+        if let Err(errores) = analyzer.analizar_con_cache(&programa, &claves_semanticas, &mut errores_semanticos) {
+            compilacion.errores.extend(errores);
+        }
+        self.errores_semanticos = errores_semanticos;
+        compilacion.advertencias = analyzer.obtener_advertencias().to_vec();
+        compilacion.programa = Some(programa);
+
+        compilacion
+    }
+}
+
+fn hash<T: Hash>(valor: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    valor.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn parsear_seccion(fragmento: &Fragmento, opciones: &Opciones) -> SeccionCompilada {
+    let texto = format!("{}{}", fragmento.tipo.encabezado(), fragmento.texto);
+    Parser::from_lexer(opciones.lexer(&texto)).sin_bloque_principal().parse_todo()
+}
+
+// Separa los procesos y robots del resto del programa. El esqueleto conserva
+// las líneas del original: las secciones quedan en blanco, salvo un robot
+// vacío con el mismo nombre en lugar de cada robot. Devuelve `None`
+// si alguna sección no tiene su `fin`, para compilar el programa entero.
+fn dividir(source: &str) -> Option<(String, Vec<Fragmento<'_>>)> {
+    let lineas: Vec<&str> = source.split_inclusive('\n').collect();
+    let mut esqueleto = String::with_capacity(source.len());
+    let mut fragmentos = Vec::new();
+    let mut comentarios = 0usize;
+    let mut inicio_linea = 0;
+    let mut i = 0;
+
+    while i < lineas.len() {
+        let linea = lineas[i];
+        let tipo = match primera_palabra(linea) {
+            Some("proceso") if comentarios == 0 => Some(TipoSeccion::Proceso),
+            Some("robot") if comentarios == 0 => Some(TipoSeccion::Robot),
+            _ => None,
+        };
+
+        let Some(tipo) = tipo else {
+            comentarios = profundidad_comentarios(linea, comentarios);
+            esqueleto.push_str(linea);
+            inicio_linea += linea.len();
+            i += 1;
+            continue;
+        };
+
+        // La sección termina en el primer `fin` con su misma indentación
+        let encabezado = sangria(linea);
+        let mut profundidad = profundidad_comentarios(linea, 0);
+        let mut fin = None;
+        for (j, siguiente) in lineas.iter().enumerate().skip(i + 1) {
+            if profundidad == 0 && sangria(siguiente) == encabezado && primera_palabra(siguiente) == Some("fin") {
+                fin = Some(j);
+                break;
+            }
+            profundidad = profundidad_comentarios(siguiente, profundidad);
+        }
+        let fin = fin?;
+
+        let largo: usize = lineas[i..=fin].iter().map(|l| l.len()).sum();
+        fragmentos.push(Fragmento {
+            tipo,
+            linea: i + 1,
+            texto: &source[inicio_linea..inicio_linea + largo],
+        });
+
+        for (j, linea) in lineas[i..=fin].iter().enumerate() {
+            if tipo == TipoSeccion::Robot && j == 0 {
+                esqueleto.push_str(linea);
+                continue;
+            }
+            if tipo == TipoSeccion::Robot && i + j == fin {
+                // Cuerpo vacío en la línea del `fin`, para que el parser siga
+                // leyendo los robots siguientes
+                esqueleto.push_str(encabezado);
+                esqueleto.push_str("comenzar fin");
+            }
+            if linea.ends_with('\n') {
+                esqueleto.push('\n');
+            }
+        }
+        inicio_linea += largo;
+        i = fin + 1;
+    }
+
+    Some((esqueleto, fragmentos))
+}
+
+// Primera palabra de la línea, sin contar los comentarios que la siguen
+fn primera_palabra(linea: &str) -> Option<&str> {
+    linea.split_whitespace()
+        .next()
+        .and_then(|palabra| palabra.split(['{', '/']).next())
+        .filter(|palabra| !palabra.is_empty())
+}
+
+fn sangria(linea: &str) -> &str {
+    &linea[..linea.len() - linea.trim_start().len()]
+}
+
+// Profundidad de comentarios `{ }` al terminar la línea; fuera de ellos, un
+// `//` comenta el resto
+fn profundidad_comentarios(linea: &str, mut profundidad: usize) -> usize {
+    let mut caracteres = linea.chars().peekable();
+    while let Some(c) = caracteres.next() {
+        match c {
+            '/' if profundidad == 0 && caracteres.peek() == Some(&'/') => break,
+            '{' => profundidad += 1,
+            '}' => profundidad = profundidad.saturating_sub(1),
+            _ => {}
+        }
+    }
+    profundidad
+}
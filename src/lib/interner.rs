@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+// Nombre internado: comparar o buscar símbolos es comparar enteros
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Symbol(u32);
+
+impl Symbol {
+    // Posición del símbolo en la tabla que lo creó
+    pub fn indice(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// Tabla de nombres de un programa. Cada nombre distinto se guarda una sola
+// vez, sin importar cuántas veces aparezca en el código.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Vec<String>", into = "Vec<String>"))]
+pub struct Interner {
+    simbolos: HashMap<String, Symbol>,
+    nombres: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, nombre: &str) -> Symbol {
+        if let Some(&simbolo) = self.simbolos.get(nombre) {
+            return simbolo;
+        }
+        let simbolo = Symbol(self.nombres.len() as u32);
+        self.nombres.push(nombre.to_string());
+        self.simbolos.insert(nombre.to_string(), simbolo);
+        simbolo
+    }
+
+    // Símbolo de un nombre ya internado, sin agregarlo
+    pub fn get(&self, nombre: &str) -> Option<Symbol> {
+        self.simbolos.get(nombre).copied()
+    }
+
+    pub fn resolver(&self, simbolo: Symbol) -> &str {
+        &self.nombres[simbolo.0 as usize]
+    }
+
+    // Interna todos los nombres de `otro` y devuelve, para cada símbolo de
+    // `otro` (por índice), el símbolo equivalente en esta tabla
+    pub fn fusionar(&mut self, otro: &Interner) -> Vec<Symbol> {
+        otro.nombres.iter().map(|nombre| self.intern(nombre)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nombres.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nombres.is_empty()
+    }
+}
+
+// Se serializa sólo la lista de nombres; el índice se reconstruye al leerla
+impl From<Vec<String>> for Interner {
+    fn from(nombres: Vec<String>) -> Self {
+        let mut interner = Interner::new();
+        for nombre in &nombres {
+            interner.intern(nombre);
+        }
+        interner
+    }
+}
+
+impl From<Interner> for Vec<String> {
+    fn from(interner: Interner) -> Self {
+        interner.nombres
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashSet;
+use crate::interner::Interner;
+use super::super::parser::arena::{Arena, ExprId, InstrId};
+use super::super::parser::ast::Visitor;
+use super::super::parser::processor::{Expresion, Instruccion, Parametro, Proceso, Robot, Variable};
+
+// Operaciones planas que ejecuta el intérprete. Los bloques de control se
+// traducen a saltos para poder ejecutar el programa paso a paso. Las
+// expresiones quedan en el arena del programa y se referencian por índice.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operacion {
+    Accion { nombre: String, argumentos: Vec<ExprId> },
+    Asignar { variable: String, valor: ExprId },
+    // `arreglo[indice] := valor`
+    AsignarElemento { arreglo: String, indice: ExprId, valor: ExprId },
+    Llamar { proceso: String, argumentos: Vec<ExprId> },
+    // Varias asignaciones en un solo paso, en orden. Reemplazan la llamada y
+    // el regreso de un proceso expandido, para que la copia lleve los mismos
+    // pasos que el registro de activación.
+    Copiar { asignaciones: Vec<(String, ExprId)> },
+    // Llamada a un proceso dentro de una expresión. Se ejecuta antes de la
+    // operación que usa la expresión, y lo que devuelve queda guardado para
+    // cuando se evalúa `llamada`.
+    Calcular { llamada: ExprId },
+    // Sensor usado como sentencia: se consulta y se descarta el valor
+    Consultar { sensor: String },
+    SaltarSiFalso { condicion: ExprId, destino: usize },
+    Saltar { destino: usize },
+    IniciarContador { cantidad: ExprId },
+    // Si el contador actual llegó a cero lo descarta y salta a `destino`,
+    // si no lo decrementa y continúa con el cuerpo del repetir
+    DescontarOSaltar { destino: usize },
+}
+
+impl Operacion {
+    // Nombre de la clase de operación, para el perfil de ejecución
+    pub fn clase(&self) -> &'static str {
+        match self {
+            Operacion::Accion { .. } => "acción",
+            Operacion::Asignar { .. } | Operacion::AsignarElemento { .. } => "asignación",
+            Operacion::Llamar { .. } | Operacion::Calcular { .. } | Operacion::Copiar { .. } => "llamada",
+            Operacion::Consultar { .. } => "sensor",
+            Operacion::SaltarSiFalso { .. } => "condición",
+            Operacion::Saltar { .. } => "salto",
+            Operacion::IniciarContador { .. } | Operacion::DescontarOSaltar { .. } => "repetir",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CodigoProceso {
+    pub nombre: String,
+    pub parametros: Vec<Parametro>,
+    pub variables: Vec<Variable>,
+    pub operaciones: Vec<Operacion>,
+    // Línea del código fuente de la que proviene cada operación
+    pub lineas: Vec<usize>,
+}
+
+impl CodigoProceso {
+    pub fn linea(&self, indice: usize) -> Option<usize> {
+        self.lineas.get(indice).copied()
+    }
+}
+
+pub fn compilar_proceso(arena: &Arena, simbolos: &Interner, proceso: &Proceso, procesos: &HashSet<&str>) -> CodigoProceso {
+    let emisor = emitir(arena, simbolos, &proceso.instrucciones, procesos);
+    // Lo que devuelve se guarda en una variable con el nombre del proceso
+    let retorno = proceso.retorno.iter().map(|tipo| Variable {
+        nombre: proceso.nombre.clone(),
+        tipo_dato: tipo.clone(),
+        longitud: None,
+        linea: proceso.linea,
+    });
+    let declaradas: Vec<Variable> = proceso.variables.iter().cloned().chain(retorno).collect();
+    CodigoProceso {
+        nombre: proceso.nombre.clone(),
+        parametros: proceso.parametros.clone(),
+        variables: emisor.variables(&declaradas),
+        operaciones: emisor.operaciones,
+        lineas: emisor.lineas,
+    }
+}
+
+// El cuerpo de un robot se compila como un proceso sin parámetros
+pub fn compilar_robot(arena: &Arena, simbolos: &Interner, robot: &Robot, procesos: &HashSet<&str>) -> CodigoProceso {
+    let emisor = emitir(arena, simbolos, &robot.instrucciones, procesos);
+    CodigoProceso {
+        nombre: robot.nombre.clone(),
+        parametros: Vec::new(),
+        variables: emisor.variables(&robot.variables),
+        operaciones: emisor.operaciones,
+        lineas: emisor.lineas,
+    }
+}
+
+// Devuelve las operaciones junto con la línea de origen de cada una
+pub fn compilar_instrucciones(
+    arena: &Arena,
+    simbolos: &Interner,
+    instrucciones: &[InstrId],
+    procesos: &HashSet<&str>,
+) -> (Vec<Operacion>, Vec<usize>) {
+    let emisor = emitir(arena, simbolos, instrucciones, procesos);
+    (emisor.operaciones, emisor.lineas)
+}
+
+fn emitir<'a>(
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    instrucciones: &'a [InstrId],
+    procesos: &'a HashSet<&'a str>,
+) -> Emisor<'a> {
+    let mut emisor = Emisor {
+        arena,
+        simbolos,
+        procesos,
+        operaciones: Vec::new(),
+        lineas: Vec::new(),
+        variables_de_ciclo: Vec::new(),
+    };
+    emisor.visitar_bloque(instrucciones);
+    emisor
+}
+
+struct Emisor<'a> {
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    procesos: &'a HashSet<&'a str>,
+    operaciones: Vec<Operacion>,
+    lineas: Vec<usize>,
+    // Variables de los `para`, que existen durante toda la rutina aunque
+    // el código sólo las vea dentro del ciclo
+    variables_de_ciclo: Vec<(String, usize)>,
+}
+
+impl<'a> Emisor<'a> {
+    fn emitir(&mut self, operacion: Operacion, linea: usize) -> usize {
+        self.operaciones.push(operacion);
+        self.lineas.push(linea);
+        self.operaciones.len() - 1
+    }
+
+    fn siguiente(&self) -> usize {
+        self.operaciones.len()
+    }
+
+    // Las variables declaradas seguidas de las de los `para` que no tienen
+    // el nombre de una declarada
+    fn variables(&self, declaradas: &[Variable]) -> Vec<Variable> {
+        let de_ciclo = self.variables_de_ciclo.iter()
+            .filter(|(nombre, _)| !declaradas.iter().any(|variable| variable.nombre == *nombre))
+            .map(|(nombre, linea)| Variable {
+                nombre: nombre.clone(),
+                tipo_dato: "numero".to_string(),
+                longitud: None,
+                linea: *linea,
+            });
+        declaradas.iter().cloned().chain(de_ciclo).collect()
+    }
+
+    // Ejecuta las llamadas que hay dentro de la expresión, cada una después
+    // de las de sus argumentos
+    fn calcular(&mut self, expresion: ExprId, linea: usize) {
+        match &self.arena[expresion] {
+            Expresion::Binaria { izquierda, derecha, .. } => {
+                self.calcular(*izquierda, linea);
+                self.calcular(*derecha, linea);
+            }
+            Expresion::Negacion(operando) => self.calcular(*operando, linea),
+            Expresion::Llamada { argumentos, .. } => {
+                for argumento in argumentos {
+                    self.calcular(*argumento, linea);
+                }
+                self.emitir(Operacion::Calcular { llamada: expresion }, linea);
+            }
+            Expresion::Indice { indice, .. } => self.calcular(*indice, linea),
+            _ => {}
+        }
+    }
+
+    // Ramas `(condición, cuerpo, línea)` de un `si`: cada una evalúa su
+    // condición si fallaron las anteriores y, al terminar, salta al final de
+    // la cadena. El `sino` se ejecuta si fallaron todas. Con `calcular` cada
+    // rama ejecuta antes las llamadas de su condición.
+    fn cadena(&mut self, ramas: Vec<(ExprId, &'a [InstrId], usize)>, sino: &'a [InstrId], linea: usize, calcular: bool) {
+        let cantidad = ramas.len();
+        let mut saltos_fin = Vec::new();
+        for (i, (condicion, cuerpo, linea_rama)) in ramas.into_iter().enumerate() {
+            if calcular {
+                self.calcular(condicion, linea_rama);
+            }
+            let salto_siguiente = self.emitir(Operacion::SaltarSiFalso { condicion, destino: 0 }, linea_rama);
+            self.visitar_bloque(cuerpo);
+            if i + 1 < cantidad || !sino.is_empty() {
+                saltos_fin.push(self.emitir(Operacion::Saltar { destino: 0 }, linea));
+            }
+            let siguiente = self.siguiente();
+            fijar_destino(&mut self.operaciones, salto_siguiente, siguiente);
+        }
+
+        self.visitar_bloque(sino);
+        let fin = self.siguiente();
+        for salto in saltos_fin {
+            fijar_destino(&mut self.operaciones, salto, fin);
+        }
+    }
+}
+
+// Los bloques anidados se compilan con `visitar_bloque`, que vuelve a pasar
+// por `visitar_instruccion` para cada instrucción
+impl<'a> Visitor<'a> for Emisor<'a> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+        match instruccion {
+            Instruccion::Elemental { nombre, linea } => {
+                let sensor = self.simbolos.resolver(*nombre).to_string();
+                self.emitir(Operacion::Consultar { sensor }, *linea);
+            }
+            Instruccion::Asignacion { variable, indice, valor, linea } => {
+                if let Some(indice) = indice {
+                    self.calcular(*indice, *linea);
+                }
+                self.calcular(*valor, *linea);
+                let variable = self.simbolos.resolver(*variable).to_string();
+                let operacion = match indice {
+                    Some(indice) => Operacion::AsignarElemento { arreglo: variable, indice: *indice, valor: *valor },
+                    None => Operacion::Asignar { variable, valor: *valor },
+                };
+                self.emitir(operacion, *linea);
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, linea } => {
+                for argumento in argumentos {
+                    self.calcular(*argumento, *linea);
+                }
+                let nombre = self.simbolos.resolver(*nombre);
+                if self.procesos.contains(nombre) {
+                    self.emitir(Operacion::Llamar {
+                        proceso: nombre.to_string(),
+                        argumentos: argumentos.clone(),
+                    }, *linea);
+                } else {
+                    self.emitir(Operacion::Accion {
+                        nombre: nombre.to_string(),
+                        argumentos: argumentos.clone(),
+                    }, *linea);
+                }
+            }
+            Instruccion::Si { condicion, entonces, alternativas, sino, linea } => {
+                let ramas = std::iter::once((*condicion, entonces.as_slice(), *linea))
+                    .chain(alternativas.iter().map(|alternativa| (alternativa.condicion, alternativa.cuerpo.as_slice(), alternativa.linea)))
+                    .collect();
+                self.cadena(ramas, sino, *linea, true);
+            }
+            Instruccion::Mientras { condicion, cuerpo, linea } => {
+                // Cada vuelta vuelve a ejecutar las llamadas de la condición
+                let inicio = self.siguiente();
+                self.calcular(*condicion, *linea);
+                let salto = self.emitir(Operacion::SaltarSiFalso { condicion: *condicion, destino: 0 }, *linea);
+                self.visitar_bloque(cuerpo);
+                self.emitir(Operacion::Saltar { destino: inicio }, *linea);
+                let fin = self.siguiente();
+                fijar_destino(&mut self.operaciones, salto, fin);
+            }
+            Instruccion::Repetir { condicion, cuerpo, linea } => {
+                self.calcular(*condicion, *linea);
+                self.emitir(Operacion::IniciarContador { cantidad: *condicion }, *linea);
+                let inicio = self.emitir(Operacion::DescontarOSaltar { destino: 0 }, *linea);
+                self.visitar_bloque(cuerpo);
+                self.emitir(Operacion::Saltar { destino: inicio }, *linea);
+                let fin = self.siguiente();
+                fijar_destino(&mut self.operaciones, inicio, fin);
+            }
+            // Un `repetir` de `cantidad` vueltas que empieza asignando `desde`
+            // a la variable y le suma uno al final de cada vuelta
+            Instruccion::Para { variable, desde, hasta, cantidad, siguiente, cuerpo, linea } => {
+                let variable = self.simbolos.resolver(*variable).to_string();
+                if !self.variables_de_ciclo.iter().any(|(nombre, _)| *nombre == variable) {
+                    self.variables_de_ciclo.push((variable.clone(), *linea));
+                }
+                self.calcular(*desde, *linea);
+                self.calcular(*hasta, *linea);
+                self.emitir(Operacion::Asignar { variable: variable.clone(), valor: *desde }, *linea);
+                self.emitir(Operacion::IniciarContador { cantidad: *cantidad }, *linea);
+                let inicio = self.emitir(Operacion::DescontarOSaltar { destino: 0 }, *linea);
+                self.visitar_bloque(cuerpo);
+                self.emitir(Operacion::Asignar { variable, valor: *siguiente }, *linea);
+                self.emitir(Operacion::Saltar { destino: inicio }, *linea);
+                let fin = self.siguiente();
+                fijar_destino(&mut self.operaciones, inicio, fin);
+            }
+            // Se ejecuta como una cadena de `si`, con la comparación de cada
+            // caso como condición. Las llamadas del selector se ejecutan una
+            // sola vez, antes de la primera comparación.
+            Instruccion::Segun { selector, casos, sino, linea } => {
+                self.calcular(*selector, *linea);
+                let ramas = casos.iter().map(|caso| (caso.comparacion, caso.cuerpo.as_slice(), caso.linea)).collect();
+                self.cadena(ramas, sino, *linea, false);
+            }
+            // No hace nada, como si la instrucción no estuviera
+            Instruccion::Error { .. } => {}
+        }
+    }
+}
+
+fn fijar_destino(operaciones: &mut [Operacion], indice: usize, nuevo_destino: usize) {
+    match &mut operaciones[indice] {
+        Operacion::SaltarSiFalso { destino, .. }
+        | Operacion::Saltar { destino }
+        | Operacion::DescontarOSaltar { destino } => *destino = nuevo_destino,
+        _ => {}
+    }
+}
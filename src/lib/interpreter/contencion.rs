@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fmt;
+use super::world::AccesoEsquina;
+
+// Pasos dentro de los cuales dos accesos a la misma esquina de robots
+// distintos se consideran simultáneos: con otro intercalado podrían ocurrir
+// en el orden inverso
+pub const VENTANA_CONTENCION: u64 = 10;
+
+// Dos robots que usaron la misma esquina casi al mismo tiempo sin tenerla
+// bloqueada, y al menos uno de ellos cambió su contenido
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contencion {
+    pub av: i32,
+    pub ca: i32,
+    // El primero en acceder y el segundo, con el paso de cada acceso
+    pub robots: [String; 2],
+    pub pasos: [u64; 2],
+}
+
+impl fmt::Display for Contencion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "esquina ({}, {}) sin BloquearEsquina: '{}' en el paso {} y '{}' en el paso {}",
+            self.av, self.ca, self.robots[0], self.pasos[0], self.robots[1], self.pasos[1]
+        )
+    }
+}
+
+// Último acceso sin bloquear de un robot a una esquina
+#[derive(Debug, Clone, Copy)]
+struct Acceso {
+    robot: usize,
+    paso: u64,
+    escritura: bool,
+}
+
+// Recuerda los accesos recientes a cada esquina que el robot no tenía
+// bloqueada. Un acceso protegido con `BloquearEsquina` no cuenta: mientras
+// un robot tiene la esquina ningún otro puede entrar en ella.
+#[derive(Debug, Clone)]
+pub struct DetectorContencion {
+    ventana: u64,
+    recientes: HashMap<(i32, i32), Vec<Acceso>>,
+    contenciones: Vec<Contencion>,
+}
+
+impl DetectorContencion {
+    pub fn new() -> Self {
+        Self {
+            ventana: VENTANA_CONTENCION,
+            recientes: HashMap::new(),
+            contenciones: Vec::new(),
+        }
+    }
+
+    pub fn fijar_ventana(&mut self, ventana: u64) {
+        self.ventana = ventana;
+    }
+
+    // Registra un acceso del robot en el paso indicado. `nombres` son los de
+    // todos los robots, para poder informar al que accedió antes.
+    pub fn registrar(&mut self, robot: usize, paso: u64, acceso: &AccesoEsquina, nombres: &[&str]) {
+        if acceso.protegido {
+            return;
+        }
+        let ventana = self.ventana;
+        let recientes = self.recientes.entry((acceso.av, acceso.ca)).or_default();
+        recientes.retain(|anterior| paso.saturating_sub(anterior.paso) <= ventana);
+
+        for anterior in recientes.iter().filter(|anterior| anterior.robot != robot) {
+            if anterior.escritura || acceso.escritura {
+                self.contenciones.push(Contencion {
+                    av: acceso.av,
+                    ca: acceso.ca,
+                    robots: [nombres[anterior.robot].to_string(), nombres[robot].to_string()],
+                    pasos: [anterior.paso, paso],
+                });
+            }
+        }
+
+        // De cada robot alcanza con su última lectura y su última escritura
+        let mismo = |anterior: &&mut Acceso| anterior.robot == robot && anterior.escritura == acceso.escritura;
+        match recientes.iter_mut().find(mismo) {
+            Some(anterior) => anterior.paso = paso,
+            None => recientes.push(Acceso { robot, paso, escritura: acceso.escritura }),
+        }
+    }
+
+    // Olvida los accesos recientes, por ejemplo al volver a un estado anterior
+    pub fn reiniciar(&mut self) {
+        self.recientes.clear();
+    }
+
+    pub fn contenciones(&self) -> &[Contencion] {
+        &self.contenciones
+    }
+}
+
+impl Default for DetectorContencion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::fmt;
+use super::code::Operacion;
+use super::runtime::{RuntimeError, Valor};
+use super::simulation::{EstadoSimulacion, Simulation};
+
+// Cantidad máxima de pasos que ejecuta `siguiente` antes de rendirse, por si el
+// robot enfocado queda bloqueado mientras los demás no terminan nunca
+const LIMITE_NEXT: u64 = 100_000;
+
+// Punto de parada en una línea, opcionalmente restringido a un robot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub linea: usize,
+    pub robot: Option<String>,
+}
+
+impl Breakpoint {
+    pub fn en_linea(linea: usize) -> Self {
+        Self { linea, robot: None }
+    }
+
+    pub fn en_robot(linea: usize, robot: &str) -> Self {
+        Self { linea, robot: Some(robot.to_string()) }
+    }
+
+    fn aplica(&self, robot: &str, linea: usize) -> bool {
+        self.linea == linea && self.robot.as_deref().is_none_or(|nombre| nombre == robot)
+    }
+}
+
+// Motivo por el que el depurador devolvió el control
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parada {
+    // Paso normal; `linea` es None si el robot terminó
+    Paso { robot: String, linea: Option<usize> },
+    Breakpoint { robot: String, linea: usize },
+    // Cambió el valor de una variable vigilada
+    Watch { robot: String, variable: String, anterior: Valor, nuevo: Valor },
+    // El robot está por ejecutar un EnviarMensaje
+    Mensaje { robot: String, linea: usize },
+    Terminada,
+    Bloqueada,
+    // Se agotaron los pasos sin llegar a ninguna parada
+    Limite,
+}
+
+impl fmt::Display for Parada {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parada::Paso { robot, linea: Some(linea) } => write!(f, "{} en la línea {}", robot, linea),
+            Parada::Paso { robot, linea: None } => write!(f, "{} terminó", robot),
+            Parada::Breakpoint { robot, linea } => write!(f, "Breakpoint: {} en la línea {}", robot, linea),
+            Parada::Watch { robot, variable, anterior, nuevo } => {
+                write!(f, "Watch: {}.{} cambió de {} a {}", robot, variable, anterior, nuevo)
+            }
+            Parada::Mensaje { robot, linea } => write!(f, "{} va a enviar un mensaje en la línea {}", robot, linea),
+            Parada::Terminada => write!(f, "La simulación terminó"),
+            Parada::Bloqueada => write!(f, "Todos los robots están bloqueados"),
+            Parada::Limite => write!(f, "Límite de pasos alcanzado"),
+        }
+    }
+}
+
+// Depurador construido sobre la ejecución paso a paso de la simulación
+pub struct Debugger {
+    simulacion: Simulation,
+    breakpoints: Vec<Breakpoint>,
+    vigiladas: Vec<String>,
+    parar_en_mensajes: bool,
+    // Robot sobre el que actúan `siguiente` e `imprimir`
+    foco: Option<usize>,
+    // Línea en la que quedó cada robot tras su último paso
+    lineas: Vec<Option<usize>>,
+    // Último valor visto de cada variable vigilada, por robot
+    valores: Vec<HashMap<String, Valor>>,
+    // Robots cuya posición inicial todavía no se comparó con los breakpoints
+    sin_revisar: Vec<usize>,
+}
+
+impl Debugger {
+    pub fn new(simulacion: Simulation) -> Self {
+        let cantidad = simulacion.mundo().robots.len();
+        let lineas = (0..cantidad)
+            .map(|indice| Self::linea_de(&simulacion, indice))
+            .collect();
+
+        Self {
+            simulacion,
+            breakpoints: Vec::new(),
+            vigiladas: Vec::new(),
+            parar_en_mensajes: false,
+            foco: None,
+            lineas,
+            valores: vec![HashMap::new(); cantidad],
+            sin_revisar: (0..cantidad).rev().collect(),
+        }
+    }
+
+    pub fn simulacion(&self) -> &Simulation {
+        &self.simulacion
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn agregar_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn quitar_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+        let antes = self.breakpoints.len();
+        self.breakpoints.retain(|actual| actual != breakpoint);
+        self.breakpoints.len() != antes
+    }
+
+    pub fn vigilar(&mut self, variable: &str) {
+        if self.vigiladas.iter().any(|vigilada| vigilada == variable) {
+            return;
+        }
+        self.vigiladas.push(variable.to_string());
+        for indice in 0..self.valores.len() {
+            if let Some(valor) = self.valor_visible(indice, variable) {
+                self.valores[indice].insert(variable.to_string(), valor);
+            }
+        }
+    }
+
+    pub fn parar_en_mensajes(&mut self, activado: bool) {
+        self.parar_en_mensajes = activado;
+    }
+
+    pub fn para_en_mensajes(&self) -> bool {
+        self.parar_en_mensajes
+    }
+
+    pub fn foco(&self) -> Option<&str> {
+        self.foco.map(|indice| self.nombre(indice))
+    }
+
+    pub fn enfocar(&mut self, robot: &str) -> Result<(), RuntimeError> {
+        let indice = self.indice(robot)?;
+        self.foco = Some(indice);
+        Ok(())
+    }
+
+    // Valor de una variable del robot indicado, o del enfocado si no se indica
+    pub fn imprimir(&self, variable: &str, robot: Option<&str>) -> Result<Valor, RuntimeError> {
+        let indice = match robot {
+            Some(nombre) => self.indice(nombre)?,
+            None => self.foco.unwrap_or(0),
+        };
+        self.valor_visible(indice, variable)
+            .ok_or_else(|| RuntimeError::UndefinedVariable(variable.to_string()))
+    }
+
+    // Ejecuta una única operación de la simulación
+    pub fn paso(&mut self) -> Result<Parada, RuntimeError> {
+        if let Some(fin) = self.avanzar()? {
+            return Ok(fin);
+        }
+        let indice = self.ultimo()?;
+        self.registrar(indice);
+        Ok(Parada::Paso {
+            robot: self.nombre(indice).to_string(),
+            linea: self.lineas[indice],
+        })
+    }
+
+    // Avanza el robot enfocado hasta la siguiente línea sin entrar en los
+    // procesos que llame. Los demás robots siguen ejecutándose mientras tanto.
+    pub fn siguiente(&mut self) -> Result<Parada, RuntimeError> {
+        let Some(foco) = self.foco else {
+            return self.paso();
+        };
+        let linea_inicial = self.lineas[foco];
+        let profundidad_inicial = self.profundidad(foco);
+
+        for _ in 0..LIMITE_NEXT {
+            if let Some(fin) = self.avanzar()? {
+                return Ok(fin);
+            }
+            let indice = self.ultimo()?;
+            if let Some(parada) = self.revisar(indice) {
+                return Ok(parada);
+            }
+
+            if indice == foco {
+                let profundidad = self.profundidad(foco);
+                let linea = self.lineas[foco];
+                if profundidad < profundidad_inicial || (profundidad == profundidad_inicial && linea != linea_inicial) {
+                    return Ok(Parada::Paso { robot: self.nombre(foco).to_string(), linea });
+                }
+            }
+        }
+        Ok(Parada::Limite)
+    }
+
+    // Ejecuta hasta un breakpoint, un watch, un envío de mensaje o el final
+    pub fn continuar(&mut self, max_pasos: u64) -> Result<Parada, RuntimeError> {
+        while let Some(indice) = self.sin_revisar.pop() {
+            if let Some(parada) = self.revisar_breakpoint(indice) {
+                self.foco = Some(indice);
+                return Ok(parada);
+            }
+        }
+
+        for _ in 0..max_pasos {
+            if let Some(fin) = self.avanzar()? {
+                return Ok(fin);
+            }
+            let indice = self.ultimo()?;
+            if let Some(parada) = self.revisar(indice) {
+                return Ok(parada);
+            }
+        }
+        Ok(Parada::Limite)
+    }
+
+    // Ejecuta un paso de la simulación; devuelve la parada si ya no hay nada que ejecutar
+    fn avanzar(&mut self) -> Result<Option<Parada>, RuntimeError> {
+        let pasos = self.simulacion.pasos();
+        let estado = self.simulacion.paso()?;
+        if self.simulacion.pasos() != pasos {
+            return Ok(None);
+        }
+        Ok(Some(match estado {
+            EstadoSimulacion::Bloqueada => Parada::Bloqueada,
+            EstadoSimulacion::Ejecutando | EstadoSimulacion::Terminada => Parada::Terminada,
+        }))
+    }
+
+    fn ultimo(&self) -> Result<usize, RuntimeError> {
+        self.simulacion
+            .ultimo_robot()
+            .ok_or_else(|| RuntimeError::Robot("Ningún robot ejecutó el último paso".to_string()))
+    }
+
+    // Actualiza la línea y las variables vigiladas del robot que acaba de moverse
+    // y devuelve el cambio de línea junto con los watches que cambiaron
+    fn registrar(&mut self, indice: usize) -> (bool, Vec<Parada>) {
+        let linea = Self::linea_de(&self.simulacion, indice);
+        let cambio_linea = linea != self.lineas[indice];
+        self.lineas[indice] = linea;
+
+        let mut cambios = Vec::new();
+        for variable in self.vigiladas.clone() {
+            let Some(nuevo) = self.valor_visible(indice, &variable) else {
+                continue;
+            };
+            if let Some(anterior) = self.valores[indice].insert(variable.clone(), nuevo.clone())
+                && anterior != nuevo
+            {
+                cambios.push(Parada::Watch {
+                    robot: self.nombre(indice).to_string(),
+                    variable,
+                    anterior,
+                    nuevo,
+                });
+            }
+        }
+        (cambio_linea, cambios)
+    }
+
+    fn revisar(&mut self, indice: usize) -> Option<Parada> {
+        let (cambio_linea, cambios) = self.registrar(indice);
+
+        let parada = cambios.into_iter().next().or_else(|| {
+            if !cambio_linea {
+                return None;
+            }
+            self.revisar_breakpoint(indice).or_else(|| self.revisar_mensaje(indice))
+        });
+
+        if parada.is_some() {
+            self.foco = Some(indice);
+        }
+        parada
+    }
+
+    fn revisar_breakpoint(&self, indice: usize) -> Option<Parada> {
+        let linea = self.lineas[indice]?;
+        let robot = self.nombre(indice);
+        self.breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.aplica(robot, linea))
+            .then(|| Parada::Breakpoint { robot: robot.to_string(), linea })
+    }
+
+    fn revisar_mensaje(&self, indice: usize) -> Option<Parada> {
+        if !self.parar_en_mensajes {
+            return None;
+        }
+        let ejecucion = self.simulacion.ejecucion(indice)?;
+        match self.simulacion.interprete().proxima_operacion(ejecucion)? {
+            (Operacion::Accion { nombre, .. }, linea) if nombre == "EnviarMensaje" => {
+                Some(Parada::Mensaje { robot: self.nombre(indice).to_string(), linea })
+            }
+            _ => None,
+        }
+    }
+
+    fn linea_de(simulacion: &Simulation, indice: usize) -> Option<usize> {
+        let ejecucion = simulacion.ejecucion(indice)?;
+        simulacion.interprete().linea_actual(ejecucion)
+    }
+
+    fn profundidad(&self, indice: usize) -> usize {
+        self.simulacion.ejecucion(indice).map_or(0, |ejecucion| ejecucion.profundidad())
+    }
+
+    fn valor_visible(&self, indice: usize, variable: &str) -> Option<Valor> {
+        self.simulacion.ejecucion(indice)?.variables().get(variable).cloned()
+    }
+
+    fn nombre(&self, indice: usize) -> &str {
+        &self.simulacion.mundo().robots[indice].nombre
+    }
+
+    fn indice(&self, robot: &str) -> Result<usize, RuntimeError> {
+        self.simulacion
+            .mundo()
+            .indice_robot(robot)
+            .ok_or_else(|| RuntimeError::Robot(format!("Robot '{}' no declarado", robot)))
+    }
+}
+
+// Comandos de la consola de depuración
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Comando {
+    Step,
+    Next,
+    Continue,
+    Print { variable: String, robot: Option<String> },
+    Break(Breakpoint),
+    Delete(Breakpoint),
+    Watch(String),
+    Mensajes,
+    Robots,
+    Ayuda,
+    Salir,
+}
+
+impl Comando {
+    pub fn parse(linea: &str) -> Result<Comando, String> {
+        let partes: Vec<&str> = linea.split_whitespace().collect();
+        let Some((comando, argumentos)) = partes.split_first() else {
+            return Err("Comando vacío".to_string());
+        };
+
+        match (*comando, argumentos) {
+            ("step" | "s", []) => Ok(Comando::Step),
+            ("next" | "n", []) => Ok(Comando::Next),
+            ("continue" | "c", []) => Ok(Comando::Continue),
+            ("print" | "p", [variable]) => Ok(Comando::Print { variable: variable.to_string(), robot: None }),
+            ("print" | "p", [variable, robot]) => Ok(Comando::Print {
+                variable: variable.to_string(),
+                robot: Some(robot.to_string()),
+            }),
+            ("break" | "b", [linea, resto @ ..]) if resto.len() <= 1 => {
+                Ok(Comando::Break(parse_breakpoint(linea, resto.first())?))
+            }
+            ("delete" | "d", [linea, resto @ ..]) if resto.len() <= 1 => {
+                Ok(Comando::Delete(parse_breakpoint(linea, resto.first())?))
+            }
+            ("watch" | "w", [variable]) => Ok(Comando::Watch(variable.to_string())),
+            ("mensajes" | "m", []) => Ok(Comando::Mensajes),
+            ("robots" | "r", []) => Ok(Comando::Robots),
+            ("help" | "h" | "ayuda", []) => Ok(Comando::Ayuda),
+            ("quit" | "q" | "salir", []) => Ok(Comando::Salir),
+            _ => Err(format!("Comando no reconocido: '{}' (use 'help')", linea.trim())),
+        }
+    }
+}
+
+fn parse_breakpoint(linea: &str, robot: Option<&&str>) -> Result<Breakpoint, String> {
+    let linea = linea
+        .parse::<usize>()
+        .map_err(|_| format!("Número de línea inválido: '{}'", linea))?;
+    Ok(match robot {
+        Some(robot) => Breakpoint::en_robot(linea, robot),
+        None => Breakpoint::en_linea(linea),
+    })
+}
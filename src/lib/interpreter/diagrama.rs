@@ -0,0 +1,35 @@
+use std::fmt::Write;
+use super::world::{EventoMensaje, Mundo};
+
+// Diagrama de secuencia de Mermaid con los mensajes que se intercambiaron
+// los robots, en el orden en que ocurrieron. Cada envío es una flecha del
+// emisor al destino y cada recepción una nota sobre el robot que lo recibió,
+// así se ve cuánto tardó en leerlo.
+pub fn secuencia_mensajes(mundo: &Mundo) -> String {
+    let mut diagrama = String::from("sequenceDiagram\n");
+    for robot in &mundo.robots {
+        let _ = writeln!(diagrama, "    participant {} as {} ({})", robot.nombre, robot.nombre, robot.tipo);
+    }
+    for evento in &mundo.mensajes {
+        let _ = match evento {
+            EventoMensaje::Enviado { emisor, destino, valor } => {
+                writeln!(diagrama, "    {}-){}: {}", emisor, destino, escapar(&valor.to_string()))
+            }
+            EventoMensaje::Recibido { receptor, emisor, valor } => {
+                writeln!(diagrama, "    Note over {}: recibe {} de {}", receptor, escapar(&valor.to_string()), emisor)
+            }
+        };
+    }
+    diagrama
+}
+
+// Mermaid usa `;` para separar sentencias y `#` para sus propios escapes
+fn escapar(texto: &str) -> String {
+    texto.chars()
+        .map(|c| match c {
+            '#' => "#35;".to_string(),
+            ';' => "#59;".to_string(),
+            otro => otro.to_string(),
+        })
+        .collect()
+}
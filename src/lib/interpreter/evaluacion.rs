@@ -0,0 +1,194 @@
+use std::fmt;
+use crate::compilerError::CompilerError;
+use crate::mensajes::{Codigo, Mensaje};
+use super::world::{Ciudad, EstadoRobot, Mundo};
+
+// Comprobación sobre el mundo final: `Err` lleva lo que se encontró en su lugar
+type Comprobacion = Box<dyn Fn(&Mundo) -> Result<(), String>>;
+
+// Resultados esperados de una simulación, para corregir ejercicios de forma
+// automática: se escriben en un archivo de afirmaciones o desde el programa
+// que usa la biblioteca, y se verifican sobre el mundo al terminar.
+#[derive(Default)]
+pub struct Evaluacion {
+    afirmaciones: Vec<(String, Comprobacion)>,
+}
+
+impl Evaluacion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Lee las afirmaciones de un archivo, una por línea:
+    //   flores <av> <ca> <cantidad>
+    //   papeles <av> <ca> <cantidad>
+    //   robot <nombre> en <av> <ca>
+    //   robot <nombre> flores <cantidad>
+    //   robot <nombre> papeles <cantidad>
+    //   robot <nombre> informa <texto>
+    // Las líneas vacías y los comentarios `{ ... }` se ignoran.
+    pub fn desde_texto(texto: &str) -> Result<Self, CompilerError> {
+        let mut evaluacion = Self::new();
+
+        for (i, linea) in texto.lines().enumerate() {
+            let linea = linea.trim();
+            if linea.is_empty() || linea.starts_with('{') {
+                continue;
+            }
+
+            let error = |codigo: Codigo| CompilerError::new(Mensaje::new(codigo).con(linea), i + 1, 0);
+            let numero = |texto: &str| texto.parse::<i32>().map_err(|_| error(Codigo::NumeroInvalido));
+            let cantidad = |texto: &str| texto.parse::<u32>().map_err(|_| error(Codigo::CantidadInvalida));
+            let partes: Vec<&str> = linea.split_whitespace().collect();
+            evaluacion = match partes[..] {
+                [objeto @ ("flores" | "papeles"), av, ca, total] => {
+                    let (av, ca, total) = (numero(av)?, numero(ca)?, cantidad(total)?);
+                    if !Ciudad::dentro(av, ca) {
+                        return Err(error(Codigo::EsquinaFueraDeCiudad));
+                    }
+                    if objeto == "flores" {
+                        evaluacion.flores_en_esquina(av, ca, total)
+                    } else {
+                        evaluacion.papeles_en_esquina(av, ca, total)
+                    }
+                }
+                ["robot", robot, "en", av, ca] => evaluacion.robot_en(robot, numero(av)?, numero(ca)?),
+                ["robot", robot, "flores", total] => evaluacion.flores_en_bolsa(robot, cantidad(total)?),
+                ["robot", robot, "papeles", total] => evaluacion.papeles_en_bolsa(robot, cantidad(total)?),
+                ["robot", robot, "informa", ..] => {
+                    let texto = partes[3..].join(" ");
+                    evaluacion.informa(robot, &texto)
+                }
+                _ => return Err(error(Codigo::AfirmacionDesconocida)),
+            };
+        }
+
+        Ok(evaluacion)
+    }
+
+    // Afirmación arbitraria sobre el mundo final
+    pub fn afirmar(self, descripcion: &str, condicion: impl Fn(&Mundo) -> bool + 'static) -> Self {
+        self.agregar(descripcion.to_string(), move |mundo| {
+            if condicion(mundo) { Ok(()) } else { Err("no se cumple".to_string()) }
+        })
+    }
+
+    pub fn flores_en_esquina(self, av: i32, ca: i32, cantidad: u32) -> Self {
+        self.agregar(format!("la esquina ({}, {}) tiene {} flores", av, ca, cantidad), move |mundo| {
+            let flores = mundo.ciudad.esquina(av, ca).map_or(0, |esquina| esquina.flores);
+            esperar(flores, cantidad)
+        })
+    }
+
+    pub fn papeles_en_esquina(self, av: i32, ca: i32, cantidad: u32) -> Self {
+        self.agregar(format!("la esquina ({}, {}) tiene {} papeles", av, ca, cantidad), move |mundo| {
+            let papeles = mundo.ciudad.esquina(av, ca).map_or(0, |esquina| esquina.papeles);
+            esperar(papeles, cantidad)
+        })
+    }
+
+    pub fn robot_en(self, robot: &str, av: i32, ca: i32) -> Self {
+        let nombre = robot.to_string();
+        self.agregar(format!("el robot '{}' termina en ({}, {})", robot, av, ca), move |mundo| {
+            let robot = buscar_robot(mundo, &nombre)?;
+            if (robot.av, robot.ca) == (av, ca) { Ok(()) } else { Err(format!("está en ({}, {})", robot.av, robot.ca)) }
+        })
+    }
+
+    pub fn flores_en_bolsa(self, robot: &str, cantidad: u32) -> Self {
+        let nombre = robot.to_string();
+        self.agregar(format!("el robot '{}' tiene {} flores en la bolsa", robot, cantidad), move |mundo| {
+            esperar(buscar_robot(mundo, &nombre)?.flores, cantidad)
+        })
+    }
+
+    pub fn papeles_en_bolsa(self, robot: &str, cantidad: u32) -> Self {
+        let nombre = robot.to_string();
+        self.agregar(format!("el robot '{}' tiene {} papeles en la bolsa", robot, cantidad), move |mundo| {
+            esperar(buscar_robot(mundo, &nombre)?.papeles, cantidad)
+        })
+    }
+
+    // El robot informó el texto en alguna de sus llamadas a `Informar`
+    pub fn informa(self, robot: &str, texto: &str) -> Self {
+        let (nombre, esperado) = (robot.to_string(), texto.to_string());
+        self.agregar(format!("el robot '{}' informa '{}'", robot, texto), move |mundo| {
+            let robot = buscar_robot(mundo, &nombre)?;
+            if robot.informes.contains(&esperado) {
+                Ok(())
+            } else if robot.informes.is_empty() {
+                Err("no informó nada".to_string())
+            } else {
+                Err(format!("informó {}", robot.informes.iter().map(|informe| format!("'{}'", informe)).collect::<Vec<_>>().join(", ")))
+            }
+        })
+    }
+
+    fn agregar(mut self, descripcion: String, comprobacion: impl Fn(&Mundo) -> Result<(), String> + 'static) -> Self {
+        self.afirmaciones.push((descripcion, Box::new(comprobacion)));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.afirmaciones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.afirmaciones.is_empty()
+    }
+
+    pub fn evaluar(&self, mundo: &Mundo) -> Informe {
+        let resultados = self.afirmaciones.iter()
+            .map(|(descripcion, comprobacion)| Resultado {
+                descripcion: descripcion.clone(),
+                fallo: comprobacion(mundo).err(),
+            })
+            .collect();
+        Informe { resultados }
+    }
+}
+
+fn esperar(encontrado: u32, esperado: u32) -> Result<(), String> {
+    if encontrado == esperado { Ok(()) } else { Err(format!("hay {}", encontrado)) }
+}
+
+fn buscar_robot<'a>(mundo: &'a Mundo, nombre: &str) -> Result<&'a EstadoRobot, String> {
+    mundo.robot(nombre).ok_or_else(|| "el robot no existe".to_string())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resultado {
+    pub descripcion: String,
+    // Qué se encontró en lugar de lo esperado; `None` si se cumplió
+    pub fallo: Option<String>,
+}
+
+// Resultado de cada afirmación, en el orden en que se agregaron
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Informe {
+    pub resultados: Vec<Resultado>,
+}
+
+impl Informe {
+    pub fn aprobado(&self) -> bool {
+        self.resultados.iter().all(|resultado| resultado.fallo.is_none())
+    }
+
+    pub fn cumplidas(&self) -> usize {
+        self.resultados.iter().filter(|resultado| resultado.fallo.is_none()).count()
+    }
+}
+
+impl fmt::Display for Informe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for resultado in &self.resultados {
+            match &resultado.fallo {
+                None => writeln!(f, "✓ {}", resultado.descripcion)?,
+                Some(fallo) => writeln!(f, "✗ {}: {}", resultado.descripcion, fallo)?,
+            }
+        }
+        write!(f, "{} de {} afirmaciones cumplidas", self.cumplidas(), self.resultados.len())
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+use super::machine::Ejecucion;
+use super::runtime::RuntimeError;
+use super::simulation::{Simulation, SimulationState};
+use super::world::{Mundo, TAMANIO_CIUDAD};
+
+// Límites de una exploración. Con `cambios` se acota cuántas veces se le
+// puede quitar el turno a un robot que todavía podía avanzar: la mayoría de
+// las condiciones de carrera aparecen con uno o dos cambios, y así la
+// cantidad de intercalados crece de forma polinomial y no exponencial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limites {
+    pub cambios: u32,
+    pub pasos: u64,
+    pub ejecuciones: usize,
+}
+
+impl Default for Limites {
+    fn default() -> Self {
+        Self { cambios: 2, pasos: 100_000, ejecuciones: 5_000 }
+    }
+}
+
+// Lo que interesa comparar del mundo al final de una ejecución: dónde quedó
+// cada robot, qué lleva y qué informó, y lo que quedó en cada esquina
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Foto {
+    pub robots: Vec<RobotFinal>,
+    // (avenida, calle, flores, papeles) de las esquinas con algo
+    pub esquinas: Vec<(i32, i32, u32, u32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobotFinal {
+    pub nombre: String,
+    pub av: i32,
+    pub ca: i32,
+    pub flores: u32,
+    pub papeles: u32,
+    pub informes: Vec<String>,
+}
+
+impl Foto {
+    pub fn new(mundo: &Mundo) -> Self {
+        let robots = mundo.robots
+            .iter()
+            .map(|robot| RobotFinal {
+                nombre: robot.nombre.clone(),
+                av: robot.av,
+                ca: robot.ca,
+                flores: robot.flores,
+                papeles: robot.papeles,
+                informes: robot.informes.clone(),
+            })
+            .collect();
+
+        let mut esquinas = Vec::new();
+        for av in 1..=TAMANIO_CIUDAD {
+            for ca in 1..=TAMANIO_CIUDAD {
+                if let Some(esquina) = mundo.ciudad.esquina(av, ca)
+                    && (esquina.flores > 0 || esquina.papeles > 0)
+                {
+                    esquinas.push((av, ca, esquina.flores, esquina.papeles));
+                }
+            }
+        }
+
+        Self { robots, esquinas }
+    }
+
+    // Esquinas cuyo contenido no coincide con el de otra foto
+    pub fn esquinas_distintas(&self, otra: &Foto) -> Vec<(i32, i32, u32, u32)> {
+        let mut distintas: Vec<_> = self.esquinas
+            .iter()
+            .filter(|esquina| !otra.esquinas.contains(esquina))
+            .copied()
+            .collect();
+        // Las que quedaron vacías en esta foto y no en la otra
+        for &(av, ca, _, _) in &otra.esquinas {
+            if !self.esquinas.iter().any(|&(a, c, _, _)| (a, c) == (av, ca)) {
+                distintas.push((av, ca, 0, 0));
+            }
+        }
+        distintas.sort();
+        distintas
+    }
+}
+
+impl fmt::Display for Foto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, robot) in self.robots.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{} en ({}, {}) con {} flores y {} papeles", robot.nombre, robot.av, robot.ca, robot.flores, robot.papeles)?;
+            if !robot.informes.is_empty() {
+                write!(f, ", informó {}", robot.informes.join(" / "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Cómo terminó una ejecución
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Desenlace {
+    Terminada(Foto),
+    // Todos los robots que quedaban esperan un mensaje o una esquina
+    Bloqueada(Foto),
+    Error(String),
+    // Se agotaron los pasos de `Limites::pasos` sin terminar
+    SinTerminar,
+}
+
+impl Desenlace {
+    pub fn foto(&self) -> Option<&Foto> {
+        match self {
+            Desenlace::Terminada(foto) | Desenlace::Bloqueada(foto) => Some(foto),
+            Desenlace::Error(_) | Desenlace::SinTerminar => None,
+        }
+    }
+}
+
+impl fmt::Display for Desenlace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Desenlace::Terminada(foto) => write!(f, "terminó: {}", foto),
+            Desenlace::Bloqueada(foto) => write!(f, "deadlock: {}", foto),
+            Desenlace::Error(mensaje) => write!(f, "error de ejecución: {}", mensaje),
+            Desenlace::SinTerminar => write!(f, "no terminó en el máximo de pasos"),
+        }
+    }
+}
+
+// Un desenlace distinto, con el primer intercalado que llegó a él y cuántos
+// de los explorados terminaron igual
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resultado {
+    pub desenlace: Desenlace,
+    // Índice del robot que avanzó en cada paso
+    pub intercalado: Vec<usize>,
+    pub ejecuciones: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exploracion {
+    pub resultados: Vec<Resultado>,
+    pub ejecuciones: usize,
+    // Falso si se alcanzó `Limites::ejecuciones` antes de recorrer todos los
+    // intercalados dentro de la cota de cambios
+    pub completa: bool,
+    // Nombre de cada robot, para mostrar los intercalados
+    pub robots: Vec<String>,
+}
+
+impl Exploracion {
+    pub fn determinista(&self) -> bool {
+        self.resultados.len() <= 1
+    }
+
+    // Intercalado como tramos consecutivos de cada robot: "r1×5, r2×3, r1×2"
+    pub fn tramos(&self, intercalado: &[usize]) -> String {
+        let mut tramos: Vec<(usize, usize)> = Vec::new();
+        for &robot in intercalado {
+            match tramos.last_mut() {
+                Some((ultimo, veces)) if *ultimo == robot => *veces += 1,
+                _ => tramos.push((robot, 1)),
+            }
+        }
+        tramos
+            .iter()
+            .map(|&(robot, veces)| format!("{}×{}", self.robots[robot], veces))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// Intercalado pendiente: se reproduce `prefijo` desde el estado inicial y
+// después avanza `robot`, si puede
+struct Rama {
+    prefijo: Vec<usize>,
+    robot: usize,
+    cambios: u32,
+}
+
+// Ejecuta la simulación bajo distintos intercalados de sus robots y agrupa
+// las ejecuciones según cómo terminaron. Cada rama arranca desde el estado
+// inicial de `simulacion`, que al final queda en el de la última ejecución.
+pub fn explorar(simulacion: &mut Simulation, limites: Limites) -> Result<Exploracion, RuntimeError> {
+    let inicial = simulacion.snapshot();
+    let robots: Vec<String> = simulacion.mundo().robots.iter().map(|robot| robot.nombre.clone()).collect();
+    let mut exploracion = Exploracion { resultados: Vec::new(), ejecuciones: 0, completa: true, robots };
+
+    // La primera ejecución no fuerza a ningún robot
+    let mut pendientes = vec![None];
+    while let Some(rama) = pendientes.pop() {
+        if exploracion.ejecuciones >= limites.ejecuciones {
+            exploracion.completa = false;
+            break;
+        }
+
+        simulacion.restore(inicial.clone())?;
+        let mut ramas = Vec::new();
+        let ejecucion = seguir(simulacion, &inicial, &limites, rama, &mut ramas)?;
+        pendientes.extend(ramas.into_iter().map(Some));
+
+        // Si el robot forzado estaba bloqueado ese intercalado ya lo cubre otra rama
+        let Some((desenlace, intercalado)) = ejecucion else {
+            continue;
+        };
+        exploracion.ejecuciones += 1;
+        match exploracion.resultados.iter_mut().find(|resultado| resultado.desenlace == desenlace) {
+            Some(resultado) => resultado.ejecuciones += 1,
+            None => exploracion.resultados.push(Resultado { desenlace, intercalado, ejecuciones: 1 }),
+        }
+    }
+
+    Ok(exploracion)
+}
+
+// Reproduce una rama y continúa la ejecución hasta el final, dejando en
+// `ramas` los intercalados en los que otro robot le quita el turno al actual.
+// Cuando el actual no puede avanzar el cambio no se cuenta, y se abre una
+// rama por cada robot que sí. Los intentos bloqueados sólo cambian las
+// estadísticas de espera, que no forman parte del desenlace.
+fn seguir(
+    simulacion: &mut Simulation,
+    inicial: &SimulationState,
+    limites: &Limites,
+    rama: Option<Rama>,
+    ramas: &mut Vec<Rama>,
+) -> Result<Option<(Desenlace, Vec<usize>)>, RuntimeError> {
+    let cantidad = simulacion.mundo().robots.len();
+    let terminado = |simulacion: &Simulation, robot: usize| simulacion.ejecucion(robot).is_none_or(Ejecucion::terminada);
+
+    let (mut intercalado, mut actual, cambios) = match rama {
+        None => (Vec::new(), None, 0),
+        Some(Rama { prefijo, robot, cambios }) => {
+            // El prefijo ya se ejecutó sin errores, así que vuelve a avanzar igual
+            for &anterior in &prefijo {
+                simulacion.paso_de(anterior)?;
+            }
+            match simulacion.paso_de(robot) {
+                Ok(true) => {}
+                Ok(false) => return Ok(None),
+                Err(e) => return Ok(Some((Desenlace::Error(e.to_string()), prefijo))),
+            }
+            let mut intercalado = prefijo;
+            intercalado.push(robot);
+            (intercalado, Some(robot), cambios)
+        }
+    };
+
+    loop {
+        if simulacion.terminada() {
+            return Ok(Some((Desenlace::Terminada(Foto::new(simulacion.mundo())), intercalado)));
+        }
+        if simulacion.pasos() - inicial.pasos >= limites.pasos {
+            return Ok(Some((Desenlace::SinTerminar, intercalado)));
+        }
+
+        if let Some(robot) = actual {
+            match simulacion.paso_de(robot) {
+                Ok(true) => {
+                    if cambios < limites.cambios {
+                        for otro in (0..cantidad).filter(|&otro| otro != robot && !terminado(simulacion, otro)) {
+                            ramas.push(Rama { prefijo: intercalado.clone(), robot: otro, cambios: cambios + 1 });
+                        }
+                    }
+                    intercalado.push(robot);
+                    continue;
+                }
+                Ok(false) => {}
+                Err(e) => return Ok(Some((Desenlace::Error(e.to_string()), intercalado))),
+            }
+        }
+
+        // Cambio de robot sin costo: se prueba cada uno desde el mismo estado
+        let antes = simulacion.snapshot();
+        let mut habilitados = Vec::new();
+        for otro in (0..cantidad).filter(|&otro| Some(otro) != actual) {
+            let avanzo = simulacion.paso_de(otro);
+            simulacion.restore(antes.clone())?;
+            match avanzo {
+                Ok(true) => habilitados.push(otro),
+                Ok(false) => {}
+                Err(e) => {
+                    let mut intercalado = intercalado;
+                    intercalado.push(otro);
+                    return Ok(Some((Desenlace::Error(e.to_string()), intercalado)));
+                }
+            }
+        }
+
+        let Some((&siguiente, otros)) = habilitados.split_first() else {
+            return Ok(Some((Desenlace::Bloqueada(Foto::new(simulacion.mundo())), intercalado)));
+        };
+        for &otro in otros {
+            ramas.push(Rama { prefijo: intercalado.clone(), robot: otro, cambios });
+        }
+        simulacion.paso_de(siguiente)?;
+        intercalado.push(siguiente);
+        actual = Some(siguiente);
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// Veces que un robot puede repetir la misma espera o la misma condición sin
+// que nada cambie antes de que se lo informe como posible livelock
+pub const UMBRAL_LIVELOCK: u32 = 1_000;
+
+// Qué repite el robot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Repeticion {
+    // Intenta una acción que queda bloqueada (esquina ocupada, mensaje que no llega)
+    Espera,
+    // Vuelve a evaluar la condición de un `mientras` o un `si`
+    Condicion,
+}
+
+// Un robot que repitió lo mismo `veces` veces seguidas sin que cambiara el
+// mundo ni sus propias variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvisoLivelock {
+    pub robot: String,
+    pub linea: usize,
+    pub repeticion: Repeticion,
+    pub veces: u32,
+}
+
+impl fmt::Display for AvisoLivelock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let que = match self.repeticion {
+            Repeticion::Espera => "esperó",
+            Repeticion::Condicion => "evaluó la condición",
+        };
+        write!(
+            f,
+            "posible livelock: el robot '{}' {} {} veces en la línea {} sin cambios en el mundo",
+            self.robot, que, self.veces, self.linea
+        )
+    }
+}
+
+// Lleva la cuenta de lo que repite cada robot desde el último cambio. Un
+// cambio es una acción completada por cualquier robot (el mundo cambió) o
+// una asignación del propio robot (sus variables cambiaron): un `mientras`
+// que cuenta con una variable avanza aunque no toque el mundo.
+#[derive(Debug, Clone)]
+pub struct DetectorLivelock {
+    umbral: u32,
+    cambios_mundo: u64,
+    robots: Vec<Seguimiento>,
+    avisos: Vec<AvisoLivelock>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Seguimiento {
+    asignaciones: u64,
+    // Cambios del mundo y asignaciones cuando empezó la cuenta actual
+    desde: (u64, u64),
+    repeticiones: HashMap<(usize, Repeticion), u32>,
+}
+
+impl DetectorLivelock {
+    pub fn new(robots: usize) -> Self {
+        Self {
+            umbral: UMBRAL_LIVELOCK,
+            cambios_mundo: 0,
+            robots: vec![Seguimiento::default(); robots],
+            avisos: Vec::new(),
+        }
+    }
+
+    pub fn fijar_umbral(&mut self, umbral: u32) {
+        self.umbral = umbral;
+    }
+
+    pub fn cambio_mundo(&mut self) {
+        self.cambios_mundo += 1;
+    }
+
+    pub fn asignacion(&mut self, robot: usize) {
+        if let Some(seguimiento) = self.robots.get_mut(robot) {
+            seguimiento.asignaciones += 1;
+        }
+    }
+
+    // Registra que el robot repitió algo en la línea. Cada racha sin cambios
+    // se informa una sola vez, al llegar al umbral.
+    pub fn registrar(&mut self, robot: usize, nombre: &str, linea: usize, repeticion: Repeticion) {
+        let Some(seguimiento) = self.robots.get_mut(robot) else {
+            return;
+        };
+        let ahora = (self.cambios_mundo, seguimiento.asignaciones);
+        if seguimiento.desde != ahora {
+            seguimiento.desde = ahora;
+            seguimiento.repeticiones.clear();
+        }
+
+        let veces = seguimiento.repeticiones.entry((linea, repeticion)).or_default();
+        *veces += 1;
+        if *veces == self.umbral {
+            self.avisos.push(AvisoLivelock {
+                robot: nombre.to_string(),
+                linea,
+                repeticion,
+                veces: self.umbral,
+            });
+        }
+    }
+
+    // Olvida las cuentas en curso, por ejemplo al volver a un estado anterior
+    pub fn reiniciar(&mut self) {
+        for seguimiento in &mut self.robots {
+            *seguimiento = Seguimiento::default();
+        }
+        self.cambios_mundo = 0;
+    }
+
+    pub fn avisos(&self) -> &[AvisoLivelock] {
+        &self.avisos
+    }
+}
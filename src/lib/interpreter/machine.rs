@@ -0,0 +1,626 @@
+use std::collections::{HashMap, HashSet};
+use crate::lexer::token::Keywords;
+use crate::interner::Interner;
+use super::super::parser::arena::{Arena, ExprId};
+use super::super::parser::processor::{Expresion, Program, Proceso, Robot};
+use super::super::semanticizer::signatures::{firma_elemental, Argumento};
+use super::code::{compilar_proceso, compilar_robot, CodigoProceso, Operacion};
+use super::optimizer;
+use super::runtime::{es_accion_con_salida, Llamada, RespuestaAccion, RobotRuntime, RuntimeError, Valor, TODOS};
+
+// Código que ejecuta un registro de activación
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rutina {
+    Proceso(String),
+    Robot(String),
+}
+
+impl Rutina {
+    pub fn nombre(&self) -> &str {
+        match self {
+            Rutina::Proceso(nombre) | Rutina::Robot(nombre) => nombre,
+        }
+    }
+}
+
+// Registro de activación de un proceso en ejecución
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Marco {
+    rutina: Rutina,
+    pc: usize,
+    variables: HashMap<String, Valor>,
+    contadores: Vec<i32>,
+    salidas: Vec<(String, String)>, // (parámetro ES, variable del llamador)
+    // Llamada del llamador, dentro de una expresión, que espera lo que
+    // devuelve este proceso
+    retorno: Option<ExprId>,
+    // Lo que devolvió cada llamada dentro de una expresión, para cuando se
+    // evalúa la operación que la usa
+    devueltos: Vec<(ExprId, Valor)>,
+}
+
+// Resultado de ejecutar un paso
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstadoPaso {
+    Avanzo,
+    Bloqueado,
+    Terminado,
+}
+
+// Estado de una ejecución en curso, avanzable de a un paso
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ejecucion {
+    marcos: Vec<Marco>,
+    resultado: HashMap<String, Valor>,
+}
+
+impl Ejecucion {
+    pub fn terminada(&self) -> bool {
+        self.marcos.is_empty()
+    }
+
+    // Variables visibles en el proceso actual, o las finales si ya terminó
+    pub fn variables(&self) -> &HashMap<String, Valor> {
+        match self.marcos.last() {
+            Some(marco) => &marco.variables,
+            None => &self.resultado,
+        }
+    }
+
+    pub fn rutina_actual(&self) -> Option<&Rutina> {
+        self.marcos.last().map(|marco| &marco.rutina)
+    }
+
+    pub fn proceso_actual(&self) -> Option<&str> {
+        self.rutina_actual().map(|rutina| rutina.nombre())
+    }
+
+    pub fn profundidad(&self) -> usize {
+        self.marcos.len()
+    }
+
+    // Rutinas de la pila de llamadas, desde la más externa
+    pub fn rutinas(&self) -> impl Iterator<Item = &Rutina> {
+        self.marcos.iter().map(|marco| &marco.rutina)
+    }
+}
+
+pub struct Interpreter {
+    // Expresiones y nombres del programa, referenciados desde las operaciones
+    arena: Arena,
+    simbolos: Interner,
+    procesos: HashMap<String, CodigoProceso>,
+    robots: HashMap<String, CodigoProceso>,
+    // Constantes del programa, visibles desde todas las rutinas
+    constantes: HashMap<String, Valor>,
+    keywords: Keywords,
+}
+
+impl Interpreter {
+    pub fn new(programa: &Program) -> Self {
+        let mut interprete = Self::con_rutinas(&programa.arena, &programa.simbolos, &programa.procesos, &programa.robots_definidos);
+        interprete.constantes = programa.constantes.iter()
+            .map(|constante| (constante.nombre.clone(), Valor::Numero(constante.valor)))
+            .collect();
+        interprete
+    }
+
+    pub fn con_procesos(arena: &Arena, simbolos: &Interner, procesos: &[Proceso]) -> Self {
+        Self::con_rutinas(arena, simbolos, procesos, &[])
+    }
+
+    fn con_rutinas(arena: &Arena, simbolos: &Interner, procesos: &[Proceso], robots: &[Robot]) -> Self {
+        let nombres: HashSet<&str> = procesos.iter().map(|p| p.nombre.as_str()).collect();
+        let procesos = procesos
+            .iter()
+            .map(|p| (p.nombre.clone(), compilar_proceso(arena, simbolos, p, &nombres)))
+            .collect();
+        let robots = robots
+            .iter()
+            .map(|r| (r.nombre.clone(), compilar_robot(arena, simbolos, r, &nombres)))
+            .collect();
+
+        Self {
+            arena: arena.clone(),
+            simbolos: simbolos.clone(),
+            procesos,
+            robots,
+            constantes: HashMap::new(),
+            // Lo que el programa pudo usar depende de cómo se compiló
+            keywords: Keywords::new().con_extensiones(),
+        }
+    }
+
+    // Expande en cada lugar de llamada los procesos no recursivos de hasta
+    // `umbral` operaciones (ver `optimizer::expandir_procesos`) y devuelve
+    // cuántas llamadas se expandieron. Cambia las posiciones de las
+    // operaciones, así que tiene que aplicarse antes de iniciar ejecuciones.
+    pub fn expandir_procesos(&mut self, umbral: usize) -> usize {
+        optimizer::expandir_procesos(&mut self.arena, &mut self.simbolos, &mut self.procesos, &mut self.robots, umbral)
+    }
+
+    pub fn proceso(&self, nombre: &str) -> Option<&CodigoProceso> {
+        self.procesos.get(nombre)
+    }
+
+    pub fn robot(&self, nombre: &str) -> Option<&CodigoProceso> {
+        self.robots.get(nombre)
+    }
+
+    fn codigo(&self, rutina: &Rutina) -> Result<&CodigoProceso, RuntimeError> {
+        match rutina {
+            Rutina::Proceso(nombre) => self.procesos.get(nombre),
+            Rutina::Robot(nombre) => self.robots.get(nombre),
+        }
+        .ok_or_else(|| RuntimeError::UnknownProcess(rutina.nombre().to_string()))
+    }
+
+    // Ejecuta un proceso completo sobre el robot dado y devuelve sus variables
+    // finales (parámetros y variables locales)
+    pub fn ejecutar_proceso(
+        &self,
+        nombre: &str,
+        argumentos: &[Valor],
+        robot: &mut dyn RobotRuntime,
+    ) -> Result<HashMap<String, Valor>, RuntimeError> {
+        let mut ejecucion = self.iniciar_proceso(nombre, argumentos)?;
+        while !ejecucion.terminada() {
+            // Un único robot bloqueado nunca podrá ser despertado
+            if self.paso(&mut ejecucion, robot)? == EstadoPaso::Bloqueado {
+                return Err(RuntimeError::Deadlock);
+            }
+        }
+        Ok(ejecucion.resultado)
+    }
+
+    pub fn iniciar_proceso(&self, nombre: &str, argumentos: &[Valor]) -> Result<Ejecucion, RuntimeError> {
+        let marco = self.crear_marco(Rutina::Proceso(nombre.to_string()), argumentos)?;
+        Ok(Ejecucion {
+            marcos: vec![marco],
+            resultado: HashMap::new(),
+        })
+    }
+
+    // Prepara la ejecución del cuerpo de un tipo de robot
+    pub fn iniciar_robot(&self, tipo: &str) -> Result<Ejecucion, RuntimeError> {
+        let marco = self.crear_marco(Rutina::Robot(tipo.to_string()), &[])?;
+        Ok(Ejecucion {
+            marcos: vec![marco],
+            resultado: HashMap::new(),
+        })
+    }
+
+    // Próxima operación a ejecutar y su línea en el código fuente. Los saltos
+    // incondicionales se siguen para ubicar la instrucción que realmente sigue.
+    pub fn proxima_operacion(&self, ejecucion: &Ejecucion) -> Option<(&Operacion, usize)> {
+        let marco = ejecucion.marcos.last()?;
+        let codigo = self.codigo(&marco.rutina).ok()?;
+
+        let mut pc = marco.pc;
+        // Todo salto lleva a una operación condicional, pero se acota por las dudas
+        for _ in 0..=codigo.operaciones.len() {
+            match codigo.operaciones.get(pc)? {
+                Operacion::Saltar { destino } => pc = *destino,
+                operacion => return Some((operacion, codigo.linea(pc)?)),
+            }
+        }
+        None
+    }
+
+    // Operación que ejecuta el próximo paso, sin seguir los saltos, y su
+    // línea. `None` si el próximo paso termina el proceso actual.
+    pub fn operacion_actual(&self, ejecucion: &Ejecucion) -> Option<(&Operacion, usize)> {
+        let marco = ejecucion.marcos.last()?;
+        let codigo = self.codigo(&marco.rutina).ok()?;
+        Some((codigo.operaciones.get(marco.pc)?, codigo.linea(marco.pc)?))
+    }
+
+    pub fn linea_actual(&self, ejecucion: &Ejecucion) -> Option<usize> {
+        self.proxima_operacion(ejecucion).map(|(_, linea)| linea)
+    }
+
+    // Pila de llamadas desde la rutina más externa. Como cada paso avanza el
+    // contador antes de ejecutar la operación, después de un paso que falló
+    // la última llamada queda en la línea de la operación que dio el error.
+    pub fn pila_llamadas(&self, ejecucion: &Ejecucion) -> Vec<Llamada> {
+        ejecucion.marcos.iter()
+            .map(|marco| Llamada {
+                rutina: marco.rutina.clone(),
+                linea: self.codigo(&marco.rutina).ok()
+                    .zip(marco.pc.checked_sub(1))
+                    .and_then(|(codigo, pc)| codigo.linea(pc)),
+            })
+            .collect()
+    }
+
+    // Ejecuta una única operación de la ejecución
+    pub fn paso(&self, ejecucion: &mut Ejecucion, robot: &mut dyn RobotRuntime) -> Result<EstadoPaso, RuntimeError> {
+        let Some(marco) = ejecucion.marcos.last_mut() else {
+            return Ok(EstadoPaso::Terminado);
+        };
+
+        let codigo = self.codigo(&marco.rutina)?;
+
+        // Fin del proceso: devolver los parámetros ES y el valor del proceso
+        // al llamador
+        if marco.pc >= codigo.operaciones.len() {
+            if let Some(terminado) = ejecucion.marcos.pop() {
+                match ejecucion.marcos.last_mut() {
+                    Some(llamador) => {
+                        for (parametro, destino) in &terminado.salidas {
+                            if let Some(valor) = terminado.variables.get(parametro) {
+                                llamador.variables.insert(destino.clone(), valor.clone());
+                            }
+                        }
+                        if let Some(llamada) = terminado.retorno
+                            && let Some(valor) = terminado.variables.get(terminado.rutina.nombre())
+                        {
+                            llamador.devueltos.retain(|(devuelta, _)| *devuelta != llamada);
+                            llamador.devueltos.push((llamada, valor.clone()));
+                        }
+                    }
+                    None => ejecucion.resultado = terminado.variables,
+                }
+            }
+            return Ok(if ejecucion.terminada() { EstadoPaso::Terminado } else { EstadoPaso::Avanzo });
+        }
+
+        let operacion = &codigo.operaciones[marco.pc];
+        marco.pc += 1;
+
+        match operacion {
+            Operacion::Accion { nombre, argumentos } => {
+                if !self.keywords.is_elemental_instruction(nombre) {
+                    return Err(RuntimeError::UnknownProcess(nombre.clone()));
+                }
+
+                // El primer argumento de RecibirMensaje/Random/Leer es la variable destino
+                let (destino, entradas) = if es_accion_con_salida(nombre) {
+                    match argumentos.split_first().map(|(primero, resto)| (&self.arena[*primero], resto)) {
+                        Some((Expresion::Identificador(variable), resto)) => {
+                            (Some(self.simbolos.resolver(*variable)), resto)
+                        }
+                        _ => return Err(RuntimeError::UnknownInstruction(nombre.clone())),
+                    }
+                } else {
+                    (None, &argumentos[..])
+                };
+
+                let desplazamiento = argumentos.len() - entradas.len();
+                let valores = entradas
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &argumento)| {
+                        self.evaluar_argumento(nombre, desplazamiento + i, argumento, marco, robot)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                match robot.accion(nombre, &valores)? {
+                    RespuestaAccion::Hecha => {}
+                    RespuestaAccion::Valor(valor) => {
+                        if let Some(variable) = destino {
+                            if let (Some(Valor::Nombre(emisor)), Some(actual)) = (valores.first(), marco.variables.get(variable))
+                                && matches!(nombre.as_str(), "RecibirMensaje" | "RecibirMensajeTimeout")
+                                && actual.tipo() != valor.tipo()
+                            {
+                                return Err(RuntimeError::MessageTypeMismatch {
+                                    emisor: emisor.clone(),
+                                    variable: variable.to_string(),
+                                    esperado: actual.tipo().to_string(),
+                                    encontrado: valor.tipo().to_string(),
+                                });
+                            }
+                            asignar(&mut marco.variables, variable, valor)?;
+                        }
+                    }
+                    RespuestaAccion::Bloqueada => {
+                        marco.pc -= 1;
+                        return Ok(EstadoPaso::Bloqueado);
+                    }
+                    RespuestaAccion::Esperando => marco.pc -= 1,
+                }
+            }
+            Operacion::Asignar { variable, valor } => {
+                let nuevo = self.evaluar(*valor, marco, robot)?;
+                asignar(&mut marco.variables, variable, nuevo)?;
+            }
+            Operacion::Copiar { asignaciones } => {
+                for (variable, valor) in asignaciones {
+                    let nuevo = self.evaluar(*valor, marco, robot)?;
+                    asignar(&mut marco.variables, variable, nuevo)?;
+                }
+            }
+            Operacion::AsignarElemento { arreglo, indice, valor } => {
+                let indice = self.evaluar(*indice, marco, robot)?.como_numero()?;
+                let nuevo = self.evaluar(*valor, marco, robot)?;
+                let elementos = elementos(&mut marco.variables, arreglo)?;
+                let posicion = posicion(arreglo, indice, elementos.len())?;
+                reemplazar(&mut elementos[posicion], nuevo)?;
+            }
+            Operacion::Llamar { proceso, argumentos } => {
+                let nuevo = self.llamar(marco, proceso, argumentos, robot)?;
+                ejecucion.marcos.push(nuevo);
+            }
+            // El proceso devuelve lo que tiene en la variable con su nombre
+            Operacion::Calcular { llamada } => {
+                let Expresion::Llamada { nombre, argumentos } = &self.arena[*llamada] else {
+                    return Err(RuntimeError::UnknownInstruction(format!("{:?}", self.arena[*llamada])));
+                };
+                let proceso = self.simbolos.resolver(*nombre);
+                match self.procesos.get(proceso) {
+                    None => return Err(RuntimeError::UnknownProcess(proceso.to_string())),
+                    Some(codigo) if !codigo.variables.iter().any(|variable| variable.nombre == proceso) => {
+                        return Err(RuntimeError::NoReturnValue(proceso.to_string()));
+                    }
+                    Some(_) => {}
+                }
+                let mut nuevo = self.llamar(marco, proceso, argumentos, robot)?;
+                nuevo.retorno = Some(*llamada);
+                ejecucion.marcos.push(nuevo);
+            }
+            Operacion::Consultar { sensor } => {
+                robot.sensor(sensor)?;
+            }
+            Operacion::SaltarSiFalso { condicion, destino } => {
+                if !self.evaluar(*condicion, marco, robot)?.como_booleano()? {
+                    marco.pc = *destino;
+                }
+            }
+            Operacion::Saltar { destino } => {
+                marco.pc = *destino;
+            }
+            Operacion::IniciarContador { cantidad } => {
+                let cantidad = self.evaluar(*cantidad, marco, robot)?.como_numero()?;
+                marco.contadores.push(cantidad.max(0));
+            }
+            Operacion::DescontarOSaltar { destino } => match marco.contadores.last_mut() {
+                Some(restantes) if *restantes > 0 => *restantes -= 1,
+                _ => {
+                    marco.contadores.pop();
+                    marco.pc = *destino;
+                }
+            },
+        }
+
+        Ok(EstadoPaso::Avanzo)
+    }
+
+    // Registro de activación para llamar a `proceso` desde `marco`, con los
+    // parámetros ES y S ligados a las variables que los reciben
+    fn llamar(
+        &self,
+        marco: &Marco,
+        proceso: &str,
+        argumentos: &[ExprId],
+        robot: &mut dyn RobotRuntime,
+    ) -> Result<Marco, RuntimeError> {
+        let valores = argumentos
+            .iter()
+            .map(|&argumento| self.evaluar(argumento, marco, robot))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut nuevo = self.crear_marco(Rutina::Proceso(proceso.to_string()), &valores)?;
+
+        let destino = &self.procesos[proceso];
+        for (parametro, argumento) in destino.parametros.iter().zip(argumentos) {
+            if parametro.tipo == "E" {
+                continue;
+            }
+            match &self.arena[*argumento] {
+                Expresion::Identificador(variable)
+                    if marco.variables.contains_key(self.simbolos.resolver(*variable)) =>
+                {
+                    let variable = self.simbolos.resolver(*variable).to_string();
+                    nuevo.salidas.push((parametro.nombre.clone(), variable));
+                }
+                _ => {
+                    return Err(RuntimeError::InvalidOutParameter {
+                        proceso: proceso.to_string(),
+                        parametro: parametro.nombre.clone(),
+                    });
+                }
+            }
+        }
+        Ok(nuevo)
+    }
+
+    fn crear_marco(&self, rutina: Rutina, argumentos: &[Valor]) -> Result<Marco, RuntimeError> {
+        let codigo = self.codigo(&rutina)?;
+
+        if codigo.parametros.len() != argumentos.len() {
+            return Err(RuntimeError::ArityMismatch {
+                nombre: codigo.nombre.clone(),
+                esperados: codigo.parametros.len(),
+                recibidos: argumentos.len(),
+            });
+        }
+
+        let mut variables = HashMap::new();
+
+        for (parametro, valor) in codigo.parametros.iter().zip(argumentos) {
+            if parametro.tipo_dato != valor.tipo() {
+                return Err(RuntimeError::TypeMismatch {
+                    esperado: parametro.tipo_dato.clone(),
+                    encontrado: valor.tipo().to_string(),
+                });
+            }
+            variables.insert(parametro.nombre.clone(), valor.clone());
+        }
+
+        for variable in &codigo.variables {
+            let inicial = Valor::inicial(variable)
+                .ok_or_else(|| RuntimeError::UnknownType(variable.tipo_dato.clone()))?;
+            variables.insert(variable.nombre.clone(), inicial);
+        }
+
+        Ok(Marco {
+            rutina,
+            pc: 0,
+            variables,
+            contadores: Vec::new(),
+            salidas: Vec::new(),
+            retorno: None,
+            devueltos: Vec::new(),
+        })
+    }
+
+    // Los argumentos de instrucciones elementales pueden nombrar robots o
+    // áreas (EnviarMensaje(v, robot1)); esos nombres no son variables. En
+    // cualquier otra posición un nombre que no está en el marco ni es una
+    // constante es una variable no definida: el proceso no ve las variables
+    // de quien lo llama.
+    fn evaluar_argumento(
+        &self,
+        instruccion: &str,
+        indice: usize,
+        argumento: ExprId,
+        marco: &Marco,
+        robot: &mut dyn RobotRuntime,
+    ) -> Result<Valor, RuntimeError> {
+        let admite_nombre = matches!(
+            firma_elemental(instruccion).and_then(|firma| firma.argumento(indice)),
+            Some(Argumento::Robot | Argumento::RobotOTodos | Argumento::Area)
+        );
+        match &self.arena[argumento] {
+            Expresion::Identificador(nombre)
+                if admite_nombre && !marco.variables.contains_key(self.simbolos.resolver(*nombre)) =>
+            {
+                Ok(Valor::Nombre(self.simbolos.resolver(*nombre).to_string()))
+            }
+            _ => self.evaluar(argumento, marco, robot),
+        }
+    }
+
+    fn evaluar(
+        &self,
+        expresion: ExprId,
+        marco: &Marco,
+        robot: &mut dyn RobotRuntime,
+    ) -> Result<Valor, RuntimeError> {
+        match &self.arena[expresion] {
+            Expresion::Numero(n) => Ok(Valor::Numero(*n)),
+            Expresion::Booleano(b) => Ok(Valor::Booleano(*b)),
+            Expresion::Cadena(texto) => Ok(Valor::Cadena(texto.clone())),
+            Expresion::Todos => Ok(Valor::Nombre(TODOS.to_string())),
+            Expresion::Sensor { nombre } => robot.sensor(self.simbolos.resolver(*nombre)),
+            Expresion::Identificador(nombre) => {
+                let nombre = self.simbolos.resolver(*nombre);
+                marco.variables.get(nombre)
+                    .or_else(|| self.constantes.get(nombre))
+                    .cloned()
+                    .ok_or_else(|| RuntimeError::UndefinedVariable(nombre.to_string()))
+            }
+            Expresion::Binaria { izquierda, operador, derecha } => {
+                let izquierda = self.evaluar(*izquierda, marco, robot)?;
+                let derecha = self.evaluar(*derecha, marco, robot)?;
+                aplicar_operador(operador, izquierda, derecha)
+            }
+            Expresion::Negacion(operando) => Ok(Valor::Booleano(!self.evaluar(*operando, marco, robot)?.como_booleano()?)),
+            // La operación `Calcular` de la llamada ya se ejecutó
+            Expresion::Llamada { nombre, .. } => marco.devueltos.iter()
+                .find(|(llamada, _)| *llamada == expresion)
+                .map(|(_, valor)| valor.clone())
+                .ok_or_else(|| RuntimeError::NoReturnValue(self.simbolos.resolver(*nombre).to_string())),
+            Expresion::Indice { arreglo, indice } => {
+                let indice = self.evaluar(*indice, marco, robot)?.como_numero()?;
+                let arreglo = self.simbolos.resolver(*arreglo);
+                match marco.variables.get(arreglo) {
+                    Some(Valor::Arreglo(elementos)) => Ok(elementos[posicion(arreglo, indice, elementos.len())?].clone()),
+                    Some(otro) => Err(RuntimeError::TypeMismatch {
+                        esperado: "arreglo".to_string(),
+                        encontrado: otro.tipo().to_string(),
+                    }),
+                    None => Err(RuntimeError::UndefinedVariable(arreglo.to_string())),
+                }
+            }
+        }
+    }
+}
+
+// Elementos del arreglo guardado en la variable
+fn elementos<'a>(variables: &'a mut HashMap<String, Valor>, arreglo: &str) -> Result<&'a mut Vec<Valor>, RuntimeError> {
+    match variables.get_mut(arreglo) {
+        Some(Valor::Arreglo(elementos)) => Ok(elementos),
+        Some(otro) => Err(RuntimeError::TypeMismatch {
+            esperado: "arreglo".to_string(),
+            encontrado: otro.tipo().to_string(),
+        }),
+        None => Err(RuntimeError::UndefinedVariable(arreglo.to_string())),
+    }
+}
+
+// Posición en el vector del elemento `indice`, que cuenta desde 1
+fn posicion(arreglo: &str, indice: i32, longitud: usize) -> Result<usize, RuntimeError> {
+    usize::try_from(indice).ok()
+        .filter(|indice| (1..=longitud).contains(indice))
+        .map(|indice| indice - 1)
+        .ok_or_else(|| RuntimeError::IndexOutOfBounds { arreglo: arreglo.to_string(), indice, longitud })
+}
+
+fn asignar(variables: &mut HashMap<String, Valor>, variable: &str, nuevo: Valor) -> Result<(), RuntimeError> {
+    match variables.get_mut(variable) {
+        Some(actual) => reemplazar(actual, nuevo),
+        None => Err(RuntimeError::UndefinedVariable(variable.to_string())),
+    }
+}
+
+// Cambia el valor de una variable o de un elemento por otro del mismo tipo
+fn reemplazar(actual: &mut Valor, nuevo: Valor) -> Result<(), RuntimeError> {
+    if actual.tipo() != nuevo.tipo() {
+        return Err(RuntimeError::TypeMismatch {
+            esperado: actual.tipo().to_string(),
+            encontrado: nuevo.tipo().to_string(),
+        });
+    }
+    *actual = nuevo;
+    Ok(())
+}
+
+fn aplicar_operador(operador: &str, izquierda: Valor, derecha: Valor) -> Result<Valor, RuntimeError> {
+    match operador {
+        "+" | "-" | "*" | "/" => {
+            let a = izquierda.como_numero()?;
+            let b = derecha.como_numero()?;
+            let resultado = match operador {
+                "+" => a.checked_add(b),
+                "-" => a.checked_sub(b),
+                "*" => a.checked_mul(b),
+                _ => {
+                    if b == 0 {
+                        return Err(RuntimeError::DivisionByZero);
+                    }
+                    a.checked_div(b)
+                }
+            };
+            resultado.map(Valor::Numero).ok_or(RuntimeError::Overflow)
+        }
+        "<" | "<=" | ">" | ">=" => {
+            let a = izquierda.como_numero()?;
+            let b = derecha.como_numero()?;
+            Ok(Valor::Booleano(match operador {
+                "<" => a < b,
+                "<=" => a <= b,
+                ">" => a > b,
+                _ => a >= b,
+            }))
+        }
+        "==" | "<>" => {
+            if izquierda.tipo() != derecha.tipo() {
+                return Err(RuntimeError::TypeMismatch {
+                    esperado: izquierda.tipo().to_string(),
+                    encontrado: derecha.tipo().to_string(),
+                });
+            }
+            let iguales = izquierda == derecha;
+            Ok(Valor::Booleano(if operador == "==" { iguales } else { !iguales }))
+        }
+        "&" | "|" => {
+            let a = izquierda.como_booleano()?;
+            let b = derecha.como_booleano()?;
+            Ok(Valor::Booleano(if operador == "&" { a && b } else { a || b }))
+        }
+        _ => Err(RuntimeError::UnknownInstruction(operador.to_string())),
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::{HashMap, VecDeque};
+use super::runtime::{es_accion_con_salida, RespuestaAccion, RobotRuntime, RuntimeError, Valor};
+
+// Acción ejecutada por el intérprete sobre el robot simulado
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccionRegistrada {
+    pub nombre: String,
+    pub argumentos: Vec<Valor>,
+}
+
+// Robot simulado para probar procesos de forma aislada: los sensores
+// responden con valores programados y las acciones quedan registradas,
+// sin necesidad de construir una ciudad ni un programa completo.
+#[derive(Debug, Clone, Default)]
+pub struct MockRobot {
+    respuestas: HashMap<String, VecDeque<Valor>>,
+    fijos: HashMap<String, Valor>,
+    acciones: Vec<AccionRegistrada>,
+    lecturas: Vec<(String, Valor)>,
+}
+
+impl MockRobot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Programa una secuencia de respuestas para un sensor. Cada lectura consume
+    // la siguiente; al agotarse se usa el valor fijo si lo hay. También sirve
+    // para acciones que producen un valor (RecibirMensaje, Random).
+    pub fn con_sensor(mut self, nombre: &str, valores: impl IntoIterator<Item = Valor>) -> Self {
+        self.respuestas
+            .entry(nombre.to_string())
+            .or_default()
+            .extend(valores);
+        self
+    }
+
+    // Valor que el sensor devuelve siempre que no queden respuestas programadas
+    pub fn con_sensor_fijo(mut self, nombre: &str, valor: Valor) -> Self {
+        self.fijos.insert(nombre.to_string(), valor);
+        self
+    }
+
+    pub fn acciones(&self) -> &[AccionRegistrada] {
+        &self.acciones
+    }
+
+    pub fn nombres_acciones(&self) -> Vec<&str> {
+        self.acciones.iter().map(|a| a.nombre.as_str()).collect()
+    }
+
+    pub fn cantidad(&self, nombre: &str) -> usize {
+        self.acciones.iter().filter(|a| a.nombre == nombre).count()
+    }
+
+    pub fn lecturas(&self) -> &[(String, Valor)] {
+        &self.lecturas
+    }
+
+    pub fn respuestas_pendientes(&self, nombre: &str) -> usize {
+        self.respuestas.get(nombre).map_or(0, |cola| cola.len())
+    }
+}
+
+impl RobotRuntime for MockRobot {
+    fn accion(&mut self, nombre: &str, argumentos: &[Valor]) -> Result<RespuestaAccion, RuntimeError> {
+        self.acciones.push(AccionRegistrada {
+            nombre: nombre.to_string(),
+            argumentos: argumentos.to_vec(),
+        });
+
+        if es_accion_con_salida(nombre) {
+            return self.sensor(nombre).map(RespuestaAccion::Valor);
+        }
+        Ok(RespuestaAccion::Hecha)
+    }
+
+    fn sensor(&mut self, nombre: &str) -> Result<Valor, RuntimeError> {
+        let valor = self.respuestas
+            .get_mut(nombre)
+            .and_then(|cola| cola.pop_front())
+            .or_else(|| self.fijos.get(nombre).cloned())
+            .ok_or_else(|| RuntimeError::SensorNotScripted(nombre.to_string()))?;
+
+        self.lecturas.push((nombre.to_string(), valor.clone()));
+        Ok(valor)
+    }
+}
@@ -0,0 +1,16 @@
+pub mod runtime;
+pub mod code;
+pub mod machine;
+pub mod optimizer;
+pub mod perfil;
+pub mod livelock;
+pub mod contencion;
+pub mod planificador;
+pub mod exploracion;
+pub mod mock;
+pub mod world;
+pub mod simulation;pub mod debugger;
+
+pub mod evaluacion;
+pub mod reporte;
+pub mod diagrama;
\ No newline at end of file
@@ -0,0 +1,347 @@
+use std::collections::{HashMap, HashSet};
+use crate::interner::Interner;
+use super::super::parser::arena::{Arena, ExprId};
+use super::super::parser::processor::{Expresion, Variable};
+use super::code::{CodigoProceso, Operacion};
+use super::runtime::Valor;
+
+// Cantidad máxima de operaciones de un proceso para expandirlo por defecto
+pub const UMBRAL_EXPANSION: usize = 16;
+
+// Separa el nombre del proceso expandido del de sus variables en el código
+// del llamador (`sumar.total`). Ningún identificador del lenguaje lo
+// contiene, así que las variables expandidas no chocan con las del llamador.
+const SEPARADOR: char = '.';
+
+// Reemplaza cada llamada a un proceso no recursivo de hasta `umbral`
+// operaciones por una copia de su código. La copia asigna los argumentos a
+// los parámetros, reinicia las variables locales, ejecuta el cuerpo y
+// devuelve los parámetros de salida, igual que un registro de activación
+// pero sin crearlo. La entrada y la salida son una operación cada una, como
+// la llamada y el regreso, así que la simulación lleva los mismos pasos con o
+// sin expandir. Los procesos se expanden desde las hojas del grafo de
+// llamadas, así que una expansión ya incluye las de sus propias llamadas.
+// Las llamadas dentro de expresiones siempre crean su registro, y un proceso
+// que las tiene no se expande. Tampoco uno con arreglos, que no pueden
+// reiniciarse con una asignación.
+// Devuelve la cantidad de llamadas expandidas.
+pub fn expandir_procesos(
+    arena: &mut Arena,
+    simbolos: &mut Interner,
+    procesos: &mut HashMap<String, CodigoProceso>,
+    robots: &mut HashMap<String, CodigoProceso>,
+    umbral: usize,
+) -> usize {
+    let llamadas: HashMap<String, HashSet<String>> = procesos
+        .iter()
+        .map(|(nombre, codigo)| (nombre.clone(), procesos_llamados(codigo)))
+        .collect();
+
+    let mut orden = Vec::new();
+    let mut visitados = HashSet::new();
+    let mut nombres: Vec<&String> = llamadas.keys().collect();
+    nombres.sort();
+    for nombre in nombres {
+        postorden(nombre, &llamadas, &mut visitados, &mut orden);
+    }
+
+    let mut expansor = Expansor { arena, simbolos, expandibles: HashMap::new(), expandidas: 0 };
+    for nombre in orden {
+        let Some(codigo) = procesos.get_mut(&nombre) else {
+            continue;
+        };
+        expansor.expandir(codigo);
+        let calcula = codigo.operaciones.iter().any(|operacion| matches!(operacion, Operacion::Calcular { .. }));
+        if codigo.operaciones.len() <= umbral && !calcula && !es_recursivo(&nombre, &llamadas) {
+            expansor.expandibles.insert(nombre, codigo.clone());
+        }
+    }
+
+    let mut tipos: Vec<&mut CodigoProceso> = robots.values_mut().collect();
+    tipos.sort_by(|a, b| a.nombre.cmp(&b.nombre));
+    for codigo in tipos {
+        expansor.expandir(codigo);
+    }
+    expansor.expandidas
+}
+
+fn procesos_llamados(codigo: &CodigoProceso) -> HashSet<String> {
+    codigo.operaciones
+        .iter()
+        .filter_map(|operacion| match operacion {
+            Operacion::Llamar { proceso, .. } => Some(proceso.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+// Un proceso queda en el orden después de todos los que llama, salvo los que
+// forman un ciclo con él
+fn postorden(
+    nombre: &str,
+    llamadas: &HashMap<String, HashSet<String>>,
+    visitados: &mut HashSet<String>,
+    orden: &mut Vec<String>,
+) {
+    if !visitados.insert(nombre.to_string()) {
+        return;
+    }
+    let Some(llamados) = llamadas.get(nombre) else {
+        return;
+    };
+    let mut llamados: Vec<&String> = llamados.iter().collect();
+    llamados.sort();
+    for llamado in llamados {
+        postorden(llamado, llamadas, visitados, orden);
+    }
+    orden.push(nombre.to_string());
+}
+
+// Si el proceso puede volver a llamarse a sí mismo, directa o indirectamente
+fn es_recursivo(nombre: &str, llamadas: &HashMap<String, HashSet<String>>) -> bool {
+    let mut pendientes: Vec<&str> = vec![nombre];
+    let mut visitados = HashSet::new();
+    while let Some(actual) = pendientes.pop() {
+        for llamado in llamadas.get(actual).into_iter().flatten() {
+            if llamado == nombre {
+                return true;
+            }
+            if visitados.insert(llamado.as_str()) {
+                pendientes.push(llamado);
+            }
+        }
+    }
+    false
+}
+
+struct Expansor<'a> {
+    arena: &'a mut Arena,
+    simbolos: &'a mut Interner,
+    // Código final de los procesos que se pueden expandir
+    expandibles: HashMap<String, CodigoProceso>,
+    expandidas: usize,
+}
+
+impl Expansor<'_> {
+    fn expandir(&mut self, codigo: &mut CodigoProceso) {
+        let mut operaciones = Vec::new();
+        let mut lineas = Vec::new();
+        // Nueva posición de cada operación original, para corregir los saltos
+        let mut posiciones = Vec::with_capacity(codigo.operaciones.len() + 1);
+        let mut declaradas: Vec<Variable> = Vec::new();
+
+        for (operacion, &linea) in codigo.operaciones.iter().zip(&codigo.lineas) {
+            posiciones.push(operaciones.len());
+            let expansion = match operacion {
+                Operacion::Llamar { proceso, argumentos } => self.expansion(codigo, proceso, argumentos, linea),
+                _ => None,
+            };
+            match expansion {
+                Some((nuevas, nuevas_lineas, variables)) => {
+                    let base = operaciones.len();
+                    operaciones.extend(nuevas.into_iter().map(|mut nueva| {
+                        reubicar(&mut nueva, |destino| destino + base);
+                        nueva
+                    }));
+                    lineas.extend(nuevas_lineas);
+                    for variable in variables {
+                        if !declaradas.iter().any(|existente| existente.nombre == variable.nombre) {
+                            declaradas.push(variable);
+                        }
+                    }
+                    self.expandidas += 1;
+                }
+                None => {
+                    operaciones.push(operacion.clone());
+                    lineas.push(linea);
+                }
+            }
+        }
+        posiciones.push(operaciones.len());
+
+        // Las llamadas no tienen destino, así que sólo se corrigen los saltos
+        // propios del llamador y no los de las copias ya reubicadas
+        for (indice, operacion) in codigo.operaciones.iter().enumerate() {
+            if !matches!(operacion, Operacion::Llamar { .. }) {
+                reubicar(&mut operaciones[posiciones[indice]], |destino| posiciones[destino]);
+            }
+        }
+        codigo.operaciones = operaciones;
+        codigo.lineas = lineas;
+        codigo.variables.extend(declaradas);
+    }
+
+    // Operaciones que reemplazan la llamada, con sus líneas y las variables
+    // que el llamador tiene que declarar. `None` si la llamada no se puede
+    // expandir y tiene que quedar a cargo del intérprete, que también informa
+    // los errores (cantidad de argumentos, parámetros de salida inválidos).
+    // Los saltos de la copia son relativos al comienzo de la expansión.
+    fn expansion(
+        &mut self,
+        llamador: &CodigoProceso,
+        proceso: &str,
+        argumentos: &[ExprId],
+        linea: usize,
+    ) -> Option<(Vec<Operacion>, Vec<usize>, Vec<Variable>)> {
+        let llamado = self.expandibles.get(proceso)?.clone();
+        if llamado.parametros.len() != argumentos.len() {
+            return None;
+        }
+
+        let visibles: HashSet<&str> = llamador.parametros
+            .iter()
+            .map(|parametro| parametro.nombre.as_str())
+            .chain(llamador.variables.iter().map(|variable| variable.nombre.as_str()))
+            .collect();
+        let mut salidas = Vec::new();
+        for (parametro, argumento) in llamado.parametros.iter().zip(argumentos) {
+            if parametro.tipo == "E" {
+                continue;
+            }
+            match &self.arena[*argumento] {
+                Expresion::Identificador(variable) if visibles.contains(self.simbolos.resolver(*variable)) => {
+                    salidas.push((parametro.nombre.clone(), self.simbolos.resolver(*variable).to_string()));
+                }
+                _ => return None,
+            }
+        }
+
+        let prefijo = format!("{}{}", llamado.nombre, SEPARADOR);
+        let renombrar: HashSet<String> = llamado.parametros
+            .iter()
+            .map(|parametro| parametro.nombre.clone())
+            .chain(llamado.variables.iter().map(|variable| variable.nombre.clone()))
+            .collect();
+
+        let mut entrada = Vec::new();
+        let mut variables = Vec::new();
+
+        for (parametro, argumento) in llamado.parametros.iter().zip(argumentos) {
+            let nombre = format!("{}{}", prefijo, parametro.nombre);
+            entrada.push((nombre.clone(), *argumento));
+            variables.push(Variable { nombre, tipo_dato: parametro.tipo_dato.clone(), longitud: None, linea: parametro.linea });
+        }
+
+        for variable in &llamado.variables {
+            let nombre = format!("{}{}", prefijo, variable.nombre);
+            // Las variables que vienen de expansiones anteriores siempre se
+            // asignan antes de usarse, así que no hace falta reiniciarlas
+            if !variable.nombre.contains(SEPARADOR) {
+                let inicial = match Valor::inicial(variable)? {
+                    Valor::Numero(n) => Expresion::Numero(n),
+                    Valor::Booleano(b) => Expresion::Booleano(b),
+                    Valor::Cadena(texto) => Expresion::Cadena(texto),
+                    _ => return None,
+                };
+                let valor = self.arena.agregar_expresion(inicial, Default::default());
+                entrada.push((nombre.clone(), valor));
+            }
+            variables.push(Variable { nombre, ..variable.clone() });
+        }
+
+        let mut operaciones = vec![Operacion::Copiar { asignaciones: entrada }];
+        let mut lineas = vec![linea];
+        let inicio = operaciones.len();
+        for operacion in &llamado.operaciones {
+            let mut copia = self.renombrar(operacion, &prefijo, &renombrar);
+            reubicar(&mut copia, |destino| destino + inicio);
+            operaciones.push(copia);
+        }
+        lineas.extend(&llamado.lineas);
+
+        let salida = salidas.into_iter()
+            .map(|(parametro, destino)| {
+                let simbolo = self.simbolos.intern(&format!("{}{}", prefijo, parametro));
+                (destino, self.arena.agregar_expresion(Expresion::Identificador(simbolo), Default::default()))
+            })
+            .collect();
+        operaciones.push(Operacion::Copiar { asignaciones: salida });
+        lineas.push(linea);
+
+        Some((operaciones, lineas, variables))
+    }
+
+    fn renombrar(&mut self, operacion: &Operacion, prefijo: &str, nombres: &HashSet<String>) -> Operacion {
+        let variable = |variable: &String| {
+            if nombres.contains(variable) { format!("{}{}", prefijo, variable) } else { variable.clone() }
+        };
+        match operacion {
+            Operacion::Accion { nombre, argumentos } => Operacion::Accion {
+                nombre: nombre.clone(),
+                argumentos: argumentos.iter().map(|&a| self.renombrar_expresion(a, prefijo, nombres)).collect(),
+            },
+            Operacion::Asignar { variable: destino, valor } => Operacion::Asignar {
+                variable: variable(destino),
+                valor: self.renombrar_expresion(*valor, prefijo, nombres),
+            },
+            Operacion::Copiar { asignaciones } => Operacion::Copiar {
+                asignaciones: asignaciones.iter()
+                    .map(|(destino, valor)| (variable(destino), self.renombrar_expresion(*valor, prefijo, nombres)))
+                    .collect(),
+            },
+            Operacion::AsignarElemento { arreglo, indice, valor } => Operacion::AsignarElemento {
+                arreglo: variable(arreglo),
+                indice: self.renombrar_expresion(*indice, prefijo, nombres),
+                valor: self.renombrar_expresion(*valor, prefijo, nombres),
+            },
+            Operacion::Llamar { proceso, argumentos } => Operacion::Llamar {
+                proceso: proceso.clone(),
+                argumentos: argumentos.iter().map(|&a| self.renombrar_expresion(a, prefijo, nombres)).collect(),
+            },
+            Operacion::SaltarSiFalso { condicion, destino } => Operacion::SaltarSiFalso {
+                condicion: self.renombrar_expresion(*condicion, prefijo, nombres),
+                destino: *destino,
+            },
+            Operacion::IniciarContador { cantidad } => Operacion::IniciarContador {
+                cantidad: self.renombrar_expresion(*cantidad, prefijo, nombres),
+            },
+            Operacion::Consultar { .. }
+            | Operacion::Calcular { .. }
+            | Operacion::Saltar { .. }
+            | Operacion::DescontarOSaltar { .. } => {
+                operacion.clone()
+            }
+        }
+    }
+
+    // Copia la expresión con las variables del proceso renombradas. Las que
+    // no nombran ninguna (constantes, sensores, robots, áreas) se comparten.
+    fn renombrar_expresion(&mut self, expresion: ExprId, prefijo: &str, nombres: &HashSet<String>) -> ExprId {
+        let span = self.arena.span_expresion(expresion);
+        match self.arena[expresion].clone() {
+            Expresion::Identificador(simbolo) if nombres.contains(self.simbolos.resolver(simbolo)) => {
+                let nombre = format!("{}{}", prefijo, self.simbolos.resolver(simbolo));
+                let simbolo = self.simbolos.intern(&nombre);
+                self.arena.agregar_expresion(Expresion::Identificador(simbolo), span)
+            }
+            Expresion::Binaria { izquierda, operador, derecha } => {
+                let nueva_izquierda = self.renombrar_expresion(izquierda, prefijo, nombres);
+                let nueva_derecha = self.renombrar_expresion(derecha, prefijo, nombres);
+                if nueva_izquierda == izquierda && nueva_derecha == derecha {
+                    return expresion;
+                }
+                self.arena.agregar_expresion(
+                    Expresion::Binaria { izquierda: nueva_izquierda, operador, derecha: nueva_derecha },
+                    span,
+                )
+            }
+            Expresion::Negacion(operando) => {
+                let nuevo = self.renombrar_expresion(operando, prefijo, nombres);
+                if nuevo == operando {
+                    return expresion;
+                }
+                self.arena.agregar_expresion(Expresion::Negacion(nuevo), span)
+            }
+            _ => expresion,
+        }
+    }
+}
+
+fn reubicar(operacion: &mut Operacion, nuevo: impl Fn(usize) -> usize) {
+    match operacion {
+        Operacion::SaltarSiFalso { destino, .. }
+        | Operacion::Saltar { destino }
+        | Operacion::DescontarOSaltar { destino } => *destino = nuevo(*destino),
+        _ => {}
+    }
+}
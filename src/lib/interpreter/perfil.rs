@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+use super::code::Operacion;
+
+// Cuántas veces se ejecutó cada línea del programa y cada clase de operación
+// durante una simulación, para encontrar dónde se van los pasos
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Perfil {
+    lineas: BTreeMap<usize, u64>,
+    clases: BTreeMap<&'static str, u64>,
+    total: u64,
+}
+
+// Clase de los pasos que terminan un proceso y vuelven al llamador
+pub const RETORNO: &str = "retorno";
+
+impl Perfil {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Registra un paso con la operación que ejecutó y su línea. `None` si el
+    // paso terminó un proceso en lugar de ejecutar una operación.
+    pub fn registrar(&mut self, operacion: Option<(&Operacion, usize)>) {
+        let clase = match operacion {
+            Some((operacion, linea)) => {
+                *self.lineas.entry(linea).or_default() += 1;
+                operacion.clase()
+            }
+            None => RETORNO,
+        };
+        *self.clases.entry(clase).or_default() += 1;
+        self.total += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn ejecuciones_linea(&self, linea: usize) -> u64 {
+        self.lineas.get(&linea).copied().unwrap_or(0)
+    }
+
+    pub fn ejecuciones_clase(&self, clase: &str) -> u64 {
+        self.clases.get(clase).copied().unwrap_or(0)
+    }
+
+    // Las `cantidad` líneas con más ejecuciones, de la más ejecutada a la
+    // menos; las empatadas quedan en el orden del programa
+    pub fn lineas_mas_ejecutadas(&self, cantidad: usize) -> Vec<(usize, u64)> {
+        let mut lineas: Vec<(usize, u64)> = self.lineas.iter().map(|(&linea, &veces)| (linea, veces)).collect();
+        lineas.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        lineas.truncate(cantidad);
+        lineas
+    }
+
+    // Ejecuciones de cada clase de operación, de la más frecuente a la menos
+    pub fn clases(&self) -> Vec<(&'static str, u64)> {
+        let mut clases: Vec<(&'static str, u64)> = self.clases.iter().map(|(&clase, &veces)| (clase, veces)).collect();
+        clases.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        clases
+    }
+}
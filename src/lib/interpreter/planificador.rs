@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use crate::azar::Azar;
+use super::world::EstadoRobot;
+
+// Política que decide a qué robot le toca el próximo paso. En cada paso la
+// simulación pide un orden de candidatos y ejecuta el primero que no esté
+// terminado ni bloqueado, así que una política nunca puede trabar la ejecución.
+pub trait SchedulerPolicy {
+    // Completa `orden` con los índices de los robots a intentar. `turno` es el
+    // robot siguiente al último que avanzó, como en la política por turnos.
+    fn orden(&mut self, turno: usize, robots: &[EstadoRobot], orden: &mut Vec<usize>);
+}
+
+// Por turnos: cada robot avanza una operación y le pasa el turno al siguiente
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoundRobin;
+
+impl SchedulerPolicy for RoundRobin {
+    fn orden(&mut self, turno: usize, robots: &[EstadoRobot], orden: &mut Vec<usize>) {
+        let cantidad = robots.len();
+        orden.extend((0..cantidad).map(|desplazamiento| (turno + desplazamiento) % cantidad));
+    }
+}
+
+// Intercalado aleatorio reproducible: la misma semilla da siempre la misma
+// ejecución, así que una condición de carrera encontrada se puede repetir
+#[derive(Debug, Clone)]
+pub struct Aleatorio {
+    azar: Azar,
+}
+
+impl Aleatorio {
+    pub fn new(semilla: u64) -> Self {
+        Self { azar: Azar::new(semilla) }
+    }
+}
+
+impl SchedulerPolicy for Aleatorio {
+    fn orden(&mut self, _turno: usize, robots: &[EstadoRobot], orden: &mut Vec<usize>) {
+        orden.extend(0..robots.len());
+        // Fisher-Yates
+        for i in (1..orden.len()).rev() {
+            let j = self.azar.hasta(i + 1);
+            orden.swap(i, j);
+        }
+    }
+}
+
+// Prioridades por nombre de robot: siempre avanza el de mayor prioridad que
+// pueda hacerlo, y los de igual prioridad se alternan por turnos. Los robots
+// sin prioridad asignada tienen prioridad 0.
+#[derive(Debug, Clone, Default)]
+pub struct PorPrioridad {
+    prioridades: HashMap<String, i32>,
+}
+
+impl PorPrioridad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn con(mut self, robot: &str, prioridad: i32) -> Self {
+        self.prioridades.insert(robot.to_string(), prioridad);
+        self
+    }
+
+    pub fn prioridad(&self, robot: &str) -> i32 {
+        self.prioridades.get(robot).copied().unwrap_or(0)
+    }
+}
+
+impl SchedulerPolicy for PorPrioridad {
+    fn orden(&mut self, turno: usize, robots: &[EstadoRobot], orden: &mut Vec<usize>) {
+        RoundRobin.orden(turno, robots, orden);
+        // Orden estable: dentro de cada prioridad se conserva el de los turnos
+        orden.sort_by_key(|&indice| std::cmp::Reverse(self.prioridad(&robots[indice].nombre)));
+    }
+}
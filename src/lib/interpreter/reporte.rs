@@ -0,0 +1,205 @@
+use std::fmt::Write;
+use super::contencion::Contencion;
+use super::runtime::RuntimeError;
+use super::simulation::{EstadoSimulacion, Simulation};
+use super::world::{Direccion, Estadisticas};
+
+// Resumen de una simulación al terminar de ejecutarla: cómo terminó, qué
+// hizo cada robot y dónde quedó. Se escribe como JSON o como una página HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reporte {
+    pub pasos: u64,
+    // `None` si la ejecución terminó con un error
+    pub estado: Option<EstadoSimulacion>,
+    pub error: Option<String>,
+    pub robots: Vec<ReporteRobot>,
+    // Esquinas usadas por dos robots a la vez sin `BloquearEsquina`
+    pub contenciones: Vec<Contencion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReporteRobot {
+    pub nombre: String,
+    pub tipo: String,
+    pub av: i32,
+    pub ca: i32,
+    pub direccion: Direccion,
+    // Contenido final de la bolsa
+    pub flores: u32,
+    pub papeles: u32,
+    pub estadisticas: Estadisticas,
+    // Lo que mostró con `Informar`, en orden
+    pub informes: Vec<String>,
+}
+
+impl Reporte {
+    // `resultado` es lo que devolvió la última llamada a `ejecutar`
+    pub fn new(simulacion: &Simulation, resultado: &Result<EstadoSimulacion, RuntimeError>) -> Self {
+        let robots = simulacion.mundo().robots.iter()
+            .map(|robot| ReporteRobot {
+                nombre: robot.nombre.clone(),
+                tipo: robot.tipo.clone(),
+                av: robot.av,
+                ca: robot.ca,
+                direccion: robot.direccion,
+                flores: robot.flores,
+                papeles: robot.papeles,
+                estadisticas: robot.estadisticas.clone(),
+                informes: robot.informes.clone(),
+            })
+            .collect();
+        Self {
+            pasos: simulacion.pasos(),
+            estado: resultado.as_ref().ok().copied(),
+            error: resultado.as_ref().err().map(|error| error.to_string()),
+            robots,
+            contenciones: simulacion.contenciones().to_vec(),
+        }
+    }
+
+    pub fn a_json(&self) -> String {
+        let mut json = String::from("{\n");
+        let _ = writeln!(json, "  \"pasos\": {},", self.pasos);
+        let estado = self.estado.map_or("null".to_string(), |estado| cadena_json(&format!("{:?}", estado)));
+        let _ = writeln!(json, "  \"estado\": {},", estado);
+        let error = self.error.as_deref().map_or("null".to_string(), cadena_json);
+        let _ = writeln!(json, "  \"error\": {},", error);
+        json.push_str("  \"robots\": [");
+        for (i, robot) in self.robots.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let _ = write!(
+                json,
+                "    {{\"nombre\": {}, \"tipo\": {}, \"av\": {}, \"ca\": {}, \"direccion\": {}, \"flores\": {}, \"papeles\": {}",
+                cadena_json(&robot.nombre), cadena_json(&robot.tipo), robot.av, robot.ca,
+                cadena_json(&format!("{:?}", robot.direccion)), robot.flores, robot.papeles
+            );
+            for (campo, valor) in campos(&robot.estadisticas) {
+                let _ = write!(json, ", \"{}\": {}", campo, valor);
+            }
+            let informes: Vec<String> = robot.informes.iter().map(|informe| cadena_json(informe)).collect();
+            let _ = write!(json, ", \"informes\": [{}]}}", informes.join(", "));
+        }
+        json.push_str(if self.robots.is_empty() { "],\n" } else { "\n  ],\n" });
+        json.push_str("  \"contenciones\": [");
+        for (i, contencion) in self.contenciones.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let _ = write!(
+                json,
+                "    {{\"av\": {}, \"ca\": {}, \"robots\": [{}, {}], \"pasos\": [{}, {}]}}",
+                contencion.av, contencion.ca, cadena_json(&contencion.robots[0]), cadena_json(&contencion.robots[1]),
+                contencion.pasos[0], contencion.pasos[1]
+            );
+        }
+        json.push_str(if self.contenciones.is_empty() { "]\n}\n" } else { "\n  ]\n}\n" });
+        json
+    }
+
+    // Página autocontenida con una tabla de los robots
+    pub fn a_html(&self) -> String {
+        let mut html = String::from(
+"<!DOCTYPE html>
+<html lang=\"es\">
+<head>
+<meta charset=\"utf-8\">
+<title>Reporte de la simulación</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #999; padding: 0.3em 0.6em; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+.error { color: #b00; }
+</style>
+</head>
+<body>
+<h1>Reporte de la simulación</h1>
+");
+        let estado = match (&self.estado, &self.error) {
+            (Some(estado), _) => format!("{:?}", estado),
+            (None, Some(error)) => format!("<span class=\"error\">Error: {}</span>", escapar_html(error)),
+            (None, None) => String::new(),
+        };
+        let _ = writeln!(html, "<p>{} en {} pasos</p>", estado, self.pasos);
+
+        html.push_str("<table>\n<tr><th>Robot</th><th>Tipo</th><th>Posición</th><th>Dirección</th><th>Flores</th><th>Papeles</th>");
+        for (campo, _) in campos(&Estadisticas::default()) {
+            let _ = write!(html, "<th>{}</th>", campo.replace('_', " "));
+        }
+        html.push_str("<th>informes</th></tr>\n");
+        for robot in &self.robots {
+            let _ = write!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>({}, {})</td><td>{:?}</td><td>{}</td><td>{}</td>",
+                escapar_html(&robot.nombre), escapar_html(&robot.tipo), robot.av, robot.ca,
+                robot.direccion, robot.flores, robot.papeles
+            );
+            for (_, valor) in campos(&robot.estadisticas) {
+                let _ = write!(html, "<td>{}</td>", valor);
+            }
+            let _ = writeln!(html, "<td>{}</td></tr>", escapar_html(&robot.informes.join(" / ")));
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Esquinas compartidas sin bloquear</h2>\n");
+        if self.contenciones.is_empty() {
+            html.push_str("<p>Ningún par de robots usó la misma esquina a la vez sin <code>BloquearEsquina</code>.</p>\n");
+        } else {
+            html.push_str("<table>\n<tr><th>Esquina</th><th>Robots</th><th>Pasos</th></tr>\n");
+            for contencion in &self.contenciones {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>({}, {})</td><td>{}, {}</td><td>{}, {}</td></tr>",
+                    contencion.av, contencion.ca, escapar_html(&contencion.robots[0]),
+                    escapar_html(&contencion.robots[1]), contencion.pasos[0], contencion.pasos[1]
+                );
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+// Estadísticas con el nombre con que se escriben en el reporte
+fn campos(estadisticas: &Estadisticas) -> [(&'static str, u64); 13] {
+    [
+        ("pasos", estadisticas.pasos),
+        ("acciones", estadisticas.acciones),
+        ("recorrido", estadisticas.recorrido),
+        ("flores_tomadas", estadisticas.flores_tomadas),
+        ("flores_depositadas", estadisticas.flores_depositadas),
+        ("papeles_tomados", estadisticas.papeles_tomados),
+        ("papeles_depositados", estadisticas.papeles_depositados),
+        ("mensajes_enviados", estadisticas.mensajes_enviados),
+        ("mensajes_difundidos", estadisticas.mensajes_difundidos),
+        ("mensajes_recibidos", estadisticas.mensajes_recibidos),
+        ("difusiones_recibidas", estadisticas.difusiones_recibidas),
+        ("esperas_esquina", estadisticas.esperas_esquina),
+        ("esperas_mensaje", estadisticas.esperas_mensaje),
+    ]
+}
+
+pub(crate) fn cadena_json(texto: &str) -> String {
+    let mut json = String::with_capacity(texto.len() + 2);
+    json.push('"');
+    for c in texto.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            otro => json.push(otro),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn escapar_html(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
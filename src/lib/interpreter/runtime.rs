@@ -0,0 +1,220 @@
+use std::fmt;
+use crate::parser::processor::Variable;
+use super::machine::Rutina;
+
+// Valores que maneja el intérprete en tiempo de ejecución
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Valor {
+    Numero(i32),
+    Booleano(bool),
+    // Texto para Informar o enviar como mensaje
+    Cadena(String),
+    // Referencia por nombre a un robot o área, sólo como argumento de instrucciones
+    Nombre(String),
+    // Los elementos de una variable `arreglo[N] de tipo`, que se leen y
+    // asignan de a uno
+    Arreglo(Vec<Valor>),
+}
+
+impl Valor {
+    pub fn tipo(&self) -> &'static str {
+        match self {
+            Valor::Numero(_) => "numero",
+            Valor::Booleano(_) => "booleano",
+            Valor::Cadena(_) => "cadena",
+            Valor::Nombre(_) => "nombre",
+            Valor::Arreglo(_) => "arreglo",
+        }
+    }
+
+    pub fn como_numero(&self) -> Result<i32, RuntimeError> {
+        match self {
+            Valor::Numero(n) => Ok(*n),
+            otro => Err(RuntimeError::TypeMismatch {
+                esperado: "numero".to_string(),
+                encontrado: otro.tipo().to_string(),
+            }),
+        }
+    }
+
+    pub fn como_booleano(&self) -> Result<bool, RuntimeError> {
+        match self {
+            Valor::Booleano(b) => Ok(*b),
+            otro => Err(RuntimeError::TypeMismatch {
+                esperado: "booleano".to_string(),
+                encontrado: otro.tipo().to_string(),
+            }),
+        }
+    }
+
+    // Valor inicial de una variable según su tipo declarado
+    pub fn por_defecto(tipo_dato: &str) -> Option<Valor> {
+        match tipo_dato {
+            "numero" => Some(Valor::Numero(0)),
+            "booleano" => Some(Valor::Booleano(false)),
+            "cadena" => Some(Valor::Cadena(String::new())),
+            _ => None,
+        }
+    }
+
+    // Valor inicial de una variable declarada: el de su tipo o, si es un
+    // arreglo, un elemento con ese valor por cada posición
+    pub fn inicial(variable: &Variable) -> Option<Valor> {
+        let valor = Valor::por_defecto(&variable.tipo_dato)?;
+        Some(match variable.longitud {
+            Some(longitud) => Valor::Arreglo(vec![valor; longitud]),
+            None => valor,
+        })
+    }
+}
+
+impl fmt::Display for Valor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Valor::Numero(n) => write!(f, "{}", n),
+            Valor::Booleano(true) => write!(f, "V"),
+            Valor::Booleano(false) => write!(f, "F"),
+            Valor::Cadena(texto) => write!(f, "{}", texto),
+            Valor::Nombre(nombre) => write!(f, "{}", nombre),
+            Valor::Arreglo(elementos) => {
+                let elementos: Vec<String> = elementos.iter().map(Valor::to_string).collect();
+                write!(f, "[{}]", elementos.join(", "))
+            }
+        }
+    }
+}
+
+// Errores que pueden ocurrir durante la ejecución
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    UndefinedVariable(String),
+    UnknownProcess(String),
+    UnknownInstruction(String),
+    UnknownType(String),
+    ArityMismatch { nombre: String, esperados: usize, recibidos: usize },
+    TypeMismatch { esperado: String, encontrado: String },
+    // RecibirMensaje con un valor de otro tipo que la variable destino.
+    // `emisor` es el pedido, `*` si se aceptaba de cualquiera.
+    MessageTypeMismatch { emisor: String, variable: String, esperado: String, encontrado: String },
+    InvalidOutParameter { proceso: String, parametro: String },
+    // Llamada dentro de una expresión a un proceso que no devuelve un valor
+    NoReturnValue(String),
+    // Índice fuera de 1..=longitud
+    IndexOutOfBounds { arreglo: String, indice: i32, longitud: usize },
+    DivisionByZero,
+    Overflow,
+    SensorNotScripted(String),
+    Deadlock,
+    Robot(String),
+    // Error de un paso del robot `robot`, con la pila de llamadas en que
+    // ocurrió, desde el cuerpo del robot
+    EnLlamada { robot: String, pila: Vec<Llamada>, error: Box<RuntimeError> },
+}
+
+impl RuntimeError {
+    // El error sin la pila de llamadas en que ocurrió
+    pub fn causa(&self) -> &RuntimeError {
+        match self {
+            RuntimeError::EnLlamada { error, .. } => error.causa(),
+            otro => otro,
+        }
+    }
+}
+
+// Rutina de una pila de llamadas y línea de la última operación que empezó:
+// el lugar de la llamada en las rutinas que llamaron a otra
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Llamada {
+    pub rutina: Rutina,
+    pub linea: Option<usize>,
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeError::UndefinedVariable(nombre) => write!(f, "Variable '{}' no definida", nombre),
+            RuntimeError::UnknownProcess(nombre) => write!(f, "Proceso '{}' no definido", nombre),
+            RuntimeError::UnknownInstruction(nombre) => write!(f, "Instrucción desconocida: {}", nombre),
+            RuntimeError::UnknownType(tipo) => write!(f, "Tipo de dato desconocido: {}", tipo),
+            RuntimeError::ArityMismatch { nombre, esperados, recibidos } => write!(
+                f,
+                "'{}' espera {} argumento(s), recibió {}",
+                nombre, esperados, recibidos
+            ),
+            RuntimeError::TypeMismatch { esperado, encontrado } => write!(
+                f,
+                "Tipo incorrecto: esperado '{}', encontrado '{}'",
+                esperado, encontrado
+            ),
+            RuntimeError::MessageTypeMismatch { emisor, variable, esperado, encontrado } => write!(
+                f,
+                "Mensaje de '{}' de tipo '{}', pero la variable '{}' es de tipo '{}'",
+                emisor, encontrado, variable, esperado
+            ),
+            RuntimeError::InvalidOutParameter { proceso, parametro } => write!(
+                f,
+                "El parámetro ES '{}' del proceso '{}' debe recibir una variable",
+                parametro, proceso
+            ),
+            RuntimeError::NoReturnValue(nombre) => write!(f, "El proceso '{}' no devuelve un valor", nombre),
+            RuntimeError::IndexOutOfBounds { arreglo, indice, longitud } => write!(
+                f,
+                "El índice {} está fuera del arreglo '{}', que va de 1 a {}",
+                indice, arreglo, longitud
+            ),
+            RuntimeError::DivisionByZero => write!(f, "División por cero"),
+            RuntimeError::Overflow => write!(f, "Desbordamiento aritmético"),
+            RuntimeError::SensorNotScripted(nombre) => write!(f, "Sensor '{}' sin respuesta programada", nombre),
+            RuntimeError::Deadlock => write!(f, "Todos los robots están bloqueados esperando"),
+            RuntimeError::Robot(mensaje) => write!(f, "{}", mensaje),
+            RuntimeError::EnLlamada { robot, pila, error } => {
+                write!(f, "{}; en programa principal", error)?;
+                for llamada in pila {
+                    match &llamada.rutina {
+                        Rutina::Robot(_) => write!(f, " → robot {}", robot)?,
+                        Rutina::Proceso(nombre) => write!(f, " → proceso {}", nombre)?,
+                    }
+                    if let Some(linea) = llamada.linea {
+                        write!(f, " línea {}", linea)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+// Resultado de una acción sobre el robot
+#[derive(Debug, Clone, PartialEq)]
+pub enum RespuestaAccion {
+    Hecha,
+    // Valor producido por acciones como RecibirMensaje o Random, que el
+    // intérprete guarda en la variable pasada como primer argumento
+    Valor(Valor),
+    // La acción no puede completarse todavía (mensaje pendiente, esquina
+    // bloqueada); el intérprete la reintenta en el próximo paso
+    Bloqueada,
+    // Como `Bloqueada`, pero el turno cuenta como avance: la acción espera
+    // con un límite de turnos y no puede trabar la ejecución
+    Esperando,
+}
+
+// Interfaz que el intérprete usa para actuar sobre un robot.
+// Las acciones (mover, derecha, tomarFlor, Pos, Informar...) modifican el mundo;
+// los sensores (HayFlorEnLaEsquina, PosAv...) sólo lo consultan.
+pub trait RobotRuntime {
+    fn accion(&mut self, nombre: &str, argumentos: &[Valor]) -> Result<RespuestaAccion, RuntimeError>;
+    fn sensor(&mut self, nombre: &str) -> Result<Valor, RuntimeError>;
+}
+
+// Nombre con que llega `*` a EnviarMensaje (a todos los robots) y a
+// RecibirMensaje (de cualquiera); ningún robot puede llamarse así
+pub const TODOS: &str = "*";
+
+// Acciones cuyo primer argumento es la variable donde se guarda el resultado
+pub fn es_accion_con_salida(nombre: &str) -> bool {
+    matches!(nombre, "RecibirMensaje" | "RecibirMensajeTimeout" | "Random" | "Leer")
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use crate::interner::Symbol;
+use super::super::parser::arena::ExprId;
+use super::super::parser::processor::{valor_con_constantes, Expresion, Program};
+use super::code::Operacion;
+use super::contencion::{Contencion, DetectorContencion};
+use super::livelock::{AvisoLivelock, DetectorLivelock, Repeticion};
+use super::machine::{EstadoPaso, Ejecucion, Interpreter, Rutina};
+use super::perfil::Perfil;
+use super::planificador::{RoundRobin, SchedulerPolicy};
+use super::runtime::RuntimeError;
+use super::world::{AreaCiudad, Ciudad, EstadoRobot, Mundo, VistaRobot};
+use crate::traza;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EstadoSimulacion {
+    Ejecutando,
+    Terminada,
+    // Quedan robots sin terminar pero todos esperan un mensaje o una esquina
+    Bloqueada,
+}
+
+// Foto completa de una simulación: ciudad, robots (posición, bolsa, mensajes)
+// y el estado de ejecución de cada uno (pila de llamadas y variables)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulationState {
+    pub mundo: Mundo,
+    pub ejecuciones: Vec<Ejecucion>,
+    pub turno: usize,
+    pub pasos: u64,
+}
+
+// Ejecución concurrente de todos los robots instanciados en el programa.
+// Cada paso ejecuta una operación de un robot, elegido por el planificador
+// (por defecto, alternando por turnos).
+pub struct Simulation {
+    interprete: Interpreter,
+    mundo: Mundo,
+    ejecuciones: Vec<Ejecucion>,
+    turno: usize,
+    pasos: u64,
+    ultimo: Option<usize>,
+    perfil: Perfil,
+    livelock: DetectorLivelock,
+    contencion: DetectorContencion,
+    planificador: Box<dyn SchedulerPolicy>,
+    orden: Vec<usize>,
+}
+
+impl Simulation {
+    pub fn new(programa: &Program) -> Result<Self, RuntimeError> {
+        Self::con_ciudad(programa, Ciudad::new())
+    }
+
+    // Igual que `new` pero partiendo de una ciudad ya poblada con flores y papeles
+    pub fn con_ciudad(programa: &Program, ciudad: Ciudad) -> Result<Self, RuntimeError> {
+        Self::con_interprete(programa, ciudad, Interpreter::new(programa))
+    }
+
+    // Igual que `con_ciudad` pero ejecutando el código de un intérprete ya
+    // preparado, por ejemplo con los procesos chicos expandidos
+    pub fn con_interprete(programa: &Program, mut ciudad: Ciudad, interprete: Interpreter) -> Result<Self, RuntimeError> {
+
+        for area in &programa.areas {
+            let (av1, ca1, av2, ca2) = area.coordenadas;
+            ciudad.areas.push(AreaCiudad {
+                nombre: area.nombre.clone(),
+                tipo: area.tipo.clone(),
+                av_min: av1.min(av2),
+                ca_min: ca1.min(ca2),
+                av_max: av1.max(av2),
+                ca_max: ca1.max(ca2),
+            });
+        }
+
+        let mut mundo = Mundo::new(ciudad);
+        for instancia in &programa.robots_instanciados {
+            if interprete.robot(&instancia.tipo).is_none() {
+                return Err(RuntimeError::Robot(format!("Tipo de robot '{}' no definido", instancia.tipo)));
+            }
+            mundo.robots.push(EstadoRobot::new(&instancia.nombre, &instancia.tipo));
+        }
+
+        for asignacion in &programa.asignaciones_areas {
+            let robot = nombre_referenciado(programa, asignacion.robot)?;
+            let area = nombre_referenciado(programa, asignacion.area)?;
+            if mundo.ciudad.area(area).is_none() {
+                return Err(RuntimeError::Robot(format!("Área '{}' no definida", area)));
+            }
+            let indice = indice_robot(&mundo, robot)?;
+            mundo.robots[indice].areas.push(area.to_string());
+        }
+
+        let constantes = programa.valores_constantes();
+        for inicializacion in &programa.inicializaciones {
+            let robot = nombre_referenciado(programa, inicializacion.robot)?;
+            let indice = indice_robot(&mundo, robot)?;
+            let av = coordenada(programa, &constantes, inicializacion.pos_x)?;
+            let ca = coordenada(programa, &constantes, inicializacion.pos_y)?;
+            if !Ciudad::dentro(av, ca) {
+                return Err(RuntimeError::Robot(format!(
+                    "Robot '{}' iniciado fuera de la ciudad en ({}, {})",
+                    robot, av, ca
+                )));
+            }
+            let estado = &mut mundo.robots[indice];
+            estado.av = av;
+            estado.ca = ca;
+            estado.iniciado = true;
+        }
+
+        // Los robots que nunca se inician no ejecutan su cuerpo
+        let ejecuciones = mundo.robots
+            .iter()
+            .map(|robot| {
+                if robot.iniciado {
+                    interprete.iniciar_robot(&robot.tipo)
+                } else {
+                    Ok(Ejecucion::default())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            interprete,
+            ejecuciones,
+            turno: 0,
+            pasos: 0,
+            ultimo: None,
+            perfil: Perfil::new(),
+            livelock: DetectorLivelock::new(mundo.robots.len()),
+            contencion: DetectorContencion::new(),
+            planificador: Box::new(RoundRobin),
+            orden: Vec::new(),
+            mundo,
+        })
+    }
+
+    pub fn interprete(&self) -> &Interpreter {
+        &self.interprete
+    }
+
+    pub fn mundo(&self) -> &Mundo {
+        &self.mundo
+    }
+
+    pub fn ciudad_mut(&mut self) -> &mut Ciudad {
+        &mut self.mundo.ciudad
+    }
+
+    pub fn ejecucion(&self, robot: usize) -> Option<&Ejecucion> {
+        self.ejecuciones.get(robot)
+    }
+
+    pub fn pasos(&self) -> u64 {
+        self.pasos
+    }
+
+    // Ejecuciones de cada línea y clase de operación desde que empezó la
+    // simulación. No forma parte del estado, así que `restore` no lo cambia.
+    pub fn perfil(&self) -> &Perfil {
+        &self.perfil
+    }
+
+    // Robots que repitieron una espera o una condición muchas veces seguidas
+    // sin que nada cambiara, en el orden en que se detectaron
+    pub fn avisos_livelock(&self) -> &[AvisoLivelock] {
+        self.livelock.avisos()
+    }
+
+    // Cuántas repeticiones sin cambios hacen falta para un aviso de livelock
+    pub fn fijar_umbral_livelock(&mut self, umbral: u32) {
+        self.livelock.fijar_umbral(umbral);
+    }
+
+    // Veces que dos robots usaron la misma esquina en pasos cercanos sin
+    // bloquearla, en el orden en que ocurrieron
+    pub fn contenciones(&self) -> &[Contencion] {
+        self.contencion.contenciones()
+    }
+
+    // Cuántos pasos pueden separar dos accesos para considerarlos simultáneos
+    pub fn fijar_ventana_contencion(&mut self, ventana: u64) {
+        self.contencion.fijar_ventana(ventana);
+    }
+
+    // Cambia la política que elige qué robot avanza en cada paso. El estado
+    // interno de la política no forma parte de `snapshot`.
+    pub fn fijar_planificador(&mut self, planificador: Box<dyn SchedulerPolicy>) {
+        self.planificador = planificador;
+    }
+
+    // Índice del robot que ejecutó el último paso
+    pub fn ultimo_robot(&self) -> Option<usize> {
+        self.ultimo
+    }
+
+    pub fn terminada(&self) -> bool {
+        self.ejecuciones.iter().all(Ejecucion::terminada)
+    }
+
+    pub fn snapshot(&self) -> SimulationState {
+        SimulationState {
+            mundo: self.mundo.clone(),
+            ejecuciones: self.ejecuciones.clone(),
+            turno: self.turno,
+            pasos: self.pasos,
+        }
+    }
+
+    // Vuelve a un estado tomado con `snapshot`. El estado debe provenir del
+    // mismo programa: cada robot tiene que tener su ejecución y su tipo definido.
+    pub fn restore(&mut self, estado: SimulationState) -> Result<(), RuntimeError> {
+        if estado.mundo.robots.len() != estado.ejecuciones.len() {
+            return Err(RuntimeError::Robot(format!(
+                "Estado inconsistente: {} robots y {} ejecuciones",
+                estado.mundo.robots.len(),
+                estado.ejecuciones.len()
+            )));
+        }
+
+        for robot in &estado.mundo.robots {
+            if self.interprete.robot(&robot.tipo).is_none() {
+                return Err(RuntimeError::Robot(format!("Tipo de robot '{}' no definido", robot.tipo)));
+            }
+        }
+
+        for ejecucion in &estado.ejecuciones {
+            for rutina in ejecucion.rutinas() {
+                let definida = match rutina {
+                    Rutina::Proceso(nombre) => self.interprete.proceso(nombre).is_some(),
+                    Rutina::Robot(nombre) => self.interprete.robot(nombre).is_some(),
+                };
+                if !definida {
+                    return Err(RuntimeError::UnknownProcess(rutina.nombre().to_string()));
+                }
+            }
+        }
+
+        self.mundo = estado.mundo;
+        self.ejecuciones = estado.ejecuciones;
+        self.turno = if self.ejecuciones.is_empty() { 0 } else { estado.turno % self.ejecuciones.len() };
+        self.pasos = estado.pasos;
+        self.ultimo = None;
+        self.livelock.reiniciar();
+        self.contencion.reiniciar();
+        Ok(())
+    }
+
+    // Ejecuta una operación del primer robot, en el orden del planificador,
+    // que pueda avanzar
+    pub fn paso(&mut self) -> Result<EstadoSimulacion, RuntimeError> {
+        let mut orden = std::mem::take(&mut self.orden);
+        orden.clear();
+        self.planificador.orden(self.turno, &self.mundo.robots, &mut orden);
+        let resultado = self.paso_en_orden(&orden);
+        self.orden = orden;
+        resultado
+    }
+
+    fn paso_en_orden(&mut self, orden: &[usize]) -> Result<EstadoSimulacion, RuntimeError> {
+        for &indice in orden {
+            if self.paso_de(indice)? {
+                return Ok(self.estado());
+            }
+        }
+        Ok(self.estado_sin_avance())
+    }
+
+    // Intenta ejecutar una operación del robot indicado, sin consultar al
+    // planificador. Devuelve falso si el robot ya terminó o está bloqueado.
+    pub fn paso_de(&mut self, indice: usize) -> Result<bool, RuntimeError> {
+        let cantidad = self.ejecuciones.len();
+        if indice >= cantidad || self.ejecuciones[indice].terminada() {
+            return Ok(false);
+        }
+
+        let _robot = traza::span!(DEBUG, "robot", nombre = %self.mundo.robots[indice].nombre);
+        let operacion = self.interprete.operacion_actual(&self.ejecuciones[indice]);
+        let mut vista = VistaRobot { mundo: &mut self.mundo, indice, accesos: Vec::new() };
+        let estado = match self.interprete.paso(&mut self.ejecuciones[indice], &mut vista) {
+            Ok(estado) => estado,
+            Err(error) => {
+                return Err(RuntimeError::EnLlamada {
+                    robot: self.mundo.robots[indice].nombre.clone(),
+                    pila: self.interprete.pila_llamadas(&self.ejecuciones[indice]),
+                    error: Box::new(error),
+                });
+            }
+        };
+        let accesos = vista.accesos;
+        if !accesos.is_empty() {
+            let nombres: Vec<&str> = self.mundo.robots.iter().map(|robot| robot.nombre.as_str()).collect();
+            for acceso in &accesos {
+                self.contencion.registrar(indice, self.pasos + 1, acceso, &nombres);
+            }
+        }
+
+        let repeticion = match (operacion, estado) {
+            (Some((_, linea)), EstadoPaso::Bloqueado) => Some((linea, Repeticion::Espera)),
+            (Some((Operacion::SaltarSiFalso { .. }, linea)), _) => Some((linea, Repeticion::Condicion)),
+            (Some((Operacion::Accion { .. }, _)), _) => {
+                self.livelock.cambio_mundo();
+                None
+            }
+            (Some((Operacion::Asignar { .. }, _)), _) => {
+                self.livelock.asignacion(indice);
+                None
+            }
+            _ => None,
+        };
+        if let Some((linea, repeticion)) = repeticion {
+            self.livelock.registrar(indice, &self.mundo.robots[indice].nombre, linea, repeticion);
+        }
+
+        if estado == EstadoPaso::Bloqueado {
+            traza::evento!(TRACE, linea = operacion.map(|(_, linea)| linea), "bloqueado");
+            return Ok(false);
+        }
+        traza::evento!(TRACE, paso = self.pasos + 1, linea = operacion.map(|(_, linea)| linea), "paso");
+        if self.ejecuciones[indice].terminada() {
+            traza::evento!(DEBUG, paso = self.pasos + 1, "terminó");
+        }
+        self.perfil.registrar(operacion);
+        self.mundo.robots[indice].estadisticas.pasos += 1;
+        self.turno = (indice + 1) % cantidad;
+        self.pasos += 1;
+        self.ultimo = Some(indice);
+        Ok(true)
+    }
+
+    // Ejecuta hasta terminar o hasta agotar `max_pasos`
+    pub fn ejecutar(&mut self, max_pasos: u64) -> Result<EstadoSimulacion, RuntimeError> {
+        let _fase = traza::span!(INFO, "ejecutar", max_pasos);
+        let resultado = self.ejecutar_hasta(self.pasos.saturating_add(max_pasos));
+        traza::evento!(DEBUG, pasos = self.pasos, resultado = ?resultado, "simulación detenida");
+        resultado
+    }
+
+    fn ejecutar_hasta(&mut self, limite: u64) -> Result<EstadoSimulacion, RuntimeError> {
+        while self.pasos < limite {
+            match self.paso()? {
+                EstadoSimulacion::Ejecutando => {}
+                EstadoSimulacion::Bloqueada => return Err(RuntimeError::Deadlock),
+                EstadoSimulacion::Terminada => return Ok(EstadoSimulacion::Terminada),
+            }
+        }
+        Ok(self.estado())
+    }
+
+    pub fn estado(&self) -> EstadoSimulacion {
+        if self.terminada() {
+            EstadoSimulacion::Terminada
+        } else {
+            EstadoSimulacion::Ejecutando
+        }
+    }
+
+    fn estado_sin_avance(&self) -> EstadoSimulacion {
+        if self.terminada() {
+            EstadoSimulacion::Terminada
+        } else {
+            EstadoSimulacion::Bloqueada
+        }
+    }
+}
+
+fn nombre_referenciado(programa: &Program, expresion: ExprId) -> Result<&str, RuntimeError> {
+    match &programa.arena[expresion] {
+        Expresion::Identificador(nombre) => Ok(programa.simbolos.resolver(*nombre)),
+        otra => Err(RuntimeError::Robot(format!("Se esperaba un nombre, encontrado {:?}", otra))),
+    }
+}
+
+fn indice_robot(mundo: &Mundo, nombre: &str) -> Result<usize, RuntimeError> {
+    mundo.indice_robot(nombre)
+        .ok_or_else(|| RuntimeError::Robot(format!("Robot '{}' no declarado", nombre)))
+}
+
+// Las coordenadas de `Iniciar` son números o constantes del programa
+fn coordenada(programa: &Program, constantes: &HashMap<Symbol, i32>, expresion: ExprId) -> Result<i32, RuntimeError> {
+    let expresion = &programa.arena[expresion];
+    valor_con_constantes(&programa.arena, expresion, constantes)
+        .ok_or_else(|| RuntimeError::Robot(format!("Coordenada no constante: {:?}", expresion)))
+}
@@ -0,0 +1,614 @@
+use std::collections::VecDeque;
+use crate::azar::Azar;
+use crate::compilerError::CompilerError;
+use crate::mensajes::{Codigo, Mensaje};
+use super::runtime::{RespuestaAccion, RobotRuntime, RuntimeError, Valor, TODOS};
+
+// La ciudad tiene 100 avenidas por 100 calles, numeradas desde 1
+pub const TAMANIO_CIUDAD: i32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direccion {
+    Norte,
+    Este,
+    Sur,
+    Oeste,
+}
+
+impl Direccion {
+    pub fn derecha(self) -> Self {
+        match self {
+            Direccion::Norte => Direccion::Este,
+            Direccion::Este => Direccion::Sur,
+            Direccion::Sur => Direccion::Oeste,
+            Direccion::Oeste => Direccion::Norte,
+        }
+    }
+
+    // Desplazamiento (avenida, calle) de un paso en esta dirección
+    pub fn desplazamiento(self) -> (i32, i32) {
+        match self {
+            Direccion::Norte => (0, 1),
+            Direccion::Este => (1, 0),
+            Direccion::Sur => (0, -1),
+            Direccion::Oeste => (-1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Esquina {
+    pub flores: u32,
+    pub papeles: u32,
+    pub bloqueada_por: Option<usize>, // índice del robot que la bloqueó
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AreaCiudad {
+    pub nombre: String,
+    pub tipo: String, // "AreaC", "AreaP", "AreaPC"
+    pub av_min: i32,
+    pub ca_min: i32,
+    pub av_max: i32,
+    pub ca_max: i32,
+}
+
+impl AreaCiudad {
+    pub fn contiene(&self, av: i32, ca: i32) -> bool {
+        av >= self.av_min && av <= self.av_max && ca >= self.ca_min && ca <= self.ca_max
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ciudad {
+    esquinas: Vec<Esquina>,
+    pub areas: Vec<AreaCiudad>,
+}
+
+impl Ciudad {
+    pub fn new() -> Self {
+        Self {
+            esquinas: vec![Esquina::default(); (TAMANIO_CIUDAD * TAMANIO_CIUDAD) as usize],
+            areas: Vec::new(),
+        }
+    }
+
+    pub fn dentro(av: i32, ca: i32) -> bool {
+        (1..=TAMANIO_CIUDAD).contains(&av) && (1..=TAMANIO_CIUDAD).contains(&ca)
+    }
+
+    fn indice(av: i32, ca: i32) -> Option<usize> {
+        if Self::dentro(av, ca) {
+            Some(((av - 1) * TAMANIO_CIUDAD + (ca - 1)) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn esquina(&self, av: i32, ca: i32) -> Option<&Esquina> {
+        Self::indice(av, ca).map(|i| &self.esquinas[i])
+    }
+
+    pub fn esquina_mut(&mut self, av: i32, ca: i32) -> Option<&mut Esquina> {
+        Self::indice(av, ca).map(move |i| &mut self.esquinas[i])
+    }
+
+    pub fn poner_flores(&mut self, av: i32, ca: i32, cantidad: u32) {
+        if let Some(esquina) = self.esquina_mut(av, ca) {
+            esquina.flores += cantidad;
+        }
+    }
+
+    pub fn poner_papeles(&mut self, av: i32, ca: i32, cantidad: u32) {
+        if let Some(esquina) = self.esquina_mut(av, ca) {
+            esquina.papeles += cantidad;
+        }
+    }
+
+    // Lee el contenido inicial de la ciudad desde un archivo de mundo, con
+    // una esquina por línea:
+    //   flores <av> <ca> <cantidad>
+    //   papeles <av> <ca> <cantidad>
+    // Las líneas vacías y los comentarios `{ ... }` se ignoran.
+    pub fn desde_texto(texto: &str) -> Result<Self, CompilerError> {
+        let mut ciudad = Ciudad::new();
+
+        for (i, linea) in texto.lines().enumerate() {
+            let linea = linea.trim();
+            if linea.is_empty() || linea.starts_with('{') {
+                continue;
+            }
+
+            let error = |codigo: Codigo| CompilerError::new(Mensaje::new(codigo).con(linea), i + 1, 0);
+            let partes: Vec<&str> = linea.split_whitespace().collect();
+            let [objeto, av, ca, cantidad] = partes[..] else {
+                return Err(error(Codigo::FormatoDeMundo));
+            };
+            let (Ok(av), Ok(ca), Ok(cantidad)) = (av.parse(), ca.parse(), cantidad.parse()) else {
+                return Err(error(Codigo::NumeroInvalido));
+            };
+            if !Self::dentro(av, ca) {
+                return Err(error(Codigo::EsquinaFueraDeCiudad));
+            }
+
+            match objeto {
+                "flores" => ciudad.poner_flores(av, ca, cantidad),
+                "papeles" => ciudad.poner_papeles(av, ca, cantidad),
+                _ => return Err(error(Codigo::ObjetoDesconocido)),
+            }
+        }
+
+        Ok(ciudad)
+    }
+
+    pub fn area(&self, nombre: &str) -> Option<&AreaCiudad> {
+        self.areas.iter().find(|area| area.nombre == nombre)
+    }
+}
+
+impl Default for Ciudad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EstadoRobot {
+    pub nombre: String,
+    pub tipo: String,
+    pub av: i32,
+    pub ca: i32,
+    pub direccion: Direccion,
+    pub flores: u32,
+    pub papeles: u32,
+    pub areas: Vec<String>,
+    pub iniciado: bool,
+    pub mensajes: VecDeque<MensajePendiente>,
+    // Turnos que lleva esperando en el RecibirMensajeTimeout actual, y si
+    // el último recibió un mensaje antes del límite
+    pub espera: u32,
+    pub recibio_mensaje: bool,
+    pub informes: Vec<String>,
+    pub estadisticas: Estadisticas,
+}
+
+// Mensaje que llegó a un robot y todavía no recibió
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MensajePendiente {
+    pub emisor: String,
+    pub valor: Valor,
+    // Enviado a todos con `EnviarMensaje(valor, *)`
+    pub difundido: bool,
+}
+
+// Lo que hizo un robot durante la simulación
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Estadisticas {
+    // Operaciones del intérprete que ejecutó
+    pub pasos: u64,
+    // Instrucciones elementales completadas
+    pub acciones: u64,
+    // Cuadras recorridas; `Pos` cuenta la distancia entre las dos esquinas
+    pub recorrido: u64,
+    pub flores_tomadas: u64,
+    pub flores_depositadas: u64,
+    pub papeles_tomados: u64,
+    pub papeles_depositados: u64,
+    // Los dirigidos a un robot y los enviados a todos con `*`, que cuentan
+    // una vez aunque lleguen a varios
+    pub mensajes_enviados: u64,
+    pub mensajes_difundidos: u64,
+    pub mensajes_recibidos: u64,
+    // De los recibidos, cuántos eran difusiones
+    pub difusiones_recibidas: u64,
+    // Turnos que esperó una esquina bloqueada por otro robot
+    pub esperas_esquina: u64,
+    // Turnos que esperó un mensaje que todavía no llegaba
+    pub esperas_mensaje: u64,
+}
+
+impl EstadoRobot {
+    pub fn new(nombre: &str, tipo: &str) -> Self {
+        Self {
+            nombre: nombre.to_string(),
+            tipo: tipo.to_string(),
+            av: 1,
+            ca: 1,
+            direccion: Direccion::Norte,
+            flores: 0,
+            papeles: 0,
+            areas: Vec::new(),
+            iniciado: false,
+            mensajes: VecDeque::new(),
+            espera: 0,
+            recibio_mensaje: false,
+            informes: Vec::new(),
+            estadisticas: Estadisticas::default(),
+        }
+    }
+}
+
+// Envío o recepción de un mensaje entre dos robots
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventoMensaje {
+    Enviado { emisor: String, destino: String, valor: Valor },
+    Recibido { receptor: String, emisor: String, valor: Valor },
+}
+
+// Lectura o cambio del contenido de una esquina por parte de un robot.
+// `protegido` indica si el robot la tenía bloqueada con `BloquearEsquina`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccesoEsquina {
+    pub av: i32,
+    pub ca: i32,
+    pub escritura: bool,
+    pub protegido: bool,
+}
+
+// Estado completo de la simulación: ciudad y robots
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mundo {
+    pub ciudad: Ciudad,
+    pub robots: Vec<EstadoRobot>,
+    pub semilla: Azar,
+    // Mensajes enviados y recibidos, en el orden en que ocurrieron
+    pub mensajes: Vec<EventoMensaje>,
+}
+
+impl Mundo {
+    pub fn new(ciudad: Ciudad) -> Self {
+        Self {
+            ciudad,
+            robots: Vec::new(),
+            semilla: Azar::new(1),
+            mensajes: Vec::new(),
+        }
+    }
+
+    pub fn robot(&self, nombre: &str) -> Option<&EstadoRobot> {
+        self.robots.iter().find(|robot| robot.nombre == nombre)
+    }
+
+    pub fn indice_robot(&self, nombre: &str) -> Option<usize> {
+        self.robots.iter().position(|robot| robot.nombre == nombre)
+    }
+
+    // Reproducible a partir de la semilla
+    fn aleatorio(&mut self, minimo: i32, maximo: i32) -> i32 {
+        self.semilla.entre(minimo as i64, maximo as i64) as i32
+    }
+}
+
+// Vista del mundo desde un robot: implementa las instrucciones elementales
+pub struct VistaRobot<'a> {
+    pub mundo: &'a mut Mundo,
+    pub indice: usize,
+    // Esquinas cuyo contenido leyó o cambió el robot durante el paso
+    pub accesos: Vec<AccesoEsquina>,
+}
+
+fn error_del_robot(robot: &EstadoRobot, mensaje: &str) -> RuntimeError {
+    RuntimeError::Robot(format!(
+        "{} (robot '{}' en ({}, {}))",
+        mensaje, robot.nombre, robot.av, robot.ca
+    ))
+}
+
+fn fuera_de_la_ciudad(robot: &EstadoRobot) -> RuntimeError {
+    error_del_robot(robot, &format!("Esquina ({}, {}) fuera de la ciudad", robot.av, robot.ca))
+}
+
+impl VistaRobot<'_> {
+    fn robot(&self) -> &EstadoRobot {
+        &self.mundo.robots[self.indice]
+    }
+
+    fn robot_mut(&mut self) -> &mut EstadoRobot {
+        &mut self.mundo.robots[self.indice]
+    }
+
+    fn error(&self, mensaje: &str) -> RuntimeError {
+        error_del_robot(self.robot(), mensaje)
+    }
+
+    // `Iniciar` y los movimientos no dejan a un robot fuera de la ciudad,
+    // pero si pasara es un error de ejecución y no un pánico
+    fn esquina_actual(&self) -> Result<&Esquina, RuntimeError> {
+        let robot = self.robot();
+        self.mundo.ciudad.esquina(robot.av, robot.ca).ok_or_else(|| fuera_de_la_ciudad(robot))
+    }
+
+    fn esquina_actual_mut(&mut self) -> Result<&mut Esquina, RuntimeError> {
+        let robot = &self.mundo.robots[self.indice];
+        self.mundo.ciudad.esquina_mut(robot.av, robot.ca).ok_or_else(|| fuera_de_la_ciudad(robot))
+    }
+
+    fn registrar_acceso(&mut self, escritura: bool) {
+        let (av, ca) = (self.robot().av, self.robot().ca);
+        let protegido = self.esquina_actual().is_ok_and(|esquina| esquina.bloqueada_por == Some(self.indice));
+        self.accesos.push(AccesoEsquina { av, ca, escritura, protegido });
+    }
+
+    // Comprueba que el robot pueda ocupar la esquina; devuelve Ok(false) si
+    // otro robot la tiene bloqueada y hay que esperar
+    fn puede_ocupar(&self, av: i32, ca: i32) -> Result<bool, RuntimeError> {
+        if !Ciudad::dentro(av, ca) {
+            return Err(self.error(&format!("No puede moverse a ({}, {}): fuera de la ciudad", av, ca)));
+        }
+
+        let robot = self.robot();
+        let en_area = robot.areas.is_empty() || robot.areas.iter().any(|nombre| {
+            self.mundo.ciudad.area(nombre).is_some_and(|area| area.contiene(av, ca))
+        });
+        if !en_area {
+            return Err(self.error(&format!("No puede moverse a ({}, {}): fuera de sus áreas", av, ca)));
+        }
+
+        let libre = self.mundo.ciudad
+            .esquina(av, ca)
+            .is_none_or(|esquina| esquina.bloqueada_por.is_none_or(|propietario| propietario == self.indice));
+        Ok(libre)
+    }
+
+    fn ir_a(&mut self, av: i32, ca: i32) -> Result<RespuestaAccion, RuntimeError> {
+        if !self.puede_ocupar(av, ca)? {
+            return Ok(RespuestaAccion::Bloqueada);
+        }
+        let robot = self.robot_mut();
+        robot.av = av;
+        robot.ca = ca;
+        Ok(RespuestaAccion::Hecha)
+    }
+
+    fn coordenadas(&self, nombre: &str, argumentos: &[Valor]) -> Result<(i32, i32), RuntimeError> {
+        match argumentos {
+            [av, ca] => Ok((av.como_numero()?, ca.como_numero()?)),
+            _ => Err(RuntimeError::ArityMismatch {
+                nombre: nombre.to_string(),
+                esperados: 2,
+                recibidos: argumentos.len(),
+            }),
+        }
+    }
+
+    fn ejecutar_accion(&mut self, nombre: &str, argumentos: &[Valor]) -> Result<RespuestaAccion, RuntimeError> {
+        match nombre {
+            "mover" => {
+                let robot = self.robot();
+                let (dav, dca) = robot.direccion.desplazamiento();
+                let (av, ca) = (robot.av + dav, robot.ca + dca);
+                self.ir_a(av, ca)
+            }
+            "derecha" => {
+                let robot = self.robot_mut();
+                robot.direccion = robot.direccion.derecha();
+                Ok(RespuestaAccion::Hecha)
+            }
+            "Pos" => {
+                let (av, ca) = self.coordenadas(nombre, argumentos)?;
+                self.ir_a(av, ca)
+            }
+            "tomarFlor" => {
+                if self.esquina_actual()?.flores == 0 {
+                    return Err(self.error("No hay flores en la esquina"));
+                }
+                self.esquina_actual_mut()?.flores -= 1;
+                self.robot_mut().flores += 1;
+                Ok(RespuestaAccion::Hecha)
+            }
+            "tomarPapel" => {
+                if self.esquina_actual()?.papeles == 0 {
+                    return Err(self.error("No hay papeles en la esquina"));
+                }
+                self.esquina_actual_mut()?.papeles -= 1;
+                self.robot_mut().papeles += 1;
+                Ok(RespuestaAccion::Hecha)
+            }
+            "depositarFlor" => {
+                if self.robot().flores == 0 {
+                    return Err(self.error("No hay flores en la bolsa"));
+                }
+                self.robot_mut().flores -= 1;
+                self.esquina_actual_mut()?.flores += 1;
+                Ok(RespuestaAccion::Hecha)
+            }
+            "depositarPapel" => {
+                if self.robot().papeles == 0 {
+                    return Err(self.error("No hay papeles en la bolsa"));
+                }
+                self.robot_mut().papeles -= 1;
+                self.esquina_actual_mut()?.papeles += 1;
+                Ok(RespuestaAccion::Hecha)
+            }
+            "Informar" => {
+                let texto = argumentos
+                    .iter()
+                    .map(|valor| valor.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.robot_mut().informes.push(texto);
+                Ok(RespuestaAccion::Hecha)
+            }
+            "EnviarMensaje" => {
+                let [valor, Valor::Nombre(destino)] = argumentos else {
+                    return Err(self.error("EnviarMensaje espera un valor y el nombre del robot destino"));
+                };
+                // `*` lo envía a cada uno de los demás robots
+                let difundido = destino == TODOS;
+                let destinos: Vec<usize> = if difundido {
+                    (0..self.mundo.robots.len()).filter(|&otro| otro != self.indice).collect()
+                } else {
+                    match self.mundo.indice_robot(destino) {
+                        Some(destino) => vec![destino],
+                        None => return Err(self.error(&format!("Robot destino '{}' inexistente", destino))),
+                    }
+                };
+                let emisor = self.robot().nombre.clone();
+                for destino in destinos {
+                    self.mundo.mensajes.push(EventoMensaje::Enviado {
+                        emisor: emisor.clone(),
+                        destino: self.mundo.robots[destino].nombre.clone(),
+                        valor: valor.clone(),
+                    });
+                    self.mundo.robots[destino].mensajes.push_back(MensajePendiente {
+                        emisor: emisor.clone(),
+                        valor: valor.clone(),
+                        difundido,
+                    });
+                }
+                Ok(RespuestaAccion::Hecha)
+            }
+            "RecibirMensaje" => {
+                let [Valor::Nombre(emisor)] = argumentos else {
+                    return Err(self.error("RecibirMensaje espera una variable y el nombre del robot emisor"));
+                };
+                match self.tomar_mensaje(emisor) {
+                    Some(valor) => Ok(RespuestaAccion::Valor(valor)),
+                    None => Ok(RespuestaAccion::Bloqueada),
+                }
+            }
+            // Extensión: deja de esperar después de `turnos` turnos sin el
+            // mensaje, y la variable conserva su valor
+            "RecibirMensajeTimeout" => {
+                let [Valor::Nombre(emisor), Valor::Numero(turnos)] = argumentos else {
+                    return Err(self.error("RecibirMensajeTimeout espera una variable, el nombre del robot emisor y una cantidad de turnos"));
+                };
+                let recibido = self.tomar_mensaje(emisor);
+                let robot = self.robot_mut();
+                match recibido {
+                    Some(valor) => {
+                        robot.espera = 0;
+                        robot.recibio_mensaje = true;
+                        Ok(RespuestaAccion::Valor(valor))
+                    }
+                    None if i64::from(robot.espera) >= i64::from(*turnos) => {
+                        robot.espera = 0;
+                        robot.recibio_mensaje = false;
+                        Ok(RespuestaAccion::Hecha)
+                    }
+                    None => {
+                        robot.espera += 1;
+                        Ok(RespuestaAccion::Esperando)
+                    }
+                }
+            }
+            "BloquearEsquina" => {
+                let (av, ca) = self.coordenadas(nombre, argumentos)?;
+                let indice = self.indice;
+                let Some(esquina) = self.mundo.ciudad.esquina_mut(av, ca) else {
+                    return Err(self.error(&format!("Esquina ({}, {}) fuera de la ciudad", av, ca)));
+                };
+                match esquina.bloqueada_por {
+                    Some(propietario) if propietario != indice => Ok(RespuestaAccion::Bloqueada),
+                    _ => {
+                        esquina.bloqueada_por = Some(indice);
+                        Ok(RespuestaAccion::Hecha)
+                    }
+                }
+            }
+            "LiberarEsquina" => {
+                let (av, ca) = self.coordenadas(nombre, argumentos)?;
+                let indice = self.indice;
+                match self.mundo.ciudad.esquina_mut(av, ca) {
+                    Some(esquina) if esquina.bloqueada_por == Some(indice) => {
+                        esquina.bloqueada_por = None;
+                        Ok(RespuestaAccion::Hecha)
+                    }
+                    _ => Err(self.error(&format!("La esquina ({}, {}) no está bloqueada por este robot", av, ca))),
+                }
+            }
+            "Random" => {
+                let (minimo, maximo) = self.coordenadas(nombre, argumentos)?;
+                Ok(RespuestaAccion::Valor(Valor::Numero(self.mundo.aleatorio(minimo, maximo))))
+            }
+            _ => Err(RuntimeError::UnknownInstruction(nombre.to_string())),
+        }
+    }
+
+    // Saca de la cola del robot el primer mensaje de `emisor` y lo registra.
+    // Con `*` se recibe el primero que llegó, sea de quien sea.
+    fn tomar_mensaje(&mut self, emisor: &str) -> Option<Valor> {
+        let mensajes = &mut self.robot_mut().mensajes;
+        let posicion = mensajes.iter().position(|mensaje| emisor == TODOS || mensaje.emisor == emisor)?;
+        let mensaje = mensajes.remove(posicion)?;
+        if mensaje.difundido {
+            self.robot_mut().estadisticas.difusiones_recibidas += 1;
+        }
+        let receptor = self.robot().nombre.clone();
+        self.mundo.mensajes.push(EventoMensaje::Recibido {
+            receptor,
+            emisor: mensaje.emisor,
+            valor: mensaje.valor.clone(),
+        });
+        Some(mensaje.valor)
+    }
+
+    // Suma a las estadísticas del robot la acción que acaba de intentar
+    fn contabilizar(&mut self, nombre: &str, argumentos: &[Valor], respuesta: &RespuestaAccion, desde: (i32, i32)) {
+        let robot = self.robot_mut();
+        let estadisticas = &mut robot.estadisticas;
+        if matches!(respuesta, RespuestaAccion::Bloqueada | RespuestaAccion::Esperando) {
+            match nombre {
+                "RecibirMensaje" | "RecibirMensajeTimeout" => estadisticas.esperas_mensaje += 1,
+                _ => estadisticas.esperas_esquina += 1,
+            }
+            return;
+        }
+
+        estadisticas.acciones += 1;
+        match nombre {
+            "mover" | "Pos" => {
+                estadisticas.recorrido += u64::from(robot.av.abs_diff(desde.0) + robot.ca.abs_diff(desde.1));
+            }
+            "tomarFlor" => estadisticas.flores_tomadas += 1,
+            "depositarFlor" => estadisticas.flores_depositadas += 1,
+            "tomarPapel" => estadisticas.papeles_tomados += 1,
+            "depositarPapel" => estadisticas.papeles_depositados += 1,
+            "EnviarMensaje" => match argumentos {
+                [_, Valor::Nombre(destino)] if destino == TODOS => estadisticas.mensajes_difundidos += 1,
+                _ => estadisticas.mensajes_enviados += 1,
+            },
+            "RecibirMensaje" => estadisticas.mensajes_recibidos += 1,
+            "RecibirMensajeTimeout" if matches!(respuesta, RespuestaAccion::Valor(_)) => estadisticas.mensajes_recibidos += 1,
+            _ => {}
+        }
+    }
+}
+
+impl RobotRuntime for VistaRobot<'_> {
+    fn accion(&mut self, nombre: &str, argumentos: &[Valor]) -> Result<RespuestaAccion, RuntimeError> {
+        let desde = (self.robot().av, self.robot().ca);
+        let respuesta = self.ejecutar_accion(nombre, argumentos)?;
+        self.contabilizar(nombre, argumentos, &respuesta, desde);
+        if matches!(nombre, "tomarFlor" | "tomarPapel" | "depositarFlor" | "depositarPapel") {
+            self.registrar_acceso(true);
+        }
+        Ok(respuesta)
+    }
+
+    fn sensor(&mut self, nombre: &str) -> Result<Valor, RuntimeError> {
+        if matches!(nombre, "HayFlorEnLaEsquina" | "HayPapelEnLaEsquina") {
+            self.registrar_acceso(false);
+        }
+        let robot = self.robot();
+        match nombre {
+            "PosAv" => Ok(Valor::Numero(robot.av)),
+            "PosCa" => Ok(Valor::Numero(robot.ca)),
+            "HayFlorEnLaBolsa" => Ok(Valor::Booleano(robot.flores > 0)),
+            "HayPapelEnLaBolsa" => Ok(Valor::Booleano(robot.papeles > 0)),
+            "HayFlorEnLaEsquina" => Ok(Valor::Booleano(self.esquina_actual()?.flores > 0)),
+            "HayPapelEnLaEsquina" => Ok(Valor::Booleano(self.esquina_actual()?.papeles > 0)),
+            "RecibioMensaje" => Ok(Valor::Booleano(robot.recibio_mensaje)),
+            _ => Err(RuntimeError::UnknownInstruction(nombre.to_string())),
+        }
+    }
+}
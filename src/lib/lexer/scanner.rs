@@ -1,60 +1,141 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use super::token::{Token, TokenType, Keywords};
-use crate::lib::compilerError::{CompilerError};
+use crate::compilerError::{CompilerError};
+use crate::mensajes::{Codigo, Mensaje};
+use crate::source::{FileId, Span};
+use crate::traza;
 
-pub struct Lexer<'a> {
-    source: &'a str,
-    chars: Vec<char>,
-    position: usize,
+// Cómo se mide y valida la indentación
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexerConfig {
+    // Columnas que ocupa un tabulador
+    pub tab_width: usize,
+    // Si es falso, un tabulador en la indentación es un error
+    pub allow_tabs: bool,
+    // Si es verdadero, mezclar tabuladores y espacios o volver a un nivel de
+    // indentación que no abrió ningún bloque es un error; si no, una
+    // advertencia y el lexer sigue
+    pub require_consistent: bool,
+    // Si es verdadero, `//` comenta hasta el fin de la línea; si no, son dos
+    // operadores de división
+    pub line_comments: bool,
+    // Si es verdadero, cada '{' dentro de un comentario necesita su propia
+    // '}'; si no, el comentario termina en la primera '}' y una '{' interna
+    // se advierte
+    pub nested_comments: bool,
+}
+
+impl Default for LexerConfig {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            allow_tabs: true,
+            require_consistent: false,
+            line_comments: true,
+            nested_comments: true,
+        }
+    }
+}
+
+// Trabaja sobre índices de bytes del código fuente: los tokens toman su valor
+// como un slice de `source`, sin copiar texto.
+//
+// El lexer es un iterador de tokens y errores: después de un error sigue
+// leyendo desde el carácter siguiente, así que puede informar varios errores
+// en una sola pasada. `tokenize` lo recorre completo y se detiene en el primero.
+pub struct Lexer<'src> {
+    source: &'src str,
+    position: usize, // en bytes
     line: usize,
     column: usize,
-    tokens: Vec<Token>,
+    // Tokens y errores listos para entregar; un solo carácter puede producir
+    // varios (por ejemplo, los DEDENT al bajar la indentación)
+    pendientes: VecDeque<Result<Token<'src>, CompilerError>>,
+    terminado: bool,
     indent_stack: Vec<usize>,
     at_line_start: bool,
     current_indent: usize,
     keywords: Keywords,
     paren_stack: Vec<(char, usize, usize)>, // (tipo de paréntesis, línea, columna)
+    // Archivo que se marca en cada token y error producido
+    archivo: FileId,
+    config: LexerConfig,
+    // Carácter de la primera línea indentada (' ' o '\t'), para detectar
+    // archivos que mezclan los dos
+    indent_char: Option<char>,
+    // Problemas que no impiden seguir, como una indentación irregular
+    advertencias: Vec<CompilerError>,
+    // Tramo de cada comentario leído, para quien resalta el código
+    comentarios: Vec<Span>,
 }
 
-impl<'a> Lexer<'a> {
-    pub fn new(source: &'a str) -> Self {
-        let chars: Vec<char> = source.chars().collect();
-        
+impl<'src> Lexer<'src> {
+    pub fn new(source: &'src str) -> Self {
         Self {
             source,
-            chars,
             position: 0,
             line: 1,
             column: 1,
-            tokens: Vec::new(),
+            pendientes: VecDeque::new(),
+            terminado: false,
             indent_stack: vec![0],
             at_line_start: true,
             current_indent: 0,
             keywords: Keywords::new(),
             paren_stack: Vec::new(),
+            archivo: FileId::PRINCIPAL,
+            config: LexerConfig::default(),
+            indent_char: None,
+            advertencias: Vec::new(),
+            comentarios: Vec::new(),
         }
     }
     
-    pub fn with_keywords(source: &'a str, keywords: Keywords) -> Self {
-        let chars: Vec<char> = source.chars().collect();
-        
+    pub fn with_keywords(source: &'src str, keywords: Keywords) -> Self {
         Self {
             source,
-            chars,
             position: 0,
             line: 1,
             column: 1,
-            tokens: Vec::new(),
+            pendientes: VecDeque::new(),
+            terminado: false,
             indent_stack: vec![0],
             at_line_start: true,
             current_indent: 0,
             keywords,
             paren_stack: Vec::new(),
+            archivo: FileId::PRINCIPAL,
+            config: LexerConfig::default(),
+            indent_char: None,
+            advertencias: Vec::new(),
+            comentarios: Vec::new(),
+        }
+    }
+    
+    pub fn tokenize(&mut self) -> Result<Vec<Token<'src>>, CompilerError> {
+        self.reset();
+        self.collect()
+    }
+    
+    // Recorre todo el código y devuelve los tokens junto con todos los errores
+    // encontrados, en lugar de detenerse en el primero
+    pub fn tokenize_all(&mut self) -> (Vec<Token<'src>>, Vec<CompilerError>) {
+        self.reset();
+        
+        let mut tokens = Vec::new();
+        let mut errores = Vec::new();
+        for resultado in self.by_ref() {
+            match resultado {
+                Ok(token) => tokens.push(token),
+                Err(error) => errores.push(error),
+            }
         }
+        (tokens, errores)
     }
     
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, CompilerError> {
-        self.tokens.clear();
+    fn reset(&mut self) {
+        self.pendientes.clear();
+        self.terminado = false;
         self.position = 0;
         self.line = 1;
         self.column = 1;
@@ -62,99 +143,151 @@ impl<'a> Lexer<'a> {
         self.indent_stack = vec![0];
         self.current_indent = 0;
         self.paren_stack.clear();
+        self.indent_char = None;
+        self.advertencias.clear();
+        self.comentarios.clear();
+    }
+    
+    fn push_token(&mut self, token: Token<'src>) {
+        self.pendientes.push_back(Ok(token));
+    }
+    
+    // Lee el próximo elemento del código (un token, un comentario o espacios)
+    fn scan_token(&mut self, char: char) -> Result<(), CompilerError> {
+        // Una línea sin sangría también cierra los bloques abiertos
+        if self.at_line_start && !matches!(char, '\n' | '\r' | '\u{FEFF}')
+            && !char.is_whitespace() && !es_espacio_no_estandar(char)
+        {
+            self.handle_indentation()?;
+        }
         
-        while self.position < self.chars.len() {
-            let char = self.chars[self.position];
+        match char {
+            // Comentarios
+            '{' => self.read_comment()?,
+            '/' if self.at_line_comment() => self.read_line_comment(),
             
-            match char {
-                // Comentarios
-                '{' => {
-                    self.read_comment()?;
-                    continue;
-                }
-                
-                // Paréntesis que abre
-                '(' => self.handle_open_parenthesis()?,
-                
-                // Paréntesis que cierra
-                ')' => self.handle_close_parenthesis()?,
-                
-                // Nueva línea
-                '\n' => {
-                    self.line += 1;
-                    self.column = 1;
-                    self.position += 1;
-                    self.at_line_start = true;
-                }
-                
-                // Espacios en blanco
-                c if c.is_whitespace() && c != '\n' => {
-                    if self.at_line_start {
-                        self.handle_indentation()?;
-                    } else {
-                        self.skip_whitespace_only();
-                    }
-                }
-                
-                // Dígitos
-                c if c.is_ascii_digit() => self.read_number()?,
-                
-                // Letras (identificadores)
-                c if c.is_alphabetic() || c == '_' => {self.read_identifier()?; self.at_line_start = false;},
-                
-                // Strings
-                '"' | '\'' => self.read_string(char)?,
-                
-                // Operadores
-                c if self.is_operator(c) || c == ',' || c == ':' => self.read_operator()?,
-                
-                // Carácter inesperado
-                _ => {
-                    return Err(CompilerError::new(
-                        format!("Carácter inesperado: < {} >", char),
-                        self.line,
-                        self.column
-                    ));
+            // Paréntesis o corchete que abre
+            '(' | '[' => self.handle_open_parenthesis(char)?,
+            
+            // Paréntesis o corchete que cierra
+            ')' | ']' => self.handle_close_parenthesis(char)?,
+            
+            // Nueva línea ('\n', '\r\n' o '\r' solo)
+            '\n' | '\r' => {
+                self.salto_de_linea(char);
+                self.at_line_start = true;
+            }
+            
+            // Marca de orden de bytes que agregan algunos editores al guardar
+            '\u{FEFF}' if self.position == 0 => {
+                self.position += char.len_utf8();
+            }
+            
+            // Espacios en blanco
+            c if c.is_whitespace() || es_espacio_no_estandar(c) => {
+                if self.at_line_start {
+                    self.handle_indentation()?;
+                } else {
+                    self.skip_whitespace_only();
                 }
             }
+            
+            // Dígitos
+            c if c.is_ascii_digit() => self.read_number()?,
+            
+            // Letras (identificadores)
+            c if c.is_alphabetic() || c == '_' => {self.read_identifier()?; self.at_line_start = false;},
+            
+            // Strings
+            '"' | '\'' => self.read_string(char)?,
+            
+            // Operadores
+            c if self.is_operator(c) || c == ',' || c == ':' => self.read_operator()?,
+            
+            // Carácter inesperado
+            _ => {
+                return Err(CompilerError::new(
+                    Mensaje::new(Codigo::CaracterInesperado).con(char),
+                    self.line,
+                    self.column
+                ));
+            }
+        }
+        Ok(())
+    }
+    
+    // Cierre del archivo: paréntesis sin cerrar, DEDENT finales y fin de archivo
+    fn finish(&mut self) {
+        // Informar cada paréntesis sin cerrar
+        for error in self.unclosed_parentheses_errors() {
+            self.pendientes.push_back(Err(error));
         }
         
-        // Verificar paréntesis sin cerrar al final del archivo
-        self.check_unclosed_parentheses()?;
-        
-        // Añadir tokens DEDENT finales
+        // Añadir tokens DEDENT finales, donde termina el código
         while self.indent_stack.len() > 1 {
-            self.tokens.push(Token::new(
+            self.push_token(Token::new(
                 TokenType::Dedent,
                 "",
                 self.line,
-                1
+                self.column
             ));
             self.indent_stack.pop();
         }
         
         // Añadir token de fin de archivo
-        self.tokens.push(Token::new(
+        self.push_token(Token::new(
             TokenType::EndFile,
             "",
             self.line,
             self.column
         ));
         
-        Ok(self.tokens.clone())
+        self.terminado = true;
+    }
+    
+    // Carácter en la posición actual
+    fn peek_char(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+    
+    // Avanza un carácter (que puede ocupar más de un byte) en la misma línea
+    fn advance_char(&mut self, c: char) {
+        self.position += c.len_utf8();
+        self.column += 1;
+    }
+    
+    // Consume un fin de línea: '\n', '\r\n' (archivos de Windows) o '\r' solo
+    fn salto_de_linea(&mut self, c: char) {
+        self.position += 1;
+        if c == '\r' && self.peek_char() == Some('\n') {
+            self.position += 1;
+        }
+        self.line += 1;
+        self.column = 1;
+    }
+    
+    // Espacio que no es ' ' ni '\t' (por ejemplo el espacio duro que se cuela
+    // al copiar código de una página web): se toma como un espacio común
+    fn espacio_no_estandar(&mut self, c: char) {
+        self.advertir(CompilerError::new(
+            Mensaje::new(Codigo::EspacioNoEstandar).con(format!("{:04X}", c as u32)),
+            self.line,
+            self.column
+        ));
+        self.advance_char(c);
     }
     
-    fn handle_open_parenthesis(&mut self) -> Result<(), CompilerError> {
+    fn handle_open_parenthesis(&mut self, apertura: char) -> Result<(), CompilerError> {
         let start_line = self.line;
         let start_column = self.column;
         
         // Añadir a la pila de paréntesis
-        self.paren_stack.push(('(', start_line, start_column));
+        self.paren_stack.push((apertura, start_line, start_column));
         
         // Crear token de paréntesis que abre
-        self.tokens.push(Token::new(
-            TokenType::OpenedParenthesis,
-            "(".to_string(),
+        self.push_token(Token::new(
+            if apertura == '[' { TokenType::OpenedBracket } else { TokenType::OpenedParenthesis },
+            &self.source[self.position..self.position + 1],
             start_line,
             start_column
         ));
@@ -166,24 +299,24 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
     
-    fn handle_close_parenthesis(&mut self) -> Result<(), CompilerError> {
+    fn handle_close_parenthesis(&mut self, cierre: char) -> Result<(), CompilerError> {
         let start_line = self.line;
         let start_column = self.column;
         
         // Verificar si hay paréntesis que abrir
-        if self.paren_stack.is_empty() {
+        let Some(last_paren) = self.paren_stack.last() else {
             return Err(CompilerError::new(
-                "Paréntesis de cierre sin apertura correspondiente".to_string(),
+                Codigo::CierreSinApertura,
                 start_line,
                 start_column
             ));
-        }
+        };
         
         // Verificar que el paréntesis que cierra corresponda al que abre
-        let last_paren = self.paren_stack.last().unwrap();
-        if last_paren.0 != '(' {
+        let apertura = if cierre == ']' { '[' } else { '(' };
+        if last_paren.0 != apertura {
             return Err(CompilerError::new(
-                "Paréntesis de cierre no corresponde con la apertura".to_string(),
+                Codigo::CierreNoCorresponde,
                 start_line,
                 start_column
             ));
@@ -193,9 +326,9 @@ impl<'a> Lexer<'a> {
         self.paren_stack.pop();
         
         // Crear token de paréntesis que cierra
-        self.tokens.push(Token::new(
-            TokenType::ClosedParenthesis,
-            ")".to_string(),
+        self.push_token(Token::new(
+            if cierre == ']' { TokenType::ClosedBracket } else { TokenType::ClosedParenthesis },
+            &self.source[self.position..self.position + 1],
             start_line,
             start_column
         ));
@@ -207,52 +340,98 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
     
-    fn check_unclosed_parentheses(&self) -> Result<(), CompilerError> {
-        for (paren_type, line, column) in &self.paren_stack {
+    // Tabuladores prohibidos, o tabuladores y espacios mezclados en la misma
+    // línea o entre líneas del archivo
+    fn check_indent_chars(&mut self, espacios: bool, tabs: bool) -> Result<(), CompilerError> {
+        if tabs && !self.config.allow_tabs {
             return Err(CompilerError::new(
-                format!("Paréntesis '{}' sin cerrar", paren_type),
-                *line,
-                *column
+                Codigo::TabuladorEnIndentacion,
+                self.line,
+                1
             ));
         }
+        
+        let caracter = if tabs { '\t' } else { ' ' };
+        let mezcla = (espacios && tabs) || self.indent_char.is_some_and(|anterior| anterior != caracter);
+        self.indent_char.get_or_insert(caracter);
+        if !mezcla {
+            return Ok(());
+        }
+        
+        let error = CompilerError::new(
+            Mensaje::new(Codigo::IndentacionMezclada).con(self.config.tab_width),
+            self.line,
+            1
+        );
+        if self.config.require_consistent {
+            return Err(error);
+        }
+        self.advertir(error);
         Ok(())
     }
+
+    fn advertir(&mut self, advertencia: CompilerError) {
+        self.advertencias.push(advertencia.en_archivo(self.archivo));
+    }
+    
+    fn unclosed_parentheses_errors(&self) -> Vec<CompilerError> {
+        self.paren_stack
+            .iter()
+            .map(|(paren_type, line, column)| CompilerError::new(
+                Mensaje::new(Codigo::ParentesisSinCerrar).con(*paren_type),
+                *line,
+                *column
+            ))
+            .collect()
+    }
     
     fn handle_indentation(&mut self) -> Result<(), CompilerError> {
-        let start_pos = self.position;
         let mut indent = 0;
+        let (mut espacios, mut tabs) = (false, false);
         
         // Solo contar espacios/tabs al inicio de línea
-        while self.position < self.chars.len() {
-            match self.chars[self.position] {
+        while let Some(c) = self.peek_char() {
+            match c {
                 ' ' => {
                     indent += 1;
+                    espacios = true;
                     self.position += 1;
                     self.column += 1;
                 }
                 '\t' => {
-                    indent += 4; // Tabs como 4 espacios
+                    indent += self.config.tab_width;
+                    tabs = true;
                     self.position += 1;
                     self.column += 1;
                 }
+                c if es_espacio_no_estandar(c) => {
+                    indent += 1;
+                    espacios = true;
+                    self.espacio_no_estandar(c);
+                }
                 _ => break,
             }
         }
         
         // IMPORTANTE: Solo procesar indentación si estamos realmente al inicio de línea
         // y después de espacios hay algo que no sea salto de línea
-        if self.position >= self.chars.len() || self.chars[self.position] == '\n' {
-            // Línea vacía o solo espacios, no generar tokens de indentación
+        if self.solo_comentarios() {
+            // Línea vacía, solo espacios o solo comentarios: no generar tokens de indentación
             self.at_line_start = false;
             return Ok(());
         }
         
-        let last_indent = *self.indent_stack.last().unwrap();
+        if espacios || tabs {
+            self.check_indent_chars(espacios, tabs)?;
+        }
+        
+        // La pila empieza con la columna 0 y nunca se vacía
+        let last_indent = self.indent_stack.last().copied().unwrap_or(0);
         
         // Solo generar tokens INDENT/DEDENT si hay cambio real de indentación
         if indent != self.current_indent {
             if indent > last_indent {
-                self.tokens.push(Token::new(
+                self.push_token(Token::new(
                     TokenType::Indent,
                     "",
                     self.line,
@@ -264,8 +443,18 @@ impl<'a> Lexer<'a> {
                 while let Some(&stack_indent) = self.indent_stack.last() {
                     if stack_indent <= indent {
                         if stack_indent < indent {
-                            return Err(CompilerError::new(
-                                "Indentación inconsistente",
+                            // Sin política estricta la línea queda en el
+                            // bloque más cercano que la contiene
+                            let error = CompilerError::new(
+                                Codigo::IndentacionInconsistente,
+                                self.line,
+                                1
+                            );
+                            if self.config.require_consistent {
+                                return Err(error);
+                            }
+                            self.advertir(CompilerError::new(
+                                Mensaje::new(Codigo::IndentacionEnBloque).con(stack_indent),
                                 self.line,
                                 1
                             ));
@@ -273,7 +462,7 @@ impl<'a> Lexer<'a> {
                         break;
                     }
                     
-                    self.tokens.push(Token::new(
+                    self.push_token(Token::new(
                         TokenType::Dedent,
                         "",
                         self.line,
@@ -296,11 +485,14 @@ impl<'a> Lexer<'a> {
     
     // Saltar espacios sin procesar indentación
     fn skip_whitespace_only(&mut self) {
-        while self.position < self.chars.len() && 
-            self.chars[self.position].is_whitespace() &&
-            self.chars[self.position] != '\n' {
-            self.position += 1;
-            self.column += 1;
+        while let Some(c) = self.peek_char() {
+            if es_espacio_no_estandar(c) {
+                self.espacio_no_estandar(c);
+            } else if c == ' ' || c == '\t' {
+                self.advance_char(c);
+            } else {
+                break;
+            }
             self.at_line_start = false;
         }
     }
@@ -311,14 +503,13 @@ impl<'a> Lexer<'a> {
         let start_pos = self.position;
         
         // Leer parte entera
-        while self.position < self.chars.len() && self.chars[self.position].is_ascii_digit() {
-            self.position += 1;
-            self.column += 1;
+        while let Some(c) = self.peek_char().filter(char::is_ascii_digit) {
+            self.advance_char(c);
         }
         
-        let value: String = self.chars[start_pos..self.position].iter().collect();
+        let value = &self.source[start_pos..self.position];
         
-        self.tokens.push(Token::new(
+        self.push_token(Token::new(
             TokenType::Num,
             value,
             start_line,
@@ -333,24 +524,41 @@ impl<'a> Lexer<'a> {
         let start_column = self.column;
         let start_pos = self.position;
         
-        while self.position < self.chars.len() {
-            let c = self.chars[self.position];
+        while let Some(c) = self.peek_char() {
             if c.is_alphanumeric() || c == '_' {
-                self.position += 1;
-                self.column += 1;
+                self.advance_char(c);
             } else {
                 break;
             }
         }
         
-        let value: String = self.chars[start_pos..self.position].iter().collect();
+        let value = &self.source[start_pos..self.position];
+        
+        // Un alias se lee como la instrucción que nombra, y el token conserva
+        // la extensión del texto escrito
+        if let Some(alias) = self.keywords.alias.get(value).copied() {
+            if alias.obsoleto {
+                self.advertir(CompilerError::new(
+                    Mensaje::new(Codigo::InstruccionObsoleta).con(value).con(alias.instruccion),
+                    start_line,
+                    start_column
+                ));
+            }
+            self.push_token(Token::new(
+                TokenType::ElementalInstruction,
+                alias.instruccion,
+                start_line,
+                start_column
+            ).hasta(self.line, self.column));
+            return Ok(());
+        }
         
         // Determinar el tipo de token
-        let token_type = self.determine_identifier_type(&value);
+        let token_type = self.determine_identifier_type(value);
         
-        self.tokens.push(Token::new(
+        self.push_token(Token::new(
             token_type,
-            value.clone(),
+            value,
             start_line,
             start_column
         ));
@@ -364,93 +572,83 @@ impl<'a> Lexer<'a> {
             return token_type;
         }
         
-        // Luego verificar en types_defined
+        // Luego verificar en types_defined, que incluye los literales
+        // booleanos `V` y `F` (sólo en mayúscula: `v` y `f` son nombres)
         if let Some(&token_type) = self.keywords.types_defined.get(value) {
             return token_type;
         }
         
-        // Verificar si es un valor booleano literal
-        if self.is_boolean_literal(value) {
-            return TokenType::Bool;
-        }
-        
         // Por defecto, es un identificador
         TokenType::Identifier
     }
     
-    fn is_boolean_literal(&self, value: &str) -> bool {
-        matches!(
-            value.to_lowercase().as_str(),
-            "true" | "false" | "verdadero" | "falso" | "v" | "f"
-        )
-    }
-    
+    // El valor del token es el texto entre comillas tal como aparece en el
+    // código; las secuencias de escape se validan aquí y se resuelven con
+    // `Token::valor_cadena`
     fn read_string(&mut self, quote: char) -> Result<(), CompilerError> {
         let start_line = self.line;
         let start_column = self.column;
         
-        self.position += 1; // Saltar comilla inicial
-        self.column += 1;
+        self.advance_char(quote); // Saltar comilla inicial
         
         let start_pos = self.position;
-        let mut value = String::new();
+        let mut invalid_escape = None;
         
-        while self.position < self.chars.len() && self.chars[self.position] != quote {
-            let c = self.chars[self.position];
+        while let Some(c) = self.peek_char() {
+            if c == quote {
+                break;
+            }
             
-            // Manejar secuencias de escape
+            // Validar secuencias de escape
             if c == '\\' {
-                self.position += 1;
-                self.column += 1;
+                self.advance_char(c);
                 
-                if self.position >= self.chars.len() {
+                let Some(escaped) = self.peek_char() else {
                     return Err(CompilerError::new(
-                        "Secuencia de escape incompleta",
+                        Codigo::EscapeIncompleto,
                         self.line,
                         self.column
                     ));
-                }
+                };
                 
-                let escaped = match self.chars[self.position] {
-                    'n' => '\n',
-                    't' => '\t',
-                    'r' => '\r',
-                    '\\' => '\\',
-                    '\'' => '\'',
-                    '"' => '"',
-                    _ => return Err(CompilerError::new(
-                        format!("Secuencia de escape desconocida: \\{}", self.chars[self.position]),
+                // Se sigue leyendo hasta el cierre para no confundir el resto
+                // de la cadena con código
+                if !matches!(escaped, 'n' | 't' | 'r' | '\\' | '\'' | '"') && invalid_escape.is_none() {
+                    invalid_escape = Some(CompilerError::new(
+                        Mensaje::new(Codigo::EscapeDesconocido).con(escaped),
                         self.line,
                         self.column
-                    )),
-                };
-                
-                value.push(escaped);
+                    ));
+                }
+                self.advance_char(escaped);
+            } else if c == '\n' || c == '\r' {
+                self.salto_de_linea(c);
             } else {
-                value.push(c);
+                self.advance_char(c);
             }
-            
-            self.position += 1;
-            self.column += 1;
         }
         
-        if self.position >= self.chars.len() {
+        if self.peek_char().is_none() {
             return Err(CompilerError::new(
-                "Cadena sin cerrar",
+                Codigo::CadenaSinCerrar,
                 start_line,
                 start_column
             ));
         }
         
-        self.position += 1; // Saltar comilla final
-        self.column += 1;
+        let value = &self.source[start_pos..self.position];
+        self.advance_char(quote); // Saltar comilla final
+        
+        if let Some(error) = invalid_escape {
+            return Err(error);
+        }
         
-        self.tokens.push(Token::new(
+        self.push_token(Token::new(
             TokenType::Str, 
             value,
             start_line,
             start_column
-        ));
+        ).hasta(self.line, self.column));
         
         Ok(())
     }
@@ -458,92 +656,52 @@ impl<'a> Lexer<'a> {
     fn read_operator(&mut self) -> Result<(), CompilerError> {
         let start_line = self.line;
         let start_column = self.column;
-        let first_char = self.chars[self.position];
-        
-        // Verificar si hay suficientes caracteres para un operador de dos caracteres
-        if self.position + 1 < self.chars.len() {
-            let second_char = self.chars[self.position + 1];
-            let two_char_op = format!("{}{}", first_char, second_char);
-            
-            // Lista de operadores de dos caracteres
-            let (token_type, value, chars_to_consume) = match two_char_op.as_str() {
-                ":=" => (TokenType::Assign, two_char_op, 2),
-                "<>" => (TokenType::NotEquals, two_char_op, 2),
-                "<=" => (TokenType::LessEqual, two_char_op, 2),
-                ">=" => (TokenType::GreaterEqual, two_char_op, 2),
-                "==" => (TokenType::Equals, two_char_op, 2),
-                _ => {
-                    // No es un operador de dos caracteres, usar un solo carácter
-                    let (token_type, value) = match first_char {
-                        ',' => (TokenType::Comma, first_char.to_string()),
-                        ':' => (TokenType::Declaration, first_char.to_string()),
-                        '&' => (TokenType::And, first_char.to_string()),
-                        '|' => (TokenType::Or, first_char.to_string()),
-                        '~' => (TokenType::Not, first_char.to_string()),
-                        '+' => (TokenType::Plus, first_char.to_string()),
-                        '-' => (TokenType::Minus, first_char.to_string()),
-                        '*' => (TokenType::Multiply, first_char.to_string()),
-                        '/' => (TokenType::Divide, first_char.to_string()),
-                        '=' => (TokenType::Equals, first_char.to_string()),
-                        '<' => (TokenType::Less, first_char.to_string()),
-                        '>' => (TokenType::Greater, first_char.to_string()),
-                        _ => {
-                            return Err(CompilerError::new(
-                                format!("Operador no reconocido: '{}'", first_char),
-                                start_line,
-                                start_column
-                            ));
-                        }
-                    };
-                    (token_type, value, 1)
-                }
-            };
-            
-            self.tokens.push(Token::new(
-                token_type,
-                value,
-                start_line,
-                start_column
-            ));
-            
-            self.position += chars_to_consume;
-            self.column += chars_to_consume;
-            self.at_line_start = false;
-            return Ok(());
-        }
-        
-        // Solo queda un carácter, manejar operadores de un solo carácter
-        let (token_type, value) = match first_char {
-            ',' => (TokenType::Comma, first_char.to_string()),
-            ':' => (TokenType::Declaration, first_char.to_string()),
-            '&' => (TokenType::And, first_char.to_string()),
-            '|' => (TokenType::Or, first_char.to_string()),
-            '~' => (TokenType::Not, first_char.to_string()),
-            '+' => (TokenType::Plus, first_char.to_string()),
-            '-' => (TokenType::Minus, first_char.to_string()),
-            '*' => (TokenType::Multiply, first_char.to_string()),
-            '/' => (TokenType::Divide, first_char.to_string()),
-            '=' => (TokenType::Equals, first_char.to_string()),
-            '<' => (TokenType::Less, first_char.to_string()),
-            '>' => (TokenType::Greater, first_char.to_string()),
+        let first_char = self.source[self.position..].chars().next().unwrap_or_default();
+        
+        // Primero intentar con un operador de dos caracteres
+        let (token_type, chars_to_consume) = match self.source.get(self.position..self.position + 2) {
+            Some(":=") => (TokenType::Assign, 2),
+            Some("<>") => (TokenType::NotEquals, 2),
+            Some("<=") => (TokenType::LessEqual, 2),
+            Some(">=") => (TokenType::GreaterEqual, 2),
+            Some("==") => (TokenType::Equals, 2),
             _ => {
-                return Err(CompilerError::new(
-                    format!("Operador no reconocido: '{}'", first_char),
-                    start_line,
-                    start_column
-                ));
+                // No es un operador de dos caracteres, usar un solo carácter
+                let token_type = match first_char {
+                    ',' => TokenType::Comma,
+                    ':' => TokenType::Declaration,
+                    '&' => TokenType::And,
+                    '|' => TokenType::Or,
+                    '~' => TokenType::Not,
+                    '+' => TokenType::Plus,
+                    '-' => TokenType::Minus,
+                    '*' => TokenType::Multiply,
+                    '/' => TokenType::Divide,
+                    '=' => TokenType::Equals,
+                    '<' => TokenType::Less,
+                    '>' => TokenType::Greater,
+                    _ => {
+                        return Err(CompilerError::new(
+                            Mensaje::new(Codigo::CaracterDeOperador).con(first_char),
+                            start_line,
+                            start_column
+                        ));
+                    }
+                };
+                (token_type, 1)
             }
         };
         
-        self.tokens.push(Token::new(
+        // Todos los operadores son ASCII: cada carácter ocupa un byte
+        self.push_token(Token::new(
             token_type,
-            value,
+            &self.source[self.position..self.position + chars_to_consume],
             start_line,
             start_column
         ));
         
-        self.position += 1;
-        self.column += 1;
+        self.position += chars_to_consume;
+        self.column += chars_to_consume;
         self.at_line_start = false;
         
         Ok(())
@@ -553,62 +711,105 @@ impl<'a> Lexer<'a> {
         let start_line = self.line;
         let start_column = self.column;
         
-        self.position += 1; // Saltar '{'
-        self.column += 1;
+        self.advance_char('{'); // Saltar '{'
         
-        while self.position < self.chars.len() && self.chars[self.position] != '}' {
-            if self.chars[self.position] == '\n' {
-                self.line += 1;
-                self.column = 1;
+        // '{' internas todavía abiertas, si se admite anidar
+        let mut profundidad = 0;
+        while let Some(c) = self.peek_char() {
+            if c == '}' {
+                if profundidad == 0 {
+                    break;
+                }
+                profundidad -= 1;
+                self.advance_char(c);
+            } else if c == '{' {
+                if self.config.nested_comments {
+                    profundidad += 1;
+                } else {
+                    self.advertir(CompilerError::new(
+                        Codigo::ComentarioAnidado,
+                        self.line,
+                        self.column
+                    ));
+                }
+                self.advance_char(c);
+            } else if c == '\n' || c == '\r' {
+                self.salto_de_linea(c);
             } else {
-                self.column += 1;
+                self.advance_char(c);
             }
-            self.position += 1;
         }
         
-        if self.position >= self.chars.len() {
+        if self.peek_char().is_none() {
             return Err(CompilerError::new(
-                "Comentario sin cerrar",
+                Codigo::ComentarioSinCerrar,
                 start_line,
                 start_column
             ));
         }
         
-        self.position += 1; // Saltar '}'
-        self.column += 1;
+        self.advance_char('}'); // Saltar '}'
+        self.comentarios.push(Span::new(start_line, start_column, self.line, self.column));
         
         Ok(())
     }
     
-    // Método de utilidad para depuración
-    pub fn debug_tokens(&self) {
-        println!("=== Tokens generados ===");
-        for token in &self.tokens {
-            println!("{:20} '{}' (línea {}, columna {})",
-                token.token_type.as_str(),
-                token.value,
-                token.line,
-                token.column
-            );
-        }
-        
-        // Mostrar estadísticas de paréntesis
-        println!("\n=== Balance de paréntesis ===");
-        if self.paren_stack.is_empty() {
-            println!("Todos los paréntesis están balanceados");
-        } else {
-            println!("Paréntesis sin cerrar: {}", self.paren_stack.len());
-            for (paren_type, line, column) in &self.paren_stack {
-                println!("  '{}' en línea {}, columna {}", paren_type, line, column);
+    // Desde la posición actual, la línea no tiene más que espacios y
+    // comentarios, así que no cuenta para la indentación: la decide la
+    // próxima línea con código
+    fn solo_comentarios(&self) -> bool {
+        let mut resto = &self.source[self.position..];
+        loop {
+            resto = resto.trim_start_matches(|c: char| {
+                !matches!(c, '\n' | '\r') && (c.is_whitespace() || es_espacio_no_estandar(c))
+            });
+            if resto.is_empty() || resto.starts_with(['\n', '\r']) || (self.config.line_comments && resto.starts_with("//")) {
+                return true;
+            }
+            if !resto.starts_with('{') {
+                return false;
+            }
+            match self.largo_comentario(resto) {
+                Some(largo) => resto = &resto[largo..],
+                // Un comentario sin cerrar se informa al leerlo
+                None => return false,
+            }
+        }
+    }
+    
+    // Bytes que ocupa el comentario `{ ... }` con el que empieza `texto`;
+    // `None` si no se cierra
+    fn largo_comentario(&self, texto: &str) -> Option<usize> {
+        let mut profundidad = 0;
+        for (i, c) in texto.char_indices().skip(1) {
+            match c {
+                '{' if self.config.nested_comments => profundidad += 1,
+                '}' if profundidad == 0 => return Some(i + 1),
+                '}' => profundidad -= 1,
+                _ => {}
             }
         }
+        None
+    }
+    
+    fn at_line_comment(&self) -> bool {
+        self.config.line_comments && self.source[self.position..].starts_with("//")
+    }
+    
+    // Saltea un comentario `//` hasta el fin de la línea, sin consumirlo
+    fn read_line_comment(&mut self) {
+        let (start_line, start_column) = (self.line, self.column);
+        while let Some(c) = self.peek_char().filter(|c| !matches!(c, '\n' | '\r')) {
+            self.advance_char(c);
+        }
+        self.comentarios.push(Span::new(start_line, start_column, self.line, self.column));
     }
     
     // Método para obtener estadísticas
-    pub fn get_statistics(&self) -> HashMap<TokenType, usize> {
+    pub fn get_statistics(tokens: &[Token<'src>]) -> HashMap<TokenType, usize> {
         let mut stats = HashMap::new();
         
-        for token in &self.tokens {
+        for token in tokens {
             *stats.entry(token.token_type).or_insert(0) += 1;
         }
         
@@ -620,8 +821,74 @@ impl<'a> Lexer<'a> {
         self.paren_stack.is_empty()
     }
     
+    // Indica de qué archivo de la compilación es el código fuente
+    pub fn con_config(mut self, config: LexerConfig) -> Self {
+        self.config = config;
+        self
+    }
+    
+    // Advertencias de la última lectura
+    pub fn advertencias(&self) -> &[CompilerError] {
+        &self.advertencias
+    }
+    
+    // Comentarios de la última lectura, en el orden en que aparecen
+    pub fn comentarios(&self) -> &[Span] {
+        &self.comentarios
+    }
+    
+    pub fn en_archivo(mut self, archivo: FileId) -> Self {
+        self.archivo = archivo;
+        self
+    }
+
     // Método para obtener información sobre paréntesis no cerrados
     pub fn get_unclosed_parentheses(&self) -> Vec<(char, usize, usize)> {
         self.paren_stack.clone()
     }
-}
\ No newline at end of file
+}
+
+// Espacios en blanco distintos de ' ', '\t' y los fines de línea, incluidos
+// los de ancho cero que `char::is_whitespace` no reconoce
+fn es_espacio_no_estandar(c: char) -> bool {
+    (c.is_whitespace() && !matches!(c, ' ' | '\t' | '\n' | '\r'))
+        || matches!(c, '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+}
+
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Result<Token<'src>, CompilerError>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(pendiente) = self.pendientes.pop_front() {
+                let archivo = self.archivo;
+                return Some(pendiente
+                    .map(|token| {
+                        let token = Token { archivo, ..token };
+                        traza::evento!(TRACE, linea = token.line, columna = token.column, tipo = token.token_type.as_str(), valor = token.value, "token");
+                        token
+                    })
+                    .map_err(|error| error.en_archivo(archivo)));
+            }
+            if self.terminado {
+                return None;
+            }
+            
+            let Some(char) = self.peek_char() else {
+                self.finish();
+                continue;
+            };
+            
+            let inicio = self.position;
+            if let Err(error) = self.scan_token(char) {
+                self.pendientes.push_back(Err(error));
+                
+                // Recuperación: si el error no consumió nada, saltear el carácter
+                if self.position == inicio {
+                    self.advance_char(char);
+                }
+                self.at_line_start = false;
+            }
+        }
+    }
+}
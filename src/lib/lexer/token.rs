@@ -1,11 +1,17 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
+use crate::source::{FileId, Span};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType {
     ParameterType,
     OpenedParenthesis,
     ClosedParenthesis,
+    // Extensión: `[` y `]` de los arreglos
+    OpenedBracket,
+    ClosedBracket,
     Str,
     Num,
     Bool,
@@ -42,6 +48,8 @@ impl TokenType {
             TokenType::ParameterType => "PARAMETER_TYPE",
             TokenType::OpenedParenthesis => "OPENED_PARENTHESIS",
             TokenType::ClosedParenthesis => "CLOSED_PARENTHESIS",
+            TokenType::OpenedBracket => "OPENED_BRACKET",
+            TokenType::ClosedBracket => "CLOSED_BRACKET",
             TokenType::Str => "STRING",
             TokenType::Num => "NUMBER",
             TokenType::Bool => "BOOLEAN",
@@ -71,30 +79,191 @@ impl TokenType {
             TokenType::Divide => "DIVIDE"
         }
     }
+
+    // Operadores que pueden aparecer en una expresión: los binarios y `~`
+    pub fn is_operator(&self) -> bool {
+        self.operator_symbol().is_some() || *self == TokenType::Not
+    }
+
+    // Texto con que el AST guarda cada operador binario
+    pub fn operator_symbol(&self) -> Option<&'static str> {
+        match self {
+            TokenType::Plus => Some("+"),
+            TokenType::Minus => Some("-"),
+            TokenType::Multiply => Some("*"),
+            TokenType::Divide => Some("/"),
+            TokenType::Less => Some("<"),
+            TokenType::LessEqual => Some("<="),
+            TokenType::Greater => Some(">"),
+            TokenType::GreaterEqual => Some(">="),
+            TokenType::Equals => Some("=="),
+            TokenType::NotEquals => Some("<>"),
+            TokenType::And => Some("&"),
+            TokenType::Or => Some("|"),
+            _ => None,
+        }
+    }
+
+    // Precedencia del operador binario; `None` si el token no lo es
+    pub fn binding_power(&self) -> Option<u8> {
+        self.operator_symbol().map(precedencia)
+    }
+}
+
+// Precedencia de cada operador binario según su texto: cuanto mayor, más
+// fuerte agrupa
+pub fn precedencia(operador: &str) -> u8 {
+    match operador {
+        "*" | "/" => 5,
+        "+" | "-" => 4,
+        "<" | "<=" | ">" | ">=" | "=" | "==" | "<>" => 3,
+        "&" => 2,
+        _ => 1,
+    }
 }
 
+// Secciones de un programa en el orden en que deben aparecer, antes del
+// bloque principal. `incluir` puede repetirse; el resto, no.
+pub const SECCIONES: [&str; 6] = ["incluir", "constantes", "procesos", "areas", "robots", "variables"];
+
 impl std::fmt::Display for TokenType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Token {
+// El valor de cada token es un slice del código fuente, salvo el de un alias,
+// que es el nombre de la instrucción que nombra; `archivo` indica de qué
+// archivo de la compilación proviene
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Token<'src> {
     pub token_type: TokenType,
-    pub value: String,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub value: &'src str,
     pub line: usize,
     pub column: usize,
+    // Posición siguiente al último carácter del token
+    pub end_line: usize,
+    pub end_column: usize,
+    pub archivo: FileId,
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, value: impl Into<String>, line: usize, column: usize) -> Self {
+impl<'src> Token<'src> {
+    // El token termina donde termina su valor; los que no se escriben tal
+    // cual (como las cadenas, sin sus comillas) lo corrigen con `hasta`
+    pub fn new(token_type: TokenType, value: &'src str, line: usize, column: usize) -> Self {
         Self {
             token_type,
-            value: value.into(),
+            value,
             line,
             column,
+            end_line: line,
+            end_column: column + value.chars().count(),
+            archivo: FileId::PRINCIPAL,
+        }
+    }
+
+    pub fn hasta(self, end_line: usize, end_column: usize) -> Self {
+        Self { end_line, end_column, ..self }
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.line, self.column, self.end_line, self.end_column)
+    }
+    
+    // Números, cadenas y `V`/`F`. El tipo `numero` comparte la clase de
+    // token con los números, así que se distingue por el texto.
+    pub fn is_literal(&self) -> bool {
+        match self.token_type {
+            TokenType::Num => self.value.starts_with(|c: char| c.is_ascii_digit()),
+            TokenType::Str | TokenType::BoolValue => true,
+            _ => false,
+        }
+    }
+
+    // Palabra clave que abre una sección del programa. `variables` también
+    // abre las variables locales de un proceso o un robot.
+    pub fn is_section_keyword(&self) -> bool {
+        self.token_type == TokenType::Keyword && SECCIONES.contains(&self.value)
+    }
+
+    // Valor de un literal booleano (`V` o `F`); `None` si el token no lo es
+    pub fn valor_booleano(&self) -> Option<bool> {
+        match (self.token_type, self.value) {
+            (TokenType::BoolValue, "V") => Some(true),
+            (TokenType::BoolValue, "F") => Some(false),
+            _ => None,
+        }
+    }
+
+    // Contenido de una cadena con las secuencias de escape resueltas. Sólo
+    // copia el texto si la cadena tiene alguna secuencia de escape.
+    pub fn valor_cadena(&self) -> Cow<'src, str> {
+        if !self.value.contains('\\') {
+            return Cow::Borrowed(self.value);
+        }
+        
+        let mut valor = String::with_capacity(self.value.len());
+        let mut chars = self.value.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                valor.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => valor.push('\n'),
+                Some('t') => valor.push('\t'),
+                Some('r') => valor.push('\r'),
+                Some(otro) => valor.push(otro),
+                None => {}
+            }
         }
+        Cow::Owned(valor)
+    }
+}
+
+// Instrucciones que no son parte de R-Info y sólo se reconocen con
+// `--extensions`: una recepción que deja de esperar después de unos turnos
+// y el sensor que indica si llegó el mensaje
+pub const INSTRUCCIONES_EXTENDIDAS: [(&str, &str); 2] = [
+    ("EXTENSION1", "RecibirMensajeTimeout"),
+    ("EXTENSION2", "RecibioMensaje"),
+];
+
+// Sentencias de control que sólo se reconocen con `--extensions`: `segun`
+// elige un bloque según el valor de una expresión y `para` repite un bloque
+// contando de un número a otro
+pub const SENTENCIAS_EXTENDIDAS: [(&str, &str); 4] = [
+    ("CONTROL_EXTENSION1", "segun"),
+    ("CONTROL_EXTENSION2", "caso"),
+    ("CONTROL_EXTENSION3", "para"),
+    ("CONTROL_EXTENSION4", "hasta"),
+];
+
+// Palabras clave que sólo se reconocen con `--extensions`: `arreglo`
+// declara una variable con varios valores del mismo tipo
+pub const PALABRAS_EXTENDIDAS: [(&str, &str); 1] = [
+    ("KEYWORD_EXTENSION1", "arreglo"),
+];
+
+// Otro nombre de una instrucción elemental, como los que usan algunos
+// apuntes de la materia. Se lee como la instrucción; si es `obsoleto`, el
+// lexer además advierte cuál es el nombre que se prefiere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alias {
+    pub instruccion: &'static str,
+    pub obsoleto: bool,
+}
+
+impl Alias {
+    // Alias de `instruccion`; `None` si no es una instrucción elemental,
+    // con o sin `--extensions`
+    pub fn new(instruccion: &str, obsoleto: bool) -> Option<Self> {
+        let instruccion = Keywords::new().con_extensiones().elemental_instructions
+            .into_values()
+            .find(|&nombre| nombre == instruccion)?;
+        Some(Self { instruccion, obsoleto })
     }
 }
 
@@ -105,6 +274,7 @@ pub struct Keywords {
     pub elemental_instructions: HashMap<&'static str, &'static str>,
     pub keyword_map: HashMap<String, TokenType>,
     pub types_defined: HashMap<String, TokenType>,
+    pub alias: HashMap<String, Alias>,
 }
 
 impl Keywords {
@@ -119,6 +289,8 @@ impl Keywords {
         basic_keywords.insert("KEYWORD7", "procesos");
         basic_keywords.insert("KEYWORD8", "areas");
         basic_keywords.insert("KEYWORD9", "robots");
+        basic_keywords.insert("KEYWORD10", "incluir");
+        basic_keywords.insert("KEYWORD11", "constantes");
 
         let mut control_sentences = HashMap::new();
         control_sentences.insert("CONTROL_SENTENCE1", "si");
@@ -181,7 +353,36 @@ impl Keywords {
             elemental_instructions,
             keyword_map,
             types_defined,
+            alias: HashMap::new(),
+        }
+    }
+
+    // Agrega las instrucciones de `INSTRUCCIONES_EXTENDIDAS`, las sentencias
+    // de `SENTENCIAS_EXTENDIDAS` y las palabras de `PALABRAS_EXTENDIDAS`
+    pub fn con_extensiones(mut self) -> Self {
+        for (clave, nombre) in INSTRUCCIONES_EXTENDIDAS {
+            self.elemental_instructions.insert(clave, nombre);
+            self.keyword_map.insert(nombre.to_string(), TokenType::ElementalInstruction);
+        }
+        for (clave, nombre) in SENTENCIAS_EXTENDIDAS {
+            self.control_sentences.insert(clave, nombre);
+            self.keyword_map.insert(nombre.to_string(), TokenType::ControlSentence);
+        }
+        for (clave, nombre) in PALABRAS_EXTENDIDAS {
+            self.basic_keywords.insert(clave, nombre);
+            self.keyword_map.insert(nombre.to_string(), TokenType::Keyword);
+        }
+        self
+    }
+
+    // Lee `nombre` como la instrucción de `alias`. Se ignora si `nombre` ya es
+    // una palabra reservada o si la instrucción no se reconoce con estas
+    // palabras (una extensión sin `con_extensiones`).
+    pub fn con_alias(mut self, nombre: &str, alias: Alias) -> Self {
+        if self.get_token_type(nombre).is_none() && self.is_elemental_instruction(alias.instruccion) {
+            self.alias.insert(nombre.to_string(), alias);
         }
+        self
     }
 
     pub fn get_token_type(&self, word: &str) -> Option<TokenType> {
@@ -0,0 +1,202 @@
+use std::fmt::Write;
+use std::path::Path;
+use crate::driver::{compilar_archivo_con, Opciones};
+use crate::parser::processor::Parser;
+use crate::resaltado::{highlight_con, HighlightKind};
+use crate::source::FileId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gravedad {
+    Error,
+    Advertencia,
+}
+
+impl Gravedad {
+    fn clase(self) -> &'static str {
+        match self {
+            Gravedad::Error => "error",
+            Gravedad::Advertencia => "advertencia",
+        }
+    }
+
+    fn marca(self) -> &'static str {
+        match self {
+            Gravedad::Error => "✗",
+            Gravedad::Advertencia => "⚠",
+        }
+    }
+}
+
+// Un diagnóstico del archivo principal, en su línea (0 si no tiene)
+struct Anotacion {
+    gravedad: Gravedad,
+    linea: usize,
+    mensaje: String,
+}
+
+// Código del archivo como HTML autocontenido, coloreado como lo resalta
+// `highlight` y con cada error y advertencia debajo de su línea. La línea
+// con diagnósticos se marca y los muestra también al pasar el mouse. Los
+// diagnósticos de los archivos incluidos no se muestran.
+pub fn listado_html(source: &str, ruta: &Path, opciones: Opciones) -> String {
+    let tramos = highlight_con(source, &opciones);
+    let anotaciones = anotaciones(source, ruta, opciones);
+
+    let lineas: Vec<&str> = source.lines().collect();
+    let mut clases: Vec<Vec<Option<HighlightKind>>> = lineas.iter()
+        .map(|linea| vec![None; linea.chars().count()])
+        .collect();
+    for (tramo, clase) in tramos {
+        for linea in tramo.linea..=tramo.fin_linea.min(lineas.len()) {
+            let Some(columnas) = clases.get_mut(linea - 1) else {
+                continue;
+            };
+            let desde = if linea == tramo.linea { tramo.columna } else { 1 };
+            let hasta = if linea == tramo.fin_linea { tramo.fin_columna } else { columnas.len() + 1 };
+            for columna in desde.max(1)..hasta.min(columnas.len() + 1) {
+                columnas[columna - 1] = Some(clase);
+            }
+        }
+    }
+
+    let nombre = escapar_html(&ruta.display().to_string());
+    let mut html = format!(
+"<!DOCTYPE html>
+<html lang=\"es\">
+<head>
+<meta charset=\"utf-8\">
+<title>{nombre}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+pre {{ line-height: 1.4; }}
+.numero {{ color: #999; user-select: none; }}
+.palabra-clave {{ color: #708; font-weight: bold; }}
+.instruccion {{ color: #05a; }}
+.robot {{ color: #a50; }}
+.proceso {{ color: #085; }}
+.area {{ color: #a11; }}
+.constante {{ color: #30a; }}
+.literal {{ color: #164; }}
+.comentario {{ color: #888; font-style: italic; }}
+.linea.error {{ background: #fdd; }}
+.linea.advertencia {{ background: #ffd; }}
+.nota.error {{ color: #b00; }}
+.nota.advertencia {{ color: #850; }}
+</style>
+</head>
+<body>
+<h1>{nombre}</h1>
+");
+    for anotacion in anotaciones.iter().filter(|anotacion| anotacion.linea == 0 || anotacion.linea > lineas.len()) {
+        let _ = writeln!(
+            html, "<p class=\"nota {}\">{} {}</p>",
+            anotacion.gravedad.clase(), anotacion.gravedad.marca(), escapar_html(&anotacion.mensaje)
+        );
+    }
+
+    html.push_str("<pre>\n");
+    let ancho = lineas.len().to_string().len();
+    for (indice, (texto, clases)) in lineas.iter().zip(&clases).enumerate() {
+        let numero = indice + 1;
+        let propias: Vec<&Anotacion> = anotaciones.iter().filter(|anotacion| anotacion.linea == numero).collect();
+        // La línea toma el color del diagnóstico más grave
+        let gravedad = propias.iter().map(|anotacion| anotacion.gravedad)
+            .min_by_key(|gravedad| *gravedad == Gravedad::Advertencia);
+        match gravedad {
+            Some(gravedad) => {
+                let mensajes: Vec<String> = propias.iter().map(|anotacion| escapar_html(&anotacion.mensaje)).collect();
+                let _ = write!(
+                    html, "<span class=\"linea {}\" title=\"{}\"><span class=\"numero\">{:>ancho$} {} </span>",
+                    gravedad.clase(), mensajes.join("&#10;"), numero, gravedad.marca()
+                );
+            }
+            None => {
+                let _ = write!(html, "<span class=\"linea\"><span class=\"numero\">{:>ancho$}   </span>", numero);
+            }
+        }
+        colorear(&mut html, texto, clases);
+        html.push_str("</span>\n");
+        for anotacion in propias {
+            let _ = writeln!(
+                html, "<span class=\"nota {}\">{:ancho$}   {} {}</span>",
+                anotacion.gravedad.clase(), "", anotacion.gravedad.marca(), escapar_html(&anotacion.mensaje)
+            );
+        }
+    }
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+// Agrega el texto de una línea agrupando los caracteres seguidos de la
+// misma clase en un solo `span`
+fn colorear(html: &mut String, texto: &str, clases: &[Option<HighlightKind>]) {
+    let caracteres: Vec<char> = texto.chars().collect();
+    let mut inicio = 0;
+    while inicio < caracteres.len() {
+        let clase = clases[inicio];
+        let fin = (inicio..caracteres.len()).find(|&i| clases[i] != clase).unwrap_or(caracteres.len());
+        let fragmento = escapar_html(&caracteres[inicio..fin].iter().collect::<String>());
+        match clase.and_then(clase_css) {
+            Some(clase) => {
+                let _ = write!(html, "<span class=\"{}\">{}</span>", clase, fragmento);
+            }
+            None => html.push_str(&fragmento),
+        }
+        inicio = fin;
+    }
+}
+
+// Clase CSS de cada clase de tramo; las variables quedan sin color
+fn clase_css(clase: HighlightKind) -> Option<&'static str> {
+    match clase {
+        HighlightKind::PalabraClave => Some("palabra-clave"),
+        HighlightKind::Instruccion => Some("instruccion"),
+        HighlightKind::Robot => Some("robot"),
+        HighlightKind::Proceso => Some("proceso"),
+        HighlightKind::Area => Some("area"),
+        HighlightKind::Variable => None,
+        HighlightKind::Constante => Some("constante"),
+        HighlightKind::Literal => Some("literal"),
+        HighlightKind::Comentario => Some("comentario"),
+    }
+}
+
+// Errores de la compilación y advertencias del lexer, del parser y de las
+// reglas, del archivo principal y en el idioma de `opciones`
+fn anotaciones(source: &str, ruta: &Path, opciones: Opciones) -> Vec<Anotacion> {
+    let idioma = opciones.idioma;
+    let principal = |archivo: Option<FileId>| archivo.is_none_or(|archivo| archivo == FileId::PRINCIPAL);
+
+    let mut lexer = opciones.lexer(source);
+    let (tokens, _) = lexer.tokenize_all();
+    let mut parser = Parser::new(&tokens).con_secciones_flexibles(opciones.secciones_flexibles);
+    let _ = parser.parse();
+    let mut anotaciones: Vec<Anotacion> = lexer.advertencias().iter()
+        .chain(parser.advertencias())
+        .map(|advertencia| Anotacion {
+            gravedad: Gravedad::Advertencia,
+            linea: advertencia.line,
+            mensaje: advertencia.mensaje.traducir(idioma),
+        })
+        .collect();
+
+    let lints = opciones.registro_lints().ok();
+    let compilacion = compilar_archivo_con(source, ruta, opciones);
+    anotaciones.extend(compilacion.errores.iter()
+        .filter(|error| principal(error.archivo))
+        .map(|error| Anotacion { gravedad: Gravedad::Error, linea: error.line, mensaje: error.mensaje.traducir(idioma) }));
+    if let (Some(programa), Some(lints)) = (&compilacion.programa, lints) {
+        anotaciones.extend(lints.advertencias(programa).into_iter()
+            .filter(|hallazgo| principal(Some(hallazgo.archivo)))
+            .map(|hallazgo| Anotacion {
+                gravedad: Gravedad::Advertencia,
+                linea: hallazgo.linea,
+                mensaje: hallazgo.mensaje.traducir(idioma),
+            }));
+    }
+    anotaciones
+}
+
+fn escapar_html(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
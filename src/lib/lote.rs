@@ -0,0 +1,205 @@
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::driver::{compilar_archivo_con, Opciones};
+use crate::interpreter::evaluacion::Evaluacion;
+use crate::interpreter::reporte::cadena_json;
+use crate::interpreter::simulation::{EstadoSimulacion, Simulation};
+use crate::interpreter::world::Ciudad;
+use crate::semanticizer::inventario::campo_csv;
+
+// Extensión de los programas que se corrigen
+const EXTENSION: &str = "ri";
+
+// Mundo, afirmaciones y límite de pasos con que se ejecuta cada entrega
+pub struct Escenario {
+    pub ciudad: Ciudad,
+    pub evaluacion: Evaluacion,
+    pub max_pasos: u64,
+}
+
+// Hasta dónde llegó una entrega
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Desenlace {
+    // No se pudo leer el archivo
+    Ilegible(String),
+    NoCompila,
+    // Compila y no se ejecutó porque no hay escenario
+    Compila,
+    Terminada,
+    // Se agotaron los pasos antes de que terminaran todos los robots
+    SinTerminar,
+    // Error de ejecución, incluido el bloqueo de todos los robots
+    Fallo(String),
+}
+
+impl Desenlace {
+    fn nombre(&self) -> &'static str {
+        match self {
+            Desenlace::Ilegible(_) => "ilegible",
+            Desenlace::NoCompila => "no_compila",
+            Desenlace::Compila => "compila",
+            Desenlace::Terminada => "terminada",
+            Desenlace::SinTerminar => "sin_terminar",
+            Desenlace::Fallo(_) => "fallo",
+        }
+    }
+
+    fn detalle(&self) -> Option<&str> {
+        match self {
+            Desenlace::Ilegible(detalle) | Desenlace::Fallo(detalle) => Some(detalle),
+            _ => None,
+        }
+    }
+}
+
+// Resultado de corregir un archivo del lote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrega {
+    // Ruta relativa al directorio del lote
+    pub archivo: String,
+    pub errores: usize,
+    pub advertencias: usize,
+    // Afirmaciones que se cumplieron y total, si se ejecutó
+    pub afirmaciones: Option<(usize, usize)>,
+    // Pasos que llevó la ejecución, si se ejecutó
+    pub pasos: Option<u64>,
+    pub desenlace: Desenlace,
+}
+
+// Todas las entregas de un directorio, ordenadas por archivo
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lote {
+    pub entregas: Vec<Entrega>,
+}
+
+impl Lote {
+    // Compila cada archivo `.ri` del directorio y de sus subdirectorios y,
+    // si hay escenario, ejecuta los que compilan y verifica sus afirmaciones
+    // sobre el mundo en que quedaron, aunque la ejecución falle
+    pub fn corregir(directorio: &Path, opciones: &Opciones, escenario: Option<&Escenario>) -> io::Result<Self> {
+        let mut archivos = Vec::new();
+        buscar_programas(directorio, &mut archivos)?;
+        archivos.sort();
+
+        let entregas = archivos.iter()
+            .map(|ruta| {
+                let archivo = ruta.strip_prefix(directorio).unwrap_or(ruta).display().to_string();
+                corregir_archivo(archivo, ruta, opciones, escenario)
+            })
+            .collect();
+        Ok(Self { entregas })
+    }
+
+    // Una fila por entrega con encabezado; las columnas de la ejecución
+    // quedan vacías si no se ejecutó
+    pub fn a_csv(&self) -> String {
+        let mut csv = String::from("archivo,errores,advertencias,afirmaciones_cumplidas,afirmaciones,pasos,desenlace,detalle\n");
+        for entrega in &self.entregas {
+            let (cumplidas, total) = match entrega.afirmaciones {
+                Some((cumplidas, total)) => (cumplidas.to_string(), total.to_string()),
+                None => (String::new(), String::new()),
+            };
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                campo_csv(&entrega.archivo),
+                entrega.errores,
+                entrega.advertencias,
+                cumplidas,
+                total,
+                entrega.pasos.map_or(String::new(), |pasos| pasos.to_string()),
+                entrega.desenlace.nombre(),
+                campo_csv(entrega.desenlace.detalle().unwrap_or("")),
+            );
+        }
+        csv
+    }
+
+    pub fn a_json(&self) -> String {
+        let mut json = String::from("[");
+        for (i, entrega) in self.entregas.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let (cumplidas, total) = match entrega.afirmaciones {
+                Some((cumplidas, total)) => (cumplidas.to_string(), total.to_string()),
+                None => ("null".to_string(), "null".to_string()),
+            };
+            let _ = write!(
+                json,
+                "  {{\"archivo\": {}, \"errores\": {}, \"advertencias\": {}, \"afirmaciones_cumplidas\": {}, \"afirmaciones\": {}, \"pasos\": {}, \"desenlace\": \"{}\", \"detalle\": {}}}",
+                cadena_json(&entrega.archivo),
+                entrega.errores,
+                entrega.advertencias,
+                cumplidas,
+                total,
+                entrega.pasos.map_or("null".to_string(), |pasos| pasos.to_string()),
+                entrega.desenlace.nombre(),
+                entrega.desenlace.detalle().map_or("null".to_string(), cadena_json),
+            );
+        }
+        json.push_str(if self.entregas.is_empty() { "]\n" } else { "\n]\n" });
+        json
+    }
+}
+
+fn buscar_programas(directorio: &Path, archivos: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entrada in fs::read_dir(directorio)? {
+        let ruta = entrada?.path();
+        if ruta.is_dir() {
+            buscar_programas(&ruta, archivos)?;
+        } else if ruta.extension().is_some_and(|extension| extension == EXTENSION) {
+            archivos.push(ruta);
+        }
+    }
+    Ok(())
+}
+
+fn corregir_archivo(archivo: String, ruta: &Path, opciones: &Opciones, escenario: Option<&Escenario>) -> Entrega {
+    let mut entrega = Entrega {
+        archivo,
+        errores: 0,
+        advertencias: 0,
+        afirmaciones: None,
+        pasos: None,
+        desenlace: Desenlace::Compila,
+    };
+    let source = match fs::read_to_string(ruta) {
+        Ok(source) => source,
+        Err(error) => {
+            entrega.desenlace = Desenlace::Ilegible(error.to_string());
+            return entrega;
+        }
+    };
+
+    let compilacion = compilar_archivo_con(&source, ruta, opciones.clone());
+    entrega.errores = compilacion.errores.len();
+    entrega.advertencias = compilacion.advertencias.len();
+    let programa = match compilacion.programa {
+        Some(programa) if compilacion.errores.is_empty() => programa,
+        _ => {
+            entrega.desenlace = Desenlace::NoCompila;
+            return entrega;
+        }
+    };
+    let Some(escenario) = escenario else {
+        return entrega;
+    };
+
+    let mut simulacion = match Simulation::con_ciudad(&programa, escenario.ciudad.clone()) {
+        Ok(simulacion) => simulacion,
+        Err(error) => {
+            entrega.desenlace = Desenlace::Fallo(error.to_string());
+            return entrega;
+        }
+    };
+    entrega.desenlace = match simulacion.ejecutar(escenario.max_pasos) {
+        Ok(EstadoSimulacion::Terminada) => Desenlace::Terminada,
+        Ok(_) => Desenlace::SinTerminar,
+        Err(error) => Desenlace::Fallo(error.to_string()),
+    };
+    let informe = escenario.evaluacion.evaluar(simulacion.mundo());
+    entrega.afirmaciones = Some((informe.cumplidas(), informe.resultados.len()));
+    entrega.pasos = Some(simulacion.pasos());
+    entrega
+}
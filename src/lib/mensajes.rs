@@ -0,0 +1,921 @@
+use std::fmt;
+
+// Idioma en que se muestran los diagnósticos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Idioma {
+    #[default]
+    Espanol,
+    Ingles,
+}
+
+impl Idioma {
+    // "es" o "en", como se indica en la opción `--lang`
+    pub fn desde_codigo(codigo: &str) -> Option<Self> {
+        match codigo {
+            "es" => Some(Idioma::Espanol),
+            "en" => Some(Idioma::Ingles),
+            _ => None,
+        }
+    }
+}
+
+// Diagnósticos del compilador. Cada uno tiene un código estable, que no
+// depende del idioma, y su texto en español y en inglés; `{0}`, `{1}`, ...
+// se reemplazan por los argumentos del mensaje. La letra del código indica
+// la fase: L (lexer), P (parser), I (inclusiones), S (análisis semántico),
+// A (advertencias) y M (archivos de mundo, de afirmaciones y de
+// configuración de las advertencias).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Codigo {
+    CaracterInesperado,
+    EspacioNoEstandar,
+    CierreSinApertura,
+    CierreNoCorresponde,
+    TabuladorEnIndentacion,
+    IndentacionMezclada,
+    ParentesisSinCerrar,
+    IndentacionInconsistente,
+    IndentacionEnBloque,
+    EscapeIncompleto,
+    EscapeDesconocido,
+    CadenaSinCerrar,
+    CaracterDeOperador,
+    ComentarioAnidado,
+    ComentarioSinCerrar,
+    InstruccionObsoleta,
+
+    EsperadoSimbolo,
+    EsperadaRutaInclusion,
+    EsperadoNombrePrograma,
+    EsperadoNombreProceso,
+    EsperadoNombreParametro,
+    EsperadoNombreVariable,
+    EsperadoNombreRobot,
+    EsperadoTipoDato,
+    EsperadoTipoArea,
+    TipoRobotNoDefinido,
+    EsperadoTipoRobot,
+    DeclaracionRobotIncompleta,
+    EsperadoDosPuntosRobot,
+    FaltaFinPrincipal,
+    SeccionRepetida,
+    SeccionFueraDeOrden,
+    InclusionFueraDeOrden,
+    SeccionInesperada,
+    FaltaBloquePrincipal,
+    FaltaFinProceso,
+    FaltaFinRobot,
+    ControlDesconocido,
+    InstruccionNoReconocida,
+    EsperadaInstruccion,
+    InstruccionSinValor,
+    ExpresionSimpleInvalida,
+    EsperadaExpresionSimple,
+    OperadorNoReconocido,
+    OperadorEsperadoEncontrado,
+    EsperadoOperador,
+    EsperadaExpresion,
+    EsperadoContratoMensaje,
+    DimensionNoConstante,
+    CantidadDeDimensiones,
+    ConstanteNoCalculable,
+    LongitudDeArreglo,
+    AnidamientoExcesivo,
+    BloquePrincipalRepetido,
+    CodigoDespuesDelPrincipal,
+    RobotSinArea,
+
+    InclusionCircular,
+    InclusionIlegible,
+    InclusionConDeclaraciones,
+
+    NombreRepetido,
+    SinRobots,
+    Recursion,
+    AsignacionANoVariable,
+    VariableNoDeclarada,
+    TipoEnAsignacion,
+    OperandosDeTipo,
+    ComparacionDeTipos,
+    NoEsVariable,
+    VariableNoDeclaradaEnExpresion,
+    TipoDeCondicion,
+    ArgumentoDeCategoria,
+    ArgumentoInvalido,
+    CantidadDeArgumentos,
+    ArgumentoNoVariable,
+    TipoDeArgumento,
+    MensajeDeTipoIncompatible,
+    ContratoConRobotDesconocido,
+    CasoNoConstante,
+    CasoDeTipo,
+    CasoRepetido,
+    LimiteDePara,
+    VariableDeParaDeclarada,
+    VariableDeParaModificada,
+    LlamadaSinValor,
+    ParametroDeSalidaEnFuncion,
+    ArregloSinIndice,
+    IndiceDeNoArreglo,
+    IndiceDeTipo,
+    IndiceFueraDeRango,
+    RobotSinIniciar,
+    IniciarRepetido,
+    AreaDespuesDeIniciar,
+    NegacionDeTipo,
+    ProcesoNoDeclarado,
+    RetornoSinAsignar,
+
+    CoordenadaFueraDeCiudad,
+    RepetirNegativo,
+    DivisionPorCero,
+    SinInicializar,
+    PosiblementeSinInicializar,
+    AnidamientoProfundo,
+    NumeroMagico,
+    SiVacio,
+    ProcesoLargo,
+    SegunSinSino,
+    SaleDelArea,
+    BolsaVacia,
+    TomarSinVerificar,
+
+    FormatoDeMundo,
+    NumeroInvalido,
+    CantidadInvalida,
+    EsquinaFueraDeCiudad,
+    ObjetoDesconocido,
+    AfirmacionDesconocida,
+    FormatoDeConfiguracion,
+    NivelDesconocido,
+    ReglaDesconocida,
+    FormatoDeProyecto,
+    ClaveDesconocida,
+    ValorInvalido,
+}
+
+impl Codigo {
+    pub const TODOS: &'static [Codigo] = &[
+        Codigo::CaracterInesperado,
+        Codigo::EspacioNoEstandar,
+        Codigo::CierreSinApertura,
+        Codigo::CierreNoCorresponde,
+        Codigo::TabuladorEnIndentacion,
+        Codigo::IndentacionMezclada,
+        Codigo::ParentesisSinCerrar,
+        Codigo::IndentacionInconsistente,
+        Codigo::IndentacionEnBloque,
+        Codigo::EscapeIncompleto,
+        Codigo::EscapeDesconocido,
+        Codigo::CadenaSinCerrar,
+        Codigo::CaracterDeOperador,
+        Codigo::ComentarioAnidado,
+        Codigo::ComentarioSinCerrar,
+        Codigo::InstruccionObsoleta,
+        Codigo::EsperadoSimbolo,
+        Codigo::EsperadaRutaInclusion,
+        Codigo::EsperadoNombrePrograma,
+        Codigo::EsperadoNombreProceso,
+        Codigo::EsperadoNombreParametro,
+        Codigo::EsperadoNombreVariable,
+        Codigo::EsperadoNombreRobot,
+        Codigo::EsperadoTipoDato,
+        Codigo::EsperadoTipoArea,
+        Codigo::TipoRobotNoDefinido,
+        Codigo::EsperadoTipoRobot,
+        Codigo::DeclaracionRobotIncompleta,
+        Codigo::EsperadoDosPuntosRobot,
+        Codigo::FaltaFinPrincipal,
+        Codigo::SeccionRepetida,
+        Codigo::SeccionFueraDeOrden,
+        Codigo::InclusionFueraDeOrden,
+        Codigo::SeccionInesperada,
+        Codigo::FaltaBloquePrincipal,
+        Codigo::FaltaFinProceso,
+        Codigo::FaltaFinRobot,
+        Codigo::ControlDesconocido,
+        Codigo::InstruccionNoReconocida,
+        Codigo::EsperadaInstruccion,
+        Codigo::InstruccionSinValor,
+        Codigo::ExpresionSimpleInvalida,
+        Codigo::EsperadaExpresionSimple,
+        Codigo::OperadorNoReconocido,
+        Codigo::OperadorEsperadoEncontrado,
+        Codigo::EsperadoOperador,
+        Codigo::EsperadaExpresion,
+        Codigo::EsperadoContratoMensaje,
+        Codigo::DimensionNoConstante,
+        Codigo::CantidadDeDimensiones,
+        Codigo::ConstanteNoCalculable,
+        Codigo::LongitudDeArreglo,
+        Codigo::AnidamientoExcesivo,
+        Codigo::BloquePrincipalRepetido,
+        Codigo::CodigoDespuesDelPrincipal,
+        Codigo::RobotSinArea,
+        Codigo::InclusionCircular,
+        Codigo::InclusionIlegible,
+        Codigo::InclusionConDeclaraciones,
+        Codigo::NombreRepetido,
+        Codigo::SinRobots,
+        Codigo::Recursion,
+        Codigo::AsignacionANoVariable,
+        Codigo::VariableNoDeclarada,
+        Codigo::TipoEnAsignacion,
+        Codigo::OperandosDeTipo,
+        Codigo::ComparacionDeTipos,
+        Codigo::NoEsVariable,
+        Codigo::VariableNoDeclaradaEnExpresion,
+        Codigo::TipoDeCondicion,
+        Codigo::ArgumentoDeCategoria,
+        Codigo::ArgumentoInvalido,
+        Codigo::CantidadDeArgumentos,
+        Codigo::ArgumentoNoVariable,
+        Codigo::TipoDeArgumento,
+        Codigo::MensajeDeTipoIncompatible,
+        Codigo::ContratoConRobotDesconocido,
+        Codigo::CasoNoConstante,
+        Codigo::CasoDeTipo,
+        Codigo::CasoRepetido,
+        Codigo::LimiteDePara,
+        Codigo::VariableDeParaDeclarada,
+        Codigo::VariableDeParaModificada,
+        Codigo::LlamadaSinValor,
+        Codigo::ParametroDeSalidaEnFuncion,
+        Codigo::ArregloSinIndice,
+        Codigo::IndiceDeNoArreglo,
+        Codigo::IndiceDeTipo,
+        Codigo::IndiceFueraDeRango,
+        Codigo::RobotSinIniciar,
+        Codigo::IniciarRepetido,
+        Codigo::AreaDespuesDeIniciar,
+        Codigo::NegacionDeTipo,
+        Codigo::ProcesoNoDeclarado,
+        Codigo::RetornoSinAsignar,
+        Codigo::CoordenadaFueraDeCiudad,
+        Codigo::RepetirNegativo,
+        Codigo::DivisionPorCero,
+        Codigo::SinInicializar,
+        Codigo::PosiblementeSinInicializar,
+        Codigo::AnidamientoProfundo,
+        Codigo::NumeroMagico,
+        Codigo::SiVacio,
+        Codigo::ProcesoLargo,
+        Codigo::SegunSinSino,
+        Codigo::SaleDelArea,
+        Codigo::BolsaVacia,
+        Codigo::TomarSinVerificar,
+        Codigo::FormatoDeMundo,
+        Codigo::NumeroInvalido,
+        Codigo::CantidadInvalida,
+        Codigo::EsquinaFueraDeCiudad,
+        Codigo::ObjetoDesconocido,
+        Codigo::AfirmacionDesconocida,
+        Codigo::FormatoDeConfiguracion,
+        Codigo::NivelDesconocido,
+        Codigo::ReglaDesconocida,
+        Codigo::FormatoDeProyecto,
+        Codigo::ClaveDesconocida,
+        Codigo::ValorInvalido,
+    ];
+
+    // (código, español, inglés)
+    fn entrada(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Codigo::CaracterInesperado => ("L001",
+                "Carácter inesperado: < {0} >",
+                "Unexpected character: < {0} >"),
+            Codigo::EspacioNoEstandar => ("L002",
+                "Espacio no estándar (U+{0}) tomado como un espacio común",
+                "Non-standard space (U+{0}) taken as a regular space"),
+            Codigo::CierreSinApertura => ("L003",
+                "Paréntesis de cierre sin apertura correspondiente",
+                "Closing parenthesis without a matching opening one"),
+            Codigo::CierreNoCorresponde => ("L004",
+                "Paréntesis de cierre no corresponde con la apertura",
+                "Closing parenthesis does not match the opening one"),
+            Codigo::TabuladorEnIndentacion => ("L005",
+                "Tabulador en la indentación: la configuración sólo admite espacios",
+                "Tab in indentation: the configuration only allows spaces"),
+            Codigo::IndentacionMezclada => ("L006",
+                "Indentación con tabuladores y espacios mezclados (un tabulador cuenta como {0} espacios)",
+                "Indentation mixes tabs and spaces (a tab counts as {0} spaces)"),
+            Codigo::ParentesisSinCerrar => ("L007",
+                "Paréntesis '{0}' sin cerrar",
+                "Unclosed parenthesis '{0}'"),
+            Codigo::IndentacionInconsistente => ("L008",
+                "Indentación inconsistente",
+                "Inconsistent indentation"),
+            Codigo::IndentacionEnBloque => ("L009",
+                "Indentación inconsistente: la línea se toma como parte del bloque indentado en {0}",
+                "Inconsistent indentation: the line is taken as part of the block indented at {0}"),
+            Codigo::EscapeIncompleto => ("L010",
+                "Secuencia de escape incompleta",
+                "Incomplete escape sequence"),
+            Codigo::EscapeDesconocido => ("L011",
+                "Secuencia de escape desconocida: \\{0}",
+                "Unknown escape sequence: \\{0}"),
+            Codigo::CadenaSinCerrar => ("L012",
+                "Cadena sin cerrar",
+                "Unterminated string"),
+            Codigo::CaracterDeOperador => ("L013",
+                "Operador no reconocido: '{0}'",
+                "Unrecognized operator: '{0}'"),
+            Codigo::ComentarioAnidado => ("L014",
+                "Comentario anidado: el comentario termina en la primera '}'",
+                "Nested comment: the comment ends at the first '}'"),
+            Codigo::ComentarioSinCerrar => ("L015",
+                "Comentario sin cerrar",
+                "Unterminated comment"),
+            Codigo::InstruccionObsoleta => ("L016",
+                "'{0}' es un nombre en desuso: se prefiere '{1}'",
+                "'{0}' is a deprecated name: prefer '{1}'"),
+
+            Codigo::EsperadoSimbolo => ("P001",
+                "Esperado '{0}'",
+                "Expected '{0}'"),
+            Codigo::EsperadaRutaInclusion => ("P002",
+                "Esperada la ruta entre comillas después de 'incluir'",
+                "Expected a quoted path after 'incluir'"),
+            Codigo::EsperadoNombrePrograma => ("P003",
+                "Esperado nombre del programa",
+                "Expected the program name"),
+            Codigo::EsperadoNombreProceso => ("P004",
+                "Esperado nombre del proceso",
+                "Expected the process name"),
+            Codigo::EsperadoNombreParametro => ("P005",
+                "Esperado nombre del parámetro",
+                "Expected the parameter name"),
+            Codigo::EsperadoNombreVariable => ("P006",
+                "Esperado nombre de variable",
+                "Expected a variable name"),
+            Codigo::EsperadoNombreRobot => ("P007",
+                "Esperado nombre del robot",
+                "Expected the robot name"),
+            Codigo::EsperadoTipoDato => ("P008",
+                "Esperado tipo de dato",
+                "Expected a data type"),
+            Codigo::EsperadoTipoArea => ("P009",
+                "Esperado tipo de área",
+                "Expected an area type"),
+            Codigo::TipoRobotNoDefinido => ("P010",
+                "Tipo de robot no definido: {0}",
+                "Undefined robot type: {0}"),
+            Codigo::EsperadoTipoRobot => ("P011",
+                "Esperado tipo de robot después de ':'",
+                "Expected a robot type after ':'"),
+            Codigo::DeclaracionRobotIncompleta => ("P012",
+                "Declaración de robot incompleta",
+                "Incomplete robot declaration"),
+            Codigo::EsperadoDosPuntosRobot => ("P013",
+                "Esperado ':' en declaración de robot",
+                "Expected ':' in robot declaration"),
+            Codigo::FaltaFinPrincipal => ("P014",
+                "Fin de archivo inesperado: falta el 'fin' del bloque principal",
+                "Unexpected end of file: missing the 'fin' of the main block"),
+            Codigo::SeccionRepetida => ("P015",
+                "La sección '{0}' aparece más de una vez",
+                "Section '{0}' appears more than once"),
+            Codigo::SeccionFueraDeOrden => ("P016",
+                "La sección '{0}' debe ir antes de la sección '{1}'",
+                "Section '{0}' must come before section '{1}'"),
+            Codigo::InclusionFueraDeOrden => ("P017",
+                "'incluir' debe ir antes de la sección '{0}'",
+                "'incluir' must come before section '{0}'"),
+            Codigo::SeccionInesperada => ("P018",
+                "Se encontró '{0}'; se esperaba {1}",
+                "Found '{0}'; expected {1}"),
+            Codigo::FaltaBloquePrincipal => ("P019",
+                "Fin de archivo inesperado: falta el bloque principal; se esperaba {0}",
+                "Unexpected end of file: missing the main block; expected {0}"),
+            Codigo::FaltaFinProceso => ("P020",
+                "Falta el 'fin' del proceso '{0}'",
+                "Missing the 'fin' of process '{0}'"),
+            Codigo::FaltaFinRobot => ("P021",
+                "Falta el 'fin' del robot '{0}'",
+                "Missing the 'fin' of robot '{0}'"),
+            Codigo::ControlDesconocido => ("P022",
+                "Instrucción de control desconocida: {0}",
+                "Unknown control statement: {0}"),
+            Codigo::InstruccionNoReconocida => ("P023",
+                "Instrucción no reconocida: {0}",
+                "Unrecognized statement: {0}"),
+            Codigo::EsperadaInstruccion => ("P024",
+                "Se esperaba una instrucción",
+                "Expected a statement"),
+            Codigo::InstruccionSinValor => ("P025",
+                "La instrucción '{0}' no produce un valor y no puede usarse en una expresión",
+                "Statement '{0}' does not produce a value and cannot be used in an expression"),
+            Codigo::ExpresionSimpleInvalida => ("P026",
+                "Expresión simple no válida: {0}",
+                "Invalid simple expression: {0}"),
+            Codigo::EsperadaExpresionSimple => ("P027",
+                "Se esperaba una expresión simple",
+                "Expected a simple expression"),
+            Codigo::OperadorNoReconocido => ("P028",
+                "Operador no reconocido",
+                "Unrecognized operator"),
+            Codigo::OperadorEsperadoEncontrado => ("P029",
+                "Se esperaba operador binario, encontrado: {0}",
+                "Expected a binary operator, found: {0}"),
+            Codigo::EsperadoOperador => ("P030",
+                "Se esperaba operador binario",
+                "Expected a binary operator"),
+            Codigo::EsperadaExpresion => ("P031",
+                "Se esperaba una expresión",
+                "Expected an expression"),
+            Codigo::EsperadoContratoMensaje => ("P032",
+                "Se esperaba 'envia <tipo> a <robot>' o 'recibe <tipo> de <robot>'",
+                "Expected 'envia <type> a <robot>' or 'recibe <type> de <robot>'"),
+            Codigo::DimensionNoConstante => ("P033",
+                "La dimensión {0} del área '{1}' debe ser un número que pueda calcularse al compilar",
+                "Dimension {0} of area '{1}' must be a number that can be computed at compile time"),
+            Codigo::CantidadDeDimensiones => ("P034",
+                "El área '{0}' necesita 4 dimensiones, tiene {1}",
+                "Area '{0}' needs 4 dimensions, it has {1}"),
+            Codigo::ConstanteNoCalculable => ("P035",
+                "El valor de la constante '{0}' debe ser un número que pueda calcularse al compilar",
+                "The value of constant '{0}' must be a number that can be computed at compile time"),
+            Codigo::LongitudDeArreglo => ("P036",
+                "La longitud del arreglo '{0}' debe ser un número mayor que cero que pueda calcularse al compilar",
+                "The length of array '{0}' must be a number greater than zero that can be computed at compile time"),
+            Codigo::AnidamientoExcesivo => ("P037",
+                "Hay más de {0} niveles de bloques o paréntesis anidados",
+                "There are more than {0} levels of nested blocks or parentheses"),
+            Codigo::BloquePrincipalRepetido => ("P038",
+                "El programa tiene un solo bloque principal, y el primero termina en la línea {0}",
+                "A program has a single main block, and the first one ends on line {0}"),
+            Codigo::CodigoDespuesDelPrincipal => ("P039",
+                "Se encontró '{0}' después del 'fin' del bloque principal (línea {1})",
+                "Found '{0}' after the 'fin' of the main block (line {1})"),
+            Codigo::RobotSinArea => ("P040",
+                "El robot '{0}' no tiene asignación de área",
+                "Robot '{0}' has no area assigned"),
+
+            Codigo::InclusionCircular => ("I001",
+                "Inclusión circular de '{0}'",
+                "Circular inclusion of '{0}'"),
+            Codigo::InclusionIlegible => ("I002",
+                "No se pudo leer '{0}': {1}",
+                "Could not read '{0}': {1}"),
+            Codigo::InclusionConDeclaraciones => ("I003",
+                "Un archivo incluido sólo puede declarar procesos",
+                "An included file can only declare processes"),
+
+            Codigo::NombreRepetido => ("S001",
+                "Nombre '{0}' declarado como {1} (línea {2}) y como {3} (línea {4})",
+                "Name '{0}' declared as {1} (line {2}) and as {3} (line {4})"),
+            Codigo::SinRobots => ("S002",
+                "El programa no define ningún robot: falta la sección 'robots'",
+                "The program does not define any robot: the 'robots' section is missing"),
+            Codigo::Recursion => ("S003",
+                "Proceso '{0}' no puede llamarse a sí mismo",
+                "Process '{0}' cannot call itself"),
+            Codigo::AsignacionANoVariable => ("S004",
+                "No se puede asignar a '{0}': es {1}, no una variable (en '{2}')",
+                "Cannot assign to '{0}': it is {1}, not a variable (in '{2}')"),
+            Codigo::VariableNoDeclarada => ("S005",
+                "Variable '{0}' no declarada en '{1}'{2}",
+                "Variable '{0}' not declared in '{1}'{2}"),
+            Codigo::TipoEnAsignacion => ("S006",
+                "Tipo incorrecto en asignación a '{0}': esperado '{1}', encontrado '{2}' (en '{3}')",
+                "Wrong type in assignment to '{0}': expected '{1}', found '{2}' (in '{3}')"),
+            Codigo::OperandosDeTipo => ("S007",
+                "El operador '{0}' espera operandos de tipo '{1}', encontrado '{2}' y '{3}'",
+                "Operator '{0}' expects operands of type '{1}', found '{2}' and '{3}'"),
+            Codigo::ComparacionDeTipos => ("S008",
+                "El operador '{0}' compara valores de distinto tipo: '{1}' y '{2}'",
+                "Operator '{0}' compares values of different types: '{1}' and '{2}'"),
+            Codigo::NoEsVariable => ("S009",
+                "'{0}' es {1}, no una variable (en '{2}')",
+                "'{0}' is {1}, not a variable (in '{2}')"),
+            Codigo::VariableNoDeclaradaEnExpresion => ("S010",
+                "Variable '{0}' no declarada en expresión (en '{1}'){2}",
+                "Variable '{0}' not declared in expression (in '{1}'){2}"),
+            Codigo::TipoDeCondicion => ("S011",
+                "La condición de '{0}' debe ser de tipo '{1}', pero es de tipo '{2}' (en '{3}')",
+                "The condition of '{0}' must be of type '{1}', but it is of type '{2}' (in '{3}')"),
+            Codigo::ArgumentoDeCategoria => ("S012",
+                "El argumento {0} de '{1}' debe ser {2}, pero '{3}' es {4} (en '{5}')",
+                "Argument {0} of '{1}' must be {2}, but '{3}' is {4} (in '{5}')"),
+            Codigo::ArgumentoInvalido => ("S013",
+                "El argumento {0} de '{1}' debe ser {2} (en '{3}')",
+                "Argument {0} of '{1}' must be {2} (in '{3}')"),
+            Codigo::CantidadDeArgumentos => ("S014",
+                "'{0}' {1}, recibió {2} (en '{3}')",
+                "'{0}' {1}, got {2} (in '{3}')"),
+            Codigo::ArgumentoNoVariable => ("S015",
+                "El argumento {0} de '{1}' debe ser una variable: el parámetro '{2}' es {3} (en '{4}')",
+                "Argument {0} of '{1}' must be a variable: parameter '{2}' is {3} (in '{4}')"),
+            Codigo::TipoDeArgumento => ("S016",
+                "El argumento {0} de '{1}' debe ser de tipo '{2}', pero es de tipo '{3}' (en '{4}')",
+                "Argument {0} of '{1}' must be of type '{2}', but it is of type '{3}' (in '{4}')"),
+            Codigo::MensajeDeTipoIncompatible => ("S017",
+                "Mensaje de '{0}' a '{1}': {2} es de tipo '{3}', pero {4} es de tipo '{5}'",
+                "Message from '{0}' to '{1}': {2} is of type '{3}', but {4} is of type '{5}'"),
+            Codigo::ContratoConRobotDesconocido => ("S018",
+                "El bloque 'mensajes' de '{0}' nombra a '{1}', que no es un robot ni un tipo de robot",
+                "The 'mensajes' block of '{0}' names '{1}', which is neither a robot nor a robot type"),
+            Codigo::CasoNoConstante => ("S019",
+                "El valor de un 'caso' debe poder calcularse al compilar (en '{0}')",
+                "The value of a 'caso' must be computable at compile time (in '{0}')"),
+            Codigo::CasoDeTipo => ("S020",
+                "El caso {0} es de tipo '{1}', pero el valor de 'segun' es de tipo '{2}' (en '{3}')",
+                "Case {0} is of type '{1}', but the 'segun' value is of type '{2}' (in '{3}')"),
+            Codigo::CasoRepetido => ("S021",
+                "El caso {0} ya aparece en la línea {1} (en '{2}')",
+                "Case {0} already appears on line {1} (in '{2}')"),
+            Codigo::LimiteDePara => ("S022",
+                "Los límites de 'para' deben ser de tipo 'numero', pero éste es de tipo '{0}' (en '{1}')",
+                "The bounds of 'para' must be of type 'numero', but this one is of type '{0}' (in '{1}')"),
+            Codigo::VariableDeParaDeclarada => ("S023",
+                "'{0}' ya es {1}: la variable de 'para' debe tener un nombre nuevo (en '{2}')",
+                "'{0}' is already {1}: the 'para' variable needs a new name (in '{2}')"),
+            Codigo::VariableDeParaModificada => ("S024",
+                "La variable '{0}' de 'para' no puede modificarse dentro del ciclo (en '{1}')",
+                "The 'para' variable '{0}' cannot be modified inside the loop (in '{1}')"),
+            Codigo::LlamadaSinValor => ("S025",
+                "'{0}' no es un proceso que devuelva un valor (en '{1}')",
+                "'{0}' is not a process that returns a value (in '{1}')"),
+            Codigo::ParametroDeSalidaEnFuncion => ("S026",
+                "El proceso '{0}' devuelve un valor, así que sus parámetros deben ser E, pero '{1}' es {2}",
+                "Process '{0}' returns a value, so its parameters must be E, but '{1}' is {2}"),
+            Codigo::ArregloSinIndice => ("S027",
+                "'{0}' es un arreglo: se usa de a un elemento, como '{0}[1]' (en '{1}')",
+                "'{0}' is an array: it is used one element at a time, like '{0}[1]' (in '{1}')"),
+            Codigo::IndiceDeNoArreglo => ("S028",
+                "'{0}' no es un arreglo, así que no lleva índice (en '{1}')",
+                "'{0}' is not an array, so it takes no index (in '{1}')"),
+            Codigo::IndiceDeTipo => ("S029",
+                "El índice de '{0}' debe ser de tipo 'numero', pero es de tipo '{1}'",
+                "The index of '{0}' must be of type 'numero', but it is of type '{1}'"),
+            Codigo::IndiceFueraDeRango => ("S030",
+                "El índice {0} está fuera del arreglo '{1}', que va de 1 a {2} (en '{3}')",
+                "Index {0} is outside array '{1}', which goes from 1 to {2} (in '{3}')"),
+            Codigo::RobotSinIniciar => ("S031",
+                "El robot '{0}' nunca se inicia: falta 'Iniciar({0}, ...)' en el programa principal",
+                "Robot '{0}' is never started: 'Iniciar({0}, ...)' is missing from the main program"),
+            Codigo::IniciarRepetido => ("S032",
+                "El robot '{0}' ya se inició en la línea {1}",
+                "Robot '{0}' was already started on line {1}"),
+            Codigo::AreaDespuesDeIniciar => ("S033",
+                "Se asigna un área a '{0}' después de iniciarlo en la línea {1}: 'Iniciar' va después de todos sus 'AsignarArea'",
+                "An area is assigned to '{0}' after starting it on line {1}: 'Iniciar' goes after all its 'AsignarArea'"),
+            Codigo::NegacionDeTipo => ("S034",
+                "El operador '~' espera un operando de tipo 'booleano', encontrado '{0}'",
+                "Operator '~' expects an operand of type 'booleano', found '{0}'"),
+            Codigo::ProcesoNoDeclarado => ("S035",
+                "Proceso '{0}' no declarado (en '{1}')",
+                "Process '{0}' not declared (in '{1}')"),
+            Codigo::RetornoSinAsignar => ("S036",
+                "El proceso '{0}' devuelve un valor de tipo '{1}', pero nunca lo asigna: falta '{0} := ...'",
+                "Process '{0}' returns a value of type '{1}', but never assigns it: '{0} := ...' is missing"),
+
+            Codigo::CoordenadaFueraDeCiudad => ("A001",
+                "La coordenada {0} de '{1}' está fuera de la ciudad (1 a {2})",
+                "Coordinate {0} of '{1}' is outside the city (1 to {2})"),
+            Codigo::RepetirNegativo => ("A002",
+                "'repetir' con una cantidad negativa ({0}) no ejecuta su cuerpo",
+                "'repetir' with a negative count ({0}) does not run its body"),
+            Codigo::DivisionPorCero => ("A003",
+                "División por cero",
+                "Division by zero"),
+            Codigo::SinInicializar => ("A004",
+                "Variable '{0}' usada sin inicializar (en '{1}', línea {2})",
+                "Variable '{0}' used before being initialized (in '{1}', line {2})"),
+            Codigo::PosiblementeSinInicializar => ("A005",
+                "Variable '{0}' posiblemente sin inicializar (en '{1}', línea {2})",
+                "Variable '{0}' possibly used before being initialized (in '{1}', line {2})"),
+            Codigo::AnidamientoProfundo => ("A006",
+                "{0} estructuras de control anidadas, más que el máximo de {1}",
+                "{0} nested control structures, more than the maximum of {1}"),
+            Codigo::NumeroMagico => ("A007",
+                "La coordenada {0} de '{1}' está escrita como número; conviene un parámetro o una variable",
+                "Coordinate {0} of '{1}' is written as a number; prefer a parameter or a variable"),
+            Codigo::SiVacio => ("A008",
+                "'si' sin instrucciones cuando la condición es verdadera",
+                "'si' with no instructions when the condition is true"),
+            Codigo::ProcesoLargo => ("A009",
+                "El proceso tiene {0} instrucciones, más que el máximo de {1}; conviene dividirlo",
+                "The process has {0} instructions, more than the maximum of {1}; consider splitting it"),
+            Codigo::SegunSinSino => ("A010",
+                "'segun' sin 'sino' que no cubre todos los valores: si no coincide ningún caso no se ejecuta nada",
+                "'segun' without 'sino' does not cover every value: if no case matches nothing runs"),
+            Codigo::SaleDelArea => ("A011",
+                "'{0}' sale de sus áreas al moverse a ({1}, {2})",
+                "'{0}' leaves its areas when moving to ({1}, {2})"),
+            Codigo::BolsaVacia => ("A012",
+                "'{0}' se ejecuta siempre sin nada para depositar en la bolsa",
+                "'{0}' always runs with nothing to deposit in the bag"),
+            Codigo::TomarSinVerificar => ("A013",
+                "'{0}' dentro de un ciclo sin verificar antes '{1}': la esquina puede quedar vacía en alguna vuelta",
+                "'{0}' inside a loop without checking '{1}' first: the corner may run out on some iteration"),
+
+            Codigo::FormatoDeMundo => ("M001",
+                "Se esperaba '<objeto> <av> <ca> <cantidad>': '{0}'",
+                "Expected '<object> <av> <ca> <amount>': '{0}'"),
+            Codigo::NumeroInvalido => ("M002",
+                "Número inválido: '{0}'",
+                "Invalid number: '{0}'"),
+            Codigo::CantidadInvalida => ("M003",
+                "Cantidad inválida: '{0}'",
+                "Invalid amount: '{0}'"),
+            Codigo::EsquinaFueraDeCiudad => ("M004",
+                "Esquina fuera de la ciudad: '{0}'",
+                "Corner outside the city: '{0}'"),
+            Codigo::ObjetoDesconocido => ("M005",
+                "Objeto desconocido: '{0}'",
+                "Unknown object: '{0}'"),
+            Codigo::AfirmacionDesconocida => ("M006",
+                "Afirmación desconocida: '{0}'",
+                "Unknown assertion: '{0}'"),
+            Codigo::FormatoDeConfiguracion => ("M007",
+                "Se esperaba '<regla> = allow|warn|deny [<límite>]': '{0}'",
+                "Expected '<rule> = allow|warn|deny [<limit>]': '{0}'"),
+            Codigo::NivelDesconocido => ("M008",
+                "Nivel desconocido, se admite 'allow', 'warn' o 'deny': '{0}'",
+                "Unknown level, expected 'allow', 'warn' or 'deny': '{0}'"),
+            Codigo::ReglaDesconocida => ("M009",
+                "Regla desconocida: '{0}' (las reglas son {1})",
+                "Unknown rule: '{0}' (the rules are {1})"),
+            Codigo::FormatoDeProyecto => ("M010",
+                "Se esperaba '[<sección>]' o '<clave> = <valor>': '{0}'",
+                "Expected '[<section>]' or '<key> = <value>': '{0}'"),
+            Codigo::ClaveDesconocida => ("M011",
+                "Clave desconocida en la sección '{0}': '{1}'",
+                "Unknown key in section '{0}': '{1}'"),
+            Codigo::ValorInvalido => ("M012",
+                "Valor inválido para '{0}': {1}",
+                "Invalid value for '{0}': {1}"),
+        }
+    }
+
+    pub fn codigo(self) -> &'static str {
+        self.entrada().0
+    }
+
+    pub fn plantilla(self, idioma: Idioma) -> &'static str {
+        let (_, espanol, ingles) = self.entrada();
+        match idioma {
+            Idioma::Espanol => espanol,
+            Idioma::Ingles => ingles,
+        }
+    }
+}
+
+// Partes de un diagnóstico que también se traducen pero no son un
+// diagnóstico por sí solas: palabras que se insertan en otro mensaje, notas,
+// ayudas y la forma de indicar dónde ocurrió.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Termino {
+    // `archivo:línea:columna: mensaje`
+    Ubicado,
+    Nota,
+    Advertencia,
+    Ayuda,
+    // Mensaje seguido de la sección donde ocurrió, y de la línea
+    EnSeccion,
+    EnLinea,
+    EnCondicion,
+    Alternativas,
+    Proceso,
+    Area,
+    TipoRobot,
+    Robot,
+    Constante,
+    Programa,
+    Parametro,
+    Variable,
+    UnaVariable,
+    UnRobot,
+    UnArea,
+    UnProceso,
+    UnaConstante,
+    DeTipoNumero,
+    UnValor,
+    UnRobotDeclarado,
+    UnRobotDeclaradoOTodos,
+    UnAreaDeclarada,
+    SinArgumentos,
+    UnArgumento,
+    Argumentos,
+    AlMenosUnArgumento,
+    AlMenosArgumentos,
+    VariableDeRobot,
+    VariableDeRobots,
+    PrimeraDeclaracion,
+    TambienSeUsaAqui,
+    QuisoDecir,
+    DeclararVariable,
+    ValorEnviado,
+    VariableReceptora,
+    ContratoDe,
+    TipoDelMensaje,
+    ReglaRechazada,
+}
+
+impl Termino {
+    pub fn plantilla(self, idioma: Idioma) -> &'static str {
+        let (espanol, ingles) = match self {
+            Termino::Ubicado => ("{0}: {1}", "{0}: {1}"),
+            Termino::Nota => ("nota: {0}", "note: {0}"),
+            Termino::Advertencia => ("advertencia: {0}", "warning: {0}"),
+            Termino::Ayuda => ("ayuda: {0}", "help: {0}"),
+            Termino::EnSeccion => ("{0} (en '{1}')", "{0} (in '{1}')"),
+            Termino::EnLinea => ("{0} (en '{1}', línea {2})", "{0} (in '{1}', line {2})"),
+            Termino::EnCondicion => ("{0} en la condición de '{1}' (en '{2}')", "{0} in the condition of '{1}' (in '{2}')"),
+            Termino::Alternativas => ("{0} o {1}", "{0} or {1}"),
+            Termino::Proceso => ("proceso", "process"),
+            Termino::Area => ("área", "area"),
+            Termino::TipoRobot => ("tipo de robot", "robot type"),
+            Termino::Robot => ("robot", "robot"),
+            Termino::Constante => ("constante", "constant"),
+            Termino::Programa => ("nombre del programa", "program name"),
+            Termino::Parametro => ("parámetro", "parameter"),
+            Termino::Variable => ("variable", "variable"),
+            Termino::UnaVariable => ("una variable", "a variable"),
+            Termino::UnRobot => ("un robot", "a robot"),
+            Termino::UnArea => ("un área", "an area"),
+            Termino::UnProceso => ("un proceso", "a process"),
+            Termino::UnaConstante => ("una constante", "a constant"),
+            Termino::DeTipoNumero => ("de tipo 'numero'", "of type 'numero'"),
+            Termino::UnValor => ("un valor", "a value"),
+            Termino::UnRobotDeclarado => ("un robot declarado", "a declared robot"),
+            Termino::UnRobotDeclaradoOTodos => ("un robot declarado o '*'", "a declared robot or '*'"),
+            Termino::UnAreaDeclarada => ("un área declarada", "a declared area"),
+            Termino::SinArgumentos => ("no lleva argumentos", "takes no arguments"),
+            Termino::UnArgumento => ("espera 1 argumento", "expects 1 argument"),
+            Termino::Argumentos => ("espera {0} argumentos", "expects {0} arguments"),
+            Termino::AlMenosUnArgumento => ("espera al menos 1 argumento", "expects at least 1 argument"),
+            Termino::AlMenosArgumentos => ("espera al menos {0} argumentos", "expects at least {0} arguments"),
+            Termino::VariableDeRobot => (
+                ": es una variable del robot '{0}', que los procesos no ven; debe pasarse como parámetro",
+                ": it is a variable of robot '{0}', which processes cannot see; it must be passed as a parameter",
+            ),
+            Termino::VariableDeRobots => (
+                ": es una variable de los robots '{0}', que los procesos no ven; debe pasarse como parámetro",
+                ": it is a variable of robots '{0}', which processes cannot see; it must be passed as a parameter",
+            ),
+            Termino::PrimeraDeclaracion => ("primera declaración, como {0}", "first declared here, as {0}"),
+            Termino::TambienSeUsaAqui => ("también se usa aquí", "also used here"),
+            Termino::QuisoDecir => ("¿se quiso decir '{0}'?", "did you mean '{0}'?"),
+            Termino::DeclararVariable => (
+                "declarar '{0}' en la sección 'variables' de '{1}'",
+                "declare '{0}' in the 'variables' section of '{1}'",
+            ),
+            Termino::ValorEnviado => ("el valor enviado", "the sent value"),
+            Termino::VariableReceptora => ("la variable '{0}'", "variable '{0}'"),
+            Termino::ContratoDe => ("el bloque 'mensajes' de '{0}'", "the 'mensajes' block of '{0}'"),
+            Termino::TipoDelMensaje => ("{0} es de tipo '{1}'", "{0} is of type '{1}'"),
+            Termino::ReglaRechazada => ("la regla '{0}' está configurada como 'deny'", "rule '{0}' is set to 'deny'"),
+        };
+        match idioma {
+            Idioma::Espanol => espanol,
+            Idioma::Ingles => ingles,
+        }
+    }
+}
+
+// Texto de un diagnóstico sin traducir todavía: una entrada del catálogo con
+// sus argumentos, que a su vez pueden ser mensajes. Se traduce recién al
+// mostrarlo, así el mismo error se puede mostrar en cualquier idioma.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mensaje {
+    // Texto que no se traduce: nombres del programa, valores, rutas
+    Texto(String),
+    Diagnostico(Codigo, Vec<Mensaje>),
+    Termino(Termino, Vec<Mensaje>),
+}
+
+impl Mensaje {
+    pub fn new(codigo: Codigo) -> Self {
+        Mensaje::Diagnostico(codigo, Vec::new())
+    }
+
+    pub fn termino(termino: Termino) -> Self {
+        Mensaje::Termino(termino, Vec::new())
+    }
+
+    // Agrega el siguiente argumento de la plantilla
+    pub fn con(mut self, argumento: impl Into<Mensaje>) -> Self {
+        if let Mensaje::Diagnostico(_, argumentos) | Mensaje::Termino(_, argumentos) = &mut self {
+            argumentos.push(argumento.into());
+        }
+        self
+    }
+
+    // Código del diagnóstico; el de un término es el del mensaje que
+    // acompaña, como en "<mensaje> (en 'r')"
+    pub fn codigo(&self) -> Option<Codigo> {
+        match self {
+            Mensaje::Texto(_) => None,
+            Mensaje::Diagnostico(codigo, _) => Some(*codigo),
+            Mensaje::Termino(_, argumentos) => argumentos.iter().find_map(Mensaje::codigo),
+        }
+    }
+
+    pub fn traducir(&self, idioma: Idioma) -> String {
+        match self {
+            Mensaje::Texto(texto) => texto.clone(),
+            Mensaje::Diagnostico(codigo, argumentos) => completar(codigo.plantilla(idioma), argumentos, idioma),
+            Mensaje::Termino(termino, argumentos) => completar(termino.plantilla(idioma), argumentos, idioma),
+        }
+    }
+}
+
+// Reemplaza cada `{n}` de la plantilla por el argumento n traducido; el
+// resto de las llaves se copian tal cual
+fn completar(plantilla: &str, argumentos: &[Mensaje], idioma: Idioma) -> String {
+    let mut texto = String::with_capacity(plantilla.len());
+    let mut resto = plantilla;
+    while let Some(inicio) = resto.find('{') {
+        texto.push_str(&resto[..inicio]);
+        let despues = &resto[inicio + 1..];
+        let argumento = despues.find('}')
+            .and_then(|fin| Some((fin, despues[..fin].parse::<usize>().ok()?)));
+        match argumento {
+            Some((fin, indice)) => {
+                if let Some(argumento) = argumentos.get(indice) {
+                    texto.push_str(&argumento.traducir(idioma));
+                }
+                resto = &despues[fin + 1..];
+            }
+            None => {
+                texto.push('{');
+                resto = despues;
+            }
+        }
+    }
+    texto.push_str(resto);
+    texto
+}
+
+// En español, el idioma original de los diagnósticos
+impl fmt::Display for Mensaje {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.traducir(Idioma::Espanol))
+    }
+}
+
+impl PartialEq<str> for Mensaje {
+    fn eq(&self, otro: &str) -> bool {
+        self.traducir(Idioma::Espanol) == otro
+    }
+}
+
+impl PartialEq<&str> for Mensaje {
+    fn eq(&self, otro: &&str) -> bool {
+        self == *otro
+    }
+}
+
+impl From<Codigo> for Mensaje {
+    fn from(codigo: Codigo) -> Self {
+        Mensaje::new(codigo)
+    }
+}
+
+impl From<Termino> for Mensaje {
+    fn from(termino: Termino) -> Self {
+        Mensaje::termino(termino)
+    }
+}
+
+impl From<String> for Mensaje {
+    fn from(texto: String) -> Self {
+        Mensaje::Texto(texto)
+    }
+}
+
+impl From<&str> for Mensaje {
+    fn from(texto: &str) -> Self {
+        Mensaje::Texto(texto.to_string())
+    }
+}
+
+impl From<&String> for Mensaje {
+    fn from(texto: &String) -> Self {
+        Mensaje::Texto(texto.clone())
+    }
+}
+
+impl From<char> for Mensaje {
+    fn from(caracter: char) -> Self {
+        Mensaje::Texto(caracter.to_string())
+    }
+}
+
+impl From<usize> for Mensaje {
+    fn from(numero: usize) -> Self {
+        Mensaje::Texto(numero.to_string())
+    }
+}
+
+impl From<i32> for Mensaje {
+    fn from(numero: i32) -> Self {
+        Mensaje::Texto(numero.to_string())
+    }
+}
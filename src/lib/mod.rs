@@ -1,4 +1,34 @@
 pub mod lexer;
 pub mod parser;
 pub mod semanticizer;
-pub mod compilerError;
\ No newline at end of file
+pub mod interpreter;
+pub mod compilerError;
+pub mod error;
+pub mod mensajes;
+pub mod driver;
+pub mod eventos;
+pub mod source;
+pub mod interner;
+pub mod incremental;
+pub mod resaltado;
+pub mod listado;
+pub mod lote;
+pub mod proyecto;
+pub mod bench;
+pub mod testgen;
+pub mod watch;
+pub mod fuzz;
+pub mod azar;
+pub(crate) mod traza;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "node")]
+pub mod node;
+
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(feature = "svg")]
+pub mod render;
\ No newline at end of file
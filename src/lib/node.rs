@@ -0,0 +1,136 @@
+use std::path::Path;
+use napi::bindgen_prelude::*;
+use napi::{Env, JsUnknown, Task};
+use napi_derive::napi;
+use crate::driver::{compilar_archivo_con, Compilacion, Opciones, NOMBRE_ENTRADA};
+use crate::interpreter::reporte::Reporte;
+use crate::interpreter::simulation::Simulation;
+use crate::interpreter::world::Ciudad;
+use crate::semanticizer::analizer::AnalysisSummary;
+use crate::semanticizer::lints::ConfiguracionLints;
+use crate::semanticizer::metricas::Metricas;
+
+// Pasos que ejecuta `run` si no se indica `maxSteps`
+const PASOS_POR_DEFECTO: u32 = 1_000_000;
+
+fn a_napi(error: impl std::fmt::Display) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+// Opciones de `compile`, `analyze` y `run`; las que faltan toman el valor
+// por defecto del compilador
+#[napi(object)]
+#[derive(Default)]
+pub struct CompileOptions {
+    pub flexible_sections: Option<bool>,
+    pub extensions: Option<bool>,
+    // Contenido de un archivo de `--lint-config`
+    pub lint_config: Option<String>,
+}
+
+impl CompileOptions {
+    pub fn opciones(&self) -> Result<Opciones> {
+        let lints = match &self.lint_config {
+            Some(texto) => ConfiguracionLints::desde_texto(texto).map_err(a_napi)?,
+            None => ConfiguracionLints::default(),
+        };
+        Ok(Opciones {
+            secciones_flexibles: self.flexible_sections.unwrap_or(false),
+            extensiones: self.extensions.unwrap_or(false),
+            lints,
+            ..Opciones::default()
+        })
+    }
+}
+
+pub fn compilar_con<'src>(source: &'src str, opciones: Option<&CompileOptions>) -> Result<Compilacion<'src>> {
+    let opciones = opciones.map_or(Ok(Opciones::default()), CompileOptions::opciones)?;
+    Ok(compilar_archivo_con(source, Path::new(NOMBRE_ENTRADA), opciones))
+}
+
+// Compila el código fuente y devuelve tokens, AST y diagnósticos, como
+// `compile` de WebAssembly
+#[napi]
+pub fn compile(env: Env, source: String, options: Option<CompileOptions>) -> Result<JsUnknown> {
+    env.to_js_value(&compilar_con(&source, options.as_ref())?)
+}
+
+// Errores, advertencias y métricas del programa, sin ejecutarlo
+#[napi]
+pub fn analyze(env: Env, source: String, options: Option<CompileOptions>) -> Result<JsUnknown> {
+    env.to_js_value(&resumir(&source, options.as_ref())?)
+}
+
+// Lo que devuelve `analyze`
+pub fn resumir(source: &str, opciones: Option<&CompileOptions>) -> Result<AnalysisSummary> {
+    let compilacion = compilar_con(source, opciones)?;
+    Ok(AnalysisSummary {
+        metricas: compilacion.programa.as_ref().map(Metricas::new).unwrap_or_default(),
+        errores: compilacion.errores,
+        advertencias: compilacion.advertencias,
+        ..AnalysisSummary::default()
+    })
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct RunOptions {
+    pub compile: Option<CompileOptions>,
+    // Contenido de un archivo de mundo, como el de `--mundo`
+    pub world: Option<String>,
+    pub max_steps: Option<u32>,
+}
+
+// Compilación y simulación de un programa en un hilo del pool de Node
+pub struct Ejecucion {
+    source: String,
+    opciones: Opciones,
+    mundo: Option<String>,
+    max_pasos: u32,
+}
+
+impl Ejecucion {
+    // Las opciones de compilación inválidas se informan acá, antes de
+    // pasar al pool
+    pub fn new(source: String, options: RunOptions) -> Result<Self> {
+        let opciones = options.compile.as_ref().map_or(Ok(Opciones::default()), CompileOptions::opciones)?;
+        Ok(Ejecucion {
+            source,
+            opciones,
+            mundo: options.world,
+            max_pasos: options.max_steps.unwrap_or(PASOS_POR_DEFECTO),
+        })
+    }
+}
+
+impl Task for Ejecucion {
+    type Output = Reporte;
+    type JsValue = JsUnknown;
+
+    // Un error de compilación o del mundo rechaza la promesa; uno de
+    // ejecución queda en el reporte, junto con lo que hicieron los robots
+    fn compute(&mut self) -> Result<Reporte> {
+        let opciones = std::mem::take(&mut self.opciones);
+        let programa = compilar_archivo_con(&self.source, Path::new(NOMBRE_ENTRADA), opciones)
+            .resultado()
+            .map_err(a_napi)?;
+        let ciudad = match &self.mundo {
+            Some(texto) => Ciudad::desde_texto(texto).map_err(|e| a_napi(format!("Mundo inválido: {}", e)))?,
+            None => Ciudad::new(),
+        };
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).map_err(a_napi)?;
+        let ejecucion = simulacion.ejecutar(u64::from(self.max_pasos));
+        Ok(Reporte::new(&simulacion, &ejecucion))
+    }
+
+    fn resolve(&mut self, env: Env, reporte: Reporte) -> Result<JsUnknown> {
+        env.to_js_value(&reporte)
+    }
+}
+
+// Ejecuta el programa sin bloquear el hilo de JavaScript y devuelve una
+// promesa con el reporte de la simulación
+#[napi(ts_return_type = "Promise<unknown>")]
+pub fn run(source: String, options: Option<RunOptions>) -> Result<AsyncTask<Ejecucion>> {
+    Ok(AsyncTask::new(Ejecucion::new(source, options.unwrap_or_default())?))
+}
@@ -0,0 +1,226 @@
+use std::ops::Index;
+use crate::interner::Symbol;
+use crate::source::Span;
+use super::processor::{Alternativa, Caso, Expresion, Instruccion};
+
+// Índice de una instrucción dentro del `Arena` de su programa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstrId(u32);
+
+// Índice de una expresión dentro del `Arena` de su programa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExprId(u32);
+
+// Nodos del AST de un programa guardados en vectores planos. Los hijos de
+// cada nodo son índices a este arena en lugar de `Box` o vectores propios:
+// el árbol se construye con dos vectores y se recorre sin copiar nodos. El
+// tramo de código de cada nodo se guarda aparte, en la misma posición.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arena {
+    instrucciones: Vec<Instruccion>,
+    expresiones: Vec<Expresion>,
+    spans_instrucciones: Vec<Span>,
+    spans_expresiones: Vec<Span>,
+}
+
+impl ExprId {
+    // Posición de la expresión en su arena, para las tablas que la acompañan
+    pub fn indice(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn agregar_instruccion(&mut self, instruccion: Instruccion, span: Span) -> InstrId {
+        self.instrucciones.push(instruccion);
+        self.spans_instrucciones.push(span);
+        InstrId((self.instrucciones.len() - 1) as u32)
+    }
+
+    pub fn agregar_expresion(&mut self, expresion: Expresion, span: Span) -> ExprId {
+        self.expresiones.push(expresion);
+        self.spans_expresiones.push(span);
+        ExprId((self.expresiones.len() - 1) as u32)
+    }
+
+    pub fn instruccion(&self, id: InstrId) -> &Instruccion {
+        &self.instrucciones[id.0 as usize]
+    }
+
+    pub fn expresion(&self, id: ExprId) -> &Expresion {
+        &self.expresiones[id.0 as usize]
+    }
+
+    // Código que ocupa la instrucción, desde su primer token hasta el último
+    pub fn span_instruccion(&self, id: InstrId) -> Span {
+        self.spans_instrucciones[id.0 as usize]
+    }
+
+    pub fn span_expresion(&self, id: ExprId) -> Span {
+        self.spans_expresiones[id.0 as usize]
+    }
+
+    // Instrucciones de un bloque, en orden
+    pub fn bloque<'a>(&'a self, ids: &'a [InstrId]) -> impl Iterator<Item = &'a Instruccion> {
+        ids.iter().map(|&id| self.instruccion(id))
+    }
+
+    // Todas las instrucciones, también las que ya no cuelgan de ningún bloque
+    pub fn instrucciones(&self) -> impl Iterator<Item = &Instruccion> {
+        self.instrucciones.iter()
+    }
+
+    pub fn cantidad_instrucciones(&self) -> usize {
+        self.instrucciones.len()
+    }
+
+    pub fn cantidad_expresiones(&self) -> usize {
+        self.expresiones.len()
+    }
+}
+
+// Dónde quedaron los nodos de un arena después de anexarlo a otro
+#[derive(Debug, Clone, Copy)]
+pub struct Reubicacion {
+    instrucciones: u32,
+    expresiones: u32,
+}
+
+impl Reubicacion {
+    pub fn instruccion(&self, id: InstrId) -> InstrId {
+        InstrId(id.0 + self.instrucciones)
+    }
+
+    pub fn expresion(&self, id: ExprId) -> ExprId {
+        ExprId(id.0 + self.expresiones)
+    }
+}
+
+impl Arena {
+    // Copia al final de este arena los nodos de `otro`, traduciendo sus
+    // índices, sus símbolos (según `simbolos`, indexado por el símbolo
+    // original) y desplazando sus líneas y tramos en `lineas`
+    pub fn anexar(&mut self, otro: &Arena, simbolos: &[Symbol], lineas: isize) -> Reubicacion {
+        let reubicacion = Reubicacion {
+            instrucciones: self.instrucciones.len() as u32,
+            expresiones: self.expresiones.len() as u32,
+        };
+        let simbolo = |s: &Symbol| simbolos[s.indice()];
+        let linea = |l: &usize| l.saturating_add_signed(lineas);
+        let bloque = |ids: &[InstrId]| ids.iter().map(|&id| reubicacion.instruccion(id)).collect::<Vec<_>>();
+
+        for instruccion in &otro.instrucciones {
+            self.instrucciones.push(match instruccion {
+                Instruccion::Elemental { nombre, linea: l } => {
+                    Instruccion::Elemental { nombre: simbolo(nombre), linea: linea(l) }
+                }
+                Instruccion::Asignacion { variable, indice, valor, linea: l } => Instruccion::Asignacion {
+                    variable: simbolo(variable),
+                    indice: indice.map(|indice| reubicacion.expresion(indice)),
+                    valor: reubicacion.expresion(*valor),
+                    linea: linea(l),
+                },
+                Instruccion::LlamadaFuncion { nombre, argumentos, linea: l } => Instruccion::LlamadaFuncion {
+                    nombre: simbolo(nombre),
+                    argumentos: argumentos.iter().map(|&id| reubicacion.expresion(id)).collect(),
+                    linea: linea(l),
+                },
+                Instruccion::Si { condicion, entonces, alternativas, sino, linea: l } => Instruccion::Si {
+                    condicion: reubicacion.expresion(*condicion),
+                    entonces: bloque(entonces),
+                    alternativas: alternativas.iter().map(|alternativa| Alternativa {
+                        condicion: reubicacion.expresion(alternativa.condicion),
+                        cuerpo: bloque(&alternativa.cuerpo),
+                        linea: linea(&alternativa.linea),
+                    }).collect(),
+                    sino: bloque(sino),
+                    linea: linea(l),
+                },
+                Instruccion::Mientras { condicion, cuerpo, linea: l } => Instruccion::Mientras {
+                    condicion: reubicacion.expresion(*condicion),
+                    cuerpo: bloque(cuerpo),
+                    linea: linea(l),
+                },
+                Instruccion::Repetir { condicion, cuerpo, linea: l } => Instruccion::Repetir {
+                    condicion: reubicacion.expresion(*condicion),
+                    cuerpo: bloque(cuerpo),
+                    linea: linea(l),
+                },
+                Instruccion::Para { variable, desde, hasta, cantidad, siguiente, cuerpo, linea: l } => Instruccion::Para {
+                    variable: simbolo(variable),
+                    desde: reubicacion.expresion(*desde),
+                    hasta: reubicacion.expresion(*hasta),
+                    cantidad: reubicacion.expresion(*cantidad),
+                    siguiente: reubicacion.expresion(*siguiente),
+                    cuerpo: bloque(cuerpo),
+                    linea: linea(l),
+                },
+                Instruccion::Segun { selector, casos, sino, linea: l } => Instruccion::Segun {
+                    selector: reubicacion.expresion(*selector),
+                    casos: casos.iter().map(|caso| Caso {
+                        valor: reubicacion.expresion(caso.valor),
+                        comparacion: reubicacion.expresion(caso.comparacion),
+                        cuerpo: bloque(&caso.cuerpo),
+                        linea: linea(&caso.linea),
+                    }).collect(),
+                    sino: bloque(sino),
+                    linea: linea(l),
+                },
+                Instruccion::Error { linea: l } => Instruccion::Error { linea: linea(l) },
+            });
+        }
+
+        for expresion in &otro.expresiones {
+            self.expresiones.push(match expresion {
+                Expresion::Sensor { nombre } => Expresion::Sensor { nombre: simbolo(nombre) },
+                Expresion::Identificador(nombre) => Expresion::Identificador(simbolo(nombre)),
+                Expresion::Numero(n) => Expresion::Numero(*n),
+                Expresion::Booleano(b) => Expresion::Booleano(*b),
+                Expresion::Cadena(texto) => Expresion::Cadena(texto.clone()),
+                Expresion::Todos => Expresion::Todos,
+                Expresion::Binaria { izquierda, operador, derecha } => Expresion::Binaria {
+                    izquierda: reubicacion.expresion(*izquierda),
+                    operador: operador.clone(),
+                    derecha: reubicacion.expresion(*derecha),
+                },
+                Expresion::Negacion(operando) => Expresion::Negacion(reubicacion.expresion(*operando)),
+                Expresion::Llamada { nombre, argumentos } => Expresion::Llamada {
+                    nombre: simbolo(nombre),
+                    argumentos: argumentos.iter().map(|argumento| reubicacion.expresion(*argumento)).collect(),
+                },
+                Expresion::Indice { arreglo, indice } => Expresion::Indice {
+                    arreglo: simbolo(arreglo),
+                    indice: reubicacion.expresion(*indice),
+                },
+            });
+        }
+
+        self.spans_instrucciones.extend(otro.spans_instrucciones.iter().map(|span| span.desplazar(lineas)));
+        self.spans_expresiones.extend(otro.spans_expresiones.iter().map(|span| span.desplazar(lineas)));
+
+        reubicacion
+    }
+}
+
+impl Index<InstrId> for Arena {
+    type Output = Instruccion;
+
+    fn index(&self, id: InstrId) -> &Instruccion {
+        self.instruccion(id)
+    }
+}
+
+impl Index<ExprId> for Arena {
+    type Output = Expresion;
+
+    fn index(&self, id: ExprId) -> &Expresion {
+        self.expresion(id)
+    }
+}
@@ -1,4 +1,10 @@
 // Definiciones de AST
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use super::arena::{Arena, ExprId, InstrId};
+use crate::interner::Symbol;
+use super::processor::{Expresion, Instruccion, Proceso, Program, Robot, SentidoMensaje, Variable};
 #[derive(Debug, Clone)]
 pub enum ASTNode {
     Program {
@@ -92,4 +98,477 @@ pub struct Parameter {
 #[derive(Debug, Clone)]
 pub struct Condition {
     pub expression: String,
-}
\ No newline at end of file
+}
+
+// Recorrido del AST. Cada método tiene una implementación por defecto que
+// visita los hijos del nodo; quien implementa el trait sobreescribe sólo los
+// nodos que le interesan y llama a la función `recorrer_*` correspondiente
+// para seguir bajando. Los hijos se buscan en el arena que devuelve `arena`,
+// que tiene que ser el del programa que se recorre.
+pub trait Visitor<'ast> {
+    fn arena(&self) -> &'ast Arena;
+
+    fn visitar_programa(&mut self, programa: &'ast Program) {
+        recorrer_programa(self, programa);
+    }
+
+    fn visitar_proceso(&mut self, proceso: &'ast Proceso) {
+        recorrer_proceso(self, proceso);
+    }
+
+    fn visitar_robot(&mut self, robot: &'ast Robot) {
+        recorrer_robot(self, robot);
+    }
+
+    fn visitar_bloque(&mut self, instrucciones: &'ast [InstrId]) {
+        recorrer_bloque(self, instrucciones);
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'ast Instruccion) {
+        recorrer_instruccion(self, instruccion);
+    }
+
+    fn visitar_expresion(&mut self, expresion: &'ast Expresion) {
+        recorrer_expresion(self, expresion);
+    }
+}
+
+pub fn recorrer_programa<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, programa: &'ast Program) {
+    let arena = visitor.arena();
+    for proceso in &programa.procesos {
+        visitor.visitar_proceso(proceso);
+    }
+    for robot in &programa.robots_definidos {
+        visitor.visitar_robot(robot);
+    }
+    for asignacion in &programa.asignaciones_areas {
+        visitor.visitar_expresion(&arena[asignacion.robot]);
+        visitor.visitar_expresion(&arena[asignacion.area]);
+    }
+    for inicializacion in &programa.inicializaciones {
+        visitor.visitar_expresion(&arena[inicializacion.robot]);
+        visitor.visitar_expresion(&arena[inicializacion.pos_x]);
+        visitor.visitar_expresion(&arena[inicializacion.pos_y]);
+    }
+}
+
+pub fn recorrer_proceso<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, proceso: &'ast Proceso) {
+    visitor.visitar_bloque(&proceso.instrucciones);
+}
+
+pub fn recorrer_robot<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, robot: &'ast Robot) {
+    visitor.visitar_bloque(&robot.instrucciones);
+}
+
+pub fn recorrer_bloque<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, instrucciones: &'ast [InstrId]) {
+    let arena = visitor.arena();
+    for instruccion in arena.bloque(instrucciones) {
+        visitor.visitar_instruccion(instruccion);
+    }
+}
+
+// Sin comodín: agregar una variante a `Instruccion` obliga a decidir aquí cómo se recorre
+pub fn recorrer_instruccion<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, instruccion: &'ast Instruccion) {
+    let arena = visitor.arena();
+    match instruccion {
+        Instruccion::Elemental { .. } => {}
+        Instruccion::Asignacion { indice, valor, .. } => {
+            if let Some(indice) = indice {
+                visitor.visitar_expresion(&arena[*indice]);
+            }
+            visitor.visitar_expresion(&arena[*valor]);
+        }
+        Instruccion::LlamadaFuncion { argumentos, .. } => {
+            for argumento in argumentos {
+                visitor.visitar_expresion(&arena[*argumento]);
+            }
+        }
+        Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+            visitor.visitar_expresion(&arena[*condicion]);
+            visitor.visitar_bloque(entonces);
+            for alternativa in alternativas {
+                visitor.visitar_expresion(&arena[alternativa.condicion]);
+                visitor.visitar_bloque(&alternativa.cuerpo);
+            }
+            visitor.visitar_bloque(sino);
+        }
+        Instruccion::Mientras { condicion, cuerpo, .. } | Instruccion::Repetir { condicion, cuerpo, .. } => {
+            visitor.visitar_expresion(&arena[*condicion]);
+            visitor.visitar_bloque(cuerpo);
+        }
+        // Tampoco `cantidad` ni `siguiente`
+        Instruccion::Para { desde, hasta, cuerpo, .. } => {
+            visitor.visitar_expresion(&arena[*desde]);
+            visitor.visitar_expresion(&arena[*hasta]);
+            visitor.visitar_bloque(cuerpo);
+        }
+        // La comparación de cada caso no está escrita en el código
+        Instruccion::Segun { selector, casos, sino, .. } => {
+            visitor.visitar_expresion(&arena[*selector]);
+            for caso in casos {
+                visitor.visitar_expresion(&arena[caso.valor]);
+                visitor.visitar_bloque(&caso.cuerpo);
+            }
+            visitor.visitar_bloque(sino);
+        }
+        // Lo que no se pudo parsear no tiene partes para recorrer
+        Instruccion::Error { .. } => {}
+    }
+}
+
+pub fn recorrer_expresion<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expresion: &'ast Expresion) {
+    let arena = visitor.arena();
+    match expresion {
+        Expresion::Sensor { .. }
+        | Expresion::Identificador(_)
+        | Expresion::Numero(_)
+        | Expresion::Booleano(_)
+        | Expresion::Cadena(_)
+        | Expresion::Todos => {}
+        Expresion::Binaria { izquierda, derecha, .. } => {
+            visitor.visitar_expresion(&arena[*izquierda]);
+            visitor.visitar_expresion(&arena[*derecha]);
+        }
+        Expresion::Negacion(operando) => visitor.visitar_expresion(&arena[*operando]),
+        Expresion::Llamada { argumentos, .. } => {
+            for argumento in argumentos {
+                visitor.visitar_expresion(&arena[*argumento]);
+            }
+        }
+        Expresion::Indice { indice, .. } => visitor.visitar_expresion(&arena[*indice]),
+    }
+}
+
+// Comparación estructural de código de dos programas, por ejemplo dos
+// versiones de una entrega: dos bloques son iguales si tienen las mismas
+// instrucciones con los mismos nombres y expresiones, aunque estén en otras
+// líneas. Cada bloque se busca en el arena y los símbolos de su programa.
+// `==` entre dos `Expresion` compara sus hijos por índice en el arena, así
+// que sólo sirve dentro de un mismo programa.
+pub fn bloques_iguales(izquierdo: &Program, a: &[InstrId], derecho: &Program, b: &[InstrId]) -> bool {
+    Comparador { izquierdo, derecho }.bloques(a, b)
+}
+
+// Como `bloques_iguales`, para el programa entero: sus declaraciones, el
+// cuerpo de cada proceso y robot, y las áreas e inicios del bloque principal
+pub fn programas_iguales(izquierdo: &Program, derecho: &Program) -> bool {
+    Comparador { izquierdo, derecho }.programas()
+}
+
+pub fn expresiones_iguales(izquierdo: &Program, a: ExprId, derecho: &Program, b: ExprId) -> bool {
+    Comparador { izquierdo, derecho }.expresiones(a, b)
+}
+
+struct Comparador<'a> {
+    izquierdo: &'a Program,
+    derecho: &'a Program,
+}
+
+impl Comparador<'_> {
+    fn simbolos(&self, a: Symbol, b: Symbol) -> bool {
+        self.izquierdo.simbolos.resolver(a) == self.derecho.simbolos.resolver(b)
+    }
+
+    fn programas(&self) -> bool {
+        let (a, b) = (self.izquierdo, self.derecho);
+        declaraciones(a) == declaraciones(b)
+            && a.procesos.iter().zip(&b.procesos).all(|(a, b)| self.bloques(&a.instrucciones, &b.instrucciones))
+            && a.robots_definidos.iter().zip(&b.robots_definidos).all(|(a, b)| self.bloques(&a.instrucciones, &b.instrucciones))
+            && a.asignaciones_areas.len() == b.asignaciones_areas.len()
+            && a.asignaciones_areas.iter().zip(&b.asignaciones_areas)
+                .all(|(a, b)| self.lista(&[a.robot, a.area], &[b.robot, b.area]))
+            && a.inicializaciones.len() == b.inicializaciones.len()
+            && a.inicializaciones.iter().zip(&b.inicializaciones)
+                .all(|(a, b)| self.lista(&[a.robot, a.pos_x, a.pos_y], &[b.robot, b.pos_x, b.pos_y]))
+    }
+
+    fn bloques(&self, a: &[InstrId], b: &[InstrId]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.instrucciones(*a, *b))
+    }
+
+    fn lista(&self, a: &[ExprId], b: &[ExprId]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.expresiones(*a, *b))
+    }
+
+    // Se elige la rama por la instrucción de la izquierda, sin comodín, para
+    // que una variante nueva de `Instruccion` obligue a decidir cómo se compara
+    fn instrucciones(&self, a: InstrId, b: InstrId) -> bool {
+        let derecha = &self.derecho.arena[b];
+        match &self.izquierdo.arena[a] {
+            Instruccion::Elemental { nombre, .. } => {
+                matches!(derecha, Instruccion::Elemental { nombre: otro, .. } if self.simbolos(*nombre, *otro))
+            }
+            Instruccion::Asignacion { variable, indice, valor, .. } => {
+                let Instruccion::Asignacion { variable: otra, indice: otro_indice, valor: otro_valor, .. } = derecha else {
+                    return false;
+                };
+                let indices = match (indice, otro_indice) {
+                    (Some(a), Some(b)) => self.expresiones(*a, *b),
+                    (a, b) => a.is_none() && b.is_none(),
+                };
+                self.simbolos(*variable, *otra) && indices && self.expresiones(*valor, *otro_valor)
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, .. } => {
+                let Instruccion::LlamadaFuncion { nombre: otro, argumentos: otros, .. } = derecha else {
+                    return false;
+                };
+                self.simbolos(*nombre, *otro) && self.lista(argumentos, otros)
+            }
+            Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                let Instruccion::Si { condicion: otra, entonces: otro_entonces, alternativas: otras, sino: otro_sino, .. } = derecha else {
+                    return false;
+                };
+                self.expresiones(*condicion, *otra)
+                    && self.bloques(entonces, otro_entonces)
+                    && alternativas.len() == otras.len()
+                    && alternativas.iter().zip(otras).all(|(a, b)| {
+                        self.expresiones(a.condicion, b.condicion) && self.bloques(&a.cuerpo, &b.cuerpo)
+                    })
+                    && self.bloques(sino, otro_sino)
+            }
+            Instruccion::Mientras { condicion, cuerpo, .. } => {
+                let Instruccion::Mientras { condicion: otra, cuerpo: otro, .. } = derecha else {
+                    return false;
+                };
+                self.expresiones(*condicion, *otra) && self.bloques(cuerpo, otro)
+            }
+            Instruccion::Repetir { condicion, cuerpo, .. } => {
+                let Instruccion::Repetir { condicion: otra, cuerpo: otro, .. } = derecha else {
+                    return false;
+                };
+                self.expresiones(*condicion, *otra) && self.bloques(cuerpo, otro)
+            }
+            Instruccion::Segun { selector, casos, sino, .. } => {
+                let Instruccion::Segun { selector: otro, casos: otros, sino: otro_sino, .. } = derecha else {
+                    return false;
+                };
+                self.expresiones(*selector, *otro)
+                    && casos.len() == otros.len()
+                    && casos.iter().zip(otros).all(|(a, b)| self.expresiones(a.valor, b.valor) && self.bloques(&a.cuerpo, &b.cuerpo))
+                    && self.bloques(sino, otro_sino)
+            }
+            // `cantidad` y `siguiente` se derivan de las demás
+            Instruccion::Para { variable, desde, hasta, cuerpo, .. } => {
+                let Instruccion::Para { variable: otra, desde: otro_desde, hasta: otro_hasta, cuerpo: otro, .. } = derecha else {
+                    return false;
+                };
+                self.simbolos(*variable, *otra)
+                    && self.expresiones(*desde, *otro_desde)
+                    && self.expresiones(*hasta, *otro_hasta)
+                    && self.bloques(cuerpo, otro)
+            }
+            Instruccion::Error { .. } => matches!(derecha, Instruccion::Error { .. }),
+        }
+    }
+
+    fn expresiones(&self, a: ExprId, b: ExprId) -> bool {
+        let derecha = &self.derecho.arena[b];
+        match &self.izquierdo.arena[a] {
+            Expresion::Sensor { nombre } => {
+                matches!(derecha, Expresion::Sensor { nombre: otro } if self.simbolos(*nombre, *otro))
+            }
+            Expresion::Identificador(nombre) => {
+                matches!(derecha, Expresion::Identificador(otro) if self.simbolos(*nombre, *otro))
+            }
+            Expresion::Numero(_) | Expresion::Booleano(_) | Expresion::Cadena(_) | Expresion::Todos => {
+                self.izquierdo.arena[a] == *derecha
+            }
+            Expresion::Binaria { izquierda, operador, derecha: segundo } => {
+                let Expresion::Binaria { izquierda: otra, operador: otro_operador, derecha: otro_segundo } = derecha else {
+                    return false;
+                };
+                operador == otro_operador && self.expresiones(*izquierda, *otra) && self.expresiones(*segundo, *otro_segundo)
+            }
+            Expresion::Negacion(operando) => {
+                matches!(derecha, Expresion::Negacion(otro) if self.expresiones(*operando, *otro))
+            }
+            Expresion::Llamada { nombre, argumentos } => {
+                let Expresion::Llamada { nombre: otro, argumentos: otros } = derecha else {
+                    return false;
+                };
+                self.simbolos(*nombre, *otro) && self.lista(argumentos, otros)
+            }
+            Expresion::Indice { arreglo, indice } => {
+                let Expresion::Indice { arreglo: otro, indice: otro_indice } = derecha else {
+                    return false;
+                };
+                self.simbolos(*arreglo, *otro) && self.expresiones(*indice, *otro_indice)
+            }
+        }
+    }
+}
+
+// Hash estructural de un bloque, compatible con `bloques_iguales`: dos
+// bloques iguales tienen la misma huella, aunque sean de programas distintos
+// o estén en otras líneas. Sirve de clave para guardar resultados por
+// subárbol; dos huellas iguales no garantizan que los bloques lo sean.
+pub fn huella_bloque(programa: &Program, bloque: &[InstrId]) -> u64 {
+    let mut huella = Huella { programa, hasher: DefaultHasher::new() };
+    huella.bloque(bloque);
+    huella.hasher.finish()
+}
+
+// Como `huella_bloque`, compatible con `programas_iguales`
+pub fn huella_programa(programa: &Program) -> u64 {
+    let mut huella = Huella { programa, hasher: DefaultHasher::new() };
+    huella.programa();
+    huella.hasher.finish()
+}
+
+// Como `huella_bloque`, compatible con `expresiones_iguales`
+pub fn huella_expresion(programa: &Program, expresion: ExprId) -> u64 {
+    let mut huella = Huella { programa, hasher: DefaultHasher::new() };
+    huella.expresion(expresion);
+    huella.hasher.finish()
+}
+
+// Tiene que recorrer exactamente lo que compara `Comparador`
+struct Huella<'a> {
+    programa: &'a Program,
+    hasher: DefaultHasher,
+}
+
+impl Huella<'_> {
+    fn programa(&mut self) {
+        let programa = self.programa;
+        declaraciones(programa).hash(&mut self.hasher);
+        for seccion in programa.procesos.iter().map(|proceso| &proceso.instrucciones)
+            .chain(programa.robots_definidos.iter().map(|robot| &robot.instrucciones))
+        {
+            self.bloque(seccion);
+        }
+        programa.asignaciones_areas.len().hash(&mut self.hasher);
+        for asignacion in &programa.asignaciones_areas {
+            self.lista(&[asignacion.robot, asignacion.area]);
+        }
+        programa.inicializaciones.len().hash(&mut self.hasher);
+        for inicio in &programa.inicializaciones {
+            self.lista(&[inicio.robot, inicio.pos_x, inicio.pos_y]);
+        }
+    }
+
+    // Los nombres por su texto y no por su símbolo, que depende del programa
+    fn simbolo(&mut self, simbolo: Symbol) {
+        self.programa.simbolos.resolver(simbolo).hash(&mut self.hasher);
+    }
+
+    fn bloque(&mut self, bloque: &[InstrId]) {
+        bloque.len().hash(&mut self.hasher);
+        for instruccion in bloque {
+            self.instruccion(*instruccion);
+        }
+    }
+
+    fn lista(&mut self, expresiones: &[ExprId]) {
+        expresiones.len().hash(&mut self.hasher);
+        for expresion in expresiones {
+            self.expresion(*expresion);
+        }
+    }
+
+    fn instruccion(&mut self, id: InstrId) {
+        let instruccion = &self.programa.arena[id];
+        mem::discriminant(instruccion).hash(&mut self.hasher);
+        match instruccion {
+            Instruccion::Elemental { nombre, .. } => self.simbolo(*nombre),
+            Instruccion::Asignacion { variable, indice, valor, .. } => {
+                self.simbolo(*variable);
+                indice.is_some().hash(&mut self.hasher);
+                if let Some(indice) = indice {
+                    self.expresion(*indice);
+                }
+                self.expresion(*valor);
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, .. } => {
+                self.simbolo(*nombre);
+                self.lista(argumentos);
+            }
+            Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                self.expresion(*condicion);
+                self.bloque(entonces);
+                alternativas.len().hash(&mut self.hasher);
+                for alternativa in alternativas {
+                    self.expresion(alternativa.condicion);
+                    self.bloque(&alternativa.cuerpo);
+                }
+                self.bloque(sino);
+            }
+            Instruccion::Mientras { condicion, cuerpo, .. } | Instruccion::Repetir { condicion, cuerpo, .. } => {
+                self.expresion(*condicion);
+                self.bloque(cuerpo);
+            }
+            Instruccion::Segun { selector, casos, sino, .. } => {
+                self.expresion(*selector);
+                casos.len().hash(&mut self.hasher);
+                for caso in casos {
+                    self.expresion(caso.valor);
+                    self.bloque(&caso.cuerpo);
+                }
+                self.bloque(sino);
+            }
+            Instruccion::Para { variable, desde, hasta, cuerpo, .. } => {
+                self.simbolo(*variable);
+                self.expresion(*desde);
+                self.expresion(*hasta);
+                self.bloque(cuerpo);
+            }
+            Instruccion::Error { .. } => {}
+        }
+    }
+
+    fn expresion(&mut self, id: ExprId) {
+        let expresion = &self.programa.arena[id];
+        mem::discriminant(expresion).hash(&mut self.hasher);
+        match expresion {
+            Expresion::Sensor { nombre } | Expresion::Identificador(nombre) => self.simbolo(*nombre),
+            Expresion::Numero(valor) => valor.hash(&mut self.hasher),
+            Expresion::Booleano(valor) => valor.hash(&mut self.hasher),
+            Expresion::Cadena(texto) => texto.hash(&mut self.hasher),
+            Expresion::Todos => {}
+            Expresion::Binaria { izquierda, operador, derecha } => {
+                operador.hash(&mut self.hasher);
+                self.expresion(*izquierda);
+                self.expresion(*derecha);
+            }
+            Expresion::Negacion(operando) => self.expresion(*operando),
+            Expresion::Llamada { nombre, argumentos } => {
+                self.simbolo(*nombre);
+                self.lista(argumentos);
+            }
+            Expresion::Indice { arreglo, indice } => {
+                self.simbolo(*arreglo);
+                self.expresion(*indice);
+            }
+        }
+    }
+}
+
+// Lo que el programa declara con texto en lugar de nodos del arena, sin las
+// líneas: lo comparan `programas_iguales` y `huella_programa` por igual
+fn declaraciones(programa: &Program) -> impl PartialEq + Hash + '_ {
+    fn variables(variables: &[Variable]) -> Vec<(&str, &str, Option<usize>)> {
+        variables.iter().map(|variable| (variable.nombre.as_str(), variable.tipo_dato.as_str(), variable.longitud)).collect()
+    }
+    (
+        programa.nombre.as_str(),
+        programa.inclusiones.iter().map(|inclusion| inclusion.ruta.as_str()).collect::<Vec<_>>(),
+        programa.constantes.iter().map(|constante| (constante.nombre.as_str(), constante.valor)).collect::<Vec<_>>(),
+        programa.procesos.iter().map(|proceso| (
+            proceso.nombre.as_str(),
+            proceso.parametros.iter()
+                .map(|parametro| (parametro.tipo.as_str(), parametro.nombre.as_str(), parametro.tipo_dato.as_str()))
+                .collect::<Vec<_>>(),
+            variables(&proceso.variables),
+            proceso.retorno.as_deref(),
+        )).collect::<Vec<_>>(),
+        programa.areas.iter().map(|area| (area.nombre.as_str(), area.tipo.as_str(), area.coordenadas)).collect::<Vec<_>>(),
+        programa.robots_definidos.iter().map(|robot| (
+            robot.nombre.as_str(),
+            variables(&robot.variables),
+            robot.mensajes.iter()
+                .map(|contrato| (contrato.sentido == SentidoMensaje::Envia, contrato.tipo_dato.as_str(), contrato.robot.as_str()))
+                .collect::<Vec<_>>(),
+        )).collect::<Vec<_>>(),
+        programa.robots_instanciados.iter().map(|robot| (robot.nombre.as_str(), robot.tipo.as_str())).collect::<Vec<_>>(),
+    )
+}
@@ -0,0 +1,300 @@
+use super::super::lexer::scanner::Lexer;
+use super::super::lexer::token::{TokenType, SECCIONES};
+use crate::source::Span;
+
+// Árbol de sintaxis concreto: a diferencia del AST conserva cada token tal
+// como se escribió, con los espacios, saltos de línea y comentarios entre
+// ellos, así que concatenar sus hojas devuelve exactamente el código. Sirve
+// para que las herramientas que editan el código, como un renombrado, no
+// destruyan el formato del autor. Se arma aparte del AST y sólo si se pide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cst {
+    pub raiz: Nodo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClaseNodo {
+    Programa,
+    // Desde la palabra de una sección hasta la siguiente; el cuerpo del
+    // programa va desde `comenzar` hasta su `fin`
+    Seccion,
+    // Desde `proceso` o `robot` hasta su `fin`
+    Proceso,
+    Robot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClaseTrivia {
+    Espacio,
+    // Un solo salto: `\n`, `\r\n` o `\r`
+    SaltoDeLinea,
+    Comentario,
+    // Texto que el lexer no pudo leer como token
+    Desconocido,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nodo {
+    pub clase: ClaseNodo,
+    pub hijos: Vec<Elemento>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Elemento {
+    Nodo(Nodo),
+    Token(TokenCst),
+    Trivia(Trivia),
+}
+
+// Token con el texto escrito, que no siempre es su valor: una cadena
+// conserva sus comillas y un alias el nombre con que se escribió. El tramo
+// es el del código original, aunque después se reemplace el texto.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenCst {
+    pub tipo: TokenType,
+    pub texto: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trivia {
+    pub clase: ClaseTrivia,
+    pub texto: String,
+}
+
+impl Cst {
+    pub fn new(source: &str) -> Self {
+        Self::con_lexer(source, Lexer::new(source))
+    }
+
+    // Con un lexer ya configurado, por ejemplo el de `Opciones::lexer`. Los
+    // tokens que el lexer no puede leer quedan como trivia `Desconocido`.
+    pub fn con_lexer<'src>(source: &'src str, mut lexer: Lexer<'src>) -> Self {
+        let (tokens, _) = lexer.tokenize_all();
+        let posiciones = Posiciones::new(source);
+        let mut comentarios: Vec<(usize, usize)> = lexer.comentarios().iter()
+            .map(|tramo| (posiciones.byte(tramo.linea, tramo.columna), posiciones.byte(tramo.fin_linea, tramo.fin_columna)))
+            .collect();
+        comentarios.sort();
+
+        // Nodos abiertos, del programa al más interno
+        let mut abiertos = vec![Nodo { clase: ClaseNodo::Programa, hijos: Vec::new() }];
+        let mut posicion = 0;
+        // Los tokens sin texto (INDENT, DEDENT, fin de archivo) no se escriben
+        for token in tokens.iter().filter(|token| token.end_line > token.line || token.end_column > token.column) {
+            let (desde, hasta) = (posiciones.byte(token.line, token.column), posiciones.byte(token.end_line, token.end_column));
+            let hijos = &mut abiertos.last_mut().expect("el programa siempre está abierto").hijos;
+            trivia(&source[posicion..desde], posicion, &comentarios, hijos);
+
+            let en_declaracion = abiertos.iter().any(|nodo| matches!(nodo.clase, ClaseNodo::Proceso | ClaseNodo::Robot));
+            let palabra = (token.token_type == TokenType::Keyword).then_some(token.value);
+            match palabra {
+                Some(seccion) if !en_declaracion && (SECCIONES.contains(&seccion) || seccion == "comenzar") => {
+                    cerrar_hasta(&mut abiertos, 1);
+                    abiertos.push(Nodo { clase: ClaseNodo::Seccion, hijos: Vec::new() });
+                }
+                Some("proceso") if !en_declaracion => abiertos.push(Nodo { clase: ClaseNodo::Proceso, hijos: Vec::new() }),
+                Some("robot") if !en_declaracion => abiertos.push(Nodo { clase: ClaseNodo::Robot, hijos: Vec::new() }),
+                _ => {}
+            }
+
+            let hoja = Elemento::Token(TokenCst {
+                tipo: token.token_type,
+                texto: source[desde..hasta].to_string(),
+                span: token.span(),
+            });
+            abiertos.last_mut().expect("el programa siempre está abierto").hijos.push(hoja);
+            // `fin` cierra el proceso o robot, o el cuerpo del programa
+            let cantidad = abiertos.len();
+            if palabra == Some("fin") && cantidad > 1 {
+                cerrar_hasta(&mut abiertos, cantidad - 1);
+            }
+            posicion = hasta;
+        }
+        cerrar_hasta(&mut abiertos, 1);
+        let mut raiz = abiertos.pop().expect("el programa siempre está abierto");
+        trivia(&source[posicion..], posicion, &comentarios, &mut raiz.hijos);
+        Self { raiz }
+    }
+
+    // El código, con los reemplazos que se hayan hecho
+    pub fn texto(&self) -> String {
+        self.raiz.texto()
+    }
+
+    pub fn tokens(&self) -> Vec<&TokenCst> {
+        let mut tokens = Vec::new();
+        self.raiz.juntar_tokens(&mut tokens);
+        tokens
+    }
+
+    // Cambia el texto del token que empieza en `span`. Como los tramos son
+    // los del código original, se pueden hacer varios reemplazos seguidos
+    // con las posiciones que dio el análisis.
+    pub fn reemplazar(&mut self, span: Span, texto: &str) -> bool {
+        let tokens = self.raiz.tokens_mut(&mut |token| token.span == span);
+        let encontrado = !tokens.is_empty();
+        for token in tokens {
+            token.texto = texto.to_string();
+        }
+        encontrado
+    }
+
+    // Cambia cada identificador `viejo` por `nuevo`, sin mirar su ámbito, y
+    // devuelve cuántos cambió
+    pub fn renombrar(&mut self, viejo: &str, nuevo: &str) -> usize {
+        let tokens = self.raiz.tokens_mut(&mut |token| token.tipo == TokenType::Identifier && token.texto == viejo);
+        let cambiados = tokens.len();
+        for token in tokens {
+            token.texto = nuevo.to_string();
+        }
+        cambiados
+    }
+}
+
+impl Nodo {
+    pub fn texto(&self) -> String {
+        let mut texto = String::new();
+        self.escribir(&mut texto);
+        texto
+    }
+
+    // El primer identificador: el nombre de un proceso o de un robot
+    pub fn nombre(&self) -> Option<&str> {
+        self.hijos.iter().find_map(|hijo| match hijo {
+            Elemento::Token(token) if token.tipo == TokenType::Identifier => Some(token.texto.as_str()),
+            _ => None,
+        })
+    }
+
+    // Los nodos hijos, sin los tokens ni la trivia
+    pub fn nodos(&self) -> impl Iterator<Item = &Nodo> {
+        self.hijos.iter().filter_map(|hijo| match hijo {
+            Elemento::Nodo(nodo) => Some(nodo),
+            _ => None,
+        })
+    }
+
+    fn escribir(&self, texto: &mut String) {
+        for hijo in &self.hijos {
+            match hijo {
+                Elemento::Nodo(nodo) => nodo.escribir(texto),
+                Elemento::Token(token) => texto.push_str(&token.texto),
+                Elemento::Trivia(trivia) => texto.push_str(&trivia.texto),
+            }
+        }
+    }
+
+    fn juntar_tokens<'a>(&'a self, tokens: &mut Vec<&'a TokenCst>) {
+        for hijo in &self.hijos {
+            match hijo {
+                Elemento::Nodo(nodo) => nodo.juntar_tokens(tokens),
+                Elemento::Token(token) => tokens.push(token),
+                Elemento::Trivia(_) => {}
+            }
+        }
+    }
+
+    fn tokens_mut(&mut self, filtro: &mut dyn FnMut(&TokenCst) -> bool) -> Vec<&mut TokenCst> {
+        let mut tokens = Vec::new();
+        for hijo in &mut self.hijos {
+            match hijo {
+                Elemento::Nodo(nodo) => tokens.extend(nodo.tokens_mut(filtro)),
+                Elemento::Token(token) if filtro(token) => tokens.push(token),
+                Elemento::Token(_) | Elemento::Trivia(_) => {}
+            }
+        }
+        tokens
+    }
+}
+
+// Cierra los nodos abiertos hasta que queden `cantidad`, agregando cada uno
+// a su padre
+fn cerrar_hasta(abiertos: &mut Vec<Nodo>, cantidad: usize) {
+    while abiertos.len() > cantidad.max(1) {
+        let nodo = abiertos.pop().expect("hay más de un nodo abierto");
+        abiertos.last_mut().expect("el programa siempre está abierto").hijos.push(Elemento::Nodo(nodo));
+    }
+}
+
+// Separa el texto entre dos tokens, que empieza en el byte `inicio`, en
+// comentarios, espacios, saltos de línea y lo que el lexer no leyó
+fn trivia(texto: &str, inicio: usize, comentarios: &[(usize, usize)], hijos: &mut Vec<Elemento>) {
+    let mut agregar = |clase, texto: &str| {
+        if let (ClaseTrivia::Espacio | ClaseTrivia::Desconocido, Some(Elemento::Trivia(anterior))) = (clase, hijos.last_mut())
+            && anterior.clase == clase
+        {
+            anterior.texto.push_str(texto);
+        } else {
+            hijos.push(Elemento::Trivia(Trivia { clase, texto: texto.to_string() }));
+        }
+    };
+
+    let mut resto = texto;
+    let mut posicion = inicio;
+    while let Some(c) = resto.chars().next() {
+        let largo = match comentarios.iter().find(|(desde, _)| *desde == posicion) {
+            Some((desde, hasta)) => {
+                let largo = (hasta - desde).min(resto.len());
+                agregar(ClaseTrivia::Comentario, &resto[..largo]);
+                largo
+            }
+            None if c == '\r' && resto[1..].starts_with('\n') => {
+                agregar(ClaseTrivia::SaltoDeLinea, "\r\n");
+                2
+            }
+            None if c == '\n' || c == '\r' => {
+                agregar(ClaseTrivia::SaltoDeLinea, &resto[..1]);
+                1
+            }
+            None if c.is_whitespace() || c == '\u{FEFF}' => {
+                agregar(ClaseTrivia::Espacio, &resto[..c.len_utf8()]);
+                c.len_utf8()
+            }
+            None => {
+                agregar(ClaseTrivia::Desconocido, &resto[..c.len_utf8()]);
+                c.len_utf8()
+            }
+        };
+        resto = &resto[largo..];
+        posicion += largo;
+    }
+}
+
+// Byte de cada línea y columna, contadas como las cuenta el lexer: un salto
+// `\r\n` es uno solo, cada carácter ocupa una columna y la marca de orden de
+// bytes del comienzo no ocupa ninguna
+struct Posiciones<'a> {
+    source: &'a str,
+    lineas: Vec<usize>,
+}
+
+impl<'a> Posiciones<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut lineas = vec![if source.starts_with('\u{FEFF}') { '\u{FEFF}'.len_utf8() } else { 0 }];
+        let bytes = source.as_bytes();
+        for (i, byte) in bytes.iter().enumerate() {
+            let salto = *byte == b'\n' || (*byte == b'\r' && bytes.get(i + 1) != Some(&b'\n'));
+            if salto {
+                lineas.push(i + 1);
+            }
+        }
+        Self { source, lineas }
+    }
+
+    fn byte(&self, linea: usize, columna: usize) -> usize {
+        let Some(&inicio) = self.lineas.get(linea.saturating_sub(1)) else {
+            return self.source.len();
+        };
+        self.source[inicio..].char_indices()
+            .nth(columna.saturating_sub(1))
+            .map_or(self.source.len(), |(desplazamiento, _)| inicio + desplazamiento)
+    }
+}
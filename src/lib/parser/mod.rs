@@ -1,2 +1,6 @@
+pub mod arena;
 pub mod ast;
-pub mod processor;
\ No newline at end of file
+pub mod cst;
+pub mod printer;
+pub mod processor;
+pub mod stream;
\ No newline at end of file
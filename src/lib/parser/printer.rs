@@ -0,0 +1,309 @@
+use std::fmt::Write;
+use super::arena::{ExprId, InstrId};
+use super::processor::{precedencia, ContratoMensaje, Expresion, Instruccion, Program, SentidoMensaje, Variable};
+use crate::source::FileId;
+
+// Sangría de cada nivel de bloque
+const SANGRIA: &str = "    ";
+
+// Código R-Info equivalente al programa: volver a parsearlo da el mismo
+// programa. A diferencia de su `Debug`, que muestra el árbol, el resultado
+// compila, y es la base del formateador. Los comentarios no forman parte del
+// AST y se pierden; los procesos que vienen de un archivo incluido no se
+// escriben, sólo su `incluir`.
+pub fn a_codigo(programa: &Program) -> String {
+    let mut impresor = Impresor { programa, salida: String::new(), nivel: 0 };
+    impresor.programa();
+    impresor.salida
+}
+
+// Texto de una expresión, con los paréntesis que hacen falta para que se
+// agrupe igual al volver a parsearla
+pub fn expresion_a_codigo(programa: &Program, expresion: ExprId) -> String {
+    let mut impresor = Impresor { programa, salida: String::new(), nivel: 0 };
+    impresor.expresion(expresion, 0);
+    impresor.salida
+}
+
+struct Impresor<'a> {
+    programa: &'a Program,
+    salida: String,
+    nivel: usize,
+}
+
+impl Impresor<'_> {
+    fn linea(&mut self, texto: &str) {
+        for _ in 0..self.nivel {
+            self.salida.push_str(SANGRIA);
+        }
+        self.salida.push_str(texto);
+        self.salida.push('\n');
+    }
+
+    // Ejecuta `contenido` un nivel más adentro
+    fn indentado(&mut self, contenido: impl FnOnce(&mut Self)) {
+        self.nivel += 1;
+        contenido(self);
+        self.nivel -= 1;
+    }
+
+    fn programa(&mut self) {
+        let programa = self.programa;
+        self.linea(&format!("programa {}", programa.nombre));
+        for inclusion in &programa.inclusiones {
+            self.linea(&format!("incluir {}", cadena(&inclusion.ruta)));
+        }
+
+        if !programa.constantes.is_empty() {
+            self.linea("constantes");
+            self.indentado(|impresor| {
+                for constante in &programa.constantes {
+                    let valor = impresor.texto(constante.expresion);
+                    impresor.linea(&format!("{} = {}", constante.nombre, valor));
+                }
+            });
+        }
+
+        let procesos: Vec<_> = programa.procesos.iter()
+            .filter(|proceso| proceso.archivo == FileId::PRINCIPAL)
+            .collect();
+        if !procesos.is_empty() {
+            self.linea("procesos");
+            self.indentado(|impresor| {
+                for proceso in procesos {
+                    let parametros: Vec<String> = proceso.parametros.iter()
+                        .map(|parametro| format!("{} {}: {}", parametro.tipo, parametro.nombre, parametro.tipo_dato))
+                        .collect();
+                    let mut encabezado = format!("proceso {}", proceso.nombre);
+                    if !parametros.is_empty() {
+                        let _ = write!(encabezado, "({})", parametros.join(", "));
+                    }
+                    if let Some(retorno) = &proceso.retorno {
+                        let _ = write!(encabezado, ": {}", retorno);
+                    }
+                    impresor.linea(&encabezado);
+                    impresor.seccion(&proceso.variables, &[], &proceso.instrucciones);
+                }
+            });
+        }
+
+        if !programa.areas.is_empty() {
+            self.linea("areas");
+            self.indentado(|impresor| {
+                for area in &programa.areas {
+                    let (x1, y1, x2, y2) = area.coordenadas;
+                    impresor.linea(&format!("{} : {}({}, {}, {}, {})", area.nombre, area.tipo, x1, y1, x2, y2));
+                }
+            });
+        }
+
+        if !programa.robots_definidos.is_empty() {
+            self.linea("robots");
+            self.indentado(|impresor| {
+                for robot in &programa.robots_definidos {
+                    impresor.linea(&format!("robot {}", robot.nombre));
+                    impresor.seccion(&robot.variables, &robot.mensajes, &robot.instrucciones);
+                }
+            });
+        }
+
+        if !programa.robots_instanciados.is_empty() {
+            self.linea("variables");
+            self.indentado(|impresor| {
+                for robot in &programa.robots_instanciados {
+                    impresor.linea(&format!("{} : {}", robot.nombre, robot.tipo));
+                }
+            });
+        }
+
+        // El bloque principal sólo conserva las asignaciones de áreas y las
+        // inicializaciones de los robots
+        self.linea("comenzar");
+        self.indentado(|impresor| {
+            for asignacion in &programa.asignaciones_areas {
+                let argumentos = impresor.argumentos(&[asignacion.robot, asignacion.area]);
+                impresor.linea(&format!("AsignarArea({})", argumentos));
+            }
+            for inicializacion in &programa.inicializaciones {
+                let argumentos = impresor.argumentos(&[inicializacion.robot, inicializacion.pos_x, inicializacion.pos_y]);
+                impresor.linea(&format!("Iniciar({})", argumentos));
+            }
+        });
+        self.linea("fin");
+    }
+
+    // Variables, tipos de los mensajes (sólo en robots) y cuerpo de un proceso o robot
+    fn seccion(&mut self, variables: &[Variable], mensajes: &[ContratoMensaje], instrucciones: &[InstrId]) {
+        if !variables.is_empty() {
+            self.linea("variables");
+            self.indentado(|impresor| {
+                for variable in variables {
+                    impresor.linea(&format!("{} : {}", variable.nombre, variable.tipo_declarado()));
+                }
+            });
+        }
+        if !mensajes.is_empty() {
+            self.linea("mensajes");
+            self.indentado(|impresor| {
+                for contrato in mensajes {
+                    let (verbo, preposicion) = match contrato.sentido {
+                        SentidoMensaje::Envia => ("envia", "a"),
+                        SentidoMensaje::Recibe => ("recibe", "de"),
+                    };
+                    impresor.linea(&format!("{} {} {} {}", verbo, contrato.tipo_dato, preposicion, contrato.robot));
+                }
+            });
+        }
+        self.linea("comenzar");
+        self.indentado(|impresor| impresor.bloque(instrucciones));
+        self.linea("fin");
+    }
+
+    fn bloque(&mut self, instrucciones: &[InstrId]) {
+        for instruccion in instrucciones {
+            self.instruccion(*instruccion);
+        }
+    }
+
+    fn instruccion(&mut self, id: InstrId) {
+        let programa = self.programa;
+        let simbolos = &programa.simbolos;
+        match &programa.arena[id] {
+            Instruccion::Elemental { nombre, .. } => self.linea(simbolos.resolver(*nombre)),
+            Instruccion::Asignacion { variable, indice, valor, .. } => {
+                let destino = match indice {
+                    Some(indice) => format!("{}[{}]", simbolos.resolver(*variable), self.texto(*indice)),
+                    None => simbolos.resolver(*variable).to_string(),
+                };
+                let valor = self.texto(*valor);
+                self.linea(&format!("{} := {}", destino, valor));
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, .. } => {
+                let nombre = simbolos.resolver(*nombre);
+                if argumentos.is_empty() {
+                    self.linea(nombre);
+                } else {
+                    let argumentos = self.argumentos(argumentos);
+                    self.linea(&format!("{}({})", nombre, argumentos));
+                }
+            }
+            Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                let condicion = self.texto(*condicion);
+                self.linea(&format!("si {}", condicion));
+                self.indentado(|impresor| impresor.bloque(entonces));
+                for alternativa in alternativas {
+                    let condicion = self.texto(alternativa.condicion);
+                    self.linea(&format!("sino si {}", condicion));
+                    self.indentado(|impresor| impresor.bloque(&alternativa.cuerpo));
+                }
+                if !sino.is_empty() {
+                    self.linea("sino");
+                    self.indentado(|impresor| impresor.bloque(sino));
+                }
+            }
+            Instruccion::Mientras { condicion, cuerpo, .. } => {
+                let condicion = self.texto(*condicion);
+                self.linea(&format!("mientras {}", condicion));
+                self.indentado(|impresor| impresor.bloque(cuerpo));
+            }
+            Instruccion::Repetir { condicion, cuerpo, .. } => {
+                let condicion = self.texto(*condicion);
+                self.linea(&format!("repetir {}", condicion));
+                self.indentado(|impresor| impresor.bloque(cuerpo));
+            }
+            Instruccion::Para { variable, desde, hasta, cuerpo, .. } => {
+                let (desde, hasta) = (self.texto(*desde), self.texto(*hasta));
+                self.linea(&format!("para {} := {} hasta {}", simbolos.resolver(*variable), desde, hasta));
+                self.indentado(|impresor| impresor.bloque(cuerpo));
+            }
+            Instruccion::Segun { selector, casos, sino, .. } => {
+                let selector = self.texto(*selector);
+                self.linea(&format!("segun {}", selector));
+                self.indentado(|impresor| {
+                    for caso in casos {
+                        let valor = impresor.texto(caso.valor);
+                        impresor.linea(&format!("caso {}:", valor));
+                        impresor.indentado(|impresor| impresor.bloque(&caso.cuerpo));
+                    }
+                    if !sino.is_empty() {
+                        impresor.linea("sino");
+                        impresor.indentado(|impresor| impresor.bloque(sino));
+                    }
+                });
+            }
+            // El texto de lo que no se pudo parsear no queda en el árbol
+            Instruccion::Error { .. } => {}
+        }
+    }
+
+    fn argumentos(&self, argumentos: &[ExprId]) -> String {
+        argumentos.iter().map(|argumento| self.texto(*argumento)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn texto(&self, expresion: ExprId) -> String {
+        expresion_a_codigo(self.programa, expresion)
+    }
+
+    // Escribe la expresión; si su operador agrupa menos que `minima`, va
+    // entre paréntesis
+    fn expresion(&mut self, id: ExprId, minima: u8) {
+        let programa = self.programa;
+        match &programa.arena[id] {
+            Expresion::Sensor { nombre } | Expresion::Identificador(nombre) => {
+                self.salida.push_str(programa.simbolos.resolver(*nombre));
+            }
+            Expresion::Numero(n) => {
+                let _ = write!(self.salida, "{}", n);
+            }
+            Expresion::Booleano(b) => self.salida.push(if *b { 'V' } else { 'F' }),
+            Expresion::Cadena(texto) => self.salida.push_str(&cadena(texto)),
+            Expresion::Todos => self.salida.push('*'),
+            Expresion::Binaria { izquierda, operador, derecha } => {
+                // Los operadores de igual precedencia agrupan de izquierda a
+                // derecha, así que a la derecha hace falta una más
+                let propia = precedencia(operador);
+                let parentesis = propia < minima;
+                if parentesis {
+                    self.salida.push('(');
+                }
+                self.expresion(*izquierda, propia);
+                let _ = write!(self.salida, " {} ", operador);
+                self.expresion(*derecha, propia + 1);
+                if parentesis {
+                    self.salida.push(')');
+                }
+            }
+            // El operando de `~` es una expresión simple: uno binario va
+            // entre paréntesis
+            Expresion::Negacion(operando) => {
+                self.salida.push('~');
+                self.expresion(*operando, u8::MAX);
+            }
+            Expresion::Llamada { nombre, argumentos } => {
+                let _ = write!(self.salida, "{}({})", programa.simbolos.resolver(*nombre), self.argumentos(argumentos));
+            }
+            Expresion::Indice { arreglo, indice } => {
+                let _ = write!(self.salida, "{}[{}]", programa.simbolos.resolver(*arreglo), self.texto(*indice));
+            }
+        }
+    }
+}
+
+// Literal de cadena con las secuencias de escape que `Token::valor_cadena`
+// resuelve
+fn cadena(texto: &str) -> String {
+    let mut literal = String::with_capacity(texto.len() + 2);
+    literal.push('"');
+    for c in texto.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            otro => literal.push(otro),
+        }
+    }
+    literal.push('"');
+    literal
+}
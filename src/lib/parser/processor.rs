@@ -1,30 +1,72 @@
 use std::collections::HashMap;
-use crate::lib::compilerError::CompilerError;
-use super::super::lexer::token::{Token, TokenType};
+use crate::compilerError::CompilerError;
+use super::super::lexer::scanner::Lexer;
+use super::super::lexer::token::{Token, TokenType, SECCIONES};
+use crate::interner::{Interner, Symbol};
+use crate::mensajes::{Codigo, Mensaje, Termino};
+use crate::source::{FileId, Span};
+use super::arena::{Arena, ExprId, InstrId};
+use super::stream::TokenStream;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RobotInstanciado {
     pub nombre: String,
     pub tipo: String,
+    pub linea: usize,
 }
 
+// Directiva `incluir "ruta"`: incorpora los procesos de otro archivo
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inclusion {
+    pub ruta: String,
+    pub linea: usize,
+}
+
+// Constante de la sección `constantes`: `NOMBRE = expresión`. Su valor se
+// calcula al compilar, así que sirve donde hace falta un número fijo, como
+// las dimensiones de un área.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Constante {
+    pub nombre: String,
+    pub expresion: ExprId,
+    pub valor: i32,
+    pub linea: usize,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AsignacionArea {
-    pub robot: Expresion,
-    pub area: Expresion,
+    pub robot: ExprId,
+    pub area: ExprId,
+    pub linea: usize,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InicializacionRobot {
-    pub robot: Expresion,
-    pub pos_x: Expresion,
-    pub pos_y: Expresion,
+    pub robot: ExprId,
+    pub pos_x: ExprId,
+    pub pos_y: ExprId,
+    pub linea: usize,
 }
 
-// Estructura principal del Ast
+// Estructura principal del Ast. Las instrucciones y expresiones de todo el
+// programa están en `arena`; el resto del árbol las referencia por índice.
+// Los nombres usados dentro de instrucciones y expresiones se guardan como
+// `Symbol` de `simbolos`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     pub nombre: String,
+    // Línea del nombre del programa
+    pub linea: usize,
+    pub arena: Arena,
+    pub simbolos: Interner,
+    pub inclusiones: Vec<Inclusion>, // Archivos pedidos con `incluir`, sin resolver
+    pub constantes: Vec<Constante>,
     pub procesos: Vec<Proceso>,
     pub areas: Vec<Area>,
     pub robots_declarados: Vec<String>, // Nombres de tipos de robot
@@ -34,301 +76,635 @@ pub struct Program {
     pub inicializaciones: Vec<InicializacionRobot>, // Inicializaciones de posición
 }
 
+impl Program {
+    // Valor de cada constante según el símbolo con que la nombran las expresiones
+    pub fn valores_constantes(&self) -> HashMap<Symbol, i32> {
+        self.constantes.iter()
+            .filter_map(|constante| Some((self.simbolos.get(&constante.nombre)?, constante.valor)))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Proceso {
     pub nombre: String,
     pub parametros: Vec<Parametro>,
     pub variables: Vec<Variable>,
-    pub instrucciones: Vec<Instruccion>,
+    // Tipo del valor que devuelve, si se declaró `proceso nombre(...): tipo`.
+    // Como en Pascal, el proceso lo devuelve asignándolo a su propio nombre.
+    pub retorno: Option<String>,
+    pub instrucciones: Vec<InstrId>,
+    // Archivo donde está declarado; las líneas de sus instrucciones son de ese archivo
+    pub archivo: FileId,
+    pub linea: usize,
+}
+
+impl Proceso {
+    // Corrige las líneas de la declaración cuando se parseó con líneas agregadas
+    // (las instrucciones están en el arena y se corrigen al anexarlo)
+    pub fn desplazar_lineas(&mut self, desplazamiento: isize) {
+        self.linea = self.linea.saturating_add_signed(desplazamiento);
+        for parametro in &mut self.parametros {
+            parametro.linea = parametro.linea.saturating_add_signed(desplazamiento);
+        }
+        for variable in &mut self.variables {
+            variable.linea = variable.linea.saturating_add_signed(desplazamiento);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Parametro {
     pub tipo: String, // "E", "S", "ES"
     pub nombre: String,
     pub tipo_dato: String,
+    pub linea: usize,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variable {
     pub nombre: String,
+    // En un arreglo, el tipo de sus elementos
     pub tipo_dato: String,
+    // Extensión: cantidad de elementos si se declaró `arreglo[N] de tipo`.
+    // Los índices van de 1 a N.
+    pub longitud: Option<usize>,
+    pub linea: usize,
+}
+
+impl Variable {
+    // El tipo como se escribió en la declaración
+    pub fn tipo_declarado(&self) -> String {
+        match self.longitud {
+            Some(longitud) => format!("arreglo[{}] de {}", longitud, self.tipo_dato),
+            None => self.tipo_dato.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Area {
     pub nombre: String,
     pub tipo: String,
     pub coordenadas: (i32, i32, i32, i32),
+    pub linea: usize,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Robot {
     pub nombre: String,
     pub variables: Vec<Variable>,
-    pub instrucciones: Vec<Instruccion>,
+    // Tipos declarados en el bloque opcional `mensajes`
+    pub mensajes: Vec<ContratoMensaje>,
+    pub instrucciones: Vec<InstrId>,
+    pub archivo: FileId,
+    pub linea: usize,
 }
 
+impl Robot {
+    pub fn desplazar_lineas(&mut self, desplazamiento: isize) {
+        self.linea = self.linea.saturating_add_signed(desplazamiento);
+        for variable in &mut self.variables {
+            variable.linea = variable.linea.saturating_add_signed(desplazamiento);
+        }
+        for contrato in &mut self.mensajes {
+            contrato.linea = contrato.linea.saturating_add_signed(desplazamiento);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SentidoMensaje {
+    Envia,
+    Recibe,
+}
+
+// Extensión del lenguaje: `envia numero a r2` o `recibe booleano de r1` en
+// el bloque `mensajes` de un robot fija el tipo de los valores que viajan
+// entre los dos. `robot` puede ser un robot del programa, un tipo de robot
+// o `*`, para los mensajes a todos o de cualquiera.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContratoMensaje {
+    pub sentido: SentidoMensaje,
+    pub tipo_dato: String,
+    pub robot: String,
+    pub linea: usize,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+// Cada instrucción guarda la línea del código fuente donde comienza
 pub enum Instruccion {
-    Elemental { nombre: String },
-    Asignacion { variable: String, valor: Expresion },
-    LlamadaFuncion { nombre: String, argumentos: Vec<Expresion> },
-    Si { condicion: Expresion, entonces: Vec<Instruccion>, sino: Vec<Instruccion> },
-    Mientras { condicion: Expresion, cuerpo: Vec<Instruccion> },
-    Repetir { condicion: Expresion, cuerpo: Vec<Instruccion> },
+    Elemental { nombre: Symbol, linea: usize },
+    // `indice` si se asigna un elemento de un arreglo: `flores[av] := n`
+    Asignacion { variable: Symbol, indice: Option<ExprId>, valor: ExprId, linea: usize },
+    LlamadaFuncion { nombre: Symbol, argumentos: Vec<ExprId>, linea: usize },
+    // Las ramas `sino si` van en `alternativas`, en orden, todas al mismo
+    // nivel que la primera
+    Si { condicion: ExprId, entonces: Vec<InstrId>, alternativas: Vec<Alternativa>, sino: Vec<InstrId>, linea: usize },
+    Mientras { condicion: ExprId, cuerpo: Vec<InstrId>, linea: usize },
+    Repetir { condicion: ExprId, cuerpo: Vec<InstrId>, linea: usize },
+    // Extensión: ejecuta el primer caso cuyo valor es el del selector, o el
+    // `sino` si no hay ninguno
+    Segun { selector: ExprId, casos: Vec<Caso>, sino: Vec<InstrId>, linea: usize },
+    // Extensión: `para variable := desde hasta hasta` ejecuta el cuerpo con
+    // la variable valiendo de `desde` a `hasta`. `cantidad` (`hasta - desde
+    // + 1`) y `siguiente` (`variable + 1`) no son parte del código escrito:
+    // son las expresiones con que se ejecuta como un `repetir`.
+    Para {
+        variable: Symbol,
+        desde: ExprId,
+        hasta: ExprId,
+        cantidad: ExprId,
+        siguiente: ExprId,
+        cuerpo: Vec<InstrId>,
+        linea: usize,
+    },
+    // Lugar de una instrucción que no se pudo parsear. Los recorridos la
+    // saltean, así que el análisis del resto del bloque sigue sin errores
+    // de más, y al ejecutar no hace nada.
+    Error { linea: usize },
+}
+
+// Rama `sino si <condición>` de un `si`, con la línea donde aparece
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alternativa {
+    pub condicion: ExprId,
+    pub cuerpo: Vec<InstrId>,
+    pub linea: usize,
+}
+
+// Rama `caso <valor>:` de un `segun`. `comparacion` es `selector == valor`,
+// la condición con que se ejecuta el caso como una rama de un `si`; no es
+// parte del código escrito, así que los recorridos no la visitan.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Caso {
+    pub valor: ExprId,
+    pub comparacion: ExprId,
+    pub cuerpo: Vec<InstrId>,
+    pub linea: usize,
+}
+
+impl Instruccion {
+    pub fn linea(&self) -> usize {
+        match self {
+            Instruccion::Elemental { linea, .. }
+            | Instruccion::Asignacion { linea, .. }
+            | Instruccion::LlamadaFuncion { linea, .. }
+            | Instruccion::Si { linea, .. }
+            | Instruccion::Mientras { linea, .. }
+            | Instruccion::Repetir { linea, .. }
+            | Instruccion::Segun { linea, .. }
+            | Instruccion::Para { linea, .. }
+            | Instruccion::Error { linea } => *linea,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expresion {
-    Elemental { nombre: String },
-    Identificador(String),
+    // Consulta a un sensor del robot (HayFlorEnLaEsquina, PosAv...): una
+    // llamada sin argumentos que produce un valor
+    Sensor { nombre: Symbol },
+    Identificador(Symbol),
     Numero(i32),
     Booleano(bool),
-    Binaria { izquierda: Box<Expresion>, operador: String, derecha: Box<Expresion> },
+    // Texto entre comillas, con las secuencias de escape ya resueltas
+    Cadena(String),
+    // `*` como robot de EnviarMensaje (a todos) o de RecibirMensaje (de cualquiera)
+    Todos,
+    Binaria { izquierda: ExprId, operador: String, derecha: ExprId },
+    // `~ expresion`: la negación de un booleano
+    Negacion(ExprId),
+    // Llamada a un proceso que devuelve un valor: `distancia(a, b)`
+    Llamada { nombre: Symbol, argumentos: Vec<ExprId> },
+    // Elemento de un arreglo: `flores[av]`
+    Indice { arreglo: Symbol, indice: ExprId },
+}
+
+// Tipo del valor que devuelve cada sensor del robot; `None` si el nombre no
+// es un sensor
+pub fn tipo_sensor(nombre: &str) -> Option<&'static str> {
+    match nombre {
+        "PosAv" | "PosCa" => Some("numero"),
+        "HayFlorEnLaBolsa" | "HayPapelEnLaBolsa" | "HayFlorEnLaEsquina" | "HayPapelEnLaEsquina" => Some("booleano"),
+        // Extensión: si el último RecibirMensajeTimeout recibió algo
+        "RecibioMensaje" => Some("booleano"),
+        _ => None,
+    }
 }
 
-pub struct Parser<'a> {
-    tokens: &'a [Token],
-    pos: usize,
-    current: Option<&'a Token>,
+// Valor de una expresión hecha sólo de números y operadores aritméticos;
+// `None` si depende de variables o sensores, o si no puede calcularse
+pub fn valor_constante(arena: &Arena, expresion: &Expresion) -> Option<i32> {
+    valor_con_constantes(arena, expresion, &HashMap::new())
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(tokens: &'a [Token]) -> Self {
-        let mut parser = Self {
-            tokens,
-            pos: 0,
-            current: None,
-        };
-        parser.avanzar();
-        parser
+// Como `valor_constante`, pero la expresión también puede nombrar las
+// constantes de `constantes`
+pub fn valor_con_constantes(arena: &Arena, expresion: &Expresion, constantes: &HashMap<Symbol, i32>) -> Option<i32> {
+    match expresion {
+        Expresion::Numero(n) => Some(*n),
+        Expresion::Identificador(nombre) => constantes.get(nombre).copied(),
+        Expresion::Binaria { izquierda, operador, derecha } => {
+            let izquierda = valor_con_constantes(arena, &arena[*izquierda], constantes)?;
+            let derecha = valor_con_constantes(arena, &arena[*derecha], constantes)?;
+            match operador.as_str() {
+                "+" => izquierda.checked_add(derecha),
+                "-" => izquierda.checked_sub(derecha),
+                "*" => izquierda.checked_mul(derecha),
+                "/" => izquierda.checked_div(derecha),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+pub use super::super::lexer::token::precedencia;
+
+// Niveles de bloques y de paréntesis que se pueden anidar. El parser, el
+// análisis y el intérprete recorren el programa recursivamente, y sin un
+// límite un programa con miles de niveles agota la pila.
+pub const ANIDAMIENTO_MAXIMO: usize = 128;
+
+fn orden_seccion(seccion: &str) -> Option<usize> {
+    SECCIONES.iter().position(|s| *s == seccion)
+}
+
+// "'a', 'b' o 'c'"
+fn enumerar(opciones: &[&str]) -> Mensaje {
+    let opciones: Vec<String> = opciones.iter().map(|opcion| format!("'{}'", opcion)).collect();
+    match opciones.split_last() {
+        Some((ultima, [])) => ultima.into(),
+        Some((ultima, resto)) => Mensaje::termino(Termino::Alternativas).con(resto.join(", ")).con(ultima),
+        None => Mensaje::from(""),
+    }
+}
+
+// Falta un símbolo o una palabra clave puntual
+fn esperado(simbolo: &str) -> Mensaje {
+    Mensaje::new(Codigo::EsperadoSimbolo).con(simbolo)
+}
+
+pub struct Parser<'src> {
+    tokens: TokenStream<'src>,
+    // Nodos y nombres del programa que se está parseando
+    arena: Arena,
+    simbolos: Interner,
+    // Si es verdadero, las secciones pueden aparecer en cualquier orden
+    secciones_flexibles: bool,
+    // Falso para los archivos incluidos y las secciones sueltas, que no
+    // tienen bloque principal
+    bloque_principal: bool,
+    // Valores de las constantes ya declaradas
+    constantes: HashMap<Symbol, i32>,
+    // Bloques y paréntesis abiertos en este momento
+    anidamiento: usize,
+    // Primer anidamiento excesivo, que se informa aunque el bloque donde
+    // ocurrió descarte el error
+    error_anidamiento: Option<CompilerError>,
+    // Problemas que no impiden armar el programa, como un robot sin área
+    advertencias: Vec<CompilerError>,
+    // Instrucciones que no se pudieron parsear. El programa se sigue armando
+    // con un `Instruccion::Error` en su lugar, pero no compila.
+    errores: Vec<CompilerError>,
+}
+
+impl<'src> Parser<'src> {
+    pub fn new(tokens: &[Token<'src>]) -> Self {
+        Self {
+            tokens: TokenStream::new(tokens),
+            arena: Arena::new(),
+            simbolos: Interner::new(),
+            secciones_flexibles: false,
+            bloque_principal: true,
+            constantes: HashMap::new(),
+            anidamiento: 0,
+            error_anidamiento: None,
+            advertencias: Vec::new(),
+            errores: Vec::new(),
+        }
     }
     
-    fn avanzar(&mut self) {
-        if self.pos < self.tokens.len() {
-            self.current = Some(&self.tokens[self.pos]);
-            self.pos += 1;
-        } else {
-            self.current = None;
+    // Parsea leyendo los tokens del lexer a medida que se necesitan, sin
+    // generar antes el vector completo
+    pub fn from_lexer(lexer: Lexer<'src>) -> Self {
+        Self {
+            tokens: TokenStream::lazy(lexer),
+            arena: Arena::new(),
+            simbolos: Interner::new(),
+            secciones_flexibles: false,
+            bloque_principal: true,
+            constantes: HashMap::new(),
+            anidamiento: 0,
+            error_anidamiento: None,
+            advertencias: Vec::new(),
+            errores: Vec::new(),
         }
     }
     
-    fn coincidir(&mut self, tipo: TokenType) -> bool {
-        if let Some(token) = self.current {
-            token.token_type == tipo
-        } else {
-            false
+    pub fn con_secciones_flexibles(mut self, flexibles: bool) -> Self {
+        self.secciones_flexibles = flexibles;
+        self
+    }
+    
+    pub fn sin_bloque_principal(mut self) -> Self {
+        self.bloque_principal = false;
+        self
+    }
+    
+    pub fn parse(&mut self) -> Result<Program, CompilerError> {
+        self.parse_todo().map_err(|mut errores| errores.swap_remove(0))
+    }
+    
+    // Como `parse`, pero con todos los errores léxicos del código y no sólo
+    // el primero. Un error léxico explica mejor el problema que el error de
+    // sintaxis que suele provocar, así que éste sólo se informa si no hay
+    // ninguno.
+    pub fn parse_todo(&mut self) -> Result<Program, Vec<CompilerError>> {
+        match self.parse_parcial() {
+            (Some(programa), errores) if errores.is_empty() => Ok(programa),
+            (_, errores) => Err(errores),
         }
     }
     
-    fn consumir(&mut self, tipo: TokenType, mensaje: &str) -> Result<(), CompilerError> {
-        if self.coincidir(tipo) {
-            self.avanzar();
-            Ok(())
-        } else {
-            let token = self.current.unwrap();
-            Err(CompilerError::new(
-                format!("{}: esperado {:?}", mensaje, tipo),
-                token.line,
-                token.column
-            ))
+    // Como `parse_todo`, pero si sólo fallaron algunas instrucciones
+    // devuelve también el programa, con un `Instruccion::Error` en lugar de
+    // cada una, junto con sus errores
+    pub fn parse_parcial(&mut self) -> (Option<Program>, Vec<CompilerError>) {
+        self.advertencias.clear();
+        self.errores.clear();
+        let resultado = self.parse_programa();
+        self.tokens.agotar();
+        let (programa, errores) = match (self.error_anidamiento.take(), resultado) {
+            // Un anidamiento excesivo explica los errores que provoca al
+            // cortar el bloque donde ocurre
+            (Some(error), _) => (None, vec![error]),
+            (None, Ok(programa)) => (Some(programa), std::mem::take(&mut self.errores)),
+            (None, Err(error)) => {
+                let mut errores = std::mem::take(&mut self.errores);
+                errores.push(error);
+                (None, errores)
+            }
+        };
+        match self.tokens.errores_lexicos() {
+            [] => (programa, errores),
+            lexicos => (programa, lexicos.to_vec()),
         }
     }
     
-    pub fn parse(&mut self) -> Result<Program, CompilerError> {
-        self.parse_programa()
+    // Advertencias del último programa parseado
+    pub fn advertencias(&self) -> &[CompilerError] {
+        &self.advertencias
+    }
+    
+    // Errores del lexer encontrados mientras se parseaba con `from_lexer`
+    pub fn errores_lexicos(&self) -> &[CompilerError] {
+        self.tokens.errores_lexicos()
     }
     
     fn parse_programa(&mut self) -> Result<Program, CompilerError> {
         // programa nombre
-        self.consumir(TokenType::Keyword, "Esperado 'programa'")?;
-        let nombre = if let Some(token) = self.current {
-            let nombre = token.value.clone();
-            self.avanzar();
-            nombre
-        } else {
-            return Err(CompilerError::new("Esperado nombre del programa", 0, 0));
-        };
+        self.tokens.expect(TokenType::Keyword, esperado("programa"))?;
+        // El nombre es un identificador: ni una palabra reservada ni un
+        // número, una cadena o un símbolo
+        let nombre = self.tokens.expect(TokenType::Identifier, Codigo::EsperadoNombrePrograma)?;
+        let (linea, nombre) = (nombre.line, nombre.value.to_string());
         
+        let mut inclusiones = Vec::new();
+        let mut constantes = Vec::new();
         let mut procesos = Vec::new();
         let mut areas = Vec::new();
         let mut robots_declarados = Vec::new();
         let mut robots_definidos = Vec::new();
         let mut robots_instanciados: Vec<RobotInstanciado> = Vec::new(); // Nuevo: robots declarados en sección variables
+        // Dónde se nombra cada robot instanciado, para las advertencias
+        let mut spans_robots: Vec<Span> = Vec::new();
         
         // Parsear secciones
-        while let Some(token) = self.current {
+        let mut vistas = Vec::new();
+        while let Some(token) = self.tokens.actual() {
+            if token.is_section_keyword() {
+                self.verificar_orden_seccion(&token, &vistas)?;
+                vistas.push(token.value);
+            }
             match token.token_type {
-                TokenType::Keyword => match token.value.as_str() {
+                TokenType::Keyword => match token.value {
+                    "incluir" => {
+                        self.tokens.avanzar(); // consumir "incluir"
+                        let ruta = self.tokens.expect(TokenType::Str, Codigo::EsperadaRutaInclusion)?;
+                        inclusiones.push(Inclusion {
+                            ruta: ruta.valor_cadena().into_owned(),
+                            linea: token.line,
+                        });
+                    }
+                    "constantes" => {
+                        self.tokens.avanzar(); // consumir "constantes"
+                        constantes = self.parse_constantes()?;
+                    }
                     "procesos" => {
-                        self.avanzar(); // consumir "procesos"
+                        self.tokens.avanzar(); // consumir "procesos"
                         procesos = self.parse_procesos()?;
                     }
                     "areas" => {
-                        self.avanzar(); // consumir "areas"
+                        self.tokens.avanzar(); // consumir "areas"
                         areas = self.parse_areas()?;
                     }
                     "robots" => {
-                        self.avanzar(); // consumir "robots"
+                        self.tokens.avanzar(); // consumir "robots"
                         let (declarados, definidos) = self.parse_robots()?;
                         robots_declarados = declarados;
                         robots_definidos = definidos;
                     }
                     "variables" => {
-                        self.avanzar(); // consumir "variables"
+                        self.tokens.avanzar(); // consumir "variables"
                         
                         // Parsear declaraciones de variables globales (instanciación de robots)
-                        while let Some(t) = self.current {
+                        while let Some(t) = self.tokens.actual() {
                             // Saltar indentación
                             if t.token_type == TokenType::Indent || t.token_type == TokenType::Dedent {
-                                self.avanzar();
+                                self.tokens.avanzar();
                                 continue;
                             }
                             
-                            // Si encontramos "comenzar", terminamos la sección de variables
-                            if t.token_type == TokenType::Keyword && t.value == "comenzar" {
+                            // La sección de variables termina en "comenzar" o
+                            // en cualquier otra palabra clave
+                            if t.token_type == TokenType::Keyword || t.token_type == TokenType::EndFile {
                                 break;
                             }
                             
-                            // Parsear declaración de robot: nombre_instancia : tipo_robot
+                            // Parsear declaración de robot: nombre_instancia : tipo_robot.
+                            // Varias instancias comparten el tipo si se separan con
+                            // comas: `exp1, exp2, exp3: explorador`
                             if t.token_type == TokenType::Identifier {
-                                let nombre_instancia = t.value.clone();
-                                self.avanzar();
+                                let mut instancias = vec![(t.value.to_string(), t.span())];
+                                self.tokens.avanzar();
+                                while self.tokens.actual().is_some_and(|coma| coma.token_type == TokenType::Comma) {
+                                    self.tokens.avanzar(); // consumir ","
+                                    let instancia = self.tokens.expect(TokenType::Identifier, Codigo::EsperadoNombreRobot)?;
+                                    instancias.push((instancia.value.to_string(), instancia.span()));
+                                }
                                 
                                 // Verificar que siga el operador de declaración
-                                if let Some(next_token) = self.current {
+                                if let Some(next_token) = self.tokens.actual() {
                                     if next_token.token_type == TokenType::Declaration {
-                                        self.avanzar(); // consumir ":"
+                                        self.tokens.avanzar(); // consumir ":"
                                         
                                         // Obtener el tipo de robot
-                                        if let Some(tipo_token) = self.current {
+                                        if let Some(tipo_token) = self.tokens.actual() {
                                             if tipo_token.token_type == TokenType::Identifier {
-                                                let tipo_robot = tipo_token.value.clone();
-                                                self.avanzar();
+                                                let tipo_robot = tipo_token.value.to_string();
+                                                self.tokens.avanzar();
                                                 
                                                 // Verificar que el tipo de robot esté definido
                                                 if !robots_declarados.contains(&tipo_robot) {
-                                                    return Err(CompilerError::new(
-                                                        format!("Tipo de robot no definido: {}", tipo_robot),
-                                                        tipo_token.line,
-                                                        tipo_token.column
+                                                    return Err(CompilerError::en(
+                                                        Mensaje::new(Codigo::TipoRobotNoDefinido).con(&tipo_robot),
+                                                        tipo_token.span()
                                                     ));
                                                 }
                                                 
-                                                robots_instanciados.push(RobotInstanciado {
-                                                    nombre: nombre_instancia,
-                                                    tipo: tipo_robot,
-                                                });
+                                                for (nombre, span) in instancias {
+                                                    robots_instanciados.push(RobotInstanciado {
+                                                        nombre,
+                                                        tipo: tipo_robot.clone(),
+                                                        linea: span.linea,
+                                                    });
+                                                    spans_robots.push(span);
+                                                }
                                             } else {
-                                                return Err(CompilerError::new(
-                                                    "Esperado tipo de robot después de ':'",
-                                                    tipo_token.line,
-                                                    tipo_token.column
+                                                return Err(CompilerError::en(
+                                                    Codigo::EsperadoTipoRobot,
+                                                    tipo_token.span()
                                                 ));
                                             }
                                         } else {
-                                            return Err(CompilerError::new(
-                                                "Declaración de robot incompleta",
-                                                t.line,
-                                                t.column
+                                            return Err(CompilerError::en(
+                                                Codigo::DeclaracionRobotIncompleta,
+                                                t.span()
                                             ));
                                         }
                                     } else {
-                                        return Err(CompilerError::new(
-                                            "Esperado ':' en declaración de robot",
-                                            next_token.line,
-                                            next_token.column
+                                        return Err(CompilerError::en(
+                                            Codigo::EsperadoDosPuntosRobot,
+                                            next_token.span()
                                         ));
                                     }
                                 } else {
-                                    return Err(CompilerError::new(
-                                        "Declaración de robot incompleta",
-                                        t.line,
-                                        t.column
+                                    return Err(CompilerError::en(
+                                        Codigo::DeclaracionRobotIncompleta,
+                                        t.span()
                                     ));
                                 }
                             } else {
-                                self.avanzar(); // saltar otros tokens
+                                // Fuera de un bloque no hay instrucciones sueltas
+                                return Err(CompilerError::en(Codigo::EsperadoNombreRobot, t.span()));
                             }
                         }
                     }
                     "comenzar" => break, // Salir para parsear instrucciones principales
-                    _ => self.avanzar(),
+                    _ => return Err(self.seccion_inesperada(&token, &vistas)),
                 }
                 TokenType::Indent | TokenType::Dedent => {
-                    self.avanzar(); // ignorar indentación
+                    self.tokens.avanzar(); // ignorar indentación
                 }
-                _ => break,
+                TokenType::EndFile => break,
+                _ => return Err(self.seccion_inesperada(&token, &vistas)),
             }
         }
         
+        // Todas las secciones son opcionales, pero un programa necesita su
+        // bloque principal
+        if self.bloque_principal
+            && let Some(token) = self.tokens.actual()
+            && !(token.token_type == TokenType::Keyword && token.value == "comenzar")
+        {
+            return Err(self.seccion_inesperada(&token, &vistas));
+        }
+        
         // Parsear bloque principal (instrucciones después de "comenzar")
         let mut instrucciones_principales = Vec::new();
         let mut asignaciones_areas = Vec::new();
         let mut inicializaciones = Vec::new();
         
-        if let Some(token) = self.current {
-            if token.token_type == TokenType::Keyword && token.value == "comenzar" {
-                self.avanzar(); // consumir "comenzar"
-                while let Some(token) = self.current {
-                    if token.token_type == TokenType::Keyword && token.value == "fin" {
-                        self.avanzar();
-                        break;
-                    } else if token.token_type == TokenType::Indent || 
-                              token.token_type == TokenType::Dedent {
-                        self.avanzar();
-                    } else {
-                        if let Ok(instr) = self.parse_instruccion() {
-                            // Clasificar las instrucciones principales
-                            match &instr {
-                                Instruccion::LlamadaFuncion { nombre, argumentos } => {
-                                    if nombre == "AsignarArea" && argumentos.len() == 2 {
-                                        // Capturar asignación de área
-                                        asignaciones_areas.push(AsignacionArea {
-                                            robot: argumentos[0].clone(),
-                                            area: argumentos[1].clone(),
-                                        });
-                                    } else if nombre == "Iniciar" && argumentos.len() == 3 {
-                                        // Capturar inicialización de robot
-                                        inicializaciones.push(InicializacionRobot {
-                                            robot: argumentos[0].clone(),
-                                            pos_x: argumentos[1].clone(),
-                                            pos_y: argumentos[2].clone(),
-                                        });
-                                    }
-                                    instrucciones_principales.push(instr);
-                                }
-                                _ => {
-                                    instrucciones_principales.push(instr);
-                                }
+        if let Some(token) = self.tokens.actual()
+            && token.token_type == TokenType::Keyword && token.value == "comenzar"
+        {
+            self.tokens.avanzar(); // consumir "comenzar"
+            while let Some(token) = self.tokens.actual() {
+                if token.token_type == TokenType::Keyword && token.value == "fin" {
+                    self.tokens.avanzar();
+                    self.verificar_fin_del_programa(&token)?;
+                    break;
+                } else if token.token_type == TokenType::EndFile {
+                    return Err(CompilerError::en(
+                        Codigo::FaltaFinPrincipal,
+                        token.span()
+                    ));
+                } else if token.token_type == TokenType::Indent || 
+                          token.token_type == TokenType::Dedent {
+                    self.tokens.avanzar();
+                } else {
+                    let instr = match self.parse_instruccion() {
+                        Ok(instr) => instr,
+                        Err(error) => {
+                            self.saltear_instruccion(&mut instrucciones_principales, token, error);
+                            continue;
+                        }
+                    };
+                    // Clasificar las instrucciones principales
+                    match &self.arena[instr] {
+                        Instruccion::LlamadaFuncion { nombre, argumentos, linea } => {
+                            let nombre = self.simbolos.resolver(*nombre);
+                            if nombre == "AsignarArea" && argumentos.len() == 2 {
+                                // Capturar asignación de área
+                                asignaciones_areas.push(AsignacionArea {
+                                    robot: argumentos[0],
+                                    area: argumentos[1],
+                                    linea: *linea,
+                                });
+                            } else if nombre == "Iniciar" && argumentos.len() == 3 {
+                                // Capturar inicialización de robot
+                                inicializaciones.push(InicializacionRobot {
+                                    robot: argumentos[0],
+                                    pos_x: argumentos[1],
+                                    pos_y: argumentos[2],
+                                    linea: *linea,
+                                });
                             }
-                        } else {
-                            self.avanzar(); // saltar si hay error
+                            instrucciones_principales.push(instr);
+                        }
+                        _ => {
+                            instrucciones_principales.push(instr);
                         }
                     }
                 }
             }
         }
         
-        // Validar que todos los robots instanciados tengan asignación de área e inicialización
-        for robot in &robots_instanciados {
-            let nombre_robot_exp = Expresion::Identificador(robot.nombre.clone());
+        // Validar que todos los robots instanciados tengan asignación de área.
+        // Las inicializaciones las verifica el análisis semántico.
+        for (robot, span) in robots_instanciados.iter().zip(&spans_robots) {
+            let nombre_robot_exp = self.simbolos.get(&robot.nombre).map(Expresion::Identificador);
             
             // Verificar asignación de área
             let tiene_asignacion_area = asignaciones_areas.iter()
-                .any(|asig| asig.robot == nombre_robot_exp);
+                .any(|asig| nombre_robot_exp.as_ref() == Some(&self.arena[asig.robot]));
             
             if !tiene_asignacion_area {
-                println!("Advertencia: Robot '{}' no tiene asignación de área", robot.nombre);
-            }
-            
-            // Verificar inicialización
-            let tiene_inicializacion = inicializaciones.iter()
-                .any(|init| init.robot == nombre_robot_exp);
-            
-            if !tiene_inicializacion {
-                println!("Advertencia: Robot '{}' no tiene inicialización", robot.nombre);
+                let mensaje = Mensaje::new(Codigo::RobotSinArea).con(&robot.nombre);
+                self.advertencias.push(CompilerError::en(mensaje, *span));
             }
         }
         
@@ -344,6 +720,11 @@ impl<'a> Parser<'a> {
         
         Ok(Program {
             nombre,
+            linea,
+            arena: std::mem::take(&mut self.arena),
+            simbolos: std::mem::take(&mut self.simbolos),
+            inclusiones,
+            constantes,
             procesos,
             areas,
             robots_declarados,
@@ -354,12 +735,120 @@ impl<'a> Parser<'a> {
         })
     }
     
+    // Después del bloque principal termina el programa: lo que siga, sea otro
+    // bloque principal o cualquier otra cosa, es un error y no se ignora
+    fn verificar_fin_del_programa(&mut self, fin: &Token) -> Result<(), CompilerError> {
+        while let Some(token) = self.tokens.actual() {
+            match token.token_type {
+                TokenType::Indent | TokenType::Dedent => self.tokens.avanzar(),
+                TokenType::EndFile => break,
+                _ => {
+                    let mensaje = if token.token_type == TokenType::Keyword && token.value == "comenzar" {
+                        Mensaje::new(Codigo::BloquePrincipalRepetido)
+                    } else {
+                        Mensaje::new(Codigo::CodigoDespuesDelPrincipal).con(token.value)
+                    };
+                    return Err(CompilerError::en(mensaje.con(fin.line), token.span()));
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    // Una sección repetida es siempre un error; una fuera de orden, sólo si
+    // no se admiten secciones flexibles
+    fn verificar_orden_seccion(&self, token: &Token, vistas: &[&str]) -> Result<(), CompilerError> {
+        let seccion = token.value;
+        if seccion != "incluir" && vistas.contains(&seccion) {
+            return Err(CompilerError::en(
+                Mensaje::new(Codigo::SeccionRepetida).con(seccion),
+                token.span()
+            ));
+        }
+        if self.secciones_flexibles {
+            return Ok(());
+        }
+        match vistas.iter().find(|vista| orden_seccion(vista) > orden_seccion(seccion)) {
+            Some(posterior) => {
+                let mensaje = if seccion == "incluir" {
+                    Mensaje::new(Codigo::InclusionFueraDeOrden)
+                } else {
+                    Mensaje::new(Codigo::SeccionFueraDeOrden).con(seccion)
+                };
+                Err(CompilerError::en(mensaje.con(*posterior), token.span()))
+            }
+            None => Ok(()),
+        }
+    }
+    
+    // Error para un token que no comienza ninguna sección, con lo que podía
+    // seguir en ese punto del programa
+    fn seccion_inesperada(&self, token: &Token, vistas: &[&str]) -> CompilerError {
+        let ultima = vistas.iter().filter_map(|vista| orden_seccion(vista)).max();
+        let mut esperadas: Vec<&str> = SECCIONES.iter()
+            .enumerate()
+            .filter(|(orden, seccion)| {
+                let repetida = **seccion != "incluir" && vistas.contains(seccion);
+                let anterior = !self.secciones_flexibles && ultima.is_some_and(|ultima| *orden < ultima);
+                !repetida && !anterior
+            })
+            .map(|(_, seccion)| *seccion)
+            .collect();
+        if self.bloque_principal {
+            esperadas.push("comenzar");
+        }
+        
+        let mensaje = if token.token_type == TokenType::EndFile {
+            Mensaje::new(Codigo::FaltaBloquePrincipal)
+        } else {
+            Mensaje::new(Codigo::SeccionInesperada).con(token.value)
+        };
+        CompilerError::en(mensaje.con(enumerar(&esperadas)), token.span())
+    }
+    
+    // Cada constante es `NOMBRE = expresión`, con un valor que tiene que
+    // poder calcularse al compilar; puede usar las constantes anteriores
+    fn parse_constantes(&mut self) -> Result<Vec<Constante>, CompilerError> {
+        let mut constantes = Vec::new();
+        
+        while let Some(token) = self.tokens.actual() {
+            if token.token_type == TokenType::Identifier {
+                let nombre = token.value.to_string();
+                self.tokens.avanzar();
+                
+                self.tokens.expect(TokenType::Equals, esperado("="))?;
+                let expresion = self.parse_expresion()?;
+                let Some(valor) = valor_con_constantes(&self.arena, &self.arena[expresion], &self.constantes) else {
+                    return Err(CompilerError::en(
+                        Mensaje::new(Codigo::ConstanteNoCalculable).con(&nombre),
+                        self.arena.span_expresion(expresion),
+                    ));
+                };
+                
+                self.constantes.insert(self.simbolos.intern(&nombre), valor);
+                constantes.push(Constante {
+                    nombre,
+                    expresion,
+                    valor,
+                    linea: token.line,
+                });
+            } else if token.token_type == TokenType::Indent || 
+                      token.token_type == TokenType::Dedent {
+                self.tokens.avanzar();
+            } else {
+                break;
+            }
+        }
+        
+        Ok(constantes)
+    }
+    
     fn parse_procesos(&mut self) -> Result<Vec<Proceso>, CompilerError> {
         let mut procesos = Vec::new();
         
-        while let Some(token) = self.current {
+        while let Some(token) = self.tokens.actual() {
             if (token.token_type == TokenType::Indent) || (token.token_type == TokenType::Dedent){
-                self.avanzar();
+                self.tokens.avanzar();
             } else if token.token_type == TokenType::Keyword && token.value == "proceso" {
                 procesos.push(self.parse_proceso()?);
             } else {
@@ -371,110 +860,128 @@ impl<'a> Parser<'a> {
     }
     
     fn parse_proceso(&mut self) -> Result<Proceso, CompilerError> {
-        self.consumir(TokenType::Keyword, "Esperado 'proceso'")?;
+        let clave = self.tokens.expect(TokenType::Keyword, esperado("proceso"))?;
+        let archivo = clave.archivo;
+        let linea = self.tokens.actual().map_or(0, |token| token.line);
         
-        let nombre = if let Some(token) = self.current {
-            let nombre = token.value.clone();
-            self.avanzar();
-            nombre
+        let (nombre, encabezado) = if let Some(token) = self.tokens.actual() {
+            let nombre = token.value.to_string();
+            self.tokens.avanzar();
+            (nombre, clave.span().hasta(token.span()))
         } else {
-            return Err(CompilerError::new("Esperado nombre del proceso", 0, 0));
+            return Err(CompilerError::new(Codigo::EsperadoNombreProceso, 0, 0));
         };
         
         // Parámetros
         let mut parametros = Vec::new();
-        if self.coincidir(TokenType::OpenedParenthesis) {
-            self.avanzar(); // consumir '('
+        if self.tokens.check(TokenType::OpenedParenthesis) {
+            self.tokens.avanzar(); // consumir '('
             
-            while let Some(token) = self.current {
+            while let Some(token) = self.tokens.actual() {
                 if token.token_type == TokenType::ClosedParenthesis {
-                    self.avanzar();
+                    self.tokens.avanzar();
                     break;
                 }
                 
                 // Tipo de parámetro (E, S, ES)
                 let tipo_param = if token.token_type == TokenType::ParameterType {
-                    let tipo = token.value.clone();
-                    self.avanzar();
+                    let tipo = token.value.to_string();
+                    self.tokens.avanzar();
                     tipo
                 } else {
                     "E".to_string() // Por defecto
                 };
                 
                 // Nombre del parámetro
-                let nombre_param = if let Some(t) = self.current {
-                    let nombre = t.value.clone();
-                    self.avanzar();
-                    nombre
+                let (nombre_param, linea_param) = if let Some(t) = self.tokens.actual() {
+                    let nombre = t.value.to_string();
+                    self.tokens.avanzar();
+                    (nombre, t.line)
                 } else {
-                    return Err(CompilerError::new("Esperado nombre del parámetro", 0, 0));
+                    return Err(CompilerError::new(Codigo::EsperadoNombreParametro, 0, 0));
                 };
                 
                 // Tipo de dato
-                self.consumir(TokenType::Declaration, "Esperado ':'")?;
-                let tipo_dato = if let Some(t) = self.current {
-                    let tipo = t.value.clone();
-                    self.avanzar();
+                self.tokens.expect(TokenType::Declaration, esperado(":"))?;
+                let tipo_dato = if let Some(t) = self.tokens.actual() {
+                    let tipo = t.value.to_string();
+                    self.tokens.avanzar();
                     tipo
                 } else {
-                    return Err(CompilerError::new("Esperado tipo de dato", 0, 0));
+                    return Err(CompilerError::new(Codigo::EsperadoTipoDato, 0, 0));
                 };
                 
                 parametros.push(Parametro {
                     tipo: tipo_param,
                     nombre: nombre_param,
                     tipo_dato,
+                    linea: linea_param,
                 });
                 
                 // Verificar si hay más parámetros
-                if let Some(t) = self.current {
-                    if t.token_type == TokenType::Comma {
-                        self.avanzar();
-                    }
+                if self.tokens.check(TokenType::Comma) {
+                    self.tokens.avanzar();
+                }
+            }
+        }
+        
+        // Tipo del valor que devuelve
+        let mut retorno = None;
+        if self.tokens.check(TokenType::Declaration) {
+            self.tokens.avanzar(); // consumir ':'
+            match self.tokens.actual() {
+                Some(token) => {
+                    retorno = Some(token.value.to_string());
+                    self.tokens.avanzar();
                 }
+                None => return Err(CompilerError::new(Codigo::EsperadoTipoDato, 0, 0)),
             }
         }
         
         // Variables
         let mut variables = Vec::new();
-        if let Some(token) = self.current {
-            if token.token_type == TokenType::Keyword && token.value == "variables" {
-                self.avanzar(); // consumir "variables"
-                
-                while let Some(token) = self.current {
-                    if token.token_type == TokenType::Keyword && token.value == "comenzar" {
-                        break;
-                    } else if token.token_type == TokenType::Indent || 
-                              token.token_type == TokenType::Dedent {
-                        self.avanzar();
-                    } else if token.token_type == TokenType::Identifier {
-                        variables.push(self.parse_variable()?);
-                    } else {
-                        self.avanzar(); // saltar otros tokens
-                    }
+        if let Some(token) = self.tokens.actual()
+            && token.token_type == TokenType::Keyword && token.value == "variables"
+        {
+            self.tokens.avanzar(); // consumir "variables"
+            
+            while let Some(token) = self.tokens.actual() {
+                if token.token_type == TokenType::Keyword && token.value == "comenzar" {
+                    break;
+                } else if token.token_type == TokenType::Indent || 
+                          token.token_type == TokenType::Dedent {
+                    self.tokens.avanzar();
+                } else if token.token_type == TokenType::Identifier {
+                    variables.push(self.parse_variable()?);
+                } else {
+                    self.tokens.avanzar(); // saltar otros tokens
                 }
             }
         }
         
         // Instrucciones
         let mut instrucciones = Vec::new();
-        if let Some(token) = self.current {
-            if token.token_type == TokenType::Keyword && token.value == "comenzar" {
-                self.avanzar(); // consumir "comenzar"
-                
-                while let Some(token) = self.current {
-                    if token.token_type == TokenType::Keyword && token.value == "fin" {
-                        self.avanzar();
-                        break;
-                    } else if token.token_type == TokenType::Indent || 
-                              token.token_type == TokenType::Dedent {
-                        self.avanzar();
-                    } else {
-                        if let Ok(instr) = self.parse_instruccion() {
-                            instrucciones.push(instr);
-                        } else {
-                            self.avanzar(); // saltar si hay error
-                        }
+        if let Some(token) = self.tokens.actual()
+            && token.token_type == TokenType::Keyword && token.value == "comenzar"
+        {
+            self.tokens.avanzar(); // consumir "comenzar"
+            
+            while let Some(token) = self.tokens.actual() {
+                if token.token_type == TokenType::Keyword && token.value == "fin" {
+                    self.tokens.avanzar();
+                    break;
+                } else if termina_sin_fin(&token) {
+                    return Err(CompilerError::en(
+                        Mensaje::new(Codigo::FaltaFinProceso).con(&nombre),
+                        encabezado
+                    ));
+                } else if token.token_type == TokenType::Indent || 
+                          token.token_type == TokenType::Dedent {
+                    self.tokens.avanzar();
+                } else {
+                    match self.parse_instruccion() {
+                        Ok(instr) => instrucciones.push(instr),
+                        Err(error) => self.saltear_instruccion(&mut instrucciones, token, error),
                     }
                 }
             }
@@ -484,87 +991,113 @@ impl<'a> Parser<'a> {
             nombre,
             parametros,
             variables,
+            retorno,
             instrucciones,
+            archivo,
+            linea,
         })
     }
     
     fn parse_variable(&mut self) -> Result<Variable, CompilerError> {
-        let nombre = if let Some(token) = self.current {
-            let nombre = token.value.clone();
-            self.avanzar();
-            nombre
+        let (nombre, linea) = if let Some(token) = self.tokens.actual() {
+            let nombre = token.value.to_string();
+            self.tokens.avanzar();
+            (nombre, token.line)
         } else {
-            return Err(CompilerError::new("Esperado nombre de variable", 0, 0));
+            return Err(CompilerError::new(Codigo::EsperadoNombreVariable, 0, 0));
         };
         
-        self.consumir(TokenType::Declaration, "Esperado ':'")?;
+        self.tokens.expect(TokenType::Declaration, esperado(":"))?;
         
-        let tipo_dato = if let Some(token) = self.current {
-            let tipo = token.value.clone();
-            self.avanzar();
-            tipo
-        } else {
-            return Err(CompilerError::new("Esperado tipo de dato", 0, 0));
+        let (tipo_dato, longitud) = match self.tokens.actual() {
+            Some(token) if token.token_type == TokenType::Keyword && token.value == "arreglo" => {
+                self.tokens.avanzar();
+                let (tipo, longitud) = self.parse_arreglo(&nombre, linea)?;
+                (tipo, Some(longitud))
+            }
+            Some(token) => {
+                self.tokens.avanzar();
+                (token.value.to_string(), None)
+            }
+            None => return Err(CompilerError::new(Codigo::EsperadoTipoDato, 0, 0)),
         };
         
-        Ok(Variable { nombre, tipo_dato })
+        Ok(Variable { nombre, tipo_dato, longitud, linea })
+    }
+
+    // `[N] de tipo` después de `arreglo`. Como las dimensiones de un área, la
+    // longitud tiene que poder calcularse al compilar.
+    fn parse_arreglo(&mut self, nombre: &str, linea: usize) -> Result<(String, usize), CompilerError> {
+        self.tokens.expect(TokenType::OpenedBracket, esperado("["))?;
+        let expresion = self.parse_expresion_linea_completa(linea)?;
+        self.tokens.expect(TokenType::ClosedBracket, esperado("]"))?;
+        let longitud = valor_con_constantes(&self.arena, &self.arena[expresion], &self.constantes)
+            .and_then(|valor| usize::try_from(valor).ok())
+            .filter(|longitud| *longitud > 0)
+            .ok_or_else(|| CompilerError::en(
+                Mensaje::new(Codigo::LongitudDeArreglo).con(nombre),
+                self.arena.span_expresion(expresion),
+            ))?;
+
+        self.palabra(&["de"]).ok_or_else(|| CompilerError::new(esperado("de"), linea, 0))?;
+        let tipo = self.palabra(&["numero", "booleano"])
+            .ok_or_else(|| CompilerError::new(Codigo::EsperadoTipoDato, linea, 0))?;
+        Ok((tipo.to_string(), longitud))
     }
     
     fn parse_areas(&mut self) -> Result<Vec<Area>, CompilerError> {
         let mut areas = Vec::new();
         
-        while let Some(token) = self.current {
+        while let Some(token) = self.tokens.actual() {
             if token.token_type == TokenType::Identifier {
-                let nombre = token.value.clone();
-                self.avanzar();
+                let nombre = token.value.to_string();
+                let linea = token.line;
+                self.tokens.avanzar();
                 
-                self.consumir(TokenType::Declaration, "Esperado ':'")?;
+                self.tokens.expect(TokenType::Declaration, esperado(":"))?;
                 
-                let tipo = if let Some(t) = self.current {
-                    let tipo = t.value.clone();
-                    self.avanzar();
+                let tipo = if let Some(t) = self.tokens.actual() {
+                    let tipo = t.value.to_string();
+                    self.tokens.avanzar();
                     tipo
                 } else {
-                    return Err(CompilerError::new("Esperado tipo de área", 0, 0));
+                    return Err(CompilerError::new(Codigo::EsperadoTipoArea, 0, 0));
                 };
                 
-                self.consumir(TokenType::OpenedParenthesis, "Esperado '('")?;
+                self.tokens.expect(TokenType::OpenedParenthesis, esperado("("))?;
                 
-                // Leer 4 números
-                let mut nums = Vec::new();
-                for _ in 0..4 {
-                    if let Some(t) = self.current {
-                        if t.token_type == TokenType::Num {
-                            let num = t.value.parse::<i32>().unwrap_or(0);
-                            nums.push(num);
-                            self.avanzar();
-                            
-                            // Consumir coma si no es el último
-                            if nums.len() < 4 {
-                                if let Some(next) = self.current {
-                                    if next.token_type == TokenType::Comma {
-                                        self.avanzar();
-                                    }
-                                }
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                }
-                
-                self.consumir(TokenType::ClosedParenthesis, "Esperado ')'")?;
-                
-                if nums.len() == 4 {
-                    areas.push(Area {
-                        nombre,
-                        tipo,
-                        coordenadas: (nums[0], nums[1], nums[2], nums[3]),
-                    });
+                // Cada dimensión es una expresión que tiene que poder
+                // calcularse al compilar: un número, una constante o una
+                // cuenta entre ellos
+                let dimensiones = self.parse_lista_argumentos()?;
+                self.tokens.expect(TokenType::ClosedParenthesis, esperado(")"))?;
+
+                let mut valores = Vec::new();
+                for (i, dimension) in dimensiones.iter().enumerate() {
+                    let Some(valor) = valor_con_constantes(&self.arena, &self.arena[*dimension], &self.constantes) else {
+                        return Err(CompilerError::en(
+                            Mensaje::new(Codigo::DimensionNoConstante).con(i + 1).con(&nombre),
+                            self.arena.span_expresion(*dimension),
+                        ));
+                    };
+                    valores.push(valor);
                 }
+                let [x1, y1, x2, y2] = valores[..] else {
+                    return Err(CompilerError::new(
+                        Mensaje::new(Codigo::CantidadDeDimensiones).con(&nombre).con(valores.len()),
+                        linea, 0
+                    ));
+                };
+
+                areas.push(Area {
+                    nombre,
+                    tipo,
+                    coordenadas: (x1, y1, x2, y2),
+                    linea,
+                });
             } else if token.token_type == TokenType::Indent || 
                       token.token_type == TokenType::Dedent {
-                self.avanzar();
+                self.tokens.avanzar();
             } else {
                 break;
             }
@@ -577,61 +1110,83 @@ impl<'a> Parser<'a> {
         let mut declarados = Vec::new();
         let mut definidos = Vec::new();
         
-        while let Some(token) = self.current {
+        while let Some(token) = self.tokens.actual() {
             if ((token.token_type == TokenType::Keyword) && (token.value == "robot") && (token.value != "variables")) {
-                self.avanzar(); // consumir "robot"
+                let archivo = token.archivo;
+                self.tokens.avanzar(); // consumir "robot"
                 
                 // Nombre del robot
-                let nombre = if let Some(t) = self.current {
-                    let nombre = t.value.clone();
-                    self.avanzar();
-                    nombre
+                let (nombre, linea, encabezado) = if let Some(t) = self.tokens.actual() {
+                    let nombre = t.value.to_string();
+                    self.tokens.avanzar();
+                    (nombre, t.line, token.span().hasta(t.span()))
                 } else {
-                    return Err(CompilerError::new("Esperado nombre del robot", 0, 0));
+                    return Err(CompilerError::new(Codigo::EsperadoNombreRobot, 0, 0));
                 };
                 
                 declarados.push(nombre.clone());
                 
                 // Variables del robot
                 let mut variables = Vec::new();
-                if let Some(t) = self.current {
-                    if t.token_type == TokenType::Keyword && t.value == "variables" {
-                        self.avanzar(); // consumir "variables"
-                        
-                        while let Some(t) = self.current {
-                            if t.token_type == TokenType::Keyword && t.value == "comenzar" {
-                                break;
-                            } else if t.token_type == TokenType::Indent || 
-                                      t.token_type == TokenType::Dedent {
-                                self.avanzar();
-                            } else if t.token_type == TokenType::Identifier {
-                                variables.push(self.parse_variable()?);
-                            } else {
-                                self.avanzar();
-                            }
+                if let Some(t) = self.tokens.actual()
+                    && t.token_type == TokenType::Keyword && t.value == "variables"
+                {
+                    self.tokens.avanzar(); // consumir "variables"
+                    
+                    while let Some(t) = self.tokens.actual() {
+                        if t.token_type == TokenType::Keyword && t.value == "comenzar" || self.es_bloque_mensajes() {
+                            break;
+                        } else if t.token_type == TokenType::Indent || 
+                                  t.token_type == TokenType::Dedent {
+                            self.tokens.avanzar();
+                        } else if t.token_type == TokenType::Identifier {
+                            variables.push(self.parse_variable()?);
+                        } else {
+                            self.tokens.avanzar();
                         }
                     }
                 }
                 
+                // Tipos de los mensajes del robot
+                let mut mensajes = Vec::new();
+                if self.es_bloque_mensajes() {
+                    self.tokens.avanzar(); // consumir "mensajes"
+
+                    while let Some(t) = self.tokens.actual() {
+                        if t.token_type == TokenType::Keyword && t.value == "comenzar" {
+                            break;
+                        } else if t.token_type == TokenType::Indent ||
+                                  t.token_type == TokenType::Dedent {
+                            self.tokens.avanzar();
+                        } else {
+                            mensajes.push(self.parse_contrato_mensaje()?);
+                        }
+                    }
+                }
+
                 // Instrucciones del robot
                 let mut instrucciones = Vec::new();
-                if let Some(t) = self.current {
-                    if t.token_type == TokenType::Keyword && t.value == "comenzar" {
-                        self.avanzar(); // consumir "comenzar"
-                        
-                        while let Some(t) = self.current {
-                            if t.token_type == TokenType::Keyword && t.value == "fin" {
-                                self.avanzar();
-                                break;
-                            } else if t.token_type == TokenType::Indent || 
-                                      t.token_type == TokenType::Dedent {
-                                self.avanzar();
-                            } else {
-                                if let Ok(instr) = self.parse_instruccion() {
-                                    instrucciones.push(instr);
-                                } else {
-                                    self.avanzar();
-                                }
+                if let Some(t) = self.tokens.actual()
+                    && t.token_type == TokenType::Keyword && t.value == "comenzar"
+                {
+                    self.tokens.avanzar(); // consumir "comenzar"
+                    
+                    while let Some(t) = self.tokens.actual() {
+                        if t.token_type == TokenType::Keyword && t.value == "fin" {
+                            self.tokens.avanzar();
+                            break;
+                        } else if termina_sin_fin(&t) {
+                            return Err(CompilerError::en(
+                                Mensaje::new(Codigo::FaltaFinRobot).con(&nombre),
+                                encabezado
+                            ));
+                        } else if t.token_type == TokenType::Indent || 
+                                  t.token_type == TokenType::Dedent {
+                            self.tokens.avanzar();
+                        } else {
+                            match self.parse_instruccion() {
+                                Ok(instr) => instrucciones.push(instr),
+                                Err(error) => self.saltear_instruccion(&mut instrucciones, t, error),
                             }
                         }
                     }
@@ -640,11 +1195,14 @@ impl<'a> Parser<'a> {
                 definidos.push(Robot {
                     nombre,
                     variables,
+                    mensajes,
                     instrucciones,
+                    archivo,
+                    linea,
                 });
             } else if token.token_type == TokenType::Indent || 
                       token.token_type == TokenType::Dedent {
-                self.avanzar();
+                self.tokens.avanzar();
             } else {
                 break;
             }
@@ -653,114 +1211,182 @@ impl<'a> Parser<'a> {
         Ok((declarados, definidos))
     }
     
-    fn parse_instruccion(&mut self) -> Result<Instruccion, CompilerError> {
-        if let Some(token) = self.current {
+    // `mensajes` no es una palabra reservada: sólo abre el bloque si no es
+    // una variable que se está declarando
+    fn es_bloque_mensajes(&mut self) -> bool {
+        let es_identificador = self.tokens.actual()
+            .is_some_and(|t| t.token_type == TokenType::Identifier && t.value == "mensajes");
+        es_identificador && self.tokens.peek(1).is_none_or(|t| t.token_type != TokenType::Declaration)
+    }
+
+    // envia <tipo> a <robot> | recibe <tipo> de <robot>
+    fn parse_contrato_mensaje(&mut self) -> Result<ContratoMensaje, CompilerError> {
+        let inicio = self.tokens.actual().map(|t| t.span()).unwrap_or_default();
+        let error = || CompilerError::en(Mensaje::new(Codigo::EsperadoContratoMensaje), inicio);
+
+        let sentido = match self.palabra(&["envia", "recibe"]).ok_or_else(error)? {
+            "envia" => SentidoMensaje::Envia,
+            _ => SentidoMensaje::Recibe,
+        };
+        let tipo_dato = self.palabra(&["numero", "booleano", "cadena"]).ok_or_else(error)?.to_string();
+        let preposicion = match sentido {
+            SentidoMensaje::Envia => "a",
+            SentidoMensaje::Recibe => "de",
+        };
+        self.palabra(&[preposicion]).ok_or_else(error)?;
+        let robot = match self.tokens.actual() {
+            Some(t) if matches!(t.token_type, TokenType::Identifier | TokenType::Multiply) => t,
+            _ => return Err(error()),
+        };
+        self.tokens.avanzar();
+
+        Ok(ContratoMensaje { sentido, tipo_dato, robot: robot.value.to_string(), linea: inicio.linea })
+    }
+
+    // Consume el token actual si su texto es uno de `valores`
+    fn palabra(&mut self, valores: &[&str]) -> Option<&'src str> {
+        let token = self.tokens.actual().filter(|t| valores.contains(&t.value))?;
+        self.tokens.avanzar();
+        Some(token.value)
+    }
+
+    // Parsea una instrucción y la guarda en el arena
+    fn parse_instruccion(&mut self) -> Result<InstrId, CompilerError> {
+        let inicio = self.tokens.actual().map(|token| token.span()).unwrap_or_default();
+        let instruccion = self.anidado(inicio, Self::parse_nodo_instruccion)?;
+        let span = self.tokens.ultimo_consumido().map_or(inicio, |fin| inicio.hasta(fin.span()));
+        Ok(self.arena.agregar_instruccion(instruccion, span))
+    }
+    
+    fn parse_nodo_instruccion(&mut self) -> Result<Instruccion, CompilerError> {
+        if let Some(token) = self.tokens.actual() {
             let start_line = token.line; // Guardar línea inicial
             
             match token.token_type {
+                // Con un token de lookahead se distingue `x := ...` (o
+                // `x[i] := ...`) de una llamada `x(...)`
+                TokenType::Identifier if self.tokens.peek(1).is_some_and(|t| matches!(t.token_type, TokenType::Assign | TokenType::OpenedBracket)) => {
+                    let nombre = token.value;
+                    self.tokens.avanzar();
+                    let indice = if self.tokens.check(TokenType::OpenedBracket) {
+                        self.tokens.avanzar(); // consumir '['
+                        let indice = self.parse_expresion_linea_completa(start_line)?;
+                        self.tokens.expect(TokenType::ClosedBracket, esperado("]"))?;
+                        Some(indice)
+                    } else {
+                        None
+                    };
+                    self.tokens.expect(TokenType::Assign, esperado(":="))?;
+                    
+                    // Parsear expresión completa hasta cambio de línea o indentación
+                    let valor = self.parse_expresion_linea_completa(start_line)?;
+                    
+                    Ok(Instruccion::Asignacion {
+                        variable: self.simbolos.intern(nombre),
+                        indice,
+                        valor,
+                        linea: start_line,
+                    })
+                }
                 TokenType::Identifier => {
-                    let nombre = token.value.clone();
-                    self.avanzar();
+                    let nombre = token.value;
+                    self.tokens.avanzar();
                     
-                    // Verificar si es asignación
-                    if let Some(t) = self.current {
-                        if t.token_type == TokenType::Assign {
-                            self.avanzar(); // consumir ":="
-                            
-                            // Parsear expresión completa hasta cambio de línea o indentación
-                            let valor = self.parse_expresion_linea_completa(start_line)?;
-                            
-                            Ok(Instruccion::Asignacion {
-                                variable: nombre,
-                                valor,
-                            })
-                        } else {
-                            // Llamada a función
-                            let argumentos = if self.coincidir(TokenType::OpenedParenthesis) {
-                                self.avanzar(); // consumir '('
-                                let args = self.parse_lista_argumentos()?;
-                                self.consumir(TokenType::ClosedParenthesis, "Esperado ')'")?;
-                                args
-                            } else {
-                                Vec::new()
-                            };
-                            
-                            Ok(Instruccion::LlamadaFuncion {
-                                nombre,
-                                argumentos,
-                            })
-                        }
+                    // Llamada a función, con o sin argumentos
+                    let argumentos = if self.tokens.check(TokenType::OpenedParenthesis) {
+                        self.tokens.avanzar(); // consumir '('
+                        let args = self.parse_lista_argumentos()?;
+                        self.tokens.expect(TokenType::ClosedParenthesis, esperado(")"))?;
+                        args
                     } else {
-                        // Solo identificador sin operador (instrucción simple)
-                        Ok(Instruccion::LlamadaFuncion {
-                            nombre,
-                            argumentos: Vec::new(),
-                        })
-                    }
+                        Vec::new()
+                    };
+                    
+                    Ok(Instruccion::LlamadaFuncion {
+                        nombre: self.simbolos.intern(nombre),
+                        argumentos,
+                        linea: start_line,
+                    })
                 }
                 TokenType::ElementalInstruction => {
-                    let nombre = token.value.clone();
-                    self.avanzar();
+                    let nombre = token.value;
+                    self.tokens.avanzar();
                     
                     // Verificar si es una de las palabras clave especiales
-                    if self.es_instruccion_elemental(&nombre) {
+                    if self.es_instruccion_elemental(nombre) {
                         // Instrucción elemental sin argumentos
-                        Ok(Instruccion::Elemental { nombre })
+                        Ok(Instruccion::Elemental { nombre: self.simbolos.intern(nombre), linea: start_line })
                     } else {
                         // Llamada a función elemental
-                        let argumentos = if self.coincidir(TokenType::OpenedParenthesis) {
-                            self.avanzar(); // consumir '('
+                        let argumentos = if self.tokens.check(TokenType::OpenedParenthesis) {
+                            self.tokens.avanzar(); // consumir '('
                             let args = self.parse_lista_argumentos()?;
-                            self.consumir(TokenType::ClosedParenthesis, "Esperado ')'")?;
+                            self.tokens.expect(TokenType::ClosedParenthesis, esperado(")"))?;
                             args
                         } else {
                             Vec::new()
                         };
                         
                         Ok(Instruccion::LlamadaFuncion {
-                            nombre,
+                            nombre: self.simbolos.intern(nombre),
                             argumentos,
+                            linea: start_line,
                         })
                     }
                 }
-                TokenType::ControlSentence => match token.value.as_str() {
+                TokenType::ControlSentence => match token.value {
                     "si" => self.parse_si(),
                     "mientras" => self.parse_mientras(),
                     "repetir" => self.parse_repetir(),
-                    _ => Err(CompilerError::new(
-                        format!("Instrucción de control desconocida: {}", token.value),
-                        token.line,
-                        token.column
+                    "segun" => self.parse_segun(),
+                    "para" => self.parse_para(),
+                    _ => Err(CompilerError::en(
+                        Mensaje::new(Codigo::ControlDesconocido).con(token.value),
+                        token.span()
                     )),
                 }
-                _ => Err(CompilerError::new(
-                    format!("Instrucción no reconocida: {:?}", token.token_type),
-                    token.line,
-                    token.column
+                _ => Err(CompilerError::en(
+                    Mensaje::new(Codigo::InstruccionNoReconocida).con(format!("{:?}", token.token_type)),
+                    token.span()
                 )),
             }
         } else {
-            Err(CompilerError::new("Se esperaba una instrucción", 0, 0))
+            Err(CompilerError::new(Codigo::EsperadaInstruccion, 0, 0))
+        }
+    }
+
+    // Saltea un token de una instrucción que no se pudo parsear y deja en
+    // su lugar un `Instruccion::Error`, uno solo por cada tramo salteado.
+    // Se guarda el error del comienzo del tramo; los del resto son
+    // consecuencia de él.
+    fn saltear_instruccion(&mut self, instrucciones: &mut Vec<InstrId>, inicio: Token, error: CompilerError) {
+        let anterior = instrucciones.last().map(|&id| &self.arena[id]);
+        if !matches!(anterior, Some(Instruccion::Error { .. })) {
+            self.errores.push(error);
+            instrucciones.push(self.arena.agregar_instruccion(Instruccion::Error { linea: inicio.line }, inicio.span()));
         }
+        self.tokens.avanzar();
     }
 
-    // Verificar si es una instrucción elemental (sin argumentos)
     fn es_instruccion_elemental(&self, nombre: &str) -> bool {
-        matches!(nombre,
-            "HayFlorEnLaBolsa" |
-            "HayPapelEnLaBolsa" |
-            "HayFlorEnLaEsquina" |
-            "HayPapelEnLaEsquina"
-        )
+        tipo_sensor(nombre).is_some()
     }
 
     // Nueva función para parsear expresión completa en una línea
-    fn parse_expresion_linea_completa(&mut self, start_line: usize) -> Result<Expresion, CompilerError> {
+    fn parse_expresion_linea_completa(&mut self, start_line: usize) -> Result<ExprId, CompilerError> {
+        self.parse_expresion_binaria(start_line, 0)
+    }
+
+    // Operadores por precedencia: primero `* /`, después `+ -`, las
+    // comparaciones, `&` y por último `|`. Los de igual precedencia se
+    // agrupan de izquierda a derecha.
+    fn parse_expresion_binaria(&mut self, start_line: usize, precedencia_minima: u8) -> Result<ExprId, CompilerError> {
         // Parsear la primera parte de la expresión
-        let mut expr = self.parse_expresion_simple()?;
+        let inicio = self.tokens.actual().map(|token| token.span()).unwrap_or_default();
+        let mut expr = self.anidado(inicio, Self::parse_expresion_simple)?;
         
         // Continuar parseando mientras estemos en la misma línea
-        while let Some(token) = self.current {
+        while let Some(token) = self.tokens.actual() {
             // Verificar si seguimos en la misma línea
             if token.line != start_line {
                 break;
@@ -775,16 +1401,21 @@ impl<'a> Parser<'a> {
                 break;
             }
             
-            // Si encontramos un operador, parsear como expresión binaria
-            if self.es_operador_binario(token) {
+            // Si encontramos un operador, parsear como expresión binaria; uno
+            // de menor precedencia lo agrupa quien llamó
+            if let Some(precedencia) = token.token_type.binding_power() {
+                if precedencia < precedencia_minima {
+                    break;
+                }
                 let operador = self.parse_operador_binario()?;
-                let derecha = self.parse_expresion_simple()?;
+                let derecha = self.parse_expresion_binaria(start_line, precedencia + 1)?;
                 
-                expr = Expresion::Binaria {
-                    izquierda: Box::new(expr),
+                let span = self.arena.span_expresion(expr).hasta(self.arena.span_expresion(derecha));
+                expr = self.arena.agregar_expresion(Expresion::Binaria {
+                    izquierda: expr,
                     operador,
-                    derecha: Box::new(derecha),
-                };
+                    derecha,
+                }, span);
             } else {
                 // Si no es operador, terminamos la expresión
                 break;
@@ -795,160 +1426,130 @@ impl<'a> Parser<'a> {
     }
 
     // Método para parsear expresión simple (sin operadores binarios)
-    fn parse_expresion_simple(&mut self) -> Result<Expresion, CompilerError> {
-        if let Some(token) = self.current {
-            match token.token_type {
+    fn parse_expresion_simple(&mut self) -> Result<ExprId, CompilerError> {
+        if let Some(token) = self.tokens.actual() {
+            let expresion = match token.token_type {
                 TokenType::ElementalInstruction => {
-                    let nombre = token.value.clone();
-                    self.avanzar();
+                    let nombre = token.value;
+                    self.tokens.avanzar();
                     
-                    // Verificar si es una instrucción elemental
-                    if self.es_instruccion_elemental(&nombre) {
-                        Ok(Expresion::Elemental { nombre: nombre.clone() })
-                    } else {
-                        // Llamada a función elemental
-                        let argumentos = if self.coincidir(TokenType::OpenedParenthesis) {
-                            self.avanzar(); // consumir '('
-                            let args = self.parse_lista_argumentos()?;
-                            self.consumir(TokenType::ClosedParenthesis, "Esperado ')'")?;
-                            args
-                        } else {
-                            Vec::new()
-                        };
-                        
-                        // Convertir llamada a función a expresión
-                        if argumentos.is_empty() {
-                            Ok(Expresion::Identificador(nombre))
-                        } else {
-                            // Para simplificar, tratamos funciones con argumentos como identificadores
-                            Ok(Expresion::Identificador(format!("{}(...)", nombre)))
-                        }
+                    // Sólo los sensores producen un valor; el resto de las
+                    // instrucciones elementales son acciones
+                    if !self.es_instruccion_elemental(nombre) {
+                        return Err(CompilerError::en(
+                            Mensaje::new(Codigo::InstruccionSinValor).con(nombre),
+                            token.span()
+                        ));
                     }
+                    Expresion::Sensor { nombre: self.simbolos.intern(nombre) }
+                },
+                // Un nombre seguido de paréntesis es una llamada a proceso
+                TokenType::Identifier if self.tokens.peek(1).is_some_and(|t| t.token_type == TokenType::OpenedParenthesis) => {
+                    let nombre = self.simbolos.intern(token.value);
+                    self.tokens.avanzar();
+                    self.tokens.avanzar(); // consumir '('
+                    let argumentos = self.parse_lista_argumentos()?;
+                    let cierre = self.tokens.expect(TokenType::ClosedParenthesis, esperado(")"))?;
+                    let span = token.span().hasta(cierre.span());
+                    return Ok(self.arena.agregar_expresion(Expresion::Llamada { nombre, argumentos }, span));
+                },
+                TokenType::Identifier if self.tokens.peek(1).is_some_and(|t| t.token_type == TokenType::OpenedBracket) => {
+                    let arreglo = self.simbolos.intern(token.value);
+                    self.tokens.avanzar();
+                    self.tokens.avanzar(); // consumir '['
+                    let indice = self.parse_expresion_linea_completa(token.line)?;
+                    let cierre = self.tokens.expect(TokenType::ClosedBracket, esperado("]"))?;
+                    let span = token.span().hasta(cierre.span());
+                    return Ok(self.arena.agregar_expresion(Expresion::Indice { arreglo, indice }, span));
                 },
                 TokenType::Identifier => {
-                    let nombre = token.value.clone();
-                    self.avanzar();
-                    Ok(Expresion::Identificador(nombre))
+                    let nombre = token.value;
+                    self.tokens.avanzar();
+                    Expresion::Identificador(self.simbolos.intern(nombre))
                 },
                 TokenType::Num => {
                     let valor = token.value.parse::<i32>().unwrap_or(0);
-                    self.avanzar();
-                    Ok(Expresion::Numero(valor))
+                    self.tokens.avanzar();
+                    Expresion::Numero(valor)
                 },
                 TokenType::BoolValue => {
-                    let valor = token.value == "V";
-                    self.avanzar();
-                    Ok(Expresion::Booleano(valor))
+                    let valor = token.valor_booleano() == Some(true);
+                    self.tokens.avanzar();
+                    Expresion::Booleano(valor)
+                },
+                TokenType::Str => {
+                    self.tokens.avanzar();
+                    Expresion::Cadena(token.valor_cadena().into_owned())
+                },
+                // `~` agrupa más fuerte que cualquier operador binario
+                TokenType::Not => {
+                    self.tokens.avanzar();
+                    let operando = self.anidado(token.span(), Self::parse_expresion_simple)?;
+                    let span = token.span().hasta(self.arena.span_expresion(operando));
+                    return Ok(self.arena.agregar_expresion(Expresion::Negacion(operando), span));
                 },
                 TokenType::OpenedParenthesis => {
-                    self.avanzar(); // consumir '('
+                    // La expresión entre paréntesis ya queda guardada en el arena
+                    self.tokens.avanzar(); // consumir '('
                     let expr = self.parse_expresion_linea_completa(token.line)?;
-                    self.consumir(TokenType::ClosedParenthesis, "Esperado ')'")?;
-                    Ok(expr)
+                    self.tokens.expect(TokenType::ClosedParenthesis, esperado(")"))?;
+                    return Ok(expr);
                 },
-                _ => Err(CompilerError::new(
-                    format!("Expresión simple no válida: {:?}", token.token_type),
-                    token.line,
-                    token.column
+                _ => return Err(CompilerError::en(
+                    Mensaje::new(Codigo::ExpresionSimpleInvalida).con(format!("{:?}", token.token_type)),
+                    token.span()
                 )),
-            }
+            };
+            Ok(self.arena.agregar_expresion(expresion, token.span()))
         } else {
-            Err(CompilerError::new("Se esperaba una expresión simple", 0, 0))
+            Err(CompilerError::new(Codigo::EsperadaExpresionSimple, 0, 0))
         }
     }
 
-    // Verificar si el token es un operador binario
-    fn es_operador_binario(&self, token: &Token) -> bool {
-        matches!(token.token_type,
-            TokenType::Plus |
-            TokenType::Minus |
-            TokenType::Multiply |
-            TokenType::Divide |
-            TokenType::Less |
-            TokenType::LessEqual |
-            TokenType::Greater |
-            TokenType::GreaterEqual |
-            TokenType::Equals |
-            TokenType::NotEquals |
-            TokenType::And |
-            TokenType::Or
-        )
-    }
-
     // Parsear operador binario
     fn parse_operador_binario(&mut self) -> Result<String, CompilerError> {
-        if let Some(token) = self.current {
-            if self.es_operador_binario(token) {
-                let operador = match token.token_type {
-                    TokenType::Plus => "+",
-                    TokenType::Minus => "-",
-                    TokenType::Multiply => "*",
-                    TokenType::Divide => "/",
-                    TokenType::Less => "<",
-                    TokenType::LessEqual => "<=",
-                    TokenType::Greater => ">",
-                    TokenType::GreaterEqual => ">=",
-                    TokenType::Equals => "==",
-                    TokenType::NotEquals => "<>",
-                    TokenType::And => "&",
-                    TokenType::Or => "|",
-                    _ => return Err(CompilerError::new(
-                        "Operador no reconocido",
-                        token.line,
-                        token.column
-                    )),
-                };
-                
-                self.avanzar();
+        if let Some(token) = self.tokens.actual() {
+            if let Some(operador) = token.token_type.operator_symbol() {
+                self.tokens.avanzar();
                 Ok(operador.to_string())
             } else {
-                Err(CompilerError::new(
-                    format!("Se esperaba operador binario, encontrado: {:?}", token.token_type),
-                    token.line,
-                    token.column
+                Err(CompilerError::en(
+                    Mensaje::new(Codigo::OperadorEsperadoEncontrado).con(format!("{:?}", token.token_type)),
+                    token.span()
                 ))
             }
         } else {
-            Err(CompilerError::new("Se esperaba operador binario", 0, 0))
+            Err(CompilerError::new(Codigo::EsperadoOperador, 0, 0))
         }
     }
     
     fn parse_si(&mut self) -> Result<Instruccion, CompilerError> {
-        self.avanzar(); // consumir "si"
+        let linea = self.tokens.actual().map_or(0, |token| token.line);
+        self.tokens.avanzar(); // consumir "si"
         
         let condicion = self.parse_expresion()?;
         
-        let mut entonces = Vec::new();
-        while let Some(token) = self.current {
-            if token.token_type == TokenType::ControlSentence && token.value == "sino" {
-                self.avanzar(); // consumir "sino"
-                break;
-            } else if token.token_type == TokenType::Dedent {
-                break;
-            } else if token.token_type == TokenType::Indent || 
-                      token.token_type == TokenType::Dedent {
-                self.avanzar();
-            } else {
-                if let Ok(instr) = self.parse_instruccion() {
-                    entonces.push(instr);
-                } else {
-                    self.avanzar();
-                }
-            }
-        }
+        let entonces = self.parse_bloque();
         
+        // `sino si <condición>` en una misma línea agrega una rama a la
+        // cadena; el primer `sino` solo la termina
+        let mut alternativas = Vec::new();
         let mut sino = Vec::new();
-        while let Some(token) = self.current {
-            if token.token_type == TokenType::Dedent {
-                break;
-            } else if token.token_type == TokenType::Indent || 
-                      token.token_type == TokenType::Dedent {
-                self.avanzar();
-            } else {
-                if let Ok(instr) = self.parse_instruccion() {
-                    sino.push(instr);
-                } else {
-                    self.avanzar();
+        while let Some(token) = self.tokens.actual()
+            && token.token_type == TokenType::ControlSentence
+            && token.value == "sino"
+        {
+            self.tokens.avanzar(); // consumir "sino"
+            match self.tokens.actual() {
+                Some(si) if si.token_type == TokenType::ControlSentence && si.value == "si" && si.line == token.line => {
+                    self.tokens.avanzar(); // consumir "si"
+                    let condicion = self.parse_expresion()?;
+                    let cuerpo = self.parse_bloque();
+                    alternativas.push(Alternativa { condicion, cuerpo, linea: token.line });
+                }
+                _ => {
+                    sino = self.parse_bloque();
+                    break;
                 }
             }
         }
@@ -956,91 +1557,217 @@ impl<'a> Parser<'a> {
         Ok(Instruccion::Si {
             condicion,
             entonces,
+            alternativas,
             sino,
+            linea,
         })
     }
     
     fn parse_mientras(&mut self) -> Result<Instruccion, CompilerError> {
-        self.avanzar(); // consumir "mientras"
+        let linea = self.tokens.actual().map_or(0, |token| token.line);
+        self.tokens.avanzar(); // consumir "mientras"
         
-        let condicion = if self.coincidir(TokenType::OpenedParenthesis) {
-            self.avanzar(); // consumir '('
-            let cond = self.parse_expresion()?;
-            self.consumir(TokenType::ClosedParenthesis, "Esperado ')'")?;
-            cond
-        } else {
-            self.parse_expresion()?
-        };
+        // Los paréntesis, si los hay, son parte de la expresión: la condición
+        // puede seguir después de cerrarlos, como en `(a) & b`
+        let condicion = self.parse_expresion()?;
         
-        let mut cuerpo = Vec::new();
-        while let Some(token) = self.current {
-            if token.token_type == TokenType::Dedent {
-                break;
-            } else if token.token_type == TokenType::Indent || 
-                      token.token_type == TokenType::Dedent {
-                self.avanzar();
-            } else {
-                if let Ok(instr) = self.parse_instruccion() {
-                    cuerpo.push(instr);
-                } else {
-                    self.avanzar();
-                }
-            }
-        }
+        let cuerpo = self.parse_bloque();
         
-        Ok(Instruccion::Mientras { condicion, cuerpo })
+        Ok(Instruccion::Mientras { condicion, cuerpo, linea })
     }
     
     fn parse_repetir(&mut self) -> Result<Instruccion, CompilerError> {
-        self.avanzar(); // consumir "repetir"
+        let linea = self.tokens.actual().map_or(0, |token| token.line);
+        self.tokens.avanzar(); // consumir "repetir"
         
         let condicion = self.parse_expresion()?;
         
-        let mut cuerpo = Vec::new();
-        while let Some(token) = self.current {
-            if token.token_type == TokenType::Dedent {
-                break;
-            } else if token.token_type == TokenType::Indent || 
-                      token.token_type == TokenType::Dedent {
-                self.avanzar();
-            } else {
-                if let Ok(instr) = self.parse_instruccion() {
-                    cuerpo.push(instr);
-                } else {
-                    self.avanzar();
+        let cuerpo = self.parse_bloque();
+        
+        Ok(Instruccion::Repetir { condicion, cuerpo, linea })
+    }
+    
+    fn parse_para(&mut self) -> Result<Instruccion, CompilerError> {
+        let linea = self.tokens.actual().map_or(0, |token| token.line);
+        self.tokens.avanzar(); // consumir "para"
+        
+        let nombre = self.tokens.expect(TokenType::Identifier, Codigo::EsperadoNombreVariable)?;
+        self.tokens.expect(TokenType::Assign, esperado(":="))?;
+        let desde = self.parse_expresion()?;
+        match self.tokens.actual() {
+            Some(token) if token.token_type == TokenType::ControlSentence && token.value == "hasta" => {
+                self.tokens.avanzar();
+            }
+            Some(token) => return Err(CompilerError::en(esperado("hasta"), token.span())),
+            None => return Err(CompilerError::new(esperado("hasta"), linea, 0)),
+        }
+        let hasta = self.parse_expresion()?;
+        
+        let variable = self.simbolos.intern(nombre.value);
+        let span = nombre.span();
+        let diferencia = self.arena.agregar_expresion(
+            Expresion::Binaria { izquierda: hasta, operador: "-".to_string(), derecha: desde }, span
+        );
+        let uno = self.arena.agregar_expresion(Expresion::Numero(1), span);
+        let cantidad = self.arena.agregar_expresion(
+            Expresion::Binaria { izquierda: diferencia, operador: "+".to_string(), derecha: uno }, span
+        );
+        let actual = self.arena.agregar_expresion(Expresion::Identificador(variable), span);
+        let siguiente = self.arena.agregar_expresion(
+            Expresion::Binaria { izquierda: actual, operador: "+".to_string(), derecha: uno }, span
+        );
+        
+        let cuerpo = self.parse_bloque();
+        
+        Ok(Instruccion::Para { variable, desde, hasta, cantidad, siguiente, cuerpo, linea })
+    }
+    
+    // `segun <selector>` seguido de un bloque con los `caso <valor>:` y, al
+    // final, un `sino` opcional
+    fn parse_segun(&mut self) -> Result<Instruccion, CompilerError> {
+        let linea = self.tokens.actual().map_or(0, |token| token.line);
+        self.tokens.avanzar(); // consumir "segun"
+        
+        let selector = self.parse_expresion()?;
+        self.tokens.expect(TokenType::Indent, esperado("caso"))?;
+        
+        let mut casos = Vec::new();
+        let mut sino = None;
+        while let Some(token) = self.tokens.actual() {
+            match (token.token_type, token.value) {
+                (TokenType::Dedent, _) if !casos.is_empty() => {
+                    self.tokens.avanzar();
+                    break;
+                }
+                (TokenType::ControlSentence, "caso") if sino.is_none() => {
+                    self.tokens.avanzar(); // consumir "caso"
+                    let valor = self.parse_expresion()?;
+                    self.tokens.expect(TokenType::Declaration, esperado(":"))?;
+                    let span = self.arena.span_expresion(valor);
+                    let comparacion = self.arena.agregar_expresion(Expresion::Binaria {
+                        izquierda: selector,
+                        operador: "==".to_string(),
+                        derecha: valor,
+                    }, span);
+                    let cuerpo = self.parse_bloque();
+                    casos.push(Caso { valor, comparacion, cuerpo, linea: token.line });
+                }
+                (TokenType::ControlSentence, "sino") if !casos.is_empty() && sino.is_none() => {
+                    self.tokens.avanzar(); // consumir "sino"
+                    sino = Some(self.parse_bloque());
                 }
+                // Después del `sino` sólo puede terminar el bloque
+                _ if sino.is_some() => return Err(CompilerError::en(esperado("fin del segun"), token.span())),
+                _ => return Err(CompilerError::en(esperado("caso"), token.span())),
             }
         }
         
-        Ok(Instruccion::Repetir { condicion, cuerpo })
+        Ok(Instruccion::Segun {
+            selector,
+            casos,
+            sino: sino.unwrap_or_default(),
+            linea,
+        })
     }
     
+    // Bloque indentado de una sentencia de control, desde su INDENT hasta el
+    // DEDENT que lo cierra, ambos incluidos. Sin INDENT el bloque está vacío.
+    fn parse_bloque(&mut self) -> Vec<InstrId> {
+        let mut instrucciones = Vec::new();
+        if !self.tokens.check(TokenType::Indent) {
+            return instrucciones;
+        }
+        self.tokens.avanzar(); // consumir INDENT
+
+        while let Some(token) = self.tokens.actual() {
+            match token.token_type {
+                TokenType::Dedent => {
+                    self.tokens.avanzar();
+                    break;
+                }
+                // Una indentación de más sin sentencia de control que la
+                // abra sigue siendo parte de este bloque
+                TokenType::Indent => match self.anidado(token.span(), |parser| Ok(parser.parse_bloque())) {
+                    Ok(anidadas) => instrucciones.extend(anidadas),
+                    Err(_) => self.tokens.avanzar(),
+                },
+                _ => {
+                    match self.parse_instruccion() {
+                        Ok(instr) => instrucciones.push(instr),
+                        Err(error) => self.saltear_instruccion(&mut instrucciones, token, error),
+                    }
+                }
+            }
+        }
+        instrucciones
+    }
+
+    // Parsea un nivel más de bloques o paréntesis, o falla si ya hay
+    // `ANIDAMIENTO_MAXIMO` abiertos
+    fn anidado<T>(&mut self, span: Span, parsear: impl FnOnce(&mut Self) -> Result<T, CompilerError>) -> Result<T, CompilerError> {
+        if self.anidamiento >= ANIDAMIENTO_MAXIMO {
+            let error = CompilerError::en(Mensaje::new(Codigo::AnidamientoExcesivo).con(ANIDAMIENTO_MAXIMO), span);
+            self.error_anidamiento.get_or_insert_with(|| error.clone());
+            return Err(error);
+        }
+        self.anidamiento += 1;
+        let resultado = parsear(self);
+        self.anidamiento -= 1;
+        resultado
+    }
+
     // Método parse_expresion original modificado para usar la nueva implementación
-    fn parse_expresion(&mut self) -> Result<Expresion, CompilerError> {
-        if let Some(token) = self.current {
+    fn parse_expresion(&mut self) -> Result<ExprId, CompilerError> {
+        if let Some(token) = self.tokens.actual() {
             self.parse_expresion_linea_completa(token.line)
         } else {
-            Err(CompilerError::new("Se esperaba una expresión", 0, 0))
+            Err(CompilerError::new(Codigo::EsperadaExpresion, 0, 0))
         }
     }
     
-    fn parse_lista_argumentos(&mut self) -> Result<Vec<Expresion>, CompilerError> {
+    fn parse_lista_argumentos(&mut self) -> Result<Vec<ExprId>, CompilerError> {
         let mut argumentos = Vec::new();
         
-        while let Some(token) = self.current {
+        while let Some(token) = self.tokens.actual() {
             if token.token_type == TokenType::ClosedParenthesis {
                 break;
             }
             
-            argumentos.push(self.parse_expresion()?);
-            
-            if let Some(t) = self.current {
-                if t.token_type == TokenType::Comma {
-                    self.avanzar();
+            // `*` sólo es un argumento completo, nunca parte de una expresión
+            let comodin = self.tokens.actual()
+                .filter(|t| t.token_type == TokenType::Multiply)
+                .filter(|_| self.tokens.peek(1).is_some_and(|t| matches!(t.token_type, TokenType::Comma | TokenType::ClosedParenthesis)));
+            match comodin {
+                Some(t) => {
+                    self.tokens.avanzar();
+                    argumentos.push(self.arena.agregar_expresion(Expresion::Todos, t.span()));
                 }
+                None => argumentos.push(self.parse_expresion()?),
+            }
+            
+            if self.tokens.check(TokenType::Comma) {
+                self.tokens.avanzar();
             }
         }
         
         Ok(argumentos)
     }
-}
\ No newline at end of file
+}
+
+// Una sección o un proceso o robot que empieza, o el fin del archivo, dentro
+// de las instrucciones de un proceso o un robot: le falta su `fin`, y el
+// error va en su encabezado, que es lo que quedó abierto
+fn termina_sin_fin(token: &Token) -> bool {
+    token.token_type == TokenType::EndFile
+        || token.is_section_keyword()
+        || token.token_type == TokenType::Keyword && matches!(token.value, "proceso" | "robot" | "comenzar")
+}
+
+// Parsea tokens cualesquiera, por ejemplo los que deja `tokenize_all` en un
+// código con errores léxicos, y nunca entra en pánico: devuelve lo que se
+// pudo armar del programa, con un `Instruccion::Error` en lugar de cada
+// instrucción que falló, junto con todos los errores. Los tokens no tienen
+// que terminar en `EndFile` ni venir de un código válido.
+pub fn parse_tokens_lossy(tokens: &[Token]) -> (Option<Program>, Vec<CompilerError>) {
+    Parser::new(tokens).parse_parcial()
+}
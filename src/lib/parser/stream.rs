@@ -0,0 +1,127 @@
+use crate::compilerError::CompilerError;
+use crate::mensajes::Mensaje;
+use super::super::lexer::token::{Token, TokenType};
+
+// Posición guardada con `checkpoint` para volver atrás con `rollback`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+// Origen perezoso de tokens, normalmente un `Lexer`
+type Fuente<'src> = Box<dyn Iterator<Item = Result<Token<'src>, CompilerError>> + 'src>;
+
+// Cursor sobre los tokens del lexer con lookahead arbitrario. Los tokens se
+// piden a la fuente a medida que el parser los necesita; los ya leídos quedan
+// guardados para poder volver a un checkpoint.
+pub struct TokenStream<'src> {
+    tokens: Vec<Token<'src>>,
+    fuente: Option<Fuente<'src>>,
+    // Errores léxicos encontrados al leer de la fuente
+    errores: Vec<CompilerError>,
+    posicion: usize,
+}
+
+impl<'src> TokenStream<'src> {
+    pub fn new(tokens: &[Token<'src>]) -> Self {
+        Self { tokens: tokens.to_vec(), fuente: None, errores: Vec::new(), posicion: 0 }
+    }
+
+    // Lee los tokens de `fuente` sólo cuando hacen falta. Los errores léxicos
+    // no cortan la lectura: se guardan y el stream sigue con el token siguiente.
+    pub fn lazy(fuente: impl Iterator<Item = Result<Token<'src>, CompilerError>> + 'src) -> Self {
+        Self { tokens: Vec::new(), fuente: Some(Box::new(fuente)), errores: Vec::new(), posicion: 0 }
+    }
+
+    // Pide tokens a la fuente hasta tener el índice `indice` o agotarla
+    fn cargar(&mut self, indice: usize) {
+        while self.tokens.len() <= indice {
+            match self.fuente.as_mut().and_then(Iterator::next) {
+                Some(Ok(token)) => self.tokens.push(token),
+                Some(Err(error)) => self.errores.push(error),
+                None => {
+                    self.fuente = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Lee el resto de la fuente, para conocer todos sus errores léxicos
+    // aunque el parser se haya detenido antes
+    pub fn agotar(&mut self) {
+        self.cargar(usize::MAX);
+    }
+
+    pub fn errores_lexicos(&self) -> &[CompilerError] {
+        &self.errores
+    }
+
+    // Token actual, sin consumirlo
+    pub fn actual(&mut self) -> Option<Token<'src>> {
+        self.peek(0)
+    }
+
+    // Token `n` lugares adelante del actual (`peek(0)` es el actual)
+    pub fn peek(&mut self, n: usize) -> Option<Token<'src>> {
+        self.cargar(self.posicion + n);
+        self.tokens.get(self.posicion + n).copied()
+    }
+
+    // Último token consumido sin contar los de indentación, que no forman
+    // parte de ninguna instrucción
+    pub fn ultimo_consumido(&self) -> Option<Token<'src>> {
+        self.tokens[..self.posicion.min(self.tokens.len())]
+            .iter()
+            .rev()
+            .find(|token| !matches!(token.token_type, TokenType::Indent | TokenType::Dedent))
+            .copied()
+    }
+
+    pub fn avanzar(&mut self) {
+        if !self.fin() {
+            self.posicion += 1;
+        }
+    }
+
+    pub fn fin(&mut self) -> bool {
+        self.cargar(self.posicion);
+        self.posicion >= self.tokens.len()
+    }
+
+    pub fn check(&mut self, tipo: TokenType) -> bool {
+        self.actual().is_some_and(|token| token.token_type == tipo)
+    }
+
+    pub fn check_valor(&mut self, tipo: TokenType, valor: &str) -> bool {
+        self.actual().is_some_and(|token| token.token_type == tipo && token.value == valor)
+    }
+
+    // Consume el token actual si es del tipo esperado; si no, informa el error
+    // en la posición del token encontrado (o del último, al final del archivo)
+    pub fn expect(&mut self, tipo: TokenType, mensaje: impl Into<Mensaje>) -> Result<Token<'src>, CompilerError> {
+        match self.actual() {
+            Some(token) if token.token_type == tipo => {
+                self.avanzar();
+                Ok(token)
+            }
+            encontrado => Err(match encontrado.or_else(|| self.tokens.last().copied()) {
+                Some(token) => CompilerError::en(mensaje, token.span()),
+                None => CompilerError::new(mensaje, 0, 0),
+            }),
+        }
+    }
+
+    // Línea y columna del token actual, o del último si ya no quedan
+    pub fn ubicacion(&mut self) -> (usize, usize) {
+        self.actual()
+            .or_else(|| self.tokens.last().copied())
+            .map_or((0, 0), |token| (token.line, token.column))
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.posicion)
+    }
+
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        self.posicion = checkpoint.0;
+    }
+}
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+use crate::compilerError::CompilerError;
+use crate::driver::Opciones;
+use crate::lexer::token::{Alias, Keywords};
+use crate::mensajes::{Codigo, Idioma, Mensaje};
+use crate::semanticizer::lints::{AjusteRegla, RegistroLints};
+
+// Archivo de configuración que se busca en el directorio actual y en los
+// que lo contienen
+pub const ARCHIVO_PROYECTO: &str = "rinfo.toml";
+
+// Configuración de un proyecto: las opciones con que se compila y los
+// valores por defecto de las opciones de los comandos. Lo que se indique en
+// la línea de comandos tiene prioridad.
+#[derive(Debug, Clone, Default)]
+pub struct Proyecto {
+    pub opciones: Opciones,
+    // Como `--mundo`
+    pub mundo: Option<String>,
+    // Como `--scheduler`, con la semilla ya incorporada
+    pub planificador: Option<String>,
+    // Como `--report`, `--emit` y `--render`
+    pub reporte: Option<String>,
+    pub emitir: Option<String>,
+    pub imagen: Option<String>,
+}
+
+// Claves de cada sección; en `[lints]` las claves son los nombres de las
+// reglas y en `[alias]` y `[obsoletos]`, los nombres que se agregan
+const CLAVES: &[(&str, &[&str])] = &[
+    ("compilador", &["idioma", "extensiones", "secciones_flexibles"]),
+    ("lexer", &["tab_width", "allow_tabs", "require_consistent", "line_comments", "nested_comments"]),
+    ("ejecucion", &["mundo", "planificador", "semilla"]),
+    ("salida", &["reporte", "emit", "render"]),
+];
+
+// Valor de una clave: el subconjunto de TOML que hace falta para configurar
+enum Valor {
+    Cadena(String),
+    Entero(i64),
+    Booleano(bool),
+}
+
+impl Proyecto {
+    // Lee un `rinfo.toml`:
+    //   [compilador]      idioma, extensiones, secciones_flexibles
+    //   [lexer]           tab_width, allow_tabs, require_consistent,
+    //                     line_comments, nested_comments
+    //   [lints]           <regla> = "allow|warn|deny [límite]"
+    //   [alias]           <nombre> = "<instrucción>"
+    //   [obsoletos]       <nombre> = "<instrucción>", que además se advierte
+    //   [ejecucion]       mundo, planificador, semilla
+    //   [salida]          reporte, emit, render
+    // Los valores son cadenas entre comillas, números o `true`/`false`. Las
+    // líneas vacías y los comentarios `# ...` se ignoran.
+    pub fn desde_texto(texto: &str) -> Result<Self, CompilerError> {
+        let mut proyecto = Self::default();
+        let mut seccion = String::new();
+        let mut semilla = None;
+
+        for (i, linea) in texto.lines().enumerate() {
+            let linea = linea.trim();
+            if linea.is_empty() || linea.starts_with('#') {
+                continue;
+            }
+
+            let error = |mensaje: Mensaje| CompilerError::new(mensaje, i + 1, 0);
+            let formato = || error(Mensaje::new(Codigo::FormatoDeProyecto).con(linea));
+            if let Some(encabezado) = linea.strip_prefix('[') {
+                let Some((nombre, resto)) = encabezado.split_once(']') else {
+                    return Err(formato());
+                };
+                if !sin_comentario(resto).is_empty() {
+                    return Err(formato());
+                }
+                seccion = nombre.trim().to_string();
+                continue;
+            }
+
+            let Some((clave, texto_valor)) = linea.split_once('=') else {
+                return Err(formato());
+            };
+            let clave = clave.trim().trim_matches('"');
+            let Some(valor) = leer_valor(texto_valor) else {
+                return Err(formato());
+            };
+            let conocida = matches!(seccion.as_str(), "lints" | "alias" | "obsoletos") || CLAVES.iter()
+                .any(|(nombre, claves)| *nombre == seccion && claves.contains(&clave));
+            if !conocida {
+                return Err(error(Mensaje::new(Codigo::ClaveDesconocida).con(&seccion).con(clave)));
+            }
+            let invalido = || error(Mensaje::new(Codigo::ValorInvalido).con(clave).con(sin_comentario(texto_valor)));
+
+            let opciones = &mut proyecto.opciones;
+            match (seccion.as_str(), clave, valor) {
+                ("compilador", "idioma", Valor::Cadena(codigo)) => {
+                    opciones.idioma = Idioma::desde_codigo(&codigo).ok_or_else(invalido)?;
+                }
+                ("compilador", "extensiones", Valor::Booleano(valor)) => opciones.extensiones = valor,
+                ("compilador", "secciones_flexibles", Valor::Booleano(valor)) => opciones.secciones_flexibles = valor,
+                ("lexer", "tab_width", Valor::Entero(ancho)) if ancho > 0 => opciones.lexer.tab_width = ancho as usize,
+                ("lexer", "allow_tabs", Valor::Booleano(valor)) => opciones.lexer.allow_tabs = valor,
+                ("lexer", "require_consistent", Valor::Booleano(valor)) => opciones.lexer.require_consistent = valor,
+                ("lexer", "line_comments", Valor::Booleano(valor)) => opciones.lexer.line_comments = valor,
+                ("lexer", "nested_comments", Valor::Booleano(valor)) => opciones.lexer.nested_comments = valor,
+                ("lints", regla, Valor::Cadena(ajuste)) => {
+                    let ajuste = AjusteRegla::new(regla, &ajuste, i + 1)
+                        .map_err(|codigo| error(Mensaje::new(codigo).con(linea)))?;
+                    opciones.lints.ajustes.push(ajuste);
+                }
+                (seccion @ ("alias" | "obsoletos"), nombre, Valor::Cadena(instruccion)) => {
+                    // Un alias no puede tapar una palabra reservada
+                    let alias = Alias::new(&instruccion, seccion == "obsoletos")
+                        .filter(|_| Keywords::new().con_extensiones().get_token_type(nombre).is_none())
+                        .ok_or_else(invalido)?;
+                    opciones.alias.push((nombre.to_string(), alias));
+                }
+                ("ejecucion", "mundo", Valor::Cadena(archivo)) => proyecto.mundo = Some(archivo),
+                ("ejecucion", "planificador", Valor::Cadena(politica)) => proyecto.planificador = Some(politica),
+                ("ejecucion", "semilla", Valor::Entero(valor)) if valor >= 0 => semilla = Some((valor, i + 1)),
+                ("salida", "reporte", Valor::Cadena(archivo)) => proyecto.reporte = Some(archivo),
+                ("salida", "emit", Valor::Cadena(formato)) => proyecto.emitir = Some(formato),
+                ("salida", "render", Valor::Cadena(archivo)) => proyecto.imagen = Some(archivo),
+                _ => return Err(invalido()),
+            }
+        }
+
+        // La semilla es la del planificador aleatorio, que elige si no se
+        // indicó otro
+        if let Some((semilla, linea)) = semilla {
+            match proyecto.planificador.as_deref() {
+                None | Some("random") => proyecto.planificador = Some(format!("random:{}", semilla)),
+                Some(_) => {
+                    return Err(CompilerError::new(Mensaje::new(Codigo::ValorInvalido).con("semilla").con(semilla.to_string()), linea, 0));
+                }
+            }
+        }
+        RegistroLints::predeterminado().configurar(&proyecto.opciones.lints)?;
+
+        Ok(proyecto)
+    }
+
+    // Los archivos nombrados en la configuración se buscan desde su directorio
+    pub fn relativo_a(mut self, directorio: &Path) -> Self {
+        for archivo in [&mut self.mundo, &mut self.reporte, &mut self.imagen].into_iter().flatten() {
+            *archivo = directorio.join(&*archivo).display().to_string();
+        }
+        self
+    }
+}
+
+// El `rinfo.toml` de `directorio` o del directorio más cercano que lo contiene
+pub fn buscar_proyecto(directorio: &Path) -> Option<PathBuf> {
+    directorio.ancestors()
+        .map(|directorio| directorio.join(ARCHIVO_PROYECTO))
+        .find(|archivo| archivo.is_file())
+}
+
+fn sin_comentario(texto: &str) -> &str {
+    texto.split('#').next().unwrap_or_default().trim()
+}
+
+fn leer_valor(texto: &str) -> Option<Valor> {
+    let texto = texto.trim();
+    let Some(cadena) = texto.strip_prefix('"') else {
+        return match sin_comentario(texto) {
+            "true" => Some(Valor::Booleano(true)),
+            "false" => Some(Valor::Booleano(false)),
+            numero => numero.parse().ok().map(Valor::Entero),
+        };
+    };
+
+    let mut valor = String::new();
+    let mut caracteres = cadena.char_indices();
+    while let Some((i, caracter)) = caracteres.next() {
+        match caracter {
+            '"' => return sin_comentario(&cadena[i + 1..]).is_empty().then_some(Valor::Cadena(valor)),
+            '\\' => valor.push(match caracteres.next()?.1 {
+                'n' => '\n',
+                't' => '\t',
+                otro @ ('"' | '\\') => otro,
+                _ => return None,
+            }),
+            _ => valor.push(caracter),
+        }
+    }
+    None
+}
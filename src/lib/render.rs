@@ -0,0 +1,561 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+use gif::{DisposalMethod, Encoder, Frame, Repeat};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+use crate::interpreter::world::{Direccion, Mundo, TAMANIO_CIUDAD};
+
+// Lado de la celda de cada esquina, en píxeles
+const CELDA: f32 = 12.0;
+// Espacio alrededor del mapa para los números de avenidas y calles
+const MARGEN: f32 = 20.0;
+// Alto de cada renglón de la leyenda
+const RENGLON: f32 = 16.0;
+// Alto de las letras: chicas para las cantidades y los ejes, grandes para la leyenda
+const LETRA_CHICA: f32 = 5.0;
+const LETRA_GRANDE: f32 = 10.0;
+
+const FONDO: Color = Color(255, 255, 255);
+const CALLE: Color = Color(232, 232, 232);
+const CALLE_DECENA: Color = Color(190, 190, 190);
+const TEXTO: Color = Color(40, 40, 40);
+const FLORES: Color = Color(200, 30, 90);
+const PAPELES: Color = Color(30, 80, 200);
+const BLOQUEADA: Color = Color(220, 0, 0);
+const ROBOTS: [Color; 8] = [
+    Color(230, 120, 0),
+    Color(0, 150, 70),
+    Color(120, 60, 200),
+    Color(0, 140, 180),
+    Color(200, 40, 40),
+    Color(140, 110, 0),
+    Color(220, 60, 170),
+    Color(70, 70, 70),
+];
+
+// Colores de las áreas: comunes, privadas y parcialmente compartidas
+const AREAS: [Color; 3] = [Color(215, 240, 210), Color(205, 225, 250), Color(250, 225, 190)];
+
+// Colores de un cuadro de la animación: todos los que usa el dibujo. Los
+// bordes suavizados se aproximan al más cercano.
+const PALETA: [Color; 18] = [
+    FONDO, CALLE, CALLE_DECENA, TEXTO, FLORES, PAPELES, BLOQUEADA,
+    ROBOTS[0], ROBOTS[1], ROBOTS[2], ROBOTS[3], ROBOTS[4], ROBOTS[5], ROBOTS[6], ROBOTS[7],
+    AREAS[0], AREAS[1], AREAS[2],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Color(u8, u8, u8);
+
+impl Color {
+    fn hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// Color de fondo de cada tipo de área
+fn color_area(tipo: &str) -> Color {
+    match tipo {
+        "AreaP" => AREAS[1],
+        "AreaPC" => AREAS[2],
+        _ => AREAS[0],
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Figura {
+    Rectangulo { x: f32, y: f32, ancho: f32, alto: f32, color: Color },
+    Borde { x: f32, y: f32, ancho: f32, alto: f32, color: Color },
+    Linea { x1: f32, y1: f32, x2: f32, y2: f32, grosor: f32, color: Color },
+    Circulo { x: f32, y: f32, radio: f32, color: Color },
+    // `y` es el borde superior de las letras y `alto` su altura
+    Texto { x: f32, y: f32, alto: f32, texto: String, color: Color },
+}
+
+// Imagen del estado de la ciudad en un momento de la simulación: áreas
+// coloreadas por tipo, flores y papeles de cada esquina, esquinas
+// bloqueadas y robots con su dirección, más una leyenda con la bolsa de cada
+// robot. Se arma una vez como lista de figuras y se escribe como SVG o PNG.
+// Sirve tanto para el mundo final como para el de un `SimulationState`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dibujo {
+    ancho: f32,
+    alto: f32,
+    figuras: Vec<Figura>,
+}
+
+impl Dibujo {
+    pub fn new(mundo: &Mundo) -> Self {
+        let lado = TAMANIO_CIUDAD as f32 * CELDA;
+        let tipos = tipos_de_area(mundo);
+        let renglones = mundo.robots.len() + usize::from(!tipos.is_empty());
+        let mut dibujo = Self {
+            ancho: lado + 2.0 * MARGEN,
+            alto: lado + 2.0 * MARGEN + renglones as f32 * RENGLON + MARGEN / 2.0,
+            figuras: Vec::new(),
+        };
+
+        for area in &mundo.ciudad.areas {
+            dibujo.figuras.push(Figura::Rectangulo {
+                x: x_celda(area.av_min),
+                y: y_celda(area.ca_max),
+                ancho: (area.av_max - area.av_min + 1) as f32 * CELDA,
+                alto: (area.ca_max - area.ca_min + 1) as f32 * CELDA,
+                color: color_area(&area.tipo),
+            });
+        }
+        dibujo.calles();
+        dibujo.esquinas(mundo);
+        dibujo.robots(mundo);
+        dibujo.leyenda(mundo, &tipos, MARGEN * 2.0 + lado);
+        dibujo
+    }
+
+    pub fn ancho(&self) -> u32 {
+        self.ancho as u32
+    }
+
+    pub fn alto(&self) -> u32 {
+        self.alto as u32
+    }
+
+    // Una línea por avenida y por calle, más marcadas cada diez, con su número
+    fn calles(&mut self) {
+        let (inicio, fin) = (MARGEN + CELDA / 2.0, MARGEN + (TAMANIO_CIUDAD as f32 - 0.5) * CELDA);
+        for numero in 1..=TAMANIO_CIUDAD {
+            let decena = numero % 10 == 0;
+            let color = if decena { CALLE_DECENA } else { CALLE };
+            let x = x_celda(numero) + CELDA / 2.0;
+            let y = y_celda(numero) + CELDA / 2.0;
+            self.figuras.push(Figura::Linea { x1: x, y1: inicio, x2: x, y2: fin, grosor: 1.0, color });
+            self.figuras.push(Figura::Linea { x1: inicio, y1: y, x2: fin, y2: y, grosor: 1.0, color });
+
+            if decena || numero == 1 {
+                let texto = numero.to_string();
+                let ancho = ancho_texto(&texto, LETRA_CHICA);
+                let abajo = MARGEN + TAMANIO_CIUDAD as f32 * CELDA + 4.0;
+                self.texto(x - ancho / 2.0, abajo, LETRA_CHICA, texto.clone(), TEXTO);
+                self.texto(MARGEN - ancho - 4.0, y - LETRA_CHICA / 2.0, LETRA_CHICA, texto, TEXTO);
+            }
+        }
+    }
+
+    // Las flores arriba a la izquierda de la celda y los papeles abajo a la derecha
+    fn esquinas(&mut self, mundo: &Mundo) {
+        for av in 1..=TAMANIO_CIUDAD {
+            for ca in 1..=TAMANIO_CIUDAD {
+                let Some(esquina) = mundo.ciudad.esquina(av, ca) else {
+                    continue;
+                };
+                let (x, y) = (x_celda(av), y_celda(ca));
+                if esquina.bloqueada_por.is_some() {
+                    self.figuras.push(Figura::Borde { x, y, ancho: CELDA, alto: CELDA, color: BLOQUEADA });
+                }
+                if esquina.flores > 0 {
+                    self.texto(x + 1.0, y + 1.0, LETRA_CHICA, esquina.flores.to_string(), FLORES);
+                }
+                if esquina.papeles > 0 {
+                    let texto = esquina.papeles.to_string();
+                    let ancho = ancho_texto(&texto, LETRA_CHICA);
+                    self.texto(x + CELDA - ancho - 1.0, y + CELDA - LETRA_CHICA - 1.0, LETRA_CHICA, texto, PAPELES);
+                }
+            }
+        }
+    }
+
+    // Cada robot es un círculo con una línea hacia donde mira. Los que nunca
+    // se iniciaron no están en la ciudad.
+    fn robots(&mut self, mundo: &Mundo) {
+        for (indice, robot) in mundo.robots.iter().enumerate().filter(|(_, robot)| robot.iniciado) {
+            let color = ROBOTS[indice % ROBOTS.len()];
+            let x = x_celda(robot.av) + CELDA / 2.0;
+            let y = y_celda(robot.ca) + CELDA / 2.0;
+            let radio = CELDA * 0.4;
+            let (dx, dy) = match robot.direccion {
+                Direccion::Norte => (0.0, -1.0),
+                Direccion::Este => (1.0, 0.0),
+                Direccion::Sur => (0.0, 1.0),
+                Direccion::Oeste => (-1.0, 0.0),
+            };
+            self.figuras.push(Figura::Circulo { x, y, radio, color });
+            self.figuras.push(Figura::Linea {
+                x1: x,
+                y1: y,
+                x2: x + dx * (radio + 2.0),
+                y2: y + dy * (radio + 2.0),
+                grosor: 2.0,
+                color: TEXTO,
+            });
+        }
+    }
+
+    fn leyenda(&mut self, mundo: &Mundo, tipos: &[&str], mut y: f32) {
+        if !tipos.is_empty() {
+            let mut x = MARGEN;
+            for tipo in tipos {
+                self.figuras.push(Figura::Rectangulo { x, y, ancho: LETRA_GRANDE, alto: LETRA_GRANDE, color: color_area(tipo) });
+                self.texto(x + LETRA_GRANDE + 4.0, y, LETRA_GRANDE, tipo.to_string(), TEXTO);
+                x += LETRA_GRANDE + 4.0 + ancho_texto(tipo, LETRA_GRANDE) + 16.0;
+            }
+            y += RENGLON;
+        }
+
+        for (indice, robot) in mundo.robots.iter().enumerate() {
+            let radio = LETRA_GRANDE / 2.0;
+            self.figuras.push(Figura::Circulo {
+                x: MARGEN + radio,
+                y: y + radio,
+                radio,
+                color: ROBOTS[indice % ROBOTS.len()],
+            });
+            let texto = format!(
+                "{} ({}) en ({}, {}): flores {}, papeles {}",
+                robot.nombre, robot.tipo, robot.av, robot.ca, robot.flores, robot.papeles
+            );
+            self.texto(MARGEN + LETRA_GRANDE + 4.0, y, LETRA_GRANDE, texto, TEXTO);
+            y += RENGLON;
+        }
+    }
+
+    fn texto(&mut self, x: f32, y: f32, alto: f32, texto: String, color: Color) {
+        self.figuras.push(Figura::Texto { x, y, alto, texto, color });
+    }
+
+    pub fn a_svg(&self) -> String {
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+            self.ancho(),
+            self.alto()
+        );
+        let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", FONDO.hex());
+        for figura in &self.figuras {
+            let _ = match figura {
+                Figura::Rectangulo { x, y, ancho, alto, color } => writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x, y, ancho, alto, color.hex()
+                ),
+                Figura::Borde { x, y, ancho, alto, color } => writeln!(
+                    svg,
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+                    x + 0.5, y + 0.5, ancho - 1.0, alto - 1.0, color.hex()
+                ),
+                Figura::Linea { x1, y1, x2, y2, grosor, color } => writeln!(
+                    svg,
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                    x1, y1, x2, y2, color.hex(), grosor
+                ),
+                Figura::Circulo { x, y, radio, color } => writeln!(
+                    svg,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                    x, y, radio, color.hex()
+                ),
+                // La línea base queda al pie de las letras; las mayúsculas
+                // ocupan unas siete décimas del tamaño de la fuente
+                Figura::Texto { x, y, alto, texto, color } => writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{:.1}\" fill=\"{}\">{}</text>",
+                    x, y + alto, alto / 0.7, color.hex(), escapar_xml(texto)
+                ),
+            };
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn a_png(&self) -> Result<Vec<u8>, String> {
+        self.rasterizar()?.encode_png().map_err(|e| e.to_string())
+    }
+
+    fn rasterizar(&self) -> Result<Pixmap, String> {
+        let mut pixmap = Pixmap::new(self.ancho(), self.alto())
+            .ok_or_else(|| format!("Tamaño de imagen inválido: {}x{}", self.ancho(), self.alto()))?;
+        pixmap.fill(tiny_skia::Color::from_rgba8(FONDO.0, FONDO.1, FONDO.2, 255));
+
+        for figura in &self.figuras {
+            match figura {
+                Figura::Rectangulo { x, y, ancho, alto, color } => rellenar(&mut pixmap, *x, *y, *ancho, *alto, *color),
+                Figura::Borde { x, y, ancho, alto, color } => {
+                    if let Some(rectangulo) = Rect::from_xywh(x + 0.5, y + 0.5, ancho - 1.0, alto - 1.0) {
+                        let camino = PathBuilder::from_rect(rectangulo);
+                        pixmap.stroke_path(&camino, &pintura(*color), &Stroke::default(), Transform::identity(), None);
+                    }
+                }
+                Figura::Linea { x1, y1, x2, y2, grosor, color } => {
+                    let mut camino = PathBuilder::new();
+                    camino.move_to(*x1, *y1);
+                    camino.line_to(*x2, *y2);
+                    if let Some(camino) = camino.finish() {
+                        let trazo = Stroke { width: *grosor, ..Stroke::default() };
+                        pixmap.stroke_path(&camino, &pintura(*color), &trazo, Transform::identity(), None);
+                    }
+                }
+                Figura::Circulo { x, y, radio, color } => {
+                    if let Some(camino) = PathBuilder::from_circle(*x, *y, *radio) {
+                        pixmap.fill_path(&camino, &pintura(*color), FillRule::Winding, Transform::identity(), None);
+                    }
+                }
+                Figura::Texto { x, y, alto, texto, color } => escribir(&mut pixmap, *x, *y, *alto, texto, *color),
+            }
+        }
+        Ok(pixmap)
+    }
+}
+
+// Demora de cada cuadro de la animación y del último, en centésimas de segundo
+const DEMORA_CUADRO: u16 = 10;
+const DEMORA_FINAL: u16 = 200;
+
+// GIF animado de una simulación, con un cuadro por cada `Dibujo` que se le
+// agrega. Cada cuadro se codifica apenas llega y sólo guarda el rectángulo
+// que cambió respecto del anterior, así que la memoria no crece con la
+// cantidad de pasos y los cuadros en que un robot avanza una cuadra son
+// chicos. Todos los dibujos tienen que ser del mismo mundo (mismo tamaño).
+pub struct Animacion {
+    codificador: Option<Encoder<Vec<u8>>>,
+    anterior: Vec<u8>,
+    ancho: u16,
+    alto: u16,
+    cuadros: usize,
+    colores: HashMap<[u8; 3], u8>,
+}
+
+impl Animacion {
+    pub fn new() -> Self {
+        Self {
+            codificador: None,
+            anterior: Vec::new(),
+            ancho: 0,
+            alto: 0,
+            cuadros: 0,
+            colores: HashMap::new(),
+        }
+    }
+
+    pub fn cuadros(&self) -> usize {
+        self.cuadros
+    }
+
+    pub fn agregar(&mut self, dibujo: &Dibujo) -> Result<(), String> {
+        self.agregar_con_demora(dibujo, DEMORA_CUADRO)
+    }
+
+    // El último cuadro queda más tiempo en pantalla antes de repetir
+    pub fn agregar_final(&mut self, dibujo: &Dibujo) -> Result<(), String> {
+        self.agregar_con_demora(dibujo, DEMORA_FINAL)
+    }
+
+    fn agregar_con_demora(&mut self, dibujo: &Dibujo, demora: u16) -> Result<(), String> {
+        let (Ok(ancho), Ok(alto)) = (u16::try_from(dibujo.ancho()), u16::try_from(dibujo.alto())) else {
+            return Err(format!("Imagen demasiado grande para un GIF: {}x{}", dibujo.ancho(), dibujo.alto()));
+        };
+        if self.codificador.is_some() && (ancho, alto) != (self.ancho, self.alto) {
+            return Err(format!(
+                "Los cuadros tienen que medir lo mismo: {}x{} y {}x{}",
+                self.ancho, self.alto, ancho, alto
+            ));
+        }
+
+        let pixmap = dibujo.rasterizar()?;
+        let indices: Vec<u8> = pixmap.data()
+            .chunks_exact(4)
+            .map(|pixel| self.indice_color([pixel[0], pixel[1], pixel[2]]))
+            .collect();
+
+        let codificador = match &mut self.codificador {
+            Some(codificador) => codificador,
+            None => {
+                let paleta: Vec<u8> = PALETA.iter().flat_map(|color| [color.0, color.1, color.2]).collect();
+                let mut codificador = Encoder::new(Vec::new(), ancho, alto, &paleta).map_err(|e| e.to_string())?;
+                codificador.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+                self.ancho = ancho;
+                self.alto = alto;
+                self.codificador.insert(codificador)
+            }
+        };
+
+        // Rectángulo que cambió; el primer cuadro es la imagen entera. Si no
+        // cambió nada igual hace falta un cuadro para respetar la demora.
+        let (izquierda, arriba, derecha, abajo) = if self.anterior.is_empty() {
+            (0, 0, ancho - 1, alto - 1)
+        } else {
+            diferencia(&self.anterior, &indices, ancho).unwrap_or((0, 0, 0, 0))
+        };
+        let buffer: Vec<u8> = (arriba..=abajo)
+            .flat_map(|y| {
+                let inicio = y as usize * ancho as usize;
+                indices[inicio + izquierda as usize..=inicio + derecha as usize].iter().copied()
+            })
+            .collect();
+        let cuadro = Frame {
+            delay: demora,
+            dispose: DisposalMethod::Keep,
+            left: izquierda,
+            top: arriba,
+            width: derecha - izquierda + 1,
+            height: abajo - arriba + 1,
+            buffer: Cow::Owned(buffer),
+            ..Frame::default()
+        };
+        codificador.write_frame(&cuadro).map_err(|e| e.to_string())?;
+
+        self.anterior = indices;
+        self.cuadros += 1;
+        Ok(())
+    }
+
+    fn indice_color(&mut self, pixel: [u8; 3]) -> u8 {
+        *self.colores.entry(pixel).or_insert_with(|| {
+            let distancia = |color: &Color| {
+                let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+                d(color.0, pixel[0]) + d(color.1, pixel[1]) + d(color.2, pixel[2])
+            };
+            (0..PALETA.len()).min_by_key(|&i| distancia(&PALETA[i])).unwrap_or(0) as u8
+        })
+    }
+
+    // Bytes del archivo GIF; `None` si no se agregó ningún cuadro
+    pub fn terminar(self) -> Result<Option<Vec<u8>>, String> {
+        self.codificador
+            .map(|codificador| codificador.into_inner().map_err(|e| e.to_string()))
+            .transpose()
+    }
+}
+
+impl Default for Animacion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Menor rectángulo (izquierda, arriba, derecha, abajo) que contiene todos los
+// píxeles distintos entre dos cuadros; `None` si son iguales
+fn diferencia(anterior: &[u8], actual: &[u8], ancho: u16) -> Option<(u16, u16, u16, u16)> {
+    let mut rectangulo: Option<(u16, u16, u16, u16)> = None;
+    for (fila, (a, b)) in anterior.chunks_exact(ancho as usize).zip(actual.chunks_exact(ancho as usize)).enumerate() {
+        let Some(primera) = a.iter().zip(b).position(|(x, y)| x != y) else {
+            continue;
+        };
+        let ultima = a.iter().zip(b).rposition(|(x, y)| x != y).unwrap_or(primera);
+        let (primera, ultima, fila) = (primera as u16, ultima as u16, fila as u16);
+        rectangulo = Some(match rectangulo {
+            None => (primera, fila, ultima, fila),
+            Some((izquierda, arriba, derecha, _)) => (izquierda.min(primera), arriba, derecha.max(ultima), fila),
+        });
+    }
+    rectangulo
+}
+
+fn x_celda(av: i32) -> f32 {
+    MARGEN + (av - 1) as f32 * CELDA
+}
+
+// Las calles crecen hacia arriba, como en el mapa
+fn y_celda(ca: i32) -> f32 {
+    MARGEN + (TAMANIO_CIUDAD - ca) as f32 * CELDA
+}
+
+// Tipos de área presentes, en el orden en que aparecen
+fn tipos_de_area(mundo: &Mundo) -> Vec<&str> {
+    let mut tipos: Vec<&str> = Vec::new();
+    for area in &mundo.ciudad.areas {
+        if !tipos.contains(&area.tipo.as_str()) {
+            tipos.push(&area.tipo);
+        }
+    }
+    tipos
+}
+
+fn escapar_xml(texto: &str) -> String {
+    texto.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn pintura(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color.0, color.1, color.2, 255);
+    paint.anti_alias = true;
+    paint
+}
+
+fn rellenar(pixmap: &mut Pixmap, x: f32, y: f32, ancho: f32, alto: f32, color: Color) {
+    if let Some(rectangulo) = Rect::from_xywh(x, y, ancho, alto) {
+        let mut paint = pintura(color);
+        paint.anti_alias = false;
+        pixmap.fill_rect(rectangulo, &paint, Transform::identity(), None);
+    }
+}
+
+// Las imágenes PNG no dependen de las fuentes instaladas: el texto se
+// dibuja con una letra de mapa de bits de 3x5 píxeles, escalada al alto
+// pedido. Cada fila es un número de tres bits, el más alto a la izquierda.
+fn escribir(pixmap: &mut Pixmap, x: f32, y: f32, alto: f32, texto: &str, color: Color) {
+    let escala = (alto / 5.0).max(1.0);
+    for (i, caracter) in texto.chars().enumerate() {
+        let izquierda = x + i as f32 * 4.0 * escala;
+        for (fila, bits) in glifo(caracter).iter().enumerate() {
+            for columna in 0..3 {
+                if bits & (0b100 >> columna) != 0 {
+                    let (px, py) = (izquierda + columna as f32 * escala, y + fila as f32 * escala);
+                    rellenar(pixmap, px, py, escala, escala, color);
+                }
+            }
+        }
+    }
+}
+
+fn ancho_texto(texto: &str, alto: f32) -> f32 {
+    let escala = (alto / 5.0).max(1.0);
+    (texto.chars().count() as f32 * 4.0 - 1.0).max(0.0) * escala
+}
+
+fn glifo(caracter: char) -> [u8; 5] {
+    match caracter.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' | 'Á' | 'á' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' | 'É' | 'é' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' | 'Í' | 'í' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' | 'Ñ' | 'ñ' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' | 'Ó' | 'ó' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' | 'Ú' | 'ú' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => [0; 5],
+    }
+}
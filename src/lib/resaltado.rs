@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use crate::driver::Opciones;
+use crate::lexer::token::TokenType;
+use crate::parser::processor::Parser;
+use crate::semanticizer::consultas::{Clase, Consultas};
+use crate::source::Span;
+
+// Clase de cada tramo resaltado del código
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HighlightKind {
+    // Palabras reservadas, sentencias de control y tipos de dato
+    PalabraClave,
+    Instruccion,
+    // Tipos de robot y robots instanciados
+    Robot,
+    Proceso,
+    Area,
+    // Variables y parámetros
+    Variable,
+    Constante,
+    Literal,
+    Comentario,
+}
+
+// Tramos del código con su clase, en el orden en que aparecen, para el
+// playground y los tokens semánticos del servidor de lenguaje. Los nombres
+// se clasifican según su declaración en el programa; si el código no llega
+// a parsearse o un nombre no se declara, el nombre no se resalta.
+pub fn highlight(source: &str) -> Vec<(Span, HighlightKind)> {
+    highlight_con(source, &Opciones::default())
+}
+
+// Como `highlight`, leyendo el código con las palabras clave y la
+// configuración del lexer de `opciones`
+pub fn highlight_con(source: &str, opciones: &Opciones) -> Vec<(Span, HighlightKind)> {
+    let mut lexer = opciones.lexer(source);
+    let (tokens, _) = lexer.tokenize_all();
+
+    // Los nombres globales tapan a las variables con el mismo nombre
+    let mut nombres = HashMap::new();
+    if let Ok(programa) = Parser::new(&tokens).con_secciones_flexibles(opciones.secciones_flexibles).parse() {
+        for declarado in Consultas::new(&programa).declaraciones() {
+            let clase = match declarado.clase {
+                Clase::Proceso => HighlightKind::Proceso,
+                Clase::Area => HighlightKind::Area,
+                Clase::TipoRobot | Clase::Robot => HighlightKind::Robot,
+                Clase::Constante => HighlightKind::Constante,
+                Clase::Parametro | Clase::Variable => HighlightKind::Variable,
+            };
+            nombres.entry(declarado.nombre).or_insert(clase);
+        }
+    }
+
+    let mut tramos: Vec<(Span, HighlightKind)> = tokens.iter()
+        .filter_map(|token| {
+            let clase = match token.token_type {
+                _ if token.is_literal() => HighlightKind::Literal,
+                TokenType::Keyword | TokenType::ControlSentence | TokenType::ParameterType
+                | TokenType::Num | TokenType::Bool => HighlightKind::PalabraClave,
+                TokenType::ElementalInstruction => HighlightKind::Instruccion,
+                TokenType::Identifier => *nombres.get(token.value)?,
+                _ => return None,
+            };
+            Some((token.span(), clase))
+        })
+        .chain(lexer.comentarios().iter().map(|&tramo| (tramo, HighlightKind::Comentario)))
+        .collect();
+    tramos.sort_by_key(|(tramo, _)| (tramo.linea, tramo.columna));
+    tramos
+}
@@ -1,351 +1,1387 @@
 use std::collections::{HashMap, HashSet};
-use crate::lib::compilerError::CompilerError;
-use super::super::parser::processor::{Program, Proceso, Robot, Instruccion, Expresion};
+use std::num::NonZeroUsize;
+use std::thread;
+use crate::compilerError::{CompilerError, Nota};
+use crate::interner::{Interner, Symbol};
+use crate::mensajes::{Codigo, Mensaje, Termino};
+use crate::source::{FileId, Span};
+use super::super::parser::arena::{Arena, ExprId, InstrId};
+use super::super::parser::ast::{recorrer_expresion, recorrer_instruccion, Visitor};
+use super::super::parser::processor::{valor_con_constantes, Caso, Program, Proceso, Robot, Instruccion, Expresion};
+use super::contratos;
+use super::dataflow;
+use super::hir::{Hir, Tipo};
+use super::lints::RegistroLints;
+use super::metricas::Metricas;
+use super::signatures::{firma_elemental, Argumento};
+use super::symbol_table::{Categoria, TablaSimbolos};
 
+// Tipo de dato de cada variable visible, tomado del programa. Las variables
+// que el programa nunca usa no tienen símbolo y no hace falta registrarlas.
+pub(super) type VariablesDeclaradas<'ast> = HashMap<Symbol, &'ast str>;
+
+// Procesos y robots se verifican por separado una vez conocidas las
+// declaraciones de todo el programa
+enum Seccion<'ast> {
+    Proceso(&'ast Proceso),
+    Robot(&'ast Robot),
+}
+
+impl Seccion<'_> {
+    fn linea(&self) -> usize {
+        match self {
+            Seccion::Proceso(proceso) => proceso.linea,
+            Seccion::Robot(robot) => robot.linea,
+        }
+    }
+}
+
+// Lo que una sección puede consultar del resto del programa: la categoría
+// de cada nombre global, los tipos ya resueltos de sus expresiones, los
+// parámetros de cada proceso, qué robots declaran cada variable, para
+// explicar por qué un proceso no la ve, y el valor de cada constante
+struct Entorno<'ast> {
+    tabla: TablaSimbolos,
+    hir: &'ast Hir,
+    procesos: HashMap<Symbol, &'ast Proceso>,
+    variables_de_robots: HashMap<Symbol, Vec<&'ast str>>,
+    constantes: HashMap<Symbol, i32>,
+}
+
+impl<'ast> Entorno<'ast> {
+    fn new(programa: &'ast Program, hir: &'ast Hir) -> Self {
+        let mut procesos = HashMap::new();
+        for proceso in &programa.procesos {
+            if let Some(simbolo) = programa.simbolos.get(&proceso.nombre) {
+                procesos.entry(simbolo).or_insert(proceso);
+            }
+        }
+
+        let mut variables_de_robots: HashMap<Symbol, Vec<&str>> = HashMap::new();
+        for robot in &programa.robots_definidos {
+            for var in &robot.variables {
+                if let Some(simbolo) = programa.simbolos.get(&var.nombre) {
+                    variables_de_robots.entry(simbolo).or_default().push(robot.nombre.as_str());
+                }
+            }
+        }
+
+        Self {
+            tabla: TablaSimbolos::del_programa(programa),
+            hir,
+            procesos,
+            variables_de_robots,
+            constantes: programa.valores_constantes(),
+        }
+    }
+}
+
+// Valor de un `caso`, calculado al compilar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValorCaso {
+    Numero(i32),
+    Booleano(bool),
+}
+
+impl ValorCaso {
+    fn tipo(self) -> Tipo {
+        match self {
+            ValorCaso::Numero(_) => Tipo::Numero,
+            ValorCaso::Booleano(_) => Tipo::Booleano,
+        }
+    }
+}
+
+impl From<ValorCaso> for Mensaje {
+    fn from(valor: ValorCaso) -> Self {
+        match valor {
+            ValorCaso::Numero(n) => Mensaje::from(n),
+            ValorCaso::Booleano(true) => Mensaje::from("V"),
+            ValorCaso::Booleano(false) => Mensaje::from("F"),
+        }
+    }
+}
+
+// Dónde se declaró un nombre, para informar los dos lugares de un conflicto
+#[derive(Debug, Clone, Copy)]
+struct Declaracion {
+    descripcion: Termino,
+    linea: usize,
+    archivo: FileId,
+}
+
+impl Declaracion {
+    fn new(descripcion: Termino, linea: usize, archivo: FileId) -> Self {
+        Self { descripcion, linea, archivo }
+    }
+}
+
+// Error en la segunda declaración de un nombre, con una nota en la primera
+fn conflicto(nombre: &str, anterior: &Declaracion, nueva: &Declaracion) -> CompilerError {
+    let mensaje = Mensaje::new(Codigo::NombreRepetido)
+        .con(nombre)
+        .con(anterior.descripcion)
+        .con(anterior.linea)
+        .con(nueva.descripcion)
+        .con(nueva.linea);
+    CompilerError::new(mensaje, nueva.linea, 0)
+    .en_archivo(nueva.archivo)
+    .con_nota_en(
+        Mensaje::termino(Termino::PrimeraDeclaracion).con(anterior.descripcion),
+        Span::new(anterior.linea, 0, anterior.linea, 0),
+        anterior.archivo,
+    )
+}
+
+// Todo lo que deja el análisis de un programa, para quien lo muestra o lo
+// guarda sin conservar el analizador
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisSummary {
+    pub errores: Vec<CompilerError>,
+    pub advertencias: Vec<Mensaje>,
+    pub metricas: Metricas,
+    // Tipos y referencias de las expresiones; se recalcula del programa, así
+    // que no se guarda
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub hir: Hir,
+}
+
+impl AnalysisSummary {
+    pub fn exitoso(&self) -> bool {
+        self.errores.is_empty()
+    }
+
+    pub fn resultado(&self) -> Result<(), Vec<CompilerError>> {
+        if self.errores.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errores.clone())
+        }
+    }
+}
+
+// Cómo se analiza (los hilos y las reglas de advertencia) y el resultado
+// del último análisis. `examinar` no modifica el analizador: un mismo
+// analizador se puede compartir entre hilos para analizar varios programas
+// a la vez, por ejemplo desde el servidor de lenguaje o al corregir entregas.
 pub struct SemanticAnalyzer {
-    errores: Vec<CompilerError>,
-    advertencias: Vec<String>,
+    lints: RegistroLints,
+    hilos: usize,
+    ultimo: AnalysisSummary,
 }
 
 impl SemanticAnalyzer {
     pub fn new() -> Self {
+        let hilos = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Self::con_hilos(hilos)
+    }
+
+    // Analizador que reparte la verificación de secciones en `hilos` hilos
+    // como máximo; con uno solo el análisis es secuencial
+    pub fn con_hilos(hilos: usize) -> Self {
         Self {
-            errores: Vec::new(),
-            advertencias: Vec::new(),
+            lints: RegistroLints::predeterminado(),
+            hilos: hilos.max(1),
+            ultimo: AnalysisSummary::default(),
         }
     }
+
+    // Reemplaza las reglas de advertencia, por ejemplo por unas configuradas
+    // con `--lint-config`
+    pub fn con_lints(mut self, lints: RegistroLints) -> Self {
+        self.lints = lints;
+        self
+    }
     
+    // Analiza el programa y guarda el resultado para `obtener_errores`,
+    // `obtener_advertencias` y `obtener_metricas`
     pub fn analizar(&mut self, programa: &Program) -> Result<(), Vec<CompilerError>> {
-        // 1. Analizar procesos
-        let procesos_validos = self.analizar_procesos(programa);
-        
-        // 2. Analizar robots (que pueden usar procesos)
-        self.analizar_robots(programa, &procesos_validos);
-        
-        // 3. Verificar invocaciones de procesos
-        self.verificar_invocaciones_procesos(programa, &procesos_validos);
+        self.analizar_con_cache(programa, &[], &mut HashMap::new())
+    }
+    
+    // Como `analizar`, pero reutiliza los errores ya calculados de cada
+    // sección cuya clave esté en `cache` y guarda ahí los que calcula. Hay una
+    // clave por sección, primero los procesos y después los robots; la clave
+    // debe cambiar cuando cambia la sección o los procesos, robots o áreas
+    // declarados. Las secciones sin clave se verifican siempre.
+    pub fn analizar_con_cache(
+        &mut self,
+        programa: &Program,
+        claves: &[Option<u64>],
+        cache: &mut HashMap<u64, Vec<CompilerError>>,
+    ) -> Result<(), Vec<CompilerError>> {
+        self.ultimo = self.examinar_con_cache(programa, claves, cache);
+        self.ultimo.resultado()
+    }
+
+    // El análisis completo del programa, sin guardar nada en el analizador
+    pub fn examinar(&self, programa: &Program) -> AnalysisSummary {
+        self.examinar_con_cache(programa, &[], &mut HashMap::new())
+    }
+
+    pub fn examinar_con_cache(
+        &self,
+        programa: &Program,
+        claves: &[Option<u64>],
+        cache: &mut HashMap<u64, Vec<CompilerError>>,
+    ) -> AnalysisSummary {
+        let mut analisis = Analisis { programa, errores: Vec::new() };
+
+        // 1. Declaraciones: nombres globales únicos y lo que los cuerpos
+        // consultan de ellos, con el nombre y el tipo de cada expresión ya
+        // resueltos. Se reúnen antes de verificar cualquier cuerpo, así una
+        // sección puede usar un proceso declarado más abajo.
+        let globales = analisis.nombres_globales();
+        let hir = Hir::new(programa);
+        let entorno = Entorno::new(programa, &hir);
         
-        // 4. Verificar uso de variables locales
-        self.verificar_variables_locales(programa);
+        // 2. Analizar procesos y robots
+        analisis.procesos(&globales);
+        analisis.robots(&globales);
         
-        if self.errores.is_empty() {
-            Ok(())
-        } else {
-            Err(self.errores.clone())
-        }
+        // 3. Verificar invocaciones y uso de variables en cada sección
+        analisis.secciones(&entorno, claves, cache, self.hilos);
+
+        // 4. Verificar los robots y áreas del programa principal
+        analisis.programa_principal(&entorno);
+
+        // 5. Comparar los tipos de los mensajes entre robots
+        let mut errores = analisis.errores;
+        errores.extend(contratos::verificar(programa, &hir));
+
+        // 6. Aplicar las reglas de advertencia; las configuradas como `deny`
+        // dan errores
+        let (advertencias, rechazados) = self.lints.verificar(programa);
+        errores.extend(rechazados);
+
+        // 7. Medir el código de cada proceso y robot
+        let metricas = Metricas::new(programa);
+
+        AnalysisSummary { errores, advertencias, metricas, hir }
+    }
+
+    pub fn obtener_errores(&self) -> &[CompilerError] {
+        &self.ultimo.errores
     }
     
-    fn analizar_procesos(&mut self, programa: &Program) -> HashMap<String, (Vec<(String, String)>, String)> {
-        let mut procesos_validos = HashMap::new();
-        let mut nombres_procesos = HashSet::new();
+    pub fn obtener_advertencias(&self) -> &[Mensaje] {
+        &self.ultimo.advertencias
+    }
+
+    pub fn obtener_metricas(&self) -> &Metricas {
+        &self.ultimo.metricas
+    }
+
+    pub fn resumen(&self) -> AnalysisSummary {
+        self.ultimo.clone()
+    }
+    
+    pub fn mostrar_resultados(&self) {
+        let AnalysisSummary { errores, advertencias, .. } = &self.ultimo;
+        if errores.is_empty() && advertencias.is_empty() {
+            println!("✓ Análisis semántico completado sin errores ni advertencias.");
+            return;
+        }
         
-        for proceso in &programa.procesos {
-            // Verificar nombre único
-            if nombres_procesos.contains(&proceso.nombre) {
-                self.errores.push(CompilerError::new(
-                    format!("Proceso '{}' declarado múltiples veces", proceso.nombre),
-                    0, 0
-                ));
-                continue;
+        if !errores.is_empty() {
+            println!("✗ Errores encontrados:");
+            for error in errores {
+                println!("  - {}", error.message);
             }
-            nombres_procesos.insert(proceso.nombre.clone());
-            
-            // Verificar parámetros únicos
-            let mut nombres_parametros = HashSet::new();
-            for param in &proceso.parametros {
-                if nombres_parametros.contains(&param.nombre) {
-                    self.errores.push(CompilerError::new(
-                        format!("Parámetro '{}' duplicado en proceso '{}'", param.nombre, proceso.nombre),
-                        0, 0
-                    ));
-                }
-                nombres_parametros.insert(param.nombre.clone());
+        }
+        
+        if !advertencias.is_empty() {
+            println!("⚠ Advertencias:");
+            for advertencia in advertencias {
+                println!("  - {}", advertencia);
             }
-            
-            // Verificar variables locales únicas
-            let mut nombres_variables = HashSet::new();
-            for var in &proceso.variables {
-                if nombres_variables.contains(&var.nombre) {
-                    self.errores.push(CompilerError::new(
-                        format!("Variable '{}' declarada múltiples veces en proceso '{}'", 
-                                var.nombre, proceso.nombre),
-                        0, 0
-                    ));
+        }
+    }
+}
+
+// Estado de un análisis en curso: se crea en cada llamada a `examinar` y se
+// descarta al terminar
+struct Analisis<'ast> {
+    programa: &'ast Program,
+    errores: Vec<CompilerError>,
+}
+
+impl<'ast> Analisis<'ast> {
+    // Procesos, áreas, tipos de robot y robots comparten un mismo espacio de
+    // nombres. Devuelve dónde se declaró cada uno, para comparar después con
+    // los parámetros y variables de cada sección.
+    fn nombres_globales(&mut self) -> HashMap<&'ast str, Declaracion> {
+        let programa = self.programa;
+        let procesos = programa.procesos.iter()
+            .map(|proceso| (proceso.nombre.as_str(), Declaracion::new(Termino::Proceso, proceso.linea, proceso.archivo)));
+        let areas = programa.areas.iter()
+            .map(|area| (area.nombre.as_str(), Declaracion::new(Termino::Area, area.linea, FileId::PRINCIPAL)));
+        let tipos = programa.robots_definidos.iter()
+            .map(|robot| (robot.nombre.as_str(), Declaracion::new(Termino::TipoRobot, robot.linea, robot.archivo)));
+        let robots = programa.robots_instanciados.iter()
+            .map(|robot| (robot.nombre.as_str(), Declaracion::new(Termino::Robot, robot.linea, FileId::PRINCIPAL)));
+        let constantes = programa.constantes.iter()
+            .map(|constante| (constante.nombre.as_str(), Declaracion::new(Termino::Constante, constante.linea, FileId::PRINCIPAL)));
+
+        let mut globales = HashMap::new();
+        for (nombre, declaracion) in constantes.chain(procesos).chain(areas).chain(tipos).chain(robots) {
+            match globales.get(nombre) {
+                Some(anterior) => self.errores.push(conflicto(nombre, anterior, &declaracion)),
+                None => {
+                    globales.insert(nombre, declaracion);
                 }
-                nombres_variables.insert(var.nombre.clone());
             }
-            
-            // Almacenar información del proceso para verificaciones posteriores
-            let parametros_info: Vec<(String, String)> = proceso.parametros
-                .iter()
-                .map(|p| (p.nombre.clone(), p.tipo_dato.clone()))
-                .collect();
-            
-            procesos_validos.insert(proceso.nombre.clone(), (parametros_info, "void".to_string()));
         }
-        
-        procesos_validos
+
+        // El nombre del programa no se usa en el código, pero tampoco puede
+        // confundirse con algo declarado
+        if let Some(declaracion) = globales.get(programa.nombre.as_str()) {
+            let programa = Declaracion::new(Termino::Programa, programa.linea, FileId::PRINCIPAL);
+            self.errores.push(conflicto(&self.programa.nombre, &programa, declaracion));
+        }
+        globales
     }
-    
-    fn analizar_robots(&mut self, programa: &Program, procesos_validos: &HashMap<String, (Vec<(String, String)>, String)>) {
-        let mut nombres_robots = HashSet::new();
-        
-        for robot in &programa.robots_definidos {
-            // Verificar nombre único de robot
-            if nombres_robots.contains(&robot.nombre) {
+
+    // Parámetros y variables locales de cada proceso únicos, y distintos de
+    // los nombres globales. Un proceso repetido ya se informó con los nombres
+    // globales. Un proceso que devuelve un valor se usa en expresiones, así
+    // que no puede cambiar las variables de quien lo llama.
+    fn procesos(&mut self, globales: &HashMap<&str, Declaracion>) {
+        let mut vistos = HashSet::new();
+        for proceso in &self.programa.procesos {
+            if !vistos.insert(proceso.nombre.as_str()) {
+                continue;
+            }
+            let locales = proceso.parametros.iter()
+                .map(|param| (param.nombre.as_str(), param.linea, Termino::Parametro))
+                .chain(proceso.variables.iter().map(|var| (var.nombre.as_str(), var.linea, Termino::Variable)));
+            self.nombres_locales(locales, proceso.archivo, globales);
+
+            let Some(retorno) = &proceso.retorno else {
+                continue;
+            };
+            // Sin ninguna asignación a su nombre devolvería siempre el valor
+            // inicial del tipo
+            let asignado = self.programa.simbolos.get(&proceso.nombre)
+                .is_some_and(|nombre| dataflow::asignadas(self.programa, &proceso.instrucciones).contains(&nombre));
+            if !asignado {
                 self.errores.push(CompilerError::new(
-                    format!("Robot '{}' definido múltiples veces", robot.nombre),
-                    0, 0
-                ));
+                    Mensaje::new(Codigo::RetornoSinAsignar).con(&proceso.nombre).con(retorno),
+                    proceso.linea, 0
+                ).en_archivo(proceso.archivo));
             }
-            nombres_robots.insert(robot.nombre.clone());
-            
-            // Verificar variables locales únicas en robot
-            let mut nombres_variables = HashSet::new();
-            for var in &robot.variables {
-                if nombres_variables.contains(&var.nombre) {
-                    self.errores.push(CompilerError::new(
-                        format!("Variable '{}' declarada múltiples veces en robot '{}'", 
-                                var.nombre, robot.nombre),
-                        0, 0
-                    ));
-                }
-                nombres_variables.insert(var.nombre.clone());
+            for parametro in proceso.parametros.iter().filter(|parametro| parametro.tipo != "E") {
+                self.errores.push(CompilerError::new(
+                    Mensaje::new(Codigo::ParametroDeSalidaEnFuncion)
+                        .con(&proceso.nombre)
+                        .con(&parametro.nombre)
+                        .con(&parametro.tipo),
+                    parametro.linea, 0
+                ).en_archivo(proceso.archivo));
             }
-            
         }
     }
     
-    fn verificar_invocaciones_procesos(&mut self, programa: &Program, 
-                                      procesos_validos: &HashMap<String, (Vec<(String, String)>, String)>) {
-        // Verificar que los procesos solo se usen después de ser declarados
-        
-        // Primero, crear lista de procesos declarados
-        let mut procesos_declarados = HashSet::new();
-        for proceso in &programa.procesos {
-            procesos_declarados.insert(proceso.nombre.clone());
+    fn robots(&mut self, globales: &HashMap<&str, Declaracion>) {
+        for robot in &self.programa.robots_definidos {
+            let locales = robot.variables.iter().map(|var| (var.nombre.as_str(), var.linea, Termino::Variable));
+            self.nombres_locales(locales, robot.archivo, globales);
         }
-        
-        // Verificar en robots
-        for robot in &programa.robots_definidos {
-            self.verificar_invocaciones_en_instrucciones(&robot.instrucciones, &procesos_declarados, &robot.nombre);
+    }
+
+    fn nombres_locales(
+        &mut self,
+        locales: impl Iterator<Item = (&'ast str, usize, Termino)>,
+        archivo: FileId,
+        globales: &HashMap<&str, Declaracion>,
+    ) {
+        let mut declarados: HashMap<&str, Declaracion> = HashMap::new();
+        for (nombre, linea, descripcion) in locales {
+            let declaracion = Declaracion::new(descripcion, linea, archivo);
+            match declarados.get(nombre).or_else(|| globales.get(nombre)) {
+                Some(anterior) => self.errores.push(conflicto(nombre, anterior, &declaracion)),
+                None => {
+                    declarados.insert(nombre, declaracion);
+                }
+            }
         }
-        
     }
     
-    fn verificar_invocaciones_en_instrucciones(&mut self, instrucciones: &[Instruccion], 
-                                              procesos_declarados: &HashSet<String>, contexto: &str) {
-        for instruccion in instrucciones {
-            match instruccion {
-                Instruccion::LlamadaFuncion { nombre, .. } => {
-                    if procesos_declarados.contains(nombre) {
-                        // Verificar que el proceso no se llame a sí mismo (recursión simple no permitida)
-                        if nombre == contexto {
-                            self.errores.push(CompilerError::new(
-                                format!("Proceso '{}' no puede llamarse a sí mismo", nombre),
-                                0, 0
-                            ));
-                        }
-                    }
-                }
-                Instruccion::Si { entonces, sino, .. } => {
-                    self.verificar_invocaciones_en_instrucciones(entonces, procesos_declarados, contexto);
-                    self.verificar_invocaciones_en_instrucciones(sino, procesos_declarados, contexto);
+    fn secciones(
+        &mut self,
+        entorno: &Entorno,
+        claves: &[Option<u64>],
+        cache: &mut HashMap<u64, Vec<CompilerError>>,
+        hilos: usize,
+    ) {
+        let programa = self.programa;
+
+        let secciones: Vec<Seccion> = programa.procesos.iter().map(Seccion::Proceso)
+            .chain(programa.robots_definidos.iter().map(Seccion::Robot))
+            .collect();
+
+        // Sólo se verifican las secciones sin resultado en la cache
+        let pendientes: Vec<(usize, &Seccion)> = secciones.iter()
+            .enumerate()
+            .filter(|(i, _)| claves.get(*i).copied().flatten().is_none_or(|clave| !cache.contains_key(&clave)))
+            .collect();
+
+        // Cada hilo verifica un bloque contiguo de secciones y devuelve sus
+        // errores; se unen en el orden de las secciones en el programa
+        let hilos = hilos.min(pendientes.len());
+        let errores: Vec<Vec<CompilerError>> = if hilos <= 1 {
+            pendientes.iter()
+                .map(|(_, seccion)| verificar_seccion(programa, seccion, entorno))
+                .collect()
+        } else {
+            let tamaño = pendientes.len().div_ceil(hilos);
+            thread::scope(|scope| {
+                let tareas: Vec<_> = pendientes
+                    .chunks(tamaño)
+                    .map(|bloque| scope.spawn(move || {
+                        bloque.iter()
+                            .map(|(_, seccion)| verificar_seccion(programa, seccion, entorno))
+                            .collect::<Vec<_>>()
+                    }))
+                    .collect();
+                // Un pánico en un hilo es un error del analizador: se propaga tal cual
+                tareas.into_iter()
+                    .flat_map(|tarea| tarea.join().unwrap_or_else(|panico| std::panic::resume_unwind(panico)))
+                    .collect()
+            })
+        };
+
+        let mut calculados: HashMap<usize, Vec<CompilerError>> = pendientes.iter()
+            .map(|(i, _)| *i)
+            .zip(errores)
+            .collect();
+        // La cache guarda los errores con las líneas contadas desde el
+        // encabezado de la sección, que es la 1: la misma sección puede
+        // volver más arriba o más abajo en el programa
+        for (i, seccion) in secciones.iter().enumerate() {
+            let inicio = seccion.linea() as isize - 1;
+            match (calculados.remove(&i), claves.get(i).copied().flatten()) {
+                (Some(errores), Some(clave)) => {
+                    cache.insert(clave, errores.iter().map(|error| error.clone().desplazar(-inicio)).collect());
+                    self.errores.extend(errores);
                 }
-                Instruccion::Mientras { cuerpo, .. } => {
-                    self.verificar_invocaciones_en_instrucciones(cuerpo, procesos_declarados, contexto);
+                (Some(errores), None) => self.errores.extend(errores),
+                (None, Some(clave)) => self.errores.extend(cache[&clave].iter().map(|error| error.clone().desplazar(inicio))),
+                (None, None) => unreachable!("Sección sin verificar ni clave"),
+            }
+        }
+    }
+    
+    // `AsignarArea` e `Iniciar` se verifican con la misma firma que tendrían
+    // dentro de una sección
+    fn programa_principal(&mut self, entorno: &Entorno) {
+        let programa = self.programa;
+        // Las secciones son opcionales, pero sin robots el programa no hace nada
+        if programa.robots_definidos.is_empty() {
+            self.errores.push(CompilerError::new(
+                Codigo::SinRobots, 0, 0
+            ).en_archivo(FileId::PRINCIPAL));
+        }
+
+        let mut errores = Vec::new();
+        let mut verificador = VerificadorVariables {
+            errores: &mut errores,
+            arena: &programa.arena,
+            simbolos: &programa.simbolos,
+            variables_declaradas: HashMap::new(),
+            arreglos: HashMap::new(),
+            de_ciclo: Vec::new(),
+            entorno,
+            en_proceso: false,
+            contexto: &programa.nombre,
+            linea: 0,
+            instruccion: None,
+            informados: HashMap::new(),
+        };
+
+        let llamadas = programa.asignaciones_areas.iter()
+            .map(|asignacion| ("AsignarArea", vec![asignacion.robot, asignacion.area]))
+            .chain(programa.inicializaciones.iter().map(|inicializacion| {
+                ("Iniciar", vec![inicializacion.robot, inicializacion.pos_x, inicializacion.pos_y])
+            }));
+        for (nombre, argumentos) in llamadas {
+            let Some(firma) = firma_elemental(nombre) else { continue };
+            for (indice, argumento) in argumentos.into_iter().enumerate() {
+                if let Some(esperado) = firma.argumento(indice) {
+                    verificador.verificar_argumento(nombre, indice, argumento, esperado);
                 }
-                Instruccion::Repetir { cuerpo, .. } => {
-                    self.verificar_invocaciones_en_instrucciones(cuerpo, procesos_declarados, contexto);
+            }
+        }
+
+        self.errores.extend(errores.into_iter().map(|error| error.en_archivo(FileId::PRINCIPAL)));
+        self.inicializaciones();
+    }
+
+    // Cada robot declarado se inicia una sola vez, después de todas sus
+    // asignaciones de área
+    fn inicializaciones(&mut self) {
+        let programa = self.programa;
+        let robot = |expresion: ExprId| match &programa.arena[expresion] {
+            Expresion::Identificador(nombre) => Some(programa.simbolos.resolver(*nombre)),
+            _ => None,
+        };
+        // Se señala el robot, el primer argumento de la instrucción
+        let error = |mensaje: Mensaje, robot: ExprId| CompilerError::en(mensaje, programa.arena.span_expresion(robot)).en_archivo(FileId::PRINCIPAL);
+
+        let mut iniciados: HashMap<&str, usize> = HashMap::new();
+        for inicializacion in &programa.inicializaciones {
+            let Some(nombre) = robot(inicializacion.robot) else { continue };
+            match iniciados.get(nombre) {
+                Some(&anterior) => self.errores.push(error(
+                    Mensaje::new(Codigo::IniciarRepetido).con(nombre).con(anterior),
+                    inicializacion.robot,
+                )),
+                None => {
+                    iniciados.insert(nombre, inicializacion.linea);
                 }
-                _ => {}
+            }
+        }
+        for asignacion in &programa.asignaciones_areas {
+            if let Some(nombre) = robot(asignacion.robot)
+                && let Some(&iniciado) = iniciados.get(nombre)
+                && asignacion.linea > iniciado
+            {
+                self.errores.push(error(
+                    Mensaje::new(Codigo::AreaDespuesDeIniciar).con(nombre).con(iniciado),
+                    asignacion.robot,
+                ));
+            }
+        }
+        for instancia in &programa.robots_instanciados {
+            if !iniciados.contains_key(instancia.nombre.as_str()) {
+                self.errores.push(
+                    CompilerError::new(Mensaje::new(Codigo::RobotSinIniciar).con(&instancia.nombre), instancia.linea, 0)
+                        .en_archivo(FileId::PRINCIPAL)
+                );
             }
         }
     }
-    
-    fn verificar_variables_locales(&mut self, programa: &Program) {
-        // Verificar variables en procesos
-        for proceso in &programa.procesos {
-            let mut variables_declaradas = HashMap::new();
-            
+}
+
+// Verifica una sección sin modificar el analizador, para poder hacerlo en
+// paralelo con las demás
+fn verificar_seccion(programa: &Program, seccion: &Seccion, entorno: &Entorno) -> Vec<CompilerError> {
+    let mut errores = Vec::new();
+    let mut variables_declaradas = HashMap::new();
+    let variables = match seccion {
+        Seccion::Proceso(proceso) => &proceso.variables,
+        Seccion::Robot(robot) => &robot.variables,
+    };
+    let arreglos = variables.iter()
+        .filter_map(|var| Some((programa.simbolos.get(&var.nombre)?, var.longitud?)))
+        .collect();
+
+    let (nombre, instrucciones, archivo) = match seccion {
+        Seccion::Proceso(proceso) => {
             // Agregar parámetros como variables declaradas
             for param in &proceso.parametros {
-                variables_declaradas.insert(param.nombre.clone(), param.tipo_dato.clone());
+                if let Some(simbolo) = programa.simbolos.get(&param.nombre) {
+                    variables_declaradas.insert(simbolo, param.tipo_dato.as_str());
+                }
             }
-            
-            // Agregar variables locales
             for var in &proceso.variables {
-                variables_declaradas.insert(var.nombre.clone(), var.tipo_dato.clone());
+                if let Some(simbolo) = programa.simbolos.get(&var.nombre) {
+                    variables_declaradas.insert(simbolo, var.tipo_dato.as_str());
+                }
+            }
+            // Lo que devuelve se asigna a su propio nombre
+            if let Some(retorno) = &proceso.retorno
+                && let Some(simbolo) = programa.simbolos.get(&proceso.nombre)
+            {
+                variables_declaradas.insert(simbolo, retorno.as_str());
             }
-            
-            // Verificar uso de variables en instrucciones
-            self.verificar_variables_en_instrucciones(&proceso.instrucciones, &variables_declaradas, &proceso.nombre);
+            (proceso.nombre.as_str(), &proceso.instrucciones, proceso.archivo)
         }
-        
-        // Verificar variables en robots
-        for robot in &programa.robots_definidos {
-            let mut variables_declaradas = HashMap::new();
-            
-            // Agregar variables del robot
+        Seccion::Robot(robot) => {
             for var in &robot.variables {
-                variables_declaradas.insert(var.nombre.clone(), var.tipo_dato.clone());
+                if let Some(simbolo) = programa.simbolos.get(&var.nombre) {
+                    variables_declaradas.insert(simbolo, var.tipo_dato.as_str());
+                }
             }
-            
-            // Verificar uso de variables en instrucciones
-            self.verificar_variables_en_instrucciones(&robot.instrucciones, &variables_declaradas, &robot.nombre);
+
+            // Verificar que el robot no invoque un proceso con su mismo nombre
+            let mut verificador = VerificadorInvocaciones {
+                errores: &mut errores,
+                arena: &programa.arena,
+                simbolos: &programa.simbolos,
+                tabla: &entorno.tabla,
+                contexto: programa.simbolos.get(&robot.nombre),
+            };
+            verificador.visitar_bloque(&robot.instrucciones);
+            (robot.nombre.as_str(), &robot.instrucciones, robot.archivo)
         }
+    };
+
+    // Verificar uso de variables en instrucciones
+    let mut verificador = VerificadorVariables {
+        errores: &mut errores,
+        arena: &programa.arena,
+        simbolos: &programa.simbolos,
+        variables_declaradas,
+        arreglos,
+        de_ciclo: Vec::new(),
+        entorno,
+        en_proceso: matches!(seccion, Seccion::Proceso(_)),
+        contexto: nombre,
+        linea: 0,
+        instruccion: None,
+        informados: HashMap::new(),
+    };
+    verificador.visitar_bloque(instrucciones);
+
+    // Las líneas de los errores son del archivo donde está la sección
+    errores.into_iter().map(|error| error.en_archivo(archivo)).collect()
+}
+
+// Detecta procesos que se invocan a sí mismos
+struct VerificadorInvocaciones<'a> {
+    errores: &'a mut Vec<CompilerError>,
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    tabla: &'a TablaSimbolos,
+    contexto: Option<Symbol>,
+}
+
+impl<'a> Visitor<'a> for VerificadorInvocaciones<'a> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
     }
-    
-    fn verificar_variables_en_instrucciones(&mut self, instrucciones: &[Instruccion], 
-                                          variables_declaradas: &HashMap<String, String>, contexto: &str) {
-        for instruccion in instrucciones {
-            match instruccion {
-                Instruccion::Elemental { nombre } => {
-                    
+
+    // Se recorre por índice para señalar la llamada
+    fn visitar_bloque(&mut self, instrucciones: &'a [InstrId]) {
+        for &id in instrucciones {
+            let instruccion = &self.arena[id];
+            if let Instruccion::LlamadaFuncion { nombre, .. } = instruccion {
+                // Verificar que el proceso no se llame a sí mismo (recursión simple no permitida)
+                if self.tabla.es(*nombre, Categoria::Proceso) && Some(*nombre) == self.contexto {
+                    self.errores.push(CompilerError::en(
+                        Mensaje::new(Codigo::Recursion).con(self.simbolos.resolver(*nombre)),
+                        self.arena.span_instruccion(id)
+                    ));
                 }
-                Instruccion::Asignacion { variable, valor } => {
-                    // Verificar que la variable esté declarada
-                    if !variables_declaradas.contains_key(variable) {
-                        self.errores.push(CompilerError::new(
-                            format!("Variable '{}' no declarada en '{}'", variable, contexto),
-                            0, 0
-                        ));
-                    } else {
-                        // Verificar tipo de la expresión de asignación
-                        let tipo_declarado = &variables_declaradas[variable];
-                        let tipo_expresion = self.obtener_tipo_expresion(valor, variables_declaradas);
-                        
-                        if let Some(tipo_exp) = tipo_expresion {
-                            if tipo_declarado != &tipo_exp {
-                                self.errores.push(CompilerError::new(
-                                    format!("Tipo incorrecto en asignación a '{}': esperado '{}', encontrado '{}' (en '{}')",
-                                            variable, tipo_declarado, tipo_exp, contexto),
-                                    0, 0
-                                ));
-                            }
-                        }
+            }
+            recorrer_instruccion(self, instruccion);
+        }
+    }
+}
+
+// Verifica que las variables usadas estén declaradas, que las asignaciones
+// respeten su tipo y que cada condición tenga el tipo que la instrucción espera
+struct VerificadorVariables<'a> {
+    errores: &'a mut Vec<CompilerError>,
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    variables_declaradas: VariablesDeclaradas<'a>,
+    // Longitud de cada arreglo de la sección; en `variables_declaradas`
+    // figura con el tipo de sus elementos
+    arreglos: HashMap<Symbol, usize>,
+    // Variables de los `para` en cuyo cuerpo se está: se leen, pero no se
+    // les puede asignar
+    de_ciclo: Vec<Symbol>,
+    entorno: &'a Entorno<'a>,
+    // Un proceso sólo ve sus parámetros y variables, no las del robot que lo llama
+    en_proceso: bool,
+    contexto: &'a str,
+    // Instrucción que se está verificando y su línea. Fuera de las
+    // secciones, en el programa principal, no hay instrucción.
+    linea: usize,
+    instruccion: Option<InstrId>,
+    // Posición en `errores` del error de cada variable no declarada
+    informados: HashMap<Symbol, usize>,
+}
+
+impl<'a> Visitor<'a> for VerificadorVariables<'a> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    // Se recorre por índice para conocer el tramo de cada instrucción
+    fn visitar_bloque(&mut self, instrucciones: &'a [InstrId]) {
+        for &id in instrucciones {
+            self.instruccion = Some(id);
+            self.visitar_instruccion(&self.arena[id]);
+        }
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+        self.linea = instruccion.linea();
+        if let Instruccion::Asignacion { variable, indice, valor, .. } = instruccion {
+            // Verificar que la variable esté declarada
+            match self.variables_declaradas.get(variable).copied() {
+                Some(_) if self.de_ciclo.contains(variable) => self.errores.push(self.en_uso(
+                    *variable,
+                    true,
+                    Mensaje::new(Codigo::VariableDeParaModificada)
+                        .con(self.simbolos.resolver(*variable))
+                        .con(self.contexto),
+                )),
+                None => {
+                    match self.entorno.tabla.categoria(*variable) {
+                        Some(categoria) => self.errores.push(self.en_uso(
+                            *variable,
+                            true,
+                            Mensaje::new(Codigo::AsignacionANoVariable)
+                                .con(self.simbolos.resolver(*variable))
+                                .con(categoria)
+                                .con(self.contexto),
+                        )),
+                        None => self.no_declarada(*variable, true, Mensaje::new(Codigo::VariableNoDeclarada)
+                            .con(self.simbolos.resolver(*variable))
+                            .con(self.contexto)
+                            .con(self.variable_de_robot(*variable))
+                        ),
                     }
-                    
-                    // Verificar variables en la expresión
-                    self.verificar_variables_en_expresion(valor, variables_declaradas, contexto);
                 }
-                Instruccion::LlamadaFuncion { argumentos, .. } => {
-                    for arg in argumentos {
-                        self.verificar_variables_en_expresion(arg, variables_declaradas, contexto);
+                // Un arreglo se asigna de a un elemento
+                Some(_) if !self.verificar_destino(*variable, *indice) => {}
+                Some(tipo_declarado) => {
+                    // Verificar tipo de la expresión de asignación
+                    match self.entorno.hir.tipado(*valor) {
+                        Ok(Some(tipo_exp)) if tipo_declarado != tipo_exp.nombre() => {
+                            self.errores.push(CompilerError::en(
+                                Mensaje::new(Codigo::TipoEnAsignacion)
+                                    .con(self.simbolos.resolver(*variable))
+                                    .con(tipo_declarado)
+                                    .con(tipo_exp)
+                                    .con(self.contexto),
+                                self.arena.span_expresion(*valor)
+                            ));
+                        }
+                        Err(mensaje) => self.errores.push(CompilerError::en(
+                            self.en_seccion(mensaje),
+                            self.arena.span_expresion(*valor)
+                        )),
+                        _ => {}
                     }
                 }
-                Instruccion::Si { condicion, entonces, sino } => {
-                    // Verificar variables en la condición
-                    self.verificar_variables_en_expresion(condicion, variables_declaradas, contexto);
-                    
-                    // Verificar variables en los bloques
-                    self.verificar_variables_en_instrucciones(entonces, variables_declaradas, contexto);
-                    self.verificar_variables_en_instrucciones(sino, variables_declaradas, contexto);
-                }
-                Instruccion::Mientras { condicion, cuerpo } => {
-                    self.verificar_variables_en_expresion(condicion, variables_declaradas, contexto);
-                    self.verificar_variables_en_instrucciones(cuerpo, variables_declaradas, contexto);
-                }
-                Instruccion::Repetir { condicion, cuerpo } => {
-                    self.verificar_variables_en_expresion(condicion, variables_declaradas, contexto);
-                    self.verificar_variables_en_instrucciones(cuerpo, variables_declaradas, contexto);
+            }
+        }
+
+        // `si` y `mientras` necesitan una condición booleana; `repetir`, una cantidad
+        let condicion = match instruccion {
+            Instruccion::Si { condicion, .. } => Some(("si", *condicion, Tipo::Booleano)),
+            Instruccion::Mientras { condicion, .. } => Some(("mientras", *condicion, Tipo::Booleano)),
+            Instruccion::Repetir { condicion, .. } => Some(("repetir", *condicion, Tipo::Numero)),
+            _ => None,
+        };
+        if let Some((sentencia, condicion, esperado)) = condicion {
+            self.verificar_condicion(sentencia, condicion, esperado);
+        }
+
+        // Los argumentos de una instrucción elemental se verifican según su
+        // firma: los nombres de robots y áreas no son variables
+        if let Instruccion::LlamadaFuncion { nombre, argumentos, .. } = instruccion
+            && let Some(firma) = firma_elemental(self.simbolos.resolver(*nombre))
+        {
+            let nombre = self.simbolos.resolver(*nombre);
+            if !firma.acepta(argumentos.len()) {
+                self.cantidad_incorrecta(nombre, firma.argumentos.len(), firma.variadica, argumentos.len());
+            }
+            for (indice, argumento) in argumentos.iter().enumerate() {
+                if let Some(esperado) = firma.argumento(indice) {
+                    self.verificar_argumento(nombre, indice, *argumento, esperado);
                 }
             }
+            return;
+        }
+
+        // Los argumentos de un proceso se ligan a sus parámetros por posición
+        if let Instruccion::LlamadaFuncion { nombre, argumentos, .. } = instruccion
+            && let Some(proceso) = self.entorno.procesos.get(nombre)
+        {
+            self.verificar_llamada(proceso, argumentos);
+            return;
+        } else if let Instruccion::LlamadaFuncion { nombre, .. } = instruccion {
+            // Ni instrucción elemental ni proceso; sus argumentos se
+            // verifican igual
+            let mensaje = Mensaje::new(Codigo::ProcesoNoDeclarado).con(self.simbolos.resolver(*nombre)).con(self.contexto);
+            self.errores.push(self.en_instruccion(mensaje));
+        }
+
+        // Cada caso se verifica en su línea, como las ramas de un `si`
+        if let Instruccion::Segun { selector, casos, sino, linea } = instruccion {
+            let propia = self.instruccion;
+            self.visitar_expresion(&self.arena[*selector]);
+            self.verificar_casos(*selector, casos);
+            for caso in casos {
+                self.instruccion = propia;
+                self.linea = caso.linea;
+                self.visitar_expresion(&self.arena[caso.valor]);
+                self.visitar_bloque(&caso.cuerpo);
+            }
+            self.instruccion = propia;
+            self.linea = *linea;
+            self.visitar_bloque(sino);
+            return;
+        }
+
+        // La variable de un `para` es un número que sólo existe en su cuerpo
+        if let Instruccion::Para { variable, desde, hasta, cuerpo, .. } = instruccion {
+            for limite in [*desde, *hasta] {
+                self.verificar_limite(limite);
+                self.visitar_expresion(&self.arena[limite]);
+            }
+            if let Some(categoria) = self.categoria(*variable) {
+                self.errores.push(self.en_instruccion(
+                    Mensaje::new(Codigo::VariableDeParaDeclarada)
+                        .con(self.simbolos.resolver(*variable))
+                        .con(categoria)
+                        .con(self.contexto),
+                ));
+                self.visitar_bloque(cuerpo);
+                return;
+            }
+            self.variables_declaradas.insert(*variable, "numero");
+            self.de_ciclo.push(*variable);
+            self.visitar_bloque(cuerpo);
+            self.de_ciclo.pop();
+            self.variables_declaradas.remove(variable);
+            return;
         }
+
+        // Cada `sino si` se verifica en su línea, después de la rama anterior
+        if let Instruccion::Si { condicion, entonces, alternativas, sino, linea } = instruccion
+            && !alternativas.is_empty()
+        {
+            let propia = self.instruccion;
+            self.visitar_expresion(&self.arena[*condicion]);
+            self.visitar_bloque(entonces);
+            for alternativa in alternativas {
+                self.instruccion = propia;
+                self.linea = alternativa.linea;
+                self.verificar_condicion("si", alternativa.condicion, Tipo::Booleano);
+                self.visitar_expresion(&self.arena[alternativa.condicion]);
+                self.visitar_bloque(&alternativa.cuerpo);
+            }
+            self.instruccion = propia;
+            self.linea = *linea;
+            self.visitar_bloque(sino);
+            return;
+        }
+
+        // Verificar variables en expresiones y bloques anidados
+        recorrer_instruccion(self, instruccion);
     }
-    
-    fn verificar_variables_en_expresion(&mut self, expresion: &Expresion, 
-                                       variables_declaradas: &HashMap<String, String>, contexto: &str) {
-        match expresion {
-            Expresion::Identificador(nombre) => {
-                if !variables_declaradas.contains_key(nombre) {
-                    self.errores.push(CompilerError::new(
-                        format!("Variable '{}' no declarada en expresión (en '{}')", nombre, contexto),
-                        0, 0
+
+    fn visitar_expresion(&mut self, expresion: &'a Expresion) {
+        // Los argumentos se ligan a los parámetros como en una llamada
+        // usada como instrucción
+        if let Expresion::Llamada { nombre, argumentos } = expresion {
+            match self.entorno.procesos.get(nombre) {
+                Some(proceso) if proceso.retorno.is_some() => self.verificar_llamada(proceso, argumentos),
+                _ => {
+                    self.errores.push(self.en_instruccion(
+                        Mensaje::new(Codigo::LlamadaSinValor).con(self.simbolos.resolver(*nombre)).con(self.contexto),
                     ));
+                    recorrer_expresion(self, expresion);
                 }
             }
-            Expresion::Binaria { izquierda, derecha, .. } => {
-                self.verificar_variables_en_expresion(izquierda, variables_declaradas, contexto);
-                self.verificar_variables_en_expresion(derecha, variables_declaradas, contexto);
+            return;
+        }
+        let leido = match expresion {
+            Expresion::Identificador(nombre) | Expresion::Indice { arreglo: nombre, .. } => Some(nombre),
+            _ => None,
+        };
+        if let Some(nombre) = leido
+            && !self.variables_declaradas.contains_key(nombre)
+        {
+            match self.entorno.tabla.categoria(*nombre) {
+                // Una constante se lee como cualquier valor; sólo no puede asignarse
+                Some(Categoria::Constante) => {}
+                Some(categoria) => self.errores.push(self.en_uso(
+                    *nombre,
+                    false,
+                    Mensaje::new(Codigo::NoEsVariable)
+                        .con(self.simbolos.resolver(*nombre))
+                        .con(categoria)
+                        .con(self.contexto),
+                )),
+                None => self.no_declarada(*nombre, false, Mensaje::new(Codigo::VariableNoDeclaradaEnExpresion)
+                    .con(self.simbolos.resolver(*nombre))
+                    .con(self.contexto)
+                    .con(self.variable_de_robot(*nombre))
+                ),
             }
-            _ => {} // Numero y Booleano no tienen variables
         }
-    }
-    
-    fn obtener_tipo_expresion(&self, expresion: &Expresion, 
-                             variables_declaradas: &HashMap<String, String>) -> Option<String> {
         match expresion {
-            Expresion::Identificador(nombre) => {
-                variables_declaradas.get(nombre).cloned()
-            }
-            Expresion::Elemental { nombre } => {
-                // Aquí puedes manejar expresiones elementales si es necesario
-                None
-            }
-
-            Expresion::Numero(_) => Some("numero".to_string()),
-            Expresion::Booleano(_) => Some("booleano".to_string()),
-            Expresion::Binaria { izquierda, operador, derecha } => {
-                let tipo_izq = self.obtener_tipo_expresion(izquierda, variables_declaradas);
-                let tipo_der = self.obtener_tipo_expresion(derecha, variables_declaradas);
-                
-                if let (Some(tipo_i), Some(tipo_d)) = (tipo_izq, tipo_der) {
-                    // Verificar compatibilidad de tipos
-                    if tipo_i == tipo_d {
-                        // Para operaciones aritméticas
-                        if ["+", "-", "*", "/"].contains(&operador.as_str()) {
-                            if tipo_i == "numero" {
-                                return Some("numero".to_string());
-                            } else {
-                                return None; // Error de tipo
-                            }
-                        }
-                        // Para operaciones de comparación
-                        else if ["<", "<=", ">", ">=", "==", "<>"].contains(&operador.as_str()) {
-                            return Some("booleano".to_string());
-                        }
-                        // Para operaciones booleanas
-                        else if ["&", "|"].contains(&operador.as_str()) {
-                            if tipo_i == "booleano" {
-                                return Some("booleano".to_string());
-                            } else {
-                                return None; // Error de tipo
-                            }
-                        }
+            Expresion::Identificador(nombre) if self.arreglos.contains_key(nombre) => self.errores.push(self.en_uso(
+                *nombre,
+                false,
+                Mensaje::new(Codigo::ArregloSinIndice).con(self.simbolos.resolver(*nombre)).con(self.contexto),
+            )),
+            Expresion::Indice { arreglo, indice } => match self.arreglos.get(arreglo) {
+                Some(&longitud) => self.verificar_rango(*arreglo, *indice, longitud),
+                None if self.variables_declaradas.contains_key(arreglo) => self.errores.push(self.en_instruccion(
+                    Mensaje::new(Codigo::IndiceDeNoArreglo).con(self.simbolos.resolver(*arreglo)).con(self.contexto),
+                )),
+                None => {}
+            },
+            _ => {}
+        }
+        recorrer_expresion(self, expresion);
+    }
+}
+
+impl VerificadorVariables<'_> {
+    // Cada variable no declarada se informa una sola vez por sección, en su
+    // primer uso, con una nota en cada línea donde vuelve a usarse. Si hay
+    // una variable de nombre parecido se sugiere; si no, declararla.
+    // `destino` indica que se le está asignando un valor.
+    fn no_declarada(&mut self, nombre: Symbol, destino: bool, mensaje: Mensaje) {
+        let span = self.span_uso(nombre, destino);
+        let linea = self.linea;
+        if let Some(&indice) = self.informados.get(&nombre) {
+            let error = &mut self.errores[indice];
+            let repetida = linea == error.line
+                || error.notas().iter().any(|nota| nota.span.is_some_and(|span| span.linea == linea));
+            if linea > 0 && !repetida {
+                error.detalles_mut().notas.push(Nota {
+                    mensaje: Termino::TambienSeUsaAqui.into(),
+                    span: Some(span.unwrap_or(Span::new(linea, 0, linea, 0))),
+                    archivo: None,
+                });
+            }
+            return;
+        }
+
+        let texto = self.simbolos.resolver(nombre);
+        let mut error = match span {
+            Some(span) => CompilerError::en(mensaje, span),
+            None => self.en_instruccion(mensaje),
+        };
+        let de_robot = self.en_proceso && self.entorno.variables_de_robots.contains_key(&nombre);
+        error = match (self.parecida(texto), span) {
+            (Some(parecida), Some(span)) => {
+                error.con_sugerencia(Mensaje::termino(Termino::QuisoDecir).con(parecida), span, parecida)
+            }
+            (Some(parecida), None) => error.con_ayuda(Mensaje::termino(Termino::QuisoDecir).con(parecida)),
+            // El mensaje ya explica que hay que pasarla como parámetro, y en el
+            // programa principal no se declaran variables
+            _ if de_robot || self.instruccion.is_none() => error,
+            _ => error.con_ayuda(Mensaje::termino(Termino::DeclararVariable).con(texto).con(self.contexto)),
+        };
+        self.informados.insert(nombre, self.errores.len());
+        self.errores.push(error);
+    }
+
+    // Error en la instrucción que se verifica. Una rama de un `si` o un caso
+    // de un `segun` está en otra línea que la instrucción, y en el programa
+    // principal no hay instrucción: ahí sólo se conoce la línea.
+    fn en_instruccion(&self, mensaje: Mensaje) -> CompilerError {
+        match self.instruccion.map(|id| self.arena.span_instruccion(id)) {
+            Some(span) if span.linea == self.linea => CompilerError::en(mensaje, span),
+            _ => CompilerError::new(mensaje, self.linea, 0),
+        }
+    }
+
+    // Error en el uso de `nombre`, o en la instrucción si no se encuentra
+    fn en_uso(&self, nombre: Symbol, destino: bool, mensaje: Mensaje) -> CompilerError {
+        match self.span_uso(nombre, destino) {
+            Some(span) => CompilerError::en(mensaje, span),
+            None => self.en_instruccion(mensaje),
+        }
+    }
+
+    // Tramo del primer uso del nombre en la instrucción que se está
+    // verificando. El destino de una asignación es el comienzo de la
+    // instrucción.
+    fn span_uso(&self, nombre: Symbol, destino: bool) -> Option<Span> {
+        let id = self.instruccion?;
+        if destino {
+            let inicio = self.arena.span_instruccion(id);
+            let fin = inicio.columna + self.simbolos.resolver(nombre).chars().count();
+            return Some(Span::new(inicio.linea, inicio.columna, inicio.linea, fin));
+        }
+        let raices = match &self.arena[id] {
+            Instruccion::Elemental { .. } | Instruccion::Error { .. } => &[][..],
+            Instruccion::Asignacion { valor, .. } => std::slice::from_ref(valor),
+            Instruccion::LlamadaFuncion { argumentos, .. } => argumentos.as_slice(),
+            // La condición de la rama cuya línea se está verificando
+            Instruccion::Si { alternativas, .. }
+                if let Some(alternativa) = alternativas.iter().find(|alternativa| alternativa.linea == self.linea) =>
+            {
+                std::slice::from_ref(&alternativa.condicion)
+            }
+            Instruccion::Si { condicion, .. }
+            | Instruccion::Mientras { condicion, .. }
+            | Instruccion::Repetir { condicion, .. } => std::slice::from_ref(condicion),
+            Instruccion::Segun { selector, casos, .. } => match casos.iter().find(|caso| caso.linea == self.linea) {
+                Some(caso) => std::slice::from_ref(&caso.valor),
+                None => std::slice::from_ref(selector),
+            },
+            Instruccion::Para { desde, hasta, .. } => {
+                return [*desde, *hasta].into_iter()
+                    .find_map(|raiz| buscar_identificador(self.arena, raiz, nombre))
+                    .map(|expresion| self.arena.span_expresion(expresion));
+            }
+        };
+        raices.iter()
+            .find_map(|raiz| buscar_identificador(self.arena, *raiz, nombre))
+            .map(|expresion| self.arena.span_expresion(expresion))
+    }
+
+    // Variable visible con el nombre más parecido, para sugerirla ante un
+    // error de tipeo: a lo sumo una edición cada tres letras
+    fn parecida(&self, nombre: &str) -> Option<&str> {
+        let maximo = (nombre.chars().count() / 3).max(1);
+        self.variables_declaradas.keys()
+            .map(|simbolo| self.simbolos.resolver(*simbolo))
+            .map(|candidata| (distancia(nombre, candidata), candidata))
+            .filter(|(distancia, _)| *distancia <= maximo)
+            .min()
+            .map(|(_, candidata)| candidata)
+    }
+
+    fn verificar_condicion(&mut self, sentencia: &str, condicion: ExprId, esperado: Tipo) {
+        let mensaje = match self.entorno.hir.tipado(condicion) {
+            Ok(Some(tipo)) if tipo != esperado => Mensaje::new(Codigo::TipoDeCondicion)
+                .con(sentencia)
+                .con(esperado)
+                .con(tipo)
+                .con(self.contexto),
+            Err(mensaje) => Mensaje::termino(Termino::EnCondicion).con(mensaje).con(sentencia).con(self.contexto),
+            _ => return,
+        };
+        self.errores.push(CompilerError::en(mensaje, self.arena.span_expresion(condicion)));
+    }
+
+    // Un arreglo se asigna con índice y una variable sin él. Devuelve si el
+    // destino es correcto y hay que verificar el tipo del valor.
+    fn verificar_destino(&mut self, variable: Symbol, indice: Option<ExprId>) -> bool {
+        let codigo = match (self.arreglos.get(&variable), indice) {
+            (Some(_), None) => Codigo::ArregloSinIndice,
+            (None, Some(_)) => Codigo::IndiceDeNoArreglo,
+            (None, None) => return true,
+            (Some(&longitud), Some(indice)) => {
+                let mensaje = match self.entorno.hir.tipado(indice) {
+                    Ok(Some(tipo)) if tipo != Tipo::Numero => Mensaje::new(Codigo::IndiceDeTipo)
+                        .con(self.simbolos.resolver(variable))
+                        .con(tipo),
+                    Err(mensaje) => mensaje,
+                    _ => {
+                        self.verificar_rango(variable, indice, longitud);
+                        return true;
                     }
+                };
+                self.errores.push(CompilerError::en(self.en_seccion(mensaje), self.arena.span_expresion(indice)));
+                return true;
+            }
+        };
+        self.errores.push(self.en_uso(
+            variable,
+            true,
+            Mensaje::new(codigo).con(self.simbolos.resolver(variable)).con(self.contexto),
+        ));
+        false
+    }
+
+    // Un índice que se calcula al compilar tiene que estar entre 1 y la
+    // longitud del arreglo; los demás se verifican al ejecutar
+    fn verificar_rango(&mut self, arreglo: Symbol, indice: ExprId, longitud: usize) {
+        if self.usa_variables(indice) {
+            return;
+        }
+        let Some(valor) = valor_con_constantes(self.arena, &self.arena[indice], &self.entorno.constantes) else {
+            return;
+        };
+        if usize::try_from(valor).is_ok_and(|valor| (1..=longitud).contains(&valor)) {
+            return;
+        }
+        self.errores.push(CompilerError::en(
+            Mensaje::new(Codigo::IndiceFueraDeRango)
+                .con(valor)
+                .con(self.simbolos.resolver(arreglo))
+                .con(longitud)
+                .con(self.contexto),
+            self.arena.span_expresion(indice)
+        ));
+    }
+
+    // Los límites de un `para` son números
+    fn verificar_limite(&mut self, limite: ExprId) {
+        let mensaje = match self.entorno.hir.tipado(limite) {
+            Ok(Some(tipo)) if tipo != Tipo::Numero => Mensaje::new(Codigo::LimiteDePara).con(tipo).con(self.contexto),
+            Err(mensaje) => self.en_seccion(mensaje),
+            _ => return,
+        };
+        self.errores.push(CompilerError::en(mensaje, self.arena.span_expresion(limite)));
+    }
+
+    // Los casos de un `segun` son valores distintos que se calculan al
+    // compilar, del tipo del selector
+    fn verificar_casos(&mut self, selector: ExprId, casos: &[Caso]) {
+        let tipo_selector = self.entorno.hir.tipo(selector);
+        let mut vistos: Vec<(ValorCaso, usize)> = Vec::new();
+        for caso in casos {
+            let span = self.arena.span_expresion(caso.valor);
+            let Some(valor) = self.valor_de_caso(caso.valor) else {
+                // Un nombre no declarado ya se informa al recorrer el valor
+                if self.entorno.hir.tipado(caso.valor).is_ok_and(|tipo| tipo.is_some()) {
+                    self.errores.push(CompilerError::en(
+                        Mensaje::new(Codigo::CasoNoConstante).con(self.contexto), span
+                    ));
                 }
-                None
+                continue;
+            };
+            if let Some(tipo) = tipo_selector
+                && tipo != valor.tipo()
+            {
+                self.errores.push(CompilerError::en(
+                    Mensaje::new(Codigo::CasoDeTipo).con(valor).con(valor.tipo()).con(tipo).con(self.contexto), span
+                ));
+            } else if let Some((_, linea)) = vistos.iter().find(|(visto, _)| *visto == valor) {
+                self.errores.push(CompilerError::en(
+                    Mensaje::new(Codigo::CasoRepetido).con(valor).con(*linea).con(self.contexto), span
+                ));
+            } else {
+                vistos.push((valor, caso.linea));
             }
         }
     }
-    
-    pub fn obtener_errores(&self) -> &[CompilerError] {
-        &self.errores
+
+    // `V`, `F` o un número calculable con las constantes del programa; las
+    // variables de la sección ocultan a las constantes del mismo nombre
+    fn valor_de_caso(&self, valor: ExprId) -> Option<ValorCaso> {
+        if let Expresion::Booleano(booleano) = self.arena[valor] {
+            return Some(ValorCaso::Booleano(booleano));
+        }
+        if self.usa_variables(valor) {
+            return None;
+        }
+        valor_con_constantes(self.arena, &self.arena[valor], &self.entorno.constantes).map(ValorCaso::Numero)
     }
-    
-    pub fn obtener_advertencias(&self) -> &[String] {
-        &self.advertencias
+
+    fn usa_variables(&self, expresion: ExprId) -> bool {
+        match &self.arena[expresion] {
+            Expresion::Identificador(nombre) => self.variables_declaradas.contains_key(nombre),
+            Expresion::Binaria { izquierda, derecha, .. } => self.usa_variables(*izquierda) || self.usa_variables(*derecha),
+            Expresion::Negacion(operando) => self.usa_variables(*operando),
+            _ => false,
+        }
     }
-    
-    pub fn mostrar_resultados(&self) {
-        if self.errores.is_empty() && self.advertencias.is_empty() {
-            println!("✓ Análisis semántico completado sin errores ni advertencias.");
+
+    fn verificar_argumento(&mut self, instruccion: &str, indice: usize, argumento: ExprId, esperado: Argumento) {
+        let expresion = &self.arena[argumento];
+        let valido = match esperado {
+            // `*` sólo vale como robot de un mensaje
+            _ if matches!(expresion, Expresion::Todos) => esperado == Argumento::RobotOTodos,
+            Argumento::Numero => {
+                self.visitar_expresion(expresion);
+                match self.entorno.hir.tipado(argumento) {
+                    Ok(tipo) => tipo.is_none_or(|tipo| tipo == Tipo::Numero),
+                    Err(mensaje) => {
+                        self.errores.push(CompilerError::en(self.en_seccion(mensaje), self.arena.span_expresion(argumento)));
+                        true
+                    }
+                }
+            }
+            Argumento::Valor => {
+                self.visitar_expresion(expresion);
+                true
+            }
+            Argumento::Variable => {
+                self.visitar_expresion(expresion);
+                // La variable de un `para` ya tiene su propio error
+                self.modifica_de_ciclo(argumento)
+                    || matches!(expresion, Expresion::Identificador(nombre) if !self.es_constante(*nombre))
+            }
+            Argumento::Robot | Argumento::RobotOTodos => self.es_nombre(expresion, Categoria::Robot),
+            Argumento::Area => self.es_nombre(expresion, Categoria::Area),
+        };
+        if valido {
             return;
         }
-        
-        if !self.errores.is_empty() {
-            println!("✗ Errores encontrados:");
-            for error in &self.errores {
-                println!("  - {}", error.message);
+
+        // Si el argumento nombra otra cosa (una variable en lugar de un robot,
+        // por ejemplo) se indica qué es
+        let categoria = match (esperado, expresion) {
+            (Argumento::Robot | Argumento::RobotOTodos | Argumento::Area | Argumento::Variable, Expresion::Identificador(nombre)) => {
+                self.categoria(*nombre).map(|categoria| (*nombre, categoria))
             }
+            _ => None,
+        };
+        let mensaje = match categoria {
+            Some((nombre, categoria)) => Mensaje::new(Codigo::ArgumentoDeCategoria)
+                .con(indice + 1)
+                .con(instruccion)
+                .con(esperado.descripcion())
+                .con(self.simbolos.resolver(nombre))
+                .con(categoria)
+                .con(self.contexto),
+            None => Mensaje::new(Codigo::ArgumentoInvalido)
+                .con(indice + 1)
+                .con(instruccion)
+                .con(esperado.descripcion())
+                .con(self.contexto),
+        };
+        self.errores.push(CompilerError::en(mensaje, self.arena.span_expresion(argumento)));
+    }
+
+    fn cantidad_incorrecta(&mut self, nombre: &str, esperados: usize, variadica: bool, recibidos: usize) {
+        let esperados = match (esperados, variadica) {
+            (0, _) => Mensaje::termino(Termino::SinArgumentos),
+            (1, false) => Mensaje::termino(Termino::UnArgumento),
+            (n, false) => Mensaje::termino(Termino::Argumentos).con(n),
+            (1, true) => Mensaje::termino(Termino::AlMenosUnArgumento),
+            (n, true) => Mensaje::termino(Termino::AlMenosArgumentos).con(n),
+        };
+        self.errores.push(self.en_instruccion(
+            Mensaje::new(Codigo::CantidadDeArgumentos).con(nombre).con(esperados).con(recibidos).con(self.contexto),
+        ));
+    }
+
+    // Un parámetro E recibe el valor de una expresión del tipo declarado; uno
+    // ES o S necesita una variable de la sección que llama, donde se copia el
+    // valor final al terminar el proceso
+    fn verificar_llamada(&mut self, proceso: &Proceso, argumentos: &[ExprId]) {
+        let nombre = proceso.nombre.as_str();
+        if proceso.parametros.len() != argumentos.len() {
+            self.cantidad_incorrecta(nombre, proceso.parametros.len(), false, argumentos.len());
         }
-        
-        if !self.advertencias.is_empty() {
-            println!("⚠ Advertencias:");
-            for advertencia in &self.advertencias {
-                println!("  - {}", advertencia);
+
+        for (indice, (parametro, argumento)) in proceso.parametros.iter().zip(argumentos).enumerate() {
+            let expresion = &self.arena[*argumento];
+            let span = self.arena.span_expresion(*argumento);
+            self.visitar_expresion(expresion);
+
+            if matches!(expresion, Expresion::Todos) {
+                self.errores.push(CompilerError::en(
+                    Mensaje::new(Codigo::ArgumentoInvalido)
+                        .con(indice + 1)
+                        .con(nombre)
+                        .con(Termino::UnValor)
+                        .con(self.contexto),
+                    span
+                ));
+                continue;
+            }
+
+            if parametro.tipo != "E" && self.modifica_de_ciclo(*argumento) {
+                continue;
+            }
+
+            // Un nombre no declarado ya se informó al visitar la expresión
+            if parametro.tipo != "E" && !matches!(expresion, Expresion::Identificador(nombre) if !self.es_constante(*nombre)) {
+                self.errores.push(CompilerError::en(
+                    Mensaje::new(Codigo::ArgumentoNoVariable)
+                        .con(indice + 1)
+                        .con(nombre)
+                        .con(&parametro.nombre)
+                        .con(&parametro.tipo)
+                        .con(self.contexto),
+                    span
+                ));
+                continue;
+            }
+
+            match self.entorno.hir.tipado(*argumento) {
+                Ok(Some(tipo)) if tipo.nombre() != parametro.tipo_dato => self.errores.push(CompilerError::en(
+                    Mensaje::new(Codigo::TipoDeArgumento)
+                        .con(indice + 1)
+                        .con(nombre)
+                        .con(&parametro.tipo_dato)
+                        .con(tipo)
+                        .con(self.contexto),
+                    span
+                )),
+                Err(mensaje) => self.errores.push(CompilerError::en(self.en_seccion(mensaje), span)),
+                _ => {}
+            }
+        }
+    }
+
+    // Explicación para un nombre que un proceso no ve porque es una variable
+    // de algún robot; vacía en cualquier otro caso
+    fn variable_de_robot(&self, nombre: Symbol) -> Mensaje {
+        match self.entorno.variables_de_robots.get(&nombre) {
+            Some(robots) if self.en_proceso => {
+                let termino = if robots.len() == 1 { Termino::VariableDeRobot } else { Termino::VariableDeRobots };
+                Mensaje::termino(termino).con(robots.join("', '"))
+            }
+            _ => Mensaje::from(""),
+        }
+    }
+
+    // El mensaje seguido de la sección donde ocurrió: "... (en 'r')"
+    fn en_seccion(&self, mensaje: Mensaje) -> Mensaje {
+        Mensaje::termino(Termino::EnSeccion).con(mensaje).con(self.contexto)
+    }
+
+    fn es_nombre(&self, expresion: &Expresion, categoria: Categoria) -> bool {
+        matches!(expresion, Expresion::Identificador(nombre) if self.entorno.tabla.es(*nombre, categoria))
+    }
+
+    // Informa si el argumento que la instrucción modificaría es la variable
+    // de un `para`
+    fn modifica_de_ciclo(&mut self, argumento: ExprId) -> bool {
+        let Expresion::Identificador(nombre) = self.arena[argumento] else {
+            return false;
+        };
+        if !self.de_ciclo.contains(&nombre) {
+            return false;
+        }
+        self.errores.push(CompilerError::en(
+            Mensaje::new(Codigo::VariableDeParaModificada).con(self.simbolos.resolver(nombre)).con(self.contexto),
+            self.arena.span_expresion(argumento)
+        ));
+        true
+    }
+
+    fn es_constante(&self, nombre: Symbol) -> bool {
+        self.categoria(nombre) == Some(Categoria::Constante)
+    }
+
+    // Las variables de la sección ocultan a los nombres globales
+    fn categoria(&self, nombre: Symbol) -> Option<Categoria> {
+        if self.variables_declaradas.contains_key(&nombre) {
+            Some(Categoria::Variable)
+        } else {
+            self.entorno.tabla.categoria(nombre)
+        }
+    }
+}
+
+// Primera aparición del identificador dentro de la expresión
+fn buscar_identificador(arena: &Arena, expresion: ExprId, nombre: Symbol) -> Option<ExprId> {
+    match &arena[expresion] {
+        Expresion::Identificador(simbolo) if *simbolo == nombre => Some(expresion),
+        Expresion::Binaria { izquierda, derecha, .. } => {
+            buscar_identificador(arena, *izquierda, nombre).or_else(|| buscar_identificador(arena, *derecha, nombre))
+        }
+        Expresion::Negacion(operando) => buscar_identificador(arena, *operando, nombre),
+        Expresion::Llamada { argumentos, .. } => {
+            argumentos.iter().find_map(|argumento| buscar_identificador(arena, *argumento, nombre))
+        }
+        Expresion::Indice { indice, .. } => buscar_identificador(arena, *indice, nombre),
+        _ => None,
+    }
+}
+
+// Distancia de edición entre dos nombres: cuántas letras hay que agregar,
+// quitar, cambiar o intercambiar con la siguiente para pasar de uno al otro
+fn distancia(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, fila) in d.iter_mut().enumerate() {
+        fila[0] = i;
+    }
+    for (j, celda) in d[0].iter_mut().enumerate() {
+        *celda = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cambio = d[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = cambio.min(d[i - 1][j] + 1).min(d[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
             }
         }
     }
-}
\ No newline at end of file
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use crate::interner::Symbol;
+use crate::parser::arena::InstrId;
+use crate::parser::processor::{Expresion, Instruccion, Program};
+use crate::source::FileId;
+use super::symbol_table::{Categoria, TablaSimbolos};
+
+// Qué clase de declaración introduce un nombre
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Clase {
+    Proceso,
+    Parametro,
+    Variable,
+    Area,
+    TipoRobot,
+    Robot,
+    Constante,
+}
+
+// Una declaración de un nombre. Los parámetros y variables indican la
+// sección donde se declararon; los nombres globales no tienen sección.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Declarado {
+    pub nombre: String,
+    pub clase: Clase,
+    pub seccion: Option<String>,
+    // Tipo de dato de parámetros y variables, tipo de un área o de un robot
+    pub tipo: Option<String>,
+    pub linea: usize,
+    pub archivo: FileId,
+}
+
+// Llamada a un proceso desde un robot u otro proceso
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Llamada {
+    pub desde: String,
+    pub linea: usize,
+    pub archivo: FileId,
+}
+
+// Consultas sobre un programa ya analizado, para las herramientas que
+// necesitan saber dónde se declara o se usa cada cosa sin recorrer el AST:
+// el servidor de lenguaje, los visores y los correctores. Los índices se
+// arman una sola vez al crearlas.
+pub struct Consultas<'a> {
+    programa: &'a Program,
+    tabla: TablaSimbolos,
+    llamadas: HashMap<Symbol, Vec<Llamada>>,
+    lineas: HashMap<(FileId, usize), Vec<InstrId>>,
+}
+
+impl<'a> Consultas<'a> {
+    pub fn new(programa: &'a Program) -> Self {
+        let mut consultas = Self {
+            programa,
+            tabla: TablaSimbolos::del_programa(programa),
+            llamadas: HashMap::new(),
+            lineas: HashMap::new(),
+        };
+
+        let secciones = programa.procesos.iter()
+            .map(|proceso| (&proceso.nombre, &proceso.instrucciones, proceso.archivo))
+            .chain(programa.robots_definidos.iter().map(|robot| (&robot.nombre, &robot.instrucciones, robot.archivo)));
+        for (nombre, instrucciones, archivo) in secciones {
+            consultas.indexar(nombre, instrucciones, archivo);
+        }
+        consultas
+    }
+
+    fn indexar(&mut self, seccion: &str, instrucciones: &[InstrId], archivo: FileId) {
+        for &id in instrucciones {
+            let instruccion = self.programa.arena.instruccion(id);
+            self.lineas.entry((archivo, instruccion.linea())).or_default().push(id);
+            match instruccion {
+                Instruccion::LlamadaFuncion { nombre, linea, .. } if self.tabla.es(*nombre, Categoria::Proceso) => {
+                    self.llamadas.entry(*nombre).or_default().push(Llamada {
+                        desde: seccion.to_string(),
+                        linea: *linea,
+                        archivo,
+                    });
+                }
+                Instruccion::Si { entonces, alternativas, sino, .. } => {
+                    self.indexar(seccion, entonces, archivo);
+                    for alternativa in alternativas {
+                        self.indexar(seccion, &alternativa.cuerpo, archivo);
+                    }
+                    self.indexar(seccion, sino, archivo);
+                }
+                Instruccion::Mientras { cuerpo, .. }
+                | Instruccion::Repetir { cuerpo, .. }
+                | Instruccion::Para { cuerpo, .. } => {
+                    self.indexar(seccion, cuerpo, archivo);
+                }
+                Instruccion::Segun { casos, sino, .. } => {
+                    for caso in casos {
+                        self.indexar(seccion, &caso.cuerpo, archivo);
+                    }
+                    self.indexar(seccion, sino, archivo);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Todas las declaraciones del nombre: un parámetro o variable puede
+    // repetirse en varias secciones
+    pub fn buscar_simbolo(&self, nombre: &str) -> Vec<Declarado> {
+        self.declaraciones().into_iter().filter(|declarado| declarado.nombre == nombre).collect()
+    }
+
+    // Todas las declaraciones del programa: primero los nombres globales y
+    // después los parámetros y variables de cada sección
+    pub fn declaraciones(&self) -> Vec<Declarado> {
+        let programa = self.programa;
+        let global = |nombre: &String, clase, tipo: Option<&String>, linea, archivo| Declarado {
+            nombre: nombre.clone(),
+            clase,
+            seccion: None,
+            tipo: tipo.cloned(),
+            linea,
+            archivo,
+        };
+        let mut encontrados = Vec::new();
+
+        for proceso in &programa.procesos {
+            encontrados.push(global(&proceso.nombre, Clase::Proceso, None, proceso.linea, proceso.archivo));
+        }
+        for area in &programa.areas {
+            encontrados.push(global(&area.nombre, Clase::Area, Some(&area.tipo), area.linea, FileId::PRINCIPAL));
+        }
+        for robot in &programa.robots_definidos {
+            encontrados.push(global(&robot.nombre, Clase::TipoRobot, None, robot.linea, robot.archivo));
+        }
+        for robot in &programa.robots_instanciados {
+            encontrados.push(global(&robot.nombre, Clase::Robot, Some(&robot.tipo), robot.linea, FileId::PRINCIPAL));
+        }
+        for constante in &programa.constantes {
+            encontrados.push(global(&constante.nombre, Clase::Constante, None, constante.linea, FileId::PRINCIPAL));
+        }
+
+        let locales = programa.procesos.iter()
+            .flat_map(|proceso| {
+                let parametros = proceso.parametros.iter()
+                    .map(move |parametro| (Clase::Parametro, &parametro.nombre, parametro.tipo_dato.clone(), parametro.linea));
+                let variables = proceso.variables.iter()
+                    .map(move |variable| (Clase::Variable, &variable.nombre, variable.tipo_declarado(), variable.linea));
+                parametros.chain(variables).map(move |local| (&proceso.nombre, proceso.archivo, local))
+            })
+            .chain(programa.robots_definidos.iter().flat_map(|robot| {
+                robot.variables.iter()
+                    .map(move |variable| (&robot.nombre, robot.archivo, (Clase::Variable, &variable.nombre, variable.tipo_declarado(), variable.linea)))
+            }));
+        for (seccion, archivo, (clase, nombre, tipo, linea)) in locales {
+            encontrados.push(Declarado {
+                nombre: nombre.clone(),
+                clase,
+                seccion: Some(seccion.clone()),
+                tipo: Some(tipo),
+                linea,
+                archivo,
+            });
+        }
+        encontrados
+    }
+
+    // Llamadas al proceso, en el orden del programa
+    pub fn llamadores_de(&self, proceso: &str) -> &[Llamada] {
+        self.programa.simbolos.get(proceso)
+            .and_then(|simbolo| self.llamadas.get(&simbolo))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    // Robots a los que el programa principal les asigna el área
+    pub fn robots_en_area(&self, area: &str) -> Vec<&'a str> {
+        let programa = self.programa;
+        let nombre = |id| match &programa.arena[id] {
+            Expresion::Identificador(simbolo) => Some(programa.simbolos.resolver(*simbolo)),
+            _ => None,
+        };
+        let mut robots = Vec::new();
+        for asignacion in &programa.asignaciones_areas {
+            if nombre(asignacion.area) == Some(area)
+                && let Some(robot) = nombre(asignacion.robot)
+                && !robots.contains(&robot)
+            {
+                robots.push(robot);
+            }
+        }
+        robots
+    }
+
+    // Instrucciones que empiezan en la línea del archivo, incluidas las
+    // anidadas, en el orden del programa
+    pub fn instrucciones_en_linea(&self, archivo: FileId, linea: usize) -> &[InstrId] {
+        self.lineas.get(&(archivo, linea)).map_or(&[], Vec::as_slice)
+    }
+}
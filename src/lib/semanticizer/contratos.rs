@@ -0,0 +1,201 @@
+use std::collections::{HashMap, HashSet};
+use crate::compilerError::CompilerError;
+use crate::interner::Interner;
+use crate::interpreter::runtime::TODOS;
+use crate::mensajes::{Codigo, Mensaje, Termino};
+use crate::source::{FileId, Span};
+use super::super::parser::arena::Arena;
+use super::super::parser::ast::{recorrer_instruccion, Visitor};
+use super::super::parser::processor::{Expresion, Instruccion, Program, Robot, SentidoMensaje};
+use super::hir::{Hir, Referencia};
+
+// Un lugar del programa que fija el tipo de los mensajes entre dos robots:
+// un `EnviarMensaje`, un `RecibirMensaje` o una línea del bloque `mensajes`
+struct Extremo<'ast> {
+    sentido: SentidoMensaje,
+    // Robot o tipo de robot del otro lado del mensaje, o `*`
+    otro: &'ast str,
+    tipo: &'ast str,
+    descripcion: Mensaje,
+    // El valor enviado o la variable receptora; del bloque `mensajes` sólo
+    // se conoce la línea
+    span: Span,
+    archivo: FileId,
+    contrato: bool,
+}
+
+// Compara los tipos de los valores que cada par de robots se envía. Con un
+// bloque `mensajes` todos los envíos y recepciones entre los dos deben ser
+// del tipo declarado; sin él, se infiere de los envíos, y cada recepción
+// debe guardar el valor en una variable del tipo de alguno de ellos (un
+// robot puede enviar primero un número y después un booleano).
+pub fn verificar(programa: &Program, hir: &Hir) -> Vec<CompilerError> {
+    let mut errores = Vec::new();
+
+    let instancias: Vec<(&str, &str)> = programa.robots_instanciados.iter()
+        .map(|robot| (robot.nombre.as_str(), robot.tipo.as_str()))
+        .collect();
+    let nombra = |otro: &str, (nombre, tipo): (&str, &str)| otro == TODOS || otro == nombre || otro == tipo;
+
+    let mut extremos: HashMap<&str, Vec<Extremo>> = HashMap::new();
+    for robot in &programa.robots_definidos {
+        for contrato in &robot.mensajes {
+            let conocido = instancias.iter().any(|&instancia| nombra(&contrato.robot, instancia));
+            if !conocido {
+                errores.push(CompilerError::new(
+                    Mensaje::new(Codigo::ContratoConRobotDesconocido).con(&robot.nombre).con(&contrato.robot),
+                    contrato.linea, 0
+                ).en_archivo(robot.archivo));
+            }
+        }
+        extremos.insert(robot.nombre.as_str(), extremos_del_robot(programa, hir, robot));
+    }
+
+    let mut informados = HashSet::new();
+    for &emisor in &instancias {
+        for &receptor in &instancias {
+            // Un robot no se envía a sí mismo lo que manda a todos
+            let mismo = emisor.0 == receptor.0;
+            let apunta = |extremo: &&Extremo, sentido, otro| {
+                extremo.sentido == sentido && nombra(extremo.otro, otro) && !(mismo && extremo.otro == TODOS)
+            };
+            let envios = extremos.get(emisor.1).into_iter().flatten()
+                .filter(|extremo| apunta(extremo, SentidoMensaje::Envia, receptor));
+            let recepciones = extremos.get(receptor.1).into_iter().flatten()
+                .filter(|extremo| apunta(extremo, SentidoMensaje::Recibe, emisor));
+            let canal: Vec<&Extremo> = envios.chain(recepciones).collect();
+
+            let mut informar = |extremo: &Extremo, referencia: &Extremo| {
+                let clave = (extremo.archivo, extremo.span.linea, referencia.archivo, referencia.span.linea);
+                if informados.insert(clave) {
+                    errores.push(incompatible(emisor.0, receptor.0, extremo, referencia));
+                }
+            };
+
+            if let Some(declarado) = canal.iter().find(|extremo| extremo.contrato) {
+                for extremo in canal.iter().filter(|extremo| extremo.tipo != declarado.tipo) {
+                    informar(extremo, declarado);
+                }
+                continue;
+            }
+
+            let (envios, recepciones): (Vec<&Extremo>, Vec<&Extremo>) = canal.iter()
+                .partition(|extremo| extremo.sentido == SentidoMensaje::Envia);
+            // Los de un lado cuyo tipo no aparece del otro
+            let sin_par = |lado: &[&Extremo], otros: &[&Extremo]| -> Vec<usize> {
+                if otros.is_empty() {
+                    return Vec::new();
+                }
+                (0..lado.len()).filter(|&i| !otros.iter().any(|otro| otro.tipo == lado[i].tipo)).collect()
+            };
+            // Se informa del lado de quien recibe, salvo que ahí esté todo bien
+            let recepciones_sin_par = sin_par(&recepciones, &envios);
+            for &i in &recepciones_sin_par {
+                informar(recepciones[i], envios[0]);
+            }
+            if recepciones_sin_par.is_empty() {
+                for i in sin_par(&envios, &recepciones) {
+                    informar(envios[i], recepciones[0]);
+                }
+            }
+        }
+    }
+
+    errores
+}
+
+fn incompatible(emisor: &str, receptor: &str, extremo: &Extremo, referencia: &Extremo) -> CompilerError {
+    let mensaje = Mensaje::new(Codigo::MensajeDeTipoIncompatible)
+        .con(emisor)
+        .con(receptor)
+        .con(extremo.descripcion.clone())
+        .con(extremo.tipo)
+        .con(referencia.descripcion.clone())
+        .con(referencia.tipo);
+    CompilerError::en(mensaje, extremo.span)
+    .en_archivo(extremo.archivo)
+    .con_nota_en(
+        Mensaje::termino(Termino::TipoDelMensaje).con(referencia.descripcion.clone()).con(referencia.tipo),
+        referencia.span,
+        referencia.archivo,
+    )
+}
+
+// Contratos declarados y mensajes que el cuerpo del robot envía o recibe.
+// Los que están dentro de procesos no se consideran: un proceso no sabe qué
+// robot lo ejecuta.
+fn extremos_del_robot<'ast>(programa: &'ast Program, hir: &Hir, robot: &'ast Robot) -> Vec<Extremo<'ast>> {
+    let mut extremos: Vec<Extremo> = robot.mensajes.iter()
+        .map(|contrato| Extremo {
+            sentido: contrato.sentido,
+            otro: &contrato.robot,
+            tipo: &contrato.tipo_dato,
+            descripcion: Mensaje::termino(Termino::ContratoDe).con(&robot.nombre),
+            span: Span::new(contrato.linea, 0, contrato.linea, 0),
+            archivo: robot.archivo,
+            contrato: true,
+        })
+        .collect();
+
+    let mut buscador = BuscadorMensajes {
+        arena: &programa.arena,
+        simbolos: &programa.simbolos,
+        hir,
+        archivo: robot.archivo,
+        extremos: &mut extremos,
+    };
+    buscador.visitar_bloque(&robot.instrucciones);
+
+    extremos
+}
+
+struct BuscadorMensajes<'a, 'ast> {
+    arena: &'ast Arena,
+    simbolos: &'ast Interner,
+    hir: &'a Hir,
+    archivo: FileId,
+    extremos: &'a mut Vec<Extremo<'ast>>,
+}
+
+impl<'ast> Visitor<'ast> for BuscadorMensajes<'_, 'ast> {
+    fn arena(&self) -> &'ast Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'ast Instruccion) {
+        if let Instruccion::LlamadaFuncion { nombre, argumentos, .. } = instruccion
+            && let [valor, robot, ..] = argumentos.as_slice()
+            && let Some(otro) = match &self.arena[*robot] {
+                Expresion::Identificador(otro) => Some(self.simbolos.resolver(*otro)),
+                Expresion::Todos => Some(TODOS),
+                _ => None,
+            }
+        {
+            let extremo = match self.simbolos.resolver(*nombre) {
+                // Un valor con errores de tipos ya se informó al verificar la sección
+                "EnviarMensaje" => self.hir.tipo(*valor)
+                    .map(|tipo| (SentidoMensaje::Envia, tipo.nombre(), Mensaje::termino(Termino::ValorEnviado))),
+                "RecibirMensaje" | "RecibirMensajeTimeout" => match (&self.arena[*valor], self.hir.referencia(*valor)) {
+                    (Expresion::Identificador(variable), Some(Referencia::Variable { tipo: Some(tipo) })) => {
+                        let descripcion = Mensaje::termino(Termino::VariableReceptora).con(self.simbolos.resolver(*variable));
+                        Some((SentidoMensaje::Recibe, tipo.nombre(), descripcion))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some((sentido, tipo, descripcion)) = extremo {
+                self.extremos.push(Extremo {
+                    sentido,
+                    otro,
+                    tipo,
+                    descripcion,
+                    span: self.arena.span_expresion(*valor),
+                    archivo: self.archivo,
+                    contrato: false,
+                });
+            }
+        }
+        recorrer_instruccion(self, instruccion);
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use crate::interner::Symbol;
+use crate::interpreter::runtime::es_accion_con_salida;
+use crate::mensajes::{Codigo, Mensaje};
+use crate::source::FileId;
+use super::super::parser::arena::{ExprId, InstrId};
+use super::super::parser::processor::{Expresion, Instruccion, Proceso, Program, Variable};
+use super::lints::{valor_constante, Hallazgo};
+
+// Variables con valor en un punto del programa: las asignadas en todos los
+// caminos que llegan hasta ahí y las asignadas en al menos uno
+#[derive(Debug, Clone, Default)]
+struct Asignadas {
+    seguras: HashSet<Symbol>,
+    posibles: HashSet<Symbol>,
+}
+
+impl Asignadas {
+    fn asignar(&mut self, variable: Symbol) {
+        self.seguras.insert(variable);
+        self.posibles.insert(variable);
+    }
+
+    // Estado después de dos caminos alternativos
+    fn unir(mut self, otra: Asignadas) -> Self {
+        self.seguras.retain(|variable| otra.seguras.contains(variable));
+        self.posibles.extend(otra.posibles);
+        self
+    }
+}
+
+// Análisis de asignación definitiva: advierte las variables locales que se
+// leen antes de asignarles un valor en todos los caminos posibles. Los
+// parámetros ya llegan con valor. El intérprete inicia las variables en 0 o
+// F, así que no es un error, pero casi siempre es un descuido.
+pub fn advertencias(programa: &Program) -> Vec<Hallazgo> {
+    let procesos: HashMap<Symbol, &Proceso> = programa.procesos.iter()
+        .filter_map(|proceso| Some((programa.simbolos.get(&proceso.nombre)?, proceso)))
+        .collect();
+
+    let mut advertencias = Vec::new();
+    let secciones = programa.procesos.iter()
+        .map(|proceso| (proceso.nombre.as_str(), &proceso.variables, &proceso.instrucciones, proceso.archivo))
+        .chain(programa.robots_definidos.iter()
+            .map(|robot| (robot.nombre.as_str(), &robot.variables, &robot.instrucciones, robot.archivo)));
+    for (contexto, variables, instrucciones, archivo) in secciones {
+        let mut flujo = Flujo {
+            programa,
+            procesos: &procesos,
+            locales: locales(programa, variables),
+            informadas: HashSet::new(),
+            advertencias: &mut advertencias,
+            contexto,
+            archivo,
+        };
+        flujo.bloque(instrucciones, &mut Asignadas::default());
+    }
+    advertencias
+}
+
+// Variables a las que el bloque les asigna un valor en algún camino, por
+// asignación, como destino de una acción o como argumento S o ES
+pub(super) fn asignadas(programa: &Program, instrucciones: &[InstrId]) -> HashSet<Symbol> {
+    let procesos: HashMap<Symbol, &Proceso> = programa.procesos.iter()
+        .filter_map(|proceso| Some((programa.simbolos.get(&proceso.nombre)?, proceso)))
+        .collect();
+    let flujo = Flujo {
+        programa,
+        procesos: &procesos,
+        locales: HashSet::new(),
+        informadas: HashSet::new(),
+        advertencias: &mut Vec::new(),
+        contexto: "",
+        archivo: FileId::PRINCIPAL,
+    };
+    flujo.asignadas_en(instrucciones)
+}
+
+// Los arreglos no se siguen elemento por elemento, así que no se advierten
+fn locales(programa: &Program, variables: &[Variable]) -> HashSet<Symbol> {
+    variables.iter()
+        .filter(|variable| variable.longitud.is_none())
+        .filter_map(|variable| programa.simbolos.get(&variable.nombre))
+        .collect()
+}
+
+struct Flujo<'a> {
+    programa: &'a Program,
+    procesos: &'a HashMap<Symbol, &'a Proceso>,
+    locales: HashSet<Symbol>,
+    // Cada variable se advierte una sola vez por sección
+    informadas: HashSet<Symbol>,
+    advertencias: &'a mut Vec<Hallazgo>,
+    contexto: &'a str,
+    archivo: FileId,
+}
+
+impl Flujo<'_> {
+    fn bloque(&mut self, instrucciones: &[InstrId], estado: &mut Asignadas) {
+        for instruccion in self.programa.arena.bloque(instrucciones) {
+            self.instruccion(instruccion, estado);
+        }
+    }
+
+    fn instruccion(&mut self, instruccion: &Instruccion, estado: &mut Asignadas) {
+        let linea = instruccion.linea();
+        match instruccion {
+            Instruccion::Elemental { .. } => {}
+            Instruccion::Asignacion { variable, indice, valor, .. } => {
+                if let Some(indice) = indice {
+                    self.leer(*indice, estado, linea);
+                }
+                self.leer(*valor, estado, linea);
+                estado.asignar(*variable);
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, .. } => {
+                self.llamada(*nombre, argumentos, estado, linea);
+            }
+            Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                self.leer(*condicion, estado, linea);
+                let mut por_ramas = estado.clone();
+                self.bloque(entonces, &mut por_ramas);
+                // Las condiciones de los `sino si` se leen sólo si fallaron
+                // las anteriores, así que ven lo asignado antes del `si`
+                for alternativa in alternativas {
+                    self.leer(alternativa.condicion, estado, alternativa.linea);
+                    let mut por_alternativa = estado.clone();
+                    self.bloque(&alternativa.cuerpo, &mut por_alternativa);
+                    por_ramas = por_ramas.unir(por_alternativa);
+                }
+                let mut por_sino = estado.clone();
+                self.bloque(sino, &mut por_sino);
+                *estado = por_ramas.unir(por_sino);
+            }
+            Instruccion::Mientras { condicion, cuerpo, .. } => {
+                self.leer(*condicion, estado, linea);
+                self.ciclo(cuerpo, estado, false);
+            }
+            Instruccion::Repetir { condicion, cuerpo, .. } => {
+                self.leer(*condicion, estado, linea);
+                let ejecuta = valor_constante(&self.programa.arena, &self.programa.arena[*condicion])
+                    .is_some_and(|cantidad| cantidad > 0);
+                self.ciclo(cuerpo, estado, ejecuta);
+            }
+            // La variable del ciclo no es local de la sección; los límites se
+            // leen una vez, antes de la primera vuelta
+            Instruccion::Para { desde, hasta, cuerpo, .. } => {
+                self.leer(*desde, estado, linea);
+                self.leer(*hasta, estado, linea);
+                let arena = &self.programa.arena;
+                let ejecuta = valor_constante(arena, &arena[*desde])
+                    .zip(valor_constante(arena, &arena[*hasta]))
+                    .is_some_and(|(desde, hasta)| hasta >= desde);
+                self.ciclo(cuerpo, estado, ejecuta);
+            }
+            // El selector se lee una vez; los valores de los casos son constantes
+            Instruccion::Segun { selector, casos, sino, .. } => {
+                self.leer(*selector, estado, linea);
+                let mut por_ramas = estado.clone();
+                self.bloque(sino, &mut por_ramas);
+                for caso in casos {
+                    let mut por_caso = estado.clone();
+                    self.bloque(&caso.cuerpo, &mut por_caso);
+                    por_ramas = por_ramas.unir(por_caso);
+                }
+                *estado = por_ramas;
+            }
+            // Lo que no se pudo parsear pudo haberle asignado un valor a
+            // cualquier variable
+            Instruccion::Error { .. } => {
+                for &variable in &self.locales {
+                    estado.asignar(variable);
+                }
+            }
+        }
+    }
+
+    // Un ciclo puede no ejecutar su cuerpo, salvo un `repetir` con cantidad
+    // constante positiva. Desde la segunda vuelta el cuerpo ve lo que asignó
+    // en la anterior, así que eso ya cuenta como posiblemente asignado.
+    fn ciclo(&mut self, cuerpo: &[InstrId], estado: &mut Asignadas, ejecuta: bool) {
+        let mut vuelta = estado.clone();
+        vuelta.posibles.extend(self.asignadas_en(cuerpo));
+        self.bloque(cuerpo, &mut vuelta);
+        if ejecuta {
+            *estado = vuelta;
+        } else {
+            *estado = estado.clone().unir(vuelta);
+        }
+    }
+
+    fn llamada(&mut self, nombre: Symbol, argumentos: &[ExprId], estado: &mut Asignadas, linea: usize) {
+        // Los parámetros E se leen, los ES se leen y se asignan al volver, y
+        // los S sólo se asignan
+        if let Some(proceso) = self.procesos.get(&nombre) {
+            let mut salidas = Vec::new();
+            for (parametro, argumento) in proceso.parametros.iter().zip(argumentos) {
+                if parametro.tipo != "S" {
+                    self.leer(*argumento, estado, linea);
+                }
+                if parametro.tipo != "E"
+                    && let Expresion::Identificador(variable) = &self.programa.arena[*argumento]
+                {
+                    salidas.push(*variable);
+                }
+            }
+            for variable in salidas {
+                estado.asignar(variable);
+            }
+            return;
+        }
+
+        // Leer, Random y RecibirMensaje guardan su resultado en el primer argumento
+        let (destino, entradas) = match argumentos.split_first() {
+            Some((primero, resto)) if es_accion_con_salida(self.programa.simbolos.resolver(nombre)) => {
+                (Some(*primero), resto)
+            }
+            _ => (None, argumentos),
+        };
+        for argumento in entradas {
+            self.leer(*argumento, estado, linea);
+        }
+        if let Some(destino) = destino
+            && let Expresion::Identificador(variable) = &self.programa.arena[destino]
+        {
+            estado.asignar(*variable);
+        }
+    }
+
+    fn leer(&mut self, expresion: ExprId, estado: &Asignadas, linea: usize) {
+        match &self.programa.arena[expresion] {
+            Expresion::Identificador(variable) => {
+                if !self.locales.contains(variable) || estado.seguras.contains(variable) || !self.informadas.insert(*variable) {
+                    return;
+                }
+                let codigo = if estado.posibles.contains(variable) { Codigo::PosiblementeSinInicializar } else { Codigo::SinInicializar };
+                let mensaje = Mensaje::new(codigo).con(self.programa.simbolos.resolver(*variable)).con(self.contexto).con(linea);
+                self.advertencias.push(Hallazgo { mensaje, linea, archivo: self.archivo });
+            }
+            Expresion::Binaria { izquierda, derecha, .. } => {
+                self.leer(*izquierda, estado, linea);
+                self.leer(*derecha, estado, linea);
+            }
+            Expresion::Negacion(operando) => self.leer(*operando, estado, linea),
+            // Un proceso que devuelve un valor sólo tiene parámetros E
+            Expresion::Llamada { argumentos, .. } => {
+                for argumento in argumentos {
+                    self.leer(*argumento, estado, linea);
+                }
+            }
+            Expresion::Indice { indice, .. } => self.leer(*indice, estado, linea),
+            Expresion::Sensor { .. } | Expresion::Numero(_) | Expresion::Booleano(_) | Expresion::Cadena(_) | Expresion::Todos => {}
+        }
+    }
+
+    // Variables que se asignan en algún punto del bloque, incluidos los anidados
+    fn asignadas_en(&self, instrucciones: &[InstrId]) -> HashSet<Symbol> {
+        let mut estado = Asignadas::default();
+        let mut flujo = Flujo {
+            programa: self.programa,
+            procesos: self.procesos,
+            locales: HashSet::new(),
+            informadas: HashSet::new(),
+            advertencias: &mut Vec::new(),
+            contexto: self.contexto,
+            archivo: self.archivo,
+        };
+        flujo.bloque(instrucciones, &mut estado);
+        estado.posibles
+    }
+}
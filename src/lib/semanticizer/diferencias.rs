@@ -0,0 +1,251 @@
+use std::fmt::Write;
+use crate::parser::ast::{bloques_iguales, expresiones_iguales};
+use crate::parser::processor::{Proceso, Program, Robot, Variable};
+use super::consultas::Clase;
+use super::metricas::Metricas;
+
+// Parte de una declaración que cambia de una versión a la otra
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aspecto {
+    Parametros,
+    Retorno,
+    Variables,
+    // Contratos del bloque `mensajes` de un robot
+    Mensajes,
+    // El cuerpo, comparado por estructura y no por texto
+    Instrucciones,
+    // Tipo de un área o de un robot instanciado
+    Tipo,
+    Coordenadas,
+    Valor,
+}
+
+impl Aspecto {
+    fn nombre(self) -> &'static str {
+        match self {
+            Aspecto::Parametros => "parámetros",
+            Aspecto::Retorno => "tipo de retorno",
+            Aspecto::Variables => "variables",
+            Aspecto::Mensajes => "mensajes",
+            Aspecto::Instrucciones => "instrucciones",
+            Aspecto::Tipo => "tipo",
+            Aspecto::Coordenadas => "coordenadas",
+            Aspecto::Valor => "valor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cambio {
+    Agregada,
+    Eliminada,
+    Modificada(Vec<Aspecto>),
+}
+
+// Una declaración global que no está igual en las dos versiones
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diferencia {
+    pub nombre: String,
+    // `Constante`, `Area`, `Proceso`, `TipoRobot` o `Robot`
+    pub clase: Clase,
+    pub cambio: Cambio,
+    // Instrucciones antes y después contando las anidadas, 0 en la versión
+    // donde no está; sólo de los procesos y los tipos de robot
+    pub instrucciones: Option<(usize, usize)>,
+}
+
+// Comparación de dos versiones de un programa por sus declaraciones y no
+// por su texto, para revisar una entrega corregida: mover una sección,
+// cambiar la indentación o los comentarios no es una diferencia.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diferencias {
+    // Primero las constantes, después las áreas, los procesos, los tipos de
+    // robot y los robots; en cada grupo en el orden del programa viejo y al
+    // final las agregadas
+    pub declaraciones: Vec<Diferencia>,
+    // Si cambian las asignaciones de áreas o las posiciones iniciales del
+    // cuerpo del programa
+    pub principal: bool,
+}
+
+impl Diferencias {
+    pub fn new(viejo: &Program, nuevo: &Program) -> Self {
+        let mut declaraciones = Vec::new();
+
+        comparar(&mut declaraciones, Clase::Constante, &viejo.constantes, &nuevo.constantes, |constante| &constante.nombre, |a, b| {
+            if a.valor != b.valor { vec![Aspecto::Valor] } else { Vec::new() }
+        });
+        comparar(&mut declaraciones, Clase::Area, &viejo.areas, &nuevo.areas, |area| &area.nombre, |a, b| {
+            let mut aspectos = Vec::new();
+            if a.tipo != b.tipo {
+                aspectos.push(Aspecto::Tipo);
+            }
+            if a.coordenadas != b.coordenadas {
+                aspectos.push(Aspecto::Coordenadas);
+            }
+            aspectos
+        });
+        comparar(&mut declaraciones, Clase::Proceso, &viejo.procesos, &nuevo.procesos, |proceso| &proceso.nombre, |a, b| {
+            let mut aspectos = Vec::new();
+            let parametros = |proceso: &Proceso| -> Vec<(String, String, String)> {
+                proceso.parametros.iter()
+                    .map(|parametro| (parametro.tipo.clone(), parametro.nombre.clone(), parametro.tipo_dato.clone()))
+                    .collect()
+            };
+            if parametros(a) != parametros(b) {
+                aspectos.push(Aspecto::Parametros);
+            }
+            if a.retorno != b.retorno {
+                aspectos.push(Aspecto::Retorno);
+            }
+            if !mismas_variables(&a.variables, &b.variables) {
+                aspectos.push(Aspecto::Variables);
+            }
+            if !bloques_iguales(viejo, &a.instrucciones, nuevo, &b.instrucciones) {
+                aspectos.push(Aspecto::Instrucciones);
+            }
+            aspectos
+        });
+        comparar(&mut declaraciones, Clase::TipoRobot, &viejo.robots_definidos, &nuevo.robots_definidos, |robot| &robot.nombre, |a, b| {
+            let mut aspectos = Vec::new();
+            if !mismas_variables(&a.variables, &b.variables) {
+                aspectos.push(Aspecto::Variables);
+            }
+            let contratos = |robot: &Robot| -> Vec<_> {
+                robot.mensajes.iter()
+                    .map(|contrato| (contrato.sentido, contrato.tipo_dato.clone(), contrato.robot.clone()))
+                    .collect()
+            };
+            if contratos(a) != contratos(b) {
+                aspectos.push(Aspecto::Mensajes);
+            }
+            if !bloques_iguales(viejo, &a.instrucciones, nuevo, &b.instrucciones) {
+                aspectos.push(Aspecto::Instrucciones);
+            }
+            aspectos
+        });
+        comparar(&mut declaraciones, Clase::Robot, &viejo.robots_instanciados, &nuevo.robots_instanciados, |robot| &robot.nombre, |a, b| {
+            if a.tipo != b.tipo { vec![Aspecto::Tipo] } else { Vec::new() }
+        });
+
+        let (metricas_viejo, metricas_nuevo) = (Metricas::new(viejo), Metricas::new(nuevo));
+        let cantidad = |metricas: &Metricas, diferencia: &Diferencia| {
+            metricas.secciones.iter()
+                .find(|seccion| seccion.clase == diferencia.clase && seccion.nombre == diferencia.nombre)
+                .map_or(0, |seccion| seccion.instrucciones)
+        };
+        for diferencia in &mut declaraciones {
+            if matches!(diferencia.clase, Clase::Proceso | Clase::TipoRobot) {
+                diferencia.instrucciones = Some((cantidad(&metricas_viejo, diferencia), cantidad(&metricas_nuevo, diferencia)));
+            }
+        }
+
+        let asignaciones = viejo.asignaciones_areas.len() == nuevo.asignaciones_areas.len()
+            && viejo.asignaciones_areas.iter().zip(&nuevo.asignaciones_areas).all(|(a, b)| {
+                expresiones_iguales(viejo, a.robot, nuevo, b.robot) && expresiones_iguales(viejo, a.area, nuevo, b.area)
+            });
+        let inicializaciones = viejo.inicializaciones.len() == nuevo.inicializaciones.len()
+            && viejo.inicializaciones.iter().zip(&nuevo.inicializaciones).all(|(a, b)| {
+                expresiones_iguales(viejo, a.robot, nuevo, b.robot)
+                    && expresiones_iguales(viejo, a.pos_x, nuevo, b.pos_x)
+                    && expresiones_iguales(viejo, a.pos_y, nuevo, b.pos_y)
+            });
+
+        Self { declaraciones, principal: !(asignaciones && inicializaciones) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declaraciones.is_empty() && !self.principal
+    }
+
+    // Una línea por diferencia: `+` lo agregado y `-` lo eliminado, con sus
+    // instrucciones, y `~` lo que cambia, con las partes que cambian y la
+    // cantidad de instrucciones antes y después si no es la misma
+    pub fn a_texto(&self) -> String {
+        if self.is_empty() {
+            return "Sin diferencias estructurales\n".to_string();
+        }
+        let mut texto = String::new();
+        for diferencia in &self.declaraciones {
+            let marca = match diferencia.cambio {
+                Cambio::Agregada => '+',
+                Cambio::Eliminada => '-',
+                Cambio::Modificada(_) => '~',
+            };
+            let _ = write!(texto, "{} {} {}", marca, nombre_clase(diferencia.clase), diferencia.nombre);
+            if let Cambio::Modificada(aspectos) = &diferencia.cambio {
+                let aspectos: Vec<&str> = aspectos.iter().map(|aspecto| aspecto.nombre()).collect();
+                let _ = write!(texto, ": {}", aspectos.join(", "));
+            }
+            match (&diferencia.cambio, diferencia.instrucciones) {
+                (Cambio::Agregada, Some((_, cantidad))) | (Cambio::Eliminada, Some((cantidad, _))) => {
+                    let plural = if cantidad == 1 { "instrucción" } else { "instrucciones" };
+                    let _ = write!(texto, " ({} {})", cantidad, plural);
+                }
+                (Cambio::Modificada(_), Some((antes, despues))) if antes != despues => {
+                    let _ = write!(texto, " ({} → {})", antes, despues);
+                }
+                _ => {}
+            }
+            texto.push('\n');
+        }
+        if self.principal {
+            texto.push_str("~ cuerpo del programa: cambian las asignaciones de áreas o las posiciones iniciales\n");
+        }
+        texto
+    }
+}
+
+// Agrega las diferencias entre dos listas de declaraciones de la misma clase,
+// emparejadas por nombre
+fn comparar<T>(
+    diferencias: &mut Vec<Diferencia>,
+    clase: Clase,
+    viejas: &[T],
+    nuevas: &[T],
+    nombre: impl Fn(&T) -> &String,
+    aspectos: impl Fn(&T, &T) -> Vec<Aspecto>,
+) {
+    let diferencia = |declaracion: &T, cambio| Diferencia {
+        nombre: nombre(declaracion).clone(),
+        clase,
+        cambio,
+        instrucciones: None,
+    };
+    for vieja in viejas {
+        match nuevas.iter().find(|nueva| nombre(nueva) == nombre(vieja)) {
+            None => diferencias.push(diferencia(vieja, Cambio::Eliminada)),
+            Some(nueva) => {
+                let aspectos = aspectos(vieja, nueva);
+                if !aspectos.is_empty() {
+                    diferencias.push(diferencia(vieja, Cambio::Modificada(aspectos)));
+                }
+            }
+        }
+    }
+    for nueva in nuevas {
+        if !viejas.iter().any(|vieja| nombre(vieja) == nombre(nueva)) {
+            diferencias.push(diferencia(nueva, Cambio::Agregada));
+        }
+    }
+}
+
+// Mismas variables en el mismo orden y con el mismo tipo
+fn mismas_variables(a: &[Variable], b: &[Variable]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.nombre == b.nombre && a.tipo_declarado() == b.tipo_declarado())
+}
+
+fn nombre_clase(clase: Clase) -> &'static str {
+    match clase {
+        Clase::Constante => "constante",
+        Clase::Area => "área",
+        Clase::Proceso => "proceso",
+        Clase::TipoRobot => "robot",
+        // Los robots instanciados se declaran en `variables`
+        Clase::Robot | Clase::Parametro | Clase::Variable => "variable",
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use crate::interner::{Interner, Symbol};
+use crate::mensajes::{Codigo, Mensaje};
+use crate::parser::arena::{Arena, ExprId};
+use crate::parser::ast::{recorrer_instruccion, Visitor};
+use crate::parser::processor::{tipo_sensor, Expresion, Instruccion, Program};
+use super::symbol_table::{Categoria, TablaSimbolos};
+
+// Tipo de dato de un valor del lenguaje
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tipo {
+    Numero,
+    Booleano,
+    // Sólo los textos entre comillas, como contenido de un mensaje
+    Cadena,
+}
+
+impl Tipo {
+    // El tipo como se escribe en una declaración
+    pub fn desde_texto(texto: &str) -> Option<Self> {
+        match texto {
+            "numero" => Some(Tipo::Numero),
+            "booleano" => Some(Tipo::Booleano),
+            "cadena" => Some(Tipo::Cadena),
+            _ => None,
+        }
+    }
+
+    pub fn nombre(self) -> &'static str {
+        match self {
+            Tipo::Numero => "numero",
+            Tipo::Booleano => "booleano",
+            Tipo::Cadena => "cadena",
+        }
+    }
+}
+
+impl fmt::Display for Tipo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.nombre())
+    }
+}
+
+impl From<Tipo> for Mensaje {
+    fn from(tipo: Tipo) -> Self {
+        Mensaje::from(tipo.nombre())
+    }
+}
+
+// A qué declaración se refiere un identificador en el lugar donde aparece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Referencia {
+    // Parámetro o variable de la sección; sin tipo si se declaró con uno
+    // que el lenguaje no tiene
+    Variable { tipo: Option<Tipo> },
+    // Proceso, área, robot o constante del programa
+    Global(Categoria),
+}
+
+// Representación intermedia tipada: el significado de cada expresión del
+// programa ya resuelto, para que el análisis, los contratos de mensajes y
+// las herramientas lo consulten en lugar de volver a deducirlo de los
+// nombres. Se guarda al costado del arena, indexada por `ExprId`, así el AST
+// no se copia. Cada identificador tiene la declaración que ve en su sección
+// (las variables ocultan a los nombres globales) y cada expresión su tipo, o
+// el primer error de tipos que contiene.
+#[derive(Debug, Clone, Default)]
+pub struct Hir {
+    // `None` si depende de algo sin tipo, como un nombre no declarado o `*`,
+    // o si contiene un error de tipos
+    tipos: Vec<Option<Tipo>>,
+    referencias: Vec<Option<Referencia>>,
+    errores: HashMap<ExprId, Mensaje>,
+}
+
+impl Hir {
+    pub fn new(programa: &Program) -> Self {
+        let cantidad = programa.arena.cantidad_expresiones();
+        let mut hir = Self {
+            tipos: vec![None; cantidad],
+            referencias: vec![None; cantidad],
+            errores: HashMap::new(),
+        };
+        let tabla = TablaSimbolos::del_programa(programa);
+        let retornos: HashMap<Symbol, Option<Tipo>> = programa.procesos.iter()
+            .filter_map(|proceso| {
+                let retorno = proceso.retorno.as_deref()?;
+                Some((programa.simbolos.get(&proceso.nombre)?, Tipo::desde_texto(retorno)))
+            })
+            .collect();
+
+        // Dentro de un proceso que devuelve un valor, su nombre es la
+        // variable donde se guarda. Cada local indica si es un arreglo.
+        let secciones = programa.procesos.iter()
+            .map(|proceso| {
+                let locales = proceso.parametros.iter()
+                    .map(|param| (&param.nombre, &param.tipo_dato, false))
+                    .chain(proceso.variables.iter().map(|var| (&var.nombre, &var.tipo_dato, var.longitud.is_some())))
+                    .chain(proceso.retorno.iter().map(|retorno| (&proceso.nombre, retorno, false)));
+                (locales.collect::<Vec<_>>(), &proceso.instrucciones)
+            })
+            .chain(programa.robots_definidos.iter().map(|robot| {
+                let locales = robot.variables.iter().map(|var| (&var.nombre, &var.tipo_dato, var.longitud.is_some()));
+                (locales.collect(), &robot.instrucciones)
+            }));
+        for (locales, instrucciones) in secciones {
+            // Las variables que el programa nunca usa no tienen símbolo
+            let locales: Vec<_> = locales.into_iter()
+                .filter_map(|(nombre, tipo, arreglo)| Some((programa.simbolos.get(nombre)?, Tipo::desde_texto(tipo), arreglo)))
+                .collect();
+            let mut tipador = Tipador {
+                hir: &mut hir,
+                arena: &programa.arena,
+                simbolos: &programa.simbolos,
+                tabla: &tabla,
+                retornos: &retornos,
+                variables: locales.iter().map(|(simbolo, tipo, _)| (*simbolo, *tipo)).collect(),
+                arreglos: locales.iter().filter(|(_, _, arreglo)| *arreglo).map(|(simbolo, _, _)| *simbolo).collect(),
+            };
+            tipador.visitar_bloque(instrucciones);
+        }
+
+        // En el programa principal y en los valores de las constantes no hay
+        // variables: sólo robots, áreas, constantes y números
+        let raices = programa.asignaciones_areas.iter()
+            .flat_map(|asignacion| [asignacion.robot, asignacion.area])
+            .chain(programa.inicializaciones.iter()
+                .flat_map(|inicializacion| [inicializacion.robot, inicializacion.pos_x, inicializacion.pos_y]))
+            .chain(programa.constantes.iter().map(|constante| constante.expresion));
+        let mut tipador = Tipador {
+            hir: &mut hir,
+            arena: &programa.arena,
+            simbolos: &programa.simbolos,
+            tabla: &tabla,
+            retornos: &retornos,
+            variables: HashMap::new(),
+            arreglos: HashSet::new(),
+        };
+        for raiz in raices {
+            let _ = tipador.tipar(raiz);
+        }
+
+        hir
+    }
+
+    pub fn tipo(&self, expresion: ExprId) -> Option<Tipo> {
+        self.tipos.get(expresion.indice()).copied().flatten()
+    }
+
+    pub fn referencia(&self, expresion: ExprId) -> Option<Referencia> {
+        self.referencias.get(expresion.indice()).copied().flatten()
+    }
+
+    // Primer error de tipos dentro de la expresión, de izquierda a derecha
+    pub fn error_de_tipos(&self, expresion: ExprId) -> Option<&Mensaje> {
+        self.errores.get(&expresion)
+    }
+
+    // Tipo de la expresión o su primer error de tipos. `Ok(None)` si depende
+    // de algo sin tipo conocido, como un nombre no declarado, que se informa
+    // aparte.
+    pub fn tipado(&self, expresion: ExprId) -> Result<Option<Tipo>, Mensaje> {
+        match self.error_de_tipos(expresion) {
+            Some(mensaje) => Err(mensaje.clone()),
+            None => Ok(self.tipo(expresion)),
+        }
+    }
+}
+
+// Recorre una sección resolviendo los nombres y los tipos de cada expresión
+// que aparece en sus instrucciones
+struct Tipador<'a> {
+    hir: &'a mut Hir,
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    tabla: &'a TablaSimbolos,
+    // Tipo del valor de cada proceso que devuelve uno
+    retornos: &'a HashMap<Symbol, Option<Tipo>>,
+    // De un arreglo se guarda el tipo de sus elementos, que es el de
+    // `nombre[i]`; el nombre solo no tiene tipo
+    variables: HashMap<Symbol, Option<Tipo>>,
+    arreglos: HashSet<Symbol>,
+}
+
+impl<'a> Visitor<'a> for Tipador<'a> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+        let raices = match instruccion {
+            Instruccion::Elemental { .. } | Instruccion::Error { .. } => Vec::new(),
+            Instruccion::Asignacion { indice, valor, .. } => indice.iter().copied().chain([*valor]).collect(),
+            Instruccion::LlamadaFuncion { argumentos, .. } => argumentos.clone(),
+            Instruccion::Si { condicion, alternativas, .. } => std::iter::once(*condicion)
+                .chain(alternativas.iter().map(|alternativa| alternativa.condicion))
+                .collect(),
+            Instruccion::Mientras { condicion, .. }
+            | Instruccion::Repetir { condicion, .. } => vec![*condicion],
+            Instruccion::Para { desde, hasta, .. } => vec![*desde, *hasta],
+            Instruccion::Segun { selector, casos, .. } => std::iter::once(*selector)
+                .chain(casos.iter().map(|caso| caso.valor))
+                .collect(),
+        };
+        for raiz in raices {
+            let _ = self.tipar(raiz);
+        }
+
+        // La variable de un `para` es un número visible sólo en su cuerpo
+        if let Instruccion::Para { variable, cuerpo, .. } = instruccion {
+            let anterior = self.variables.insert(*variable, Some(Tipo::Numero));
+            self.visitar_bloque(cuerpo);
+            match anterior {
+                Some(tipo) => self.variables.insert(*variable, tipo),
+                None => self.variables.remove(variable),
+            };
+            return;
+        }
+        recorrer_instruccion(self, instruccion);
+    }
+}
+
+impl Tipador<'_> {
+    // Las variables de la sección ocultan a los nombres globales
+    fn referencia(&self, nombre: Symbol) -> Option<Referencia> {
+        match self.variables.get(&nombre) {
+            Some(tipo) => Some(Referencia::Variable { tipo: *tipo }),
+            None => self.tabla.categoria(nombre).map(Referencia::Global),
+        }
+    }
+
+    // Resuelve la expresión y sus subexpresiones y guarda el resultado de cada una
+    fn tipar(&mut self, id: ExprId) -> Result<Option<Tipo>, Mensaje> {
+        let resultado = match &self.arena[id] {
+            Expresion::Identificador(nombre) => {
+                let referencia = self.referencia(*nombre);
+                self.hir.referencias[id.indice()] = referencia;
+                match referencia {
+                    Some(Referencia::Variable { .. }) if self.arreglos.contains(nombre) => Ok(None),
+                    Some(Referencia::Variable { tipo }) => Ok(tipo),
+                    // Las constantes sólo pueden tener valores numéricos
+                    Some(Referencia::Global(Categoria::Constante)) => Ok(Some(Tipo::Numero)),
+                    _ => Ok(None),
+                }
+            }
+            Expresion::Sensor { nombre } => Ok(tipo_sensor(self.simbolos.resolver(*nombre)).and_then(Tipo::desde_texto)),
+            Expresion::Numero(_) => Ok(Some(Tipo::Numero)),
+            Expresion::Booleano(_) => Ok(Some(Tipo::Booleano)),
+            Expresion::Cadena(_) => Ok(Some(Tipo::Cadena)),
+            Expresion::Todos => Ok(None),
+            Expresion::Binaria { izquierda, operador, derecha } => {
+                // Se resuelven los dos lados aunque el izquierdo tenga un
+                // error, para que todas las subexpresiones queden resueltas
+                let tipo_izq = self.tipar(*izquierda);
+                let tipo_der = self.tipar(*derecha);
+                tipo_izq.and_then(|izquierda| tipo_binaria(operador, izquierda, tipo_der?))
+            }
+            Expresion::Negacion(operando) => match self.tipar(*operando)? {
+                Some(tipo) if tipo != Tipo::Booleano => Err(Mensaje::new(Codigo::NegacionDeTipo).con(tipo)),
+                _ => Ok(Some(Tipo::Booleano)),
+            },
+            // Sin tipo si el nombre no es un proceso que devuelve un valor
+            Expresion::Llamada { nombre, argumentos } => {
+                self.hir.referencias[id.indice()] = self.tabla.categoria(*nombre).map(Referencia::Global);
+                let mut resultado = Ok(self.retornos.get(nombre).copied().flatten());
+                for argumento in argumentos {
+                    if let Err(mensaje) = self.tipar(*argumento)
+                        && resultado.is_ok()
+                    {
+                        resultado = Err(mensaje);
+                    }
+                }
+                resultado
+            }
+            // Sin tipo si el nombre no es un arreglo
+            Expresion::Indice { arreglo, indice } => {
+                let referencia = self.referencia(*arreglo);
+                self.hir.referencias[id.indice()] = referencia;
+                match self.tipar(*indice) {
+                    Ok(Some(tipo)) if tipo != Tipo::Numero => Err(
+                        Mensaje::new(Codigo::IndiceDeTipo).con(self.simbolos.resolver(*arreglo)).con(tipo)
+                    ),
+                    Err(mensaje) => Err(mensaje),
+                    _ => match referencia {
+                        Some(Referencia::Variable { tipo }) if self.arreglos.contains(arreglo) => Ok(tipo),
+                        _ => Ok(None),
+                    },
+                }
+            }
+        };
+
+        match &resultado {
+            Ok(tipo) => self.hir.tipos[id.indice()] = *tipo,
+            Err(mensaje) => {
+                self.hir.errores.insert(id, mensaje.clone());
+            }
+        }
+        resultado
+    }
+}
+
+fn tipo_binaria(operador: &str, izquierda: Option<Tipo>, derecha: Option<Tipo>) -> Result<Option<Tipo>, Mensaje> {
+    let (Some(izquierda), Some(derecha)) = (izquierda, derecha) else {
+        return Ok(None);
+    };
+
+    // Tipo que exige el operador a sus operandos (`None`: ambos del mismo
+    // tipo) y tipo del resultado
+    let (operandos, resultado) = match operador {
+        "+" | "-" | "*" | "/" => (Some(Tipo::Numero), Tipo::Numero),
+        "<" | "<=" | ">" | ">=" => (Some(Tipo::Numero), Tipo::Booleano),
+        "==" | "<>" => (None, Tipo::Booleano),
+        "&" | "|" => (Some(Tipo::Booleano), Tipo::Booleano),
+        _ => return Ok(None),
+    };
+
+    match operandos {
+        Some(esperado) if izquierda != esperado || derecha != esperado => Err(
+            Mensaje::new(Codigo::OperandosDeTipo).con(operador).con(esperado).con(izquierda).con(derecha)
+        ),
+        None if izquierda != derecha => Err(
+            Mensaje::new(Codigo::ComparacionDeTipos).con(operador).con(izquierda).con(derecha)
+        ),
+        _ => Ok(Some(resultado)),
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::interner::Symbol;
+use crate::interpreter::reporte::cadena_json;
+use crate::parser::arena::Arena;
+use crate::parser::ast::{recorrer_expresion, recorrer_instruccion, Visitor};
+use crate::parser::processor::{Expresion, Instruccion, Program};
+use crate::source::{FileId, SourceMap};
+use super::consultas::{Clase, Consultas, Declarado};
+use super::dataflow;
+
+// Una declaración del programa con lo que hace falta para revisar cómo se
+// usa: si recibe un valor antes de usarse y cuántas veces se la nombra
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilaSimbolo {
+    pub declarado: Declarado,
+    // Los parámetros E y ES llegan con valor; las variables y los parámetros
+    // S, si la sección les asigna algo; un robot, si el programa lo inicia
+    pub inicializado: bool,
+    // Apariciones del nombre en las instrucciones. De un proceso, las
+    // llamadas; de un tipo de robot, los robots de ese tipo.
+    pub usos: usize,
+}
+
+// Tabla de todos los símbolos del programa, para exportarla con
+// `--emit symbols`. Los docentes la usan para revisar de un vistazo si las
+// variables están declaradas donde corresponde.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inventario {
+    pub simbolos: Vec<FilaSimbolo>,
+}
+
+impl Inventario {
+    pub fn new(programa: &Program) -> Self {
+        // Los locales se identifican por dónde se declararon: dos secciones
+        // pueden tener variables con el mismo nombre
+        let mut locales: HashMap<(FileId, usize, &str), (bool, usize)> = HashMap::new();
+        let mut globales = Referencias::new(&programa.arena);
+
+        let secciones = programa.procesos.iter()
+            .map(|proceso| {
+                let parametros = proceso.parametros.iter()
+                    .map(|parametro| (parametro.nombre.as_str(), parametro.linea, parametro.tipo != "S"));
+                let variables = proceso.variables.iter().map(|variable| (variable.nombre.as_str(), variable.linea, false));
+                (proceso.archivo, &proceso.instrucciones, parametros.chain(variables).collect::<Vec<_>>())
+            })
+            .chain(programa.robots_definidos.iter().map(|robot| {
+                let variables = robot.variables.iter().map(|variable| (variable.nombre.as_str(), variable.linea, false));
+                (robot.archivo, &robot.instrucciones, variables.collect())
+            }));
+        for (archivo, instrucciones, declarados) in secciones {
+            let asignadas = dataflow::asignadas(programa, instrucciones);
+            let mut referencias = Referencias::new(&programa.arena);
+            referencias.visitar_bloque(instrucciones);
+
+            let mut propios = Vec::new();
+            for (nombre, linea, con_valor) in declarados {
+                let simbolo = programa.simbolos.get(nombre);
+                let inicializado = con_valor || simbolo.is_some_and(|simbolo| asignadas.contains(&simbolo));
+                let usos = simbolo.map_or(0, |simbolo| referencias.usos(simbolo));
+                locales.insert((archivo, linea, nombre), (inicializado, usos));
+                propios.extend(simbolo);
+            }
+            // Lo que la sección no declara es un nombre global
+            for (simbolo, usos) in referencias.usos {
+                if !propios.contains(&simbolo) {
+                    *globales.usos.entry(simbolo).or_default() += usos;
+                }
+            }
+        }
+        for asignacion in &programa.asignaciones_areas {
+            globales.visitar_expresion(&programa.arena[asignacion.robot]);
+            globales.visitar_expresion(&programa.arena[asignacion.area]);
+        }
+        for inicializacion in &programa.inicializaciones {
+            globales.visitar_expresion(&programa.arena[inicializacion.robot]);
+            globales.visitar_expresion(&programa.arena[inicializacion.pos_x]);
+            globales.visitar_expresion(&programa.arena[inicializacion.pos_y]);
+        }
+        // Una constante puede usar las anteriores
+        for constante in &programa.constantes {
+            globales.visitar_expresion(&programa.arena[constante.expresion]);
+        }
+
+        let iniciados: Vec<Symbol> = programa.inicializaciones.iter()
+            .filter_map(|inicializacion| match &programa.arena[inicializacion.robot] {
+                Expresion::Identificador(robot) => Some(*robot),
+                _ => None,
+            })
+            .collect();
+
+        let simbolos = Consultas::new(programa).declaraciones().into_iter()
+            .map(|declarado| {
+                let simbolo = programa.simbolos.get(&declarado.nombre);
+                let (inicializado, usos) = match declarado.clase {
+                    Clase::Parametro | Clase::Variable => locales
+                        .get(&(declarado.archivo, declarado.linea, declarado.nombre.as_str()))
+                        .copied()
+                        .unwrap_or_default(),
+                    Clase::TipoRobot => {
+                        let robots = programa.robots_instanciados.iter()
+                            .filter(|robot| robot.tipo == declarado.nombre)
+                            .count();
+                        (true, robots)
+                    }
+                    Clase::Robot => (
+                        simbolo.is_some_and(|simbolo| iniciados.contains(&simbolo)),
+                        simbolo.map_or(0, |simbolo| globales.usos(simbolo)),
+                    ),
+                    Clase::Proceso | Clase::Area | Clase::Constante => (true, simbolo.map_or(0, |simbolo| globales.usos(simbolo))),
+                };
+                FilaSimbolo { declarado, inicializado, usos }
+            })
+            .collect();
+
+        Self { simbolos }
+    }
+
+    // Una fila por símbolo con encabezado. El ámbito es la sección de un
+    // parámetro o variable, o `global`.
+    pub fn a_csv(&self, fuentes: &SourceMap) -> String {
+        let mut csv = String::from("nombre,clase,ambito,tipo,archivo,linea,inicializado,usos\n");
+        for fila in &self.simbolos {
+            let declarado = &fila.declarado;
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                campo_csv(&declarado.nombre),
+                nombre_clase(declarado.clase),
+                campo_csv(declarado.seccion.as_deref().unwrap_or(AMBITO_GLOBAL)),
+                campo_csv(declarado.tipo.as_deref().unwrap_or("")),
+                campo_csv(fuentes.nombre(declarado.archivo).unwrap_or("")),
+                declarado.linea,
+                fila.inicializado,
+                fila.usos,
+            );
+        }
+        csv
+    }
+
+    pub fn a_json(&self, fuentes: &SourceMap) -> String {
+        let mut json = String::from("[");
+        for (i, fila) in self.simbolos.iter().enumerate() {
+            let declarado = &fila.declarado;
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let _ = write!(
+                json,
+                "  {{\"nombre\": {}, \"clase\": \"{}\", \"ambito\": {}, \"tipo\": {}, \"archivo\": {}, \"linea\": {}, \"inicializado\": {}, \"usos\": {}}}",
+                cadena_json(&declarado.nombre),
+                nombre_clase(declarado.clase),
+                cadena_json(declarado.seccion.as_deref().unwrap_or(AMBITO_GLOBAL)),
+                declarado.tipo.as_deref().map_or("null".to_string(), cadena_json),
+                fuentes.nombre(declarado.archivo).map_or("null".to_string(), cadena_json),
+                declarado.linea,
+                fila.inicializado,
+                fila.usos,
+            );
+        }
+        json.push_str(if self.simbolos.is_empty() { "]\n" } else { "\n]\n" });
+        json
+    }
+}
+
+const AMBITO_GLOBAL: &str = "global";
+
+fn nombre_clase(clase: Clase) -> &'static str {
+    match clase {
+        Clase::Proceso => "proceso",
+        Clase::Parametro => "parametro",
+        Clase::Variable => "variable",
+        Clase::Area => "area",
+        Clase::TipoRobot => "tipo_robot",
+        Clase::Robot => "robot",
+        Clase::Constante => "constante",
+    }
+}
+
+// Entre comillas si tiene comas, comillas o saltos de línea (RFC 4180)
+pub(crate) fn campo_csv(texto: &str) -> String {
+    if texto.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", texto.replace('"', "\"\""))
+    } else {
+        texto.to_string()
+    }
+}
+
+// Cuenta las veces que se nombra cada símbolo: como variable, como destino
+// de una asignación o como proceso llamado
+struct Referencias<'ast> {
+    arena: &'ast Arena,
+    usos: HashMap<Symbol, usize>,
+}
+
+impl<'ast> Referencias<'ast> {
+    fn new(arena: &'ast Arena) -> Self {
+        Self { arena, usos: HashMap::new() }
+    }
+
+    fn usos(&self, simbolo: Symbol) -> usize {
+        self.usos.get(&simbolo).copied().unwrap_or(0)
+    }
+
+    fn contar(&mut self, simbolo: Symbol) {
+        *self.usos.entry(simbolo).or_default() += 1;
+    }
+}
+
+impl<'ast> Visitor<'ast> for Referencias<'ast> {
+    fn arena(&self) -> &'ast Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'ast Instruccion) {
+        match instruccion {
+            Instruccion::Asignacion { variable, .. } => self.contar(*variable),
+            Instruccion::LlamadaFuncion { nombre, .. } => self.contar(*nombre),
+            _ => {}
+        }
+        recorrer_instruccion(self, instruccion);
+    }
+
+    fn visitar_expresion(&mut self, expresion: &'ast Expresion) {
+        if let Expresion::Identificador(simbolo)
+        | Expresion::Llamada { nombre: simbolo, .. }
+        | Expresion::Indice { arreglo: simbolo, .. } = expresion
+        {
+            self.contar(*simbolo);
+        }
+        recorrer_expresion(self, expresion);
+    }
+}
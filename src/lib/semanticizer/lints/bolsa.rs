@@ -0,0 +1,364 @@
+use std::collections::HashSet;
+use crate::interner::Interner;
+use crate::mensajes::{Codigo, Mensaje};
+use crate::parser::arena::{Arena, ExprId, InstrId};
+use crate::parser::processor::{valor_constante, Expresion, Instruccion, Program};
+use crate::source::FileId;
+use super::super::signatures::firma_elemental;
+use super::reglas::secciones;
+use super::{ubicar, Hallazgo, LintRule};
+
+// `depositarFlor` o `depositarPapel` que se ejecutan con la bolsa vacía en
+// todos los caminos que llegan hasta ellos. Se sigue cuántas flores y
+// papeles puede tener la bolsa: un robot empieza sin nada, un proceso con lo
+// que le deje quien lo llama, y después de llamar a un proceso ya no se sabe.
+pub struct BolsaVacia;
+
+impl LintRule for BolsaVacia {
+    fn nombre(&self) -> &'static str {
+        "bolsa-vacia"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        let robots: HashSet<&str> = programa.robots_definidos.iter().map(|robot| robot.nombre.as_str()).collect();
+        for (nombre, instrucciones, archivo) in secciones(programa) {
+            let inicial = if robots.contains(nombre) { Bolsa::VACIA } else { Bolsa::DESCONOCIDA };
+            let mut flujo = Flujo {
+                arena: &programa.arena,
+                simbolos: &programa.simbolos,
+                informar: true,
+                informadas: HashSet::new(),
+                hallazgos,
+                contexto: nombre,
+                archivo,
+            };
+            flujo.bloque(instrucciones, Some(inicial));
+        }
+    }
+}
+
+// `tomarFlor` o `tomarPapel` dentro de un ciclo sin que el ciclo verifique
+// antes, con `HayFlorEnLaEsquina` o `HayPapelEnLaEsquina`, que la esquina
+// todavía tenga algo: en alguna vuelta puede quedar vacía. Una verificación
+// de afuera del ciclo no alcanza.
+pub struct TomarSinVerificar;
+
+impl LintRule for TomarSinVerificar {
+    fn nombre(&self) -> &'static str {
+        "tomar-sin-verificar"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        for (nombre, instrucciones, archivo) in secciones(programa) {
+            let mut revision = Revision { programa, hallazgos, contexto: nombre, archivo };
+            revision.bloque(instrucciones, false, Verificados::default());
+        }
+    }
+}
+
+// Cantidades posibles de un objeto en la bolsa; `max` es `None` si no tiene
+// límite conocido
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rango {
+    min: u32,
+    max: Option<u32>,
+}
+
+impl Rango {
+    fn tomar(self) -> Self {
+        Rango { min: self.min.saturating_add(1), max: self.max.map(|max| max.saturating_add(1)) }
+    }
+
+    fn depositar(self) -> Self {
+        Rango { min: self.min.saturating_sub(1), max: self.max.map(|max| max.saturating_sub(1)) }
+    }
+
+    fn vacio(self) -> bool {
+        self.max == Some(0)
+    }
+
+    fn unir(self, otro: Rango) -> Self {
+        Rango { min: self.min.min(otro.min), max: self.max.zip(otro.max).map(|(a, b)| a.max(b)) }
+    }
+
+    // Lo que creció de una vuelta a otra de un ciclo puede seguir creciendo,
+    // y lo que bajó, seguir bajando: se lleva al límite para que el ciclo se
+    // recorra una cantidad fija de veces
+    fn ensanchar(self, siguiente: Rango) -> Self {
+        Rango {
+            min: if siguiente.min < self.min { 0 } else { self.min },
+            max: if siguiente.max == self.max { self.max } else { None },
+        }
+    }
+
+    // Con la condición `HayFlorEnLaBolsa` verdadera o falsa; `None` si el
+    // rango no lo permite
+    fn con_alguno(self) -> Option<Self> {
+        (!self.vacio()).then_some(Rango { min: self.min.max(1), max: self.max })
+    }
+
+    fn sin_ninguno(self) -> Option<Self> {
+        (self.min == 0).then_some(Rango { min: 0, max: Some(0) })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bolsa {
+    flores: Rango,
+    papeles: Rango,
+}
+
+impl Bolsa {
+    const VACIA: Bolsa = Bolsa { flores: Rango { min: 0, max: Some(0) }, papeles: Rango { min: 0, max: Some(0) } };
+    const DESCONOCIDA: Bolsa = Bolsa { flores: Rango { min: 0, max: None }, papeles: Rango { min: 0, max: None } };
+
+    fn objeto(&mut self, flores: bool) -> &mut Rango {
+        if flores { &mut self.flores } else { &mut self.papeles }
+    }
+
+    fn unir(self, otra: Bolsa) -> Self {
+        Bolsa { flores: self.flores.unir(otra.flores), papeles: self.papeles.unir(otra.papeles) }
+    }
+
+    fn ensanchar(self, siguiente: Bolsa) -> Self {
+        Bolsa { flores: self.flores.ensanchar(siguiente.flores), papeles: self.papeles.ensanchar(siguiente.papeles) }
+    }
+}
+
+// Estado en un punto del código; `None` si ningún camino llega hasta ahí
+type Estado = Option<Bolsa>;
+
+fn unir(a: Estado, b: Estado) -> Estado {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.unir(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+struct Flujo<'a, 'h> {
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    // Falso mientras se busca el estado de la cabeza de un ciclo: las
+    // vueltas intermedias no ven todos los estados posibles
+    informar: bool,
+    // Cada instrucción se advierte una sola vez
+    informadas: HashSet<usize>,
+    hallazgos: &'h mut Vec<Hallazgo>,
+    contexto: &'a str,
+    archivo: FileId,
+}
+
+impl Flujo<'_, '_> {
+    fn bloque(&mut self, instrucciones: &[InstrId], mut estado: Estado) -> Estado {
+        for instruccion in self.arena.bloque(instrucciones) {
+            estado = self.instruccion(instruccion, estado);
+        }
+        estado
+    }
+
+    fn instruccion(&mut self, instruccion: &Instruccion, estado: Estado) -> Estado {
+        let mut bolsa = estado?;
+        match instruccion {
+            Instruccion::Elemental { .. } => Some(bolsa),
+            Instruccion::Asignacion { indice, valor, .. } => {
+                let llama = indice.is_some_and(|indice| self.llama(indice)) || self.llama(*valor);
+                Some(if llama { Bolsa::DESCONOCIDA } else { bolsa })
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, linea } => {
+                let nombre = self.simbolos.resolver(*nombre);
+                if firma_elemental(nombre).is_none() || argumentos.iter().any(|argumento| self.llama(*argumento)) {
+                    return Some(Bolsa::DESCONOCIDA);
+                }
+                match nombre {
+                    "tomarFlor" | "tomarPapel" => {
+                        let objeto = bolsa.objeto(nombre == "tomarFlor");
+                        *objeto = objeto.tomar();
+                    }
+                    "depositarFlor" | "depositarPapel" => {
+                        let objeto = bolsa.objeto(nombre == "depositarFlor");
+                        if objeto.vacio() {
+                            self.advertir(nombre, *linea);
+                        }
+                        *objeto = objeto.depositar();
+                    }
+                    _ => {}
+                }
+                Some(bolsa)
+            }
+            Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                if self.llama(*condicion) {
+                    bolsa = Bolsa::DESCONOCIDA;
+                }
+                let mut salida = self.bloque(entonces, self.con_condicion(bolsa, *condicion, true));
+                for alternativa in alternativas {
+                    salida = unir(salida, self.bloque(&alternativa.cuerpo, self.con_condicion(bolsa, alternativa.condicion, true)));
+                }
+                let por_sino = if alternativas.is_empty() { self.con_condicion(bolsa, *condicion, false) } else { Some(bolsa) };
+                unir(salida, self.bloque(sino, por_sino))
+            }
+            Instruccion::Mientras { condicion, cuerpo, .. } => {
+                if self.llama(*condicion) {
+                    bolsa = Bolsa::DESCONOCIDA;
+                }
+                let cabeza = self.ciclo(cuerpo, bolsa, Some(*condicion));
+                self.con_condicion(cabeza, *condicion, false)
+            }
+            Instruccion::Repetir { condicion, cuerpo, .. } => {
+                let cabeza = self.ciclo(cuerpo, bolsa, None);
+                // Con una cantidad constante positiva el cuerpo se ejecuta
+                // al menos una vez
+                match valor_constante(self.arena, &self.arena[*condicion]) {
+                    Some(cantidad) if cantidad > 0 => self.en_silencio(|flujo| flujo.bloque(cuerpo, Some(cabeza))),
+                    _ => Some(cabeza),
+                }
+            }
+            Instruccion::Para { cuerpo, .. } => Some(self.ciclo(cuerpo, bolsa, None)),
+            Instruccion::Segun { casos, sino, .. } => {
+                let salida = self.bloque(sino, Some(bolsa));
+                casos.iter().fold(salida, |salida, caso| unir(salida, self.bloque(&caso.cuerpo, Some(bolsa))))
+            }
+            // Lo que no se pudo parsear pudo haber tomado o depositado
+            Instruccion::Error { .. } => Some(Bolsa::DESCONOCIDA),
+        }
+    }
+
+    // Estado de la cabeza del ciclo: lo que hay antes de la primera vuelta
+    // unido a lo que deja cada vuelta. Después se recorre el cuerpo una vez
+    // más con ese estado para advertir lo que corresponda.
+    fn ciclo(&mut self, cuerpo: &[InstrId], entrada: Bolsa, condicion: Option<ExprId>) -> Bolsa {
+        let vuelta = |flujo: &mut Self, cabeza: Bolsa| {
+            let adentro = match condicion {
+                Some(condicion) => flujo.con_condicion(cabeza, condicion, true),
+                None => Some(cabeza),
+            };
+            flujo.bloque(cuerpo, adentro)
+        };
+        let mut cabeza = entrada;
+        loop {
+            let salida = self.en_silencio(|flujo| vuelta(flujo, cabeza));
+            let siguiente = unir(Some(cabeza), salida).unwrap_or(cabeza);
+            if siguiente == cabeza {
+                break;
+            }
+            cabeza = cabeza.ensanchar(siguiente);
+        }
+        vuelta(self, cabeza);
+        cabeza
+    }
+
+    fn en_silencio<T>(&mut self, recorrer: impl FnOnce(&mut Self) -> T) -> T {
+        let informar = std::mem::replace(&mut self.informar, false);
+        let resultado = recorrer(self);
+        self.informar = informar;
+        resultado
+    }
+
+    // La bolsa cuando `condicion` vale `valor`. Sólo se usan las condiciones
+    // que son exactamente `HayFlorEnLaBolsa` o `HayPapelEnLaBolsa`.
+    fn con_condicion(&self, mut bolsa: Bolsa, condicion: ExprId, valor: bool) -> Estado {
+        let Expresion::Sensor { nombre } = &self.arena[condicion] else {
+            return Some(bolsa);
+        };
+        let flores = match self.simbolos.resolver(*nombre) {
+            "HayFlorEnLaBolsa" => true,
+            "HayPapelEnLaBolsa" => false,
+            _ => return Some(bolsa),
+        };
+        let objeto = bolsa.objeto(flores);
+        *objeto = if valor { objeto.con_alguno()? } else { objeto.sin_ninguno()? };
+        Some(bolsa)
+    }
+
+    // Si la expresión llama a un proceso, que puede cambiar la bolsa
+    fn llama(&self, expresion: ExprId) -> bool {
+        match &self.arena[expresion] {
+            Expresion::Llamada { .. } => true,
+            Expresion::Binaria { izquierda, derecha, .. } => self.llama(*izquierda) || self.llama(*derecha),
+            Expresion::Negacion(operando) => self.llama(*operando),
+            Expresion::Indice { indice, .. } => self.llama(*indice),
+            _ => false,
+        }
+    }
+
+    fn advertir(&mut self, instruccion: &str, linea: usize) {
+        if self.informar && self.informadas.insert(linea) {
+            let mensaje = Mensaje::new(Codigo::BolsaVacia).con(instruccion);
+            self.hallazgos.push(ubicar(mensaje, self.contexto, linea, self.archivo));
+        }
+    }
+}
+
+// Sensores de la esquina que verifica alguna condición del ciclo más interno
+#[derive(Debug, Clone, Copy, Default)]
+struct Verificados {
+    flores: bool,
+    papeles: bool,
+}
+
+struct Revision<'a, 'h> {
+    programa: &'a Program,
+    hallazgos: &'h mut Vec<Hallazgo>,
+    contexto: &'a str,
+    archivo: FileId,
+}
+
+impl Revision<'_, '_> {
+    fn bloque(&mut self, instrucciones: &[InstrId], en_ciclo: bool, verificados: Verificados) {
+        for instruccion in self.programa.arena.bloque(instrucciones) {
+            match instruccion {
+                Instruccion::LlamadaFuncion { nombre, linea, .. } if en_ciclo => {
+                    let (nombre, verificado, sensor) = match self.programa.simbolos.resolver(*nombre) {
+                        "tomarFlor" => ("tomarFlor", verificados.flores, "HayFlorEnLaEsquina"),
+                        "tomarPapel" => ("tomarPapel", verificados.papeles, "HayPapelEnLaEsquina"),
+                        _ => continue,
+                    };
+                    if !verificado {
+                        let mensaje = Mensaje::new(Codigo::TomarSinVerificar).con(nombre).con(sensor);
+                        self.hallazgos.push(ubicar(mensaje, self.contexto, *linea, self.archivo));
+                    }
+                }
+                Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                    let verificados = self.verifica(*condicion, verificados);
+                    self.bloque(entonces, en_ciclo, verificados);
+                    for alternativa in alternativas {
+                        let verificados = self.verifica(alternativa.condicion, verificados);
+                        self.bloque(&alternativa.cuerpo, en_ciclo, verificados);
+                    }
+                    self.bloque(sino, en_ciclo, verificados);
+                }
+                Instruccion::Segun { selector, casos, sino, .. } => {
+                    let verificados = self.verifica(*selector, verificados);
+                    for caso in casos {
+                        self.bloque(&caso.cuerpo, en_ciclo, verificados);
+                    }
+                    self.bloque(sino, en_ciclo, verificados);
+                }
+                // Un ciclo nuevo sólo cuenta lo que verifica su condición
+                Instruccion::Mientras { condicion, cuerpo, .. } => {
+                    self.bloque(cuerpo, true, self.verifica(*condicion, Verificados::default()));
+                }
+                Instruccion::Repetir { cuerpo, .. } | Instruccion::Para { cuerpo, .. } => {
+                    self.bloque(cuerpo, true, Verificados::default());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn verifica(&self, condicion: ExprId, verificados: Verificados) -> Verificados {
+        Verificados {
+            flores: verificados.flores || self.menciona(condicion, "HayFlorEnLaEsquina"),
+            papeles: verificados.papeles || self.menciona(condicion, "HayPapelEnLaEsquina"),
+        }
+    }
+
+    fn menciona(&self, expresion: ExprId, sensor: &str) -> bool {
+        let programa = self.programa;
+        match &programa.arena[expresion] {
+            Expresion::Sensor { nombre } => programa.simbolos.resolver(*nombre) == sensor,
+            Expresion::Binaria { izquierda, derecha, .. } => self.menciona(*izquierda, sensor) || self.menciona(*derecha, sensor),
+            Expresion::Negacion(operando) => self.menciona(*operando, sensor),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use crate::interner::{Interner, Symbol};
+use crate::interpreter::world::TAMANIO_CIUDAD;
+use crate::mensajes::{Codigo, Mensaje};
+use crate::parser::arena::{Arena, ExprId};
+use crate::parser::ast::{recorrer_expresion, recorrer_instruccion, Visitor};
+use crate::parser::processor::{valor_con_constantes, Expresion, Instruccion, Program};
+use crate::source::FileId;
+use super::{ubicar, Hallazgo, LintRule};
+
+// Errores frecuentes que se detectan con los valores constantes del
+// programa: divisiones por cero, `repetir` con una cantidad negativa y
+// coordenadas fuera de la ciudad
+pub struct ValoresConstantes;
+
+impl LintRule for ValoresConstantes {
+    fn nombre(&self) -> &'static str {
+        "valores-constantes"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        let mut lints = Lints {
+            hallazgos,
+            arena: &programa.arena,
+            simbolos: &programa.simbolos,
+            constantes: programa.valores_constantes(),
+            contexto: "",
+            archivo: FileId::PRINCIPAL,
+            linea: 0,
+        };
+        for proceso in &programa.procesos {
+            lints.contexto = &proceso.nombre;
+            lints.archivo = proceso.archivo;
+            lints.visitar_bloque(&proceso.instrucciones);
+        }
+        for robot in &programa.robots_definidos {
+            lints.contexto = &robot.nombre;
+            lints.archivo = robot.archivo;
+            lints.visitar_bloque(&robot.instrucciones);
+        }
+
+        lints.contexto = &programa.nombre;
+        lints.archivo = FileId::PRINCIPAL;
+        lints.linea = 0;
+        for inicializacion in &programa.inicializaciones {
+            lints.verificar_coordenadas("Iniciar", &[inicializacion.pos_x, inicializacion.pos_y]);
+        }
+    }
+}
+
+struct Lints<'a, 'h> {
+    hallazgos: &'h mut Vec<Hallazgo>,
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    constantes: HashMap<Symbol, i32>,
+    contexto: &'a str,
+    archivo: FileId,
+    // Línea de la instrucción que se está recorriendo; 0 en el programa principal
+    linea: usize,
+}
+
+impl<'a> Lints<'a, '_> {
+    fn advertir(&mut self, mensaje: Mensaje) {
+        self.hallazgos.push(ubicar(mensaje, self.contexto, self.linea, self.archivo));
+    }
+
+    // Valor de la expresión si sólo tiene números y constantes del programa
+    fn valor(&self, expresion: ExprId) -> Option<i32> {
+        valor_con_constantes(self.arena, &self.arena[expresion], &self.constantes)
+    }
+
+    fn verificar_coordenadas(&mut self, instruccion: &str, coordenadas: &[ExprId]) {
+        for coordenada in coordenadas {
+            if let Some(valor) = self.valor(*coordenada)
+                && !(1..=TAMANIO_CIUDAD).contains(&valor)
+            {
+                self.advertir(
+                    Mensaje::new(Codigo::CoordenadaFueraDeCiudad).con(valor).con(instruccion).con(TAMANIO_CIUDAD)
+                );
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Lints<'a, '_> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+        self.linea = instruccion.linea();
+        match instruccion {
+            Instruccion::Repetir { condicion, .. } => {
+                if let Some(cantidad) = self.valor(*condicion)
+                    && cantidad < 0
+                {
+                    self.advertir(Mensaje::new(Codigo::RepetirNegativo).con(cantidad));
+                }
+            }
+            Instruccion::LlamadaFuncion { nombre, argumentos, .. } if self.simbolos.resolver(*nombre) == "Pos" => {
+                self.verificar_coordenadas("Pos", argumentos);
+            }
+            _ => {}
+        }
+        recorrer_instruccion(self, instruccion);
+    }
+
+    fn visitar_expresion(&mut self, expresion: &'a Expresion) {
+        if let Expresion::Binaria { operador, derecha, .. } = expresion
+            && operador == "/"
+            && self.valor(*derecha) == Some(0)
+        {
+            self.advertir(Codigo::DivisionPorCero.into());
+        }
+        recorrer_expresion(self, expresion);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use crate::compilerError::CompilerError;
+use crate::mensajes::{Codigo, Mensaje, Termino};
+use crate::parser::processor::Program;
+use crate::source::FileId;
+
+mod bolsa;
+mod constantes;
+mod recorridos;
+mod reglas;
+
+pub use bolsa::{BolsaVacia, TomarSinVerificar};
+pub use constantes::ValoresConstantes;
+pub use crate::parser::processor::valor_constante;
+pub use recorridos::RecorridoFueraDeArea;
+pub use reglas::{AnidamientoProfundo, NumerosMagicos, ProcesoLargo, SegunSinSino, SiVacio, SinInicializar};
+
+// Qué se hace con lo que encuentra una regla: nada, una advertencia o un
+// error que impide compilar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nivel {
+    Permitir,
+    Advertir,
+    Rechazar,
+}
+
+impl Nivel {
+    // "allow", "warn" o "deny", como se escribe en el archivo de configuración
+    pub fn desde_texto(texto: &str) -> Option<Self> {
+        match texto {
+            "allow" => Some(Nivel::Permitir),
+            "warn" => Some(Nivel::Advertir),
+            "deny" => Some(Nivel::Rechazar),
+            _ => None,
+        }
+    }
+}
+
+// Un problema que encontró una regla. El mensaje ya indica la sección y la
+// línea, como el resto de las advertencias.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hallazgo {
+    pub mensaje: Mensaje,
+    pub linea: usize,
+    pub archivo: FileId,
+}
+
+// Hallazgo en una línea de la sección, o en toda la sección si la línea es 0
+pub(crate) fn ubicar(mensaje: Mensaje, contexto: &str, linea: usize, archivo: FileId) -> Hallazgo {
+    let mensaje = if linea > 0 {
+        Mensaje::termino(Termino::EnLinea).con(mensaje).con(contexto).con(linea)
+    } else {
+        Mensaje::termino(Termino::EnSeccion).con(mensaje).con(contexto)
+    };
+    Hallazgo { mensaje, linea, archivo }
+}
+
+// Una verificación sobre el programa ya analizado, que no impide compilarlo
+// salvo que se configure como `deny`. Las reglas con un umbral (la
+// profundidad, la cantidad de instrucciones) reciben el configurado o el suyo
+// por defecto en `limite`.
+pub trait LintRule: Send + Sync {
+    // Nombre con que se configura la regla, en minúsculas y con guiones
+    fn nombre(&self) -> &'static str;
+
+    fn nivel_por_defecto(&self) -> Nivel {
+        Nivel::Advertir
+    }
+
+    fn limite_por_defecto(&self) -> Option<usize> {
+        None
+    }
+
+    fn verificar(&self, programa: &Program, limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>);
+}
+
+// Nivel y umbral que el archivo de configuración le fija a una regla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AjusteRegla {
+    pub regla: String,
+    pub nivel: Nivel,
+    pub limite: Option<usize>,
+    pub linea: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfiguracionLints {
+    pub ajustes: Vec<AjusteRegla>,
+}
+
+impl ConfiguracionLints {
+    // Lee un archivo de configuración con una regla por línea:
+    //   anidamiento-profundo = deny
+    //   proceso-largo = warn 40
+    // El número, si está, cambia el umbral de la regla. Las líneas vacías y
+    // los comentarios `{ ... }` se ignoran.
+    pub fn desde_texto(texto: &str) -> Result<Self, CompilerError> {
+        let mut ajustes = Vec::new();
+
+        for (i, linea) in texto.lines().enumerate() {
+            let linea = linea.trim();
+            if linea.is_empty() || linea.starts_with('{') {
+                continue;
+            }
+
+            let Some((regla, valor)) = linea.split_once('=') else {
+                return Err(CompilerError::new(Mensaje::new(Codigo::FormatoDeConfiguracion).con(linea), i + 1, 0));
+            };
+            ajustes.push(AjusteRegla::new(regla.trim(), valor, i + 1).map_err(|codigo| {
+                CompilerError::new(Mensaje::new(codigo).con(linea), i + 1, 0)
+            })?);
+        }
+
+        Ok(Self { ajustes })
+    }
+}
+
+impl AjusteRegla {
+    // Ajuste de `regla` escrito como `nivel [limite]`, con el código del
+    // error si está mal escrito
+    pub fn new(regla: &str, valor: &str, linea: usize) -> Result<Self, Codigo> {
+        let partes: Vec<&str> = valor.split_whitespace().collect();
+        let (nivel, limite) = match partes[..] {
+            [nivel] => (nivel, None),
+            [nivel, limite] => (nivel, Some(limite.parse().map_err(|_| Codigo::NumeroInvalido)?)),
+            _ => return Err(Codigo::FormatoDeConfiguracion),
+        };
+        let nivel = Nivel::desde_texto(nivel).ok_or(Codigo::NivelDesconocido)?;
+        Ok(Self { regla: regla.to_string(), nivel, limite, linea })
+    }
+}
+
+// Las reglas que se verifican al analizar un programa y cómo está
+// configurada cada una. Agregar una verificación es implementar `LintRule`
+// y registrarla en `predeterminado`.
+pub struct RegistroLints {
+    reglas: Vec<Box<dyn LintRule>>,
+    ajustes: HashMap<&'static str, (Nivel, Option<usize>)>,
+}
+
+impl RegistroLints {
+    pub fn new() -> Self {
+        Self { reglas: Vec::new(), ajustes: HashMap::new() }
+    }
+
+    // Las reglas del compilador, en el orden en que se informan
+    pub fn predeterminado() -> Self {
+        Self::new()
+            .con(ValoresConstantes)
+            .con(SinInicializar)
+            .con(RecorridoFueraDeArea)
+            .con(BolsaVacia)
+            .con(TomarSinVerificar)
+            .con(AnidamientoProfundo)
+            .con(NumerosMagicos)
+            .con(SiVacio)
+            .con(SegunSinSino)
+            .con(ProcesoLargo)
+    }
+
+    pub fn con(mut self, regla: impl LintRule + 'static) -> Self {
+        self.ajustes.insert(regla.nombre(), (regla.nivel_por_defecto(), regla.limite_por_defecto()));
+        self.reglas.push(Box::new(regla));
+        self
+    }
+
+    pub fn nombres(&self) -> Vec<&'static str> {
+        self.reglas.iter().map(|regla| regla.nombre()).collect()
+    }
+
+    pub fn nivel(&self, regla: &str) -> Option<Nivel> {
+        self.ajustes.get(regla).map(|(nivel, _)| *nivel)
+    }
+
+    // Aplica los ajustes del archivo; nombrar una regla que no está
+    // registrada es un error en la línea del ajuste
+    pub fn configurar(&mut self, configuracion: &ConfiguracionLints) -> Result<(), CompilerError> {
+        for ajuste in &configuracion.ajustes {
+            let Some((nivel, limite)) = self.ajustes.get_mut(ajuste.regla.as_str()) else {
+                return Err(CompilerError::new(
+                    Mensaje::new(Codigo::ReglaDesconocida).con(&ajuste.regla).con(self.nombres().join(", ")),
+                    ajuste.linea, 0,
+                ));
+            };
+            *nivel = ajuste.nivel;
+            if ajuste.limite.is_some() {
+                *limite = ajuste.limite;
+            }
+        }
+        Ok(())
+    }
+
+    // Advertencias y errores de las reglas que no están permitidas
+    pub fn verificar(&self, programa: &Program) -> (Vec<Mensaje>, Vec<CompilerError>) {
+        let mut advertencias = Vec::new();
+        let mut errores = Vec::new();
+        for (regla, nivel, hallazgo) in self.hallazgos(programa) {
+            match nivel {
+                Nivel::Rechazar => errores.push(
+                    CompilerError::new(hallazgo.mensaje, hallazgo.linea, 0)
+                        .en_archivo(hallazgo.archivo)
+                        .con_nota(Mensaje::termino(Termino::ReglaRechazada).con(regla), None)
+                ),
+                _ => advertencias.push(hallazgo.mensaje),
+            }
+        }
+        (advertencias, errores)
+    }
+
+    // Lo que encuentran las reglas que advierten, con su ubicación, para
+    // quien muestra cada advertencia junto a su línea
+    pub fn advertencias(&self, programa: &Program) -> Vec<Hallazgo> {
+        self.hallazgos(programa)
+            .into_iter()
+            .filter(|(_, nivel, _)| *nivel == Nivel::Advertir)
+            .map(|(_, _, hallazgo)| hallazgo)
+            .collect()
+    }
+
+    // Hallazgos de las reglas que no están permitidas, con el nombre y el
+    // nivel de la regla
+    fn hallazgos(&self, programa: &Program) -> Vec<(&'static str, Nivel, Hallazgo)> {
+        let mut encontrados = Vec::new();
+        for regla in &self.reglas {
+            let (nivel, limite) = self.ajustes[regla.nombre()];
+            if nivel == Nivel::Permitir {
+                continue;
+            }
+            let mut hallazgos = Vec::new();
+            regla.verificar(programa, limite, &mut hallazgos);
+            encontrados.extend(hallazgos.into_iter().map(|hallazgo| (regla.nombre(), nivel, hallazgo)));
+        }
+        encontrados
+    }
+}
+
+impl Default for RegistroLints {
+    fn default() -> Self {
+        Self::predeterminado()
+    }
+}
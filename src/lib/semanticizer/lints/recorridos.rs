@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use crate::interner::{Interner, Symbol};
+use crate::interpreter::world::Direccion;
+use crate::mensajes::{Codigo, Mensaje};
+use crate::parser::arena::{Arena, ExprId, InstrId};
+use crate::parser::ast::{recorrer_expresion, recorrer_instruccion, Visitor};
+use crate::parser::processor::{valor_con_constantes, Expresion, Instruccion, Program};
+use super::super::signatures::firma_elemental;
+use super::{ubicar, Hallazgo, LintRule};
+
+// Pasos que se siguen como máximo en el recorrido de cada robot
+const MAXIMO_PASOS: usize = 10_000;
+
+// Robots que seguro salen de las áreas que tienen asignadas. Se sigue el
+// recorrido desde un `Iniciar` constante con `mover`, `derecha` y los
+// `repetir` de cantidad constante, hasta la primera instrucción que puede
+// mover al robot de una forma que no se conoce al compilar (un `mientras`
+// que mueve, `Pos` o la llamada a un proceso). Así sólo se informa lo que
+// pasaría en cualquier ejecución.
+pub struct RecorridoFueraDeArea;
+
+impl LintRule for RecorridoFueraDeArea {
+    fn nombre(&self) -> &'static str {
+        "sale-del-area"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        let constantes = programa.valores_constantes();
+        let nombre = |expresion: ExprId| match &programa.arena[expresion] {
+            Expresion::Identificador(nombre) => Some(programa.simbolos.resolver(*nombre)),
+            _ => None,
+        };
+        let valor = |expresion: ExprId| valor_con_constantes(&programa.arena, &programa.arena[expresion], &constantes);
+
+        for instancia in &programa.robots_instanciados {
+            let Some(robot) = programa.robots_definidos.iter().find(|robot| robot.nombre == instancia.tipo) else {
+                continue;
+            };
+            let areas: Vec<(i32, i32, i32, i32)> = programa.asignaciones_areas.iter()
+                .filter(|asignacion| nombre(asignacion.robot) == Some(instancia.nombre.as_str()))
+                .filter_map(|asignacion| programa.areas.iter().find(|area| nombre(asignacion.area) == Some(area.nombre.as_str())))
+                .map(|area| {
+                    let (av1, ca1, av2, ca2) = area.coordenadas;
+                    (av1.min(av2), ca1.min(ca2), av1.max(av2), ca1.max(ca2))
+                })
+                .collect();
+            // Sin áreas el intérprete no le pone límites
+            if areas.is_empty() {
+                continue;
+            }
+            let Some(posicion) = programa.inicializaciones.iter()
+                .find(|inicializacion| nombre(inicializacion.robot) == Some(instancia.nombre.as_str()))
+                .and_then(|inicializacion| Some((valor(inicializacion.pos_x)?, valor(inicializacion.pos_y)?)))
+            else {
+                continue;
+            };
+
+            let mut recorrido = Recorrido {
+                arena: &programa.arena,
+                simbolos: &programa.simbolos,
+                constantes: &constantes,
+                areas: &areas,
+                posicion,
+                direccion: Direccion::Norte,
+                pasos: 0,
+            };
+            if let Fin::Fuera { posicion: (av, ca), linea } = recorrido.seguir(&robot.instrucciones) {
+                let mensaje = Mensaje::new(Codigo::SaleDelArea).con(&instancia.nombre).con(av).con(ca);
+                hallazgos.push(ubicar(mensaje, &robot.nombre, linea, robot.archivo));
+            }
+        }
+    }
+}
+
+// Cómo termina el recorrido de un bloque
+enum Fin {
+    // Se recorrió entero y el robot sigue dentro de sus áreas
+    Completo,
+    // Llegó a algo que no se puede seguir al compilar
+    Incierto,
+    // Salió de sus áreas al moverse a `posicion` en la instrucción de `linea`
+    Fuera { posicion: (i32, i32), linea: usize },
+}
+
+struct Recorrido<'a> {
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    constantes: &'a HashMap<Symbol, i32>,
+    areas: &'a [(i32, i32, i32, i32)],
+    posicion: (i32, i32),
+    direccion: Direccion,
+    pasos: usize,
+}
+
+impl Recorrido<'_> {
+    fn seguir(&mut self, instrucciones: &[InstrId]) -> Fin {
+        for instruccion in self.arena.bloque(instrucciones) {
+            let fin = match instruccion {
+                Instruccion::LlamadaFuncion { nombre, linea, .. } if self.simbolos.resolver(*nombre) == "mover" => {
+                    self.mover(*linea)
+                }
+                Instruccion::LlamadaFuncion { nombre, .. } if self.simbolos.resolver(*nombre) == "derecha" => {
+                    self.direccion = self.direccion.derecha();
+                    Fin::Completo
+                }
+                Instruccion::Repetir { condicion: cantidad, cuerpo, .. }
+                | Instruccion::Para { cantidad, cuerpo, .. } => match self.valor(*cantidad) {
+                    Some(veces) => self.repetir(veces, cuerpo),
+                    None if self.mueve(instruccion) => Fin::Incierto,
+                    None => Fin::Completo,
+                },
+                _ if self.mueve(instruccion) => Fin::Incierto,
+                _ => Fin::Completo,
+            };
+            if !matches!(fin, Fin::Completo) {
+                return fin;
+            }
+        }
+        Fin::Completo
+    }
+
+    fn repetir(&mut self, veces: i32, cuerpo: &[InstrId]) -> Fin {
+        for _ in 0..veces {
+            match self.seguir(cuerpo) {
+                Fin::Completo => {}
+                fin => return fin,
+            }
+        }
+        Fin::Completo
+    }
+
+    fn mover(&mut self, linea: usize) -> Fin {
+        self.pasos += 1;
+        if self.pasos > MAXIMO_PASOS {
+            return Fin::Incierto;
+        }
+        let (av, ca) = self.posicion;
+        let (dav, dca) = self.direccion.desplazamiento();
+        self.posicion = (av + dav, ca + dca);
+        let (av, ca) = self.posicion;
+        let dentro = self.areas.iter()
+            .any(|&(av_min, ca_min, av_max, ca_max)| (av_min..=av_max).contains(&av) && (ca_min..=ca_max).contains(&ca));
+        if dentro {
+            Fin::Completo
+        } else {
+            Fin::Fuera { posicion: self.posicion, linea }
+        }
+    }
+
+    fn valor(&self, expresion: ExprId) -> Option<i32> {
+        valor_con_constantes(self.arena, &self.arena[expresion], self.constantes)
+    }
+
+    // Si la instrucción o algo que tiene adentro puede mover o girar al robot
+    fn mueve(&self, instruccion: &Instruccion) -> bool {
+        let mut buscador = Movimientos { arena: self.arena, simbolos: self.simbolos, mueve: false };
+        buscador.visitar_instruccion(instruccion);
+        buscador.mueve
+    }
+}
+
+// Busca `mover`, `derecha`, `Pos` y llamadas a procesos
+struct Movimientos<'a> {
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    mueve: bool,
+}
+
+impl<'a> Visitor<'a> for Movimientos<'a> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+        if let Instruccion::LlamadaFuncion { nombre, .. } = instruccion {
+            let nombre = self.simbolos.resolver(*nombre);
+            self.mueve |= matches!(nombre, "mover" | "derecha" | "Pos") || firma_elemental(nombre).is_none();
+        }
+        recorrer_instruccion(self, instruccion);
+    }
+
+    fn visitar_expresion(&mut self, expresion: &'a Expresion) {
+        self.mueve |= matches!(expresion, Expresion::Llamada { .. });
+        recorrer_expresion(self, expresion);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::mensajes::{Codigo, Mensaje};
+use crate::parser::arena::{Arena, InstrId};
+use crate::parser::ast::{recorrer_instruccion, Visitor};
+use crate::parser::processor::{Expresion, Instruccion, Program};
+use crate::source::FileId;
+use super::super::consultas::Clase;
+use super::super::dataflow;
+use super::super::metricas::Metricas;
+use super::{ubicar, Hallazgo, LintRule, Nivel};
+
+// Nombre, instrucciones y archivo de cada proceso y robot
+pub(super) fn secciones(programa: &Program) -> impl Iterator<Item = (&str, &[InstrId], FileId)> {
+    programa.procesos.iter()
+        .map(|proceso| (proceso.nombre.as_str(), proceso.instrucciones.as_slice(), proceso.archivo))
+        .chain(programa.robots_definidos.iter()
+            .map(|robot| (robot.nombre.as_str(), robot.instrucciones.as_slice(), robot.archivo)))
+}
+
+// Variables locales que se leen antes de asignarles un valor
+pub struct SinInicializar;
+
+impl LintRule for SinInicializar {
+    fn nombre(&self) -> &'static str {
+        "sin-inicializar"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        hallazgos.extend(dataflow::advertencias(programa));
+    }
+}
+
+// Estructuras de control anidadas más allá del límite. Se informa la primera
+// que lo supera en cada camino, no las que tiene adentro.
+pub struct AnidamientoProfundo;
+
+impl LintRule for AnidamientoProfundo {
+    fn nombre(&self) -> &'static str {
+        "anidamiento-profundo"
+    }
+
+    fn limite_por_defecto(&self) -> Option<usize> {
+        Some(3)
+    }
+
+    fn verificar(&self, programa: &Program, limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        let limite = limite.unwrap_or(3);
+        for (nombre, instrucciones, archivo) in secciones(programa) {
+            anidamiento(&programa.arena, instrucciones, 0, limite, &mut |linea| {
+                let mensaje = Mensaje::new(Codigo::AnidamientoProfundo).con(limite + 1).con(limite);
+                hallazgos.push(ubicar(mensaje, nombre, linea, archivo));
+            });
+        }
+    }
+}
+
+fn anidamiento(arena: &Arena, instrucciones: &[InstrId], nivel: usize, limite: usize, informar: &mut dyn FnMut(usize)) {
+    for instruccion in arena.bloque(instrucciones) {
+        // Las ramas `sino si` están al mismo nivel que la primera
+        let bloques: Vec<&[InstrId]> = match instruccion {
+            Instruccion::Si { entonces, alternativas, sino, .. } => std::iter::once(entonces.as_slice())
+                .chain(alternativas.iter().map(|alternativa| alternativa.cuerpo.as_slice()))
+                .chain([sino.as_slice()])
+                .collect(),
+            Instruccion::Mientras { cuerpo, .. }
+            | Instruccion::Repetir { cuerpo, .. }
+            | Instruccion::Para { cuerpo, .. } => vec![cuerpo.as_slice()],
+            Instruccion::Segun { casos, sino, .. } => casos.iter()
+                .map(|caso| caso.cuerpo.as_slice())
+                .chain([sino.as_slice()])
+                .collect(),
+            _ => continue,
+        };
+        if nivel + 1 > limite {
+            informar(instruccion.linea());
+            continue;
+        }
+        for bloque in bloques {
+            anidamiento(arena, bloque, nivel + 1, limite, informar);
+        }
+    }
+}
+
+// Coordenadas escritas como números dentro de un proceso o robot: un
+// recorrido que sólo sirve para una esquina fija suele ser un descuido.
+// Permitida por defecto, porque muchos enunciados piden esquinas fijas.
+pub struct NumerosMagicos;
+
+impl LintRule for NumerosMagicos {
+    fn nombre(&self) -> &'static str {
+        "numeros-magicos"
+    }
+
+    fn nivel_por_defecto(&self) -> Nivel {
+        Nivel::Permitir
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        for (nombre, instrucciones, archivo) in secciones(programa) {
+            let mut buscador = BuscadorInstrucciones {
+                arena: &programa.arena,
+                encontrar: |instruccion: &Instruccion| {
+                    let Instruccion::LlamadaFuncion { nombre: instruccion, argumentos, linea } = instruccion else {
+                        return;
+                    };
+                    let instruccion = programa.simbolos.resolver(*instruccion);
+                    if !matches!(instruccion, "Pos" | "BloquearEsquina" | "LiberarEsquina") {
+                        return;
+                    }
+                    for argumento in argumentos {
+                        if let Expresion::Numero(valor) = programa.arena[*argumento] {
+                            let mensaje = Mensaje::new(Codigo::NumeroMagico).con(valor).con(instruccion);
+                            hallazgos.push(ubicar(mensaje, nombre, *linea, archivo));
+                        }
+                    }
+                },
+            };
+            buscador.visitar_bloque(instrucciones);
+        }
+    }
+}
+
+// `si` sin instrucciones en su rama principal
+pub struct SiVacio;
+
+impl LintRule for SiVacio {
+    fn nombre(&self) -> &'static str {
+        "si-vacio"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        for (nombre, instrucciones, archivo) in secciones(programa) {
+            let mut buscador = BuscadorInstrucciones {
+                arena: &programa.arena,
+                encontrar: |instruccion: &Instruccion| {
+                    if let Instruccion::Si { entonces, linea, .. } = instruccion
+                        && entonces.is_empty()
+                    {
+                        hallazgos.push(ubicar(Codigo::SiVacio.into(), nombre, *linea, archivo));
+                    }
+                },
+            };
+            buscador.visitar_bloque(instrucciones);
+        }
+    }
+}
+
+// `segun` sin `sino` cuyos casos no cubren todos los valores posibles del
+// selector: sólo un selector booleano con los casos `V` y `F` los cubre
+pub struct SegunSinSino;
+
+impl LintRule for SegunSinSino {
+    fn nombre(&self) -> &'static str {
+        "segun-sin-sino"
+    }
+
+    fn verificar(&self, programa: &Program, _limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        for (nombre, instrucciones, archivo) in secciones(programa) {
+            let mut buscador = BuscadorInstrucciones {
+                arena: &programa.arena,
+                encontrar: |instruccion: &Instruccion| {
+                    let Instruccion::Segun { casos, sino, linea, .. } = instruccion else {
+                        return;
+                    };
+                    let cubre = |valor| casos.iter().any(|caso| programa.arena[caso.valor] == Expresion::Booleano(valor));
+                    if sino.is_empty() && !(cubre(true) && cubre(false)) {
+                        hallazgos.push(ubicar(Codigo::SegunSinSino.into(), nombre, *linea, archivo));
+                    }
+                },
+            };
+            buscador.visitar_bloque(instrucciones);
+        }
+    }
+}
+
+// Procesos con más instrucciones que el límite, contando las anidadas
+pub struct ProcesoLargo;
+
+impl LintRule for ProcesoLargo {
+    fn nombre(&self) -> &'static str {
+        "proceso-largo"
+    }
+
+    fn limite_por_defecto(&self) -> Option<usize> {
+        Some(25)
+    }
+
+    fn verificar(&self, programa: &Program, limite: Option<usize>, hallazgos: &mut Vec<Hallazgo>) {
+        let limite = limite.unwrap_or(25);
+        let metricas = Metricas::new(programa);
+        for proceso in metricas.secciones.iter().filter(|seccion| seccion.clase == Clase::Proceso) {
+            if proceso.instrucciones > limite {
+                let mensaje = Mensaje::new(Codigo::ProcesoLargo).con(proceso.instrucciones).con(limite);
+                hallazgos.push(ubicar(mensaje, &proceso.nombre, proceso.linea, proceso.archivo));
+            }
+        }
+    }
+}
+
+// Recorre un bloque y sus anidados pasando cada instrucción a `encontrar`
+struct BuscadorInstrucciones<'a, F> {
+    arena: &'a Arena,
+    encontrar: F,
+}
+
+impl<'a, F: FnMut(&Instruccion)> Visitor<'a> for BuscadorInstrucciones<'a, F> {
+    fn arena(&self) -> &'a Arena {
+        self.arena
+    }
+
+    fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+        (self.encontrar)(instruccion);
+        recorrer_instruccion(self, instruccion);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::interner::Interner;
+use crate::interpreter::reporte::cadena_json;
+use crate::parser::arena::{Arena, ExprId, InstrId};
+use crate::parser::processor::{Expresion, Instruccion, Program};
+use crate::source::FileId;
+use super::consultas::Clase;
+use super::signatures::{categoria_elemental, CategoriaElemental};
+
+// Medidas del código de un proceso o de un tipo de robot
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetricasSeccion {
+    pub nombre: String,
+    // `Clase::Proceso` o `Clase::TipoRobot`
+    pub clase: Clase,
+    pub linea: usize,
+    pub archivo: FileId,
+    // Instrucciones de la sección, contando las anidadas
+    pub instrucciones: usize,
+    // Máxima cantidad de `si`, `mientras` y `repetir` uno dentro de otro
+    pub profundidad: usize,
+    // Complejidad ciclomática: uno más la cantidad de decisiones, que son
+    // los `si`, `mientras` y `repetir` y cada `&` o `|` de sus condiciones
+    pub complejidad: usize,
+    // Instrucciones elementales de cada categoría; las que no aparecen no están
+    pub elementales: BTreeMap<CategoriaElemental, usize>,
+    // Sólo de los procesos
+    pub parametros: Option<usize>,
+}
+
+impl MetricasSeccion {
+    pub fn elementales_de(&self, categoria: CategoriaElemental) -> usize {
+        self.elementales.get(&categoria).copied().unwrap_or(0)
+    }
+}
+
+// Métricas de todas las secciones del programa, primero los procesos y
+// después los robots, para `--emit metrics`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metricas {
+    pub secciones: Vec<MetricasSeccion>,
+}
+
+impl Metricas {
+    pub fn new(programa: &Program) -> Self {
+        let procesos = programa.procesos.iter().map(|proceso| {
+            let metricas = medir(programa, &proceso.nombre, Clase::Proceso, &proceso.instrucciones);
+            MetricasSeccion {
+                linea: proceso.linea,
+                archivo: proceso.archivo,
+                parametros: Some(proceso.parametros.len()),
+                ..metricas
+            }
+        });
+        let robots = programa.robots_definidos.iter().map(|robot| MetricasSeccion {
+            linea: robot.linea,
+            archivo: robot.archivo,
+            ..medir(programa, &robot.nombre, Clase::TipoRobot, &robot.instrucciones)
+        });
+        Self { secciones: procesos.chain(robots).collect() }
+    }
+
+    pub fn seccion(&self, nombre: &str) -> Option<&MetricasSeccion> {
+        self.secciones.iter().find(|seccion| seccion.nombre == nombre)
+    }
+
+    // Tabla con una fila por sección y una columna por cada métrica
+    pub fn a_texto(&self) -> String {
+        let mut columnas = vec!["seccion", "clase", "instrucciones", "profundidad", "complejidad", "parametros"];
+        columnas.extend(CategoriaElemental::TODAS.map(CategoriaElemental::nombre));
+        let mut filas = vec![columnas.iter().map(|columna| columna.to_string()).collect::<Vec<_>>()];
+        for seccion in &self.secciones {
+            let mut fila = vec![
+                seccion.nombre.clone(),
+                nombre_clase(seccion.clase).to_string(),
+                seccion.instrucciones.to_string(),
+                seccion.profundidad.to_string(),
+                seccion.complejidad.to_string(),
+                seccion.parametros.map_or("-".to_string(), |parametros| parametros.to_string()),
+            ];
+            fila.extend(CategoriaElemental::TODAS.map(|categoria| seccion.elementales_de(categoria).to_string()));
+            filas.push(fila);
+        }
+
+        let anchos: Vec<usize> = (0..columnas.len())
+            .map(|i| filas.iter().map(|fila| fila[i].chars().count()).max().unwrap_or(0))
+            .collect();
+        let mut texto = String::new();
+        for fila in &filas {
+            let celdas: Vec<String> = fila.iter().zip(&anchos)
+                .map(|(celda, ancho)| format!("{:<ancho$}", celda, ancho = ancho))
+                .collect();
+            let _ = writeln!(texto, "{}", celdas.join("  ").trim_end());
+        }
+        texto
+    }
+
+    pub fn a_json(&self) -> String {
+        let mut json = String::from("[");
+        for (i, seccion) in self.secciones.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let _ = write!(
+                json,
+                "  {{\"nombre\": {}, \"clase\": \"{}\", \"linea\": {}, \"instrucciones\": {}, \"profundidad\": {}, \"complejidad\": {}, \"parametros\": {}, \"elementales\": {{",
+                cadena_json(&seccion.nombre),
+                nombre_clase(seccion.clase),
+                seccion.linea,
+                seccion.instrucciones,
+                seccion.profundidad,
+                seccion.complejidad,
+                seccion.parametros.map_or("null".to_string(), |parametros| parametros.to_string()),
+            );
+            for (j, categoria) in CategoriaElemental::TODAS.iter().enumerate() {
+                let separador = if j == 0 { "" } else { ", " };
+                let _ = write!(json, "{}\"{}\": {}", separador, categoria.nombre(), seccion.elementales_de(*categoria));
+            }
+            json.push_str("}}");
+        }
+        json.push_str(if self.secciones.is_empty() { "]\n" } else { "\n]\n" });
+        json
+    }
+}
+
+fn nombre_clase(clase: Clase) -> &'static str {
+    match clase {
+        Clase::Proceso => "proceso",
+        _ => "robot",
+    }
+}
+
+fn medir(programa: &Program, nombre: &str, clase: Clase, instrucciones: &[InstrId]) -> MetricasSeccion {
+    let mut medidor = Medidor {
+        arena: &programa.arena,
+        simbolos: &programa.simbolos,
+        metricas: MetricasSeccion {
+            nombre: nombre.to_string(),
+            clase,
+            linea: 0,
+            archivo: FileId::PRINCIPAL,
+            instrucciones: 0,
+            profundidad: 0,
+            complejidad: 1,
+            elementales: BTreeMap::new(),
+            parametros: None,
+        },
+    };
+    medidor.bloque(instrucciones, 0);
+    medidor.metricas
+}
+
+struct Medidor<'a> {
+    arena: &'a Arena,
+    simbolos: &'a Interner,
+    metricas: MetricasSeccion,
+}
+
+impl Medidor<'_> {
+    // `nivel` es la cantidad de estructuras de control que rodean al bloque
+    fn bloque(&mut self, instrucciones: &[InstrId], nivel: usize) {
+        self.metricas.profundidad = self.metricas.profundidad.max(nivel);
+        for instruccion in self.arena.bloque(instrucciones) {
+            self.metricas.instrucciones += 1;
+            match instruccion {
+                Instruccion::Elemental { nombre, .. } | Instruccion::LlamadaFuncion { nombre, .. } => {
+                    if let Some(categoria) = categoria_elemental(self.simbolos.resolver(*nombre)) {
+                        *self.metricas.elementales.entry(categoria).or_default() += 1;
+                    }
+                }
+                Instruccion::Asignacion { .. } | Instruccion::Error { .. } => {}
+                Instruccion::Si { condicion, entonces, alternativas, sino, .. } => {
+                    self.decision(*condicion);
+                    self.bloque(entonces, nivel + 1);
+                    // Cada `sino si` es otra decisión, al mismo nivel que la primera
+                    for alternativa in alternativas {
+                        self.decision(alternativa.condicion);
+                        self.bloque(&alternativa.cuerpo, nivel + 1);
+                    }
+                    self.bloque(sino, nivel + 1);
+                }
+                Instruccion::Mientras { condicion, cuerpo, .. } => {
+                    self.decision(*condicion);
+                    self.bloque(cuerpo, nivel + 1);
+                }
+                Instruccion::Repetir { cuerpo, .. } | Instruccion::Para { cuerpo, .. } => {
+                    self.metricas.complejidad += 1;
+                    self.bloque(cuerpo, nivel + 1);
+                }
+                // Cada caso es una decisión, como una rama `sino si`
+                Instruccion::Segun { casos, sino, .. } => {
+                    for caso in casos {
+                        self.metricas.complejidad += 1;
+                        self.bloque(&caso.cuerpo, nivel + 1);
+                    }
+                    self.bloque(sino, nivel + 1);
+                }
+            }
+        }
+    }
+
+    fn decision(&mut self, condicion: ExprId) {
+        self.metricas.complejidad += 1 + self.operadores_logicos(&self.arena[condicion]);
+    }
+
+    fn operadores_logicos(&self, expresion: &Expresion) -> usize {
+        match expresion {
+            Expresion::Binaria { izquierda, operador, derecha } => {
+                let propio = usize::from(operador == "&" || operador == "|");
+                propio + self.operadores_logicos(&self.arena[*izquierda]) + self.operadores_logicos(&self.arena[*derecha])
+            }
+            Expresion::Negacion(operando) => self.operadores_logicos(&self.arena[*operando]),
+            _ => 0,
+        }
+    }
+}
@@ -1,2 +1,11 @@
 pub mod analizer;
-pub mod symbol_table;
\ No newline at end of file
+pub mod symbol_table;
+pub mod signatures;
+pub mod lints;
+pub mod dataflow;
+pub mod consultas;
+pub mod hir;
+pub mod contratos;
+pub mod inventario;
+pub mod metricas;
+pub mod diferencias;
@@ -0,0 +1,135 @@
+use crate::mensajes::Termino;
+
+// Qué acepta cada argumento de una instrucción elemental
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argumento {
+    // Expresión de tipo numero
+    Numero,
+    // Expresión de cualquier tipo
+    Valor,
+    // Variable donde la instrucción guarda un resultado
+    Variable,
+    // Nombre de un robot declarado en el programa principal
+    Robot,
+    // Un robot o `*`: todos al enviar un mensaje, cualquiera al recibirlo
+    RobotOTodos,
+    // Nombre de un área declarada
+    Area,
+}
+
+impl Argumento {
+    pub fn descripcion(self) -> Termino {
+        match self {
+            Argumento::Numero => Termino::DeTipoNumero,
+            Argumento::Valor => Termino::UnValor,
+            Argumento::Variable => Termino::UnaVariable,
+            Argumento::Robot => Termino::UnRobotDeclarado,
+            Argumento::RobotOTodos => Termino::UnRobotDeclaradoOTodos,
+            Argumento::Area => Termino::UnAreaDeclarada,
+        }
+    }
+}
+
+// Argumentos que espera una instrucción elemental. Si es variádica, el
+// último tipo de argumento puede repetirse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Firma {
+    pub argumentos: &'static [Argumento],
+    pub variadica: bool,
+}
+
+impl Firma {
+    const fn fija(argumentos: &'static [Argumento]) -> Self {
+        Self { argumentos, variadica: false }
+    }
+
+    pub fn acepta(&self, cantidad: usize) -> bool {
+        if self.variadica {
+            cantidad >= self.argumentos.len()
+        } else {
+            cantidad == self.argumentos.len()
+        }
+    }
+
+    // Tipo del argumento en la posición `indice` (desde 0)
+    pub fn argumento(&self, indice: usize) -> Option<Argumento> {
+        match self.argumentos.get(indice) {
+            Some(argumento) => Some(*argumento),
+            None if self.variadica => self.argumentos.last().copied(),
+            None => None,
+        }
+    }
+}
+
+// Firma de cada instrucción elemental que se usa como sentencia; `None` para
+// los nombres que no lo son (sensores, tipos de área, procesos)
+pub fn firma_elemental(nombre: &str) -> Option<Firma> {
+    use Argumento::*;
+
+    let firma = match nombre {
+        "mover" | "derecha" | "tomarFlor" | "tomarPapel" | "depositarFlor" | "depositarPapel" => Firma::fija(&[]),
+        "Pos" | "BloquearEsquina" | "LiberarEsquina" => Firma::fija(&[Numero, Numero]),
+        "Informar" => Firma { argumentos: &[Valor], variadica: true },
+        "Leer" => Firma::fija(&[Variable]),
+        "Random" => Firma::fija(&[Variable, Numero, Numero]),
+        "EnviarMensaje" => Firma::fija(&[Valor, RobotOTodos]),
+        "RecibirMensaje" => Firma::fija(&[Variable, RobotOTodos]),
+        "RecibirMensajeTimeout" => Firma::fija(&[Variable, RobotOTodos, Numero]),
+        "AsignarArea" => Firma::fija(&[Robot, Area]),
+        "Iniciar" => Firma::fija(&[Robot, Numero, Numero]),
+        _ => return None,
+    };
+    Some(firma)
+}
+
+// Para qué sirve una instrucción elemental, para contarlas por grupo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CategoriaElemental {
+    // mover, derecha y Pos
+    Movimiento,
+    // Tomar y depositar flores y papeles
+    Objetos,
+    // Enviar y recibir mensajes
+    Mensajes,
+    // BloquearEsquina y LiberarEsquina
+    Sincronizacion,
+    // Informar, Leer y Random
+    EntradaSalida,
+}
+
+impl CategoriaElemental {
+    pub const TODAS: [CategoriaElemental; 5] = [
+        CategoriaElemental::Movimiento,
+        CategoriaElemental::Objetos,
+        CategoriaElemental::Mensajes,
+        CategoriaElemental::Sincronizacion,
+        CategoriaElemental::EntradaSalida,
+    ];
+
+    pub fn nombre(self) -> &'static str {
+        match self {
+            CategoriaElemental::Movimiento => "movimiento",
+            CategoriaElemental::Objetos => "objetos",
+            CategoriaElemental::Mensajes => "mensajes",
+            CategoriaElemental::Sincronizacion => "sincronizacion",
+            CategoriaElemental::EntradaSalida => "entrada_salida",
+        }
+    }
+}
+
+// Categoría de cada instrucción elemental que puede usar un robot; `None`
+// para el resto
+pub fn categoria_elemental(nombre: &str) -> Option<CategoriaElemental> {
+    use CategoriaElemental::*;
+
+    let categoria = match nombre {
+        "mover" | "derecha" | "Pos" => Movimiento,
+        "tomarFlor" | "tomarPapel" | "depositarFlor" | "depositarPapel" => Objetos,
+        "EnviarMensaje" | "RecibirMensaje" | "RecibirMensajeTimeout" => Mensajes,
+        "BloquearEsquina" | "LiberarEsquina" => Sincronizacion,
+        "Informar" | "Leer" | "Random" => EntradaSalida,
+        _ => return None,
+    };
+    Some(categoria)
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::interner::Symbol;
+use crate::mensajes::{Mensaje, Termino};
+use crate::parser::processor::Program;
+
+// Qué declara un nombre del programa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Categoria {
+    Variable,
+    Robot,
+    Area,
+    Proceso,
+    Constante,
+}
+
+// "una variable", "un robot", ... para insertar en un diagnóstico
+impl From<Categoria> for Mensaje {
+    fn from(categoria: Categoria) -> Self {
+        Mensaje::termino(match categoria {
+            Categoria::Variable => Termino::UnaVariable,
+            Categoria::Robot => Termino::UnRobot,
+            Categoria::Area => Termino::UnArea,
+            Categoria::Proceso => Termino::UnProceso,
+            Categoria::Constante => Termino::UnaConstante,
+        })
+    }
+}
+
+impl fmt::Display for Categoria {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Mensaje::from(*self))
+    }
+}
+
+// Nombres globales del programa (procesos, áreas, robots instanciados y
+// constantes) y la categoría de cada uno. Las variables de cada sección se
+// registran aparte al verificarla. Sólo los nombres que aparecen en alguna instrucción tienen
+// símbolo, así que el resto no hace falta registrarlos.
+#[derive(Debug, Clone, Default)]
+pub struct TablaSimbolos {
+    categorias: HashMap<Symbol, Categoria>,
+}
+
+impl TablaSimbolos {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn del_programa(programa: &Program) -> Self {
+        let mut tabla = Self::new();
+        let nombres = programa.procesos.iter().map(|proceso| (&proceso.nombre, Categoria::Proceso))
+            .chain(programa.areas.iter().map(|area| (&area.nombre, Categoria::Area)))
+            .chain(programa.robots_instanciados.iter().map(|robot| (&robot.nombre, Categoria::Robot)))
+            .chain(programa.constantes.iter().map(|constante| (&constante.nombre, Categoria::Constante)));
+        for (nombre, categoria) in nombres {
+            if let Some(simbolo) = programa.simbolos.get(nombre) {
+                tabla.declarar(simbolo, categoria);
+            }
+        }
+        tabla
+    }
+
+    // Registra el nombre y devuelve la categoría que tenía antes, si ya estaba
+    pub fn declarar(&mut self, simbolo: Symbol, categoria: Categoria) -> Option<Categoria> {
+        self.categorias.insert(simbolo, categoria)
+    }
+
+    pub fn categoria(&self, simbolo: Symbol) -> Option<Categoria> {
+        self.categorias.get(&simbolo).copied()
+    }
+
+    pub fn es(&self, simbolo: Symbol, categoria: Categoria) -> bool {
+        self.categoria(simbolo) == Some(categoria)
+    }
+}
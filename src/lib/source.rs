@@ -0,0 +1,176 @@
+use crate::compilerError::CompilerError;
+use crate::mensajes::{Idioma, Mensaje, Termino};
+
+// Índice de un archivo dentro del `SourceMap` de una compilación. El archivo
+// principal es siempre el primero, así que es el valor por defecto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileId(u32);
+
+impl FileId {
+    pub const PRINCIPAL: FileId = FileId(0);
+}
+
+// Tramo del código entre dos posiciones, con líneas y columnas desde 1. La
+// columna final es la primera que ya no forma parte del tramo, así que un
+// tramo vacío (como el de un INDENT) empieza y termina en la misma columna.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub linea: usize,
+    pub columna: usize,
+    pub fin_linea: usize,
+    pub fin_columna: usize,
+}
+
+impl Span {
+    pub fn new(linea: usize, columna: usize, fin_linea: usize, fin_columna: usize) -> Self {
+        Self { linea, columna, fin_linea, fin_columna }
+    }
+
+    // Tramo desde el comienzo de este hasta el final de `otro`
+    pub fn hasta(self, otro: Span) -> Self {
+        Self { fin_linea: otro.fin_linea, fin_columna: otro.fin_columna, ..self }
+    }
+
+    // El mismo tramo corrido `lineas` líneas
+    pub fn desplazar(self, lineas: isize) -> Self {
+        Self {
+            linea: self.linea.saturating_add_signed(lineas),
+            fin_linea: self.fin_linea.saturating_add_signed(lineas),
+            ..self
+        }
+    }
+}
+
+// Un archivo de código que participa de la compilación
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceFile {
+    pub nombre: String,
+    pub texto: String,
+}
+
+impl SourceFile {
+    // Texto de una línea (desde 1), sin el salto de línea
+    pub fn linea(&self, numero: usize) -> Option<&str> {
+        self.texto.lines().nth(numero.checked_sub(1)?)
+    }
+}
+
+// Archivos de una compilación: el principal y los que incorpora con `incluir`
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceMap {
+    archivos: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn agregar(&mut self, nombre: impl Into<String>, texto: impl Into<String>) -> FileId {
+        self.archivos.push(SourceFile {
+            nombre: nombre.into(),
+            texto: texto.into(),
+        });
+        FileId((self.archivos.len() - 1) as u32)
+    }
+
+    pub fn archivo(&self, id: FileId) -> Option<&SourceFile> {
+        self.archivos.get(id.0 as usize)
+    }
+
+    pub fn nombre(&self, id: FileId) -> Option<&str> {
+        self.archivo(id).map(|archivo| archivo.nombre.as_str())
+    }
+
+    pub fn archivos(&self) -> &[SourceFile] {
+        &self.archivos
+    }
+
+    // Diagnóstico con su ubicación al estilo `archivo.ri:12:5: mensaje`. Las
+    // partes que el error no conoce (archivo, línea, columna) se omiten.
+    pub fn mostrar(&self, error: &CompilerError) -> String {
+        self.mostrar_en(error, Idioma::default())
+    }
+
+    pub fn mostrar_en(&self, error: &CompilerError, idioma: Idioma) -> String {
+        self.ubicado(error).traducir(idioma)
+    }
+
+    // El mensaje del error precedido por su ubicación, sin traducir
+    pub fn ubicado(&self, error: &CompilerError) -> Mensaje {
+        self.ubicar(error.archivo, error.line, error.column, &error.mensaje)
+    }
+
+    // Notas, ayuda y sugerencias del error, una por línea y en ese orden:
+    //
+    //   nota: principal.ri:4: primera declaración como proceso
+    //   ayuda: declarar 'total' en la sección 'variables' de 'r'
+    pub fn detalles(&self, error: &CompilerError) -> Vec<String> {
+        self.detalles_en(error, Idioma::default())
+    }
+
+    pub fn detalles_en(&self, error: &CompilerError, idioma: Idioma) -> Vec<String> {
+        let notas = error.notas().iter().map(|nota| {
+            let archivo = nota.archivo.or(error.archivo);
+            let (linea, columna) = nota.span.map_or((0, 0), |span| (span.linea, span.columna));
+            Mensaje::termino(Termino::Nota).con(self.ubicar(archivo, linea, columna, &nota.mensaje))
+        });
+        let ayudas = error.ayuda().into_iter()
+            .chain(error.sugerencias().iter().map(|sugerencia| &sugerencia.mensaje))
+            .map(|ayuda| Mensaje::termino(Termino::Ayuda).con(ayuda.clone()));
+        notas.chain(ayudas).map(|detalle| detalle.traducir(idioma)).collect()
+    }
+
+    fn ubicar(&self, archivo: Option<FileId>, linea: usize, columna: usize, mensaje: &Mensaje) -> Mensaje {
+        let mut ubicacion: Vec<String> = Vec::new();
+        if let Some(nombre) = archivo.and_then(|id| self.nombre(id)) {
+            ubicacion.push(nombre.to_string());
+        }
+        if linea > 0 {
+            ubicacion.push(linea.to_string());
+            if columna > 0 {
+                ubicacion.push(columna.to_string());
+            }
+        }
+
+        if ubicacion.is_empty() {
+            mensaje.clone()
+        } else {
+            Mensaje::termino(Termino::Ubicado).con(ubicacion.join(":")).con(mensaje.clone())
+        }
+    }
+
+    // Línea del error con el tramo señalado debajo:
+    //
+    //    12 | Informar(total)
+    //       |          ^^^^^
+    //
+    // Si el tramo sigue en otras líneas se subraya hasta el final de la
+    // primera, y si el error no tiene columna se subraya la línea entera.
+    // `None` si el error no tiene línea o el archivo no la tiene.
+    pub fn subrayar(&self, error: &CompilerError) -> Option<String> {
+        let texto = self.archivo(error.archivo.unwrap_or(FileId::PRINCIPAL))?.linea(error.line)?;
+        let span = error.span();
+        let largo = texto.chars().count();
+        let (inicio, fin) = if error.column == 0 {
+            (largo - texto.trim_start().chars().count() + 1, largo + 1)
+        } else if span.fin_linea > span.linea {
+            (span.columna.min(largo + 1), largo + 1)
+        } else {
+            (span.columna.min(largo + 1), span.fin_columna.min(largo + 1))
+        };
+        let numero = error.line.to_string();
+        Some(format!(
+            "{} | {}\n{} | {}{}",
+            numero,
+            texto,
+            " ".repeat(numero.len()),
+            " ".repeat(inicio - 1),
+            "^".repeat(fin.saturating_sub(inicio).max(1))
+        ))
+    }
+}
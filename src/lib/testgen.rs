@@ -0,0 +1,287 @@
+use crate::azar::Azar;
+use crate::interpreter::world::TAMANIO_CIUDAD;
+
+// Sangría de cada nivel de bloque, la misma del formateador
+const SANGRIA: &str = "    ";
+
+// Variables numéricas de cada proceso y robot, además de los contadores de
+// los `mientras`
+const VARIABLES: usize = 3;
+
+// Forma del programa que arma `generar`
+#[derive(Debug, Clone, Copy)]
+pub struct Configuracion {
+    pub procesos: usize,
+    pub robots: usize,
+    // Anidamiento máximo de las sentencias de control
+    pub profundidad: usize,
+    // Instrucciones de cada bloque, sin contar las que inician variables
+    pub instrucciones: usize,
+    // La misma semilla genera siempre el mismo programa
+    pub semilla: u64,
+}
+
+impl Default for Configuracion {
+    fn default() -> Self {
+        Self {
+            procesos: 4,
+            robots: 2,
+            profundidad: 2,
+            instrucciones: 4,
+            semilla: 1,
+        }
+    }
+}
+
+// Genera un programa válido del tamaño pedido, para benchmarks y para
+// probar el analizador y la simulación con muchos robots. Además de
+// compilar, el programa termina sin errores de ejecución: los ciclos tienen
+// una cantidad acotada de vueltas, los robots sólo avanzan mirando al norte y
+// si no están en la última calle, toman flores sólo si hay y cada proceso
+// llama a lo sumo al anterior, así que no hay recursión.
+pub fn generar(configuracion: &Configuracion) -> String {
+    let mut generador = Generador {
+        configuracion: *configuracion,
+        azar: Azar::new(configuracion.semilla),
+        salida: String::new(),
+        nivel: 0,
+    };
+    generador.programa();
+    generador.salida
+}
+
+struct Generador {
+    configuracion: Configuracion,
+    azar: Azar,
+    salida: String,
+    nivel: usize,
+}
+
+// Desde dónde se generan las instrucciones de un bloque
+#[derive(Clone, Copy)]
+enum Seccion {
+    // El proceso con ese número, que recibe `limite` y `total`
+    Proceso(usize),
+    Robot,
+}
+
+impl Generador {
+    fn aleatorio(&mut self, minimo: usize, maximo: usize) -> usize {
+        self.azar.entre(minimo as i64, maximo as i64) as usize
+    }
+
+    fn linea(&mut self, texto: &str) {
+        for _ in 0..self.nivel {
+            self.salida.push_str(SANGRIA);
+        }
+        self.salida.push_str(texto);
+        self.salida.push('\n');
+    }
+
+    // Ejecuta `contenido` un nivel más adentro
+    fn indentado(&mut self, contenido: impl FnOnce(&mut Self)) {
+        self.nivel += 1;
+        contenido(self);
+        self.nivel -= 1;
+    }
+
+    fn programa(&mut self) {
+        let configuracion = self.configuracion;
+        self.linea(&format!("programa generado{}", configuracion.semilla));
+
+        if configuracion.procesos > 0 {
+            self.linea("procesos");
+            self.indentado(|generador| {
+                for i in 0..configuracion.procesos {
+                    generador.linea(&format!("proceso p{}(E limite: numero, ES total: numero)", i));
+                    generador.seccion(Seccion::Proceso(i));
+                }
+            });
+        }
+
+        self.linea("areas");
+        self.indentado(|generador| {
+            generador.linea(&format!("ciudad : AreaC(1, 1, {}, {})", TAMANIO_CIUDAD, TAMANIO_CIUDAD));
+        });
+
+        self.linea("robots");
+        self.indentado(|generador| {
+            for i in 0..configuracion.robots {
+                generador.linea(&format!("robot tipo{}", i));
+                generador.seccion(Seccion::Robot);
+            }
+        });
+
+        self.linea("variables");
+        self.indentado(|generador| {
+            for i in 0..configuracion.robots {
+                generador.linea(&format!("r{} : tipo{}", i, i));
+            }
+        });
+
+        // Cada robot empieza en su propia esquina
+        self.linea("comenzar");
+        self.indentado(|generador| {
+            for i in 0..configuracion.robots {
+                generador.linea(&format!("AsignarArea(r{}, ciudad)", i));
+            }
+            for i in 0..configuracion.robots {
+                let tamanio = TAMANIO_CIUDAD as usize;
+                generador.linea(&format!("Iniciar(r{}, {}, {})", i, i % tamanio + 1, i / tamanio % tamanio + 1));
+            }
+        });
+        self.linea("fin");
+    }
+
+    // Variables y cuerpo de un proceso o robot
+    fn seccion(&mut self, seccion: Seccion) {
+        let profundidad = self.configuracion.profundidad;
+        self.linea("variables");
+        self.indentado(|generador| {
+            for i in 0..VARIABLES {
+                generador.linea(&format!("v{} : numero", i));
+            }
+            for i in 0..profundidad {
+                generador.linea(&format!("c{} : numero", i));
+            }
+            generador.linea("hay : booleano");
+        });
+
+        self.linea("comenzar");
+        self.indentado(|generador| {
+            for i in 0..VARIABLES {
+                generador.linea(&format!("v{} := {}", i, i));
+            }
+            generador.linea("hay := F");
+
+            // Las llamadas sólo van en el nivel superior del cuerpo, para que
+            // la cantidad de llamadas crezca con el programa y no con los ciclos
+            let llamadas = match seccion {
+                Seccion::Proceso(0) => 0,
+                Seccion::Proceso(_) => generador.aleatorio(0, 1),
+                Seccion::Robot => generador.aleatorio(1, 3),
+            };
+            generador.bloque(seccion, 0);
+            for _ in 0..llamadas.min(generador.configuracion.procesos) {
+                generador.llamada(seccion);
+            }
+        });
+        self.linea("fin");
+    }
+
+    fn bloque(&mut self, seccion: Seccion, anidamiento: usize) {
+        for _ in 0..self.configuracion.instrucciones.max(1) {
+            self.instruccion(seccion, anidamiento);
+        }
+    }
+
+    fn instruccion(&mut self, seccion: Seccion, anidamiento: usize) {
+        let opciones = if anidamiento < self.configuracion.profundidad { 11 } else { 8 };
+        match self.aleatorio(0, opciones - 1) {
+            0 | 1 => {
+                let destino = self.variable();
+                let valor = self.valor(seccion);
+                self.linea(&format!("{} := {}", destino, valor));
+            }
+            2 => {
+                let valor = self.valor(seccion);
+                self.linea(&format!("Informar({})", valor));
+            }
+            3 => {
+                self.linea(&format!("si PosCa < {}", TAMANIO_CIUDAD));
+                self.indentado(|generador| generador.linea("mover"));
+            }
+            4 => {
+                let (av, ca) = (self.aleatorio(1, TAMANIO_CIUDAD as usize), self.aleatorio(1, TAMANIO_CIUDAD as usize));
+                self.linea(&format!("Pos({}, {})", av, ca));
+            }
+            5 => {
+                self.linea("si HayFlorEnLaEsquina");
+                self.indentado(|generador| generador.linea("tomarFlor"));
+                self.linea("hay := HayFlorEnLaBolsa");
+            }
+            6 => {
+                self.linea("si hay & HayFlorEnLaBolsa");
+                self.indentado(|generador| generador.linea("depositarFlor"));
+            }
+            // Cuatro giros dejan al robot mirando al norte otra vez
+            7 => {
+                self.linea("repetir 4");
+                self.indentado(|generador| generador.linea("derecha"));
+            }
+            8 => {
+                let condicion = self.condicion(seccion);
+                self.linea(&format!("si {}", condicion));
+                self.indentado(|generador| generador.bloque(seccion, anidamiento + 1));
+                if self.aleatorio(0, 1) == 0 {
+                    self.linea("sino");
+                    self.indentado(|generador| generador.bloque(seccion, anidamiento + 1));
+                }
+            }
+            9 => {
+                let vueltas = self.aleatorio(1, 3);
+                self.linea(&format!("repetir {}", vueltas));
+                self.indentado(|generador| generador.bloque(seccion, anidamiento + 1));
+            }
+            // Cada nivel de anidamiento tiene su propio contador, que sólo
+            // incrementa su `mientras`
+            _ => {
+                let vueltas = self.aleatorio(1, 3);
+                self.linea(&format!("c{} := 0", anidamiento));
+                self.linea(&format!("mientras c{} < {}", anidamiento, vueltas));
+                self.indentado(|generador| {
+                    generador.bloque(seccion, anidamiento + 1);
+                    generador.linea(&format!("c{} := c{} + 1", anidamiento, anidamiento));
+                });
+            }
+        }
+    }
+
+    // Llamada a un proceso: los procesos llaman sólo al anterior y los robots
+    // a cualquiera
+    fn llamada(&mut self, seccion: Seccion) {
+        let proceso = match seccion {
+            Seccion::Proceso(i) => i - 1,
+            Seccion::Robot => self.aleatorio(0, self.configuracion.procesos - 1),
+        };
+        let limite = self.valor(seccion);
+        let total = match seccion {
+            Seccion::Proceso(_) => "total".to_string(),
+            Seccion::Robot => self.variable(),
+        };
+        self.linea(&format!("p{}({}, {})", proceso, limite, total));
+    }
+
+    fn variable(&mut self) -> String {
+        format!("v{}", self.aleatorio(0, VARIABLES - 1))
+    }
+
+    // Los valores suman a lo sumo una constante chica a una variable, así que
+    // no crecen lo suficiente para desbordar
+    fn valor(&mut self, seccion: Seccion) -> String {
+        let operando = match (seccion, self.aleatorio(0, 3)) {
+            (Seccion::Proceso(_), 0) => "limite".to_string(),
+            (Seccion::Proceso(_), 1) => "total".to_string(),
+            (_, 2) => "PosAv".to_string(),
+            _ => self.variable(),
+        };
+        match self.aleatorio(0, 3) {
+            0 => operando,
+            1 => format!("{} - {}", operando, self.aleatorio(0, 9)),
+            2 => format!("{} * {}", self.aleatorio(0, 9), self.aleatorio(0, 9)),
+            _ => format!("{} + {}", operando, self.aleatorio(0, 9)),
+        }
+    }
+
+    fn condicion(&mut self, seccion: Seccion) -> String {
+        const COMPARACIONES: [&str; 6] = ["<", "<=", ">", ">=", "=", "<>"];
+        let comparacion = COMPARACIONES[self.aleatorio(0, COMPARACIONES.len() - 1)];
+        let valor = self.valor(seccion);
+        let simple = format!("{} {} {}", valor, comparacion, self.aleatorio(0, 20));
+        match self.aleatorio(0, 3) {
+            0 => format!("{} & HayFlorEnLaEsquina", simple),
+            1 => format!("{} | hay", simple),
+            _ => simple,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// Trazas del compilador y del intérprete con `tracing`: un span por fase
+// (lex, parse, analizar, ejecutar) y uno por cada paso de un robot. Sin la
+// feature `tracing` las macros no generan código y el núcleo sigue sin
+// dependencias.
+//
+//   let _fase = traza::span!(INFO, "parse");
+//   traza::evento!(DEBUG, procesos = 3, "programa parseado");
+//
+// El nivel es uno de `tracing::Level`; el resto de los argumentos es el de
+// `tracing::span!` y `tracing::event!`, y sólo se evalúa si el nivel está
+// habilitado.
+
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($nivel:ident, $($argumentos:tt)+) => {
+        ::tracing::span!(::tracing::Level::$nivel, $($argumentos)+).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($nivel:ident, $($argumentos:tt)+) => {
+        $crate::traza::SinTraza
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! evento {
+    ($nivel:ident, $($argumentos:tt)+) => {
+        ::tracing::event!(::tracing::Level::$nivel, $($argumentos)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! evento {
+    ($nivel:ident, $($argumentos:tt)+) => {};
+}
+
+pub(crate) use {evento, span};
+
+// Lo que devuelve `span!` sin la feature: no registra nada
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct SinTraza;
@@ -0,0 +1,273 @@
+use std::cell::Cell;
+use std::io;
+use std::time::{Duration, Instant};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use crate::interpreter::simulation::{EstadoSimulacion, Simulation};
+use crate::interpreter::world::{Direccion, Mundo, TAMANIO_CIUDAD};
+
+// Niveles de zoom: cantidad de esquinas (por lado) que se agrupan en una celda
+const ZOOMS: [i32; 4] = [1, 2, 5, 10];
+
+// Porción de la ciudad visible en pantalla. `av` y `ca` son la esquina
+// inferior izquierda; las calles crecen hacia arriba como en el mapa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vista {
+    pub av: i32,
+    pub ca: i32,
+    pub zoom: i32,
+}
+
+impl Default for Vista {
+    fn default() -> Self {
+        Self { av: 1, ca: 1, zoom: 1 }
+    }
+}
+
+impl Vista {
+    pub fn desplazar(&mut self, avenidas: i32, calles: i32) {
+        self.av = (self.av + avenidas * self.zoom).clamp(1, TAMANIO_CIUDAD);
+        self.ca = (self.ca + calles * self.zoom).clamp(1, TAMANIO_CIUDAD);
+    }
+
+    pub fn acercar(&mut self) {
+        if let Some(posicion) = ZOOMS.iter().position(|&zoom| zoom == self.zoom) {
+            self.zoom = ZOOMS[posicion.saturating_sub(1)];
+        }
+    }
+
+    pub fn alejar(&mut self) {
+        if let Some(posicion) = ZOOMS.iter().position(|&zoom| zoom == self.zoom) {
+            self.zoom = ZOOMS[(posicion + 1).min(ZOOMS.len() - 1)];
+        }
+    }
+
+    // Centra la vista en una esquina para una grilla de `columnas` x `filas` celdas
+    pub fn centrar(&mut self, av: i32, ca: i32, columnas: u16, filas: u16) {
+        self.av = (av - i32::from(columnas) * self.zoom / 2).clamp(1, TAMANIO_CIUDAD);
+        self.ca = (ca - i32::from(filas) * self.zoom / 2).clamp(1, TAMANIO_CIUDAD);
+    }
+}
+
+fn flecha(direccion: Direccion) -> char {
+    match direccion {
+        Direccion::Norte => '↑',
+        Direccion::Este => '→',
+        Direccion::Sur => '↓',
+        Direccion::Oeste => '←',
+    }
+}
+
+// Símbolo de una celda que agrupa las esquinas [av, av+zoom) x [ca, ca+zoom).
+// Un robot tapa a las flores y papeles de su celda.
+fn simbolo(mundo: &Mundo, av: i32, ca: i32, zoom: i32) -> char {
+    let contiene = |x: i32, y: i32| x >= av && x < av + zoom && y >= ca && y < ca + zoom;
+
+    if let Some(robot) = mundo.robots.iter().find(|robot| robot.iniciado && contiene(robot.av, robot.ca)) {
+        return flecha(robot.direccion);
+    }
+
+    let (mut flores, mut papeles) = (false, false);
+    for x in av..av + zoom {
+        for y in ca..ca + zoom {
+            if let Some(esquina) = mundo.ciudad.esquina(x, y) {
+                flores |= esquina.flores > 0;
+                papeles |= esquina.papeles > 0;
+            }
+        }
+    }
+
+    match (flores, papeles) {
+        (true, true) => '#',
+        (true, false) => '*',
+        (false, true) => '+',
+        (false, false) => '·',
+    }
+}
+
+// Dibuja la porción visible de la ciudad, una cadena por fila y de norte a sur
+pub fn renderizar_grilla(mundo: &Mundo, vista: &Vista, columnas: u16, filas: u16) -> Vec<String> {
+    (0..i32::from(filas))
+        .rev()
+        .map(|fila| vista.ca + fila * vista.zoom)
+        .filter(|&ca| ca <= TAMANIO_CIUDAD)
+        .map(|ca| {
+            (0..i32::from(columnas))
+                .map(|columna| vista.av + columna * vista.zoom)
+                .take_while(|&av| av <= TAMANIO_CIUDAD)
+                .map(|av| format!("{} ", simbolo(mundo, av, ca, vista.zoom)))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+// Posición, dirección y bolsa de cada robot, junto con lo que hay en su esquina
+pub fn renderizar_robots(mundo: &Mundo) -> Vec<String> {
+    mundo.robots
+        .iter()
+        .map(|robot| {
+            if !robot.iniciado {
+                return format!("{}: sin iniciar", robot.nombre);
+            }
+            let (flores, papeles) = mundo.ciudad
+                .esquina(robot.av, robot.ca)
+                .map_or((0, 0), |esquina| (esquina.flores, esquina.papeles));
+            format!(
+                "{} {} ({}, {}) bolsa: {}f {}p | esquina: {}f {}p",
+                flecha(robot.direccion), robot.nombre, robot.av, robot.ca,
+                robot.flores, robot.papeles, flores, papeles
+            )
+        })
+        .collect()
+}
+
+// Visualización interactiva de una simulación. Cada tick del temporizador
+// ejecuta un paso del planificador mientras no esté en pausa.
+pub struct Visor {
+    simulacion: Simulation,
+    vista: Vista,
+    intervalo: Duration,
+    pausado: bool,
+    // Robot en el que se centra la vista con la tecla `c`
+    seguido: usize,
+    mensaje: String,
+    // Columnas y filas de la grilla en el último dibujo
+    tamanio: Cell<(u16, u16)>,
+}
+
+impl Visor {
+    pub fn new(simulacion: Simulation, intervalo: Duration) -> Self {
+        Self {
+            simulacion,
+            vista: Vista::default(),
+            intervalo,
+            pausado: true,
+            seguido: 0,
+            mensaje: String::new(),
+            tamanio: Cell::new((0, 0)),
+        }
+    }
+
+    pub fn ejecutar(mut self) -> io::Result<Simulation> {
+        let mut terminal = ratatui::init();
+        let resultado = self.bucle(&mut terminal);
+        ratatui::restore();
+        resultado.map(|_| self.simulacion)
+    }
+
+    fn bucle(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let mut ultimo_tick = Instant::now();
+        loop {
+            terminal.draw(|frame| self.dibujar(frame))?;
+
+            let espera = self.intervalo.saturating_sub(ultimo_tick.elapsed());
+            if event::poll(espera)?
+                && let Event::Key(tecla) = event::read()?
+                && tecla.kind == KeyEventKind::Press
+                && !self.tecla(tecla.code)
+            {
+                return Ok(());
+            }
+
+            if ultimo_tick.elapsed() >= self.intervalo {
+                if !self.pausado {
+                    self.avanzar();
+                }
+                ultimo_tick = Instant::now();
+            }
+        }
+    }
+
+    // Procesa una tecla; devuelve false si hay que salir
+    fn tecla(&mut self, codigo: KeyCode) -> bool {
+        match codigo {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char(' ') => self.pausado = !self.pausado,
+            KeyCode::Char('n') => self.avanzar(),
+            KeyCode::Up => self.vista.desplazar(0, 1),
+            KeyCode::Down => self.vista.desplazar(0, -1),
+            KeyCode::Right => self.vista.desplazar(1, 0),
+            KeyCode::Left => self.vista.desplazar(-1, 0),
+            KeyCode::Char('+') => self.vista.acercar(),
+            KeyCode::Char('-') => self.vista.alejar(),
+            KeyCode::Char('c') => self.seguir_siguiente(),
+            _ => {}
+        }
+        true
+    }
+
+    fn avanzar(&mut self) {
+        match self.simulacion.paso() {
+            Ok(EstadoSimulacion::Ejecutando) => {}
+            Ok(EstadoSimulacion::Terminada) => {
+                self.pausado = true;
+                self.mensaje = "Simulación terminada".to_string();
+            }
+            Ok(EstadoSimulacion::Bloqueada) => {
+                self.pausado = true;
+                self.mensaje = "Todos los robots están bloqueados".to_string();
+            }
+            Err(error) => {
+                self.pausado = true;
+                self.mensaje = format!("Error: {}", error);
+            }
+        }
+    }
+
+    fn seguir_siguiente(&mut self) {
+        let robots = &self.simulacion.mundo().robots;
+        if robots.is_empty() {
+            return;
+        }
+        self.seguido = (self.seguido + 1) % robots.len();
+        let robot = &robots[self.seguido];
+        let (columnas, filas) = self.tamanio.get();
+        self.vista.centrar(robot.av, robot.ca, columnas, filas);
+    }
+
+    fn dibujar(&self, frame: &mut Frame) {
+        let [grilla, lateral] = Layout::horizontal([Constraint::Min(20), Constraint::Length(48)])
+            .areas(frame.area());
+
+        // Cada celda ocupa dos caracteres para que la grilla se vea cuadrada
+        let columnas = grilla.width.saturating_sub(2) / 2;
+        let filas = grilla.height.saturating_sub(2);
+        self.tamanio.set((columnas, filas));
+        let lineas: Vec<Line> = renderizar_grilla(self.simulacion.mundo(), &self.vista, columnas, filas)
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        let titulo = format!(
+            " Ciudad av {}+ ca {}+ (zoom 1:{}) ",
+            self.vista.av, self.vista.ca, self.vista.zoom
+        );
+        frame.render_widget(
+            Paragraph::new(lineas).block(Block::default().borders(Borders::ALL).title(titulo)),
+            grilla,
+        );
+
+        let estado = if self.pausado { "en pausa" } else { "ejecutando" };
+        let mut texto: Vec<Line> = vec![
+            Line::from(format!("Pasos: {} ({})", self.simulacion.pasos(), estado)),
+            Line::from(""),
+        ];
+        texto.extend(renderizar_robots(self.simulacion.mundo()).into_iter().map(Line::from));
+        texto.push(Line::from(""));
+        texto.push(Line::from("* flores  + papeles  # ambos"));
+        texto.push(Line::from("espacio: pausa  n: paso  c: centrar"));
+        texto.push(Line::from("flechas: mover  +/-: zoom  q: salir"));
+        if !self.mensaje.is_empty() {
+            texto.push(Line::from(""));
+            texto.push(Line::from(self.mensaje.clone()));
+        }
+        frame.render_widget(
+            Paragraph::new(texto).block(Block::default().borders(Borders::ALL).title(" Robots ")),
+            lateral,
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::Path;
+use serde::de::DeserializeOwned;
+use wasm_bindgen::prelude::*;
+use crate::compilerError::CompilerError;
+use crate::driver::{compilar_archivo_con, Opciones, NOMBRE_ENTRADA};
+use crate::interpreter::simulation::{EstadoSimulacion, Simulation, SimulationState};
+use crate::interpreter::world::Ciudad;
+use crate::resaltado;
+use crate::semanticizer::lints::ConfiguracionLints;
+
+fn a_error(error: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+fn a_js<T: serde::Serialize>(valor: &T) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(valor).map_err(a_error)
+}
+
+// Objeto de opciones de JavaScript; sin él, las opciones por defecto
+fn desde_js<T: DeserializeOwned + Default>(valor: JsValue) -> Result<T, JsValue> {
+    if valor.is_undefined() || valor.is_null() {
+        return Ok(T::default());
+    }
+    serde_wasm_bindgen::from_value(valor).map_err(a_error)
+}
+
+// Opciones de `compile` y de `SimulationHandle`, como las de Node; las que
+// faltan toman el valor por defecto del compilador
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileOptions {
+    pub flexible_sections: Option<bool>,
+    pub extensions: Option<bool>,
+    // Contenido de un archivo de `--lint-config`
+    pub lint_config: Option<String>,
+}
+
+impl CompileOptions {
+    pub fn opciones(&self) -> Result<Opciones, CompilerError> {
+        let lints = match &self.lint_config {
+            Some(texto) => ConfiguracionLints::desde_texto(texto)?,
+            None => ConfiguracionLints::default(),
+        };
+        Ok(Opciones {
+            secciones_flexibles: self.flexible_sections.unwrap_or(false),
+            extensiones: self.extensions.unwrap_or(false),
+            lints,
+            ..Opciones::default()
+        })
+    }
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationOptions {
+    pub compile: Option<CompileOptions>,
+    // Contenido de un archivo de mundo, como el de `--mundo`
+    pub world: Option<String>,
+}
+
+impl SimulationOptions {
+    // Ciudad del mundo indicado, o una vacía
+    pub fn ciudad(&self) -> Result<Ciudad, CompilerError> {
+        self.world.as_deref().map_or(Ok(Ciudad::new()), Ciudad::desde_texto)
+    }
+}
+
+// Compila el código fuente y devuelve tokens, AST y diagnósticos. `options`
+// es un `CompileOptions` opcional.
+#[wasm_bindgen]
+pub fn compile(source: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let opciones = desde_js::<CompileOptions>(options)?.opciones().map_err(a_error)?;
+    a_js(&compilar_archivo_con(source, Path::new(NOMBRE_ENTRADA), opciones))
+}
+
+// Tramos del código con su clase, para colorearlo
+#[wasm_bindgen]
+pub fn highlight(source: &str) -> Result<JsValue, JsValue> {
+    a_js(&resaltado::highlight(source))
+}
+
+// Simulación avanzable de a un paso desde JavaScript
+#[wasm_bindgen]
+pub struct SimulationHandle {
+    simulacion: Simulation,
+}
+
+#[wasm_bindgen]
+impl SimulationHandle {
+    // `options` es un `SimulationOptions` opcional. Con errores de
+    // compilación se lanza la lista de diagnósticos; con un mundo o una
+    // configuración inválidos, su mensaje.
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: &str, options: JsValue) -> Result<SimulationHandle, JsValue> {
+        let options = desde_js::<SimulationOptions>(options)?;
+        let opciones = options.compile.as_ref().map_or(Ok(Opciones::default()), CompileOptions::opciones).map_err(a_error)?;
+        let compilacion = compilar_archivo_con(source, Path::new(NOMBRE_ENTRADA), opciones);
+        let programa = match compilacion.programa {
+            Some(programa) if compilacion.errores.is_empty() => programa,
+            _ => return Err(a_js(&compilacion.errores)?),
+        };
+        let ciudad = options.ciudad().map_err(|error| a_error(format!("Mundo inválido: {}", error)))?;
+
+        let simulacion = Simulation::con_ciudad(&programa, ciudad).map_err(a_error)?;
+        Ok(SimulationHandle { simulacion })
+    }
+
+    // Ejecuta una operación y devuelve "Ejecutando", "Terminada" o "Bloqueada"
+    pub fn step(&mut self) -> Result<JsValue, JsValue> {
+        let estado = self.simulacion
+            .paso()
+            .map_err(a_error)?;
+        a_js(&estado)
+    }
+
+    pub fn run(&mut self, max_steps: u32) -> Result<JsValue, JsValue> {
+        let estado = self.simulacion
+            .ejecutar(u64::from(max_steps))
+            .map_err(a_error)?;
+        a_js(&estado)
+    }
+
+    // Ciudad y robots en el estado actual
+    pub fn state(&self) -> Result<JsValue, JsValue> {
+        a_js(self.simulacion.mundo())
+    }
+
+    // Estado completo serializado, para guardarlo y reanudarlo con `restore`
+    pub fn snapshot(&self) -> Result<JsValue, JsValue> {
+        a_js(&self.simulacion.snapshot())
+    }
+
+    pub fn restore(&mut self, state: JsValue) -> Result<(), JsValue> {
+        let estado: SimulationState = serde_wasm_bindgen::from_value(state)
+            .map_err(a_error)?;
+        self.simulacion
+            .restore(estado)
+            .map_err(a_error)
+    }
+
+    pub fn steps(&self) -> f64 {
+        self.simulacion.pasos() as f64
+    }
+
+    pub fn finished(&self) -> bool {
+        self.simulacion.estado() == EstadoSimulacion::Terminada
+    }
+}
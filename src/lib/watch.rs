@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Marca de un archivo: si cambia la fecha de modificación o el tamaño, el
+// archivo cambió. Un archivo que no existe no tiene marca.
+type Marca = Option<(SystemTime, u64)>;
+
+// Detecta cambios en un conjunto de archivos consultando su fecha de
+// modificación, sin depender de las notificaciones del sistema operativo
+pub struct Vigilante {
+    archivos: Vec<(PathBuf, Marca)>,
+}
+
+impl Vigilante {
+    pub fn new<P: Into<PathBuf>>(rutas: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            archivos: rutas.into_iter().map(|ruta| (ruta.into(), None)).collect(),
+        }
+    }
+
+    // Agrega un archivo tomando su estado actual, así que no cuenta como
+    // cambio hasta que se modifique
+    pub fn vigilar(&mut self, ruta: impl Into<PathBuf>) {
+        let ruta = ruta.into();
+        if self.archivos.iter().all(|(vigilada, _)| *vigilada != ruta) {
+            let marca = marca(&ruta);
+            self.archivos.push((ruta, marca));
+        }
+    }
+
+    // Indica si algún archivo cambió desde la consulta anterior. La primera
+    // consulta informa un cambio si algún archivo existe.
+    pub fn hubo_cambios(&mut self) -> bool {
+        let mut cambio = false;
+        for (ruta, anterior) in &mut self.archivos {
+            let actual = marca(ruta);
+            if actual != *anterior {
+                *anterior = actual;
+                cambio = true;
+            }
+        }
+        cambio
+    }
+}
+
+fn marca(ruta: &Path) -> Marca {
+    fs::metadata(ruta)
+        .ok()
+        .and_then(|datos| Some((datos.modified().ok()?, datos.len())))
+}
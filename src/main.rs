@@ -1,54 +1,1041 @@
-use crate::lib::lexer::scanner::Lexer;
-use crate::lib::lexer::token::Keywords;
-use crate::lib::parser::processor::Parser;
-use crate::lib::semanticizer::analizer::SemanticAnalyzer;
+use rinfo::bench::medir;
+use rinfo::driver::{compilar_archivo_con, FormatoMensajes, Opciones};
+use rinfo::eventos::compilar_con_eventos;
+use rinfo::incremental::CompiladorIncremental;
+use rinfo::interpreter::debugger::{Comando, Debugger};
+use rinfo::interpreter::diagrama::secuencia_mensajes;
+use rinfo::interpreter::evaluacion::Evaluacion;
+use rinfo::interpreter::exploracion::{explorar, Limites};
+use rinfo::interpreter::machine::Interpreter;
+use rinfo::interpreter::planificador::{Aleatorio, PorPrioridad, RoundRobin, SchedulerPolicy};
+use rinfo::interpreter::reporte::Reporte;
+use rinfo::interpreter::runtime::RuntimeError;
+use rinfo::interpreter::simulation::{EstadoSimulacion, Simulation};
+use rinfo::interpreter::world::Ciudad;
+use rinfo::listado::listado_html;
+use rinfo::lote::{Escenario, Lote};
+use rinfo::mensajes::{Idioma, Mensaje, Termino};
+use rinfo::parser::processor::Program;
+use rinfo::proyecto::{buscar_proyecto, Proyecto};
+use rinfo::semanticizer::diferencias::Diferencias;
+use rinfo::semanticizer::inventario::Inventario;
+use rinfo::semanticizer::lints::{ConfiguracionLints, RegistroLints};
+use rinfo::semanticizer::metricas::Metricas;
+use rinfo::source::SourceMap;
+use rinfo::watch::Vigilante;
+use std::env;
 use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-mod lib;
+#[cfg(test)]
 mod tests;
 
+const AYUDA_DEBUG: &str = "Comandos:
+  step (s)                 ejecuta una operación
+  next (n)                 avanza el robot actual hasta la siguiente línea
+  continue (c)             ejecuta hasta la próxima parada
+  print (p) <var> [robot]  muestra el valor de una variable
+  break (b) <línea> [robot]
+  delete (d) <línea> [robot]
+  watch (w) <var>          se detiene cuando la variable cambia
+  mensajes (m)             activa/desactiva la parada en EnviarMensaje
+  robots (r)               lista los robots y su posición
+  quit (q)";
+
+// Programa que se compila cuando no se indica un archivo
+const ARCHIVO_PRUEBA: &str = "tests/fixtures/codigo.txt";
+
+// Máximo de pasos que ejecuta `continue` antes de devolver el control
+const PASOS_CONTINUE: u64 = 1_000_000;
+
+// Cantidad de líneas que muestra `--profile`
+const LINEAS_PERFIL: usize = 10;
+
+// Pasos entre cuadros de `--animate` si no se indica `--frame-every`
+const PASOS_POR_CUADRO: u64 = 10;
+
+// Cada cuánto se revisa si los archivos vigilados cambiaron
+const INTERVALO_WATCH: Duration = Duration::from_millis(300);
+
+// Informa un error en los argumentos y termina con código 2, el mismo que
+// usa `diff` cuando no puede comparar, para distinguirlo de un programa que
+// no compila o falla (código 1)
+macro_rules! uso {
+    ($($argumento:tt)*) => {{
+        eprintln!($($argumento)*);
+        std::process::exit(2)
+    }};
+}
+
 fn main() {
-    let source = fs::read_to_string("src/tests/codigo.txt")
-        .expect("Failed to read source file");
-    let mut lx = Lexer::new(&source);
-    match lx.tokenize() {
-        Ok(tokens) => {
-            //Lexer
-            for token in &tokens {
-                println!("{:?}", token);
-            }
-            
-            //Parser
-            
-            let mut parser = Parser::new(&tokens);
-
-            match parser.parse() {
-                Ok(ast) => {
-                    println!("{:?}", ast);
-                    println!("\n");
-
-                    //Semantic Analyzer
-                    let mut analyzer = SemanticAnalyzer::new();
-
-                    match analyzer.analizar(&ast) {
-                        Ok(_) => {
-                            analyzer.mostrar_resultados();
-                            
-                        }
-                        Err(errores) => {
-                            //analyzer.mostrar_resultados();
-                            println!("{:?}", errores);
-                        }
-                    }
+    let mut argumentos: Vec<String> = env::args().collect();
+
+    // `-v`/`-vv`, `--flexible-sections`, `--extensions`, `--lang es|en`,
+    // `--message-format human|json`, `--lint-config <archivo>` y `--config
+    // <archivo>` valen para cualquier comando: muestran las trazas de la
+    // compilación y la ejecución, aceptan las secciones del programa en cualquier orden,
+    // habilitan las instrucciones que no son de R-Info, eligen el idioma y
+    // el formato de los diagnósticos, configuran las reglas de advertencia y leen la
+    // configuración del proyecto de otro archivo que el `rinfo.toml` más
+    // cercano. Lo que se indica acá tiene prioridad sobre la configuración.
+    // `--lang` sólo traduce los diagnósticos de la compilación: los errores
+    // de ejecución y los mensajes propios de cada comando están en español.
+    let verbosidad = argumentos.iter()
+        .map(|argumento| match argumento.as_str() {
+            "-v" => 1,
+            "-vv" => 2,
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0);
+    argumentos.retain(|argumento| argumento != "-v" && argumento != "-vv");
+    iniciar_trazas(verbosidad);
+
+    let secciones_flexibles = argumentos.iter().any(|argumento| argumento == "--flexible-sections");
+    let extensiones = argumentos.iter().any(|argumento| argumento == "--extensions");
+    argumentos.retain(|argumento| argumento != "--flexible-sections" && argumento != "--extensions");
+    let idioma = match extraer_opcion(&mut argumentos, "--lang") {
+        None => None,
+        Some(codigo) => match Idioma::desde_codigo(&codigo) {
+            Some(idioma) => Some(idioma),
+            None => uso!("Idioma desconocido para --lang: '{}' (se admite 'es' o 'en', y se aplica sólo a los diagnósticos de la compilación)", codigo),
+        },
+    };
+    let formato_mensajes = match extraer_opcion(&mut argumentos, "--message-format").as_deref() {
+        None | Some("human") => FormatoMensajes::Humano,
+        Some("json") => FormatoMensajes::Json,
+        Some(formato) => uso!("Formato desconocido para --message-format: '{}' (se admite 'human' o 'json')", formato),
+    };
+    let configuracion = extraer_opcion(&mut argumentos, "--config").map(PathBuf::from)
+        .or_else(|| env::current_dir().ok().and_then(|directorio| buscar_proyecto(&directorio)));
+    let proyecto = match configuracion {
+        None => Proyecto::default(),
+        Some(archivo) => match leer_proyecto(&archivo, idioma.unwrap_or_default()) {
+            Ok(proyecto) => proyecto,
+            Err(e) => uso!("{}", e),
+        },
+    };
+
+    let mut opciones = proyecto.opciones.clone();
+    opciones.secciones_flexibles |= secciones_flexibles;
+    opciones.extensiones |= extensiones;
+    opciones.idioma = idioma.unwrap_or(opciones.idioma);
+    opciones.formato_mensajes = formato_mensajes;
+    if let Some(archivo) = extraer_opcion(&mut argumentos, "--lint-config") {
+        match leer_configuracion_lints(&archivo, opciones.idioma) {
+            Ok(lints) => opciones.lints.ajustes.extend(lints.ajustes),
+            Err(e) => uso!("{}", e),
+        }
+    }
+
+    // `--timings [<archivo>]` mide cada fase de la compilación en lugar de
+    // ejecutar un comando; el archivo puede ir antes o después
+    if let Some(posicion) = argumentos.iter().position(|argumento| argumento == "--timings") {
+        argumentos.remove(posicion);
+        return match &argumentos[1..] {
+            [] => cronometrar(ARCHIVO_PRUEBA),
+            [ruta] if !ruta.starts_with("--") => cronometrar(ruta),
+            _ => uso!("Uso: {} --timings [<archivo>]", argumentos[0]),
+        };
+    }
+
+    // `--watch <archivo>` recompila cada vez que cambia el archivo o su
+    // mundo; la bandera, el archivo y sus opciones van en cualquier orden
+    if let Some(posicion) = argumentos.iter().position(|argumento| argumento == "--watch") {
+        argumentos.remove(posicion);
+        return vigilar(&argumentos[1..], opciones, &proyecto);
+    }
+
+    match (argumentos.get(1).map(String::as_str), argumentos.get(2)) {
+        (Some("debug"), Some(ruta)) => return depurar(ruta, opciones),
+        (Some("verificar"), Some(ruta)) => return verificar(ruta, &argumentos[3..], opciones, &proyecto),
+        (Some("ejecutar"), Some(ruta)) => return ejecutar(ruta, &argumentos[3..], opciones, &proyecto),
+        (Some("explorar"), Some(ruta)) => return explorar_intercalados(ruta, &argumentos[3..], opciones, &proyecto),
+        (Some("batch"), Some(directorio)) => return corregir_lote(directorio, &argumentos[3..], opciones),
+        (Some("diff"), Some(viejo)) if argumentos.len() == 4 => return comparar_versiones(viejo, &argumentos[3], opciones),
+        #[cfg(feature = "tui")]
+        (Some("tui"), Some(ruta)) => return visualizar(ruta, opciones),
+        (Some("verificar"), None) => {
+            uso!("Uso: {} verificar <archivo> <afirmaciones> [--mundo <archivo>]", argumentos[0]);
+        }
+        (Some("ejecutar"), None) => {
+            uso!("Uso: {} ejecutar <archivo> [--mundo <archivo>] [--report <archivo.json|.html>] [--emit mermaid|symbols|metrics[:json]|html] [--inline <umbral>] [--profile] [--render <archivo.svg|.png>] [--animate <archivo.gif>] [--frame-every <pasos>] [--scheduler round-robin|random[:semilla]|priority:<robot>=<n>,...]", argumentos[0]);
+        }
+        (Some("batch"), None) => {
+            uso!("Uso: {} batch <directorio> [--mundo <archivo>] [--afirmaciones <archivo>] [--steps <n>] [--emit csv|json]", argumentos[0]);
+        }
+        (Some("diff"), _) => {
+            uso!("Uso: {} diff <viejo> <nuevo>", argumentos[0]);
+        }
+        (Some("explorar"), None) => {
+            uso!("Uso: {} explorar <archivo> [--mundo <archivo>] [--preemptions <n>] [--runs <n>] [--steps <n>]", argumentos[0]);
+        }
+        (Some(comando @ ("debug" | "tui")), None) => {
+            uso!("Uso: {} {} <archivo>", argumentos[0], comando);
+        }
+        #[cfg(not(feature = "tui"))]
+        (Some("tui"), Some(_)) => {
+            uso!("El comando 'tui' necesita compilar con `--features tui`");
+        }
+        (Some(otro), _) => {
+            uso!(
+                "Comando u opción desconocida: '{}'\n\
+                 Comandos: ejecutar, verificar, explorar, batch, diff, debug, tui, --watch y --timings; sin comando se compila '{}'\n\
+                 Opciones de todos los comandos: -v, -vv, --flexible-sections, --extensions, --lang es|en (sólo para los \
+                 diagnósticos de la compilación), --message-format human|json, --lint-config <archivo>, --config <archivo>",
+                otro, ARCHIVO_PRUEBA
+            );
+        }
+        (None, _) => {}
+    }
+
+    // Sin comando se compila el programa de prueba; con -v y -vv las trazas
+    // muestran cada fase, sus tokens y su AST. Con `--message-format json`
+    // la salida son sólo los eventos.
+    let json = opciones.formato_mensajes == FormatoMensajes::Json;
+    if let Some((programa, _)) = cargar_programa(ARCHIVO_PRUEBA, opciones).filter(|_| !json) {
+        println!(
+            "✓ '{}' compila: {} procesos y {} robots",
+            ARCHIVO_PRUEBA, programa.procesos.len(), programa.robots_definidos.len()
+        );
+    }
+}
+
+// `-v` traza las fases de la compilación y de la ejecución y los robots que
+// terminan; `-vv` además cada token, el AST y cada paso de cada robot. Las
+// trazas van a stderr.
+#[cfg(feature = "tracing")]
+fn iniciar_trazas(verbosidad: usize) {
+    let nivel = match verbosidad {
+        0 => return,
+        1 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(nivel)
+        .with_writer(io::stderr)
+        .init();
+}
+
+#[cfg(not(feature = "tracing"))]
+fn iniciar_trazas(verbosidad: usize) {
+    if verbosidad > 0 {
+        eprintln!("⚠ -v y -vv requieren compilar con la feature `tracing`");
+    }
+}
+
+// Recompila el programa cada vez que cambia el archivo fuente o el archivo de
+// mundo. Opciones: `--mundo <archivo>` y `--simular` para ejecutar el programa
+// después de cada compilación exitosa.
+fn vigilar(argumentos: &[String], opciones_compilacion: Opciones, proyecto: &Proyecto) {
+    let mut ruta = None;
+    let mut mundo = proyecto.mundo.as_deref();
+    let mut simular = false;
+    let mut argumentos = argumentos.iter();
+    while let Some(argumento) = argumentos.next() {
+        match argumento.as_str() {
+            "--mundo" => match argumentos.next() {
+                Some(archivo) => mundo = Some(archivo.as_str()),
+                None => uso!("Falta el archivo de mundo después de --mundo"),
+            },
+            "--simular" => simular = true,
+            otra if otra.starts_with('-') => uso!("Opción desconocida: {}", otra),
+            archivo if ruta.is_none() => ruta = Some(archivo),
+            otro => uso!("Argumento de más para --watch: '{}'", otro),
+        }
+    }
+    let Some(ruta) = ruta else {
+        uso!("Uso: --watch <archivo> [--mundo <archivo>] [--simular]");
+    };
+
+    let mut vigilante = Vigilante::new([Some(ruta), mundo].into_iter().flatten());
+    let idioma = opciones_compilacion.idioma;
+    let mut compilador = CompiladorIncremental::con_opciones(opciones_compilacion);
+    loop {
+        if vigilante.hubo_cambios() {
+            // Limpiar la pantalla antes de mostrar los nuevos diagnósticos
+            print!("\x1b[2J\x1b[H");
+            for incluido in recompilar(&mut compilador, ruta, mundo, simular, idioma) {
+                vigilante.vigilar(incluido);
+            }
+            let _ = io::stdout().flush();
+        }
+        thread::sleep(INTERVALO_WATCH);
+    }
+}
+
+// Devuelve los archivos incluidos por el programa, para vigilarlos también
+fn recompilar(compilador: &mut CompiladorIncremental, ruta: &str, mundo: Option<&str>, simular: bool, idioma: Idioma) -> Vec<PathBuf> {
+    let source = match fs::read_to_string(ruta) {
+        Ok(source) => source,
+        Err(e) => {
+            println!("No se pudo leer '{}': {}", ruta, e);
+            return Vec::new();
+        }
+    };
+
+    let compilacion = compilador.compilar_archivo(&source, Path::new(ruta));
+    let incluidos = compilacion.incluidos;
+    for error in &compilacion.errores {
+        println!("✗ {}", compilacion.fuentes.mostrar_en(error, idioma));
+        if let Some(subrayado) = compilacion.fuentes.subrayar(error) {
+            println!("{}", subrayado);
+        }
+        for detalle in compilacion.fuentes.detalles_en(error, idioma) {
+            println!("  {}", detalle);
+        }
+    }
+    for advertencia in &compilacion.advertencias {
+        println!("⚠ {}", advertencia.traducir(idioma));
+    }
+    let programa = match compilacion.programa {
+        Some(programa) if compilacion.errores.is_empty() => programa,
+        _ => return incluidos,
+    };
+    println!("✓ '{}' compilado sin errores", ruta);
+
+    if simular {
+        simular_programa(&programa, mundo);
+    }
+    incluidos
+}
+
+// Quita de los argumentos la opción `nombre` y su valor, y devuelve el valor
+fn extraer_opcion(argumentos: &mut Vec<String>, nombre: &str) -> Option<String> {
+    let posicion = argumentos.iter().position(|argumento| argumento == nombre)?;
+    let valor = argumentos.get(posicion + 1).cloned().unwrap_or_default();
+    argumentos.drain(posicion..(posicion + 2).min(argumentos.len()));
+    Some(valor)
+}
+
+// Configuración del proyecto leída de `archivo`, con los archivos que nombra
+// ubicados desde su directorio
+fn leer_proyecto(archivo: &Path, idioma: Idioma) -> Result<Proyecto, String> {
+    let texto = fs::read_to_string(archivo)
+        .map_err(|e| format!("No se pudo leer el archivo de configuración '{}': {}", archivo.display(), e))?;
+    let mut fuentes = SourceMap::new();
+    let id = fuentes.agregar(archivo.display().to_string(), &texto);
+    Proyecto::desde_texto(&texto)
+        .map(|proyecto| proyecto.relativo_a(archivo.parent().unwrap_or(Path::new(""))))
+        .map_err(|error| fuentes.mostrar_en(&error.en_archivo(id), idioma))
+}
+
+// Ajustes de las reglas de advertencia leídos de `archivo`. Una regla que
+// no existe se informa acá y no en cada compilación.
+fn leer_configuracion_lints(archivo: &str, idioma: Idioma) -> Result<ConfiguracionLints, String> {
+    let texto = fs::read_to_string(archivo)
+        .map_err(|e| format!("No se pudo leer el archivo de configuración '{}': {}", archivo, e))?;
+    let mut fuentes = SourceMap::new();
+    let id = fuentes.agregar(archivo.to_string(), &texto);
+    ConfiguracionLints::desde_texto(&texto)
+        .and_then(|configuracion| {
+            RegistroLints::predeterminado().configurar(&configuracion)?;
+            Ok(configuracion)
+        })
+        .map_err(|error| fuentes.mostrar_en(&error.en_archivo(id), idioma))
+}
+
+// Ciudad vacía o la leída del archivo de mundo
+fn leer_ciudad(mundo: Option<&str>) -> Result<Ciudad, String> {
+    match mundo.map(fs::read_to_string) {
+        None => Ok(Ciudad::new()),
+        Some(Ok(texto)) => Ciudad::desde_texto(&texto).map_err(|e| format!("Mundo inválido: {}", e)),
+        Some(Err(e)) => Err(format!("No se pudo leer el archivo de mundo: {}", e)),
+    }
+}
+
+fn simular_programa(programa: &Program, mundo: Option<&str>) {
+    let ciudad = match leer_ciudad(mundo) {
+        Ok(ciudad) => ciudad,
+        Err(e) => return println!("✗ {}", e),
+    };
+
+    let resultado = Simulation::con_ciudad(programa, ciudad)
+        .and_then(|mut simulacion| simulacion.ejecutar(PASOS_CONTINUE).map(|estado| (simulacion, estado)));
+    match resultado {
+        Ok((simulacion, estado)) => {
+            println!("Simulación: {:?} en {} pasos", estado, simulacion.pasos());
+            for robot in &simulacion.mundo().robots {
+                println!("  {} ({}) en ({}, {}), flores: {}, papeles: {}",
+                    robot.nombre, robot.tipo, robot.av, robot.ca, robot.flores, robot.papeles);
+                if !robot.informes.is_empty() {
+                    println!("    informó {}", robot.informes.join(" / "));
                 }
-                Err(e) => {
-                    println!("Error al generar el AST: {}", e);
+            }
+            for aviso in simulacion.avisos_livelock() {
+                println!("⚠ {}", aviso);
+            }
+        }
+        Err(e) => println!("✗ Error de ejecución: {}", e),
+    }
+}
+
+// Informa cuánto tarda cada fase del pipeline sobre el archivo
+fn cronometrar(ruta: &str) {
+    let source = match fs::read_to_string(ruta) {
+        Ok(source) => source,
+        Err(e) => return eprintln!("No se pudo leer '{}': {}", ruta, e),
+    };
+
+    match medir(&source) {
+        Ok(tiempos) => println!("{}", tiempos),
+        Err(errores) => {
+            for error in &errores {
+                eprintln!("{}", error);
+            }
+        }
+    }
+}
+
+// Ejecuta el programa y verifica las afirmaciones del archivo sobre el
+// mundo final, para corregir ejercicios. Termina con código 1 si alguna no
+// se cumple o si el programa no compila o falla. Opción: `--mundo <archivo>`.
+fn verificar(ruta: &str, argumentos: &[String], opciones: Opciones, proyecto: &Proyecto) {
+    let (afirmaciones, mundo) = match argumentos {
+        [afirmaciones] => (afirmaciones, proyecto.mundo.as_deref()),
+        [afirmaciones, opcion, mundo] if opcion == "--mundo" => (afirmaciones, Some(mundo.as_str())),
+        _ => uso!("Uso: verificar <archivo> <afirmaciones> [--mundo <archivo>]"),
+    };
+    let evaluacion = match fs::read_to_string(afirmaciones).map(|texto| Evaluacion::desde_texto(&texto)) {
+        Ok(Ok(evaluacion)) => evaluacion,
+        Ok(Err(e)) => return eprintln!("Afirmaciones inválidas: {}", e),
+        Err(e) => return eprintln!("No se pudo leer '{}': {}", afirmaciones, e),
+    };
+    let ciudad = match leer_ciudad(mundo) {
+        Ok(ciudad) => ciudad,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let Some(mut simulacion) = cargar_simulacion(ruta, ciudad, opciones, None) else {
+        std::process::exit(1);
+    };
+
+    // Si el programa falla se verifica igual el mundo en que quedó
+    let ejecucion = simulacion.ejecutar(PASOS_CONTINUE);
+    match &ejecucion {
+        Ok(estado) => println!("Simulación: {:?} en {} pasos", estado, simulacion.pasos()),
+        Err(e) => println!("✗ Error de ejecución: {}", e),
+    }
+    let informe = evaluacion.evaluar(simulacion.mundo());
+    println!("{}", informe);
+    if ejecucion.is_err() || !informe.aprobado() {
+        std::process::exit(1);
+    }
+}
+
+// Compara dos versiones de un programa por sus declaraciones y no por su
+// texto: procesos, robots, áreas y constantes agregados, eliminados o
+// modificados, con la cantidad de instrucciones de cada sección. Como
+// `diff`, termina con código 1 si hay diferencias.
+fn comparar_versiones(viejo: &str, nuevo: &str, opciones: Opciones) {
+    let Some((viejo, _)) = cargar_programa(viejo, opciones.clone()) else {
+        std::process::exit(2);
+    };
+    let Some((nuevo, _)) = cargar_programa(nuevo, opciones) else {
+        std::process::exit(2);
+    };
+    let diferencias = Diferencias::new(&viejo, &nuevo);
+    print!("{}", diferencias.a_texto());
+    if !diferencias.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+// Compila cada archivo `.ri` del directorio y muestra por stdout una fila
+// por archivo con sus errores y advertencias, en CSV o en JSON con `--emit
+// json`. Con `--mundo`, `--afirmaciones` o `--steps` además ejecuta los que
+// compilan en ese mundo y con ese máximo de pasos, y cuenta las
+// afirmaciones que se cumplen y los pasos que llevó.
+fn corregir_lote(directorio: &str, argumentos: &[String], opciones: Opciones) {
+    let mut mundo = None;
+    let mut afirmaciones = None;
+    let mut pasos = None;
+    let mut formato = "csv";
+    let mut argumentos = argumentos.iter();
+    while let Some(opcion) = argumentos.next() {
+        let Some(valor) = argumentos.next() else {
+            uso!("Falta un valor después de {}", opcion);
+        };
+        match opcion.as_str() {
+            "--mundo" => mundo = Some(valor.as_str()),
+            "--afirmaciones" => afirmaciones = Some(valor.as_str()),
+            "--steps" => pasos = Some(valor.as_str()),
+            "--emit" => formato = valor.as_str(),
+            otra => uso!("Opción desconocida: {}", otra),
+        }
+    }
+    if !matches!(formato, "csv" | "json") {
+        uso!("Formato desconocido para --emit: {} (se admite 'csv' o 'json')", formato);
+    }
+
+    let escenario = if mundo.is_some() || afirmaciones.is_some() || pasos.is_some() {
+        let ciudad = match leer_ciudad(mundo) {
+            Ok(ciudad) => ciudad,
+            Err(e) => return eprintln!("{}", e),
+        };
+        let evaluacion = match afirmaciones {
+            None => Evaluacion::new(),
+            Some(archivo) => match fs::read_to_string(archivo).map(|texto| Evaluacion::desde_texto(&texto)) {
+                Ok(Ok(evaluacion)) => evaluacion,
+                Ok(Err(e)) => return eprintln!("Afirmaciones inválidas: {}", e),
+                Err(e) => return eprintln!("No se pudo leer '{}': {}", archivo, e),
+            },
+        };
+        let max_pasos = match pasos.map(str::parse::<u64>) {
+            None => PASOS_CONTINUE,
+            Some(Ok(pasos)) => pasos,
+            Some(Err(_)) => uso!("Cantidad de pasos inválida para --steps: {}", pasos.unwrap_or_default()),
+        };
+        Some(Escenario { ciudad, evaluacion, max_pasos })
+    } else {
+        None
+    };
+
+    match Lote::corregir(Path::new(directorio), &opciones, escenario.as_ref()) {
+        Ok(lote) if formato == "json" => print!("{}", lote.a_json()),
+        Ok(lote) => print!("{}", lote.a_csv()),
+        Err(e) => {
+            eprintln!("No se pudo leer el directorio '{}': {}", directorio, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Ejecuta el programa bajo muchos intercalados distintos de sus robots y
+// muestra si todos terminan igual. Si no, lista cada desenlace con un
+// intercalado que llega a él y termina con código 1. Opciones: `--mundo
+// <archivo>`, `--preemptions <n>` para la cantidad de veces que se le puede
+// quitar el turno a un robot que podía seguir, `--runs <n>` para el máximo
+// de ejecuciones y `--steps <n>` para el máximo de pasos de cada una.
+fn explorar_intercalados(ruta: &str, argumentos: &[String], opciones: Opciones, proyecto: &Proyecto) {
+    let mut mundo = proyecto.mundo.as_deref();
+    let mut limites = Limites::default();
+    let mut argumentos = argumentos.iter();
+    while let Some(opcion) = argumentos.next() {
+        let Some(valor) = argumentos.next() else {
+            uso!("Falta un valor después de {}", opcion);
+        };
+        let valido = match opcion.as_str() {
+            "--mundo" => {
+                mundo = Some(valor.as_str());
+                true
+            }
+            "--preemptions" => valor.parse().map(|cambios| limites.cambios = cambios).is_ok(),
+            "--runs" => valor.parse().map(|ejecuciones| limites.ejecuciones = ejecuciones).is_ok(),
+            "--steps" => valor.parse().map(|pasos| limites.pasos = pasos).is_ok(),
+            otra => uso!("Opción desconocida: {}", otra),
+        };
+        if !valido {
+            uso!("Valor inválido para {}: {}", opcion, valor);
+        }
+    }
+
+    let ciudad = match leer_ciudad(mundo) {
+        Ok(ciudad) => ciudad,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let Some(mut simulacion) = cargar_simulacion(ruta, ciudad, opciones, None) else {
+        std::process::exit(1);
+    };
+    let exploracion = match explorar(&mut simulacion, limites) {
+        Ok(exploracion) => exploracion,
+        Err(e) => return eprintln!("✗ Error de ejecución: {}", e),
+    };
+
+    println!(
+        "Exploración: {} intercalados con hasta {} cambios de turno{}, {} desenlaces distintos",
+        exploracion.ejecuciones,
+        limites.cambios,
+        if exploracion.completa { "" } else { " (parcial: se alcanzó el máximo de ejecuciones)" },
+        exploracion.resultados.len()
+    );
+    let referencia = exploracion.resultados.first().and_then(|resultado| resultado.desenlace.foto());
+    for (i, resultado) in exploracion.resultados.iter().enumerate() {
+        println!("[{}] {} intercalados, {}", i + 1, resultado.ejecuciones, resultado.desenlace);
+        if exploracion.determinista() {
+            break;
+        }
+        println!("    intercalado: {}", exploracion.tramos(&resultado.intercalado));
+        if let (Some(foto), Some(referencia)) = (resultado.desenlace.foto(), referencia.filter(|_| i > 0)) {
+            for (av, ca, flores, papeles) in foto.esquinas_distintas(referencia) {
+                println!("    ({}, {}) quedó con {} flores y {} papeles", av, ca, flores, papeles);
+            }
+        }
+    }
+
+    if exploracion.determinista() {
+        println!("✓ Todos los intercalados explorados llegan al mismo resultado");
+    } else {
+        println!("✗ El resultado depende del orden en que avanzan los robots");
+        std::process::exit(1);
+    }
+}
+
+// Ejecuta el programa y muestra cómo quedó cada robot y lo que informó. Opciones:
+// `--mundo <archivo>`, `--report <archivo>` para escribir el reporte de la
+// ejecución, en HTML si el archivo termina en `.html` y si no en JSON,
+// `--emit mermaid` para mostrar en su lugar el diagrama de secuencia de los
+// mensajes entre robots (o, sin ejecutar, `--emit symbols` para mostrar la
+// tabla de símbolos en CSV y `--emit metrics` las métricas de cada proceso y
+// robot, las dos en JSON con `:json`, y `--emit html` el código coloreado con
+// sus errores y advertencias; estos tres no admiten `--report` ni
+// `--render`), `--inline <umbral>` para expandir los procesos de
+// hasta esa cantidad de operaciones en cada llamada, `--profile` para
+// mostrar las líneas que más veces se ejecutaron y `--render <archivo>` para
+// dibujar la ciudad final, en PNG si el archivo termina en `.png` y si no en
+// SVG, y `--animate <archivo.gif>` para grabar la simulación con un cuadro
+// cada `--frame-every <pasos>` pasos (estas dos con la feature `svg`).
+// `--scheduler` elige qué robot avanza en cada paso: por turnos (el valor
+// por defecto), `random` con una semilla o `priority` con la prioridad de
+// cada robot.
+// Las opciones que no se indican toman el valor de la configuración del
+// proyecto, si lo tiene.
+fn ejecutar(ruta: &str, argumentos: &[String], opciones: Opciones, proyecto: &Proyecto) {
+    let mut mundo = proyecto.mundo.as_deref();
+    let mut reporte = proyecto.reporte.as_deref();
+    let mut emitir = proyecto.emitir.as_deref();
+    let mut expandir = None;
+    let mut imagen = proyecto.imagen.as_deref();
+    let mut animacion = None;
+    let mut cada = None;
+    let mut politica = proyecto.planificador.as_deref();
+    let mut perfilar = false;
+    let mut argumentos = argumentos.iter();
+    while let Some(opcion) = argumentos.next() {
+        let destino = match opcion.as_str() {
+            "--profile" => {
+                perfilar = true;
+                continue;
+            }
+            "--mundo" => &mut mundo,
+            "--report" => &mut reporte,
+            "--emit" => &mut emitir,
+            "--inline" => &mut expandir,
+            "--render" => &mut imagen,
+            "--animate" => &mut animacion,
+            "--frame-every" => &mut cada,
+            "--scheduler" => &mut politica,
+            otra => uso!("Opción desconocida: {}", otra),
+        };
+        match argumentos.next() {
+            Some(valor) => *destino = Some(valor.as_str()),
+            None => uso!("Falta un valor después de {}", opcion),
+        }
+    }
+    match emitir {
+        None | Some("mermaid") => {}
+        // Estos formatos no ejecutan el programa, así que no hay reporte ni
+        // ciudad final que escribir
+        Some(formato @ ("symbols" | "symbols:json" | "metrics" | "metrics:json" | "html"))
+            if reporte.is_some() || imagen.is_some() =>
+        {
+            uso!(
+                "--emit {} no ejecuta el programa y no se puede combinar con {}",
+                formato,
+                if reporte.is_some() { "--report" } else { "--render" }
+            );
+        }
+        Some(formato @ ("symbols" | "symbols:json" | "metrics" | "metrics:json")) => {
+            return emitir_analisis(ruta, opciones, formato);
+        }
+        Some("html") => return emitir_listado(ruta, opciones),
+        Some(formato) => {
+            uso!(
+                "Formato desconocido para --emit: {} (se admite 'mermaid', 'symbols', 'metrics', 'symbols:json', 'metrics:json' o 'html')",
+                formato
+            );
+        }
+    }
+    let umbral = match expandir.map(str::parse::<usize>) {
+        None => None,
+        Some(Ok(umbral)) => Some(umbral),
+        Some(Err(_)) => uso!("Umbral inválido para --inline: {}", expandir.unwrap_or_default()),
+    };
+    let cada = match cada.map(str::parse::<u64>) {
+        None => PASOS_POR_CUADRO,
+        Some(Ok(pasos)) if pasos > 0 => pasos,
+        Some(_) => uso!("Cantidad de pasos inválida para --frame-every: {}", cada.unwrap_or_default()),
+    };
+    let planificador = match politica.map(planificador) {
+        None => None,
+        Some(Ok(planificador)) => Some(planificador),
+        Some(Err(e)) => uso!("{}", e),
+    };
+
+    let ciudad = match leer_ciudad(mundo) {
+        Ok(ciudad) => ciudad,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let Some(mut simulacion) = cargar_simulacion(ruta, ciudad, opciones, umbral) else {
+        std::process::exit(1);
+    };
+    if let Some(planificador) = planificador {
+        simulacion.fijar_planificador(planificador);
+    }
+
+    let ejecucion = match animacion {
+        Some(archivo) => animar(&mut simulacion, archivo, cada),
+        None => simulacion.ejecutar(PASOS_CONTINUE),
+    };
+    if emitir.is_some() {
+        // Sólo el diagrama por stdout, para poder redirigirlo a un archivo
+        if let Err(e) = &ejecucion {
+            eprintln!("✗ Error de ejecución: {}", e);
+        }
+        print!("{}", secuencia_mensajes(simulacion.mundo()));
+        for robot in simulacion.mundo().robots.iter().filter(|robot| !robot.informes.is_empty()) {
+            eprintln!("{} informó {}", robot.nombre, robot.informes.join(" / "));
+        }
+        for aviso in simulacion.avisos_livelock() {
+            eprintln!("⚠ {}", aviso);
+        }
+        if perfilar {
+            eprint!("{}", perfil(&simulacion, ruta));
+        }
+    } else {
+        match &ejecucion {
+            Ok(estado) => println!("Simulación: {:?} en {} pasos", estado, simulacion.pasos()),
+            Err(e) => println!("✗ Error de ejecución: {}", e),
+        }
+        for robot in &simulacion.mundo().robots {
+            println!("  {} ({}) en ({}, {}), flores: {}, papeles: {}",
+                robot.nombre, robot.tipo, robot.av, robot.ca, robot.flores, robot.papeles);
+            if !robot.informes.is_empty() {
+                println!("    informó {}", robot.informes.join(" / "));
+            }
+        }
+        if let Ok(EstadoSimulacion::Ejecutando) = ejecucion {
+            println!("⚠ se alcanzó el máximo de {} pasos sin que terminaran todos los robots", PASOS_CONTINUE);
+        }
+        for aviso in simulacion.avisos_livelock() {
+            println!("⚠ {}", aviso);
+        }
+        if perfilar {
+            print!("{}", perfil(&simulacion, ruta));
+        }
+    }
+
+    if let Some(archivo) = reporte {
+        let reporte = Reporte::new(&simulacion, &ejecucion);
+        let contenido = if archivo.ends_with(".html") { reporte.a_html() } else { reporte.a_json() };
+        match fs::write(archivo, contenido) {
+            Ok(()) => eprintln!("Reporte escrito en '{}'", archivo),
+            Err(e) => eprintln!("No se pudo escribir '{}': {}", archivo, e),
+        }
+    }
+    if let Some(archivo) = imagen {
+        dibujar(&simulacion, archivo);
+    }
+    if ejecucion.is_err() {
+        std::process::exit(1);
+    }
+}
+
+// Política de `--scheduler`. Sin semilla, `random` usa una tomada del reloj
+// y la informa para poder repetir la ejecución.
+fn planificador(texto: &str) -> Result<Box<dyn SchedulerPolicy>, String> {
+    let (nombre, valor) = match texto.split_once(':') {
+        Some((nombre, valor)) => (nombre, Some(valor)),
+        None => (texto, None),
+    };
+    match (nombre, valor) {
+        ("round-robin", None) => Ok(Box::new(RoundRobin)),
+        ("random", None) => {
+            let semilla = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(1, |tiempo| tiempo.as_nanos() as u64);
+            eprintln!("Planificador aleatorio con semilla {} (--scheduler random:{})", semilla, semilla);
+            Ok(Box::new(Aleatorio::new(semilla)))
+        }
+        ("random", Some(semilla)) => semilla
+            .parse()
+            .map(|semilla| Box::new(Aleatorio::new(semilla)) as Box<dyn SchedulerPolicy>)
+            .map_err(|_| format!("Semilla inválida para --scheduler: {}", semilla)),
+        ("priority", Some(prioridades)) => {
+            let mut politica = PorPrioridad::new();
+            for asignacion in prioridades.split(',') {
+                let prioridad = asignacion
+                    .split_once('=')
+                    .and_then(|(robot, prioridad)| Some((robot.trim(), prioridad.trim().parse().ok()?)));
+                match prioridad {
+                    Some((robot, prioridad)) => politica = politica.con(robot, prioridad),
+                    None => return Err(format!("Prioridad inválida para --scheduler: {} (se espera <robot>=<n>)", asignacion)),
                 }
             }
-            
+            Ok(Box::new(politica))
+        }
+        _ => Err(format!(
+            "Planificador desconocido: {} (se admite 'round-robin', 'random[:semilla]' o 'priority:<robot>=<n>,...')",
+            texto
+        )),
+    }
+}
+
+#[cfg(feature = "svg")]
+fn dibujar(simulacion: &Simulation, archivo: &str) {
+    use rinfo::render::Dibujo;
+
+    let dibujo = Dibujo::new(simulacion.mundo());
+    let contenido = if archivo.ends_with(".png") {
+        match dibujo.a_png() {
+            Ok(png) => png,
+            Err(e) => return eprintln!("No se pudo generar la imagen: {}", e),
+        }
+    } else {
+        dibujo.a_svg().into_bytes()
+    };
+    match fs::write(archivo, contenido) {
+        Ok(()) => eprintln!("Imagen escrita en '{}'", archivo),
+        Err(e) => eprintln!("No se pudo escribir '{}': {}", archivo, e),
+    }
+}
+
+// Ejecuta la simulación de a `cada` pasos y la graba en un GIF con un cuadro
+// al empezar, otro después de cada tramo y el último al terminar
+#[cfg(feature = "svg")]
+fn animar(simulacion: &mut Simulation, archivo: &str, cada: u64) -> Result<EstadoSimulacion, RuntimeError> {
+    use rinfo::render::{Animacion, Dibujo};
+    const MAXIMO_CUADROS: usize = 1_000;
+
+    let mut animacion = Animacion::new();
+    let mut error = animacion.agregar(&Dibujo::new(simulacion.mundo())).err();
+    let mut resultado = Ok(simulacion.estado());
+    while simulacion.pasos() < PASOS_CONTINUE {
+        resultado = simulacion.ejecutar(cada.min(PASOS_CONTINUE - simulacion.pasos()));
+        if resultado != Ok(EstadoSimulacion::Ejecutando) {
+            break;
+        }
+        // Pasado el máximo de cuadros sólo se agrega el final
+        if error.is_none() && animacion.cuadros() < MAXIMO_CUADROS - 1 {
+            error = animacion.agregar(&Dibujo::new(simulacion.mundo())).err();
+        }
+    }
+    if error.is_none() {
+        error = animacion.agregar_final(&Dibujo::new(simulacion.mundo())).err();
+    }
+
+    let cuadros = animacion.cuadros();
+    match error.map_or_else(|| animacion.terminar(), Err) {
+        Ok(Some(gif)) => match fs::write(archivo, gif) {
+            Ok(()) => eprintln!("Animación de {} cuadros escrita en '{}'", cuadros, archivo),
+            Err(e) => eprintln!("No se pudo escribir '{}': {}", archivo, e),
+        },
+        Ok(None) => eprintln!("La animación no tiene cuadros"),
+        Err(e) => eprintln!("No se pudo generar la animación: {}", e),
+    }
+    resultado
+}
+
+#[cfg(not(feature = "svg"))]
+fn animar(simulacion: &mut Simulation, archivo: &str, _cada: u64) -> Result<EstadoSimulacion, RuntimeError> {
+    eprintln!("No se puede escribir '{}': --animate requiere compilar con la feature 'svg'", archivo);
+    simulacion.ejecutar(PASOS_CONTINUE)
+}
+
+#[cfg(not(feature = "svg"))]
+fn dibujar(_simulacion: &Simulation, archivo: &str) {
+    eprintln!("No se puede escribir '{}': --render requiere compilar con la feature 'svg'", archivo);
+}
+
+// Líneas más ejecutadas y pasos por clase de operación:
+//
+//   Líneas más ejecutadas:
+//       9 |    300 |             mover
+//   Pasos por operación: acción 412, repetir 305, asignación 3
+fn perfil(simulacion: &Simulation, ruta: &str) -> String {
+    let perfil = simulacion.perfil();
+    // El texto de cada línea se toma del archivo principal
+    let fuente = fs::read_to_string(ruta).unwrap_or_default();
+    let mut texto = String::from("Líneas más ejecutadas:\n");
+    for (linea, veces) in perfil.lineas_mas_ejecutadas(LINEAS_PERFIL) {
+        let codigo = linea.checked_sub(1).and_then(|indice| fuente.lines().nth(indice)).unwrap_or_default();
+        texto.push_str(&format!("  {:>5} | {:>6} | {}\n", linea, veces, codigo));
+    }
+    let clases: Vec<String> = perfil.clases()
+        .iter()
+        .map(|(clase, veces)| format!("{} {}", clase, veces))
+        .collect();
+    texto.push_str(&format!("Pasos por operación: {}\n", clases.join(", ")));
+    texto
+}
+
+// Muestra por stdout la tabla de símbolos o las métricas del programa
+fn emitir_analisis(ruta: &str, opciones: Opciones, formato: &str) {
+    let Some((programa, fuentes)) = cargar_programa(ruta, opciones) else {
+        std::process::exit(1);
+    };
+    let salida = match formato {
+        "symbols" => Inventario::new(&programa).a_csv(&fuentes),
+        "symbols:json" => Inventario::new(&programa).a_json(&fuentes),
+        "metrics" => Metricas::new(&programa).a_texto(),
+        _ => Metricas::new(&programa).a_json(),
+    };
+    print!("{}", salida);
+}
+
+// Muestra por stdout el código como HTML coloreado, con sus errores y
+// advertencias; a diferencia del resto, también si no compila
+fn emitir_listado(ruta: &str, opciones: Opciones) {
+    match fs::read_to_string(ruta) {
+        Ok(source) => print!("{}", listado_html(&source, Path::new(ruta), opciones)),
+        Err(e) => {
+            eprintln!("No se pudo leer '{}': {}", ruta, e);
+            std::process::exit(1);
         }
+    }
+}
+
+// Compila el archivo y prepara su simulación, informando los errores por
+// stderr. Con `umbral` expande antes los procesos chicos en cada llamada.
+fn cargar_simulacion(ruta: &str, ciudad: Ciudad, opciones: Opciones, umbral: Option<usize>) -> Option<Simulation> {
+    let (programa, _) = cargar_programa(ruta, opciones)?;
+    let mut interprete = Interpreter::new(&programa);
+    if let Some(umbral) = umbral {
+        interprete.expandir_procesos(umbral);
+    }
+    Simulation::con_interprete(&programa, ciudad, interprete)
+        .map_err(|e| eprintln!("{}", e))
+        .ok()
+}
+
+// Compila el archivo, informando los errores por stderr. Devuelve también
+// los archivos de la compilación, para ubicar lo que se muestre del programa.
+fn cargar_programa(ruta: &str, opciones: Opciones) -> Option<(Program, SourceMap)> {
+    let source = match fs::read_to_string(ruta) {
+        Ok(source) => source,
         Err(e) => {
-            eprintln!("Lexing error: {}", e);
+            eprintln!("No se pudo leer '{}': {}", ruta, e);
+            return None;
+        }
+    };
+
+    let idioma = opciones.idioma;
+    let compilacion = match opciones.formato_mensajes {
+        // Las advertencias no detienen la compilación, pero se muestran
+        FormatoMensajes::Humano => {
+            let compilacion = compilar_archivo_con(&source, Path::new(ruta), opciones);
+            for advertencia in &compilacion.advertencias {
+                eprintln!("{}", Mensaje::termino(Termino::Advertencia).con(advertencia.clone()).traducir(idioma));
+            }
+            compilacion
+        }
+        // Los diagnósticos ya quedan en los eventos
+        FormatoMensajes::Json => match compilar_con_eventos(&source, Path::new(ruta), opciones, &mut io::stdout().lock()) {
+            Ok(compilacion) if compilacion.exitosa() => compilacion,
+            Ok(_) => return None,
+            Err(e) => {
+                eprintln!("No se pudieron escribir los eventos: {}", e);
+                return None;
+            }
+        },
+    };
+    let programa = match compilacion.programa {
+        Some(programa) if compilacion.errores.is_empty() => programa,
+        _ => {
+            for error in &compilacion.errores {
+                eprintln!("{}", compilacion.fuentes.mostrar_en(error, idioma));
+                if let Some(subrayado) = compilacion.fuentes.subrayar(error) {
+                    eprintln!("{}", subrayado);
+                }
+                for detalle in compilacion.fuentes.detalles_en(error, idioma) {
+                    eprintln!("  {}", detalle);
+                }
+            }
+            return None;
+        }
+    };
+    Some((programa, compilacion.fuentes))
+}
+
+#[cfg(feature = "tui")]
+fn visualizar(ruta: &str, opciones: Opciones) {
+    use rinfo::tui::Visor;
+
+    let Some(simulacion) = cargar_simulacion(ruta, Ciudad::new(), opciones, None) else {
+        return;
+    };
+    if let Err(e) = Visor::new(simulacion, Duration::from_millis(100)).ejecutar() {
+        eprintln!("Error en la terminal: {}", e);
+    }
+}
+
+fn depurar(ruta: &str, opciones: Opciones) {
+    let Some(simulacion) = cargar_simulacion(ruta, Ciudad::new(), opciones, None) else {
+        return;
+    };
+    let mut debugger = Debugger::new(simulacion);
+
+    println!("{}", AYUDA_DEBUG);
+    let mut anterior: Option<Comando> = None;
+    let stdin = io::stdin();
+    loop {
+        print!("(rinfo) ");
+        let _ = io::stdout().flush();
+
+        let mut linea = String::new();
+        if stdin.lock().read_line(&mut linea).unwrap_or(0) == 0 {
+            break;
+        }
+
+        // Una línea vacía repite el comando anterior
+        let comando = if linea.trim().is_empty() {
+            match anterior.clone() {
+                Some(comando) => comando,
+                None => continue,
+            }
+        } else {
+            match Comando::parse(&linea) {
+                Ok(comando) => comando,
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                }
+            }
+        };
+
+        let resultado = match &comando {
+            Comando::Step => debugger.paso().map(|parada| parada.to_string()),
+            Comando::Next => debugger.siguiente().map(|parada| parada.to_string()),
+            Comando::Continue => debugger.continuar(PASOS_CONTINUE).map(|parada| parada.to_string()),
+            Comando::Print { variable, robot } => debugger
+                .imprimir(variable, robot.as_deref())
+                .map(|valor| format!("{} = {}", variable, valor)),
+            Comando::Break(breakpoint) => {
+                debugger.agregar_breakpoint(breakpoint.clone());
+                Ok(format!("Breakpoint en la línea {}", breakpoint.linea))
+            }
+            Comando::Delete(breakpoint) => Ok(if debugger.quitar_breakpoint(breakpoint) {
+                format!("Breakpoint de la línea {} eliminado", breakpoint.linea)
+            } else {
+                "No existe ese breakpoint".to_string()
+            }),
+            Comando::Watch(variable) => {
+                debugger.vigilar(variable);
+                Ok(format!("Vigilando '{}'", variable))
+            }
+            Comando::Mensajes => {
+                let activado = !debugger.para_en_mensajes();
+                debugger.parar_en_mensajes(activado);
+                Ok(format!("Parada en EnviarMensaje {}", if activado { "activada" } else { "desactivada" }))
+            }
+            Comando::Robots => Ok(debugger
+                .simulacion()
+                .mundo()
+                .robots
+                .iter()
+                .map(|robot| format!("{} ({}) en ({}, {})", robot.nombre, robot.tipo, robot.av, robot.ca))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            Comando::Ayuda => Ok(AYUDA_DEBUG.to_string()),
+            Comando::Salir => break,
+        };
+
+        match resultado {
+            Ok(mensaje) => println!("{}", mensaje),
+            Err(e) => println!("Error: {}", e),
         }
+        anterior = Some(comando);
     }
 }
@@ -1,3 +1,15 @@
 pub mod testLexer;
 pub mod testParser;
-pub mod testSemanticizer;
\ No newline at end of file
+pub mod testSemanticizer;
+pub mod testInterpreter;
+pub mod testFeatures;
+pub mod testDebugger;
+pub mod testTui;
+pub mod testWasm;
+pub mod testNode;
+pub mod testRender;
+pub mod testBench;
+pub mod testIncremental;
+pub mod testWatch;
+pub mod testDriver;
+pub mod testTestgen;
@@ -0,0 +1,34 @@
+use rinfo::bench::medir;
+use rinfo::testgen::{generar, Configuracion};
+
+#[cfg(test)]
+mod testing_bench {
+    use super::*;
+
+    // Programa generado con esa cantidad de procesos; cada uno agrega unas
+    // 40 líneas
+    fn programa(procesos: usize) -> String {
+        generar(&Configuracion { procesos, ..Configuracion::default() })
+    }
+
+    #[test]
+    fn test_timings_report_every_phase() {
+        let tiempos = medir(&programa(5)).expect("El programa generado debe compilar");
+        assert_eq!(tiempos.total(), tiempos.lexer + tiempos.parser + tiempos.semantico);
+        assert!(tiempos.tokens > tiempos.lineas);
+
+        assert!(medir("programa roto\ncomenzar\n    @\nfin\n").is_err());
+    }
+
+    // Benchmark del pipeline por fase, con programas de unas 1.000, 10.000 y
+    // 100.000 líneas. Se ejecuta a pedido, en release:
+    //   cargo test --release benchmark -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn benchmark_pipeline_phases() {
+        for procesos in [25, 250, 2_500] {
+            let tiempos = medir(&programa(procesos)).expect("El programa generado debe compilar");
+            println!("{}", tiempos);
+        }
+    }
+}
@@ -0,0 +1 @@
+pub mod benchTest;
\ No newline at end of file
@@ -0,0 +1,154 @@
+use rinfo::lexer::scanner::Lexer;
+use rinfo::parser::processor::{Parser, Program};
+use rinfo::interpreter::debugger::{Breakpoint, Comando, Debugger, Parada};
+use rinfo::interpreter::runtime::Valor;
+use rinfo::interpreter::simulation::Simulation;
+use rinfo::interpreter::world::Ciudad;
+
+#[cfg(test)]
+mod testing_debugger {
+    use super::*;
+
+    fn parsear(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("Failed to tokenize source code");
+        Parser::new(&tokens).parse().expect("Failed to parse source code")
+    }
+
+    const JUNTADOR: &str =
+"programa depurar
+procesos
+    proceso juntar(ES cant: numero)
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+            cant := cant + 1
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    variables
+        total : numero
+    comenzar
+        total := 0
+        juntar(total)
+        mover
+        Informar(total)
+    fin
+variables
+    r1: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    fn depurador(source: &str, flores: u32) -> Debugger {
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(1, 1, flores);
+        Debugger::new(Simulation::con_ciudad(&parsear(source), ciudad).unwrap())
+    }
+
+    #[test]
+    fn test_breakpoint_and_next_step_over_process_call() {
+        let mut debugger = depurador(JUNTADOR, 2);
+        debugger.agregar_breakpoint(Breakpoint::en_linea(17));
+
+        assert_eq!(debugger.continuar(1_000).unwrap(), Parada::Breakpoint { robot: "r1".to_string(), linea: 17 });
+        assert_eq!(debugger.foco(), Some("r1"));
+
+        // `siguiente` ejecuta el proceso completo sin detenerse adentro
+        assert_eq!(debugger.siguiente().unwrap(), Parada::Paso { robot: "r1".to_string(), linea: Some(18) });
+        assert_eq!(debugger.imprimir("total", None).unwrap(), Valor::Numero(2));
+
+        assert_eq!(debugger.continuar(1_000).unwrap(), Parada::Terminada);
+    }
+
+    #[test]
+    fn test_breakpoint_inside_process_and_robot_filter() {
+        let mut debugger = depurador(JUNTADOR, 1);
+        debugger.agregar_breakpoint(Breakpoint::en_robot(6, "otro"));
+        debugger.agregar_breakpoint(Breakpoint::en_robot(7, "r1"));
+
+        assert_eq!(debugger.continuar(1_000).unwrap(), Parada::Breakpoint { robot: "r1".to_string(), linea: 7 });
+        assert_eq!(debugger.simulacion().mundo().robot("r1").unwrap().flores, 1);
+        assert!(debugger.imprimir("total", None).is_err());
+
+        assert!(debugger.quitar_breakpoint(&Breakpoint::en_robot(7, "r1")));
+        assert_eq!(debugger.continuar(1_000).unwrap(), Parada::Terminada);
+    }
+
+    #[test]
+    fn test_watch_stops_when_variable_changes() {
+        let mut debugger = depurador(JUNTADOR, 3);
+        debugger.vigilar("cant");
+
+        let mut cambios = Vec::new();
+        loop {
+            match debugger.continuar(1_000).unwrap() {
+                Parada::Watch { variable, anterior, nuevo, .. } => cambios.push((variable, anterior, nuevo)),
+                Parada::Terminada => break,
+                otra => panic!("Parada inesperada: {:?}", otra),
+            }
+        }
+
+        assert_eq!(cambios, vec![
+            ("cant".to_string(), Valor::Numero(0), Valor::Numero(1)),
+            ("cant".to_string(), Valor::Numero(1), Valor::Numero(2)),
+            ("cant".to_string(), Valor::Numero(2), Valor::Numero(3)),
+        ]);
+    }
+
+    #[test]
+    fn test_break_on_message_send() {
+        let mut debugger = depurador(
+"programa mensajes
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r2)
+        Informar(dato)
+    fin
+    robot emisor
+    comenzar
+        derecha
+        EnviarMensaje(7, r1)
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+", 0);
+        debugger.parar_en_mensajes(true);
+
+        assert_eq!(debugger.continuar(1_000).unwrap(), Parada::Mensaje { robot: "r2".to_string(), linea: 15 });
+        assert!(debugger.simulacion().mundo().robot("r1").unwrap().mensajes.is_empty());
+
+        assert_eq!(debugger.continuar(1_000).unwrap(), Parada::Terminada);
+        assert_eq!(debugger.imprimir("dato", Some("r1")).unwrap(), Valor::Numero(7));
+    }
+
+    #[test]
+    fn test_parse_console_commands() {
+        assert_eq!(Comando::parse("s"), Ok(Comando::Step));
+        assert_eq!(Comando::parse("next"), Ok(Comando::Next));
+        assert_eq!(Comando::parse(" c "), Ok(Comando::Continue));
+        assert_eq!(Comando::parse("b 12"), Ok(Comando::Break(Breakpoint::en_linea(12))));
+        assert_eq!(Comando::parse("break 3 r1"), Ok(Comando::Break(Breakpoint::en_robot(3, "r1"))));
+        assert_eq!(
+            Comando::parse("print total r2"),
+            Ok(Comando::Print { variable: "total".to_string(), robot: Some("r2".to_string()) })
+        );
+        assert!(Comando::parse("break x").is_err());
+        assert!(Comando::parse("saltar").is_err());
+    }
+}
@@ -0,0 +1 @@
+pub mod debuggerTest;
\ No newline at end of file
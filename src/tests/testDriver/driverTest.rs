@@ -0,0 +1,610 @@
+use rinfo::compilerError::CompilerError;
+use rinfo::driver::{compilar, compilar_archivo, compilar_archivo_con, compilar_ruta, Compilacion, Opciones};
+use rinfo::error::Error;
+use rinfo::eventos::compilar_con_eventos;
+use rinfo::incremental::CompiladorIncremental;
+use rinfo::listado::listado_html;
+use rinfo::lote::{Desenlace, Escenario, Lote};
+use rinfo::interpreter::evaluacion::Evaluacion;
+use rinfo::interpreter::simulation::{EstadoSimulacion, Simulation};
+use rinfo::interpreter::world::Ciudad;
+use rinfo::lexer::scanner::Lexer;
+use rinfo::mensajes::{Codigo, Idioma};
+use rinfo::proyecto::{buscar_proyecto, Proyecto};
+use rinfo::resaltado::{highlight, HighlightKind};
+use rinfo::semanticizer::lints::Nivel;
+use rinfo::source::{FileId, SourceMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod testing_driver {
+    use super::*;
+
+    // Directorio temporal propio de cada test con los archivos indicados
+    fn directorio(nombre: &str, archivos: &[(&str, &str)]) -> PathBuf {
+        let directorio = env::temp_dir().join(format!("rinfo-{}-{}", nombre, std::process::id()));
+        let _ = fs::remove_dir_all(&directorio);
+        for (ruta, contenido) in archivos {
+            let ruta = directorio.join(ruta);
+            fs::create_dir_all(ruta.parent().unwrap()).unwrap();
+            fs::write(ruta, contenido).unwrap();
+        }
+        directorio
+    }
+
+    // Nombre del archivo donde ocurrió el error
+    fn archivo<'a>(compilacion: &'a Compilacion, error: &CompilerError) -> &'a str {
+        error.archivo.and_then(|id| compilacion.fuentes.nombre(id)).unwrap_or("")
+    }
+
+    const PRINCIPAL: &str =
+"programa principal
+incluir \"comunes.ri\"
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        avanzar(3)
+        girar
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    const COMUNES: &str =
+"incluir \"lib/giros.ri\"
+procesos
+    proceso avanzar(E pasos: numero)
+    comenzar
+        repetir pasos
+            mover
+    fin
+";
+
+    #[test]
+    fn test_included_processes_are_available() {
+        let dir = directorio("incluir", &[
+            ("comunes.ri", COMUNES),
+            ("lib/giros.ri", "procesos\n    proceso girar\n    comenzar\n        derecha\n    fin\n"),
+        ]);
+
+        let compilacion = compilar_archivo(PRINCIPAL, &dir.join("principal.ri"));
+        assert!(compilacion.exitosa(), "{:?}", compilacion.errores);
+        assert_eq!(compilacion.incluidos.len(), 2);
+
+        let programa = compilacion.programa.unwrap();
+        let nombres: Vec<&str> = programa.procesos.iter().map(|p| p.nombre.as_str()).collect();
+        assert_eq!(nombres, vec!["girar", "avanzar"]);
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        simulacion.ejecutar(1_000).unwrap();
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!((robot.av, robot.ca), (1, 4));
+
+        // El compilador incremental resuelve las mismas inclusiones
+        let incremental = CompiladorIncremental::new().compilar_archivo(PRINCIPAL, &dir.join("principal.ri"));
+        assert!(incremental.exitosa(), "{:?}", incremental.errores);
+        assert_eq!(incremental.programa.unwrap().procesos.len(), 2);
+    }
+
+    #[test]
+    fn test_errors_name_the_included_file() {
+        let dir = directorio("incluir-error", &[
+            ("comunes.ri", "procesos\n    proceso avanzar(E pasos numero)\n    comenzar\n        mover\n    fin\n"),
+        ]);
+
+        let compilacion = compilar_archivo(PRINCIPAL, &dir.join("principal.ri"));
+        assert_eq!(compilacion.errores.len(), 1);
+        let error = &compilacion.errores[0];
+        assert!(archivo(&compilacion, error).ends_with("comunes.ri"));
+        assert_eq!(error.line, 2);
+        assert!(compilacion.fuentes.mostrar(error).contains("comunes.ri:2:"));
+    }
+
+    #[test]
+    fn test_included_file_reports_every_lexical_error() {
+        let dir = directorio("incluir-lexico", &[
+            ("comunes.ri", "procesos\n    proceso avanzar(E pasos: numero)\n    comenzar\n        mover ?\n        derecha $\n    fin\n"),
+        ]);
+
+        let compilacion = compilar_archivo(PRINCIPAL, &dir.join("principal.ri"));
+        let lineas: Vec<usize> = compilacion.errores.iter().map(|error| error.line).collect();
+        assert_eq!(lineas, vec![4, 5]);
+        assert!(compilacion.errores.iter().all(|error| archivo(&compilacion, error).ends_with("comunes.ri")));
+    }
+
+    #[test]
+    fn test_missing_and_circular_inclusions() {
+        let dir = directorio("incluir-ciclo", &[
+            ("a.ri", "incluir \"b.ri\"\n"),
+            ("b.ri", "\nincluir \"a.ri\"\n"),
+        ]);
+
+        let faltante = compilar_archivo(PRINCIPAL, &dir.join("principal.ri"));
+        assert_eq!(faltante.errores.len(), 1);
+        assert!(faltante.errores[0].message.contains("No se pudo leer 'comunes.ri'"));
+        assert_eq!((faltante.errores[0].line, faltante.errores[0].archivo), (2, Some(FileId::PRINCIPAL)));
+
+        let source = PRINCIPAL.replace("comunes.ri", "a.ri");
+        let ciclo = compilar_archivo(&source, &dir.join("principal.ri"));
+        assert_eq!(ciclo.errores.len(), 1);
+        assert!(ciclo.errores[0].message.contains("Inclusión circular de 'a.ri'"));
+        assert!(archivo(&ciclo, &ciclo.errores[0]).ends_with("b.ri"));
+        assert_eq!(ciclo.errores[0].line, 2);
+    }
+
+    // Los tokens y los errores semánticos indican su archivo, y los
+    // diagnósticos se muestran como `archivo:línea:columna: mensaje`
+    #[test]
+    fn test_extensions_are_only_recognized_when_enabled() {
+        let source =
+"programa espera
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        dato : numero
+    comenzar
+        RecibirMensajeTimeout(dato, *, 5)
+        si RecibioMensaje
+            Informar(dato)
+    fin
+variables
+    r1: r
+    r2: r
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 1)
+fin
+";
+        assert!(!compilar(source).exitosa());
+
+        let opciones = Opciones { extensiones: true, ..Opciones::default() };
+        let compilacion = compilar_archivo_con(source, Path::new("espera.ri"), opciones);
+        assert!(compilacion.exitosa(), "{:?}", compilacion.errores);
+
+        // Nadie envía: los dos robots dejan de esperar y terminan
+        let mut simulacion = Simulation::new(compilacion.programa.as_ref().unwrap()).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+        assert!(simulacion.mundo().robots.iter().all(|robot| robot.informes.is_empty()));
+    }
+
+    // Quien usa la biblioteca recibe un `Result` con la fase que falló y
+    // puede encadenar compilación y ejecución con `?`
+    #[test]
+    fn test_library_errors_tell_the_failing_phase() {
+        let casos = directorio("fases", &[
+            ("lexico.ri", "programa p\ncomenzar\n    Informar('sin cerrar)\nfin\n"),
+            ("sintactico.ri", "programa p\nareas\n    ciudad AreaC (1,1,100,100)\ncomenzar\nfin\n"),
+            ("semantico.ri", "programa p\ncomenzar\n    Iniciar(r1, 1, 1)\nfin\n"),
+            ("ejecucion.ri", "programa p\nareas\n    ciudad: AreaC (1,1,100,100)\nrobots\n    robot r\n    comenzar\n        tomarFlor\n    fin\nvariables\n    r1: r\ncomenzar\n    AsignarArea(r1, ciudad)\n    Iniciar(r1, 1, 1)\nfin\n"),
+        ]);
+        let ejecutar = |nombre: &str| -> Result<EstadoSimulacion, Error> {
+            let programa = compilar_ruta(&casos.join(nombre), Opciones::default())?;
+            let mut simulacion = Simulation::new(&programa)?;
+            Ok(simulacion.ejecutar(1_000)?)
+        };
+
+        assert!(matches!(ejecutar("inexistente.ri"), Err(Error::Io { .. })));
+        assert!(matches!(ejecutar("lexico.ri"), Err(Error::Lex(_))));
+        assert!(matches!(ejecutar("sintactico.ri"), Err(Error::Parse(_))));
+        let error = ejecutar("semantico.ri").unwrap_err();
+        assert!(matches!(error, Error::Semantic(_)));
+        assert_eq!(error.errores().len(), 2);
+        let error = ejecutar("ejecucion.ri").unwrap_err();
+        assert!(matches!(error, Error::Runtime(_)));
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(error.to_string().starts_with("Error de ejecución: No hay flores en la esquina"));
+    }
+
+    // El `rinfo.toml` más cercano configura la compilación y da valores por
+    // defecto a las opciones de los comandos
+    #[test]
+    fn test_project_file_configures_the_compilation() {
+        let configuracion =
+"# configuración del curso
+[compilador]
+idioma = \"en\"
+extensiones = true
+
+[lexer]
+allow_tabs = false   # sólo espacios
+
+[lints]
+si-vacio = \"deny\"
+proceso-largo = \"warn 40\"
+
+[ejecucion]
+mundo = \"mundos/inicial.txt\"
+semilla = 7
+
+[salida]
+emit = \"metrics:json\"
+";
+        let raiz = directorio("proyecto", &[("rinfo.toml", configuracion), ("ejercicios/uno/leeme.txt", "")]);
+        let archivo = buscar_proyecto(&raiz.join("ejercicios/uno")).unwrap();
+        assert_eq!(archivo, raiz.join("rinfo.toml"));
+
+        let proyecto = Proyecto::desde_texto(configuracion).unwrap().relativo_a(&raiz);
+        assert_eq!(proyecto.opciones.idioma, Idioma::Ingles);
+        assert!(proyecto.opciones.extensiones && !proyecto.opciones.secciones_flexibles);
+        assert!(!proyecto.opciones.lexer.allow_tabs);
+        assert_eq!(proyecto.opciones.lexer.tab_width, 4);
+        let ajustes: Vec<_> = proyecto.opciones.lints.ajustes.iter()
+            .map(|ajuste| (ajuste.regla.as_str(), ajuste.nivel, ajuste.limite))
+            .collect();
+        assert_eq!(ajustes, [("si-vacio", Nivel::Rechazar, None), ("proceso-largo", Nivel::Advertir, Some(40))]);
+        assert_eq!(proyecto.mundo, Some(raiz.join("mundos/inicial.txt").display().to_string()));
+        assert_eq!(proyecto.planificador.as_deref(), Some("random:7"));
+        assert_eq!(proyecto.emitir.as_deref(), Some("metrics:json"));
+        assert_eq!(proyecto.reporte, None);
+
+        let compilacion = compilar_archivo_con("programa p\ncomenzar\n\tmover\nfin\n", Path::new("p.ri"), proyecto.opciones);
+        assert_eq!(compilacion.errores.len(), 1);
+
+        let error = |texto: &str| {
+            let error = Proyecto::desde_texto(texto).unwrap_err();
+            (error.codigo(), error.line)
+        };
+        assert_eq!(error("[lexer]\ntab_width = \"cuatro\"\n"), (Some(Codigo::ValorInvalido), 2));
+        assert_eq!(error("[compilador]\nidioma = \"fr\"\n"), (Some(Codigo::ValorInvalido), 2));
+        assert_eq!(error("[salida]\nformato = \"json\"\n"), (Some(Codigo::ClaveDesconocida), 2));
+        assert_eq!(error("[ejecucion]\nmundo\n"), (Some(Codigo::FormatoDeProyecto), 2));
+        assert_eq!(error("[lints]\nsi-vacia = \"deny\"\n"), (Some(Codigo::ReglaDesconocida), 2));
+        assert_eq!(error("[ejecucion]\nplanificador = \"round-robin\"\nsemilla = 3\n"), (Some(Codigo::ValorInvalido), 3));
+    }
+
+    // Los nombres de otros apuntes se leen como las instrucciones elementales;
+    // los obsoletos se advierten
+    #[test]
+    fn test_project_aliases_name_elemental_instructions() {
+        let configuracion = "[alias]\ngirarDerecha = \"derecha\"\n\n[obsoletos]\navanzar = \"mover\"\n";
+        let proyecto = Proyecto::desde_texto(configuracion).unwrap();
+        let codigo = "programa p\nrobots\n  robot r\n  comenzar\n    girarDerecha\n    avanzar\n  fin\nvariables\n  r1: r\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n";
+        let compilacion = compilar_archivo_con(codigo, Path::new("p.ri"), proyecto.opciones);
+        assert!(compilacion.errores.is_empty());
+        let codigos: Vec<_> = compilacion.advertencias.iter().map(|advertencia| advertencia.codigo()).collect();
+        assert_eq!(codigos, [Some(Codigo::InstruccionObsoleta), Some(Codigo::RobotSinArea)]);
+
+        let error = |texto: &str| Proyecto::desde_texto(texto).unwrap_err().codigo();
+        assert_eq!(error("[alias]\ngirar = \"girarIzquierda\"\n"), Some(Codigo::ValorInvalido));
+        assert_eq!(error("[obsoletos]\nmientras = \"mover\"\n"), Some(Codigo::ValorInvalido));
+    }
+
+    #[test]
+    fn test_diagnostics_render_file_line_and_column() {
+        let mut fuentes = SourceMap::new();
+        fuentes.agregar("principal.ri", PRINCIPAL);
+        let biblioteca = fuentes.agregar("comunes.ri", COMUNES);
+        let token = Lexer::new(COMUNES).en_archivo(biblioteca).next().unwrap().unwrap();
+        assert_eq!((token.archivo, token.line, token.column), (biblioteca, 1, 1));
+
+        let dir = directorio("incluir-diagnostico", &[
+            ("comunes.ri", "procesos\n    proceso avanzar(E pasos: numero)\n    comenzar\n        x := pasos\n    fin\n    proceso girar\n    comenzar\n        derecha\n    fin\n"),
+        ]);
+        let compilacion = compilar_archivo(PRINCIPAL, &dir.join("principal.ri"));
+        assert_eq!(compilacion.errores.len(), 1);
+        let mostrado = compilacion.fuentes.mostrar(&compilacion.errores[0]);
+        assert!(mostrado.contains("comunes.ri:"), "{}", mostrado);
+        assert!(mostrado.ends_with("Variable 'x' no declarada en 'avanzar'"), "{}", mostrado);
+
+        let error = CompilerError::new("Token inesperado", 12, 5).en_archivo(FileId::PRINCIPAL);
+        assert_eq!(fuentes.mostrar(&error), "principal.ri:12:5: Token inesperado");
+        assert_eq!(fuentes.mostrar(&CompilerError::new("Sin ubicación", 0, 0)), "Sin ubicación");
+
+        // Una posición desconocida no se muestra como 0
+        assert_eq!(fuentes.mostrar(&CompilerError::new("Sin columna", 16, 0)), "16: Sin columna");
+        assert_eq!(CompilerError::new("Sin columna", 16, 0).to_string(), "Sin columna (línea 16)");
+        assert_eq!(CompilerError::new("Token inesperado", 12, 5).to_string(), "Token inesperado (línea 12, columna 5)");
+        assert_eq!(CompilerError::new("Sin ubicación", 0, 0).to_string(), "Sin ubicación");
+    }
+
+    // Los errores señalan el tramo completo del código que los produce
+    #[test]
+    fn test_errors_underline_their_whole_span() {
+        let codigo = "programa subrayado\nrobots\n    robot r\n    variables\n        listo : booleano\n    comenzar\n        Pos(listo, 1)\n    fin\nvariables\n    robot1 : r\ncomenzar\n    Iniciar(robot1, 1, 1)\nfin\n";
+        let compilacion = compilar_archivo(codigo, &env::temp_dir().join("subrayado.ri"));
+        assert_eq!(compilacion.errores.len(), 1);
+        let error = &compilacion.errores[0];
+        assert_eq!((error.line, error.column, error.end_column), (7, 13, 18));
+        assert_eq!(
+            compilacion.fuentes.subrayar(error).unwrap(),
+            "7 |         Pos(listo, 1)\n  |             ^^^^^"
+        );
+
+        let mut fuentes = SourceMap::new();
+        fuentes.agregar("principal.ri", "si x\n    mover");
+        let sin_columna = CompilerError::new("Sin columna", 2, 0);
+        assert_eq!(fuentes.subrayar(&sin_columna).unwrap(), "2 |     mover\n  |     ^^^^^");
+        assert!(fuentes.subrayar(&CompilerError::new("Sin ubicación", 0, 0)).is_none());
+    }
+
+    // El mismo diagnóstico se muestra en español o en inglés, con su código
+    #[test]
+    fn test_diagnostics_are_translated_from_the_catalog() {
+        let codigo = "programa idiomas\nrobots\n    robot r\n    variables\n        total : numero\n    comenzar\n        total := 10 / 0\n        Informar(totla)\n    fin\nvariables\n    r1 : r\ncomenzar\n    Iniciar(r1, 1, 1)\nfin\n";
+        let compilacion = compilar_archivo(codigo, &env::temp_dir().join("idiomas.ri"));
+        assert_eq!(compilacion.errores.len(), 1);
+        let error = &compilacion.errores[0];
+        assert_eq!(error.codigo().map(Codigo::codigo), Some("S010"));
+        assert_eq!(error.mensaje_en(Idioma::Espanol), error.message);
+
+        let fuentes = &compilacion.fuentes;
+        assert!(fuentes.mostrar(error).ends_with(":8:18: Variable 'totla' no declarada en expresión (en 'r')"));
+        assert_eq!(fuentes.mostrar(error), fuentes.mostrar_en(error, Idioma::Espanol));
+        assert!(fuentes.mostrar_en(error, Idioma::Ingles).ends_with(":8:18: Variable 'totla' not declared in expression (in 'r')"));
+        assert_eq!(fuentes.detalles(error), ["ayuda: ¿se quiso decir 'total'?"]);
+        assert_eq!(fuentes.detalles_en(error, Idioma::Ingles), ["help: did you mean 'total'?"]);
+
+        // Las advertencias del parser van antes que las del análisis
+        let sin_area = &compilacion.advertencias[0];
+        assert_eq!(sin_area.codigo(), Some(Codigo::RobotSinArea));
+        assert!(sin_area.traducir(Idioma::Espanol).ends_with(":11:5: El robot 'r1' no tiene asignación de área"));
+        assert!(sin_area.traducir(Idioma::Ingles).ends_with(":11:5: Robot 'r1' has no area assigned"));
+
+        let advertencia = &compilacion.advertencias[1];
+        assert_eq!(advertencia.codigo(), Some(Codigo::DivisionPorCero));
+        assert_eq!(advertencia.traducir(Idioma::Espanol), "División por cero (en 'r', línea 7)");
+        assert_eq!(advertencia.traducir(Idioma::Ingles), "Division by zero (in 'r', line 7)");
+    }
+
+    // Cada código es único y las dos traducciones usan los mismos argumentos
+    #[test]
+    fn test_message_catalog_is_complete() {
+        let argumentos = |plantilla: &'static str| {
+            let mut usados: Vec<&str> = plantilla.split('{').skip(1)
+                .filter_map(|resto| resto.split_once('}').map(|(indice, _)| indice))
+                .filter(|indice| indice.parse::<usize>().is_ok())
+                .collect();
+            usados.sort();
+            usados
+        };
+        let mut codigos: Vec<&str> = Codigo::TODOS.iter().map(|codigo| codigo.codigo()).collect();
+        codigos.sort();
+        codigos.dedup();
+        assert_eq!(codigos.len(), Codigo::TODOS.len());
+        for codigo in Codigo::TODOS {
+            assert_eq!(
+                argumentos(codigo.plantilla(Idioma::Espanol)),
+                argumentos(codigo.plantilla(Idioma::Ingles)),
+                "{:?}", codigo
+            );
+        }
+    }
+
+    #[test]
+    fn test_highlight_classifies_names_by_declaration() {
+        let source = "programa resaltado
+{ recorre una calle }
+procesos
+    proceso juntar(ES flores: numero)
+    comenzar
+        tomarFlor // una sola
+        flores := flores + 1
+    fin
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot juntador
+    variables
+        flores: numero
+    comenzar
+        flores := 0
+        juntar(flores)
+        Informar(\"listo\")
+    fin
+variables
+    r1: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let tramos = highlight(source);
+        let lineas = source.lines().collect::<Vec<_>>();
+        let texto = |tramo: &rinfo::source::Span| -> String {
+            lineas[tramo.linea - 1].chars().skip(tramo.columna - 1).take(tramo.fin_columna - tramo.columna).collect()
+        };
+        let en_linea = |linea: usize| -> Vec<(String, HighlightKind)> {
+            tramos.iter().filter(|(tramo, _)| tramo.linea == linea).map(|(tramo, clase)| (texto(tramo), *clase)).collect()
+        };
+
+        assert_eq!(en_linea(2), [("{ recorre una calle }".to_string(), HighlightKind::Comentario)]);
+        assert_eq!(en_linea(4), [
+            ("proceso".to_string(), HighlightKind::PalabraClave),
+            ("juntar".to_string(), HighlightKind::Proceso),
+            ("ES".to_string(), HighlightKind::PalabraClave),
+            ("flores".to_string(), HighlightKind::Variable),
+            ("numero".to_string(), HighlightKind::PalabraClave),
+        ]);
+        assert_eq!(en_linea(6), [
+            ("tomarFlor".to_string(), HighlightKind::Instruccion),
+            ("// una sola".to_string(), HighlightKind::Comentario),
+        ]);
+        assert_eq!(en_linea(7), [
+            ("flores".to_string(), HighlightKind::Variable),
+            ("flores".to_string(), HighlightKind::Variable),
+            ("1".to_string(), HighlightKind::Literal),
+        ]);
+        assert_eq!(en_linea(18), [
+            ("Informar".to_string(), HighlightKind::Instruccion),
+            ("\"listo\"".to_string(), HighlightKind::Literal),
+        ]);
+        assert_eq!(en_linea(21), [
+            ("r1".to_string(), HighlightKind::Robot),
+            ("juntador".to_string(), HighlightKind::Robot),
+        ]);
+        assert_eq!(en_linea(23), [
+            ("AsignarArea".to_string(), HighlightKind::Instruccion),
+            ("r1".to_string(), HighlightKind::Robot),
+            ("ciudad".to_string(), HighlightKind::Area),
+        ]);
+        // Sin un programa que parsear sólo se resaltan las clases léxicas
+        let sueltos: Vec<_> = highlight("mover x 3").into_iter().map(|(_, clase)| clase).collect();
+        assert_eq!(sueltos, [HighlightKind::Instruccion, HighlightKind::Literal]);
+    }
+
+    // El listado se arma aunque el programa no compile, con cada
+    // diagnóstico debajo de su línea
+    #[test]
+    fn test_html_listing_shows_diagnostics_under_their_line() {
+        let source = "programa listado
+procesos
+    proceso vacio
+    comenzar
+        si HayFlorEnLaEsquina
+        tomarFlor
+    fin
+robots
+    robot r
+    comenzar
+        vacio
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let html = listado_html(source, Path::new("entrega.ri"), Opciones::default());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>entrega.ri</title>"));
+        assert!(html.contains("<span class=\"palabra-clave\">proceso</span> <span class=\"proceso\">vacio</span>"));
+        let lineas: Vec<&str> = html.lines().collect();
+        let linea = |numero: &str| lineas.iter().position(|linea| linea.contains(&format!("<span class=\"numero\">{}", numero))).unwrap();
+
+        let si = linea(" 5");
+        assert!(lineas[si].starts_with("<span class=\"linea advertencia\" title=\"'si' sin instrucciones"));
+        assert!(lineas[si + 1].starts_with("<span class=\"nota advertencia\">"));
+        let area = linea("16");
+        assert!(lineas[area].starts_with("<span class=\"linea error\""));
+        assert!(lineas[area].contains("(<span class=\"robot\">r1</span>, ciudad)"));
+        assert!(lineas[area + 1].contains("✗ El argumento 2 de 'AsignarArea' debe ser un área declarada"));
+        assert!(lineas[linea(" 1") + 1].starts_with("<span class=\"linea\">"));
+    }
+
+    // Cada `.ri` del directorio es una fila; sólo con escenario se ejecutan
+    // los que compilan
+    #[test]
+    fn test_batch_grades_every_submission_in_a_directory() {
+        let bien = "programa bien
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    comenzar
+        repetir 3
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let directorio = directorio("lote", &[
+            ("bien.ri", bien),
+            ("comision, b/mal.ri", "programa mal\ncomenzar\n    Iniciar(r1, 1, 1)\nfin\n"),
+            ("notas.txt", "no es un programa"),
+        ]);
+
+        let lote = Lote::corregir(&directorio, &Opciones::default(), None).unwrap();
+        let archivos: Vec<&str> = lote.entregas.iter().map(|entrega| entrega.archivo.as_str()).collect();
+        assert_eq!(archivos, ["bien.ri", "comision, b/mal.ri"]);
+        assert_eq!(lote.entregas[0].desenlace, Desenlace::Compila);
+        assert_eq!(lote.entregas[1].desenlace, Desenlace::NoCompila);
+        assert!(lote.entregas[1].errores > 0);
+        assert_eq!(lote.a_csv().lines().nth(1), Some("bien.ri,0,0,,,,compila,"));
+        assert!(lote.a_csv().contains("\n\"comision, b/mal.ri\","));
+
+        let escenario = Escenario {
+            ciudad: Ciudad::new(),
+            evaluacion: Evaluacion::new().robot_en("r1", 1, 4).robot_en("r1", 2, 2),
+            max_pasos: 1000,
+        };
+        let lote = Lote::corregir(&directorio, &Opciones::default(), Some(&escenario)).unwrap();
+        assert_eq!(lote.entregas[0].desenlace, Desenlace::Terminada);
+        assert_eq!(lote.entregas[0].afirmaciones, Some((1, 2)));
+        assert!(lote.entregas[0].pasos.is_some());
+        assert_eq!(lote.entregas[1].afirmaciones, None);
+        assert!(lote.a_json().contains("\"archivo\": \"bien.ri\", \"errores\": 0, \"advertencias\": 0, \"afirmaciones_cumplidas\": 1, \"afirmaciones\": 2"));
+
+        let corto = Escenario { max_pasos: 2, ..escenario };
+        let lote = Lote::corregir(&directorio, &Opciones::default(), Some(&corto)).unwrap();
+        assert_eq!(lote.entregas[0].desenlace, Desenlace::SinTerminar);
+        assert_eq!(lote.entregas[0].pasos, Some(2));
+    }
+
+    // Con `--message-format json` cada fase escribe sus mensajes y su fin a
+    // medida que termina, y la compilación no sigue después de la primera
+    // con errores
+    #[test]
+    fn test_json_events_stream_diagnostics_by_phase() {
+        let source = "programa eventos\nareas\n  ciudad: AreaC(1, 1, 10, 10)\nrobots\n  robot r\n  variables\n    total: numero\n  comenzar\n    total := 0\n    Informar(totl)\n  fin\nvariables\n  r1: r\ncomenzar\n  AsignarArea(r1, ciudad)\n  Iniciar(r1,\u{00A0}1, 1)\nfin\n";
+        let mut salida = Vec::new();
+        let compilacion = compilar_con_eventos(source, Path::new("eventos.ri"), Opciones::default(), &mut salida).unwrap();
+        assert!(!compilacion.exitosa());
+
+        let salida = String::from_utf8(salida).unwrap();
+        let eventos: Vec<&str> = salida.lines().collect();
+        assert!(eventos.iter().all(|evento| evento.starts_with("{\"reason\": \"") && evento.ends_with('}')));
+        assert_eq!(eventos.len(), 6, "{}", salida);
+        assert!(eventos[0].starts_with("{\"reason\": \"compiler-message\", \"phase\": \"lexer\", \"severity\": \"warning\", \"code\": \"L"));
+        assert!(eventos[0].contains("\"span\": {\"file\": \"eventos.ri\", \"line\": 16, \"column\": 14, \"end_line\": 16, \"end_column\": 15}"));
+        assert_eq!(eventos[1], "{\"reason\": \"phase-finished\", \"phase\": \"lexer\", \"errors\": 0, \"warnings\": 1}");
+        assert_eq!(eventos[2], "{\"reason\": \"phase-finished\", \"phase\": \"parser\", \"errors\": 0, \"warnings\": 0}");
+        assert_eq!(eventos[3], concat!(
+            "{\"reason\": \"compiler-message\", \"phase\": \"semantic\", \"severity\": \"error\", \"code\": \"S010\", ",
+            "\"message\": \"Variable 'totl' no declarada en expresión (en 'r')\", ",
+            "\"span\": {\"file\": \"eventos.ri\", \"line\": 10, \"column\": 14, \"end_line\": 10, \"end_column\": 18}, \"notes\": [], \"help\": null, ",
+            "\"suggestions\": [{\"message\": \"¿se quiso decir 'total'?\", \"span\": {\"file\": \"eventos.ri\", \"line\": 10, \"column\": 14, \"end_line\": 10, \"end_column\": 18}, \"replacement\": \"total\"}]}",
+        ));
+        assert_eq!(eventos[4], "{\"reason\": \"phase-finished\", \"phase\": \"semantic\", \"errors\": 1, \"warnings\": 0}");
+        assert_eq!(eventos[5], "{\"reason\": \"build-finished\", \"success\": false}");
+
+        let mut salida = Vec::new();
+        compilar_con_eventos("programa roto\ncomenzar\n", Path::new("roto.ri"), Opciones::default(), &mut salida).unwrap();
+        let salida = String::from_utf8(salida).unwrap();
+        let fases: Vec<&str> = salida.lines().filter(|evento| !evento.contains("compiler-message")).collect();
+        assert_eq!(fases, [
+            "{\"reason\": \"phase-finished\", \"phase\": \"lexer\", \"errors\": 0, \"warnings\": 0}",
+            "{\"reason\": \"phase-finished\", \"phase\": \"parser\", \"errors\": 1, \"warnings\": 0}",
+            "{\"reason\": \"build-finished\", \"success\": false}",
+        ]);
+
+        // Las advertencias del parser son eventos de su fase, no texto suelto
+        let sin_area = "programa p\nrobots\n  robot r\n  comenzar\n    mover\n  fin\nvariables\n  r1: r\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n";
+        let mut salida = Vec::new();
+        compilar_con_eventos(sin_area, Path::new("p.ri"), Opciones { idioma: Idioma::Ingles, ..Opciones::default() }, &mut salida).unwrap();
+        let salida = String::from_utf8(salida).unwrap();
+        let eventos: Vec<&str> = salida.lines().collect();
+        assert_eq!(eventos[1], concat!(
+            "{\"reason\": \"compiler-message\", \"phase\": \"parser\", \"severity\": \"warning\", \"code\": \"P040\", ",
+            "\"message\": \"Robot 'r1' has no area assigned\", ",
+            "\"span\": {\"file\": \"p.ri\", \"line\": 8, \"column\": 3, \"end_line\": 8, \"end_column\": 5}, \"notes\": [], \"help\": null, \"suggestions\": []}",
+        ));
+        assert_eq!(eventos[2], "{\"reason\": \"phase-finished\", \"phase\": \"parser\", \"errors\": 0, \"warnings\": 1}");
+        assert_eq!(eventos.last(), Some(&"{\"reason\": \"build-finished\", \"success\": true}"));
+
+        // Un diagnóstico que sólo conoce su línea no tiene columnas
+        let sin_iniciar = "programa p\nrobots\n  robot r\n  comenzar\n    mover\n  fin\nvariables\n  r1: r\ncomenzar\nfin\n";
+        let mut salida = Vec::new();
+        compilar_con_eventos(sin_iniciar, Path::new("p.ri"), Opciones::default(), &mut salida).unwrap();
+        let salida = String::from_utf8(salida).unwrap();
+        let error = salida.lines().find(|evento| evento.contains("\"code\": \"S")).unwrap();
+        assert!(error.contains("\"span\": {\"file\": \"p.ri\", \"line\": 8, \"column\": null, \"end_line\": 8, \"end_column\": null}"), "{}", error);
+    }
+}
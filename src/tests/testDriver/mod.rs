@@ -0,0 +1 @@
+pub mod driverTest;
\ No newline at end of file
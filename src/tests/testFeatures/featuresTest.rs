@@ -0,0 +1,112 @@
+use rinfo::lexer::scanner::Lexer;
+use rinfo::parser::processor::Parser;
+use rinfo::semanticizer::analizer::SemanticAnalyzer;
+use rinfo::interpreter::machine::Interpreter;
+use rinfo::interpreter::mock::MockRobot;
+use rinfo::interpreter::runtime::Valor;
+use rinfo::driver::compilar;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(test)]
+mod testing_features {
+    use super::*;
+
+    const PROGRAMA: &str =
+"programa nucleo
+procesos
+    proceso avanzar(E pasos: numero)
+    comenzar
+        repetir pasos
+            mover
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        avanzar(2)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    // El pipeline completo del núcleo debe funcionar sin ninguna feature opcional
+    #[test]
+    fn test_core_pipeline_without_optional_features() {
+        let tokens = Lexer::new(PROGRAMA).tokenize().expect("Failed to tokenize source code");
+        let programa = Parser::new(&tokens).parse().expect("Failed to parse source code");
+
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analizar(&programa).is_ok());
+
+        let mut robot = MockRobot::new();
+        Interpreter::new(&programa)
+            .ejecutar_proceso("avanzar", &[Valor::Numero(2)], &mut robot)
+            .unwrap();
+        assert_eq!(robot.cantidad("mover"), 2);
+    }
+
+    // Compila sin features un crate que usa el núcleo y los módulos
+    // opcionales: la biblioteca compila y sólo fallan los opcionales
+    #[test]
+    fn test_core_builds_without_optional_modules() {
+        let raiz = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let sonda = env::temp_dir().join(format!("rinfo-sin-features-{}", std::process::id()));
+        fs::create_dir_all(sonda.join("src")).unwrap();
+        fs::write(sonda.join("Cargo.toml"), format!(
+"[package]
+name = \"sonda\"
+version = \"0.0.0\"
+edition = \"2024\"
+
+[dependencies]
+app = {{ path = {:?}, default-features = false }}
+
+[workspace]
+", raiz)).unwrap();
+        fs::write(sonda.join("src/main.rs"),
+"use rinfo::driver::compilar;
+use rinfo::interpreter::simulation::Simulation;
+use rinfo::tui;
+use rinfo::render;
+use rinfo::wasm;
+use rinfo::node;
+
+fn main() {}
+").unwrap();
+        fs::copy(raiz.join("Cargo.lock"), sonda.join("Cargo.lock")).unwrap();
+
+        let salida = Command::new(env!("CARGO"))
+            .args(["check", "--offline", "--quiet"])
+            .current_dir(&sonda)
+            .env("CARGO_TARGET_DIR", raiz.join("target").join("sin-features"))
+            .output()
+            .unwrap();
+        let errores = String::from_utf8_lossy(&salida.stderr);
+        assert!(!salida.status.success());
+        for modulo in ["tui", "render", "wasm", "node"] {
+            assert!(errores.contains(&format!("unresolved import `rinfo::{}`", modulo)), "{}", errores);
+        }
+        assert_eq!(errores.matches("error[").count(), 4, "{}", errores);
+        let _ = fs::remove_dir_all(&sonda);
+    }
+
+    #[test]
+    fn test_driver_collects_tokens_ast_and_diagnostics() {
+        let compilacion = compilar(PROGRAMA);
+        assert!(compilacion.exitosa());
+        assert!(!compilacion.tokens.is_empty());
+
+        let compilacion = compilar("programa roto\ncomenzar\n    mover $\nfin\n");
+        assert!(!compilacion.exitosa());
+        assert!(compilacion.programa.is_none());
+        assert_eq!(compilacion.errores.len(), 1);
+    }
+}
@@ -0,0 +1 @@
+pub mod featuresTest;
\ No newline at end of file
@@ -0,0 +1,218 @@
+use rinfo::driver::{compilar, Opciones};
+use rinfo::incremental::CompiladorIncremental;
+use rinfo::interpreter::simulation::Simulation;
+use rinfo::interpreter::world::Ciudad;
+
+#[cfg(test)]
+mod testing_incremental {
+    use super::*;
+
+    const PROGRAMA: &str =
+"programa incremental
+{ proceso comentado
+fin }
+procesos
+    proceso juntar(ES cant: numero)
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+            cant := cant + 1
+    fin
+    proceso avanzar(E pasos: numero)
+    comenzar
+        repetir pasos
+            mover
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    variables
+        total : numero
+    comenzar
+        total := 0
+        juntar(total)
+        avanzar(2)
+        juntar(total)
+        Informar(total)
+    fin
+    robot quieto
+    comenzar
+        derecha
+    fin
+variables
+    r1: juntador
+    r2: quieto
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 5, 5)
+fin
+";
+
+    fn simular(source: &str, incremental: &mut CompiladorIncremental) -> (Simulation, Simulation) {
+        let completo = compilar(source).programa.expect("El programa debe compilar");
+        let compilacion = incremental.compilar(source);
+        assert!(compilacion.exitosa(), "{:?}", compilacion.errores);
+        let parcial = compilacion.programa.unwrap();
+
+        let ciudad = || {
+            let mut ciudad = Ciudad::new();
+            ciudad.poner_flores(1, 1, 2);
+            ciudad.poner_flores(1, 3, 1);
+            ciudad
+        };
+        (
+            Simulation::con_ciudad(&completo, ciudad()).unwrap(),
+            Simulation::con_ciudad(&parcial, ciudad()).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_incremental_program_runs_like_full_compilation() {
+        let mut incremental = CompiladorIncremental::new();
+        let (mut completo, mut parcial) = simular(PROGRAMA, &mut incremental);
+        assert_eq!(incremental.secciones_reutilizadas(), 0);
+
+        // Mismas líneas en los nodos: la simulación pasa por las mismas líneas
+        while !completo.terminada() {
+            completo.paso().unwrap();
+            parcial.paso().unwrap();
+            let linea = |s: &Simulation| s.ultimo_robot().and_then(|r| s.interprete().linea_actual(s.ejecucion(r)?));
+            assert_eq!(linea(&completo), linea(&parcial));
+        }
+        assert!(parcial.terminada());
+        assert_eq!(completo.snapshot(), parcial.snapshot());
+        assert_eq!(parcial.mundo().robot("r1").unwrap().flores, 3);
+    }
+
+    #[test]
+    fn test_only_changed_section_is_recompiled() {
+        let mut incremental = CompiladorIncremental::new();
+        incremental.compilar(PROGRAMA);
+
+        // Cambia el cuerpo de un proceso y se agrega una línea antes de los robots
+        let editado = PROGRAMA
+            .replace("            mover\n", "            mover\n            derecha\n")
+            .replace("areas\n", "\nareas\n");
+        let (mut completo, mut parcial) = simular(&editado, &mut incremental);
+        assert_eq!(incremental.secciones_reutilizadas(), 3);
+
+        completo.ejecutar(1_000).unwrap();
+        parcial.ejecutar(1_000).unwrap();
+        assert_eq!(completo.snapshot(), parcial.snapshot());
+
+        // Un error semántico en una sección nueva se informa, y desaparece al corregirlo
+        let con_error = editado.replace("total := 0", "total := V");
+        let compilacion = incremental.compilar(&con_error);
+        assert_eq!(incremental.secciones_reutilizadas(), 3);
+        assert!(compilacion.errores.iter().any(|e| e.message.contains("'total'")));
+
+        assert!(incremental.compilar(&editado).exitosa());
+        assert_eq!(incremental.secciones_reutilizadas(), 3);
+    }
+
+    #[test]
+    fn test_section_errors_keep_source_lines() {
+        let mut incremental = CompiladorIncremental::new();
+        let con_error = PROGRAMA.replace("    robot quieto\n", "    robot quieto\n    variables\n        x numero\n");
+        let compilacion = incremental.compilar(&con_error);
+
+        let completo = compilar(&con_error);
+        assert_eq!(compilacion.errores.len(), 1);
+        assert_eq!(compilacion.errores[0].line, completo.errores[0].line);
+        assert_eq!(compilacion.errores[0].line, 31);
+    }
+
+    // Los errores semánticos de una sección reutilizada se corren con ella
+    // cuando cambia el código de más arriba
+    #[test]
+    fn test_cached_semantic_errors_follow_their_section() {
+        let programa =
+"programa desplazado
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        listo : booleano
+    comenzar
+        listo := 3
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let mut incremental = CompiladorIncremental::new();
+        let compilacion = incremental.compilar(programa);
+        assert_eq!(compilacion.errores.len(), 1);
+        assert_eq!(compilacion.errores[0].line, 9);
+
+        let desplazado = programa.replace("areas\n", "\n\nareas\n");
+        let compilacion = incremental.compilar(&desplazado);
+        assert_eq!(incremental.secciones_reutilizadas(), 1);
+        let completo = compilar(&desplazado);
+        assert_eq!(completo.errores[0].line, 11);
+        assert_eq!(compilacion.errores.len(), 1);
+        assert_eq!(
+            (compilacion.errores[0].line, compilacion.errores[0].column, &compilacion.errores[0].message),
+            (completo.errores[0].line, completo.errores[0].column, &completo.errores[0].message)
+        );
+    }
+
+    // Todos los errores léxicos de una sección se informan juntos, en las
+    // mismas líneas que en la compilación completa
+    #[test]
+    fn test_section_reports_every_lexical_error() {
+        let mut incremental = CompiladorIncremental::new();
+        let con_errores = PROGRAMA.replace("        derecha
+", "        derecha ?
+        mover $
+");
+        let compilacion = incremental.compilar(&con_errores);
+
+        let completo = compilar(&con_errores);
+        let lineas = |errores: &[rinfo::compilerError::CompilerError]| errores.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(lineas(&compilacion.errores), vec![31, 32]);
+        assert_eq!(lineas(&compilacion.errores), lineas(&completo.errores));
+    }
+
+    // Cambiar sólo el valor de una constante vuelve a verificar las
+    // secciones que la usan, aunque su texto no cambie
+    #[test]
+    fn test_constant_value_change_rechecks_sections() {
+        let programa =
+"programa arreglos
+constantes
+    LADO = 5
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        v : arreglo[3] de numero
+    comenzar
+        v[LADO] := 1
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let opciones = Opciones { extensiones: true, ..Opciones::default() };
+        let mut incremental = CompiladorIncremental::con_opciones(opciones);
+        let compilacion = incremental.compilar(programa);
+        assert_eq!(compilacion.errores.len(), 1);
+        assert!(compilacion.errores[0].message.contains("El índice 5 está fuera del arreglo 'v'"));
+
+        let compilacion = incremental.compilar(&programa.replace("LADO = 5", "LADO = 2"));
+        assert_eq!(incremental.secciones_reutilizadas(), 1);
+        assert!(compilacion.exitosa(), "{:?}", compilacion.errores);
+    }
+}
@@ -0,0 +1 @@
+pub mod incrementalTest;
\ No newline at end of file
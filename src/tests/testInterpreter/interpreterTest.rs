@@ -0,0 +1,1519 @@
+use rinfo::lexer::scanner::Lexer;
+use rinfo::lexer::token::Keywords;
+use rinfo::parser::processor::{Parser, Program};
+use rinfo::interpreter::diagrama::secuencia_mensajes;
+use rinfo::interpreter::code::Operacion;
+use rinfo::interpreter::evaluacion::Evaluacion;
+use rinfo::interpreter::exploracion::{explorar, Desenlace, Limites};
+use rinfo::interpreter::livelock::Repeticion;
+use rinfo::interpreter::machine::Interpreter;
+use rinfo::interpreter::mock::MockRobot;
+use rinfo::interpreter::planificador::{Aleatorio, PorPrioridad};
+use rinfo::interpreter::reporte::Reporte;
+use rinfo::interpreter::runtime::{RuntimeError, Valor};
+use rinfo::interpreter::simulation::{EstadoSimulacion, Simulation};
+use rinfo::interpreter::world::{Ciudad, Direccion};
+
+#[cfg(test)]
+mod testing_interpreter {
+    use super::*;
+
+    // Los programas de estos tests sólo declaran procesos, sin bloque principal
+    fn parsear(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("Failed to tokenize source code");
+        Parser::new(&tokens).sin_bloque_principal().parse().expect("Failed to parse source code")
+    }
+
+    #[test]
+    fn test_records_actions_with_arguments() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso irA(E av: numero)
+    comenzar
+        Pos(av, 1)
+        repetir 3
+            mover
+        derecha
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new();
+
+        interprete.ejecutar_proceso("irA", &[Valor::Numero(5)], &mut robot).unwrap();
+
+        assert_eq!(robot.nombres_acciones(), vec!["Pos", "mover", "mover", "mover", "derecha"]);
+        assert_eq!(robot.acciones()[0].argumentos, vec![Valor::Numero(5), Valor::Numero(1)]);
+    }
+
+    #[test]
+    fn test_scripted_sensor_drives_loop() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso juntarFlores(ES cant: numero)
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+            cant := cant + 1
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new()
+            .con_sensor("HayFlorEnLaEsquina", [Valor::Booleano(true), Valor::Booleano(true)])
+            .con_sensor_fijo("HayFlorEnLaEsquina", Valor::Booleano(false));
+
+        let variables = interprete
+            .ejecutar_proceso("juntarFlores", &[Valor::Numero(0)], &mut robot)
+            .unwrap();
+
+        assert_eq!(robot.cantidad("tomarFlor"), 2);
+        assert_eq!(robot.lecturas().len(), 3);
+        assert_eq!(variables["cant"], Valor::Numero(2));
+    }
+
+    #[test]
+    fn test_unscripted_sensor_is_an_error() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso p
+    variables
+        av : numero
+    comenzar
+        av := PosAv
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new();
+
+        let resultado = interprete.ejecutar_proceso("p", &[], &mut robot);
+
+        assert_eq!(resultado.unwrap_err(), RuntimeError::SensorNotScripted("PosAv".to_string()));
+    }
+
+    #[test]
+    fn test_nested_process_call_writes_back_es_parameter() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso sumar(ES total: numero)
+    comenzar
+        total := total + PosCa
+    fin
+    proceso principal
+    variables
+        t : numero
+    comenzar
+        t := 10
+        sumar(t)
+        sumar(t)
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new().con_sensor("PosCa", [Valor::Numero(3), Valor::Numero(4)]);
+
+        let variables = interprete.ejecutar_proceso("principal", &[], &mut robot).unwrap();
+
+        assert_eq!(variables["t"], Valor::Numero(17));
+        assert!(robot.acciones().is_empty());
+    }
+
+    #[test]
+    fn test_simulation_moves_robot_and_collects_flowers() {
+        let programa = parsear(
+"programa sim
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+        repetir 3
+            mover
+        derecha
+        Informar(PosAv, PosCa)
+    fin
+variables
+    r1: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 2, 5)
+fin
+");
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(2, 5, 2);
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).unwrap();
+
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!((robot.av, robot.ca), (2, 8));
+        assert_eq!(robot.direccion, Direccion::Este);
+        assert_eq!(robot.flores, 2);
+        assert_eq!(robot.informes, vec!["2 8".to_string()]);
+        assert_eq!(simulacion.mundo().ciudad.esquina(2, 5).unwrap().flores, 0);
+    }
+
+    #[test]
+    fn test_simulation_delivers_messages_between_robots() {
+        let programa = parsear(
+"programa mensajes
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r2)
+        Informar(dato)
+    fin
+    robot emisor
+    comenzar
+        EnviarMensaje(7, r1)
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+        assert_eq!(simulacion.mundo().robot("r1").unwrap().informes, vec!["7".to_string()]);
+    }
+
+    // Las instancias de un mismo tipo comparten el cuerpo pero tienen sus
+    // propias variables, y cada una recibe los mensajes enviados a su nombre
+    #[test]
+    fn test_instances_of_one_robot_type() {
+        let programa = parsear(
+"programa exploracion
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot explorador
+    variables
+        pasos: numero
+    comenzar
+        RecibirMensaje(pasos, jefe)
+        repetir pasos
+            mover
+        Informar(PosCa)
+    fin
+    robot coordinador
+    comenzar
+        EnviarMensaje(3, exp3)
+        EnviarMensaje(1, exp1)
+        EnviarMensaje(2, exp2)
+    fin
+variables
+    exp1, exp2, exp3: explorador
+    jefe: coordinador
+comenzar
+    AsignarArea(exp1, ciudad)
+    AsignarArea(exp2, ciudad)
+    AsignarArea(exp3, ciudad)
+    AsignarArea(jefe, ciudad)
+    Iniciar(exp1, 1, 1)
+    Iniciar(exp2, 2, 1)
+    Iniciar(exp3, 3, 1)
+    Iniciar(jefe, 4, 1)
+fin
+");
+        let instancias: Vec<_> = programa.robots_instanciados.iter()
+            .map(|robot| (robot.nombre.as_str(), robot.tipo.as_str()))
+            .collect();
+        assert_eq!(instancias, [("exp1", "explorador"), ("exp2", "explorador"), ("exp3", "explorador"), ("jefe", "coordinador")]);
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+        for (robot, calle) in [("exp1", "2"), ("exp2", "3"), ("exp3", "4")] {
+            assert_eq!(simulacion.mundo().robot(robot).unwrap().informes, [calle]);
+        }
+    }
+
+    // Un error dentro de procesos anidados informa cada rutina de la pila con
+    // la línea del lugar de la llamada, y la del error en la última
+    #[test]
+    fn test_runtime_error_reports_the_call_chain() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso juntarFlores
+    comenzar
+        tomarFlor
+    fin
+    proceso recorrer
+    comenzar
+        mover
+        juntarFlores
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    comenzar
+        recorrer
+    fin
+variables
+    r1: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+
+        let error = simulacion.ejecutar(100).unwrap_err();
+        let RuntimeError::EnLlamada { robot, pila, .. } = &error else {
+            panic!("Se esperaba la pila de llamadas: {:?}", error);
+        };
+        assert_eq!(robot, "r1");
+        let pila: Vec<_> = pila.iter().map(|llamada| (llamada.rutina.nombre(), llamada.linea)).collect();
+        assert_eq!(pila, [("juntador", Some(17)), ("recorrer", Some(10)), ("juntarFlores", Some(5))]);
+        assert_eq!(
+            error.to_string(),
+            "No hay flores en la esquina (robot 'r1' en (1, 2)); en programa principal → robot r1 línea 17 → proceso recorrer línea 10 → proceso juntarFlores línea 5"
+        );
+    }
+
+    // Sin el análisis semántico, el tipo del mensaje se controla al recibirlo
+    #[test]
+    fn test_received_message_must_match_variable_type() {
+        let programa = parsear(
+"programa mensajes
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r2)
+    fin
+    robot emisor
+    comenzar
+        EnviarMensaje(V, r1)
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+
+        let error = simulacion.ejecutar(1_000).unwrap_err();
+        assert_eq!(*error.causa(), RuntimeError::MessageTypeMismatch {
+            emisor: "r2".to_string(),
+            variable: "dato".to_string(),
+            esperado: "numero".to_string(),
+            encontrado: "booleano".to_string(),
+        });
+        assert_eq!(error.to_string(), "Mensaje de 'r2' de tipo 'booleano', pero la variable 'dato' es de tipo 'numero'; en programa principal → robot r1 línea 9");
+    }
+
+    #[test]
+    fn test_message_trace_renders_as_sequence_diagram() {
+        let programa = parsear(
+"programa protocolo
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot jefe
+    variables
+        total : numero
+    comenzar
+        EnviarMensaje(1, r2)
+        RecibirMensaje(total, r2)
+        EnviarMensaje(total + 1, r2)
+    fin
+    robot trabajador
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r1)
+        EnviarMensaje(dato * 10, r1)
+        RecibirMensaje(dato, r1)
+    fin
+variables
+    r1: jefe
+    r2: trabajador
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+
+        assert_eq!(secuencia_mensajes(simulacion.mundo()),
+"sequenceDiagram
+    participant r1 as r1 (jefe)
+    participant r2 as r2 (trabajador)
+    r1-)r2: 1
+    Note over r2: recibe 1 de r1
+    r2-)r1: 10
+    Note over r1: recibe 10 de r2
+    r1-)r2: 11
+    Note over r2: recibe 11 de r1
+");
+    }
+
+    #[test]
+    fn test_simulation_reports_deadlock() {
+        let programa = parsear(
+"programa espera
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r1)
+    fin
+variables
+    r1: receptor
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+
+        assert_eq!(simulacion.paso().unwrap(), EstadoSimulacion::Bloqueada);
+        assert_eq!(simulacion.ejecutar(10).unwrap_err(), RuntimeError::Deadlock);
+    }
+
+    #[test]
+    fn test_snapshot_restores_world_and_execution() {
+        let programa = parsear(
+"programa foto
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot caminante
+    variables
+        pasos : numero
+    comenzar
+        pasos := 0
+        repetir 4
+            mover
+            pasos := pasos + 1
+        tomarFlor
+    fin
+variables
+    r1: caminante
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 3, 3)
+fin
+");
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(3, 7, 1);
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).unwrap();
+
+        for _ in 0..4 {
+            simulacion.paso().unwrap();
+        }
+        let foto = simulacion.snapshot();
+
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+        let final_original = simulacion.snapshot();
+
+        simulacion.restore(foto.clone()).unwrap();
+        assert_eq!(simulacion.snapshot(), foto);
+        assert_eq!(simulacion.estado(), EstadoSimulacion::Ejecutando);
+
+        // Reanudar desde la foto reproduce exactamente la misma ejecución
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+        assert_eq!(simulacion.snapshot(), final_original);
+
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!((robot.av, robot.ca, robot.flores), (3, 7, 1));
+        assert_eq!(simulacion.ejecucion(0).unwrap().variables().get("pasos"), Some(&Valor::Numero(4)));
+    }
+
+    #[test]
+    fn test_restore_rejects_state_from_another_program() {
+        let programa = parsear(
+"programa vacio
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot quieto
+    comenzar
+        derecha
+    fin
+variables
+    r1: quieto
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        let mut foto = simulacion.snapshot();
+        foto.ejecuciones.clear();
+
+        assert!(simulacion.restore(foto).is_err());
+        assert_eq!(simulacion.ejecutar(10).unwrap(), EstadoSimulacion::Terminada);
+    }
+
+    // Cada argumento es una expresión completa, también en las instrucciones elementales
+    #[test]
+    fn test_arguments_are_full_expressions() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso irA(E x: numero, E y: numero)
+    comenzar
+        Pos(x + 1, 2 * y)
+        Informar((x + y) * 2)
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new();
+
+        interprete.ejecutar_proceso("irA", &[Valor::Numero(4), Valor::Numero(3)], &mut robot).unwrap();
+
+        assert_eq!(robot.acciones()[0].argumentos, vec![Valor::Numero(5), Valor::Numero(6)]);
+        assert_eq!(robot.acciones()[1].argumentos, vec![Valor::Numero(14)]);
+    }
+
+    // Las constantes valen lo mismo en todas las rutinas y en las
+    // coordenadas de Iniciar
+    #[test]
+    fn test_constants_are_visible_from_every_routine() {
+        let programa = parsear(
+"programa valores_fijos
+constantes
+    PASOS = 2
+    INICIO = PASOS * 5
+procesos
+    proceso avanzar
+    comenzar
+        repetir PASOS
+            mover
+    fin
+areas
+    ciudad: AreaC (1,1,INICIO + 10,INICIO + 10)
+robots
+    robot r
+    comenzar
+        avanzar
+        Informar(PosCa - INICIO)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, INICIO, INICIO)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(100).unwrap(), EstadoSimulacion::Terminada);
+
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!((robot.av, robot.ca), (10, 12));
+        assert_eq!(robot.informes, vec!["2".to_string()]);
+    }
+
+    // En una cadena de `sino si` se ejecuta sólo la primera rama cuya
+    // condición es verdadera, o el `sino` si no hay ninguna
+    #[test]
+    fn test_else_if_chain_runs_the_first_true_branch() {
+        let programa = parsear(
+"programa cadena
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        n := 0
+        repetir 4
+            n := n + 1
+            si n == 1
+                Informar(10)
+            sino si n < 4
+                Informar(20)
+            sino si n == 3
+                Informar(30)
+            sino
+                Informar(40)
+        si n > 5
+            Informar(1)
+        sino si n == 4
+            Informar(4)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(200).unwrap(), EstadoSimulacion::Terminada);
+
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!(robot.informes, ["10", "20", "20", "40", "4"]);
+    }
+
+    // `segun` ejecuta el primer caso igual al selector, o el `sino`; sin
+    // `sino` y sin coincidencias no ejecuta nada
+    #[test]
+    fn test_switch_runs_the_matching_case() {
+        let source =
+"programa elegir
+constantes
+    DOS = 2
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        repetir 4
+            n := n + 1
+            segun n
+                caso 1:
+                    Informar(10)
+                caso DOS:
+                    Informar(20)
+                caso DOS + 1:
+                    Informar(30)
+                sino
+                    Informar(40)
+        segun n > 5
+            caso V:
+                Informar(1)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(200).unwrap(), EstadoSimulacion::Terminada);
+        assert_eq!(simulacion.mundo().robot("r1").unwrap().informes, ["10", "20", "30", "40"]);
+    }
+
+    // La variable del `para` avanza de a uno hasta el límite, incluido; si
+    // el límite es menor que el comienzo el cuerpo no se ejecuta
+    #[test]
+    fn test_for_loop_counts_up_to_its_bound() {
+        let source =
+"programa contar
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        n := 3
+        para i := 1 hasta n
+            Informar(i)
+        para i := n hasta 1
+            Informar(0)
+        para j := 2 * n hasta 2 * n + 1
+            Informar(j)
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(200).unwrap(), EstadoSimulacion::Terminada);
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!(robot.informes, ["1", "2", "3", "6", "7"]);
+        assert_eq!((robot.av, robot.ca), (1, 3));
+    }
+
+    // Un proceso que devuelve un valor se ejecuta en su propio registro,
+    // también cuando se llama a sí mismo, y lo que devuelve es lo último que
+    // se asignó a su nombre
+    #[test]
+    fn test_processes_return_values_into_expressions() {
+        let source =
+"programa funciones
+procesos
+    proceso factorial(E n: numero): numero
+    comenzar
+        si n <= 1
+            factorial := 1
+        sino
+            factorial := n * factorial(n - 1)
+    fin
+    proceso avanzar(E pasos: numero): booleano
+    comenzar
+        repetir pasos
+            mover
+        avanzar := PosCa < 3
+    fin
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        n := factorial(4) + 1
+        Informar(n)
+        mientras avanzar(1)
+            Informar(PosCa)
+        Informar(factorial(factorial(3)))
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(500).unwrap(), EstadoSimulacion::Terminada);
+        let robot = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!(robot.informes, ["25", "2", "720"]);
+        assert_eq!(robot.ca, 3);
+    }
+
+    // Cada elemento de un arreglo empieza con el valor de su tipo, y un
+    // índice que sólo se conoce al ejecutar se verifica al usarlo
+    #[test]
+    fn test_arrays_store_one_value_per_index() {
+        let source =
+"programa flores
+constantes
+    AVENIDAS = 3
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        cantidades : arreglo[AVENIDAS] de numero
+        n : numero
+    comenzar
+        derecha
+        para av := 1 hasta AVENIDAS
+            mientras HayFlorEnLaEsquina
+                tomarFlor
+                cantidades[av] := cantidades[av] + 1
+            mover
+        para av := 1 hasta AVENIDAS
+            Informar(cantidades[av])
+        n := AVENIDAS + 1
+        cantidades[n] := 0
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(1, 1, 2);
+        ciudad.poner_flores(3, 1, 1);
+
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).unwrap();
+        let error = simulacion.ejecutar(500).unwrap_err();
+        assert_eq!(*error.causa(), RuntimeError::IndexOutOfBounds { arreglo: "cantidades".to_string(), indice: 4, longitud: 3 });
+        assert_eq!(simulacion.mundo().robot("r1").unwrap().informes, ["2", "0", "1"]);
+    }
+
+    // Los parámetros E son copias; sólo los ES se devuelven al llamador, y el
+    // proceso llamado no ve las variables de quien lo llama
+    #[test]
+    fn test_called_process_sees_only_its_parameters_and_locals() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso duplicar(E valor: numero, ES doble: numero)
+    comenzar
+        doble := valor * 2
+        valor := 0
+    fin
+    proceso espiar
+    comenzar
+        Informar(base)
+    fin
+    proceso principal
+    variables
+        base : numero
+        resultado : numero
+    comenzar
+        base := 4
+        duplicar(base, resultado)
+    fin
+    proceso curioso
+    variables
+        base : numero
+    comenzar
+        espiar
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+
+        let variables = interprete.ejecutar_proceso("principal", &[], &mut MockRobot::new()).unwrap();
+        assert_eq!(variables["base"], Valor::Numero(4));
+        assert_eq!(variables["resultado"], Valor::Numero(8));
+
+        let resultado = interprete.ejecutar_proceso("curioso", &[], &mut MockRobot::new());
+        assert_eq!(resultado.unwrap_err(), RuntimeError::UndefinedVariable("base".to_string()));
+    }
+
+    // Cada paso que avanza cuenta para su línea y su clase de operación; el
+    // paso que termina un proceso cuenta como retorno, sin línea
+    #[test]
+    fn test_profile_counts_steps_per_line_and_operation() {
+        let programa = parsear(
+"programa perfil
+procesos
+    proceso girar
+    comenzar
+        derecha
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot caminante
+    comenzar
+        repetir 4
+            mover
+        girar
+    fin
+variables
+    r1: caminante
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+
+        let perfil = simulacion.perfil();
+        assert_eq!(perfil.total(), simulacion.pasos());
+        assert_eq!(perfil.lineas_mas_ejecutadas(2), vec![(12, 10), (13, 4)]);
+        assert_eq!(perfil.ejecuciones_linea(14), 1);
+        assert_eq!(perfil.ejecuciones_clase("acción"), 5);
+        assert_eq!(perfil.ejecuciones_clase("repetir"), 6);
+        assert_eq!(perfil.ejecuciones_clase("retorno"), 2);
+        assert_eq!(perfil.clases()[0], ("repetir", 6));
+    }
+
+    // Un robot que da vueltas sin cambiar nada y otro que espera su esquina se
+    // avisan una vez cada uno; un `mientras` que cuenta con una variable no
+    #[test]
+    fn test_watchdog_reports_possible_livelock() {
+        let programa = parsear(
+"programa espera
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot dueno
+    variables
+        n : numero
+    comenzar
+        BloquearEsquina(3, 3)
+        repetir 300
+            n := n + 1
+        mientras PosAv < 5
+            HayFlorEnLaEsquina
+    fin
+    robot visitante
+    comenzar
+        BloquearEsquina(3, 3)
+    fin
+variables
+    r1: dueno
+    r2: visitante
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        simulacion.fijar_umbral_livelock(50);
+
+        assert_eq!(simulacion.ejecutar(2_000).unwrap(), EstadoSimulacion::Ejecutando);
+
+        let avisos: Vec<_> = simulacion.avisos_livelock()
+            .iter()
+            .map(|aviso| (aviso.robot.as_str(), aviso.linea, aviso.repeticion))
+            .collect();
+        assert_eq!(avisos, vec![("r2", 17, Repeticion::Espera), ("r1", 12, Repeticion::Condicion)]);
+        assert_eq!(
+            simulacion.avisos_livelock()[1].to_string(),
+            "posible livelock: el robot 'r1' evaluó la condición 50 veces en la línea 12 sin cambios en el mundo"
+        );
+    }
+
+    // Los procesos chicos se copian en cada llamada con sus variables
+    // renombradas; los recursivos y los que superan el umbral se siguen llamando
+    #[test]
+    fn test_small_processes_are_inlined_with_the_same_result() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso sumar(E valor: numero, ES total: numero)
+    variables
+        previo : numero
+    comenzar
+        repetir valor
+            previo := previo + 1
+        total := total + previo
+    fin
+    proceso cuenta(ES n: numero)
+    comenzar
+        si n > 0
+            n := n - 1
+            cuenta(n)
+    fin
+    proceso largo
+    comenzar
+        repetir 2
+            mover
+            mover
+            derecha
+    fin
+    proceso principal
+    variables
+        total : numero
+        n : numero
+    comenzar
+        n := 5
+        sumar(1, total)
+        repetir 3
+            sumar(2, total)
+        cuenta(n)
+        largo
+        Informar(total, n)
+    fin
+");
+        let original = Interpreter::new(&programa);
+        let mut expandido = Interpreter::new(&programa);
+
+        assert_eq!(expandido.expandir_procesos(5), 2);
+
+        let llamadas: Vec<_> = expandido.proceso("principal").unwrap().operaciones
+            .iter()
+            .filter_map(|operacion| match operacion {
+                Operacion::Llamar { proceso, .. } => Some(proceso.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(llamadas, vec!["cuenta", "largo"]);
+
+        let mut robot_original = MockRobot::new();
+        let mut robot_expandido = MockRobot::new();
+        let variables = original.ejecutar_proceso("principal", &[], &mut robot_original).unwrap();
+        let expandidas = expandido.ejecutar_proceso("principal", &[], &mut robot_expandido).unwrap();
+
+        assert_eq!(variables["total"], Valor::Numero(7));
+        assert_eq!(expandidas["total"], variables["total"]);
+        assert_eq!(expandidas["n"], Valor::Numero(0));
+        assert_eq!(robot_expandido.acciones(), robot_original.acciones());
+    }
+
+    // Expandir los procesos no cambia la simulación: cada robot avanza en
+    // los mismos pasos, así que el intercalado, dónde queda cada robot
+    // después de su paso y el mundo final son los mismos que con las llamadas
+    #[test]
+    fn test_inlining_keeps_steps_and_trace() {
+        let programa = parsear(
+"programa expandido
+procesos
+    proceso avanzar(E pasos: numero, ES total: numero)
+    variables
+        dados : numero
+    comenzar
+        repetir pasos
+            mover
+            dados := dados + 1
+        total := total + dados
+    fin
+    proceso vuelta
+    comenzar
+        derecha
+        avanzar(1, cuenta)
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot caminante
+    variables
+        cuenta : numero
+    comenzar
+        avanzar(2, cuenta)
+        EnviarMensaje(cuenta, r2)
+        avanzar(3, cuenta)
+        Informar(cuenta)
+    fin
+    robot receptor
+    variables
+        dato : numero
+        cuenta : numero
+    comenzar
+        RecibirMensaje(dato, r1)
+        avanzar(dato, cuenta)
+        Informar(cuenta)
+    fin
+variables
+    r1: caminante
+    r2: receptor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 5, 1)
+fin
+");
+        let traza = |umbral: Option<usize>| {
+            let mut interprete = Interpreter::new(&programa);
+            if let Some(umbral) = umbral {
+                assert_eq!(interprete.expandir_procesos(umbral), 3);
+            }
+            let mut simulacion = Simulation::con_interprete(&programa, Ciudad::new(), interprete).unwrap();
+            let mut pasos = Vec::new();
+            while simulacion.paso().unwrap() != EstadoSimulacion::Terminada {
+                let robot = &simulacion.mundo().robots[simulacion.ultimo_robot().unwrap()];
+                pasos.push((robot.nombre.clone(), robot.av, robot.ca, robot.informes.clone()));
+            }
+            (simulacion.pasos(), pasos, simulacion.snapshot().mundo)
+        };
+
+        let (pasos, intercalado, final_) = traza(None);
+        let (pasos_expandido, intercalado_expandido, final_expandido) = traza(Some(10));
+        assert_eq!(pasos_expandido, pasos);
+        assert_eq!(intercalado_expandido, intercalado);
+        assert_eq!(final_expandido, final_);
+    }
+
+    // `V` y `F` sirven como valores y como condiciones, y `v` puede ser una variable
+    // Robot que avanzó en cada paso hasta terminar
+    fn intercalado(simulacion: &mut Simulation) -> Vec<usize> {
+        let mut robots = Vec::new();
+        while simulacion.paso().unwrap() != EstadoSimulacion::Terminada {
+            robots.push(simulacion.ultimo_robot().unwrap());
+        }
+        robots.push(simulacion.ultimo_robot().unwrap());
+        robots
+    }
+
+    #[test]
+    fn test_scheduler_policies_change_the_interleaving() {
+        let programa = parsear(
+"programa turnos
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        mover
+        RecibirMensaje(dato, r2)
+        mover
+    fin
+    robot emisor
+    comenzar
+        mover
+        mover
+        EnviarMensaje(7, r1)
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        // Por turnos se alternan mientras r1 no tenga que esperar el mensaje
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(intercalado(&mut simulacion), vec![0, 1, 1, 1, 0, 1, 0, 0]);
+
+        // Con más prioridad r1 avanza primero, pero igual tiene que esperar
+        // el mensaje: un robot bloqueado no traba a los demás
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        simulacion.fijar_planificador(Box::new(PorPrioridad::new().con("r1", 10)));
+        assert_eq!(intercalado(&mut simulacion), vec![0, 1, 1, 1, 0, 0, 0, 1]);
+
+        // La misma semilla repite el mismo intercalado
+        let aleatorio = |semilla| {
+            let mut simulacion = Simulation::new(&programa).unwrap();
+            simulacion.fijar_planificador(Box::new(Aleatorio::new(semilla)));
+            intercalado(&mut simulacion)
+        };
+        assert_eq!(aleatorio(42), aleatorio(42));
+        let intercalados: Vec<_> = (0..8).map(aleatorio).collect();
+        assert!(intercalados.iter().any(|robots| robots != &intercalados[0]), "{:?}", intercalados);
+    }
+
+    #[test]
+    fn test_exploration_finds_check_then_act_race() {
+        let programa = parsear(
+"programa carrera
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    comenzar
+        Pos(5,5)
+        si HayFlorEnLaEsquina
+            tomarFlor
+        Pos(PosAv, 1)
+    fin
+variables
+    r1: juntador
+    r2: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 1)
+fin
+");
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(5, 5, 1);
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad.clone()).unwrap();
+        let exploracion = explorar(&mut simulacion, Limites::default()).unwrap();
+
+        assert!(exploracion.completa);
+        assert!(!exploracion.determinista());
+        // Se queda la flor uno u otro, o los dos ven la flor y el segundo falla
+        let errores: Vec<_> = exploracion.resultados
+            .iter()
+            .filter_map(|resultado| match &resultado.desenlace {
+                Desenlace::Error(mensaje) => Some(mensaje.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errores.len(), 2, "{:?}", exploracion.resultados);
+        assert!(errores.iter().all(|mensaje| mensaje.contains("No hay flores")), "{:?}", errores);
+        let ganadores: Vec<_> = exploracion.resultados
+            .iter()
+            .filter_map(|resultado| resultado.desenlace.foto())
+            .map(|foto| foto.robots.iter().find(|robot| robot.flores == 1).unwrap().nombre.as_str())
+            .collect();
+        assert_eq!(ganadores, vec!["r1", "r2"]);
+        assert_eq!(exploracion.resultados.iter().map(|resultado| resultado.ejecuciones).sum::<usize>(), exploracion.ejecuciones);
+
+        // Sin cambios de turno forzados sólo importa qué robot empieza
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).unwrap();
+        let exploracion = explorar(&mut simulacion, Limites { cambios: 0, ..Limites::default() }).unwrap();
+        assert_eq!(exploracion.ejecuciones, 2);
+        assert_eq!(exploracion.tramos(&exploracion.resultados[1].intercalado), "r2×5, r1×4");
+    }
+
+    #[test]
+    fn test_exploration_of_message_passing_is_deterministic() {
+        let programa = parsear(
+"programa mensajes
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r2)
+        Informar(dato)
+    fin
+    robot emisor
+    comenzar
+        mover
+        EnviarMensaje(7, r1)
+        mover
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        let exploracion = explorar(&mut simulacion, Limites::default()).unwrap();
+
+        assert!(exploracion.completa && exploracion.determinista(), "{:?}", exploracion.resultados);
+        assert!(exploracion.ejecuciones > 1);
+        assert!(matches!(&exploracion.resultados[0].desenlace, Desenlace::Terminada(foto) if foto.robots[0].informes == ["7"]));
+    }
+
+    #[test]
+    fn test_boolean_literals_end_to_end() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso p
+    variables
+        x : booleano
+        v : numero
+    comenzar
+        x := V
+        si F
+            v := 1
+        sino
+            v := 2
+        si x & V
+            Informar(x, F, v)
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new();
+
+        let variables = interprete.ejecutar_proceso("p", &[], &mut robot).unwrap();
+
+        assert_eq!(variables["v"], Valor::Numero(2));
+        assert_eq!(robot.acciones()[0].argumentos, vec![Valor::Booleano(true), Valor::Booleano(false), Valor::Numero(2)]);
+    }
+
+    #[test]
+    fn test_strings_are_informed_and_stored() {
+        let programa = parsear(
+"programa prueba
+procesos
+    proceso p
+    variables
+        texto : cadena
+    comenzar
+        texto := 'total:'
+        si texto == 'total:'
+            Informar(texto, 3)
+    fin
+");
+        let interprete = Interpreter::new(&programa);
+        let mut robot = MockRobot::new();
+
+        let variables = interprete.ejecutar_proceso("p", &[], &mut robot).unwrap();
+
+        assert_eq!(variables["texto"], Valor::Cadena("total:".to_string()));
+        assert_eq!(robot.acciones()[0].argumentos, vec![Valor::Cadena("total:".to_string()), Valor::Numero(3)]);
+    }
+
+    #[test]
+    fn test_assertions_grade_the_final_world() {
+        let programa = parsear(
+"programa corregido
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    comenzar
+        tomarFlor
+        mover
+        depositarFlor
+        Informar('listo', PosCa)
+    fin
+variables
+    r1: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 3, 3)
+fin
+");
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(3, 3, 2);
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+
+        let evaluacion = Evaluacion::desde_texto(
+"{ resultado esperado }
+flores 3 4 1
+flores 3 3 2
+robot r1 en 3 4
+robot r1 informa listo 4
+robot r2 papeles 0
+").unwrap()
+            .afirmar("el robot mira al norte", |mundo| mundo.robots[0].direccion == Direccion::Norte);
+        let informe = evaluacion.evaluar(simulacion.mundo());
+
+        assert!(!informe.aprobado());
+        assert_eq!(informe.cumplidas(), 4);
+        let fallos: Vec<_> = informe.resultados.iter().filter_map(|resultado| resultado.fallo.as_deref()).collect();
+        assert_eq!(fallos, vec!["hay 1", "el robot no existe"]);
+        assert!(informe.to_string().contains("✗ la esquina (3, 3) tiene 2 flores: hay 1"), "{}", informe);
+        assert!(informe.to_string().ends_with("4 de 6 afirmaciones cumplidas"));
+
+        let error = Evaluacion::desde_texto("flores 3 4\n").err().unwrap();
+        assert_eq!((error.line, error.message.as_str()), (1, "Afirmación desconocida: 'flores 3 4'"));
+        assert!(Evaluacion::desde_texto("robot r1 flores -1").is_err());
+    }
+
+    // Un jefe manda la misma esquina a todos y espera la respuesta de
+    // cualquiera de los dos trabajadores, en el orden en que lleguen
+    #[test]
+    fn test_broadcast_and_receive_from_anyone() {
+        let programa = parsear(
+"programa difusion
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot jefe
+    variables
+        listo : numero
+    comenzar
+        EnviarMensaje(5, *)
+        RecibirMensaje(listo, *)
+        RecibirMensaje(listo, *)
+        Informar(listo)
+    fin
+    robot trabajador
+    variables
+        calle : numero
+    comenzar
+        RecibirMensaje(calle, *)
+        Pos(PosAv, calle)
+        EnviarMensaje(PosAv, jefe1)
+    fin
+variables
+    jefe1: jefe
+    t1: trabajador
+    t2: trabajador
+comenzar
+    AsignarArea(jefe1, ciudad)
+    AsignarArea(t1, ciudad)
+    AsignarArea(t2, ciudad)
+    Iniciar(jefe1, 1, 1)
+    Iniciar(t1, 2, 1)
+    Iniciar(t2, 3, 1)
+fin
+");
+        assert!(rinfo::parser::printer::a_codigo(&programa).contains("        EnviarMensaje(5, *)\n"));
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+
+        let mundo = simulacion.mundo();
+        for trabajador in ["t1", "t2"] {
+            let robot = mundo.robot(trabajador).unwrap();
+            assert_eq!(robot.ca, 5);
+            let estadisticas = &robot.estadisticas;
+            assert_eq!((estadisticas.mensajes_recibidos, estadisticas.difusiones_recibidas), (1, 1));
+            assert_eq!((estadisticas.mensajes_enviados, estadisticas.mensajes_difundidos), (1, 0));
+        }
+        let jefe = mundo.robot("jefe1").unwrap();
+        assert_eq!(jefe.informes.len(), 1);
+        let estadisticas = &jefe.estadisticas;
+        assert_eq!((estadisticas.mensajes_enviados, estadisticas.mensajes_difundidos), (0, 1));
+        assert_eq!((estadisticas.mensajes_recibidos, estadisticas.difusiones_recibidas), (2, 0));
+        // La difusión queda en la traza como un envío a cada robot
+        assert_eq!(secuencia_mensajes(mundo).matches("jefe1-)").count(), 2);
+    }
+
+    #[test]
+    fn test_receive_with_timeout_stops_waiting() {
+        let source =
+"programa espera
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        dato := 7
+        RecibirMensajeTimeout(dato, r2, 3)
+        si ~ RecibioMensaje
+            Informar(dato)
+        RecibirMensajeTimeout(dato, r2, 50)
+        si RecibioMensaje
+            Informar(dato)
+    fin
+    robot emisor
+    comenzar
+        repetir 10
+            mover
+        EnviarMensaje(PosCa, r1)
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 1)
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).sin_bloque_principal().parse().unwrap();
+
+        let mut simulacion = Simulation::new(&programa).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000).unwrap(), EstadoSimulacion::Terminada);
+
+        // La primera espera vence antes de que llegue el mensaje y la
+        // variable conserva su valor; la segunda lo recibe
+        let receptor = simulacion.mundo().robot("r1").unwrap();
+        assert_eq!(receptor.informes, vec!["7".to_string(), "11".to_string()]);
+        assert_eq!(receptor.estadisticas.mensajes_recibidos, 1);
+        assert!(receptor.estadisticas.esperas_mensaje >= 3);
+    }
+
+    #[test]
+    fn test_report_collects_robot_statistics() {
+        let programa = parsear(
+"programa estadisticas
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot receptor
+    variables
+        dato : numero
+    comenzar
+        RecibirMensaje(dato, r2)
+        tomarFlor
+        depositarFlor
+    fin
+    robot emisor
+    comenzar
+        mover
+        Pos(5, 5)
+        EnviarMensaje(7, r1)
+    fin
+variables
+    r1: receptor
+    r2: emisor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+");
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(1, 1, 1);
+        let mut simulacion = Simulation::con_ciudad(&programa, ciudad).unwrap();
+        let resultado = simulacion.ejecutar(1_000);
+        assert_eq!(resultado, Ok(EstadoSimulacion::Terminada));
+
+        let receptor = &simulacion.mundo().robot("r1").unwrap().estadisticas;
+        assert_eq!((receptor.flores_tomadas, receptor.flores_depositadas, receptor.mensajes_recibidos), (1, 1, 1));
+        assert_eq!((receptor.acciones, receptor.recorrido), (3, 0));
+        assert!(receptor.esperas_mensaje > 0);
+        let emisor = &simulacion.mundo().robot("r2").unwrap().estadisticas;
+        assert_eq!((emisor.acciones, emisor.recorrido, emisor.mensajes_enviados), (3, 6, 1));
+        assert_eq!(emisor.esperas_mensaje + emisor.esperas_esquina, 0);
+
+        let reporte = Reporte::new(&simulacion, &resultado);
+        assert_eq!(reporte.estado, Some(EstadoSimulacion::Terminada));
+        let json = reporte.a_json();
+        assert!(json.contains("\"estado\": \"Terminada\",\n  \"error\": null"), "{}", json);
+        assert!(json.contains("{\"nombre\": \"r2\", \"tipo\": \"emisor\", \"av\": 5, \"ca\": 5, \"direccion\": \"Norte\", \"flores\": 0, \"papeles\": 0"), "{}", json);
+        assert!(json.contains("\"recorrido\": 6"), "{}", json);
+        assert!(json.contains("\"esperas_mensaje\": 0, \"informes\": []}"), "{}", json);
+        assert!(reporte.a_html().contains("<td>r1</td><td>receptor</td><td>(1, 1)</td>"));
+        assert!(json.ends_with("\"contenciones\": []\n}\n"), "{}", json);
+    }
+
+    // Dos juntadores que van a la misma esquina, con o sin bloquearla
+    fn juntadores(bloquear: bool) -> Program {
+        let (bloqueo, liberacion) = if bloquear {
+            ("BloquearEsquina(5,5)", "LiberarEsquina(5,5)")
+        } else {
+            ("Informar(0)", "Informar(1)")
+        };
+        parsear(&format!(
+"programa contencion
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    comenzar
+        {}
+        Pos(5,5)
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+        Pos(PosAv, 1)
+        {}
+    fin
+variables
+    r1: juntador
+    r2: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 1)
+fin
+", bloqueo, liberacion))
+    }
+
+    #[test]
+    fn test_report_lists_unprotected_corner_contention() {
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(5, 5, 1);
+        let mut simulacion = Simulation::con_ciudad(&juntadores(false), ciudad.clone()).unwrap();
+        let resultado = simulacion.ejecutar(1_000);
+        assert!(resultado.is_err());
+
+        let contenciones = simulacion.contenciones();
+        assert!(!contenciones.is_empty());
+        assert!(contenciones.iter().all(|contencion| (contencion.av, contencion.ca) == (5, 5)));
+        assert!(contenciones.iter().all(|contencion| contencion.robots[0] != contencion.robots[1]));
+        assert!(contenciones.iter().all(|contencion| contencion.pasos[0] <= contencion.pasos[1]));
+
+        let reporte = Reporte::new(&simulacion, &resultado);
+        let primera = &reporte.contenciones[0];
+        let fila = format!("{{\"av\": 5, \"ca\": 5, \"robots\": [\"{}\", \"{}\"], \"pasos\": [{}, {}]}}",
+            primera.robots[0], primera.robots[1], primera.pasos[0], primera.pasos[1]);
+        assert!(reporte.a_json().contains(&fila), "{}", reporte.a_json());
+        assert!(reporte.a_html().contains("<tr><td>(5, 5)</td>"), "{}", reporte.a_html());
+        // Los dos informaron antes de chocar
+        assert!(reporte.robots.iter().all(|robot| robot.informes.first().map(String::as_str) == Some("0")));
+        assert!(reporte.a_json().contains("\"informes\": [\"0\""), "{}", reporte.a_json());
+
+        // Con la esquina bloqueada el otro robot espera y no hay contención
+        let mut simulacion = Simulation::con_ciudad(&juntadores(true), ciudad).unwrap();
+        assert_eq!(simulacion.ejecutar(1_000), Ok(EstadoSimulacion::Terminada));
+        assert_eq!(simulacion.contenciones(), &[]);
+        let flores: u32 = simulacion.mundo().robots.iter().map(|robot| robot.flores).sum();
+        assert_eq!(flores, 1);
+    }
+}
@@ -0,0 +1 @@
+pub mod interpreterTest;
\ No newline at end of file
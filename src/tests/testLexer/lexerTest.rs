@@ -1,78 +1,278 @@
-use crate::Lexer;
-use crate::lib::lexer::token::TokenType;
-use std::fs;
+use rinfo::driver::compilar;
+use rinfo::lexer::scanner::{Lexer, LexerConfig};
+use rinfo::lexer::token::{Alias, Keywords, Token, TokenType};
 
 #[cfg(test)]
 mod testing_lexer{
-    use crate::lib::lexer;
-
     use super::*;
 
+    const PROGRAMA: &str = "programa ejemplo
+procesos
+    proceso juntar
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+    fin
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    comenzar
+        repetir 3
+            si PosAv < 5
+                juntar
+            sino
+                mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    fn valores(tipo: TokenType) -> Vec<&'static str> {
+        let tokens = Lexer::new(PROGRAMA).tokenize().expect("Failed to tokenize source code");
+        tokens.iter().filter(|token| token.token_type == tipo).map(|token| token.value).collect()
+    }
+
     #[test]
     fn test_basic_keywords() {
-        let source = fs::read_to_string("./src./tests/codigo.txt");
-        match source {
-            Ok(content) => {
-                let mut lexer = Lexer::new(&content);
-                
-                let keywords = [
-                    "proceso", "robot", "variables", "comenzar", 
-                    "fin", "programa", "procesos", "areas", "robots"
-                ];
-
-                if let Ok(tokens) = lexer.tokenize(){
-                    let keyword_tokens: Vec<_> = tokens
-                    .iter()
-                    .filter(|token| token.token_type == TokenType::Keyword)
-                    .collect();
-
-                    for token in keyword_tokens.iter(){
-                        
-                        assert!(keywords.contains(&token.value.as_str()), "Unexpected keyword: {}", token.value);
-                    }    
-                }else{
-                    panic!("Failed to tokenize source code");
-                }
-                
-            }
-            Err(e) => {
-                panic!("Failed to read source file: {}", e);
-            }
-        }
-        
+        assert_eq!(valores(TokenType::Keyword), [
+            "programa", "procesos", "proceso", "comenzar", "fin", "areas", "robots",
+            "robot", "comenzar", "fin", "variables", "comenzar", "fin",
+        ]);
     }
 
     #[test]
     fn test_control_sentences() {
-        let source = fs::read_to_string("./src./tests/codigo.txt");
-        match source {
-            Ok(content) => {
-                let mut lexer = Lexer::new(&content);
-                
-                let control_sentences = [
-                    "si", "sino", "mientras", "repetir"
-                ];
-
-                if let Ok(tokens) = lexer.tokenize(){
-                    let control_sentence_tokens: Vec<_> = tokens
-                    .iter()
-                    .filter(|token| token.token_type == TokenType::ControlSentence)
-                    .collect();
-
-                    for token in control_sentence_tokens.iter(){
-                        
-                        assert!(control_sentences.contains(&token.value.as_str()), "Unexpected control sentence: {}", token.value);
-                    }    
-                }else{
-                    panic!("Failed to tokenize source code");
-                }
-                
-            }
-            Err(e) => {
-                panic!("Failed to read source file: {}", e);
-            }
+        assert_eq!(valores(TokenType::ControlSentence), ["mientras", "repetir", "si", "sino"]);
+    }
+
+    #[test]
+    fn test_tokens_borrow_from_source() {
+        let content = "programa ejemplo\ncomenzar\n  Informar('hola')\nfin";
+        let tokens = Lexer::new(content).tokenize().expect("Failed to tokenize source code");
+
+        let rango = content.as_bytes().as_ptr_range();
+        for token in tokens.iter().filter(|token| !token.value.is_empty()) {
+            assert!(rango.contains(&token.value.as_ptr()), "Token not borrowed from source: {:?}", token);
         }
-        
+
+        let nombre = tokens.iter().find(|token| token.token_type == TokenType::Identifier).unwrap();
+        assert_eq!(nombre.value, "ejemplo");
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let content = "programa ejemplo\ncomenzar\n  Informar('a\\'b\\nc')\nfin";
+        let tokens = Lexer::new(content).tokenize().expect("Failed to tokenize source code");
+
+        let cadena = tokens.iter().find(|token| token.token_type == TokenType::Str).unwrap();
+        assert_eq!(cadena.value, "a\\'b\\nc");
+        assert_eq!(cadena.valor_cadena(), "a'b\nc");
+
+        assert!(Lexer::new("programa p\ncomenzar\n  Informar('\\q')\nfin").tokenize().is_err());
+    }
+
+    // Los literales booleanos son `V` y `F`; en minúscula son nombres
+    #[test]
+    fn test_boolean_literals() {
+        let content = "x := V & F | v | f | verdadero";
+        let tokens = Lexer::new(content).tokenize().expect("Failed to tokenize source code");
+
+        let clasificados: Vec<(TokenType, &str, Option<bool>)> = tokens.iter()
+            .filter(|token| matches!(token.token_type, TokenType::BoolValue | TokenType::Identifier))
+            .map(|token| (token.token_type, token.value, token.valor_booleano()))
+            .collect();
+        assert_eq!(clasificados, vec![
+            (TokenType::Identifier, "x", None),
+            (TokenType::BoolValue, "V", Some(true)),
+            (TokenType::BoolValue, "F", Some(false)),
+            (TokenType::Identifier, "v", None),
+            (TokenType::Identifier, "f", None),
+            (TokenType::Identifier, "verdadero", None),
+        ]);
+    }
+
+    #[test]
+    fn test_lexer_reports_every_error() {
+        let content = "programa p\ncomenzar\n  mover ?\n  Informar('\\q') $\n  derecha\nfin";
+        let (tokens, errores) = Lexer::new(content).tokenize_all();
+
+        let lineas: Vec<usize> = errores.iter().map(|error| error.line).collect();
+        assert_eq!(lineas, vec![3, 4, 4]);
+
+        // Después de cada error el lexer sigue con el resto del código
+        assert!(tokens.iter().any(|token| token.value == "derecha"));
+        assert_eq!(tokens.last().map(|token| token.token_type), Some(TokenType::EndFile));
+
+        // Como iterador entrega los errores en orden junto con los tokens
+        let primero = Lexer::new(content).find_map(Result::err).unwrap();
+        assert_eq!(primero.message, errores[0].message);
+    }
+
+    // Mezclar tabuladores y espacios, o volver a un nivel que no abrió ningún
+    // bloque, es una advertencia salvo que la configuración lo prohíba
+    #[test]
+    fn test_indentation_policy() {
+        let mezclado = "robot r\ncomenzar\n    mover\n\tderecha\nfin";
+        let mut lexer = Lexer::new(mezclado);
+        let (_, errores) = lexer.tokenize_all();
+        assert!(errores.is_empty());
+        assert_eq!(lexer.advertencias().len(), 1);
+        assert!(lexer.advertencias()[0].message.contains("tabuladores y espacios mezclados"));
+        assert_eq!(lexer.advertencias()[0].line, 4);
+
+        let estricta = LexerConfig { require_consistent: true, ..LexerConfig::default() };
+        let (_, errores) = Lexer::new(mezclado).con_config(estricta).tokenize_all();
+        assert_eq!(errores.len(), 1);
+        assert!(errores[0].message.contains("tabuladores y espacios mezclados"));
+
+        let sin_tabs = LexerConfig { allow_tabs: false, ..LexerConfig::default() };
+        let (_, errores) = Lexer::new("comenzar\n\tmover\nfin").con_config(sin_tabs).tokenize_all();
+        assert!(errores[0].message.starts_with("Tabulador en la indentación"));
+
+        // Con tabuladores de 2 columnas, un tabulador y dos espacios son el mismo nivel
+        let angosta = LexerConfig { tab_width: 2, ..LexerConfig::default() };
+        let mut lexer = Lexer::new("si V\n\t\tmover\n    derecha\nfin").con_config(angosta);
+        let (tokens, _) = lexer.tokenize_all();
+        let indents = tokens.iter().filter(|token| token.token_type == TokenType::Indent).count();
+        assert_eq!(indents, 1);
+
+        let irregular = "si V\n        mover\n    derecha\nfin";
+        let mut lexer = Lexer::new(irregular);
+        let (tokens, errores) = lexer.tokenize_all();
+        assert!(errores.is_empty());
+        assert!(lexer.advertencias()[0].message.starts_with("Indentación inconsistente: "));
+        assert!(tokens.iter().any(|token| token.value == "derecha"));
+        let estricta = LexerConfig { require_consistent: true, ..LexerConfig::default() };
+        let (_, errores) = Lexer::new(irregular).con_config(estricta).tokenize_all();
+        assert_eq!(errores[0].message, "Indentación inconsistente");
+    }
+
+    #[test]
+    fn test_windows_line_endings_and_unicode_spaces() {
+        let unix = "\u{FEFF}robot r\ncomenzar\n    mover\n    \n    Informar(\"año\", 1)\nfin";
+        let windows = unix.replace('\n', "\r\n");
+        let (esperados, _) = Lexer::new(unix).tokenize_all();
+        let mut lexer = Lexer::new(&windows);
+        let (tokens, errores) = lexer.tokenize_all();
+        assert!(errores.is_empty());
+        assert!(lexer.advertencias().is_empty());
+        let posiciones = |tokens: &[Token]| tokens.iter()
+            .map(|token| (token.token_type, token.line, token.column))
+            .collect::<Vec<_>>();
+        assert_eq!(posiciones(&tokens), posiciones(&esperados));
+
+        // Las columnas cuentan caracteres, no bytes
+        let uno = tokens.iter().find(|token| token.value == "1").unwrap();
+        assert_eq!((uno.line, uno.column), (5, 21));
+
+        // Un espacio duro se toma como un espacio común, con una advertencia
+        let mut lexer = Lexer::new("robot r\ncomenzar\n\u{A0}\u{A0}\u{A0}\u{A0}mover\u{A0}derecha\nfin");
+        let (tokens, errores) = lexer.tokenize_all();
+        assert!(errores.is_empty());
+        assert!(tokens.iter().any(|token| token.token_type == TokenType::Indent));
+        assert_eq!(lexer.advertencias().len(), 5);
+        assert_eq!(lexer.advertencias()[4].message, "Espacio no estándar (U+00A0) tomado como un espacio común");
+        assert_eq!((lexer.advertencias()[4].line, lexer.advertencias()[4].column), (3, 10));
+    }
+
+    #[test]
+    fn test_line_and_nested_comments() {
+        let codigo = "robot r\ncomenzar\n    { externo { interno } sigue }\n        // sólo un comentario\n    mover // avanzar\nfin";
+        let mut lexer = Lexer::new(codigo);
+        let (tokens, errores) = lexer.tokenize_all();
+        assert!(errores.is_empty());
+        let valores: Vec<_> = tokens.iter().map(|token| token.value).filter(|valor| !valor.is_empty()).collect();
+        assert_eq!(valores, ["robot", "r", "comenzar", "mover", "fin"]);
+        let indents = tokens.iter().filter(|token| token.token_type == TokenType::Indent).count();
+        assert_eq!(indents, 1);
+
+        // Sin anidar, el comentario termina en la primera '}' y el resto es código
+        let plana = LexerConfig { nested_comments: false, line_comments: false, ..LexerConfig::default() };
+        let mut lexer = Lexer::new("{ a { b } c }\nx // y").con_config(plana);
+        let (tokens, errores) = lexer.tokenize_all();
+        assert!(!errores.is_empty());
+        assert_eq!(lexer.advertencias()[0].message, "Comentario anidado: el comentario termina en la primera '}'");
+        assert_eq!(tokens.iter().filter(|token| token.token_type == TokenType::Divide).count(), 2);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_instruction_aliases() {
+        let keywords = Keywords::new()
+            .con_alias("girarDerecha", Alias::new("derecha", false).unwrap())
+            .con_alias("avanzar", Alias::new("mover", true).unwrap())
+            // No tapa una palabra reservada ni una extensión sin `--extensions`
+            .con_alias("si", Alias::new("derecha", false).unwrap())
+            .con_alias("recibio", Alias::new("RecibioMensaje", false).unwrap());
+        let mut lexer = Lexer::with_keywords("girarDerecha\navanzar\nsi recibio", keywords);
+        let (tokens, errores) = lexer.tokenize_all();
+        assert!(errores.is_empty());
+        let leidos: Vec<_> = tokens.iter().map(|token| (token.token_type, token.value)).take(4).collect();
+        assert_eq!(leidos, [
+            (TokenType::ElementalInstruction, "derecha"),
+            (TokenType::ElementalInstruction, "mover"),
+            (TokenType::ControlSentence, "si"),
+            (TokenType::Identifier, "recibio"),
+        ]);
+        // El token abarca el texto escrito, no el nombre de la instrucción
+        assert_eq!(tokens[0].span().fin_columna, 13);
+
+        assert_eq!(lexer.advertencias().len(), 1);
+        assert_eq!(lexer.advertencias()[0].message, "'avanzar' es un nombre en desuso: se prefiere 'mover'");
+        assert_eq!(lexer.advertencias()[0].line, 2);
+        assert!(Alias::new("girar", false).is_none());
+    }
+
+    #[test]
+    fn test_token_classification() {
+        let tokens = Lexer::new("procesos\nvariables\nx: numero\nrobot\nx := ~(3 + 4 * 2 <= 11) | V & \"hola\"")
+            .tokenize()
+            .unwrap();
+        let clases = |predicado: fn(&Token) -> bool| -> Vec<&str> {
+            tokens.iter().filter(|token| predicado(token)).map(|token| token.value).collect()
+        };
+        assert_eq!(clases(|token| token.is_section_keyword()), ["procesos", "variables"]);
+        assert_eq!(clases(|token| token.is_literal()), ["3", "4", "2", "11", "V", "hola"]);
+        assert_eq!(clases(|token| token.token_type.is_operator()), ["~", "+", "*", "<=", "|", "&"]);
+        // La asignación no es un operador de expresión
+        assert!(!TokenType::Assign.is_operator());
+
+        let potencias: Vec<_> = [TokenType::Multiply, TokenType::Plus, TokenType::LessEqual, TokenType::And, TokenType::Or, TokenType::Not]
+            .iter()
+            .map(TokenType::binding_power)
+            .collect();
+        assert_eq!(potencias, [Some(5), Some(4), Some(3), Some(2), Some(1), None]);
+        assert_eq!(TokenType::Equals.operator_symbol(), Some("=="));
+    }
+
+    // Dentro de los paréntesis cada argumento es un token con su propia
+    // posición, así que un diagnóstico sobre un argumento lo señala a él y
+    // no a toda la llamada
+    #[test]
+    fn test_arguments_keep_their_own_columns() {
+        let tokens = Lexer::new("Iniciar(r1, 12, 345)").tokenize().unwrap();
+        let argumentos: Vec<_> = tokens.iter()
+            .map(|token| (token.token_type, token.value, token.span().columna, token.span().fin_columna))
+            .take_while(|(tipo, ..)| *tipo != TokenType::EndFile)
+            .collect();
+        assert_eq!(argumentos, [
+            (TokenType::ElementalInstruction, "Iniciar", 1, 8),
+            (TokenType::OpenedParenthesis, "(", 8, 9),
+            (TokenType::Identifier, "r1", 9, 11),
+            (TokenType::Comma, ",", 11, 12),
+            (TokenType::Num, "12", 13, 15),
+            (TokenType::Comma, ",", 15, 16),
+            (TokenType::Num, "345", 17, 20),
+            (TokenType::ClosedParenthesis, ")", 20, 21),
+        ]);
+
+        let source = "programa p\nrobots\n  robot r\n  comenzar\n    Pos(100, V)\n  fin\nvariables\n  r1: r\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n";
+        let compilacion = compilar(source);
+        let span = compilacion.errores[0].span();
+        assert_eq!((span.linea, span.columna, span.fin_columna), (5, 14, 15));
+    }
+}
@@ -1 +1,2 @@
-pub mod lexerTest;
\ No newline at end of file
+pub mod lexerTest;
+pub mod propiedadesTest;
\ No newline at end of file
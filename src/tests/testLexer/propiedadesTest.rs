@@ -0,0 +1,245 @@
+use rinfo::azar::Azar;
+use rinfo::lexer::scanner::Lexer;
+use rinfo::lexer::token::{Token, TokenType};
+use std::panic;
+
+// Propiedades del lexer sobre entradas generadas al azar. Cada caso sale de
+// una semilla, así que un fallo se reproduce con la semilla que informa.
+#[cfg(test)]
+mod testing_propiedades_lexer {
+    use super::*;
+
+    const CASOS: u64 = 500;
+
+    // Elecciones sobre el generador de la biblioteca
+    trait Eleccion {
+        fn probabilidad(&mut self, de_cada: usize) -> bool;
+        fn elegir<'a, T>(&mut self, opciones: &'a [T]) -> &'a T;
+    }
+
+    impl Eleccion for Azar {
+        fn probabilidad(&mut self, de_cada: usize) -> bool {
+            self.hasta(de_cada) == 0
+        }
+
+        fn elegir<'a, T>(&mut self, opciones: &'a [T]) -> &'a T {
+            &opciones[self.hasta(opciones.len())]
+        }
+    }
+
+    // Texto de cada token que puede aparecer en una línea, con el tipo que
+    // le corresponde
+    const VOCABULARIO: &[(&str, TokenType)] = &[
+        ("comenzar", TokenType::Keyword),
+        ("fin", TokenType::Keyword),
+        ("variables", TokenType::Keyword),
+        ("si", TokenType::ControlSentence),
+        ("sino", TokenType::ControlSentence),
+        ("mientras", TokenType::ControlSentence),
+        ("repetir", TokenType::ControlSentence),
+        ("mover", TokenType::ElementalInstruction),
+        ("derecha", TokenType::ElementalInstruction),
+        ("PosAv", TokenType::ElementalInstruction),
+        ("numero", TokenType::Num),
+        ("booleano", TokenType::Bool),
+        ("V", TokenType::BoolValue),
+        ("F", TokenType::BoolValue),
+        ("ES", TokenType::ParameterType),
+        (":=", TokenType::Assign),
+        (":", TokenType::Declaration),
+        (",", TokenType::Comma),
+        ("+", TokenType::Plus),
+        ("-", TokenType::Minus),
+        ("*", TokenType::Multiply),
+        ("/", TokenType::Divide),
+        ("=", TokenType::Equals),
+        ("<>", TokenType::NotEquals),
+        ("<", TokenType::Less),
+        ("<=", TokenType::LessEqual),
+        (">", TokenType::Greater),
+        (">=", TokenType::GreaterEqual),
+        ("&", TokenType::And),
+        ("|", TokenType::Or),
+        ("~", TokenType::Not),
+    ];
+
+    const ESPACIOS: &[&str] = &[" ", "  ", "\t", "\u{00A0}"];
+    const SANGRIAS: &[&str] = &["  ", "    ", "\t"];
+    const SALTOS: &[&str] = &["\n", "\r\n"];
+
+    // Programa con su secuencia de tokens esperada, incluidos los INDENT y
+    // DEDENT que corresponden a la sangría de cada línea
+    struct Caso {
+        codigo: String,
+        esperados: Vec<(TokenType, String)>,
+    }
+
+    fn generar_programa(aleatorio: &mut Azar) -> Caso {
+        let sangria = *aleatorio.elegir(SANGRIAS);
+        let salto = *aleatorio.elegir(SALTOS);
+        let mut caso = Caso { codigo: String::new(), esperados: Vec::new() };
+        let mut nivel = 0;
+
+        for _ in 0..1 + aleatorio.hasta(20) {
+            // Líneas en blanco o con sólo comentarios, con cualquier sangría
+            if aleatorio.probabilidad(5) {
+                caso.codigo.push_str(&sangria.repeat(aleatorio.hasta(4)));
+                match aleatorio.hasta(3) {
+                    0 => caso.codigo.push_str("{ sin código }"),
+                    1 => caso.codigo.push_str("// sin código"),
+                    _ => {}
+                }
+                caso.codigo.push_str(salto);
+            }
+
+            // Cada línea puede abrir un solo bloque, pero cerrar varios
+            let nuevo = aleatorio.hasta(nivel + 2);
+            if nuevo > nivel {
+                caso.esperados.push((TokenType::Indent, String::new()));
+            }
+            for _ in nuevo..nivel {
+                caso.esperados.push((TokenType::Dedent, String::new()));
+            }
+            nivel = nuevo;
+
+            caso.codigo.push_str(&sangria.repeat(nivel));
+            for i in 0..1 + aleatorio.hasta(6) {
+                if i > 0 {
+                    separar(aleatorio, &mut caso.codigo);
+                }
+                generar_token(aleatorio, &mut caso, 0);
+            }
+            if aleatorio.probabilidad(4) {
+                caso.codigo.push_str(" // fin de línea");
+            }
+            caso.codigo.push_str(salto);
+        }
+
+        for _ in 0..nivel {
+            caso.esperados.push((TokenType::Dedent, String::new()));
+        }
+        caso.esperados.push((TokenType::EndFile, String::new()));
+        caso
+    }
+
+    fn separar(aleatorio: &mut Azar, codigo: &mut String) {
+        let espacio = *aleatorio.elegir(ESPACIOS);
+        codigo.push_str(espacio);
+        if aleatorio.probabilidad(8) {
+            codigo.push_str("{ comentario { anidado } } ");
+        }
+    }
+
+    fn generar_token(aleatorio: &mut Azar, caso: &mut Caso, profundidad: usize) {
+        let (texto, tipo) = match aleatorio.hasta(8) {
+            0 => (format!("x{}", aleatorio.hasta(1000)), TokenType::Identifier),
+            1 => (aleatorio.hasta(1000).to_string(), TokenType::Num),
+            2 => {
+                let valor = format!("texto {}", aleatorio.hasta(100));
+                caso.codigo.push_str(&format!("'{}'", valor));
+                caso.esperados.push((TokenType::Str, valor));
+                return;
+            }
+            3 if profundidad < 3 => {
+                caso.codigo.push('(');
+                caso.esperados.push((TokenType::OpenedParenthesis, "(".to_string()));
+                for i in 0..aleatorio.hasta(4) {
+                    if i > 0 {
+                        separar(aleatorio, &mut caso.codigo);
+                    }
+                    generar_token(aleatorio, caso, profundidad + 1);
+                }
+                caso.codigo.push(')');
+                caso.esperados.push((TokenType::ClosedParenthesis, ")".to_string()));
+                return;
+            }
+            _ => {
+                let (texto, tipo) = aleatorio.elegir(VOCABULARIO);
+                (texto.to_string(), *tipo)
+            }
+        };
+        caso.codigo.push_str(&texto);
+        caso.esperados.push((tipo, texto));
+    }
+
+    // Texto arbitrario armado con los caracteres que más le cuestan al lexer
+    fn generar_texto(aleatorio: &mut Azar) -> String {
+        const PIEZAS: &[&str] = &[
+            " ", "  ", "\t", "\n", "\r\n", "\r", "\u{00A0}", "\u{FEFF}", "{", "}", "//", "/",
+            "(", ")", "'", "\"", "\\", ":=", "<", ">", "=", "~", "-", "5", "42", "a", "x1",
+            "si", "fin", "comenzar", "ñandú", "€", "@",
+        ];
+        (0..aleatorio.hasta(60)).map(|_| *aleatorio.elegir(PIEZAS)).collect()
+    }
+
+    // Las posiciones nunca retroceden y los INDENT y DEDENT están
+    // balanceados: nunca se cierra un bloque que no se abrió, y al final del
+    // archivo no queda ninguno abierto
+    fn verificar_invariantes(tokens: &[Token], codigo: &str) {
+        let mut abiertos = 0usize;
+        for par in tokens.windows(2) {
+            assert!(
+                (par[0].line, par[0].column) <= (par[1].line, par[1].column),
+                "Posición que retrocede: {:?} y después {:?}\nen {:?}", par[0], par[1], codigo
+            );
+        }
+        for token in tokens {
+            assert!(
+                (token.line, token.column) <= (token.end_line, token.end_column),
+                "Token que termina antes de empezar: {:?}\nen {:?}", token, codigo
+            );
+            match token.token_type {
+                TokenType::Indent => abiertos += 1,
+                TokenType::Dedent => {
+                    abiertos = abiertos.checked_sub(1)
+                        .unwrap_or_else(|| panic!("DEDENT sin INDENT: {:?}\nen {:?}", token, codigo));
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(abiertos, 0, "Bloques sin cerrar al final de {:?}", codigo);
+        assert_eq!(tokens.last().map(|token| token.token_type), Some(TokenType::EndFile), "en {:?}", codigo);
+    }
+
+    #[test]
+    fn test_arbitrary_text_never_panics() {
+        for semilla in 0..CASOS {
+            let codigo = generar_texto(&mut Azar::new(semilla));
+            let resultado = panic::catch_unwind(|| {
+                let (tokens, _errores) = Lexer::new(&codigo).tokenize_all();
+                verificar_invariantes(&tokens, &codigo);
+            });
+            assert!(resultado.is_ok(), "Falló con la semilla {}: {:?}", semilla, codigo);
+        }
+    }
+
+    #[test]
+    fn test_generated_programs_lex_back_to_their_tokens() {
+        for semilla in 0..CASOS {
+            let caso = generar_programa(&mut Azar::new(semilla));
+            let (tokens, errores) = Lexer::new(&caso.codigo).tokenize_all();
+            assert!(errores.is_empty(), "Semilla {}: {:?}\nen {:?}", semilla, errores, caso.codigo);
+            verificar_invariantes(&tokens, &caso.codigo);
+
+            let obtenidos: Vec<(TokenType, String)> = tokens.iter()
+                .map(|token| (token.token_type, token.valor_cadena().into_owned()))
+                .collect();
+            assert_eq!(obtenidos, caso.esperados, "Semilla {}: {:?}", semilla, caso.codigo);
+        }
+    }
+
+    #[test]
+    fn test_token_positions_point_at_their_text() {
+        for semilla in 0..CASOS {
+            let caso = generar_programa(&mut Azar::new(semilla));
+            let lineas: Vec<&str> = caso.codigo.lines().collect();
+            let tokens = Lexer::new(&caso.codigo).tokenize().unwrap();
+
+            for token in tokens.iter().filter(|token| !token.value.is_empty() && token.token_type != TokenType::Str) {
+                let linea = lineas[token.line - 1];
+                let texto: String = linea.chars().skip(token.column - 1).take(token.end_column - token.column).collect();
+                assert_eq!(texto, token.value, "Semilla {}: {:?}", semilla, token);
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(feature = "node")]
+use napi::Task;
+#[cfg(feature = "node")]
+use rinfo::interpreter::simulation::EstadoSimulacion;
+#[cfg(feature = "node")]
+use rinfo::node::{compilar_con, resumir, CompileOptions, Ejecucion, RunOptions};
+
+// Las funciones exportadas convierten a valores de JavaScript con el `Env`
+// de Node; acá se prueba lo que calculan antes de convertirlo
+#[cfg(all(test, feature = "node"))]
+mod testing_node {
+    use super::*;
+
+    // Junta la flor de (1, 1) y la informa. Sin mundo la esquina está vacía.
+    const JUNTADOR: &str = "programa juntador
+areas
+    ciudad: AreaC(1, 1, 10, 10)
+robots
+    robot r
+    variables
+        flores: numero
+    comenzar
+        flores := 0
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+            flores := flores + 1
+        Informar(flores)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    fn flexibles() -> CompileOptions {
+        CompileOptions { flexible_sections: Some(true), ..CompileOptions::default() }
+    }
+
+    #[test]
+    fn test_compile_options_reach_the_compilation() {
+        let desordenado = JUNTADOR.replacen("areas\n    ciudad: AreaC(1, 1, 10, 10)\nrobots", "robots", 1)
+            .replacen("variables\n    r1: r", "areas\n    ciudad: AreaC(1, 1, 10, 10)\nvariables\n    r1: r", 1);
+        assert!(compilar_con(JUNTADOR, None).unwrap().exitosa());
+        assert!(!compilar_con(&desordenado, None).unwrap().exitosa());
+        assert!(compilar_con(&desordenado, Some(&flexibles())).unwrap().exitosa());
+
+        let opciones = CompileOptions { extensions: Some(true), lint_config: Some("numeros-magicos = warn\n".to_string()), ..flexibles() }
+            .opciones()
+            .unwrap();
+        assert!(opciones.secciones_flexibles && opciones.extensiones);
+        assert_eq!(opciones.lints.ajustes.len(), 1);
+
+        let invalidas = CompileOptions { lint_config: Some("numeros-magicos\n".to_string()), ..CompileOptions::default() };
+        assert!(compilar_con(JUNTADOR, Some(&invalidas)).is_err());
+    }
+
+    #[test]
+    fn test_analyze_summarizes_errors_and_metrics() {
+        let resumen = resumir(JUNTADOR, None).unwrap();
+        assert!(resumen.exitoso());
+        assert!(!resumen.metricas.secciones.is_empty());
+
+        let resumen = resumir(&JUNTADOR.replace("Informar(flores)", "Informar(flore)"), None).unwrap();
+        assert_eq!(resumen.errores.len(), 1);
+        assert!(resumen.errores[0].message.starts_with("Variable 'flore' no declarada"), "{}", resumen.errores[0].message);
+    }
+
+    #[test]
+    fn test_run_reports_the_simulation_in_the_given_world() {
+        let mut ejecucion = Ejecucion::new(JUNTADOR.to_string(), RunOptions::default()).unwrap();
+        let reporte = ejecucion.compute().unwrap();
+        assert_eq!(reporte.estado, Some(EstadoSimulacion::Terminada));
+        assert_eq!(reporte.robots[0].informes, ["0"]);
+
+        let options = RunOptions { world: Some("flores 1 1 3\n".to_string()), ..RunOptions::default() };
+        let reporte = Ejecucion::new(JUNTADOR.to_string(), options).unwrap().compute().unwrap();
+        assert_eq!(reporte.robots[0].informes, ["3"]);
+        assert_eq!(reporte.robots[0].flores, 3);
+
+        // Un mundo inválido o un programa que no compila rechazan la promesa;
+        // sin pasos suficientes el reporte queda a mitad de camino
+        let options = RunOptions { world: Some("flores 0 1 1\n".to_string()), ..RunOptions::default() };
+        let error = Ejecucion::new(JUNTADOR.to_string(), options).unwrap().compute().unwrap_err();
+        assert!(error.reason.starts_with("Mundo inválido"), "{}", error.reason);
+        assert!(Ejecucion::new("programa roto\n".to_string(), RunOptions::default()).unwrap().compute().is_err());
+
+        let options = RunOptions { world: Some("flores 1 1 3\n".to_string()), max_steps: Some(2), ..RunOptions::default() };
+        let reporte = Ejecucion::new(JUNTADOR.to_string(), options).unwrap().compute().unwrap();
+        assert_ne!(reporte.estado, Some(EstadoSimulacion::Terminada));
+
+        let invalidas = RunOptions { compile: Some(CompileOptions { lint_config: Some("numeros-magicos\n".to_string()), ..CompileOptions::default() }), ..RunOptions::default() };
+        assert!(Ejecucion::new(JUNTADOR.to_string(), invalidas).is_err());
+    }
+}
@@ -0,0 +1,1069 @@
+use rinfo::interner::Interner;
+use rinfo::lexer::scanner::Lexer;
+use rinfo::parser::arena::{Arena, ExprId};
+use rinfo::parser::ast::{bloques_iguales, expresiones_iguales, huella_bloque, huella_expresion, huella_programa, programas_iguales, recorrer_expresion, recorrer_instruccion, Visitor};
+use rinfo::lexer::token::{Keywords, TokenType};
+use rinfo::parser::cst::{ClaseNodo, Cst};
+use rinfo::parser::printer::a_codigo;
+use rinfo::fuzz;
+use rinfo::mensajes::{Codigo, Idioma};
+use rinfo::parser::processor::{parse_tokens_lossy, Expresion, Instruccion, Parser, Program, ANIDAMIENTO_MAXIMO};
+use rinfo::parser::stream::TokenStream;
+use rinfo::source::Span;
+use std::collections::HashSet;
+
+#[cfg(test)]
+mod testing_parser {
+    use super::*;
+
+    fn parsear(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("Failed to tokenize source code");
+        Parser::new(&tokens).parse().expect("Failed to parse source code")
+    }
+
+    // Registra el orden en que se visitan instrucciones e identificadores
+    struct Recolector<'a> {
+        arena: &'a Arena,
+        simbolos: &'a Interner,
+        lineas: Vec<usize>,
+        identificadores: Vec<&'a str>,
+    }
+
+    impl<'a> Visitor<'a> for Recolector<'a> {
+        fn arena(&self) -> &'a Arena {
+            self.arena
+        }
+
+        fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+            self.lineas.push(instruccion.linea());
+            recorrer_instruccion(self, instruccion);
+        }
+
+        fn visitar_expresion(&mut self, expresion: &'a Expresion) {
+            if let Expresion::Identificador(nombre) = expresion {
+                self.identificadores.push(self.simbolos.resolver(*nombre));
+            }
+            recorrer_expresion(self, expresion);
+        }
+    }
+
+    #[test]
+    fn test_visitor_walks_nested_blocks_and_expressions() {
+        let programa = parsear(
+"programa recorrido
+procesos
+    proceso contar(ES total: numero)
+    comenzar
+        repetir 2
+            total := total + 1
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot juntador
+    variables
+        cant : numero
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+        contar(cant)
+    fin
+variables
+    r1: juntador
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut recolector = Recolector {
+            arena: &programa.arena,
+            simbolos: &programa.simbolos,
+            lineas: Vec::new(),
+            identificadores: Vec::new(),
+        };
+        recolector.visitar_programa(&programa);
+
+        assert_eq!(recolector.lineas, vec![5, 6, 15, 16, 17]);
+        assert_eq!(recolector.identificadores, vec!["total", "cant", "r1", "ciudad", "r1"]);
+    }
+
+    #[test]
+    fn test_default_visitor_reaches_every_instruction() {
+        struct Contador<'a>(&'a Arena, usize);
+
+        impl<'a> Visitor<'a> for Contador<'a> {
+            fn arena(&self) -> &'a Arena {
+                self.0
+            }
+
+            fn visitar_instruccion(&mut self, instruccion: &'a Instruccion) {
+                self.1 += 1;
+                recorrer_instruccion(self, instruccion);
+            }
+        }
+
+        let programa = parsear(
+"programa conteo
+robots
+    robot r
+    comenzar
+        si HayFlorEnLaEsquina
+            tomarFlor
+        derecha
+    fin
+comenzar
+fin
+");
+        let mut contador = Contador(&programa.arena, 0);
+        contador.visitar_programa(&programa);
+
+        assert_eq!(contador.1, 3);
+    }
+
+    #[test]
+    fn test_token_stream_lookahead_and_rollback() {
+        let tokens = Lexer::new("x := 3\n").tokenize().expect("Failed to tokenize source code");
+        let mut stream = TokenStream::new(&tokens);
+
+        assert!(stream.check(TokenType::Identifier));
+        assert_eq!(stream.peek(1).map(|t| t.token_type), Some(TokenType::Assign));
+
+        let inicio = stream.checkpoint();
+        assert_eq!(stream.expect(TokenType::Identifier, "Esperado nombre").unwrap().value, "x");
+        stream.avanzar();
+        assert!(stream.check_valor(TokenType::Num, "3"));
+        assert!(stream.expect(TokenType::Assign, "Esperado ':='").is_err());
+
+        stream.rollback(inicio);
+        assert!(stream.check_valor(TokenType::Identifier, "x"));
+
+        while !stream.fin() {
+            stream.avanzar();
+        }
+        // Al final del archivo el error apunta al último token en lugar de fallar
+        let error = stream.expect(TokenType::ClosedParenthesis, "Esperado ')'").unwrap_err();
+        assert_eq!(error.line, tokens.last().unwrap().line);
+    }
+
+    #[test]
+    fn test_identifier_statement_uses_lookahead() {
+        let programa = parsear(
+"programa llamadas
+procesos
+    proceso girar
+    comenzar
+        derecha
+    fin
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        n := n + 1
+        girar
+        mover
+    fin
+comenzar
+fin
+");
+        let instrucciones = &programa.robots_definidos[0].instrucciones;
+
+        assert!(matches!(&programa.arena[instrucciones[0]], Instruccion::Asignacion { variable, .. } if programa.simbolos.resolver(*variable) == "n"));
+        assert!(matches!(&programa.arena[instrucciones[1]], Instruccion::LlamadaFuncion { nombre, argumentos, .. } if programa.simbolos.resolver(*nombre) == "girar" && argumentos.is_empty()));
+        assert_eq!(instrucciones.len(), 3);
+    }
+
+    #[test]
+    fn test_parser_reads_tokens_lazily_from_lexer() {
+        let source = "programa perezoso
+robots
+    robot r
+    comenzar
+        mover
+    fin
+comenzar
+fin
+";
+        let tokens = Lexer::new(source).tokenize().expect("Failed to tokenize source code");
+        let esperado = Parser::new(&tokens).parse().expect("Failed to parse source code");
+        let programa = Parser::from_lexer(Lexer::new(source)).parse().expect("Failed to parse source code");
+        assert_eq!(format!("{:?}", programa), format!("{:?}", esperado));
+
+        // Con un carácter inválido el parser informa el error léxico
+        let mut parser = Parser::from_lexer(Lexer::new("programa p\ncomenzar\n  mover ?\nfin\n"));
+        let error = parser.parse().unwrap_err();
+        assert!(error.message.contains("Carácter inesperado"));
+        assert_eq!(parser.errores_lexicos().len(), 1);
+    }
+
+    #[test]
+    fn test_nodes_live_in_the_program_arena() {
+        let programa = parsear(
+"programa arena
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        si n < 3
+            n := (n + 1) * 2
+    fin
+comenzar
+fin
+");
+        let arena = &programa.arena;
+        let Instruccion::Si { condicion, entonces, sino, .. } = &arena[programa.robots_definidos[0].instrucciones[0]] else {
+            panic!("Se esperaba un si");
+        };
+        assert!(matches!(&arena[*condicion], Expresion::Binaria { operador, .. } if operador == "<"));
+        assert!(sino.is_empty());
+
+        let Instruccion::Asignacion { valor, .. } = &arena[entonces[0]] else {
+            panic!("Se esperaba una asignación");
+        };
+        let Expresion::Binaria { izquierda, operador, derecha } = &arena[*valor] else {
+            panic!("Se esperaba una expresión binaria");
+        };
+        assert_eq!(operador, "*");
+        assert!(matches!(&arena[*izquierda], Expresion::Binaria { operador, .. } if operador == "+"));
+        assert_eq!(arena[*derecha], Expresion::Numero(2));
+
+        assert_eq!(arena.cantidad_instrucciones(), 2);
+        assert_eq!(arena.cantidad_expresiones(), 8);
+    }
+    #[test]
+    fn test_identifiers_share_one_symbol() {
+        let programa = parsear(
+"programa simbolos
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        n := n + n
+    fin
+comenzar
+fin
+");
+        let Instruccion::Asignacion { variable, valor, .. } = &programa.arena[programa.robots_definidos[0].instrucciones[0]] else {
+            panic!("Se esperaba una asignación");
+        };
+        let Expresion::Binaria { izquierda, derecha, .. } = &programa.arena[*valor] else {
+            panic!("Se esperaba una expresión binaria");
+        };
+
+        assert_eq!(programa.arena[*izquierda], Expresion::Identificador(*variable));
+        assert_eq!(programa.arena[*derecha], Expresion::Identificador(*variable));
+        assert_eq!(programa.simbolos.get("n"), Some(*variable));
+        assert_eq!(programa.simbolos.resolver(*variable), "n");
+        assert_eq!(programa.simbolos.get("m"), None);
+    }
+
+    #[test]
+    fn test_sensors_are_call_expressions() {
+        let programa = parsear(
+"programa sensores
+robots
+    robot r
+    variables
+        x : numero
+    comenzar
+        x := PosAv + 1
+        si HayPapelEnLaBolsa & (PosCa < 5)
+            depositarPapel
+    fin
+comenzar
+fin
+");
+        let arena = &programa.arena;
+        let sensor = |id| match &arena[id] {
+            Expresion::Sensor { nombre } => Some(programa.simbolos.resolver(*nombre)),
+            _ => None,
+        };
+        let instrucciones = &programa.robots_definidos[0].instrucciones;
+
+        let Instruccion::Asignacion { valor, .. } = &arena[instrucciones[0]] else { panic!("Esperada una asignación") };
+        let Expresion::Binaria { izquierda, .. } = &arena[*valor] else { panic!("Esperada una suma") };
+        assert_eq!(sensor(*izquierda), Some("PosAv"));
+
+        let Instruccion::Si { condicion, .. } = &arena[instrucciones[1]] else { panic!("Esperado un si") };
+        let Expresion::Binaria { izquierda, derecha, .. } = &arena[*condicion] else { panic!("Esperada una conjunción") };
+        assert_eq!(sensor(*izquierda), Some("HayPapelEnLaBolsa"));
+        assert!(matches!(&arena[*derecha], Expresion::Binaria { izquierda, .. } if sensor(*izquierda) == Some("PosCa")));
+    }
+
+    // Expresión con todos sus paréntesis, para ver cómo se agrupó
+    fn agrupada(programa: &Program, expresion: ExprId) -> String {
+        match &programa.arena[expresion] {
+            Expresion::Binaria { izquierda, operador, derecha } => format!(
+                "({} {} {})", agrupada(programa, *izquierda), operador, agrupada(programa, *derecha)
+            ),
+            Expresion::Negacion(operando) => format!("~{}", agrupada(programa, *operando)),
+            Expresion::Identificador(nombre) | Expresion::Sensor { nombre } => programa.simbolos.resolver(*nombre).to_string(),
+            Expresion::Numero(n) => n.to_string(),
+            Expresion::Booleano(b) => if *b { "V" } else { "F" }.to_string(),
+            Expresion::Cadena(texto) => format!("'{}'", texto),
+            Expresion::Todos => "*".to_string(),
+            Expresion::Llamada { nombre, argumentos } => format!(
+                "{}({})",
+                programa.simbolos.resolver(*nombre),
+                argumentos.iter().map(|argumento| agrupada(programa, *argumento)).collect::<Vec<_>>().join(", ")
+            ),
+            Expresion::Indice { arreglo, indice } => format!(
+                "{}[{}]", programa.simbolos.resolver(*arreglo), agrupada(programa, *indice)
+            ),
+        }
+    }
+
+    // `* /` agrupan antes que `+ -`, éstos antes que las comparaciones, y
+    // las comparaciones antes que `&` y `|`. `~` agrupa antes que todos.
+    #[test]
+    fn test_binary_operators_follow_precedence() {
+        let programa = parsear(
+"programa precedencia
+robots
+    robot r
+    variables
+        x : numero
+        b : booleano
+    comenzar
+        b := 3 < x + 1 * 2
+        b := x - 1 - 1 == 0 | b & x > 2
+        x := (x + 1) * 2
+        b := ~b & ~(x > 2)
+        b := (x > 1) & b
+    fin
+comenzar
+fin
+");
+        let agrupadas: Vec<String> = programa.robots_definidos[0].instrucciones.iter()
+            .map(|id| match &programa.arena[*id] {
+                Instruccion::Asignacion { valor, .. } => agrupada(&programa, *valor),
+                _ => panic!("Esperada una asignación"),
+            })
+            .collect();
+
+        assert_eq!(agrupadas, vec![
+            "(3 < (x + (1 * 2)))",
+            "((((x - 1) - 1) == 0) | (b & (x > 2)))",
+            "((x + 1) * 2)",
+            "(~b & ~(x > 2))",
+            "((x > 1) & b)",
+        ]);
+    }
+
+    // `nombre(args)` dentro de una expresión es una llamada a un proceso que
+    // devuelve un valor, declarado con `: tipo` después de los parámetros
+    #[test]
+    fn test_calls_inside_expressions_and_return_types() {
+        let source = "programa funciones
+procesos
+    proceso doble(E n: numero): numero
+    comenzar
+        doble := n * 2
+    fin
+    proceso listo: booleano
+    comenzar
+        listo := V
+    fin
+robots
+    robot r
+    variables
+        x : numero
+    comenzar
+        x := doble(x + 1, doble(2)) * 3
+        Informar(doble(x))
+    fin
+comenzar
+fin
+";
+        let programa = parsear(source);
+        let retornos: Vec<_> = programa.procesos.iter().map(|proceso| proceso.retorno.as_deref()).collect();
+        assert_eq!(retornos, [Some("numero"), Some("booleano")]);
+
+        let instrucciones = &programa.robots_definidos[0].instrucciones;
+        let Instruccion::Asignacion { valor, .. } = &programa.arena[instrucciones[0]] else { panic!("Esperada una asignación") };
+        assert_eq!(agrupada(&programa, *valor), "(doble((x + 1), doble(2)) * 3)");
+        let Instruccion::LlamadaFuncion { argumentos, .. } = &programa.arena[instrucciones[1]] else { panic!("Esperado Informar") };
+        assert!(matches!(&programa.arena[argumentos[0]], Expresion::Llamada { argumentos, .. } if argumentos.len() == 1));
+
+        let codigo = a_codigo(&programa);
+        assert!(codigo.contains("    proceso doble(E n: numero): numero\n"));
+        assert!(codigo.contains("    proceso listo: booleano\n"));
+        assert!(codigo.contains("        x := doble(x + 1, doble(2)) * 3\n"));
+    }
+
+    // Con `--extensions`, `arreglo[N] de tipo` declara un arreglo cuya
+    // longitud se calcula al compilar; sus elementos se leen y se asignan
+    // con un índice entre corchetes
+    #[test]
+    fn test_array_declarations_and_indexes() {
+        let source = "programa arreglos
+constantes
+    LARGO = 4
+robots
+    robot r
+    variables
+        flores : arreglo[LARGO * 2] de numero
+        x : numero
+    comenzar
+        flores[x + 1] := flores[flores[1]] * 2
+    fin
+comenzar
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let variables = &programa.robots_definidos[0].variables;
+        assert_eq!((variables[0].tipo_dato.as_str(), variables[0].longitud), ("numero", Some(8)));
+        assert_eq!(variables[1].longitud, None);
+
+        let instruccion = &programa.arena[programa.robots_definidos[0].instrucciones[0]];
+        let Instruccion::Asignacion { indice: Some(indice), valor, .. } = instruccion else { panic!("Esperada una asignación a un elemento") };
+        assert_eq!(agrupada(&programa, *indice), "(x + 1)");
+        assert_eq!(agrupada(&programa, *valor), "(flores[flores[1]] * 2)");
+
+        let codigo = a_codigo(&programa);
+        assert!(codigo.contains("        flores : arreglo[8] de numero\n"));
+        assert!(codigo.contains("        flores[x + 1] := flores[flores[1]] * 2\n"));
+
+        // La longitud tiene que ser positiva
+        let source = source.replace("LARGO * 2", "LARGO - 4");
+        let tokens = Lexer::with_keywords(&source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        assert_eq!(error.message, "La longitud del arreglo 'flores' debe ser un número mayor que cero que pueda calcularse al compilar");
+    }
+
+    // Cada bloque termina en su propio DEDENT: lo que sigue a un bloque
+    // anidado pertenece al bloque de afuera, y `sino` al `si` que lo precede
+    #[test]
+    fn test_nested_blocks_close_at_their_own_dedent() {
+        let programa = parsear(
+"programa bloques
+robots
+    robot r
+    comenzar
+        mientras HayFlorEnLaEsquina
+            si PosAv > 2
+                tomarFlor
+            sino
+                derecha
+            mover
+        depositarFlor
+    fin
+comenzar
+fin
+");
+        let arena = &programa.arena;
+        let instrucciones = &programa.robots_definidos[0].instrucciones;
+        assert_eq!(instrucciones.len(), 2);
+
+        let Instruccion::Mientras { cuerpo, .. } = &arena[instrucciones[0]] else { panic!("Esperado un mientras") };
+        assert_eq!(cuerpo.len(), 2);
+        let Instruccion::Si { entonces, sino, .. } = &arena[cuerpo[0]] else { panic!("Esperado un si") };
+        assert_eq!((entonces.len(), sino.len()), (1, 1));
+        assert!(matches!(&arena[cuerpo[1]], Instruccion::LlamadaFuncion { nombre, .. }
+                         if programa.simbolos.resolver(*nombre) == "mover"));
+    }
+
+    // Los `sino si` son ramas del mismo `si`, no un `si` dentro de cada `sino`;
+    // un `sino` con el `si` en la línea siguiente sigue siendo un `sino`
+    #[test]
+    fn test_else_if_chains_stay_flat() {
+        let programa = parsear(
+"programa cadena
+robots
+    robot r
+    comenzar
+        si PosAv == 1
+            mover
+        sino si PosAv == 2
+            derecha
+        sino si PosAv == 3
+            tomarFlor
+            mover
+        sino
+            si PosCa == 1
+                depositarFlor
+    fin
+comenzar
+fin
+");
+        let arena = &programa.arena;
+        let instrucciones = &programa.robots_definidos[0].instrucciones;
+        assert_eq!(instrucciones.len(), 1);
+
+        let Instruccion::Si { entonces, alternativas, sino, .. } = &arena[instrucciones[0]] else { panic!("Esperado un si") };
+        assert_eq!(entonces.len(), 1);
+        let ramas: Vec<_> = alternativas.iter().map(|alternativa| (alternativa.linea, alternativa.cuerpo.len())).collect();
+        assert_eq!(ramas, [(7, 1), (9, 2)]);
+        assert_eq!(sino.len(), 1);
+        let Instruccion::Si { alternativas, sino, .. } = &arena[sino[0]] else { panic!("Esperado un si") };
+        assert!(alternativas.is_empty() && sino.is_empty());
+
+        assert!(a_codigo(&programa).contains(
+"        si PosAv == 1
+            mover
+        sino si PosAv == 2
+            derecha
+        sino si PosAv == 3
+            tomarFlor
+            mover
+        sino
+            si PosCa == 1
+"));
+    }
+
+    // `segun` sólo es una sentencia con las extensiones; cada caso guarda su
+    // valor y la comparación con que se ejecuta, y se imprime como se escribió
+    #[test]
+    fn test_switch_is_parsed_only_with_extensions() {
+        let source = "programa elegir
+robots
+    robot r
+    comenzar
+        segun PosAv
+            caso 1:
+                mover
+            caso 2 + 3:
+                derecha
+                mover
+            sino
+                tomarFlor
+    fin
+comenzar
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let arena = &programa.arena;
+        let Instruccion::Segun { casos, sino, .. } = &arena[programa.robots_definidos[0].instrucciones[0]] else {
+            panic!("Esperado un segun")
+        };
+        let ramas: Vec<_> = casos.iter().map(|caso| (caso.linea, caso.cuerpo.len())).collect();
+        assert_eq!(ramas, [(6, 1), (8, 2)]);
+        assert_eq!(sino.len(), 1);
+        assert!(matches!(&arena[casos[1].comparacion], Expresion::Binaria { operador, derecha, .. }
+                         if operador == "==" && *derecha == casos[1].valor));
+        assert!(a_codigo(&programa).contains(
+"        segun PosAv
+            caso 1:
+                mover
+            caso 2 + 3:
+                derecha
+                mover
+            sino
+                tomarFlor
+"));
+
+        // Sin extensiones `segun` y `caso` son nombres comunes
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let segun = tokens.iter().find(|token| token.value == "segun").unwrap();
+        assert_eq!(segun.token_type, TokenType::Identifier);
+    }
+
+    // `para` guarda la variable, los límites y el cuerpo; la cantidad de
+    // vueltas es una expresión que arma el parser
+    #[test]
+    fn test_for_loop_keeps_its_variable_and_bounds() {
+        let source = "programa contar
+robots
+    robot r
+    comenzar
+        para i := 1 hasta PosAv + 2
+            mover
+            Informar(i)
+    fin
+comenzar
+fin
+";
+        let tokens = Lexer::with_keywords(source, Keywords::new().con_extensiones()).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let arena = &programa.arena;
+        let Instruccion::Para { variable, desde, cantidad, cuerpo, .. } = &arena[programa.robots_definidos[0].instrucciones[0]] else {
+            panic!("Esperado un para")
+        };
+        assert_eq!(programa.simbolos.resolver(*variable), "i");
+        assert_eq!(arena[*desde], Expresion::Numero(1));
+        assert_eq!(cuerpo.len(), 2);
+        assert!(matches!(&arena[*cantidad], Expresion::Binaria { operador, .. } if operador == "+"));
+        assert!(a_codigo(&programa).contains(
+"        para i := 1 hasta PosAv + 2
+            mover
+            Informar(i)
+"));
+    }
+
+    // `nombre (args)` y `nombre` solo son llamadas a proceso, con o sin espacio
+    // antes de los paréntesis
+    #[test]
+    fn test_process_calls_with_and_without_arguments() {
+        let programa = parsear(
+"programa llamadas
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        avanzar (n, 2)
+        avanzar(n + 1, 3)
+        girar
+    fin
+comenzar
+fin
+");
+        let llamadas: Vec<(&str, usize)> = programa.robots_definidos[0].instrucciones.iter()
+            .map(|id| match &programa.arena[*id] {
+                Instruccion::LlamadaFuncion { nombre, argumentos, .. } => (programa.simbolos.resolver(*nombre), argumentos.len()),
+                otra => panic!("Esperada una llamada, encontrado {:?}", otra),
+            })
+            .collect();
+
+        assert_eq!(llamadas, vec![("avanzar", 2), ("avanzar", 2), ("girar", 0)]);
+    }
+
+    // Cada nodo guarda el código que ocupa: una instrucción va de su primer
+    // token al último (incluido su bloque) y una expresión binaria abarca sus
+    // dos operandos
+    #[test]
+    fn test_nodes_record_their_source_span() {
+        let programa = parsear(
+"programa tramos
+robots
+    robot r
+    variables
+        b : booleano
+    comenzar
+        b := 3 < PosAv + 1
+        si b
+            Informar(\"listo\")
+    fin
+comenzar
+fin
+");
+        let instrucciones = &programa.robots_definidos[0].instrucciones;
+        assert_eq!(programa.arena.span_instruccion(instrucciones[0]), Span::new(7, 9, 7, 27));
+        assert_eq!(programa.arena.span_instruccion(instrucciones[1]), Span::new(8, 9, 9, 30));
+
+        let Instruccion::Asignacion { valor, .. } = &programa.arena[instrucciones[0]] else {
+            panic!("Esperada una asignación");
+        };
+        assert_eq!(programa.arena.span_expresion(*valor), Span::new(7, 14, 7, 27));
+        let Expresion::Binaria { derecha, .. } = &programa.arena[*valor] else {
+            panic!("Esperada una expresión binaria");
+        };
+        let Expresion::Binaria { izquierda: sensor, .. } = &programa.arena[*derecha] else {
+            panic!("Esperada una expresión binaria");
+        };
+        assert_eq!(programa.arena.span_expresion(*sensor), Span::new(7, 18, 7, 23));
+    }
+
+    // Las secciones fuera de orden, repetidas o desconocidas se informan con
+    // lo que el parser esperaba en ese punto
+    #[test]
+    fn test_section_order_diagnostics() {
+        let error = |source: &str, flexibles: bool| {
+            Parser::from_lexer(Lexer::new(source)).con_secciones_flexibles(flexibles).parse().unwrap_err()
+        };
+        let desordenado = "programa p\nrobots\n    robot r\n    comenzar\n        mover\n    fin\nareas\n    ciudad : AreaC(1, 1, 10, 10)\ncomenzar\nfin\n";
+
+        let fuera_de_orden = error(desordenado, false);
+        assert_eq!(fuera_de_orden.message, "La sección 'areas' debe ir antes de la sección 'robots'");
+        assert_eq!((fuera_de_orden.line, fuera_de_orden.column, fuera_de_orden.end_column), (7, 1, 6));
+        let programa = Parser::from_lexer(Lexer::new(desordenado)).con_secciones_flexibles(true).parse().unwrap();
+        assert_eq!(programa.areas.len(), 1);
+        assert_eq!(programa.robots_definidos.len(), 1);
+
+        let repetida = error("programa p\nareas\n    a : AreaC(1, 1, 2, 2)\nareas\n    b : AreaC(3, 3, 4, 4)\ncomenzar\nfin\n", true);
+        assert_eq!(repetida.message, "La sección 'areas' aparece más de una vez");
+
+        let desconocida = error("programa p\nareas\n    a : AreaC(1, 1, 2, 2)\nproceso q\ncomenzar\nfin\n", false);
+        assert_eq!(desconocida.message, "Se encontró 'proceso'; se esperaba 'robots', 'variables' o 'comenzar'");
+        assert_eq!(desconocida.line, 4);
+
+        let sin_fin = error("programa p\ncomenzar\n    mover\n", false);
+        assert_eq!(sin_fin.message, "Fin de archivo inesperado: falta el 'fin' del bloque principal");
+        // A un proceso o robot sin `fin` se lo señala en su encabezado, lo
+        // siga el fin del archivo u otra sección
+        let robot_sin_fin = error("programa p\nrobots\n    robot r\n    comenzar\n        mover\n", false);
+        assert_eq!(robot_sin_fin.message, "Falta el 'fin' del robot 'r'");
+        assert_eq!((robot_sin_fin.line, robot_sin_fin.column, robot_sin_fin.end_column), (3, 5, 12));
+        let seguido = error("programa p\nrobots\n    robot r\n    comenzar\n        mover\n    robot s\n    comenzar\n        mover\n    fin\nvariables\n    r1: r\ncomenzar\nfin\n", false);
+        assert_eq!((seguido.message.as_str(), seguido.line), ("Falta el 'fin' del robot 'r'", 3));
+        let proceso_sin_fin = error("programa p\nprocesos\n    proceso q\n    comenzar\n        mover\nareas\n    a : AreaC(1, 1, 2, 2)\ncomenzar\nfin\n", false);
+        assert_eq!(proceso_sin_fin.message, "Falta el 'fin' del proceso 'q'");
+        assert_eq!(proceso_sin_fin.line, 3);
+    }
+
+    // Las dimensiones de un área se calculan al compilar; las que dependen de
+    // un nombre no pueden calcularse y se informan donde están
+    #[test]
+    fn test_area_dimensions_are_computed_at_compile_time() {
+        let area = |dimensiones: &str| format!("programa p\nareas\n    a : AreaC({})\ncomenzar\nfin\n", dimensiones);
+
+        let programa = parsear(&area("1, 2 * 3, 10 + 5, (4 - 1) * 20"));
+        assert_eq!(programa.areas[0].coordenadas, (1, 6, 15, 60));
+
+        let error = Parser::new(&Lexer::new(&area("1, 1, lado, 10")).tokenize().unwrap()).parse().unwrap_err();
+        assert_eq!(error.message, "La dimensión 3 del área 'a' debe ser un número que pueda calcularse al compilar");
+        assert_eq!((error.line, error.column, error.end_column), (3, 21, 25));
+
+        let error = Parser::new(&Lexer::new(&area("1, 1, 10")).tokenize().unwrap()).parse().unwrap_err();
+        assert_eq!(error.message, "El área 'a' necesita 4 dimensiones, tiene 3");
+        assert_eq!(error.line, 3);
+
+        // Lo esperado se nombra como en el código, sin el tipo interno del token
+        let error = Parser::new(&Lexer::new("programa p\nareas\n    a AreaC(1, 1, 2, 2)\ncomenzar\nfin\n").tokenize().unwrap()).parse().unwrap_err();
+        assert_eq!(error.message, "Esperado ':'");
+        assert_eq!(error.mensaje_en(Idioma::Ingles), "Expected ':'");
+    }
+
+    // Cada constante se calcula al declararla, con las anteriores, y puede
+    // usarse en las dimensiones de las áreas
+    #[test]
+    fn test_constants_are_computed_and_usable_in_areas() {
+        let source = "programa p
+constantes
+    LADO = 10
+    FIN = (LADO + 5) * 2
+areas
+    a : AreaC(1, 1, FIN, LADO)
+comenzar
+fin
+";
+        let programa = parsear(source);
+        let constantes: Vec<_> = programa.constantes.iter()
+            .map(|constante| (constante.nombre.as_str(), constante.valor, constante.linea))
+            .collect();
+        assert_eq!(constantes, [("LADO", 10, 3), ("FIN", 30, 4)]);
+        assert_eq!(programa.areas[0].coordenadas, (1, 1, 30, 10));
+        assert!(a_codigo(&programa).contains("constantes\n    LADO = 10\n    FIN = (LADO + 5) * 2\nareas\n"));
+
+        let error = Parser::new(&Lexer::new("programa p\nconstantes\n    N = M + 1\n    M = 2\ncomenzar\nfin\n").tokenize().unwrap())
+            .parse()
+            .unwrap_err();
+        assert_eq!(error.message, "El valor de la constante 'N' debe ser un número que pueda calcularse al compilar");
+        assert_eq!((error.line, error.column), (3, 9));
+
+        let error = Parser::new(&Lexer::new("programa p\nareas\n    a : AreaC(1, 1, 2, 2)\nconstantes\n    N = 1\ncomenzar\nfin\n").tokenize().unwrap())
+            .parse()
+            .unwrap_err();
+        assert_eq!(error.message, "La sección 'constantes' debe ir antes de la sección 'areas'");
+    }
+
+    // El código impreso vuelve a parsearse en el mismo programa: imprimirlo
+    // otra vez da el mismo texto
+    #[test]
+    fn test_printed_source_round_trips() {
+        let programa = parsear(
+"programa   ida_y_vuelta
+procesos
+    proceso juntar(ES cant : numero, E hasta: numero)
+    variables
+        listo: booleano
+    comenzar
+        listo := F
+        mientras listo = F & HayFlorEnLaEsquina
+            tomarFlor
+            cant := (cant + 1) * 2 - (hasta - 1)
+            si cant >= hasta
+                listo := V
+            sino
+                Informar(\"faltan \\\"flores\\\"\", hasta - cant)
+    fin
+areas
+    ciudad : AreaC(1, 1, 100, 100)
+robots
+    robot recolector
+    variables
+        total : numero
+    comenzar
+        total := 0
+        juntar(total, 10)
+        repetir 2
+            derecha
+    fin
+variables
+    r1 : recolector
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let impreso = a_codigo(&programa);
+        assert_eq!(impreso,
+"programa ida_y_vuelta
+procesos
+    proceso juntar(ES cant: numero, E hasta: numero)
+    variables
+        listo : booleano
+    comenzar
+        listo := F
+        mientras listo == F & HayFlorEnLaEsquina
+            tomarFlor
+            cant := (cant + 1) * 2 - (hasta - 1)
+            si cant >= hasta
+                listo := V
+            sino
+                Informar(\"faltan \\\"flores\\\"\", hasta - cant)
+    fin
+areas
+    ciudad : AreaC(1, 1, 100, 100)
+robots
+    robot recolector
+    variables
+        total : numero
+    comenzar
+        total := 0
+        juntar(total, 10)
+        repetir 2
+            derecha
+    fin
+variables
+    r1 : recolector
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        assert_eq!(a_codigo(&parsear(&impreso)), impreso);
+    }
+
+    // La huella de un bloque o de un programa no depende de las líneas ni
+    // de los símbolos de cada programa, y coincide cuando `bloques_iguales`
+    // o `programas_iguales` los iguala
+    #[test]
+    fn test_structural_hash_ignores_lines() {
+        let robot = |separacion: &str, tope: u32| parsear(&format!("programa huellas
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n: numero
+    comenzar{separacion}
+        n := 0
+        mientras (n < {tope}) & HayFlorEnLaEsquina
+            tomarFlor
+            n := n + 1
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+"));
+        let (original, separado, distinto) = (robot("", 3), robot("\n\n    { otra línea }", 3), robot("", 4));
+        let cuerpo = |programa: &Program| programa.robots_definidos[0].instrucciones.clone();
+
+        assert!(bloques_iguales(&original, &cuerpo(&original), &separado, &cuerpo(&separado)));
+        assert_eq!(huella_bloque(&original, &cuerpo(&original)), huella_bloque(&separado, &cuerpo(&separado)));
+        assert!(!bloques_iguales(&original, &cuerpo(&original), &distinto, &cuerpo(&distinto)));
+        assert_ne!(huella_bloque(&original, &cuerpo(&original)), huella_bloque(&distinto, &cuerpo(&distinto)));
+
+        let Instruccion::Mientras { condicion, .. } = &original.arena[cuerpo(&original)[1]] else {
+            panic!("Se esperaba un mientras");
+        };
+        let Instruccion::Mientras { condicion: otra, .. } = &separado.arena[cuerpo(&separado)[1]] else {
+            panic!("Se esperaba un mientras");
+        };
+        assert_eq!(huella_expresion(&original, *condicion), huella_expresion(&separado, *otra));
+        assert!(expresiones_iguales(&original, *condicion, &separado, *otra));
+
+        // El programa entero también se compara y se usa como clave sin
+        // importar sus líneas
+        assert!(programas_iguales(&original, &separado));
+        assert!(!programas_iguales(&original, &distinto));
+        let huellas: HashSet<u64> = [&original, &separado, &distinto].into_iter().map(huella_programa).collect();
+        assert_eq!(huellas.len(), 2);
+        let renombrado = parsear(&a_codigo(&original).replace("r1", "r2"));
+        assert!(!programas_iguales(&original, &renombrado));
+        assert_ne!(huella_programa(&original), huella_programa(&renombrado));
+    }
+
+    // El árbol concreto devuelve el código tal cual, también con comentarios,
+    // tabulaciones, saltos de Windows, la marca de orden de bytes y texto que
+    // el lexer no lee, y renombrar no toca el resto del formato
+    #[test]
+    fn test_cst_is_lossless_and_renames_in_place() {
+        let source = "\u{FEFF}programa   renombrar { juntar se renombra }\r
+procesos\r
+\tproceso juntar(ES total: numero)\r
+\tcomenzar\r
+\t\ttomarFlor    {toma una}\r
+\t\ttotal := total + 1 $\r
+\tfin\r
+areas\r
+\tciudad: AreaC (1,1,10,10)\r
+robots\r
+\trobot r\r
+\tvariables\r
+\t\tn: numero\r
+\tcomenzar\r
+\t\tn := 0\r
+\t\tjuntar(n)\r
+\t\tInformar(\"juntar\", n)\r
+\tfin\r
+variables\r
+\tr1: r\r
+comenzar\r
+\tAsignarArea(r1, ciudad)\r
+\tIniciar(r1, 1, 1)\r
+fin\r
+";
+        let mut cst = Cst::new(source);
+        assert_eq!(cst.texto(), source);
+
+        let secciones: Vec<ClaseNodo> = cst.raiz.nodos().map(|nodo| nodo.clase).collect();
+        assert_eq!(secciones, [ClaseNodo::Seccion; 5]);
+        let procesos: Vec<_> = cst.raiz.nodos().flat_map(|seccion| seccion.nodos()).map(|nodo| (nodo.clase, nodo.nombre())).collect();
+        assert_eq!(procesos, [(ClaseNodo::Proceso, Some("juntar")), (ClaseNodo::Robot, Some("r"))]);
+        assert!(cst.raiz.nodos().nth(2).unwrap().texto().starts_with("robots\r\n\trobot r\r\n\tvariables"));
+
+        assert_eq!(cst.renombrar("juntar", "recolectar"), 2);
+        let inicio = cst.tokens().iter().find(|token| token.texto == "Iniciar").unwrap().span;
+        assert!(cst.reemplazar(inicio, "Iniciar "));
+        let esperado = source.replace("proceso juntar", "proceso recolectar")
+            .replace("\t\tjuntar(n)", "\t\trecolectar(n)")
+            .replace("Iniciar(", "Iniciar (");
+        assert_eq!(cst.texto(), esperado);
+    }
+
+    // Cualquier secuencia de tokens termina en un programa, quizás parcial,
+    // o en errores, nunca en un pánico, y anidar demasiado es un error y no
+    // una pila agotada
+    #[test]
+    fn test_arbitrary_tokens_give_diagnostics_instead_of_panicking() {
+        let source = "programa p\nprocesos\n  proceso dar(E n: numero, ES total: numero)\n  comenzar\n    si (n > 0) & PosAv < 10\n      total := total + (n * 2)\n  fin\nareas\n  ciudad: AreaC(1, 1, 10, 10)\nrobots\n  robot r\n  variables\n    t: numero\n  comenzar\n    repetir 3\n      dar(PosCa, t)\n  fin\nvariables\n  r1: r\ncomenzar\n  AsignarArea(r1, ciudad)\n  Iniciar(r1, 1, 1)\nfin\n";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        assert!(matches!(parse_tokens_lossy(&tokens), (Some(_), errores) if errores.is_empty()));
+
+        let mut casos: Vec<Vec<_>> = (0..tokens.len()).map(|fin| tokens[..fin].to_vec()).collect();
+        for i in 0..tokens.len() {
+            let mut sin_uno = tokens.clone();
+            sin_uno.remove(i);
+            casos.push(sin_uno);
+            let mut repetido = tokens.clone();
+            repetido.insert(i, tokens[i]);
+            casos.push(repetido);
+        }
+        // Sin programa hay al menos un error; con programa, uno por cada
+        // instrucción que no se pudo parsear
+        for caso in &casos {
+            let (programa, errores) = parse_tokens_lossy(caso);
+            match programa {
+                None => assert!(!errores.is_empty(), "{:?}", caso),
+                Some(programa) => {
+                    let fallidas = programa.arena.instrucciones()
+                        .filter(|instruccion| matches!(instruccion, Instruccion::Error { .. }))
+                        .count();
+                    assert_eq!(fallidas, errores.len(), "{:?}", caso);
+                }
+            }
+        }
+        let (programa, errores) = parse_tokens_lossy(&Lexer::new(&source.replace("total + (n * 2)", "total + (n *)")).tokenize().unwrap());
+        assert!(programa.is_some_and(|programa| programa.procesos[0].nombre == "dar"));
+        assert_eq!(errores.iter().map(|error| error.line).collect::<Vec<_>>(), [6]);
+        fuzz::parsear(b"programa \xff\xfe\ncomenzar\n  (((\x00");
+        for fin in 0..=source.len() {
+            fuzz::parsear(&source.as_bytes()[..fin]);
+        }
+
+        let asignacion = |niveles: usize| format!(
+            "programa p\nrobots\n  robot r\n  variables\n    x: numero\n  comenzar\n    x := {}1{}\n  fin\nvariables\n  r1: r\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n",
+            "(".repeat(niveles), ")".repeat(niveles)
+        );
+        assert!(parse_tokens_lossy(&Lexer::new(&asignacion(100)).tokenize().unwrap()).1.is_empty());
+        let (programa, errores) = parse_tokens_lossy(&Lexer::new(&asignacion(100_000)).tokenize().unwrap());
+        assert!(programa.is_none());
+        assert_eq!(errores[0].codigo(), Some(Codigo::AnidamientoExcesivo));
+        assert_eq!(errores[0].message, format!("Hay más de {} niveles de bloques o paréntesis anidados", ANIDAMIENTO_MAXIMO));
+
+        // Los errores dentro de un bloque se descartan, pero éste no
+        let mut bloques = String::from("programa p\nrobots\n  robot r\n  comenzar\n");
+        for nivel in 0..1000 {
+            bloques.push_str(&format!("{}si V\n", "  ".repeat(nivel + 2)));
+        }
+        bloques.push_str(&format!("{}mover\n  fin\nvariables\n  r1: r\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n", "  ".repeat(1002)));
+        let error = Parser::new(&Lexer::new(&bloques).tokenize().unwrap()).parse().unwrap_err();
+        assert_eq!(error.codigo(), Some(Codigo::AnidamientoExcesivo));
+    }
+
+    // Una instrucción mal escrita es un error, pero no corta el parseo: las
+    // que siguen se parsean igual y el programa conserva su lugar
+    #[test]
+    fn test_malformed_statements_are_reported_and_parsing_continues() {
+        let source = "programa p
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        x: numero
+    comenzar
+        Pos(1 +, 3)
+        mover
+        x := := 4
+        x := 2
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1 +)
+fin
+";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let (programa, errores) = Parser::new(&tokens).parse_parcial();
+        let lineas: Vec<(usize, usize)> = errores.iter().map(|error| (error.line, error.column)).collect();
+        assert_eq!(lineas, [(9, 16), (11, 14), (18, 23)], "{:?}", errores);
+        assert_eq!(errores[0].message, "Expresión simple no válida: Comma");
+
+        let programa = programa.expect("El resto del programa se parsea");
+        let clases = |instrucciones: &[_]| instrucciones.iter()
+            .map(|id| match &programa.arena[*id] {
+                Instruccion::Error { .. } => "error",
+                Instruccion::Asignacion { .. } => "asignación",
+                _ => "otra",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(clases(&programa.robots_definidos[0].instrucciones), ["error", "otra", "error", "asignación"]);
+        assert_eq!(programa.asignaciones_areas.len(), 1);
+        assert!(programa.inicializaciones.is_empty());
+
+        let errores = Parser::new(&tokens).parse_todo().unwrap_err();
+        assert_eq!(errores.len(), 3);
+    }
+
+    // Un programa tiene un nombre y un solo bloque principal, y termina en
+    // su 'fin': lo que siga no se ignora
+    #[test]
+    fn test_program_ends_with_its_single_main_block() {
+        let error = |source: &str| Parser::new(&Lexer::new(source).tokenize().unwrap()).parse().unwrap_err();
+        let cuerpo = "robots\n  robot r\n  comenzar\n    mover\n  fin\nvariables\n  r1: r\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n";
+
+        let programa = parsear(&format!("\n\nprograma recorrido\n{}", cuerpo));
+        assert_eq!((programa.nombre.as_str(), programa.linea), ("recorrido", 3));
+
+        // El nombre es un identificador, no una palabra reservada ni un número
+        for nombre in ["42", "mover", "constantes", "robot"] {
+            let invalido = error(&format!("programa {}\n{}", nombre, cuerpo));
+            assert_eq!(invalido.codigo(), Some(Codigo::EsperadoNombrePrograma), "{}", nombre);
+            assert_eq!((invalido.line, invalido.column), (1, 10));
+        }
+
+        let repetido = error(&format!("programa p\n{}comenzar\n  Iniciar(r1, 2, 2)\nfin\n", cuerpo));
+        assert_eq!(repetido.codigo(), Some(Codigo::BloquePrincipalRepetido));
+        assert_eq!(repetido.message, "El programa tiene un solo bloque principal, y el primero termina en la línea 11");
+        assert_eq!(repetido.line, 12);
+
+        let sobrante = error(&format!("programa p\n{}  mover\n", cuerpo));
+        assert_eq!(sobrante.codigo(), Some(Codigo::CodigoDespuesDelPrincipal));
+        assert_eq!(sobrante.message, "Se encontró 'mover' después del 'fin' del bloque principal (línea 11)");
+
+        let suelta = error("programa p\nrobots\n  robot r\n  comenzar\n    mover\n  fin\nvariables\n  r1: r\n  42\ncomenzar\n  Iniciar(r1, 1, 1)\nfin\n");
+        assert_eq!(suelta.codigo(), Some(Codigo::EsperadoNombreRobot));
+        assert_eq!(suelta.line, 9);
+    }
+}
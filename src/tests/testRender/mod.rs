@@ -0,0 +1 @@
+pub mod renderTest;
\ No newline at end of file
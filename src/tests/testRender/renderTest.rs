@@ -0,0 +1,90 @@
+#[cfg(feature = "svg")]
+use rinfo::interpreter::world::{AreaCiudad, Ciudad, Direccion, EstadoRobot, Mundo};
+#[cfg(feature = "svg")]
+use rinfo::render::{Animacion, Dibujo};
+
+#[cfg(all(test, feature = "svg"))]
+mod testing_render {
+    use super::*;
+
+    fn mundo() -> Mundo {
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(2, 3, 5);
+        ciudad.poner_papeles(7, 1, 12);
+        ciudad.esquina_mut(9, 9).unwrap().bloqueada_por = Some(0);
+        ciudad.areas.push(AreaCiudad {
+            nombre: "privada".to_string(),
+            tipo: "AreaP".to_string(),
+            av_min: 1,
+            ca_min: 1,
+            av_max: 10,
+            ca_max: 10,
+        });
+
+        let mut mundo = Mundo::new(ciudad);
+        let mut robot = EstadoRobot::new("r1", "juntador");
+        robot.av = 4;
+        robot.ca = 4;
+        robot.direccion = Direccion::Este;
+        robot.flores = 3;
+        robot.iniciado = true;
+        mundo.robots.push(robot);
+        mundo
+    }
+
+    #[test]
+    fn test_svg_shows_areas_counts_and_robots() {
+        let svg = Dibujo::new(&mundo()).a_svg();
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1240\" height=\"1282\""), "{}", svg);
+        // El área de 10x10 esquinas, con el color de las áreas privadas
+        assert!(svg.contains("<rect x=\"20\" y=\"1100\" width=\"120\" height=\"120\" fill=\"#cde1fa\"/>"));
+        assert!(svg.contains(">5</text>"));
+        assert!(svg.contains(">12</text>"));
+        assert!(svg.contains("fill=\"none\" stroke=\"#dc0000\""));
+        assert!(svg.contains("<circle cx=\"62\" cy=\"1178\""));
+        assert!(svg.contains(">r1 (juntador) en (4, 4): flores 3, papeles 0</text>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_png_has_the_same_size() {
+        let dibujo = Dibujo::new(&mundo());
+        let png = dibujo.a_png().unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // El encabezado IHDR guarda el ancho y el alto a partir del byte 16
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), dibujo.ancho());
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), dibujo.alto());
+    }
+
+    // El primer cuadro es la imagen entera y los siguientes sólo lo que cambió
+    #[test]
+    fn test_animation_encodes_only_changed_rectangles() {
+        let inicial = mundo();
+        let mut movido = mundo();
+        movido.robots[0].ca = 5;
+
+        let mut animacion = Animacion::new();
+        animacion.agregar(&Dibujo::new(&inicial)).unwrap();
+        animacion.agregar(&Dibujo::new(&movido)).unwrap();
+        animacion.agregar_final(&Dibujo::new(&movido)).unwrap();
+        assert_eq!(animacion.cuadros(), 3);
+        let gif = animacion.terminar().unwrap().unwrap();
+
+        let mut opciones = gif::DecodeOptions::new();
+        opciones.set_color_output(gif::ColorOutput::Indexed);
+        let mut decodificador = opciones.read_info(&gif[..]).unwrap();
+        assert_eq!((decodificador.width(), decodificador.height()), (1240, 1282));
+
+        let mut cuadros = Vec::new();
+        while let Some(cuadro) = decodificador.read_next_frame().unwrap() {
+            cuadros.push((cuadro.width, cuadro.height, cuadro.delay));
+        }
+        assert_eq!(cuadros.len(), 3);
+        assert_eq!(cuadros[0], (1240, 1282, 10));
+        // El robot subió una calle y cambió su posición en la leyenda
+        assert!(cuadros[1].0 < 1240 && cuadros[1].1 < 1282, "{:?}", cuadros[1]);
+        assert_eq!(cuadros[2], (1, 1, 200));
+    }
+}
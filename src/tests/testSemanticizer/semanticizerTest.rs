@@ -0,0 +1,1969 @@
+use rinfo::driver::Opciones;
+use rinfo::lexer::scanner::Lexer;
+use rinfo::mensajes::Codigo;
+use rinfo::parser::processor::{Instruccion, Parser, Program};
+use rinfo::semanticizer::analizer::{AnalysisSummary, SemanticAnalyzer};
+use rinfo::semanticizer::consultas::{Clase, Consultas};
+use rinfo::semanticizer::diferencias::{Aspecto, Cambio, Diferencia, Diferencias};
+use rinfo::semanticizer::hir::{Referencia, Tipo};
+use rinfo::semanticizer::inventario::Inventario;
+use rinfo::semanticizer::lints::{ConfiguracionLints, Nivel, RegistroLints};
+use rinfo::semanticizer::signatures::CategoriaElemental;
+use rinfo::semanticizer::symbol_table::Categoria;
+use rinfo::source::{FileId, SourceMap};
+
+#[cfg(test)]
+mod testing_semanticizer {
+    use super::*;
+
+    fn parsear(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("Failed to tokenize source code");
+        Parser::new(&tokens).parse().expect("Failed to parse source code")
+    }
+
+    fn mensajes(analyzer: &mut SemanticAnalyzer, programa: &Program) -> Vec<String> {
+        analyzer.analizar(programa)
+            .expect_err("El programa tiene errores")
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    // Varias secciones con errores propios: el resultado no depende de cuántos
+    // hilos se usen para verificarlas
+    #[test]
+    fn test_parallel_analysis_matches_sequential_order() {
+        let mut source = String::from("programa secciones\nprocesos\n");
+        for i in 0..6 {
+            source.push_str(&format!(
+"    proceso p{i}(E n: numero)
+    comenzar
+        x{i} := n
+    fin
+"));
+        }
+        source.push_str("areas\n    ciudad: AreaC (1,1,100,100)\nrobots\n");
+        for i in 0..6 {
+            source.push_str(&format!(
+"    robot r{i}
+    variables
+        ok : booleano
+    comenzar
+        ok := 1
+        p{i}(y{i})
+    fin
+"));
+        }
+        source.push_str("variables\n    a: r0\ncomenzar\n    AsignarArea(a, ciudad)\n    Iniciar(a, 1, 1)\nfin\n");
+        let programa = parsear(&source);
+
+        let secuencial = mensajes(&mut SemanticAnalyzer::con_hilos(1), &programa);
+        let paralelo = mensajes(&mut SemanticAnalyzer::con_hilos(4), &programa);
+
+        assert_eq!(secuencial.len(), 18);
+        assert_eq!(paralelo, secuencial);
+        assert_eq!(secuencial[0], "Variable 'x0' no declarada en 'p0'");
+        assert!(secuencial[17].contains("'y5'"));
+    }
+
+    // Un mismo analizador se comparte entre hilos y se reutiliza: cada
+    // análisis empieza de cero
+    #[test]
+    fn test_analyzer_is_reusable_and_shared_between_threads() {
+        fn compartible<T: Send + Sync>() {}
+        compartible::<SemanticAnalyzer>();
+        compartible::<AnalysisSummary>();
+        compartible::<Program>();
+
+        let con_errores = parsear(
+"programa errores
+robots
+    robot r
+    comenzar
+        x := 1
+    fin
+variables
+    a: r
+comenzar
+    Iniciar(a, 1, 1)
+fin
+");
+        let correcto = parsear(
+"programa correcto
+robots
+    robot r
+    comenzar
+        mover
+    fin
+variables
+    a: r
+comenzar
+    Iniciar(a, 1, 1)
+fin
+");
+
+        let mut analyzer = SemanticAnalyzer::new();
+        let primero = mensajes(&mut analyzer, &con_errores);
+        assert_eq!(mensajes(&mut analyzer, &con_errores), primero);
+        assert!(analyzer.analizar(&correcto).is_ok());
+        assert!(analyzer.obtener_errores().is_empty());
+
+        let analyzer = SemanticAnalyzer::con_hilos(1);
+        let resultados: Vec<AnalysisSummary> = std::thread::scope(|scope| {
+            let tareas: Vec<_> = [&con_errores, &correcto, &con_errores, &correcto]
+                .into_iter()
+                .map(|programa| scope.spawn(|| analyzer.examinar(programa)))
+                .collect();
+            tareas.into_iter().map(|tarea| tarea.join().unwrap()).collect()
+        });
+        let exitosos: Vec<bool> = resultados.iter().map(AnalysisSummary::exitoso).collect();
+        assert_eq!(exitosos, [false, true, false, true]);
+        let errores: Vec<&str> = resultados[2].errores.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(errores, primero);
+    }
+
+    // Las condiciones se tipan completas, no operando por operando
+    #[test]
+    fn test_conditions_must_have_the_expected_type() {
+        let source =
+"programa condiciones
+procesos
+    proceso p(E n: numero)
+    variables
+        x : numero
+        ok : booleano
+    comenzar
+        x := n
+        ok := V
+        si (x > 2) & HayFlorEnLaEsquina
+            tomarFlor
+        si x + 2
+            mover
+        mientras ok & x
+            mover
+        repetir ok
+            mover
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        p(3)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Las condiciones tienen errores de tipo");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+
+        assert_eq!(mensajes, vec![
+            (12, "La condición de 'si' debe ser de tipo 'booleano', pero es de tipo 'numero' (en 'p')"),
+            (14, "El operador '&' espera operandos de tipo 'booleano', encontrado 'booleano' y 'numero' en la condición de 'mientras' (en 'p')"),
+            (16, "La condición de 'repetir' debe ser de tipo 'numero', pero es de tipo 'booleano' (en 'p')"),
+        ]);
+    }
+
+    // La condición de cada `sino si` se verifica y se informa en su propia línea
+    #[test]
+    fn test_else_if_conditions_are_checked_on_their_line() {
+        let source =
+"programa cadena
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        x : numero
+    comenzar
+        x := 1
+        si x == 1
+            mover
+        sino si x + 1
+            derecha
+        sino si y > 2
+            x := 2
+        sino
+            tomarFlor
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Las condiciones tienen errores");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(mensajes[0], (12, "La condición de 'si' debe ser de tipo 'booleano', pero es de tipo 'numero' (en 'r')"));
+        assert_eq!(mensajes[1].0, 14);
+        assert_eq!(errores[1].span().columna, 17);
+        assert_eq!(errores.len(), 2);
+    }
+
+    // Los casos de un `segun` se calculan al compilar, son del tipo del
+    // selector y no se repiten; sin `sino` deben cubrir todos los valores
+    #[test]
+    fn test_switch_cases_are_distinct_constants() {
+        let source =
+"programa elegir
+constantes
+    UNO = 1
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        x : numero
+        ok : booleano
+    comenzar
+        x := 1
+        ok := V
+        segun x
+            caso UNO:
+                mover
+            caso 3 - 2:
+                derecha
+            caso x + 1:
+                tomarFlor
+            caso V:
+                mover
+        segun ok
+            caso V:
+                mover
+            caso F:
+                derecha
+        segun x
+            caso 2:
+                mover
+            sino
+                derecha
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let opciones = Opciones { extensiones: true, ..Opciones::default() };
+        let tokens = opciones.lexer(source).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Los casos tienen errores");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(mensajes, vec![
+            (17, "El caso 1 ya aparece en la línea 15 (en 'r')"),
+            (19, "El valor de un 'caso' debe poder calcularse al compilar (en 'r')"),
+            (21, "El caso V es de tipo 'booleano', pero el valor de 'segun' es de tipo 'numero' (en 'r')"),
+        ]);
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "'segun' sin 'sino' que no cubre todos los valores: si no coincide ningún caso no se ejecuta nada (en 'r', línea 14)",
+        ]);
+    }
+
+    // La variable de un `para` es un número nuevo que sólo se ve en el
+    // cuerpo, donde no puede modificarse
+    #[test]
+    fn test_for_loop_variable_is_read_only_inside_the_body() {
+        let source =
+"programa contar
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        x : numero
+    comenzar
+        x := 1
+        para i := 1 hasta HayFlorEnLaEsquina
+            x := x + i
+            i := 2
+            Leer(i)
+        para x := 1 hasta 3
+            mover
+        Informar(i)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let opciones = Opciones { extensiones: true, ..Opciones::default() };
+        let tokens = opciones.lexer(source).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("El para tiene errores");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(mensajes, vec![
+            (10, "Los límites de 'para' deben ser de tipo 'numero', pero éste es de tipo 'booleano' (en 'r')"),
+            (12, "La variable 'i' de 'para' no puede modificarse dentro del ciclo (en 'r')"),
+            (13, "La variable 'i' de 'para' no puede modificarse dentro del ciclo (en 'r')"),
+            (14, "'x' ya es una variable: la variable de 'para' debe tener un nombre nuevo (en 'r')"),
+            (16, "Variable 'i' no declarada en expresión (en 'r')"),
+        ]);
+    }
+
+    // Una llamada dentro de una expresión tiene el tipo que devuelve el
+    // proceso, y sus argumentos se verifican como en cualquier llamada
+    #[test]
+    fn test_calls_in_expressions_use_the_return_type() {
+        let source =
+"programa funciones
+procesos
+    proceso doble(E n: numero): numero
+    comenzar
+        doble := n * 2
+    fin
+    proceso contar(ES total: numero): numero
+    comenzar
+        total := total + 1
+        contar := total
+    fin
+    proceso girar
+    comenzar
+        derecha
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        x : numero
+        b : booleano
+    comenzar
+        x := doble(3) + 1
+        b := doble(x)
+        x := doble(b)
+        x := girar()
+        x := triple(x)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Las llamadas tienen errores");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(mensajes, vec![
+            (7, "El proceso 'contar' devuelve un valor, así que sus parámetros deben ser E, pero 'total' es ES"),
+            (25, "Tipo incorrecto en asignación a 'b': esperado 'booleano', encontrado 'numero' (en 'r')"),
+            (26, "El argumento 1 de 'doble' debe ser de tipo 'numero', pero es de tipo 'booleano' (en 'r')"),
+            (27, "'girar' no es un proceso que devuelva un valor (en 'r')"),
+            (28, "'triple' no es un proceso que devuelva un valor (en 'r')"),
+        ]);
+    }
+
+    // Un proceso que devuelve un valor tiene que asignárselo a su nombre en
+    // algún camino; basta con que sea dentro de un `si`
+    #[test]
+    fn test_returned_value_must_be_assigned() {
+        let source =
+"programa retornos
+procesos
+    proceso olvida(E n: numero): numero
+    variables
+        aux : numero
+    comenzar
+        aux := n * 2
+    fin
+    proceso positivo(E n: numero): booleano
+    comenzar
+        si n > 0
+            positivo := V
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        x : numero
+    comenzar
+        x := olvida(3)
+        si positivo(x)
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("'olvida' nunca asigna su valor");
+        assert_eq!(errores.len(), 1);
+        assert_eq!(errores[0].line, 3);
+        assert_eq!(errores[0].codigo(), Some(Codigo::RetornoSinAsignar));
+        assert_eq!(errores[0].message, "El proceso 'olvida' devuelve un valor de tipo 'numero', pero nunca lo asigna: falta 'olvida := ...'");
+    }
+
+    // Cada robot se inicia una sola vez y después de asignarle sus áreas
+    #[test]
+    fn test_every_robot_is_started_once_after_its_areas() {
+        let source =
+"programa inicios
+areas
+    ciudad: AreaC (1,1,10,10)
+    campo: AreaC (11,11,20,20)
+robots
+    robot r
+    comenzar
+        mover
+    fin
+variables
+    r1, r2, r3: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+    AsignarArea(r1, campo)
+    AsignarArea(r2, ciudad)
+    Iniciar(r2, 2, 2)
+    Iniciar(r2, 3, 3)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Hay robots mal iniciados");
+        let mensajes: Vec<(usize, usize, &str)> = errores.iter().map(|e| (e.line, e.column, e.message.as_str())).collect();
+
+        // Se señala el robot en la instrucción; de la declaración sólo se
+        // conoce la línea
+        assert_eq!(mensajes, vec![
+            (18, 13, "El robot 'r2' ya se inició en la línea 17"),
+            (15, 17, "Se asigna un área a 'r1' después de iniciarlo en la línea 14: 'Iniciar' va después de todos sus 'AsignarArea'"),
+            (11, 0, "El robot 'r3' nunca se inicia: falta 'Iniciar(r3, ...)' en el programa principal"),
+        ]);
+    }
+
+    // Un arreglo se usa de a un elemento, con un índice numérico; los
+    // índices que se calculan al compilar tienen que estar dentro del arreglo
+    #[test]
+    fn test_arrays_are_used_through_their_indexes() {
+        let source =
+"programa arreglos
+constantes
+    LARGO = 5
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        flores : arreglo[LARGO] de numero
+        vistas : arreglo[2] de booleano
+        x : numero
+    comenzar
+        flores[1] := flores[LARGO] + x
+        vistas[x] := HayFlorEnLaEsquina
+        flores[LARGO + 1] := 0
+        x := flores[0]
+        flores[V] := 1
+        flores := 3
+        Informar(vistas)
+        x[2] := 1
+        vistas[1] := flores[2]
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let opciones = Opciones { extensiones: true, ..Opciones::default() };
+        let tokens = opciones.lexer(source).tokenize().unwrap();
+        let programa = Parser::new(&tokens).parse().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Los arreglos se usan mal");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(mensajes, vec![
+            (15, "El índice 6 está fuera del arreglo 'flores', que va de 1 a 5 (en 'r')"),
+            (16, "El índice 0 está fuera del arreglo 'flores', que va de 1 a 5 (en 'r')"),
+            (17, "El índice de 'flores' debe ser de tipo 'numero', pero es de tipo 'booleano' (en 'r')"),
+            (18, "'flores' es un arreglo: se usa de a un elemento, como 'flores[1]' (en 'r')"),
+            (19, "'vistas' es un arreglo: se usa de a un elemento, como 'vistas[1]' (en 'r')"),
+            (20, "'x' no es un arreglo, así que no lleva índice (en 'r')"),
+            (21, "Tipo incorrecto en asignación a 'vistas': esperado 'booleano', encontrado 'numero' (en 'r')"),
+        ]);
+    }
+
+    // PosAv y PosCa son números; los sensores Hay... son booleanos
+    #[test]
+    fn test_sensors_have_their_own_types() {
+        let source =
+"programa sensores
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        av : numero
+        ok : booleano
+    comenzar
+        av := PosAv + PosCa
+        ok := HayFlorEnLaBolsa | HayPapelEnLaEsquina
+        ok := PosCa
+        si PosAv
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec![
+            "Tipo incorrecto en asignación a 'ok': esperado 'booleano', encontrado 'numero' (en 'r')",
+            "La condición de 'si' debe ser de tipo 'booleano', pero es de tipo 'numero' (en 'r')",
+        ]);
+    }
+
+    // Las instrucciones elementales se verifican con su firma: cantidad de
+    // argumentos, tipos y nombres de robots y áreas
+    #[test]
+    fn test_elemental_instructions_check_arity_and_argument_types() {
+        let source =
+"programa firmas
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        ok : booleano
+        n : numero
+    comenzar
+        Pos(1)
+        Pos(ok, 2)
+        mover(3)
+        Informar(n, ok)
+        Random(n, 1, 10)
+        EnviarMensaje(n, r2)
+        EnviarMensaje(n, otro)
+        RecibirMensaje(5, r2)
+    fin
+variables
+    r1: r
+    r2: r
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, campo)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, V, 2)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec![
+            "'Pos' espera 2 argumentos, recibió 1 (en 'r')",
+            "El argumento 1 de 'Pos' debe ser de tipo 'numero' (en 'r')",
+            "'mover' no lleva argumentos, recibió 1 (en 'r')",
+            "El argumento 2 de 'EnviarMensaje' debe ser un robot declarado o '*' (en 'r')",
+            "El argumento 1 de 'RecibirMensaje' debe ser una variable (en 'r')",
+            "El argumento 2 de 'AsignarArea' debe ser un área declarada (en 'firmas')",
+            "El argumento 2 de 'Iniciar' debe ser de tipo 'numero' (en 'firmas')",
+        ]);
+    }
+
+    // Una variable sin declarar se informa una vez por sección, en su primer
+    // uso, con las líneas de los demás
+    #[test]
+    fn test_undeclared_variable_is_reported_once_per_section() {
+        let source =
+"programa cascada
+procesos
+    proceso contar(ES total: numero)
+    comenzar
+        cuenta := cuenta + 1
+        total := total + cuenta
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        repetir 3
+            contador := contador + 1
+        si contador > 2
+            Informar(contador)
+        Pos(contador, 1)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let errores = SemanticAnalyzer::new().analizar(&programa).unwrap_err();
+        let resumen: Vec<_> = errores.iter()
+            .map(|error| {
+                let usos: Vec<_> = error.notas().iter().map(|nota| nota.span.unwrap().linea).collect();
+                (error.line, error.message.as_str(), usos)
+            })
+            .collect();
+
+        assert_eq!(resumen, vec![
+            (5, "Variable 'cuenta' no declarada en 'contar'", vec![6]),
+            (14, "Variable 'contador' no declarada en 'r'", vec![15, 16, 17]),
+        ]);
+        assert_eq!(errores[1].notas()[0].mensaje, "también se usa aquí");
+        assert_eq!(errores[1].notas()[0].span.unwrap().columna, 12);
+        assert_eq!(errores[1].ayuda().map(ToString::to_string).as_deref(), Some("declarar 'contador' en la sección 'variables' de 'r'"));
+    }
+
+    // Ante un nombre parecido a una variable visible se sugiere reemplazarlo;
+    // un nombre repetido señala con una nota su primera declaración
+    #[test]
+    fn test_diagnostics_suggest_similar_names_and_point_to_related_code() {
+        let source =
+"programa sugerencias
+procesos
+    proceso juntar(ES total: numero)
+    comenzar
+        total := totla + 1
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        contador : numero
+        juntar : numero
+    comenzar
+        contadro := 0
+        Informar(contador)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let errores = SemanticAnalyzer::new().analizar(&programa).unwrap_err();
+        assert_eq!(errores.len(), 3, "{:?}", errores);
+
+        let conflicto = &errores[0];
+        assert!(conflicto.message.starts_with("Nombre 'juntar' declarado como proceso"));
+        assert_eq!(conflicto.notas()[0].mensaje, "primera declaración, como proceso");
+        assert_eq!(conflicto.notas()[0].span.map(|span| span.linea), Some(3));
+
+        let totla = &errores[1];
+        assert_eq!((totla.line, totla.column, totla.end_column), (5, 18, 23));
+        assert_eq!(totla.sugerencias().len(), 1);
+        assert_eq!(totla.sugerencias()[0].mensaje, "¿se quiso decir 'total'?");
+        assert_eq!(totla.sugerencias()[0].reemplazo, "total");
+        assert_eq!(totla.sugerencias()[0].span, totla.span());
+
+        let contadro = &errores[2];
+        assert_eq!((contadro.line, contadro.column, contadro.end_column), (15, 9, 17));
+        assert_eq!(contadro.sugerencias()[0].reemplazo, "contador");
+        assert!(contadro.ayuda().is_none());
+    }
+
+    // Robots, áreas y procesos se buscan por categoría: no son variables
+    #[test]
+    fn test_robot_and_area_names_are_not_variables() {
+        let source =
+"programa categorias
+procesos
+    proceso avisar(E n: numero)
+    comenzar
+        Informar(n)
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        EnviarMensaje(n, r2)
+        RecibirMensaje(n, r2)
+        EnviarMensaje(n, ciudad)
+        EnviarMensaje(n, n)
+        n := r2
+        r2 := 1
+        avisar(n)
+    fin
+variables
+    r1: r
+    r2: r
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, r1)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec![
+            "El argumento 2 de 'EnviarMensaje' debe ser un robot declarado o '*', pero 'ciudad' es un área (en 'r')",
+            "El argumento 2 de 'EnviarMensaje' debe ser un robot declarado o '*', pero 'n' es una variable (en 'r')",
+            "'r2' es un robot, no una variable (en 'r')",
+            "No se puede asignar a 'r2': es un robot, no una variable (en 'r')",
+            "El argumento 2 de 'AsignarArea' debe ser un área declarada, pero 'r1' es un robot (en 'categorias')",
+        ]);
+
+        // Cada error señala el nombre
+        let errores = SemanticAnalyzer::new().analizar(&programa).unwrap_err();
+        let ubicaciones: Vec<(usize, usize, usize)> = errores.iter().map(|e| (e.line, e.column, e.end_column)).collect();
+        assert_eq!(ubicaciones, vec![(16, 26, 32), (17, 26, 27), (18, 14, 16), (19, 9, 11), (27, 21, 23)]);
+    }
+
+    // Procesos, áreas, tipos de robot, robots y variables no pueden repetir
+    // nombre; el error indica las dos declaraciones
+    #[test]
+    fn test_duplicate_names_across_categories() {
+        let source =
+"programa duplicados
+procesos
+    proceso ciudad(E n: numero)
+    variables
+        n : numero
+    comenzar
+        Informar(n)
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+    campo: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        ciudad : numero
+    comenzar
+        mover
+    fin
+variables
+    r: r
+    r1: r
+comenzar
+    AsignarArea(r1, campo)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("El programa repite nombres");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+
+        assert_eq!(mensajes, vec![
+            (10, "Nombre 'ciudad' declarado como proceso (línea 3) y como área (línea 10)"),
+            (20, "Nombre 'r' declarado como tipo de robot (línea 13) y como robot (línea 20)"),
+            (5, "Nombre 'n' declarado como parámetro (línea 3) y como variable (línea 5)"),
+            (15, "Nombre 'ciudad' declarado como proceso (línea 3) y como variable (línea 15)"),
+            (20, "El robot 'r' nunca se inicia: falta 'Iniciar(r, ...)' en el programa principal"),
+        ]);
+    }
+
+    // Los bloques de si/mientras/repetir no abren un ámbito propio: dentro de
+    // ellos se ven los parámetros y variables de la sección, a cualquier
+    // profundidad, y nada más
+    #[test]
+    fn test_nested_blocks_resolve_names_in_their_section() {
+        let source =
+"programa bloques
+procesos
+    proceso contar(E tope: numero, ES total: numero)
+    variables
+        i : numero
+    comenzar
+        i := 0
+        mientras i < tope
+            repetir 2
+                si HayFlorEnLaEsquina
+                    total := total + i
+                sino
+                    total := total + j
+            i := i + 1
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        j : numero
+    comenzar
+        j := 0
+        contar(3, j)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec![
+            "Variable 'j' no declarada en expresión (en 'contar'): es una variable del robot 'r', que los procesos no ven; debe pasarse como parámetro"
+        ]);
+    }
+
+    // Un proceso sólo ve sus parámetros y variables; cada llamada liga los
+    // argumentos a los parámetros según su cantidad, tipo y dirección
+    #[test]
+    fn test_process_calls_bind_arguments_to_parameters() {
+        let source =
+"programa llamadas
+procesos
+    proceso juntar(E tope: numero, ES flores: numero)
+    comenzar
+        repetir tope
+            flores := flores + 1
+        cant := flores
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        cant : numero
+        listo : booleano
+    comenzar
+        juntar(3, cant)
+        juntar(3)
+        juntar(listo, cant)
+        juntar(2, cant + 1)
+        juntar(2, listo)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec![
+            "Variable 'cant' no declarada en 'juntar': es una variable del robot 'r', que los procesos no ven; debe pasarse como parámetro",
+            "'juntar' espera 2 argumentos, recibió 1 (en 'r')",
+            "El argumento 1 de 'juntar' debe ser de tipo 'numero', pero es de tipo 'booleano' (en 'r')",
+            "El argumento 2 de 'juntar' debe ser una variable: el parámetro 'flores' es ES (en 'r')",
+            "El argumento 2 de 'juntar' debe ser de tipo 'numero', pero es de tipo 'booleano' (en 'r')",
+        ]);
+    }
+
+    // Una instrucción que nombra algo que no es ni una instrucción elemental
+    // ni un proceso es un error; sus argumentos se verifican igual
+    #[test]
+    fn test_calls_to_undeclared_processes_are_errors() {
+        let source =
+"programa sin_proceso
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        cant : numero
+    comenzar
+        juntar(3, cant)
+        juntar(total)
+        girar
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Los procesos no están declarados");
+        let mensajes: Vec<(usize, usize, &str)> = errores.iter().map(|e| (e.line, e.column, e.message.as_str())).collect();
+
+        assert_eq!(mensajes, vec![
+            (9, 9, "Proceso 'juntar' no declarado (en 'r')"),
+            (10, 9, "Proceso 'juntar' no declarado (en 'r')"),
+            (10, 16, "Variable 'total' no declarada en expresión (en 'r')"),
+            (11, 9, "Proceso 'girar' no declarado (en 'r')"),
+        ]);
+        assert_eq!(errores[0].codigo(), Some(Codigo::ProcesoNoDeclarado));
+    }
+
+    // Las declaraciones se reúnen antes de verificar los cuerpos: un proceso
+    // puede llamar a otro declarado más abajo, con su firma ya conocida
+    #[test]
+    fn test_processes_can_call_processes_declared_later() {
+        let source =
+"programa adelante
+procesos
+    proceso ida(E pasos: numero)
+    comenzar
+        vuelta(pasos)
+        vuelta
+    fin
+    proceso vuelta(E pasos: numero)
+    comenzar
+        repetir pasos
+            mover
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        ida(2)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec!["'vuelta' espera 1 argumento, recibió 0 (en 'ida')"]);
+    }
+
+    // El análisis deja resuelto el tipo de cada expresión y a qué declaración
+    // se refiere cada nombre; las variables de la sección ocultan a los globales
+    #[test]
+    fn test_hir_resolves_types_and_references() {
+        let source =
+"programa tipado
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        n : numero
+        listo : booleano
+    comenzar
+        listo := (n + 1 > 3) & listo
+        n := n + listo
+        EnviarMensaje(n, r1)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let resumen = SemanticAnalyzer::new().examinar(&programa);
+        let hir = &resumen.hir;
+        let instrucciones: Vec<&Instruccion> = programa.arena.bloque(&programa.robots_definidos[0].instrucciones).collect();
+
+        let Instruccion::Asignacion { valor, .. } = instrucciones[0] else { panic!("Se esperaba una asignación") };
+        assert_eq!(hir.tipo(*valor), Some(Tipo::Booleano));
+
+        let Instruccion::Asignacion { valor, .. } = instrucciones[1] else { panic!("Se esperaba una asignación") };
+        assert_eq!(hir.tipo(*valor), None);
+        assert!(hir.error_de_tipos(*valor).is_some());
+
+        let Instruccion::LlamadaFuncion { argumentos, .. } = instrucciones[2] else { panic!("Se esperaba una llamada") };
+        assert_eq!(hir.referencia(argumentos[0]), Some(Referencia::Variable { tipo: Some(Tipo::Numero) }));
+        assert_eq!(hir.referencia(argumentos[1]), Some(Referencia::Global(Categoria::Robot)));
+        assert_eq!(hir.referencia(programa.asignaciones_areas[0].area), Some(Referencia::Global(Categoria::Area)));
+        assert_eq!(hir.tipo(programa.inicializaciones[0].pos_x), Some(Tipo::Numero));
+    }
+
+    // Las comparaciones dan un booleano a partir de números y los operadores
+    // lógicos combinan booleanos; el resultado debe coincidir con la variable
+    #[test]
+    fn test_assignments_check_comparison_and_logical_types() {
+        let source =
+"programa asignaciones
+procesos
+    proceso p(E x: numero)
+    variables
+        b : booleano
+        listo : booleano
+        n : numero
+    comenzar
+        listo := V
+        b := x > 3
+        b := (x > 3) & listo
+        b := (x + 1 <= 2 * x) | (x == 4)
+        n := x > 3
+        b := (x + listo) > 3
+        b := listo & x
+        b := x == listo
+        b := ~listo & ~(x > 3)
+        b := ~x
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        p(3)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("Las asignaciones tienen errores de tipo");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+
+        assert_eq!(mensajes, vec![
+            (13, "Tipo incorrecto en asignación a 'n': esperado 'numero', encontrado 'booleano' (en 'p')"),
+            (14, "El operador '+' espera operandos de tipo 'numero', encontrado 'numero' y 'booleano' (en 'p')"),
+            (15, "El operador '&' espera operandos de tipo 'booleano', encontrado 'booleano' y 'numero' (en 'p')"),
+            (16, "El operador '==' compara valores de distinto tipo: 'numero' y 'booleano' (en 'p')"),
+            (18, "El operador '~' espera un operando de tipo 'booleano', encontrado 'numero' (en 'p')"),
+        ]);
+    }
+
+    // Los valores constantes permiten advertir errores antes de ejecutar,
+    // sin impedir que el programa compile
+    #[test]
+    fn test_constant_values_produce_lints() {
+        let source =
+"programa valores_fijos
+procesos
+    proceso p(E x: numero)
+    variables
+        n : numero
+    comenzar
+        n := x / 0
+        n := x / (2 - 2)
+        n := x / (2 - 1)
+        repetir 0 - 3
+            mover
+        repetir 2
+            Pos(0, 101)
+        Pos(1, 100)
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        p(3)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 50 * 3, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analizar(&programa).is_ok());
+
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "División por cero (en 'p', línea 7)",
+            "División por cero (en 'p', línea 8)",
+            "'repetir' con una cantidad negativa (-3) no ejecuta su cuerpo (en 'p', línea 10)",
+            "La coordenada 0 de 'Pos' está fuera de la ciudad (1 a 100) (en 'p', línea 13)",
+            "La coordenada 101 de 'Pos' está fuera de la ciudad (1 a 100) (en 'p', línea 13)",
+            "La coordenada 150 de 'Iniciar' está fuera de la ciudad (1 a 100) (en 'valores_fijos')",
+        ]);
+    }
+
+    // Las constantes declaradas se leen como números desde cualquier
+    // sección, pero no pueden recibir valores ni repetir el nombre de una
+    // variable; sus valores también alimentan las advertencias
+    #[test]
+    fn test_declared_constants_are_read_only_numbers() {
+        let source =
+"programa valores_fijos
+constantes
+    VUELTAS = 0 - 2
+    LADO = 10
+procesos
+    proceso p(ES x: numero)
+    variables
+        LADO : numero
+    comenzar
+        x := x + 1
+    fin
+areas
+    ciudad: AreaC (1,1,LADO,LADO)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        n := LADO * 2
+        repetir VUELTAS
+            mover
+        VUELTAS := n
+        p(LADO)
+        si LADO
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, LADO, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let errores = analyzer.analizar(&programa).expect_err("El programa tiene errores");
+        let mensajes: Vec<(usize, &str)> = errores.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(mensajes, vec![
+            (8, "Nombre 'LADO' declarado como constante (línea 4) y como variable (línea 8)"),
+            (22, "No se puede asignar a 'VUELTAS': es una constante, no una variable (en 'r')"),
+            (23, "El argumento 1 de 'p' debe ser una variable: el parámetro 'x' es ES (en 'r')"),
+            (24, "La condición de 'si' debe ser de tipo 'booleano', pero es de tipo 'numero' (en 'r')"),
+        ]);
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "'repetir' con una cantidad negativa (-2) no ejecuta su cuerpo (en 'r', línea 20)",
+        ]);
+
+        let resumen = SemanticAnalyzer::new().examinar(&programa);
+        let Instruccion::Asignacion { valor, .. } = programa.arena.bloque(&programa.robots_definidos[0].instrucciones).next().unwrap() else {
+            panic!("Se esperaba una asignación");
+        };
+        assert_eq!(resumen.hir.tipo(*valor), Some(Tipo::Numero));
+        assert_eq!(resumen.hir.referencia(programa.inicializaciones[0].pos_x), Some(Referencia::Global(Categoria::Constante)));
+    }
+
+    // Una variable cuenta como asignada si lo está en todos los caminos que
+    // llegan a su uso, también a través de bloques anidados
+    #[test]
+    fn test_reads_before_assignment_are_reported() {
+        let source =
+"programa flujo
+procesos
+    proceso p(E tope: numero)
+    variables
+        x : numero
+        y : numero
+        z : numero
+        w : numero
+        c : numero
+    comenzar
+        si tope > 2
+            x := 5
+        sino
+            x := 1
+        repetir x
+            mover
+        si HayFlorEnLaEsquina
+            y := 2
+        repetir y
+            mover
+        mientras HayFlorEnLaEsquina
+            Informar(c)
+            c := 1
+        repetir 3
+            w := 1
+        Random(z, 1, w)
+        Informar(z)
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        n : numero
+    comenzar
+        repetir n
+            mover
+        p(n)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analizar(&programa).is_ok());
+
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "Variable 'y' posiblemente sin inicializar (en 'p', línea 19)",
+            "Variable 'c' posiblemente sin inicializar (en 'p', línea 22)",
+            "Variable 'n' usada sin inicializar (en 'r', línea 36)",
+        ]);
+    }
+
+    // Las cadenas se pueden informar, enviar como mensaje y guardar en
+    // variables de tipo cadena, pero no operar ni usar como cantidad
+    #[test]
+    fn test_strings_are_only_valid_as_payloads() {
+        let source =
+"programa cadenas
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    variables
+        n : numero
+        texto : cadena
+    comenzar
+        texto := 'hola'
+        Informar('flores:', n, texto)
+        EnviarMensaje('listo', r2)
+        RecibirMensaje(texto, r2)
+        n := 'dos'
+        n := 'a' + 1
+        repetir 'tres'
+            mover
+        Pos('1', 1)
+    fin
+variables
+    r1: r
+    r2: r
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        let mensajes = mensajes(&mut analyzer, &programa);
+
+        assert_eq!(mensajes, vec![
+            "Tipo incorrecto en asignación a 'n': esperado 'numero', encontrado 'cadena' (en 'r')",
+            "El operador '+' espera operandos de tipo 'numero', encontrado 'cadena' y 'numero' (en 'r')",
+            "La condición de 'repetir' debe ser de tipo 'numero', pero es de tipo 'cadena' (en 'r')",
+            "El argumento 1 de 'Pos' debe ser de tipo 'numero' (en 'r')",
+        ]);
+    }
+
+    // Un ejercicio chico puede no tener procesos, áreas ni variables globales,
+    // pero necesita al menos un robot y el bloque principal
+    #[test]
+    fn test_sections_are_optional_but_robots_are_required() {
+        let mut analyzer = SemanticAnalyzer::new();
+        let programa = parsear(
+"programa minimo
+robots
+    robot r
+    comenzar
+        mover
+    fin
+comenzar
+fin
+");
+        assert!(programa.procesos.is_empty() && programa.areas.is_empty() && programa.robots_instanciados.is_empty());
+        assert!(analyzer.analizar(&programa).is_ok());
+
+        let sin_robots = parsear("programa vacio\ncomenzar\nfin\n");
+        assert_eq!(
+            mensajes(&mut SemanticAnalyzer::new(), &sin_robots),
+            vec!["El programa no define ningún robot: falta la sección 'robots'"]
+        );
+
+        let sin_bloque = "programa incompleto\nrobots\n    robot r\n    comenzar\n        mover\n    fin\n";
+        let error = Parser::from_lexer(Lexer::new(sin_bloque)).parse().unwrap_err();
+        assert_eq!(error.message, "Fin de archivo inesperado: falta el bloque principal; se esperaba 'variables' o 'comenzar'");
+    }
+
+    #[test]
+    fn test_queries_find_declarations_and_uses() {
+        let programa = parsear(
+"programa consultas
+procesos
+    proceso juntar(ES total: numero)
+    comenzar
+        si HayFlorEnLaEsquina
+            tomarFlor
+            total := total + 1
+    fin
+    proceso recorrer(ES total: numero)
+    comenzar
+        repetir 3
+            juntar(total)
+            mover
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+    otra: AreaP (5,5,6,6)
+robots
+    robot tipo1
+    variables
+        total : numero
+    comenzar
+        total := 0
+        recorrer(total)
+        juntar(total)
+    fin
+variables
+    r1: tipo1
+    r2: tipo1
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    AsignarArea(r2, otra)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 5, 5)
+fin
+");
+        let consultas = Consultas::new(&programa);
+
+        let total: Vec<_> = consultas.buscar_simbolo("total").into_iter()
+            .map(|declarado| (declarado.clase, declarado.seccion.unwrap(), declarado.linea))
+            .collect();
+        assert_eq!(total, vec![
+            (Clase::Parametro, "juntar".to_string(), 3),
+            (Clase::Parametro, "recorrer".to_string(), 9),
+            (Clase::Variable, "tipo1".to_string(), 21),
+        ]);
+        let r2 = consultas.buscar_simbolo("r2");
+        assert_eq!((r2[0].clase, r2[0].tipo.as_deref(), r2[0].seccion.as_deref()), (Clase::Robot, Some("tipo1"), None));
+        assert!(consultas.buscar_simbolo("inexistente").is_empty());
+
+        let llamadores: Vec<_> = consultas.llamadores_de("juntar").iter()
+            .map(|llamada| (llamada.desde.as_str(), llamada.linea))
+            .collect();
+        assert_eq!(llamadores, vec![("recorrer", 12), ("tipo1", 25)]);
+        assert!(consultas.llamadores_de("tipo1").is_empty());
+
+        assert_eq!(consultas.robots_en_area("ciudad"), vec!["r1", "r2"]);
+        assert_eq!(consultas.robots_en_area("otra"), vec!["r2"]);
+
+        // El `si` y las instrucciones anidadas empiezan en líneas distintas
+        assert_eq!(consultas.instrucciones_en_linea(FileId::PRINCIPAL, 5).len(), 1);
+        let instrucciones = consultas.instrucciones_en_linea(FileId::PRINCIPAL, 7);
+        assert!(matches!(programa.arena.instruccion(instrucciones[0]), Instruccion::Asignacion { .. }));
+        assert!(consultas.instrucciones_en_linea(FileId::PRINCIPAL, 2).is_empty());
+    }
+
+    #[test]
+    fn test_metrics_measure_each_section() {
+        let programa = parsear(
+"programa metricas
+procesos
+    proceso juntar(ES total: numero, E veces: numero)
+    comenzar
+        repetir veces
+            si HayFlorEnLaEsquina & (total < 10)
+                tomarFlor
+                total := total + 1
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot tipo1
+    variables
+        total : numero
+    comenzar
+        juntar(total, 3)
+        mover
+        derecha
+        EnviarMensaje(total, r1)
+        Informar(total)
+    fin
+variables
+    r1: tipo1
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analizar(&programa).unwrap();
+        let resumen = analyzer.resumen();
+        assert!(resumen.errores.is_empty());
+
+        let juntar = resumen.metricas.seccion("juntar").unwrap();
+        assert_eq!((juntar.instrucciones, juntar.profundidad, juntar.parametros), (4, 2, Some(2)));
+        // El repetir, el si y el `&` de su condición
+        assert_eq!(juntar.complejidad, 4);
+        assert_eq!(juntar.elementales_de(CategoriaElemental::Objetos), 1);
+
+        let robot = resumen.metricas.seccion("tipo1").unwrap();
+        assert_eq!((robot.instrucciones, robot.profundidad, robot.complejidad, robot.parametros), (5, 0, 1, None));
+        let elementales: Vec<usize> = CategoriaElemental::TODAS.iter().map(|&categoria| robot.elementales_de(categoria)).collect();
+        assert_eq!(elementales, vec![2, 0, 1, 0, 1]);
+
+        let tabla = resumen.metricas.a_texto();
+        assert!(tabla.starts_with("seccion  clase    instrucciones  profundidad  complejidad  parametros  movimiento"));
+        assert!(tabla.lines().nth(2).unwrap().starts_with("tipo1    robot    5              0            1            -"));
+        assert!(resumen.metricas.a_json().contains("\"complejidad\": 4, \"parametros\": 2"));
+    }
+
+    #[test]
+    fn test_symbol_table_export_lists_every_declaration() {
+        let programa = parsear(
+"programa inventario
+procesos
+    proceso juntar(ES total: numero, E veces: numero)
+    comenzar
+        repetir veces
+            total := total + 1
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot tipo1
+    variables
+        total : numero
+        nunca : booleano
+    comenzar
+        juntar(total, 3)
+        juntar(total, 2)
+        Informar(total)
+    fin
+variables
+    r1: tipo1
+    r2: tipo1
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let mut fuentes = SourceMap::new();
+        fuentes.agregar("inventario.ri", "");
+        let inventario = Inventario::new(&programa);
+
+        let csv = inventario.a_csv(&fuentes);
+        let filas: Vec<&str> = csv.lines().collect();
+        assert_eq!(filas, vec![
+            "nombre,clase,ambito,tipo,archivo,linea,inicializado,usos",
+            "juntar,proceso,global,,inventario.ri,3,true,2",
+            "ciudad,area,global,AreaC,inventario.ri,9,true,2",
+            "tipo1,tipo_robot,global,,inventario.ri,11,true,2",
+            // r2 no se inicia
+            "r1,robot,global,tipo1,inventario.ri,21,true,2",
+            "r2,robot,global,tipo1,inventario.ri,22,false,1",
+            "total,parametro,juntar,numero,inventario.ri,3,true,2",
+            "veces,parametro,juntar,numero,inventario.ri,3,true,1",
+            "total,variable,tipo1,numero,inventario.ri,13,true,3",
+            "nunca,variable,tipo1,booleano,inventario.ri,14,false,0",
+        ]);
+
+        let json = inventario.a_json(&fuentes);
+        assert_eq!(json.lines().count(), inventario.simbolos.len() + 2);
+        assert!(json.contains(r#"{"nombre": "nunca", "clase": "variable", "ambito": "tipo1", "tipo": "booleano", "archivo": "inventario.ri", "linea": 14, "inicializado": false, "usos": 0}"#));
+    }
+
+    fn mensajeros(emisor: &str, receptor: &str) -> Program {
+        parsear(&format!(
+"programa mensajes
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot emisor
+{emisor}    comenzar
+        EnviarMensaje(HayFlorEnLaEsquina, r2)
+    fin
+    robot receptor
+    variables
+        dato : numero
+{receptor}    comenzar
+        RecibirMensaje(dato, r1)
+    fin
+variables
+    r1: emisor
+    r2: receptor
+comenzar
+    AsignarArea(r1, ciudad)
+    AsignarArea(r2, ciudad)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 2, 2)
+fin
+"))
+    }
+
+    // El tipo de un mensaje se infiere de lo que se envía, o se declara en
+    // el bloque `mensajes` del robot
+    #[test]
+    fn test_message_types_must_agree_between_robots() {
+        let programa = mensajeros("", "");
+        let errores = SemanticAnalyzer::new().analizar(&programa).unwrap_err();
+        assert_eq!(errores.len(), 1);
+        assert_eq!(
+            errores[0].message,
+            "Mensaje de 'r1' a 'r2': la variable 'dato' es de tipo 'numero', pero el valor enviado es de tipo 'booleano'"
+        );
+        assert_eq!(errores[0].line, 13);
+        let nota = &errores[0].detalles.as_ref().unwrap().notas[0];
+        assert_eq!(nota.mensaje.to_string(), "el valor enviado es de tipo 'booleano'");
+        assert_eq!(nota.span.unwrap().linea, 7);
+        // El error y la nota señalan la variable receptora y el valor enviado
+        assert!(errores[0].column > 0 && nota.span.unwrap().columna > 0);
+
+        // El contrato del emisor decide: ahora el que está mal es el envío
+        let programa = mensajeros("    mensajes\n        envia numero a receptor\n", "");
+        assert_eq!(programa.robots_definidos[0].mensajes[0].linea, 7);
+        assert_eq!(
+            mensajes(&mut SemanticAnalyzer::new(), &programa),
+            vec!["Mensaje de 'r1' a 'r2': el valor enviado es de tipo 'booleano', pero el bloque 'mensajes' de 'emisor' es de tipo 'numero'"]
+        );
+
+        let programa = mensajeros("", "    mensajes\n        recibe booleano de r1\n");
+        assert_eq!(mensajes(&mut SemanticAnalyzer::new(), &programa), vec![
+            "Mensaje de 'r1' a 'r2': la variable 'dato' es de tipo 'numero', pero el bloque 'mensajes' de 'receptor' es de tipo 'booleano'",
+        ]);
+
+        let programa = mensajeros("", "    mensajes\n        recibe booleano de r3\n");
+        assert!(mensajes(&mut SemanticAnalyzer::new(), &programa)
+            .contains(&"El bloque 'mensajes' de 'receptor' nombra a 'r3', que no es un robot ni un tipo de robot".to_string()));
+
+        // El formateador conserva el bloque
+        let programa = mensajeros("    mensajes\n        envia numero a receptor\n", "");
+        let codigo = rinfo::parser::printer::a_codigo(&programa);
+        assert!(codigo.contains("    robot emisor\n    mensajes\n        envia numero a receptor\n    comenzar\n"));
+
+        let tokens = Lexer::new("programa p\nrobots\n    robot r\n    mensajes\n        envia numero r2\n    comenzar\n        mover\n    fin\ncomenzar\nfin\n")
+            .tokenize().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        assert_eq!(error.message, "Se esperaba 'envia <tipo> a <robot>' o 'recibe <tipo> de <robot>'");
+        assert_eq!(error.line, 5);
+    }
+
+    // `*` envía a todos o recibe de cualquiera, y no vale en otro lugar
+    #[test]
+    fn test_broadcast_wildcard_is_only_a_message_robot() {
+        let difusion = |jefe: &str| parsear(&format!(
+"programa difusion
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot jefe
+    comenzar
+{jefe}    fin
+    robot trabajador
+    variables
+        calle : numero
+    comenzar
+        RecibirMensaje(calle, *)
+    fin
+variables
+    j: jefe
+    t1: trabajador
+    t2: trabajador
+comenzar
+    AsignarArea(j, ciudad)
+    AsignarArea(t1, ciudad)
+    AsignarArea(t2, ciudad)
+    Iniciar(j, 1, 1)
+    Iniciar(t1, 2, 1)
+    Iniciar(t2, 3, 1)
+fin
+"));
+        assert!(SemanticAnalyzer::new().analizar(&difusion("        EnviarMensaje(5, *)\n")).is_ok());
+
+        assert_eq!(mensajes(&mut SemanticAnalyzer::new(), &difusion("        EnviarMensaje(V, *)\n")), vec![
+            "Mensaje de 'j' a 't1': la variable 'calle' es de tipo 'numero', pero el valor enviado es de tipo 'booleano'",
+        ]);
+        assert_eq!(mensajes(&mut SemanticAnalyzer::new(), &difusion("        Informar(*)\n        Pos(*, 1)\n")), vec![
+            "El argumento 1 de 'Informar' debe ser un valor (en 'jefe')",
+            "El argumento 1 de 'Pos' debe ser de tipo 'numero' (en 'jefe')",
+        ]);
+    }
+
+    const PROGRAMA_ESTILO: &str =
+"programa estilo
+procesos
+    proceso recorrer(E veces: numero)
+    comenzar
+        repetir veces
+            si HayFlorEnLaEsquina
+                mientras HayFlorEnLaEsquina
+                    si HayPapelEnLaEsquina
+                        tomarPapel
+                    tomarFlor
+            mover
+        Pos(10, 20)
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        si HayFlorEnLaEsquina
+        sino
+            tomarFlor
+        recorrer(3)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    // Las reglas de estilo advierten sin impedir compilar; los números en
+    // las coordenadas sólo se informan si se habilita la regla
+    #[test]
+    fn test_style_lints_warn_by_default() {
+        let programa = parsear(PROGRAMA_ESTILO);
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analizar(&programa).is_ok());
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "4 estructuras de control anidadas, más que el máximo de 3 (en 'recorrer', línea 8)",
+            "'si' sin instrucciones cuando la condición es verdadera (en 'r', línea 19)",
+        ]);
+
+        let registro = RegistroLints::predeterminado();
+        assert_eq!(registro.nivel("numeros-magicos"), Some(Nivel::Permitir));
+        assert_eq!(registro.nivel("proceso-largo"), Some(Nivel::Advertir));
+        assert_eq!(registro.nivel("inexistente"), None);
+    }
+
+    // Sólo se advierte la salida de las áreas cuando el recorrido no depende
+    // de nada que se sepa al ejecutar
+    #[test]
+    fn test_constant_paths_that_leave_the_areas() {
+        let source =
+"programa recorridos
+areas
+    chica: AreaP (1,1,5,5)
+    grande: AreaC (1,1,20,20)
+robots
+    robot recolector
+    comenzar
+        repetir 3
+            si HayFlorEnLaEsquina
+                tomarFlor
+            mover
+        derecha
+        repetir 10
+            mover
+    fin
+    robot buscador
+    comenzar
+        mientras HayFlorEnLaEsquina
+            mover
+        repetir 10
+            mover
+    fin
+variables
+    r1, r2: recolector
+    b1: buscador
+comenzar
+    AsignarArea(r1, chica)
+    AsignarArea(r2, grande)
+    AsignarArea(b1, chica)
+    Iniciar(r1, 1, 1)
+    Iniciar(r2, 1, 1)
+    Iniciar(b1, 2, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analizar(&programa).is_ok());
+
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "'r1' sale de sus áreas al moverse a (6, 4) (en 'recolector', línea 14)",
+        ]);
+    }
+
+    // La bolsa de un robot empieza vacía y sólo se advierte lo que pasa en
+    // todos los caminos; después de llamar a un proceso ya no se sabe qué hay
+    #[test]
+    fn test_bag_contents_along_every_path() {
+        let source =
+"programa bolsas
+procesos
+    proceso vaciar
+    comenzar
+        mientras HayFlorEnLaBolsa
+            depositarFlor
+        depositarFlor
+    fin
+areas
+    ciudad: AreaC (1,1,100,100)
+robots
+    robot r
+    comenzar
+        depositarPapel
+        repetir 3
+            tomarFlor
+            mover
+        repetir 2
+            depositarFlor
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+        si PosAv > 1
+            tomarPapel
+        depositarPapel
+        vaciar
+        depositarFlor
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+        let programa = parsear(source);
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analizar(&programa).is_ok());
+
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "'depositarFlor' se ejecuta siempre sin nada para depositar en la bolsa (en 'vaciar', línea 7)",
+            "'depositarPapel' se ejecuta siempre sin nada para depositar en la bolsa (en 'r', línea 14)",
+            "'tomarFlor' dentro de un ciclo sin verificar antes 'HayFlorEnLaEsquina': la esquina puede quedar vacía en alguna vuelta (en 'r', línea 16)",
+        ]);
+    }
+
+    // Cada regla se puede apagar, convertir en error o cambiarle el umbral
+    #[test]
+    fn test_lint_levels_come_from_the_configuration() {
+        let configuracion = ConfiguracionLints::desde_texto(
+"{ configuración más estricta }
+si-vacio = deny
+numeros-magicos = warn
+
+anidamiento-profundo = allow
+proceso-largo = warn 5
+").unwrap();
+        let mut registro = RegistroLints::predeterminado();
+        registro.configurar(&configuracion).unwrap();
+
+        let programa = parsear(PROGRAMA_ESTILO);
+        let mut analyzer = SemanticAnalyzer::new().con_lints(registro);
+        let errores = analyzer.analizar(&programa).unwrap_err();
+        assert_eq!(errores.len(), 1);
+        assert_eq!(errores[0].message, "'si' sin instrucciones cuando la condición es verdadera (en 'r', línea 19)");
+        assert_eq!(errores[0].notas()[0].mensaje, "la regla 'si-vacio' está configurada como 'deny'");
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "La coordenada 10 de 'Pos' está escrita como número; conviene un parámetro o una variable (en 'recorrer', línea 12)",
+            "La coordenada 20 de 'Pos' está escrita como número; conviene un parámetro o una variable (en 'recorrer', línea 12)",
+            "El proceso tiene 8 instrucciones, más que el máximo de 5; conviene dividirlo (en 'recorrer', línea 3)",
+        ]);
+
+        let error = ConfiguracionLints::desde_texto("si-vacio = deny\nproceso-largo = siempre\n").unwrap_err();
+        assert_eq!((error.message.as_str(), error.line), ("Nivel desconocido, se admite 'allow', 'warn' o 'deny': 'proceso-largo = siempre'", 2));
+        let error = ConfiguracionLints::desde_texto("si-vacio deny\n").unwrap_err();
+        assert_eq!(error.message, "Se esperaba '<regla> = allow|warn|deny [<límite>]': 'si-vacio deny'");
+
+        let desconocida = ConfiguracionLints::desde_texto("\nsi-vacia = deny\n").unwrap();
+        let error = RegistroLints::predeterminado().configurar(&desconocida).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.message,
+            "Regla desconocida: 'si-vacia' (las reglas son valores-constantes, sin-inicializar, sale-del-area, bolsa-vacia, tomar-sin-verificar, anidamiento-profundo, numeros-magicos, si-vacio, segun-sin-sino, proceso-largo)"
+        );
+    }
+
+    // Reordenar secciones, renombrar el programa o cambiar líneas y
+    // comentarios no es una diferencia; cambiar un cuerpo sí, aunque tenga
+    // la misma cantidad de instrucciones
+    #[test]
+    fn test_structural_diff_between_two_versions() {
+        let viejo = parsear("programa entrega
+procesos
+    proceso juntar
+    comenzar
+        si HayFlorEnLaEsquina
+            tomarFlor
+    fin
+    proceso sobrante
+    comenzar
+        derecha
+    fin
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    comenzar
+        repetir 3
+            juntar
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let reordenado = parsear("programa reentrega
+procesos
+    proceso sobrante
+    comenzar
+        derecha
+    fin
+    { con comentarios }
+    proceso juntar
+    comenzar
+        si HayFlorEnLaEsquina
+
+            tomarFlor
+    fin
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    comenzar
+        repetir 3
+            juntar
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        assert!(Diferencias::new(&viejo, &reordenado).is_empty());
+
+        let nuevo = parsear("programa reentrega
+procesos
+    proceso juntar(E veces: numero)
+    comenzar
+        mientras HayFlorEnLaEsquina
+            tomarFlor
+    fin
+    proceso girarDos
+    comenzar
+        derecha
+        derecha
+    fin
+areas
+    ciudad: AreaC (1,1,20,20)
+robots
+    robot r
+    comenzar
+        repetir 3
+            juntar(2)
+            mover
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 2, 1)
+fin
+");
+        let diferencias = Diferencias::new(&viejo, &nuevo);
+        let diferencia = |nombre: &str, clase, cambio, instrucciones| Diferencia { nombre: nombre.to_string(), clase, cambio, instrucciones };
+        assert_eq!(diferencias.declaraciones, [
+            diferencia("ciudad", Clase::Area, Cambio::Modificada(vec![Aspecto::Coordenadas]), None),
+            diferencia("juntar", Clase::Proceso, Cambio::Modificada(vec![Aspecto::Parametros, Aspecto::Instrucciones]), Some((2, 2))),
+            diferencia("sobrante", Clase::Proceso, Cambio::Eliminada, Some((1, 0))),
+            diferencia("girarDos", Clase::Proceso, Cambio::Agregada, Some((0, 2))),
+            diferencia("r", Clase::TipoRobot, Cambio::Modificada(vec![Aspecto::Instrucciones]), Some((3, 3))),
+        ]);
+        assert!(diferencias.principal);
+        assert_eq!(diferencias.a_texto().lines().take(4).collect::<Vec<_>>(), [
+            "~ área ciudad: coordenadas",
+            "~ proceso juntar: parámetros, instrucciones",
+            "- proceso sobrante (1 instrucción)",
+            "+ proceso girarDos (2 instrucciones)",
+        ]);
+    }
+
+    // Una instrucción que no se pudo parsear queda como `Instruccion::Error`
+    // y el resto del robot se sigue analizando, sin errores ni advertencias
+    // que dependan de lo que hacía
+    #[test]
+    fn test_unparsed_statements_do_not_cascade() {
+        let robot = |primera: &str| format!(
+"programa parcial
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n: numero
+    comenzar
+        {primera}
+        depositarFlor
+        Informar(n)
+        total := n
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+");
+        let completo = parsear(&robot("mover"));
+        let mut analyzer = SemanticAnalyzer::new();
+        assert_eq!(mensajes(&mut analyzer, &completo), ["Variable 'total' no declarada en 'r'"]);
+        assert_eq!(analyzer.obtener_advertencias(), [
+            "Variable 'n' usada sin inicializar (en 'r', línea 11)",
+            "'depositarFlor' se ejecuta siempre sin nada para depositar en la bolsa (en 'r', línea 10)",
+        ]);
+
+        let source = robot("n := := 3");
+        let tokens = Lexer::new(&source).tokenize().unwrap();
+        let (parcial, errores) = Parser::new(&tokens).parse_parcial();
+        let parcial = parcial.expect("El resto del programa se parsea");
+        assert_eq!(errores.len(), 1);
+        assert_eq!(errores[0].line, 9);
+        let instrucciones = &parcial.robots_definidos[0].instrucciones;
+        assert!(matches!(parcial.arena[instrucciones[0]], Instruccion::Error { linea: 9 }));
+        assert_eq!(instrucciones.len(), 4);
+
+        let mut analyzer = SemanticAnalyzer::new();
+        assert_eq!(mensajes(&mut analyzer, &parcial), ["Variable 'total' no declarada en 'r'"]);
+        assert!(analyzer.obtener_advertencias().is_empty(), "{:?}", analyzer.obtener_advertencias());
+    }
+
+    // El nombre del programa no puede ser el de un robot, un área o
+    // cualquier otra declaración global; sí el de una variable local
+    #[test]
+    fn test_program_name_does_not_clash_with_declarations() {
+        let programa = |nombre: &str| parsear(&format!(
+"programa {nombre}
+areas
+    ciudad: AreaC (1,1,10,10)
+robots
+    robot r
+    variables
+        n: numero
+    comenzar
+        n := 1
+        Informar(n)
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+"));
+        let mut analyzer = SemanticAnalyzer::new();
+        assert_eq!(mensajes(&mut analyzer, &programa("r1")), ["Nombre 'r1' declarado como nombre del programa (línea 1) y como robot (línea 13)"]);
+        assert_eq!(mensajes(&mut analyzer, &programa("ciudad")), ["Nombre 'ciudad' declarado como nombre del programa (línea 1) y como área (línea 3)"]);
+        assert!(analyzer.analizar(&programa("n")).is_ok());
+    }
+}
@@ -0,0 +1 @@
+pub mod testgenTest;
\ No newline at end of file
@@ -0,0 +1,60 @@
+use rinfo::driver::compilar;
+use rinfo::interpreter::simulation::{EstadoSimulacion, Simulation};
+use rinfo::testgen::{generar, Configuracion};
+
+#[cfg(test)]
+mod testing_testgen {
+    use super::*;
+
+    fn configuraciones() -> Vec<Configuracion> {
+        let mut configuraciones = Vec::new();
+        for semilla in 0..20 {
+            configuraciones.push(Configuracion {
+                procesos: (semilla % 5) as usize,
+                robots: 1 + (semilla % 3) as usize,
+                profundidad: (semilla % 4) as usize,
+                instrucciones: 3,
+                semilla,
+            });
+        }
+        configuraciones
+    }
+
+    #[test]
+    fn test_generated_programs_compile_with_the_requested_shape() {
+        for configuracion in configuraciones() {
+            let source = generar(&configuracion);
+            let compilacion = compilar(&source);
+            assert!(compilacion.exitosa(), "{:?}\n{}", compilacion.errores, source);
+
+            let programa = compilacion.programa.unwrap();
+            assert_eq!(programa.procesos.len(), configuracion.procesos);
+            assert_eq!(programa.robots_instanciados.len(), configuracion.robots);
+
+            // Procesos y robots abren dos niveles antes de su cuerpo
+            let niveles = source.lines()
+                .map(|linea| (linea.len() - linea.trim_start().len()) / 4)
+                .max()
+                .unwrap();
+            assert!(niveles <= 3 + configuracion.profundidad, "{} niveles\n{}", niveles, source);
+        }
+    }
+
+    #[test]
+    fn test_generated_programs_run_to_completion() {
+        for configuracion in configuraciones() {
+            let source = generar(&configuracion);
+            let programa = compilar(&source).programa.unwrap();
+            let mut simulacion = Simulation::new(&programa).unwrap();
+            let estado = simulacion.ejecutar(1_000_000);
+            assert!(matches!(estado, Ok(EstadoSimulacion::Terminada)), "{:?}\n{}", estado, source);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_generates_the_same_program() {
+        let configuracion = Configuracion { procesos: 6, robots: 4, ..Configuracion::default() };
+        assert_eq!(generar(&configuracion), generar(&configuracion));
+        assert_ne!(generar(&configuracion), generar(&Configuracion { semilla: 2, ..configuracion }));
+    }
+}
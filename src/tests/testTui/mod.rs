@@ -0,0 +1 @@
+pub mod tuiTest;
\ No newline at end of file
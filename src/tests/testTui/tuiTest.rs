@@ -0,0 +1,67 @@
+#[cfg(feature = "tui")]
+use rinfo::interpreter::world::{Ciudad, Direccion, EstadoRobot, Mundo};
+#[cfg(feature = "tui")]
+use rinfo::tui::{renderizar_grilla, renderizar_robots, Vista};
+
+#[cfg(all(test, feature = "tui"))]
+mod testing_tui {
+    use super::*;
+
+    fn mundo() -> Mundo {
+        let mut ciudad = Ciudad::new();
+        ciudad.poner_flores(1, 1, 2);
+        ciudad.poner_papeles(3, 2, 1);
+        ciudad.poner_flores(3, 3, 1);
+        ciudad.poner_papeles(3, 3, 1);
+
+        let mut mundo = Mundo::new(ciudad);
+        let mut robot = EstadoRobot::new("r1", "juntador");
+        robot.av = 2;
+        robot.ca = 3;
+        robot.direccion = Direccion::Este;
+        robot.iniciado = true;
+        mundo.robots.push(robot);
+        mundo.robots.push(EstadoRobot::new("r2", "juntador"));
+        mundo
+    }
+
+    #[test]
+    fn test_grid_draws_robots_and_corners_from_north_to_south() {
+        let grilla = renderizar_grilla(&mundo(), &Vista::default(), 4, 3);
+
+        assert_eq!(grilla, vec![
+            "· → # ·".to_string(),
+            "· · + ·".to_string(),
+            "* · · ·".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_zoom_groups_corners_and_view_scrolls() {
+        let mut vista = Vista::default();
+        vista.alejar();
+        assert_eq!(vista.zoom, 2);
+        assert_eq!(renderizar_grilla(&mundo(), &vista, 2, 2), vec!["→ #".to_string(), "* +".to_string()]);
+
+        vista.desplazar(1, 0);
+        assert_eq!(vista.av, 3);
+        vista.desplazar(-5, -5);
+        assert_eq!((vista.av, vista.ca), (1, 1));
+
+        // El borde de la ciudad corta la grilla
+        vista.acercar();
+        vista.desplazar(98, 98);
+        assert_eq!(renderizar_grilla(&mundo(), &vista, 5, 5).len(), 2);
+    }
+
+    #[test]
+    fn test_side_panel_lists_bags_and_corner_counts() {
+        let mut mundo = mundo();
+        mundo.robots[0].flores = 4;
+
+        assert_eq!(renderizar_robots(&mundo), vec![
+            "→ r1 (2, 3) bolsa: 4f 0p | esquina: 0f 0p".to_string(),
+            "r2: sin iniciar".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(feature = "wasm")]
+use std::path::Path;
+#[cfg(feature = "wasm")]
+use rinfo::driver::{compilar_archivo_con, NOMBRE_ENTRADA};
+#[cfg(feature = "wasm")]
+use rinfo::mensajes::Codigo;
+#[cfg(feature = "wasm")]
+use rinfo::wasm::{CompileOptions, SimulationOptions};
+
+// Las funciones exportadas reciben y devuelven `JsValue`, que sólo existe en
+// WebAssembly; acá se prueban las opciones que traducen
+#[cfg(all(test, feature = "wasm"))]
+mod testing_wasm {
+    use super::*;
+
+    // Áreas antes que procesos: sólo compila con secciones flexibles
+    const DESORDENADO: &str = "programa desordenado
+areas
+    ciudad: AreaC(1, 1, 10, 10)
+procesos
+    proceso avanzar
+    comenzar
+        mover
+    fin
+robots
+    robot r
+    comenzar
+        avanzar
+    fin
+variables
+    r1: r
+comenzar
+    AsignarArea(r1, ciudad)
+    Iniciar(r1, 1, 1)
+fin
+";
+
+    #[test]
+    fn test_compile_options_default_to_the_compiler_defaults() {
+        let opciones = CompileOptions::default().opciones().unwrap();
+        assert!(!opciones.secciones_flexibles && !opciones.extensiones);
+        assert!(opciones.lints.ajustes.is_empty());
+        assert!(!compilar_archivo_con(DESORDENADO, Path::new(NOMBRE_ENTRADA), opciones).exitosa());
+    }
+
+    #[test]
+    fn test_compile_options_reach_the_compilation() {
+        let opciones = CompileOptions {
+            flexible_sections: Some(true),
+            extensions: Some(true),
+            lint_config: Some("numeros-magicos = warn\n".to_string()),
+        }
+        .opciones()
+        .unwrap();
+        assert!(opciones.secciones_flexibles && opciones.extensiones);
+        assert_eq!(opciones.lints.ajustes.len(), 1);
+        assert!(compilar_archivo_con(DESORDENADO, Path::new(NOMBRE_ENTRADA), opciones).exitosa());
+
+        let error = CompileOptions { lint_config: Some("numeros-magicos\n".to_string()), ..CompileOptions::default() }
+            .opciones()
+            .unwrap_err();
+        assert_eq!(error.codigo(), Some(Codigo::FormatoDeConfiguracion));
+    }
+
+    #[test]
+    fn test_simulation_options_read_the_world() {
+        let vacia = SimulationOptions::default().ciudad().unwrap();
+        assert_eq!(vacia.esquina(2, 3).unwrap().flores, 0);
+
+        let ciudad = SimulationOptions { world: Some("flores 2 3 4\n".to_string()), ..SimulationOptions::default() }
+            .ciudad()
+            .unwrap();
+        assert_eq!(ciudad.esquina(2, 3).unwrap().flores, 4);
+
+        let invalido = SimulationOptions { world: Some("flores 0 1 1\n".to_string()), ..SimulationOptions::default() };
+        assert!(invalido.ciudad().is_err());
+    }
+}
@@ -0,0 +1 @@
+pub mod watchTest;
\ No newline at end of file
@@ -0,0 +1,48 @@
+use rinfo::interpreter::world::Ciudad;
+use rinfo::watch::Vigilante;
+use std::env;
+use std::fs;
+
+#[cfg(test)]
+mod testing_watch {
+    use super::*;
+
+    #[test]
+    fn test_watcher_reports_each_change_once() {
+        let ruta = env::temp_dir().join(format!("rinfo-watch-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&ruta);
+        let mut vigilante = Vigilante::new([&ruta]);
+
+        // Un archivo que todavía no existe no es un cambio
+        assert!(!vigilante.hubo_cambios());
+
+        fs::write(&ruta, "programa a\n").unwrap();
+        assert!(vigilante.hubo_cambios());
+        assert!(!vigilante.hubo_cambios());
+
+        fs::write(&ruta, "programa ab\n").unwrap();
+        assert!(vigilante.hubo_cambios());
+
+        fs::remove_file(&ruta).unwrap();
+        assert!(vigilante.hubo_cambios());
+        assert!(!vigilante.hubo_cambios());
+    }
+
+    #[test]
+    fn test_world_file_places_objects() {
+        let ciudad = Ciudad::desde_texto(
+"{ Mundo de prueba }
+flores 3 4 2
+
+papeles 1 1 5
+flores 3 4 1
+").unwrap();
+        assert_eq!(ciudad.esquina(3, 4).unwrap().flores, 3);
+        assert_eq!(ciudad.esquina(1, 1).unwrap().papeles, 5);
+
+        let error = Ciudad::desde_texto("flores 1 1 1\nflores 0 1 1\n").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(Ciudad::desde_texto("piedras 1 1 1").is_err());
+        assert!(Ciudad::desde_texto("flores 1 1").is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// Pruebas de la línea de comandos: se ejecuta el binario sobre programas
+// escritos en un directorio temporal y se revisa lo que muestra y su
+// código de salida.
+
+// Robot que termina en (5, 5) con un número escrito en el código, que la
+// regla `numeros-magicos` advierte si se la configura
+const MAGICO: &str = "programa magico
+areas
+  ciudad: AreaC(1, 1, 100, 100)
+robots
+  robot r
+  comenzar
+    Pos(5, 5)
+  fin
+variables
+  r1: r
+comenzar
+  AsignarArea(r1, ciudad)
+  Iniciar(r1, 1, 1)
+fin
+";
+
+// Escribe los archivos en un directorio propio de la prueba y devuelve
+// sus rutas, en el mismo orden
+fn archivos(prueba: &str, contenidos: &[(&str, &str)]) -> Vec<String> {
+    let directorio = env::temp_dir().join(format!("rinfo-cli-{}-{}", prueba, std::process::id()));
+    fs::create_dir_all(&directorio).unwrap();
+    contenidos.iter()
+        .map(|(nombre, contenido)| {
+            let ruta: PathBuf = directorio.join(nombre);
+            fs::write(&ruta, contenido).unwrap();
+            ruta.display().to_string()
+        })
+        .collect()
+}
+
+fn app(argumentos: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_app")).args(argumentos).output().expect("No se pudo ejecutar el binario")
+}
+
+fn texto(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+// Las advertencias se muestran en la salida de errores, en el idioma
+// elegido, aunque el programa compile
+#[test]
+fn test_warnings_are_printed_in_human_output() {
+    let source = "programa avisos
+areas
+  ciudad: AreaC(1, 1, 100, 100)
+robots
+  robot r
+  variables
+    x: numero
+    n: numero
+  comenzar
+    si PosAv > 5
+      x := 1
+      n := 10 / 0
+      Pos(150, 1)
+    Informar(x)
+  fin
+variables
+  r1: r
+comenzar
+  AsignarArea(r1, ciudad)
+  Iniciar(r1, 1, 1)
+fin
+";
+    let rutas = archivos("advertencias", &[("avisos.ri", source)]);
+
+    let salida = app(&["ejecutar", &rutas[0]]);
+    assert!(salida.status.success(), "{}", texto(&salida.stderr));
+    let errores = texto(&salida.stderr);
+    assert!(errores.contains("advertencia: División por cero (en 'r', línea 12)\n"), "{}", errores);
+    assert!(errores.contains("advertencia: La coordenada 150 de 'Pos' está fuera de la ciudad (1 a 100) (en 'r', línea 13)\n"), "{}", errores);
+    assert!(errores.contains("advertencia: Variable 'x' posiblemente sin inicializar (en 'r', línea 14)\n"), "{}", errores);
+    assert!(texto(&salida.stdout).contains("Simulación: Terminada"));
+
+    let salida = app(&["--lang", "en", "ejecutar", &rutas[0]]);
+    assert!(texto(&salida.stderr).contains("warning: Division by zero (in 'r', line 12)\n"));
+}
+
+// `--lang` traduce los diagnósticos de la compilación, pero los errores de
+// ejecución salen en español, como indica el README
+#[test]
+fn test_lang_only_translates_compile_time_diagnostics() {
+    let source = "programa choque
+areas
+  ciudad: AreaC(1, 1, 100, 100)
+robots
+  robot r
+  comenzar
+    repetir 120
+      mover
+  fin
+variables
+  r1: r
+comenzar
+  AsignarArea(r1, ciudad)
+  Iniciar(r1, 1, 1)
+fin
+";
+    let rutas = archivos("idioma", &[("choque.ri", source)]);
+
+    let salida = app(&["--lang", "en", "ejecutar", &rutas[0]]);
+    assert!(!salida.status.success());
+    let errores = texto(&salida.stderr);
+    assert!(errores.contains("warning: 'r1' leaves its areas when moving to (1, 101) (in 'r', line 8)\n"), "{}", errores);
+    let resultado = texto(&salida.stdout);
+    assert!(resultado.contains("Error de ejecución: No puede moverse a (1, 101): fuera de la ciudad"), "{}", resultado);
+}
+
+// Una regla en `warn` se muestra y la ejecución sigue; en `deny` es un error
+// y el programa no se ejecuta
+#[test]
+fn test_warn_level_lints_are_shown_without_stopping_the_run() {
+    let rutas = archivos("niveles", &[
+        ("magico.ri", MAGICO),
+        ("warn.txt", "numeros-magicos = warn\n"),
+        ("deny.txt", "numeros-magicos = deny\n"),
+    ]);
+    let advertencia = "La coordenada 5 de 'Pos' está escrita como número; conviene un parámetro o una variable (en 'r', línea 7)";
+
+    let sin_regla = app(&["ejecutar", &rutas[0]]);
+    assert!(sin_regla.status.success());
+    assert!(!texto(&sin_regla.stderr).contains(advertencia));
+
+    let advertida = app(&["--lint-config", &rutas[1], "ejecutar", &rutas[0]]);
+    assert!(advertida.status.success());
+    assert!(texto(&advertida.stderr).contains(&format!("advertencia: {}\n", advertencia)));
+    assert!(texto(&advertida.stdout).contains("r1 (r) en (5, 5)"));
+
+    let rechazada = app(&["--lint-config", &rutas[2], "ejecutar", &rutas[0]]);
+    assert!(!rechazada.status.success());
+    assert!(texto(&rechazada.stderr).contains(&format!(":7: {}\n", advertencia)));
+    assert!(!texto(&rechazada.stdout).contains("Simulación"));
+}
+
+// Los errores en los argumentos terminan con código 2, sin ejecutar nada
+#[test]
+fn test_usage_errors_exit_with_code_2() {
+    let rutas = archivos("uso", &[("magico.ri", MAGICO)]);
+
+    for argumentos in [
+        vec!["ejecutar", &rutas[0], "--bogus"],
+        vec!["ejecutar", &rutas[0], "--inline"],
+        vec!["ejecutar", &rutas[0], "--emit", "svg"],
+        vec!["--lang", "xx", "ejecutar", &rutas[0]],
+        vec!["ejecutar"],
+        vec!["--bogus"],
+        vec!["compilar", &rutas[0]],
+        vec!["--simular", "--watch"],
+        vec![&rutas[0], "--watch", "--bogus"],
+        vec!["--watch", &rutas[0], &rutas[0]],
+    ] {
+        let salida = app(&argumentos);
+        assert_eq!(salida.status.code(), Some(2), "{:?}: {}", argumentos, texto(&salida.stderr));
+        assert!(!texto(&salida.stdout).contains("Simulación"));
+        // Sin un comando conocido no se compila el programa de prueba
+        assert!(!texto(&salida.stdout).contains("compila:"));
+    }
+
+    let salida = app(&["compilar", &rutas[0]]);
+    let errores = texto(&salida.stderr);
+    assert!(errores.starts_with("Comando u opción desconocida: 'compilar'\nComandos: ejecutar,"), "{}", errores);
+    assert!(errores.contains("--lang es|en (sólo para los diagnósticos de la compilación)"), "{}", errores);
+}
+
+// `--timings` mide las fases del archivo que se indique antes o después
+#[test]
+fn test_timings_flag_works_in_any_position() {
+    let rutas = archivos("tiempos", &[("magico.ri", MAGICO)]);
+
+    for argumentos in [vec!["--timings", &rutas[0]], vec![&rutas[0], "--timings"]] {
+        let salida = app(&argumentos);
+        assert!(salida.status.success(), "{:?}: {}", argumentos, texto(&salida.stderr));
+        let tiempos = texto(&salida.stdout);
+        assert!(tiempos.starts_with("14 líneas, ") && tiempos.contains("  total: "), "{:?}: {}", argumentos, tiempos);
+    }
+
+    let salida = app(&["ejecutar", &rutas[0], "--timings"]);
+    assert_eq!(salida.status.code(), Some(2), "{}", texto(&salida.stderr));
+}
+
+// Los formatos de `--emit` que no ejecutan el programa no dejan pedir un
+// reporte o una imagen que no se escribirían
+#[test]
+fn test_emit_without_running_rejects_report_and_render() {
+    let rutas = archivos("emitir", &[("magico.ri", MAGICO), ("reporte.json", "")]);
+    fs::remove_file(&rutas[1]).unwrap();
+
+    for formato in ["symbols", "metrics:json", "html"] {
+        let salida = app(&["ejecutar", &rutas[0], "--emit", formato, "--report", &rutas[1]]);
+        assert_eq!(salida.status.code(), Some(2));
+        assert!(texto(&salida.stderr).contains(&format!("--emit {} no ejecuta el programa y no se puede combinar con --report", formato)));
+        assert!(salida.stdout.is_empty());
+        assert!(!PathBuf::from(&rutas[1]).exists());
+    }
+    let salida = app(&["ejecutar", &rutas[0], "--render", "ciudad.svg", "--emit", "symbols"]);
+    assert!(texto(&salida.stderr).contains("no se puede combinar con --render"));
+
+    // Con el diagrama de mensajes sí se ejecuta y se escribe el reporte
+    let salida = app(&["ejecutar", &rutas[0], "--emit", "mermaid", "--report", &rutas[1]]);
+    assert!(salida.status.success(), "{}", texto(&salida.stderr));
+    assert!(PathBuf::from(&rutas[1]).exists());
+}
+
+// Lo que informa cada robot se muestra al terminar y queda en el reporte
+#[test]
+fn test_reports_are_printed_and_written_to_the_report() {
+    let source = "programa informes
+areas
+  ciudad: AreaC(1, 1, 100, 100)
+robots
+  robot r
+  comenzar
+    Informar(PosAv)
+    mover
+    Informar(PosCa)
+  fin
+variables
+  r1: r
+comenzar
+  AsignarArea(r1, ciudad)
+  Iniciar(r1, 3, 1)
+fin
+";
+    let rutas = archivos("informes", &[("informes.ri", source), ("reporte.json", "")]);
+
+    let salida = app(&["ejecutar", &rutas[0], "--report", &rutas[1]]);
+    assert!(salida.status.success(), "{}", texto(&salida.stderr));
+    assert!(texto(&salida.stdout).contains("  r1 (r) en (3, 2), flores: 0, papeles: 0\n    informó 3 / 2\n"), "{}", texto(&salida.stdout));
+    let reporte = fs::read_to_string(&rutas[1]).unwrap();
+    assert!(reporte.contains("\"informes\": [\"3\", \"2\"]"), "{}", reporte);
+}
@@ -0,0 +1,46 @@
+// Ayudas compartidas por las pruebas de integración. Cada archivo de
+// `tests` es un crate aparte que usa sólo algunas, así que las demás no
+// cuentan como código muerto.
+#![allow(dead_code)]
+
+use rinfo::lexer::scanner::Lexer;
+use rinfo::lexer::token::Token;
+use rinfo::parser::processor::{Parser, Program};
+use rinfo::semanticizer::analizer::{AnalysisSummary, SemanticAnalyzer};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Directorio de los archivos de prueba, relativo a la raíz del paquete,
+// que es donde cargo corre las pruebas
+pub const FIXTURES: &str = "tests/fixtures";
+
+// Programa de ejemplo con un proceso, un robot y todas las secciones
+pub const CODIGO: &str = include_str!("../fixtures/codigo.txt");
+
+pub fn fixture(ruta: &str) -> PathBuf {
+    Path::new(FIXTURES).join(ruta)
+}
+
+// Los `.ri` de un directorio de `fixtures`, ordenados
+pub fn programas(directorio: &str) -> Vec<PathBuf> {
+    let mut programas: Vec<PathBuf> = fs::read_dir(fixture(directorio))
+        .unwrap_or_else(|error| panic!("No se pudo leer {}: {}", directorio, error))
+        .map(|entrada| entrada.unwrap().path())
+        .filter(|ruta| ruta.extension().is_some_and(|extension| extension == "ri"))
+        .collect();
+    programas.sort();
+    programas
+}
+
+pub fn lex_str(source: &str) -> Vec<Token<'_>> {
+    Lexer::new(source).tokenize().unwrap_or_else(|error| panic!("No se pudo tokenizar: {}", error))
+}
+
+pub fn parse_str(source: &str) -> Program {
+    Parser::new(&lex_str(source)).parse().unwrap_or_else(|error| panic!("No se pudo parsear: {}", error))
+}
+
+// Análisis semántico completo, con errores y advertencias
+pub fn analyze_str(source: &str) -> AnalysisSummary {
+    SemanticAnalyzer::new().examinar(&parse_str(source))
+}
@@ -0,0 +1,88 @@
+use rinfo::driver::compilar_archivo;
+use rinfo::mensajes::Codigo;
+use std::fs;
+use std::path::Path;
+
+mod common;
+
+// Corpus de conformidad: cada programa `.ri` de `fixtures/corpus` prueba una
+// construcción del lenguaje y anota en comentarios lo que se espera al
+// compilarlo:
+//
+//   { espera-error S005 }             error S005 en la línea del comentario
+//   { espera-error P014 linea 12 }    error P014 en la línea 12; la 0 es la
+//                                     de los errores del programa principal
+//   { espera-advertencia A004 }       advertencia A004, en cualquier línea
+//
+// Un programa sin anotaciones debe compilar sin errores ni advertencias, y
+// uno con anotaciones debe dar exactamente los diagnósticos anotados. Así el
+// corpus es la lista de lo que el compilador acepta y de lo que rechaza; los
+// nombres de los archivos empiezan con `acepta_`, `rechaza_` o `advierte_`.
+
+// Lo que anota un programa del corpus: (código, línea) de cada error y
+// código de cada advertencia
+#[derive(Debug, Default, PartialEq)]
+struct Esperado {
+    errores: Vec<(String, usize)>,
+    advertencias: Vec<String>,
+}
+
+fn anotaciones(source: &str) -> Result<Esperado, String> {
+    let mut esperado = Esperado::default();
+    for (i, linea) in source.lines().enumerate() {
+        for (inicio, _) in linea.match_indices("{ espera-") {
+            let anotacion = linea[inicio + 1..].split('}').next().unwrap_or_default();
+            let partes: Vec<&str> = anotacion.split_whitespace().collect();
+            match partes[..] {
+                ["espera-error", codigo] => esperado.errores.push((codigo.to_string(), i + 1)),
+                ["espera-error", codigo, "linea", numero] => {
+                    let numero = numero.parse().map_err(|_| format!("línea {}: número inválido '{}'", i + 1, numero))?;
+                    esperado.errores.push((codigo.to_string(), numero));
+                }
+                ["espera-advertencia", codigo] => esperado.advertencias.push(codigo.to_string()),
+                _ => return Err(format!("línea {}: anotación inválida '{}'", i + 1, anotacion.trim())),
+            }
+        }
+    }
+    esperado.errores.sort();
+    esperado.advertencias.sort();
+    Ok(esperado)
+}
+
+// Los diagnósticos de compilar el programa, como los anotaría el corpus.
+// Los que no tienen código se anotan con '?' para que el fallo los muestre.
+fn obtenido(ruta: &Path, source: &str) -> Esperado {
+    let compilacion = compilar_archivo(source, ruta);
+    let codigo = |codigo: Option<Codigo>| codigo.map_or("?", |codigo| codigo.codigo()).to_string();
+    let mut obtenido = Esperado {
+        errores: compilacion.errores.iter().map(|error| (codigo(error.codigo()), error.line)).collect(),
+        advertencias: compilacion.advertencias.iter().map(|advertencia| codigo(advertencia.codigo())).collect(),
+    };
+    obtenido.errores.sort();
+    obtenido.advertencias.sort();
+    obtenido
+}
+
+#[test]
+fn test_corpus_programs_match_their_annotations() {
+    let programas = common::programas("corpus");
+    assert!(!programas.is_empty(), "No hay programas en el corpus");
+
+    let mut fallidos = Vec::new();
+    for ruta in programas {
+        let source = fs::read_to_string(&ruta).expect("No se pudo leer el programa");
+        let esperado = match anotaciones(&source) {
+            Ok(esperado) => esperado,
+            Err(error) => {
+                fallidos.push(format!("{}: {}", ruta.display(), error));
+                continue;
+            }
+        };
+        let obtenido = obtenido(&ruta, &source);
+        if obtenido != esperado {
+            fallidos.push(format!("{}:\n  esperado {:?}\n  obtenido {:?}", ruta.display(), esperado, obtenido));
+        }
+    }
+
+    assert!(fallidos.is_empty(), "Programas del corpus que no coinciden con sus anotaciones:\n{}", fallidos.join("\n"));
+}
@@ -0,0 +1,49 @@
+use rinfo::lexer::token::TokenType;
+use rinfo::parser::cst::Cst;
+use std::fs;
+
+mod common;
+
+use common::{analyze_str, lex_str, parse_str, CODIGO};
+
+// Pruebas sobre los archivos de `fixtures`: el programa de ejemplo y los
+// programas del corpus y de los casos dorados
+
+#[test]
+fn test_basic_keywords() {
+    let keywords = [
+        "proceso", "robot", "variables", "comenzar",
+        "fin", "programa", "procesos", "areas", "robots"
+    ];
+    for token in lex_str(CODIGO).iter().filter(|token| token.token_type == TokenType::Keyword) {
+        assert!(keywords.contains(&token.value), "Unexpected keyword: {}", token.value);
+    }
+}
+
+#[test]
+fn test_control_sentences() {
+    let control_sentences = ["si", "sino", "mientras", "repetir"];
+    for token in lex_str(CODIGO).iter().filter(|token| token.token_type == TokenType::ControlSentence) {
+        assert!(control_sentences.contains(&token.value), "Unexpected control sentence: {}", token.value);
+    }
+}
+
+// El ejemplo es el programa que compila el binario sin argumentos
+#[test]
+fn test_sample_program_compiles() {
+    let programa = parse_str(CODIGO);
+    assert_eq!((programa.procesos.len(), programa.robots_definidos.len()), (1, 1));
+    let resumen = analyze_str(CODIGO);
+    assert!(resumen.exitoso(), "{:?}", resumen.errores);
+}
+
+#[test]
+fn test_cst_is_lossless_on_every_fixture() {
+    let mut programas = common::programas("corpus");
+    programas.extend(common::programas("golden"));
+    programas.push(common::fixture("codigo.txt"));
+    for ruta in programas {
+        let source = fs::read_to_string(&ruta).unwrap();
+        assert_eq!(Cst::new(&source).texto(), source, "{}", ruta.display());
+    }
+}
@@ -0,0 +1,126 @@
+use rinfo::driver::{compilar_archivo, Compilacion};
+use rinfo::interpreter::simulation::Simulation;
+use rinfo::interpreter::world::{Mundo, TAMANIO_CIUDAD};
+use rinfo::parser::printer::a_codigo;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+mod common;
+
+// Pruebas de archivos dorados: cada programa `.ri` de `fixtures/golden` se
+// compila (y se ejecuta, si compila) y el resultado de cada fase se compara
+// con el `.expected` que está a su lado. Para regenerarlos después de un
+// cambio buscado:
+//
+//   RINFO_ACTUALIZAR_ESPERADOS=1 cargo test --test golden
+//
+// y revisar el diff de los `.expected` antes de confirmarlo.
+
+// Pasos de la simulación antes de darla por colgada
+const MAX_PASOS: u64 = 100_000;
+
+// Resultado de cada fase en texto: tokens, AST (como código), diagnósticos
+// y, si el programa compila, el mundo al terminar
+fn instantanea(ruta: &Path) -> String {
+    let source = fs::read_to_string(ruta).expect("No se pudo leer el caso");
+    let compilacion = compilar_archivo(&source, ruta);
+
+    let mut salida = String::from("== tokens ==\n");
+    for token in &compilacion.tokens {
+        let _ = writeln!(salida, "{}:{} {} {:?}", token.line, token.column, token.token_type, token.value);
+    }
+
+    salida.push_str("\n== ast ==\n");
+    if let Some(programa) = &compilacion.programa {
+        salida.push_str(&a_codigo(programa));
+    }
+
+    salida.push_str("\n== diagnósticos ==\n");
+    for error in &compilacion.errores {
+        let _ = writeln!(salida, "error: {}", compilacion.fuentes.mostrar(error));
+        for detalle in compilacion.fuentes.detalles(error) {
+            let _ = writeln!(salida, "  {}", detalle);
+        }
+    }
+    for advertencia in &compilacion.advertencias {
+        let _ = writeln!(salida, "advertencia: {}", advertencia);
+    }
+
+    if compilacion.exitosa() {
+        salida.push_str("\n== mundo ==\n");
+        ejecucion(&compilacion, &mut salida);
+    }
+    salida
+}
+
+fn ejecucion(compilacion: &Compilacion, salida: &mut String) {
+    let programa = compilacion.programa.as_ref().unwrap();
+    let mut simulacion = match Simulation::new(programa) {
+        Ok(simulacion) => simulacion,
+        Err(error) => {
+            let _ = writeln!(salida, "error: {}", error);
+            return;
+        }
+    };
+    match simulacion.ejecutar(MAX_PASOS) {
+        Ok(estado) => {
+            let _ = writeln!(salida, "{:?} en {} pasos", estado, simulacion.pasos());
+        }
+        Err(error) => {
+            let _ = writeln!(salida, "error: {} (paso {})", error, simulacion.pasos());
+        }
+    }
+    mundo(simulacion.mundo(), salida);
+}
+
+fn mundo(mundo: &Mundo, salida: &mut String) {
+    for robot in &mundo.robots {
+        let _ = writeln!(
+            salida,
+            "robot {} ({}) en ({}, {}) mirando al {:?}, flores {}, papeles {}",
+            robot.nombre, robot.tipo, robot.av, robot.ca, robot.direccion, robot.flores, robot.papeles
+        );
+        for informe in &robot.informes {
+            let _ = writeln!(salida, "    informa {}", informe);
+        }
+    }
+    for av in 1..=TAMANIO_CIUDAD {
+        for ca in 1..=TAMANIO_CIUDAD {
+            let esquina = mundo.ciudad.esquina(av, ca).unwrap();
+            if esquina.flores > 0 || esquina.papeles > 0 {
+                let _ = writeln!(salida, "esquina ({}, {}): flores {}, papeles {}", av, ca, esquina.flores, esquina.papeles);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_golden_files() {
+    let actualizar = env::var_os("RINFO_ACTUALIZAR_ESPERADOS").is_some();
+    let casos = common::programas("golden");
+    assert!(!casos.is_empty(), "No hay casos dorados");
+
+    let mut distintos = Vec::new();
+    for caso in casos {
+        let obtenido = instantanea(&caso);
+        let ruta_esperado = caso.with_extension("expected");
+        if actualizar {
+            fs::write(&ruta_esperado, &obtenido).unwrap();
+            continue;
+        }
+
+        let esperado = fs::read_to_string(&ruta_esperado).unwrap_or_default();
+        if esperado.replace("\r\n", "\n") != obtenido {
+            eprintln!("--- {} ---\n{}", ruta_esperado.display(), obtenido);
+            distintos.push(caso.display().to_string());
+        }
+    }
+
+    assert!(
+        distintos.is_empty(),
+        "Casos que no coinciden con su .expected (RINFO_ACTUALIZAR_ESPERADOS=1 para regenerarlos): {:?}",
+        distintos
+    );
+}